@@ -0,0 +1,308 @@
+use crate::errors::*;
+use crate::collab::Message;
+use crate::models::application::Event;
+use crate::util::json;
+use crate::view::{Annotation, AnnotationPosition, AnnotationProvider, Colors, RGBColor, Style};
+use scribe::buffer::Position;
+use std::collections::HashMap;
+use std::io::{self, BufReader};
+use std::net::{Shutdown, TcpListener, TcpStream};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+// How often a hosted session checks for a collaborator (or for having been left).
+const ACCEPT_INTERVAL: Duration = Duration::from_millis(100);
+
+// The collaborator's cursor is tracked by position, rather
+// than content, and is replaced whenever they move it.
+pub const ANNOTATION_PROVIDER: AnnotationProvider = AnnotationProvider {
+    name: "collaborator",
+    priority: 25,
+    invalidated_by_edits: false,
+};
+
+/// An experimental pair programming session with another amp instance, over
+/// TCP. Edits are exchanged as whole buffer contents, and the last one wins.
+/// Messages from the collaborator are read in a separate thread and forwarded
+/// to the application as `CollaborationMessage` events.
+#[derive(Default)]
+pub struct Collaboration {
+    // The connection to the collaborator, which is
+    // shared with the thread accepting it when hosting.
+    stream: Arc<Mutex<Option<TcpStream>>>,
+
+    // Tells a hosted session's thread to stop waiting for a collaborator.
+    closed: Arc<AtomicBool>,
+    active: bool,
+
+    // Identifies the current session's events, so
+    // that those sent by earlier sessions are ignored.
+    session: usize,
+
+    // The revisions of paths' buffers (see `View::revision`)
+    // whose content was last sent or received.
+    synced: HashMap<PathBuf, usize>,
+    cursor: Option<Message>,
+
+    /// The buffer displaying the collaborator's cursor, if any.
+    pub peer_buffer: Option<usize>,
+}
+
+impl Collaboration {
+    /// Listens for a collaborator on the address, in the background.
+    pub fn host(&mut self, address: &str, events: Sender<Event>) -> Result<()> {
+        self.ensure_inactive()?;
+        let listener = TcpListener::bind(address)
+            .chain_err(|| format!("Couldn't listen for a collaborator on {}", address))?;
+        listener.set_nonblocking(true).chain_err(|| "Couldn't configure collaboration socket")?;
+
+        self.start(None);
+        let (stream, closed, session) = (self.stream.clone(), self.closed.clone(), self.session);
+        thread::spawn(move || {
+            while !closed.load(Ordering::SeqCst) {
+                match listener.accept() {
+                    Ok((connection, address)) => {
+                        let reader = match connection.set_nonblocking(false).and_then(|_| connection.try_clone()) {
+                            Ok(reader) => reader,
+                            Err(_) => continue,
+                        };
+                        match stream.lock() {
+                            Ok(ref mut stream) if !closed.load(Ordering::SeqCst) => **stream = Some(connection),
+                            _ => return,
+                        }
+                        if events.send(Event::CollaboratorConnected(address.to_string())).is_ok() {
+                            read_messages(reader, session, &events);
+                        }
+
+                        return;
+                    }
+                    Err(ref error) if error.kind() == io::ErrorKind::WouldBlock => {
+                        thread::sleep(ACCEPT_INTERVAL)
+                    }
+                    Err(_) => return,
+                }
+            }
+        });
+
+        Ok(())
+    }
+
+    /// Connects to a collaborator hosting a session on the address.
+    pub fn join(&mut self, address: &str, events: Sender<Event>) -> Result<()> {
+        self.ensure_inactive()?;
+        let connection = TcpStream::connect(address)
+            .chain_err(|| format!("Couldn't connect to a collaborator on {}", address))?;
+        let reader = connection.try_clone().chain_err(|| "Couldn't read from collaborator")?;
+
+        self.start(Some(connection));
+        let session = self.session;
+        thread::spawn(move || read_messages(reader, session, &events));
+
+        Ok(())
+    }
+
+    /// Ends the session, disconnecting from (or no longer waiting for) the collaborator.
+    pub fn leave(&mut self) {
+        self.active = false;
+        self.closed.store(true, Ordering::SeqCst);
+        if let Ok(mut stream) = self.stream.lock() {
+            if let Some(stream) = stream.take() {
+                let _ = stream.shutdown(Shutdown::Both);
+            }
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.active
+    }
+
+    /// Whether the event-producing session is the current one.
+    pub fn is_current(&self, session: usize) -> bool {
+        self.active && self.session == session
+    }
+
+    pub fn is_connected(&self) -> bool {
+        self.stream.lock().map(|stream| stream.is_some()).unwrap_or(false)
+    }
+
+    /// Sends a buffer's content if its revision has changed since it was last
+    /// synced, and the cursor if it's moved. The first time a path is synced,
+    /// its revision is only recorded, so that joining a session doesn't
+    /// overwrite the collaborator's copy. The content is only read when it's sent.
+    pub fn sync<F>(&mut self, path: &Path, revision: usize, content: F, position: Position, selection: Option<Position>) -> Result<()>
+        where F: FnOnce() -> String
+    {
+        if let Some(previous) = self.synced.insert(path.to_path_buf(), revision) {
+            if previous != revision {
+                self.send(&Message::Content { path: path.to_path_buf(), content: content() })?;
+            }
+        }
+
+        let cursor = Message::Cursor { path: path.to_path_buf(), position, selection };
+        if self.cursor.as_ref() != Some(&cursor) {
+            self.send(&cursor)?;
+            self.cursor = Some(cursor);
+        }
+
+        Ok(())
+    }
+
+    /// Records the revision of the buffer that content received from
+    /// the collaborator was applied to, so that it isn't sent back.
+    pub fn received(&mut self, path: &Path, revision: usize) {
+        self.synced.insert(path.to_path_buf(), revision);
+    }
+
+    fn send(&self, message: &Message) -> Result<()> {
+        let mut stream = self.stream.lock().map_err(|_| "Couldn't access collaboration connection")?;
+        if let Some(ref mut stream) = *stream {
+            json::write_message(stream, &message.encode()).chain_err(|| "Couldn't send changes to collaborator")?;
+        }
+
+        Ok(())
+    }
+
+    fn ensure_inactive(&self) -> Result<()> {
+        if self.active {
+            bail!("Already collaborating; leave the current session first");
+        }
+
+        Ok(())
+    }
+
+    fn start(&mut self, connection: Option<TcpStream>) {
+        self.active = true;
+        self.session += 1;
+        self.stream = Arc::new(Mutex::new(connection));
+        self.closed = Arc::new(AtomicBool::new(false));
+        self.synced.clear();
+        self.cursor = None;
+    }
+}
+
+fn read_messages(stream: TcpStream, session: usize, events: &Sender<Event>) {
+    let mut reader = BufReader::new(stream);
+    while let Some(message) = json::read_message(&mut reader) {
+        if let Some(message) = message.ok().as_ref().and_then(Message::decode) {
+            if events.send(Event::CollaborationMessage(session, message)).is_err() {
+                return;
+            }
+        }
+    }
+
+    let _ = events.send(Event::CollaborationEnded(session));
+}
+
+/// Marks the collaborator's cursor, and highlights their selection.
+pub fn cursor_annotations(position: Position, selection: Option<Position>) -> Vec<Annotation> {
+    let mut annotations = vec![
+        Annotation {
+            line: position.line,
+            content: String::from("»"),
+            style: Style::Bold,
+            colors: Colors::CustomFocusedForeground(RGBColor(80, 160, 230)),
+            position: AnnotationPosition::Gutter,
+        },
+        Annotation {
+            line: position.line,
+            content: String::new(),
+            style: Style::Inverted,
+            colors: Colors::Default,
+            position: AnnotationPosition::Range { start: position.offset, end: position.offset + 1 },
+        },
+    ];
+
+    if let Some(anchor) = selection {
+        let (start, end) = if anchor < position { (anchor, position) } else { (position, anchor) };
+        for line in start.line..=end.line {
+            annotations.push(Annotation {
+                line,
+                content: String::new(),
+                style: Style::Default,
+                colors: Colors::SelectMode,
+                position: AnnotationPosition::Range {
+                    start: if line == start.line { start.offset } else { 0 },
+                    end: if line == end.line { end.offset } else { usize::MAX },
+                },
+            });
+        }
+    }
+
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cursor_annotations, Collaboration};
+    use crate::collab::Message;
+    use crate::models::application::Event;
+    use crate::view::AnnotationPosition;
+    use scribe::buffer::Position;
+    use std::net::TcpListener;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn sync_sends_changed_content_and_moved_cursors_to_the_collaborator() {
+        // Reserve a free port for the host.
+        let address = TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().to_string();
+        let (host_events, host_receiver) = mpsc::channel();
+        let (guest_events, guest_receiver) = mpsc::channel();
+        let mut host = Collaboration::default();
+        let mut guest = Collaboration::default();
+        host.host(&address, host_events).unwrap();
+        guest.join(&address, guest_events).unwrap();
+        match host_receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+            Event::CollaboratorConnected(_) => (),
+            event => panic!("Unexpected event: {:?}", event),
+        }
+
+        let path = Path::new("src/main.rs");
+        let position = Position { line: 0, offset: 1 };
+        host.sync(path, 1, || String::from("amp"), position, None).unwrap();
+        host.sync(path, 1, || String::from("amp"), position, None).unwrap();
+        host.sync(path, 2, || String::from("amp editor"), position, None).unwrap();
+        assert_eq!(
+            guest_receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            Event::CollaborationMessage(1, Message::Cursor { path: path.to_path_buf(), position, selection: None })
+        );
+        assert_eq!(
+            guest_receiver.recv_timeout(Duration::from_secs(5)).unwrap(),
+            Event::CollaborationMessage(1, Message::Content {
+                path: path.to_path_buf(),
+                content: String::from("amp editor"),
+            })
+        );
+
+        // Content received from the collaborator isn't sent back.
+        guest.received(path, 3);
+        guest.sync(path, 3, || String::from("amp editor"), position, None).unwrap();
+        match host_receiver.recv_timeout(Duration::from_secs(5)).unwrap() {
+            Event::CollaborationMessage(1, Message::Cursor { .. }) => (),
+            event => panic!("Unexpected event: {:?}", event),
+        }
+
+        guest.leave();
+        assert_eq!(host_receiver.recv_timeout(Duration::from_secs(5)).unwrap(), Event::CollaborationEnded(1));
+        assert!(!guest.is_active());
+    }
+
+    #[test]
+    fn cursor_annotations_highlight_each_line_of_the_selection() {
+        let annotations = cursor_annotations(Position { line: 1, offset: 2 }, Some(Position { line: 3, offset: 4 }));
+        let positions: Vec<(usize, AnnotationPosition)> = annotations.iter().map(|a| (a.line, a.position)).collect();
+
+        assert_eq!(positions, vec![
+            (1, AnnotationPosition::Gutter),
+            (1, AnnotationPosition::Range { start: 2, end: 3 }),
+            (1, AnnotationPosition::Range { start: 2, end: usize::MAX }),
+            (2, AnnotationPosition::Range { start: 0, end: usize::MAX }),
+            (3, AnnotationPosition::Range { start: 0, end: 4 }),
+        ]);
+    }
+}
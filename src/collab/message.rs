@@ -0,0 +1,113 @@
+use crate::util::json;
+use scribe::buffer::Position;
+use std::path::PathBuf;
+use crate::yaml::yaml::Yaml;
+
+/// A change sent to (or received from) a collaborator. Paths are relative
+/// to the workspace, since each side may have checked it out elsewhere.
+#[derive(Debug, PartialEq)]
+pub enum Message {
+    /// A buffer's full content, replacing the receiver's (last writer wins).
+    Content { path: PathBuf, content: String },
+
+    /// The collaborator's cursor, and the other end of their selection, if any.
+    Cursor { path: PathBuf, position: Position, selection: Option<Position> },
+}
+
+impl Message {
+    pub fn encode(&self) -> Yaml {
+        match *self {
+            Message::Content { ref path, ref content } => json::object(vec![
+                ("type", json::string("content")),
+                ("path", json::string(path.to_string_lossy())),
+                ("content", json::string(content.as_str())),
+            ]),
+            Message::Cursor { ref path, position, selection } => {
+                let mut entries = vec![
+                    ("type", json::string("cursor")),
+                    ("path", json::string(path.to_string_lossy())),
+                    ("position", encode_position(position)),
+                ];
+                if let Some(selection) = selection {
+                    entries.push(("selection", encode_position(selection)));
+                }
+
+                json::object(entries)
+            }
+        }
+    }
+
+    /// Reads a message, returning None for anything unrecognized.
+    pub fn decode(message: &Yaml) -> Option<Message> {
+        let path = PathBuf::from(message["path"].as_str()?);
+
+        match message["type"].as_str()? {
+            "content" => Some(Message::Content {
+                path,
+                content: message["content"].as_str()?.to_string(),
+            }),
+            "cursor" => Some(Message::Cursor {
+                path,
+                position: decode_position(&message["position"])?,
+                selection: decode_position(&message["selection"]),
+            }),
+            _ => None,
+        }
+    }
+}
+
+fn encode_position(position: Position) -> Yaml {
+    json::object(vec![
+        ("line", Yaml::Integer(position.line as i64)),
+        ("offset", Yaml::Integer(position.offset as i64)),
+    ])
+}
+
+fn decode_position(position: &Yaml) -> Option<Position> {
+    Some(Position {
+        line: position["line"].as_i64()? as usize,
+        offset: position["offset"].as_i64()? as usize,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Message;
+    use crate::util::json;
+    use scribe::buffer::Position;
+    use std::path::PathBuf;
+
+    #[test]
+    fn messages_survive_being_encoded_and_decoded() {
+        let messages = vec![
+            Message::Content {
+                path: PathBuf::from("src/main.rs"),
+                content: String::from("fn main() {\n\t\"amp\"\n}\n"),
+            },
+            Message::Cursor {
+                path: PathBuf::from("src/main.rs"),
+                position: Position { line: 1, offset: 2 },
+                selection: Some(Position { line: 0, offset: 0 }),
+            },
+            Message::Cursor {
+                path: PathBuf::from("README.md"),
+                position: Position { line: 3, offset: 0 },
+                selection: None,
+            },
+        ];
+
+        for message in messages {
+            let encoded = json::parse(&json::encode(&message.encode())).unwrap();
+            assert_eq!(Message::decode(&encoded), Some(message));
+        }
+    }
+
+    #[test]
+    fn decode_ignores_unrecognized_messages() {
+        let message = json::parse(r#"{"type":"chat","path":"src/main.rs"}"#).unwrap();
+        assert_eq!(Message::decode(&message), None);
+
+        let message = json::parse(r#"{"type":"cursor","path":"src/main.rs"}"#).unwrap();
+        assert_eq!(Message::decode(&message), None);
+    }
+}
@@ -0,0 +1,5 @@
+mod collaboration;
+mod message;
+
+pub use self::collaboration::{cursor_annotations, Collaboration, ANNOTATION_PROVIDER};
+pub use self::message::Message;
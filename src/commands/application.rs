@@ -1,39 +1,359 @@
 use crate::errors::*;
-use crate::commands::{self, Result};
-use crate::input::KeyMap;
+use crate::commands::{self, Command, Result};
+use crate::dap;
+use crate::input::{Binding, Key, KeyMap, SnippetBinding};
 use scribe::Buffer;
+use scribe::buffer::LineRange;
+use smallvec::SmallVec;
+use std::collections::HashMap;
 use std::mem;
-use crate::models::application::{Application, Mode};
+use std::path::PathBuf;
+use std::slice;
+use crate::models::application::{word_index, Application, FileTree, Mode, SYSTEM_REGISTER};
 use crate::models::application::modes::*;
 use crate::util;
+use crate::util::number::{self, Base, Conversion};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn handle_input(app: &mut Application) -> Result {
     // Listen for and respond to user input.
-    let commands = app.view.last_key().as_ref().and_then(|key| {
-        app.mode_str().and_then(|mode| {
-            app.preferences.borrow().keymap().commands_for(&mode, &key)
-        })
-    });
-
-    if let Some(coms) = commands {
-        // Run all commands, stopping at the first error encountered, if any.
-        for com in coms {
-            com(app)?;
+    let key = match *app.view.last_key() {
+        Some(ref key) => key.clone(),
+        None => return Ok(()),
+    };
+    let mode = match app.mode_str() {
+        Some(mode) => mode,
+        None => return Ok(()),
+    };
+
+    // Record keys to bind, rather than running their commands.
+    if let Mode::KeyRecorder(ref mut mode) = app.mode {
+        mode.binding.keys.push(key);
+        return Ok(());
+    }
+
+    app.pending_keys.push(key.clone());
+    let binding = app.preferences.borrow().keymap().binding_for(mode, &app.pending_keys);
+    let path = app.workspace.current_buffer().and_then(|buffer| buffer.path.clone());
+    let snippet = app.preferences.borrow().snippet_for(path.as_ref(), mode, &app.pending_keys);
+
+    // The file type's snippets take precedence over the keymap.
+    let binding = match snippet {
+        SnippetBinding::Complete(snippet) => {
+            revert_inserted_keys(app)?;
+            app.pending_keys.clear();
+            return insert_snippet(app, &snippet);
+        }
+        SnippetBinding::Partial => match binding {
+            Binding::Complete(commands) => Binding::Ambiguous(commands),
+            Binding::Unbound => Binding::Partial,
+            binding => binding,
+        },
+        SnippetBinding::Unbound => binding,
+    };
+    match binding {
+        Binding::Complete(commands) => {
+            revert_inserted_keys(app)?;
+            app.pending_keys.clear();
+            run_commands(app, commands)
+        }
+        // Characters that start a sequence (e.g. the "j" in "j k") are
+        // inserted right away, rather than only once the sequence is
+        // resolved, so that typing isn't visibly delayed.
+        Binding::Ambiguous(ref commands) if is_insertion(commands) &&
+            app.inserted_pending_keys + 1 == app.pending_keys.len() => {
+            app.inserted_pending_keys += 1;
+            run_commands(app, commands.clone())
+        }
+        // Wait for another key (or the timeout) before deciding what to run.
+        Binding::Partial | Binding::Ambiguous(_) => Ok(()),
+        Binding::Unbound => {
+            app.pending_keys.pop();
+            if app.pending_keys.is_empty() {
+                if mode == "normal" && app.preferences.borrow().unbound_key_hint() {
+                    app.notification = Some(unbound_key_hint(app, &key, mode));
+                }
+                return Ok(());
+            }
+
+            // The key doesn't continue the pending sequence; resolve
+            // the sequence as-is, and then handle the key on its own.
+            resolve_pending_keys(app)?;
+            app.view.last_key = Some(key);
+            handle_input(app)
+        }
+    }
+}
+
+/// Ends an incomplete key sequence, running its commands if it's bound.
+/// Otherwise, its keys are handled individually, as though they weren't
+/// part of a sequence (e.g. inserting characters in insert mode).
+pub fn resolve_pending_keys(app: &mut Application) -> Result {
+    let keys = mem::take(&mut app.pending_keys);
+    let inserted = mem::replace(&mut app.inserted_pending_keys, 0);
+    let last_key = app.view.last_key.take();
+    let result = if inserted == 0 {
+        run_keys(app, &keys)
+    } else {
+        // Only the keys that haven't already been inserted remain.
+        keys[inserted..].iter().try_for_each(|key| run_keys(app, slice::from_ref(key)))
+    };
+    app.view.last_key = last_key;
+
+    result
+}
+
+// Describes an unbound key press, pointing to the quick start guide if it's bound.
+fn unbound_key_hint(app: &Application, key: &Key, mode: &str) -> String {
+    let hint = format!("Key {} is not bound in {} mode", key, mode);
+    let preferences = app.preferences.borrow();
+    let help: Command = display_quick_start_guide;
+    match preferences.keymap().sequence_for(mode, help) {
+        Some(sequence) => {
+            let keys: Vec<String> = sequence.iter().map(Key::to_string).collect();
+            format!("{}; press {} for help", hint, keys.join(" "))
+        }
+        None => hint,
+    }
+}
+
+// Whether the commands just insert the key that was pressed.
+fn is_insertion(commands: &[Command]) -> bool {
+    let insert_char: Command = commands::buffer::insert_char;
+
+    commands.len() == 1 && commands[0] as usize == insert_char as usize
+}
+
+// Removes pending keys that were inserted while waiting for their sequence.
+fn revert_inserted_keys(app: &mut Application) -> Result {
+    let inserted = mem::replace(&mut app.inserted_pending_keys, 0);
+    if inserted > 0 {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        for _ in 0..inserted {
+            buffer.cursor.move_left();
+            buffer.delete();
         }
     }
 
     Ok(())
 }
 
+fn run_keys(app: &mut Application, keys: &[Key]) -> Result {
+    let mode = app.mode_str().ok_or("Current mode doesn't have key bindings")?;
+    let binding = app.preferences.borrow().keymap().binding_for(mode, keys);
+    match binding {
+        Binding::Complete(commands) | Binding::Ambiguous(commands) => {
+            app.view.last_key = keys.last().cloned();
+            run_commands(app, commands)
+        }
+        Binding::Partial | Binding::Unbound => {
+            for key in keys {
+                app.view.last_key = Some(key.clone());
+                let commands = app.mode_str().and_then(|mode| {
+                    app.preferences.borrow().keymap().commands_for(mode, key)
+                });
+                if let Some(commands) = commands {
+                    run_commands(app, commands)?;
+                }
+            }
+
+            Ok(())
+        }
+    }
+}
+
+/// Inserts text pasted into the terminal all at once, as a single
+/// edit, rather than handling it a character at a time (which would
+/// be slow, and would auto-indent each of its lines).
+pub(crate) fn handle_paste(app: &mut Application, content: &str) -> Result {
+    resolve_pending_keys(app)?;
+
+    match app.mode {
+        Mode::Normal | Mode::Insert | Mode::Select(_) | Mode::SelectLine(_) => {
+            let operation = if app.preferences.borrow().edit_log() {
+                Some(format!("paste ({} characters)", content.chars().count()))
+            } else {
+                None
+            };
+
+            run_edit(app, operation, |app| commands::buffer::insert_pasted_text(app, content))
+        }
+        _ => commands::prompt::insert_pasted_text(app, content),
+    }
+}
+
+// Inserts a snippet bound to the pending keys, as a single edit.
+fn insert_snippet(app: &mut Application, snippet: &str) -> Result {
+    let operation = if app.preferences.borrow().edit_log() {
+        Some(String::from("snippet"))
+    } else {
+        None
+    };
+
+    run_edit(app, operation, |app| commands::buffer::insert_snippet(app, snippet))
+}
+
+// Runs all commands, stopping at the first error encountered, if any.
+fn run_commands(app: &mut Application, commands: SmallVec<[Command; 4]>) -> Result {
+    let operation = if app.preferences.borrow().edit_log() {
+        Some(describe_operation(app, &commands))
+    } else {
+        None
+    };
+
+    run_edit(app, operation, |app| commands.into_iter().try_for_each(|command| command(app)))
+}
+
+// Runs an edit, protecting read-only buffers and narrowed content from it,
+// and recording it in the buffer's history (and edit log, if described).
+fn run_edit<F>(app: &mut Application, operation: Option<String>, edit: F) -> Result
+    where F: FnOnce(&mut Application) -> Result
+{
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.undo_trees.track(buffer);
+        if operation.is_some() {
+            app.edit_logs.track(buffer);
+        }
+    }
+    let read_only_content = match app.workspace.current_buffer() {
+        Some(buffer) if app.read_only_buffers.contains(buffer) => buffer.id.map(|id| (id, buffer.data())),
+        _ => None,
+    };
+    let narrowed_content = match app.workspace.current_buffer() {
+        Some(buffer) if app.view.narrowing(buffer).is_some() => buffer.id.map(|id| (id, buffer.data())),
+        _ => None,
+    };
+    let selecting_register = matches!(app.mode, Mode::Register(_));
+    let mut result = edit(app);
+
+    // Any command can edit the buffer, so rather than guarding each of
+    // them, changes made to read-only buffers are undone after the fact.
+    if let Some((id, content)) = read_only_content {
+        if revert_changes(app, id, &content) {
+            result = Err(BUFFER_READ_ONLY.into());
+        }
+    }
+
+    // Likewise for the content hidden by narrowing a buffer.
+    if let Some((id, content)) = narrowed_content {
+        if protect_narrowing(app, id, &content) {
+            result = Err(BUFFER_NARROWED.into());
+        }
+    }
+
+    // Register selections only apply to the commands run by the following key.
+    if !selecting_register {
+        app.clipboard.clear_register_selection();
+    }
+
+    // Changes made in insert mode are recorded as one state, once it's left.
+    if !matches!(app.mode, Mode::Insert) {
+        if let Some(buffer) = app.workspace.current_buffer() {
+            if let Some(revision) = app.view.revision(buffer) {
+                app.undo_trees.record(buffer, revision);
+            }
+        }
+    }
+    if let (Some(operation), Some(buffer)) = (operation, app.workspace.current_buffer()) {
+        app.edit_logs.record(buffer, &operation);
+    }
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.marks.update(buffer);
+    }
+
+    result
+}
+
+// Restores the buffer's content, if it's still current and has changed,
+// returning true if so. The content is restored with buffer operations,
+// rather than by undoing the changes, as they may themselves be an undo.
+fn revert_changes(app: &mut Application, id: usize, content: &str) -> bool {
+    match app.workspace.current_buffer() {
+        Some(buffer) if buffer.id == Some(id) && buffer.data() != content => {
+            util::reload::replace_content(buffer, content);
+            true
+        }
+        _ => false,
+    }
+}
+
+// Restores the narrowed buffer's content if its hidden content has changed,
+// returning true if so, and keeps its cursor on the narrowed lines.
+fn protect_narrowing(app: &mut Application, id: usize, content: &str) -> bool {
+    let buffer = match app.workspace.current_buffer() {
+        Some(buffer) if buffer.id == Some(id) => buffer,
+        _ => return false,
+    };
+
+    // The commands may have widened the buffer.
+    let narrowing = match app.view.narrowing(buffer) {
+        Some(narrowing) => narrowing,
+        None => return false,
+    };
+    let data = buffer.data();
+    let reverted = !narrowing.intact(&data);
+    if reverted {
+        util::reload::replace_content(buffer, content);
+    }
+
+    let position = narrowing.clamp(*buffer.cursor, if reverted { content } else { data.as_str() });
+    if position != *buffer.cursor {
+        buffer.cursor.move_to(position);
+    }
+
+    reverted
+}
+
+// Describes the key press that ran the commands, for the edit log.
+fn describe_operation(app: &Application, commands: &[Command]) -> String {
+    let command_names: HashMap<usize, &str> = commands::hash_map()
+        .into_iter()
+        .map(|(name, command)| (command as usize, name))
+        .collect();
+    let names: Vec<&str> = commands
+        .iter()
+        .map(|&command| command_names.get(&(command as usize)).cloned().unwrap_or("?"))
+        .collect();
+
+    match app.view.last_key {
+        Some(ref key) => format!("{} ({})", names.join(", "), key),
+        None => names.join(", "),
+    }
+}
+
 pub fn switch_to_normal_mode(app: &mut Application) -> Result {
     let _ = commands::buffer::end_command_group(app);
+
+    // Leaving theme mode without accepting a theme reverts its preview.
+    if let Mode::Theme(ref mode) = app.mode {
+        app.preferences.borrow_mut().set_theme(mode.original_theme.as_str());
+    }
     app.mode = Mode::Normal;
 
+    // Step back onto the last character if the cursor
+    // isn't allowed to rest beyond it outside of insert mode.
+    if !app.preferences.borrow().cursor_past_end_of_line() {
+        if let Some(buffer) = app.workspace.current_buffer() {
+            let at_end_of_line = buffer.data()
+                .lines()
+                .nth(buffer.cursor.line)
+                .map(|line| buffer.cursor.offset >= line.graphemes(true).count())
+                .unwrap_or(true);
+            if at_end_of_line {
+                buffer.cursor.move_left();
+            }
+        }
+    }
+
     Ok(())
 }
 
 pub fn switch_to_insert_mode(app: &mut Application) -> Result {
-    if app.workspace.current_buffer().is_some() {
+    if let Some(buffer) = app.workspace.current_buffer() {
+        if app.read_only_buffers.contains(buffer) {
+            bail!(BUFFER_READ_ONLY);
+        }
+
         commands::buffer::start_command_group(app)?;
         app.mode = Mode::Insert;
         commands::view::scroll_to_cursor(app)?;
@@ -73,6 +393,32 @@ pub fn switch_to_jump_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Waits for a register name to use for the next copy or paste,
+/// returning to the current select mode (if any) afterwards.
+pub fn switch_to_register_mode(app: &mut Application) -> Result {
+    let old_mode = mem::replace(&mut app.mode, Mode::Normal);
+    let select_mode = match old_mode {
+        Mode::Select(select_mode) => jump::SelectModeOptions::Select(select_mode),
+        Mode::SelectLine(select_mode) => jump::SelectModeOptions::SelectLine(select_mode),
+        _ => jump::SelectModeOptions::None,
+    };
+    app.mode = Mode::Register(RegisterMode::new(select_mode));
+
+    Ok(())
+}
+
+/// Lists the system clipboard and named registers, to pick one to paste from.
+pub fn switch_to_registers_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    app.clipboard.clear_register_selection();
+    let mut registers = vec![(SYSTEM_REGISTER, app.clipboard.get_content().clone())];
+    registers.extend(app.clipboard.registers().map(|(name, content)| (name, content.clone())));
+    app.mode = Mode::Registers(RegistersMode::new(registers, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
 pub fn switch_to_second_stage_jump_mode(app: &mut Application) -> Result {
     switch_to_jump_mode(app)?;
     if let Mode::Jump(ref mut mode) = app.mode {
@@ -94,18 +440,143 @@ pub fn switch_to_line_jump_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_rename_mode(app: &mut Application) -> Result {
+    let token = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let line = buffer.data().lines().nth(buffer.cursor.line).map(String::from).ok_or(CURRENT_LINE_MISSING)?;
+        word_index::word_at(&line, buffer.cursor.offset).to_string()
+    };
+    if token.is_empty() {
+        bail!("No token under the cursor to rename");
+    }
+    app.mode = Mode::Rename(RenameMode::new(token));
+
+    Ok(())
+}
+
 pub fn switch_to_open_mode(app: &mut Application) -> Result {
     let exclusions = app.preferences.borrow().open_mode_exclusions()?;
     let config = app.preferences.borrow().search_select_config();
-    app.mode = Mode::Open(OpenMode::new(app.workspace.path.clone(), exclusions, app.event_channel.clone(), config));
+    let recent_paths = recent_buffer_paths(app);
+    let mut mode = OpenMode::new(
+        app.workspace.path.clone(),
+        exclusions,
+        app.open_mode_index.clone(),
+        app.event_channel.clone(),
+        config
+    );
+    mode.set_recent_paths(recent_paths);
+    mode.set_past_queries(app.history.get(open::HISTORY_NAMESPACE).cloned().unwrap_or_default());
+    app.mode = Mode::Open(mode);
     commands::search_select::search(app)?;
 
     Ok(())
 }
 
+// The paths of open buffers, and when they were last used.
+fn recent_buffer_paths(app: &mut Application) -> HashMap<PathBuf, u64> {
+    let mut paths = HashMap::new();
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+
+    for id in util::buffer_ids(app) {
+        if !util::select_buffer(id, app) {
+            continue;
+        }
+        let path = app.workspace.current_buffer().and_then(|b| b.path.clone());
+        if let (Some(path), Some(last_access)) = (path, app.recent_buffers.last_access(id)) {
+            paths.insert(path, last_access);
+        }
+    }
+    if let Some(id) = current_id {
+        util::select_buffer(id, app);
+    }
+
+    paths
+}
+
+pub fn switch_to_grep_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Grep(GrepMode::new(app.workspace.path.clone(), false, app.event_channel.clone(), config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_regex_grep_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Grep(GrepMode::new(app.workspace.path.clone(), true, app.event_channel.clone(), config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_buffer_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Buffer(BufferMode::new(
+        &mut app.workspace,
+        &app.view,
+        &app.pinned_buffers,
+        &app.recent_buffers,
+        config
+    ));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_close_pattern_mode(app: &mut Application) -> Result {
+    if app.workspace.current_buffer().is_none() {
+        bail!(BUFFER_MISSING);
+    }
+    app.mode = Mode::ClosePattern(ClosePatternMode::new());
+
+    Ok(())
+}
+
 pub fn switch_to_command_mode(app: &mut Application) -> Result {
     let config = app.preferences.borrow().search_select_config();
-    app.mode = Mode::Command(CommandMode::new(config));
+    let themes = app.view.theme_set.themes.keys().cloned().collect();
+    app.mode = Mode::Command(CommandMode::new(config).with_themes(themes));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Changes a preference for the rest of the session. Run from
+/// command mode, with a "setting=value" argument (e.g. "tab_width=4").
+pub(crate) fn set(app: &mut Application, argument: &str) -> Result {
+    let mut parts = argument.splitn(2, '=');
+    let setting = parts.next().unwrap_or("").trim();
+    let value = parts
+        .next()
+        .map(str::trim)
+        .ok_or_else(|| format!("Please provide a value for {} (e.g. {}=value)", setting, setting))?;
+    if setting == "theme" && !app.view.theme_set.themes.contains_key(value) {
+        bail!("Theme \"{}\" doesn't exist", value);
+    }
+
+    app.preferences.borrow_mut().set(setting, value)
+}
+
+/// Records the next key sequence pressed, and then prompts for a command to
+/// bind it to in the current mode, saving the binding to the user's config.
+pub fn switch_to_key_recorder_mode(app: &mut Application) -> Result {
+    let mode = app.mode_str().ok_or("Can't record keys in the current mode")?;
+    app.mode = Mode::KeyRecorder(KeyRecorderMode::new(mode));
+
+    Ok(())
+}
+
+/// Lists commands to run once per selected line.
+pub fn switch_to_line_command_mode(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let line_range = match app.mode {
+        Mode::Select(ref mode) => LineRange::new(mode.anchor.line, buffer.cursor.line),
+        Mode::SelectLine(ref mode) => LineRange::new(mode.anchor, buffer.cursor.line),
+        _ => bail!("Can't run a command per line outside of select modes"),
+    };
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Command(CommandMode::for_lines(line_range, config));
     commands::search_select::search(app)?;
 
     Ok(())
@@ -126,16 +597,184 @@ pub fn switch_to_symbol_jump_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Lists the symbols defined in every open buffer, jumping
+/// to the selected one's buffer and position.
+pub fn switch_to_workspace_symbol_jump_mode(app: &mut Application) -> Result {
+    if app.workspace.current_buffer().is_none() {
+        bail!(BUFFER_MISSING);
+    }
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::SymbolJump(SymbolJumpMode::across_buffers(&mut app.workspace, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_debug_mode(app: &mut Application) -> Result {
+    let project_configured = app.workspace.path.join(dap::CONFIGURATION_PATH).exists();
+    if project_configured && !commands::workspace::ensure_trusted(app, switch_to_debug_mode)? {
+        return Ok(());
+    }
+    let configurations = dap::load_configurations(&app.workspace.path)?;
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Debug(DebugMode::new(configurations, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_diagnostic_mode(app: &mut Application) -> Result {
+    let diagnostics = app.language_servers.all_diagnostics();
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Diagnostic(DiagnosticMode::new(diagnostics, &app.workspace.path, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_transform_mode(app: &mut Application) -> Result {
+    let range = util::selected_range(app)?;
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Transform(TransformMode::new(range, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+pub fn switch_to_task_mode(app: &mut Application) -> Result {
+    if !commands::workspace::ensure_trusted(app, switch_to_task_mode)? {
+        return Ok(());
+    }
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Task(TaskMode::new(&app.workspace.path, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
 pub fn switch_to_theme_mode(app: &mut Application) -> Result {
     let config = app.preferences.borrow().search_select_config();
+    let original_theme = app.preferences.borrow().theme().to_string();
     app.mode = Mode::Theme(
         ThemeMode::new(
             app.view.theme_set.themes.keys().map(|k| k.to_string()).collect(),
+            original_theme,
             config
         ),
     );
     commands::search_select::search(app)?;
 
+    // Start from the theme in use, rather than previewing another right away.
+    let index = match app.mode {
+        Mode::Theme(ref mode) => mode.results().position(|theme| *theme == mode.original_theme),
+        _ => None,
+    };
+    if let Some(index) = index {
+        commands::search_select::select_index(app, index)?;
+    }
+
+    Ok(())
+}
+
+/// Lists the repository's local branches, once
+/// they've been found in the background.
+pub fn switch_to_branch_mode(app: &mut Application) -> Result {
+    app.git_worker.list_branches()?;
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Branch(BranchMode::new(config));
+
+    Ok(())
+}
+
+/// Shows the commit that last changed each of the current buffer's
+/// lines in a column alongside them, once it's been blamed in the background.
+pub fn switch_to_blame_mode(app: &mut Application) -> Result {
+    commands::git::request_blame(app, None)
+}
+
+/// Lists the current buffer's edit history as a tree,
+/// including changes that were undone and then replaced.
+pub fn switch_to_undo_tree_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    app.undo_trees.track(buffer);
+    if let Some(revision) = app.view.revision(buffer) {
+        app.undo_trees.record(buffer, revision);
+    }
+    let tree = app.undo_trees.get(id).ok_or("No edit history available")?;
+    let mode = UndoTreeMode::new(id, tree, app.preferences.borrow().datetime_format(), config)?;
+    app.mode = Mode::UndoTree(mode);
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Focuses the file tree sidebar, opening it (with the
+/// current buffer's file selected) if it isn't already.
+pub fn switch_to_file_tree_mode(app: &mut Application) -> Result {
+    if app.file_tree.is_none() {
+        let exclusions = app.preferences.borrow().open_mode_exclusions()?;
+        let mut tree = FileTree::new(app.workspace.path.clone(), exclusions)?;
+        if let Some(path) = app.workspace.current_buffer_path() {
+            tree.reveal(&app.workspace.path.join(path))?;
+        }
+        app.view.show_sidebar(app.preferences.borrow().file_tree_width());
+        app.file_tree = Some(tree);
+    }
+    app.mode = Mode::FileTree(FileTreeMode::new());
+
+    Ok(())
+}
+
+/// Waits for the name of a mark to set at the cursor.
+pub fn switch_to_mark_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Mark(MarkMode::new(MarkAction::Set));
+
+    Ok(())
+}
+
+/// Waits for the name of a mark to jump to.
+pub fn switch_to_mark_jump_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Mark(MarkMode::new(MarkAction::Jump));
+
+    Ok(())
+}
+
+/// Lists the marks that have been set, to jump to one.
+pub fn switch_to_marks_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Marks(MarksMode::new(&app.marks, &app.workspace.path, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Lists the tags defined in the workspace's tags file, to jump to one.
+pub fn switch_to_tags_mode(app: &mut Application) -> Result {
+    let tags = util::tags::load(&app.workspace.path)?;
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Tags(TagsMode::new(tags, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Lists the locations the cursor has jumped from, to return to one.
+pub fn switch_to_jump_list_mode(app: &mut Application) -> Result {
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::JumpList(JumpListMode::new(&app.jump_list, &app.workspace.path, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
+/// Lists the repository's stashes, once they've been found in the background.
+pub fn switch_to_stash_mode(app: &mut Application) -> Result {
+    app.git_worker.list_stashes()?;
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Stash(StashMode::new(config));
+
     Ok(())
 }
 
@@ -159,11 +798,21 @@ pub fn switch_to_select_line_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn switch_to_select_block_mode(app: &mut Application) -> Result {
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.mode = Mode::SelectBlock(SelectBlockMode::new(*buffer.cursor.clone()));
+    } else {
+        bail!(BUFFER_MISSING);
+    }
+
+    Ok(())
+}
+
 pub fn switch_to_search_mode(app: &mut Application) -> Result {
     if app.workspace.current_buffer().is_some() {
-        app.mode = Mode::Search(
-            SearchMode::new(app.search_query.clone())
-        );
+        let mut mode = SearchMode::new(app.search_query.clone());
+        mode.wrap = app.preferences.borrow().search_wrap();
+        app.mode = Mode::Search(mode);
     } else {
         bail!(BUFFER_MISSING);
     }
@@ -171,6 +820,58 @@ pub fn switch_to_search_mode(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Prompts for a pattern and its replacement, using the
+/// query as the pattern when switching from search mode.
+pub fn switch_to_replace_mode(app: &mut Application) -> Result {
+    if app.workspace.current_buffer().is_none() {
+        bail!(BUFFER_MISSING);
+    }
+    let pattern = match app.mode {
+        Mode::Search(ref mode) => mode.query().map(String::from),
+        _ => None,
+    };
+    app.mode = Mode::Replace(ReplaceMode::new(pattern));
+
+    Ok(())
+}
+
+pub fn switch_to_calculator_mode(app: &mut Application) -> Result {
+    // Use the selection (if any) as the initial expression,
+    // so that its result is displayed straight away.
+    let input = match util::selected_range(app) {
+        Ok(range) => app.workspace
+            .current_buffer()
+            .ok_or(BUFFER_MISSING)?
+            .read(&range)
+            .map(|data| data.trim().to_string())
+            .unwrap_or_default(),
+        Err(_) => String::new(),
+    };
+    app.mode = Mode::Calculator(CalculatorMode::new(input));
+
+    Ok(())
+}
+
+pub fn switch_to_ex_mode(app: &mut Application) -> Result {
+    app.mode = Mode::Ex(ExMode::new());
+
+    Ok(())
+}
+
+pub fn switch_to_number_mode(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (number, range) = number::number_at_cursor(buffer).ok_or("No number found under the cursor")?;
+    let conversions = Base::all()
+        .iter()
+        .map(|&base| Conversion { base, content: number.format(base) })
+        .collect();
+    let config = app.preferences.borrow().search_select_config();
+    app.mode = Mode::Number(NumberMode::new(conversions, range, config));
+    commands::search_select::search(app)?;
+
+    Ok(())
+}
+
 pub fn switch_to_path_mode(app: &mut Application) -> Result {
     let path = app.workspace
         .current_buffer()
@@ -199,6 +900,31 @@ pub fn display_default_keymap(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Reports the approximate memory used by each open buffer in a new buffer.
+pub fn display_memory_usage(app: &mut Application) -> Result {
+    let report = util::memory::usage_report(app);
+    commands::workspace::new_buffer(app)?;
+
+    if let Some(buffer) = app.workspace.current_buffer() {
+        buffer.insert(report);
+    }
+
+    Ok(())
+}
+
+/// Reports the time recently spent rendering, highlighting,
+/// matching, and reading/writing files in a new buffer.
+pub fn display_profile(app: &mut Application) -> Result {
+    let report = app.view.profiler.report(app.preferences.borrow().profile_window());
+    commands::workspace::new_buffer(app)?;
+
+    if let Some(buffer) = app.workspace.current_buffer() {
+        buffer.insert(report);
+    }
+
+    Ok(())
+}
+
 pub fn display_quick_start_guide(app: &mut Application) -> Result {
     commands::workspace::new_buffer(app)?;
 
@@ -260,10 +986,244 @@ pub fn exit(app: &mut Application) -> Result {
 
 #[cfg(test)]
 mod tests {
+    use crate::commands;
+    use crate::input::Key;
     use crate::models::Application;
-    use crate::models::application::Mode;
+    use crate::models::application::{Mode, Preferences};
+    use crate::models::application::modes::SearchSelectMode;
+    use crate::yaml::YamlLoader;
     use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::cell::RefCell;
+    use std::env;
+    use std::fs;
     use std::path::PathBuf;
+    use std::rc::Rc;
+
+    // Sets up an application in insert mode, with "j k" bound to switch
+    // back to normal mode (overlapping the wildcard insert binding).
+    fn set_up_key_sequence_application() -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str(
+            "keymap:\n  insert:\n    j k: application::switch_to_normal_mode"
+        ).unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        app.workspace.add_buffer(Buffer::new());
+        super::switch_to_insert_mode(&mut app).unwrap();
+
+        app
+    }
+
+    fn press(app: &mut Application, key: Key) {
+        app.view.last_key = Some(key);
+        super::handle_input(app).unwrap();
+    }
+
+    #[test]
+    fn handle_input_explains_unbound_keys_in_normal_mode_unless_disabled() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        press(&mut app, Key::Ctrl('g'));
+        assert_eq!(
+            app.notification,
+            Some(String::from("Key ctrl-g is not bound in normal mode; press ? for help"))
+        );
+
+        app.notification = None;
+        let data = YamlLoader::load_from_str("unbound_key_hint: false").unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        press(&mut app, Key::Ctrl('g'));
+        assert_eq!(app.notification, None);
+    }
+
+    #[test]
+    fn handle_input_logs_changes_by_key_when_enabled() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(env::temp_dir().join("amp_edit_log.txt"));
+        app.workspace.add_buffer(buffer);
+        press(&mut app, Key::Char('i'));
+        press(&mut app, Key::Char('a'));
+        assert!(commands::buffer::export_edit_log(&mut app).is_err());
+
+        let data = YamlLoader::load_from_str("edit_log: true").unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        press(&mut app, Key::Char('m'));
+        press(&mut app, Key::Left);
+        press(&mut app, Key::Char('p'));
+        commands::buffer::export_edit_log(&mut app).unwrap();
+
+        let path = env::temp_dir().join("amp_edit_log.txt.patch");
+        let series = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(series.contains("Subject: [PATCH 1/2] buffer::insert_char (m)\n"));
+        assert!(series.contains("@@ -1 +1 @@\n-a\n+am\n"));
+        assert!(series.contains("Subject: [PATCH 2/2] buffer::insert_char (p)\n"));
+    }
+
+    #[test]
+    fn handle_input_reverts_changes_to_read_only_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        app.workspace.add_buffer(buffer);
+        let id = app.workspace.current_buffer().unwrap().id.unwrap();
+        app.read_only_buffers.set(id, true);
+
+        // Undoing the deletion's reversal is reverted, too.
+        for key in &[Key::Char(';'), Key::Char('u'), Key::Char('o')] {
+            app.view.last_key = Some(key.clone());
+            assert!(super::handle_input(&mut app).is_err());
+        }
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor\n");
+
+        press(&mut app, Key::Char('W'));
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 0 });
+        press(&mut app, Key::Char(';'));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\n");
+    }
+
+    #[test]
+    fn handle_input_inserts_snippets_bound_for_the_file_type() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str(
+            "types:\n  rs:\n    snippets:\n      insert:\n        ctrl-t: \"#[test]\\nfn $0() {\\n}\"\n        \"; d\": \"dbg!($0)\""
+        ).unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        let mut buffer = Buffer::new();
+        buffer.insert("mod tests {\n    \n}\n");
+        buffer.path = Some(PathBuf::from("lib.rs"));
+        app.workspace.add_buffer(buffer);
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 4 });
+        super::switch_to_insert_mode(&mut app).unwrap();
+
+        // Lines after the first are indented to match the cursor's line.
+        press(&mut app, Key::Ctrl('t'));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "mod tests {\n    #[test]\n    fn () {\n    }\n}\n");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 7 });
+
+        // Characters that start a snippet's keys are inserted until it's typed.
+        press(&mut app, Key::Char(';'));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "mod tests {\n    #[test]\n    fn ;() {\n    }\n}\n");
+        press(&mut app, Key::Char('d'));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "mod tests {\n    #[test]\n    fn dbg!()() {\n    }\n}\n");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 11 });
+    }
+
+    #[test]
+    fn handle_paste_inserts_pasted_text_as_one_undoable_edit() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\n");
+        app.workspace.add_buffer(buffer);
+
+        // Pasted lines keep their own indentation.
+        super::handle_paste(&mut app, "fn main() {\n    run();\n}\n").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "fn main() {\n    run();\n}\namp\n");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 3, offset: 0 });
+
+        press(&mut app, Key::Char('u'));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n");
+
+        let id = app.workspace.current_buffer().unwrap().id.unwrap();
+        app.read_only_buffers.set(id, true);
+        assert!(super::handle_paste(&mut app, "editor").is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n");
+    }
+
+    #[test]
+    fn handle_input_protects_content_hidden_by_narrowing() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\ntext\n");
+        app.workspace.add_buffer(buffer);
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 0 });
+        press(&mut app, Key::Char('V'));
+        press(&mut app, Key::Char('n'));
+        assert!(matches!(app.mode, Mode::Normal));
+
+        // The cursor can't leave the narrowed lines, but they can be edited.
+        press(&mut app, Key::Char('k'));
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 1);
+        press(&mut app, Key::Char('x'));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nditor\ntext\n");
+
+        // Merging the next (hidden) line into the narrowed one is reverted.
+        app.view.last_key = Some(Key::Char('M'));
+        assert!(super::handle_input(&mut app).is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nditor\ntext\n");
+
+        commands::view::widen(&mut app).unwrap();
+        press(&mut app, Key::Char('k'));
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 0);
+    }
+
+    #[test]
+    fn handle_input_records_keys_in_key_recorder_mode_instead_of_running_them() {
+        let mut app = set_up_key_sequence_application();
+        super::switch_to_key_recorder_mode(&mut app).unwrap();
+
+        press(&mut app, Key::Char('j'));
+        press(&mut app, Key::Char('k'));
+
+        match app.mode {
+            Mode::KeyRecorder(ref mode) => {
+                assert_eq!(mode.binding.mode, "insert");
+                assert_eq!(mode.binding.keys.to_vec(), vec![Key::Char('j'), Key::Char('k')]);
+                assert_eq!(mode.binding.to_string(), "j k (insert)");
+            }
+            _ => panic!("expected key recorder mode"),
+        }
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "");
+    }
+
+    #[test]
+    fn handle_input_runs_completed_key_sequences() {
+        let mut app = set_up_key_sequence_application();
+
+        press(&mut app, Key::Char('j'));
+        assert!(matches!(app.mode, Mode::Insert));
+        press(&mut app, Key::Char('k'));
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "");
+    }
+
+    #[test]
+    fn handle_input_resolves_interrupted_key_sequences_before_handling_the_key() {
+        let mut app = set_up_key_sequence_application();
+
+        press(&mut app, Key::Char('j'));
+        press(&mut app, Key::Char('x'));
+
+        assert!(matches!(app.mode, Mode::Insert));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "jx");
+        assert!(app.pending_keys.is_empty());
+    }
+
+    #[test]
+    fn handle_input_inserts_the_start_of_a_key_sequence_and_reverts_it_once_completed() {
+        let mut app = set_up_key_sequence_application();
+
+        press(&mut app, Key::Char('a'));
+        press(&mut app, Key::Char('j'));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "aj");
+        press(&mut app, Key::Char('k'));
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "a");
+    }
+
+    #[test]
+    fn resolve_pending_keys_runs_the_bindings_typed_so_far() {
+        let mut app = set_up_key_sequence_application();
+
+        press(&mut app, Key::Char('j'));
+        super::resolve_pending_keys(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "j");
+        assert!(app.pending_keys.is_empty());
+    }
 
     #[test]
     fn display_available_commands_creates_a_new_buffer() {
@@ -281,7 +1241,49 @@ mod tests {
         let buffer_data = app.workspace.current_buffer().unwrap().data();
         let mut lines = buffer_data.lines();
         assert_eq!(lines.nth(0), Some("application::display_available_commands"));
-        assert_eq!(lines.last(), Some("workspace::next_buffer"));
+        assert_eq!(lines.last(), Some("workspace::reload_unmodified_buffers"));
+    }
+
+    #[test]
+    fn switch_to_transform_mode_applies_the_selected_transform_to_the_selection() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        app.workspace.add_buffer(buffer);
+
+        super::switch_to_select_line_mode(&mut app).unwrap();
+        super::switch_to_transform_mode(&mut app).unwrap();
+        if let Mode::Transform(ref mut mode) = app.mode {
+            mode.query().insert_str("base64 encode");
+        }
+        commands::search_select::search(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "YW1w\neditor");
+    }
+
+    #[test]
+    fn switch_to_line_command_mode_runs_the_selected_command_on_each_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nbuffer");
+        app.workspace.add_buffer(buffer);
+
+        // Select the first two lines.
+        super::switch_to_select_line_mode(&mut app).unwrap();
+        commands::cursor::move_down(&mut app).unwrap();
+        super::switch_to_line_command_mode(&mut app).unwrap();
+        if let Mode::Command(ref mut mode) = app.mode {
+            mode.query().insert_str("buffer::insert_tab");
+        }
+        commands::search_select::search(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "  amp\n  editor\nbuffer");
+        if let Mode::Normal = app.mode {
+        } else {
+            panic!("Not in normal mode");
+        }
     }
 
     #[test]
@@ -296,7 +1298,7 @@ mod tests {
         super::switch_to_search_mode(&mut app).unwrap();
 
         let mode_query = match app.mode {
-            Mode::Search(ref mode) => mode.input.clone(),
+            Mode::Search(ref mode) => mode.query().map(String::from),
             _ => None,
         };
         assert_eq!(
@@ -314,7 +1316,7 @@ mod tests {
 
         super::switch_to_path_mode(&mut app).unwrap();
         let mode_input = match app.mode {
-            Mode::Path(ref mode) => Some(mode.input.clone()),
+            Mode::Path(ref mode) => Some(mode.input.to_string()),
             _ => None,
         };
         assert_eq!(
@@ -334,7 +1336,7 @@ mod tests {
 
         super::switch_to_path_mode(&mut app).unwrap();
         let mode_input = match app.mode {
-            Mode::Path(ref mode) => Some(mode.input.clone()),
+            Mode::Path(ref mode) => Some(mode.input.to_string()),
             _ => None,
         };
         assert_eq!(
@@ -1,17 +1,44 @@
 use crate::errors::*;
+use crate::errors;
 use crate::commands::{self, Result};
-use std::mem;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use std::{cmp, mem, ops};
+use std::time::Instant;
 use crate::input::Key;
-use crate::util;
+use crate::util::{self, encryption, generate, markup, number, PositionMap};
 use crate::util::token::{Direction, adjacent_token_position};
-use crate::models::application::{Application, ClipboardContent, Mode};
+use crate::models::application::{self, word_index, Activity, Application, ClipboardContent, Mode, MultiCursor};
 use crate::models::application::modes::ConfirmMode;
-use scribe::buffer::{Buffer, Position, Range};
+use scribe::buffer::{Buffer, Distance, Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Where the cursor is left once a snippet's been inserted.
+const SNIPPET_CURSOR: &str = "$0";
 
 pub fn save(app: &mut Application) -> Result {
+    // Saving a pending changes preview applies its changes instead.
+    let id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    if let Some(id) = id.filter(|id| app.pending_changes.contains_key(id)) {
+        return apply_pending_changes(id, app);
+    }
+
     remove_trailing_whitespace(app)?;
     ensure_trailing_newline(app)?;
 
+    // Encrypted buffers are re-encrypted, so that their plaintext never reaches the disk.
+    if let Some(passphrase) = id.and_then(|id| app.encrypted_buffers.get(&id)).cloned() {
+        let started_at = Instant::now();
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let path = buffer.path.as_ref().ok_or(BUFFER_PATH_MISSING)?;
+        encryption::encrypt(path, &passphrase, &buffer.data(), &app.preferences.borrow())?;
+        app.view.record_saved_content(buffer);
+        app.view.profiler.record(Activity::Io, started_at.elapsed());
+
+        return Ok(());
+    }
+
     // Slight duplication here, but we need to check for a buffer path without
     // borrowing the buffer for the full scope of this save command. That will
     // allow us to hand the application object to the switch_to_path_mode
@@ -23,11 +50,19 @@ pub fn save(app: &mut Application) -> Result {
         .path.is_some();
 
     if path_set {
-        app.workspace
-            .current_buffer()
-            .ok_or(BUFFER_MISSING)?
-            .save()
-            .chain_err(|| "Unable to save buffer")
+        let started_at = Instant::now();
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+        // Rewriting the file's saved content would only change its
+        // modification time (and trigger anything watching for that).
+        let exists = buffer.path.as_ref().map_or(false, |path| path.exists());
+        if !exists || !app.view.saved(buffer) {
+            buffer.save().chain_err(|| "Unable to save buffer")?;
+            app.view.record_saved_content(buffer);
+        }
+        app.view.profiler.record(Activity::Io, started_at.elapsed());
+
+        Ok(())
     } else {
         commands::application::switch_to_path_mode(app)?;
         if let Mode::Path(ref mut mode) = app.mode {
@@ -38,13 +73,57 @@ pub fn save(app: &mut Application) -> Result {
     }
 }
 
+/// Saves the current buffer to a new path, which it's associated with from
+/// then on. Run from command mode, with the path as its argument.
+pub(crate) fn save_as(app: &mut Application, path: &str) -> Result {
+    ensure_unencrypted(app, "saved to another path")?;
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.path = Some(PathBuf::from(path));
+    app.workspace.update_current_syntax().chain_err(||
+        "Failed to update buffer's syntax definition"
+    )?;
+
+    save(app)
+}
+
+// Applies the changes previewed in the current buffer, and closes it.
+fn apply_pending_changes(id: usize, app: &mut Application) -> Result {
+    let diff = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.data();
+    if let Some(changes) = app.pending_changes.get(&id) {
+        changes.apply(&mut app.workspace, &diff)?;
+    }
+
+    close(app)
+}
+
+// Operations that would write an encrypted buffer's plaintext
+// to disk, or replace it with the file's ciphertext, are refused.
+fn ensure_unencrypted(app: &mut Application, operation: &str) -> errors::Result<()> {
+    let id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    if matches!(id, Some(id) if app.encrypted_buffers.contains_key(&id)) {
+        bail!(format!("Encrypted buffers can't be {}", operation));
+    }
+
+    Ok(())
+}
+
 pub fn reload(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.reload().chain_err(|| {
-        "Unable to reload buffer."
-    })
+    ensure_unencrypted(app, "reloaded")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    util::reload::reload(buffer)?;
+    app.view.record_saved_content(buffer);
+
+    Ok(())
 }
 
 pub fn delete(app: &mut Application) -> Result {
+    let deleted = edit_at_cursors(app, |data, offset| {
+        let length = data[offset..].graphemes(true).next().map_or(0, str::len);
+        (offset, offset + length, String::new())
+    })?;
+    if deleted {
+        return Ok(());
+    }
+
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.delete();
     commands::view::scroll_to_cursor(app)?;
 
@@ -152,9 +231,9 @@ pub fn merge_next_line(app: &mut Application) -> Result {
 
 pub fn close(app: &mut Application) -> Result {
     // Build confirmation check conditions.
-    let (unmodified, empty) =
+    let (unmodified, empty, id) =
         if let Some(buf) = app.workspace.current_buffer() {
-            (!buf.modified(), buf.data().is_empty())
+            (!app.view.modified(buf), buf.data().is_empty(), buf.id)
         } else {
             bail!(BUFFER_MISSING);
         };
@@ -164,13 +243,11 @@ pub fn close(app: &mut Application) -> Result {
         } else {
             false
         };
+    // Closing a pending changes preview discards its changes.
+    let pending = id.and_then(|id| app.pending_changes.remove(&id)).is_some();
 
-    if unmodified || empty || confirm_mode {
-        // Clean up view-related data for the buffer.
-        app.view.forget_buffer(
-            app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
-        )?;
-        app.workspace.close_current_buffer();
+    if unmodified || empty || confirm_mode || pending {
+        util::close_current_buffer(app)?;
     } else {
         // Display a confirmation prompt before closing a modified buffer.
         let confirm_mode = ConfirmMode::new(close);
@@ -181,62 +258,144 @@ pub fn close(app: &mut Application) -> Result {
 }
 
 pub fn close_others(app: &mut Application) -> Result {
-    // Get the current buffer's ID so we know what *not* to close.
-    let id = app.workspace.current_buffer().map(|b| b.id).ok_or(BUFFER_MISSING)?;
-    let mut modified_buffer = false;
-
-    loop {
-        // Try to advance to the next buffer. Handles two important states:
-        //
-        // 1. The initial state, where we haven't advanced beyond the
-        //    the original/desired buffer.
-        // 2. When a buffer that is being closed is positioned *after* the
-        //    original buffer. Closing a buffer in this scenario selects the
-        //    preceding buffer, which, without advancing, would be
-        //    incorrectly interpreted as the completion of this process.
-        if app.workspace.current_buffer().map(|b| b.id) == Some(id) {
-            app.workspace.next_buffer();
-        }
+    let ids = other_unpinned_buffer_ids(app)?;
+    if util::any_modified(&ids, app) {
+        // Display a confirmation prompt before closing modified buffers.
+        let confirm_mode = ConfirmMode::new(close_others_confirm);
+        app.mode = Mode::Confirm(confirm_mode);
 
-        // If we haven't yet looped back to the original buffer,
-        // clean up view-related data and close the current buffer.
-        if let Some(buf) = app.workspace.current_buffer() {
-            if buf.id == id {
-                // We've only got one buffer open; we're done.
-                break;
-            } else if buf.modified() && !buf.data().is_empty() {
-                modified_buffer = true;
-            } else {
-                app.view.forget_buffer(buf)?;
-            }
-        }
+        Ok(())
+    } else {
+        close_others_confirm(app)
+    }
+}
 
-        if modified_buffer {
-            // Display a confirmation prompt before closing a modified buffer.
-            let confirm_mode = ConfirmMode::new(close_others_confirm);
-            app.mode = Mode::Confirm(confirm_mode);
-            break;
-        }
+/// Pins the current buffer (or unpins it, if it's already pinned), which
+/// lists it first in buffer mode and keeps it open when closing others.
+pub fn toggle_pin(app: &mut Application) -> Result {
+    let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+    if !app.pinned_buffers.remove(&id) {
+        app.pinned_buffers.insert(id);
+    }
 
-        // We haven't broken from the loop, so we're not back
-        // at the original buffer; close the current buffer.
-        app.workspace.close_current_buffer();
+    Ok(())
+}
+
+/// Allows editing the current buffer, if it's read-only (e.g. because it's
+/// in a vendored directory), or protects it from editing, if it isn't.
+pub fn toggle_read_only(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    let read_only = !app.read_only_buffers.contains(buffer);
+    app.read_only_buffers.set(id, read_only);
+
+    Ok(())
+}
+
+/// Changes a preference for the current buffer alone, in place of its
+/// type's (or global) value. Run from command mode, with a "setting=value"
+/// argument (e.g. "tab_width=4", or "syntax=rust").
+pub(crate) fn set_local(app: &mut Application, argument: &str) -> Result {
+    let mut parts = argument.splitn(2, '=');
+    let setting = parts.next().unwrap_or("").trim();
+    let value = parts
+        .next()
+        .map(str::trim)
+        .ok_or_else(|| format!("Please provide a value for {} (e.g. {}=value)", setting, setting))?;
+
+    let syntax = if setting == "syntax" {
+        let syntax = app.workspace.syntax_set
+            .find_syntax_by_token(value)
+            .ok_or_else(|| format!("Syntax \"{}\" doesn't exist", value))?;
+
+        Some(syntax.clone())
+    } else {
+        None
+    };
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if let Some(syntax) = syntax {
+        app.view.set_local_setting(buffer, setting, &syntax.name)?;
+        buffer.syntax_definition = Some(syntax);
+        app.view.clear_render_cache(buffer.id.ok_or(BUFFER_MISSING)?);
+    } else {
+        app.view.set_local_setting(buffer, setting, value)?;
     }
 
     Ok(())
 }
 
-pub fn close_others_confirm(app: &mut Application) -> Result {
-    if let Some(buf) = app.workspace.current_buffer() {
-        app.view.forget_buffer(buf)?;
+/// Shows the preferences changed for the current buffer alone.
+pub fn display_local_settings(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.notification = Some(match app.view.local_settings(buffer) {
+        Some(settings) => format!("Local settings: {}", settings),
+        None => String::from("No local settings; see buffer::set_local"),
+    });
+
+    Ok(())
+}
+
+/// Reverts the preferences changed for the current buffer alone.
+pub fn reset_local_settings(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let settings = app.view.reset_local_settings(buffer).ok_or("No local settings to reset")?;
+    if settings.syntax().is_some() {
+        app.view.clear_render_cache(buffer.id.ok_or(BUFFER_MISSING)?);
+        app.workspace.update_current_syntax().chain_err(||
+            "Failed to update buffer's syntax definition"
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Switches to the most recently used buffer other than the current one,
+/// making it easy to flip back and forth between two buffers.
+pub fn switch_to_last(app: &mut Application) -> Result {
+    let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+    app.recent_buffers.access(id);
+
+    for last_id in app.recent_buffers.ids().into_iter().skip(1) {
+        if util::select_buffer(last_id, app) {
+            app.recent_buffers.access(last_id);
+            return Ok(());
+        }
+
+        // The buffer's been closed.
+        app.recent_buffers.forget(last_id);
     }
-    app.workspace.close_current_buffer();
+
+    bail!("No other buffers have been used")
+}
+
+pub fn close_others_confirm(app: &mut Application) -> Result {
+    let ids = other_unpinned_buffer_ids(app)?;
+    util::close_buffers(&ids, app)?;
     commands::application::switch_to_normal_mode(app)?;
 
     Ok(())
 }
 
+// Pinned buffers are left open when closing others.
+fn other_unpinned_buffer_ids(app: &mut Application) -> errors::Result<Vec<usize>> {
+    let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+
+    Ok(util::buffer_ids(app)
+        .into_iter()
+        .filter(|other_id| *other_id != id && !app.pinned_buffers.contains(other_id))
+        .collect())
+}
+
 pub fn backspace(app: &mut Application) -> Result {
+    let deleted = edit_at_cursors(app, |data, offset| {
+        let start = data[..offset].grapheme_indices(true).next_back().map_or(0, |(index, _)| index);
+        (start, offset, String::new())
+    })?;
+    if deleted {
+        return Ok(());
+    }
+
     let mut outdent = false;
 
     if let Some(buffer) = app.workspace.current_buffer() {
@@ -268,11 +427,20 @@ pub fn backspace(app: &mut Application) -> Result {
 }
 
 pub fn insert_char(app: &mut Application) -> Result {
+    if let Some(Key::Char(character)) = *app.view.last_key() {
+        if edit_at_cursors(app, |_, offset| (offset, offset, character.to_string()))? {
+            return Ok(());
+        }
+    }
+
     if let Some(buffer) = app.workspace.current_buffer() {
         if let Some(Key::Char(character)) = *app.view.last_key() {
             // TODO: Drop explicit call to to_string().
             buffer.insert(character.to_string());
             buffer.cursor.move_right();
+            if character == '>' {
+                close_markup_tag(buffer);
+            }
         } else {
             bail!("No character to insert");
         }
@@ -284,6 +452,35 @@ pub fn insert_char(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Removes the name from the tags of the markup element at the cursor, placing
+/// a cursor in each, so that a new name can be typed into both at once.
+pub fn rename_tag(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or("Buffer has no ID")?;
+    let data = buffer.data();
+    let positions = PositionMap::new(&data);
+    let (opening, closing) = markup::matching_tags(&data, positions.offset(&buffer.cursor))
+        .ok_or("No tag at the cursor")?;
+    let name_length = opening.name.len();
+    let name_range = |start| Range::new(positions.position(start), positions.position(start + name_length));
+
+    // Delete the closing name first, so that the opening name's range is unaffected.
+    buffer.start_operation_group();
+    buffer.delete_range(name_range(closing.name_start()));
+    buffer.delete_range(name_range(opening.name_start()));
+    buffer.end_operation_group();
+
+    let data = buffer.data();
+    let positions = PositionMap::new(&data);
+    buffer.cursor.move_to(positions.position(opening.name_start()));
+    let mut cursors = MultiCursor::default();
+    cursors.add(positions.position(closing.name_start() - name_length));
+    app.multi_cursors.insert(id, cursors);
+    app.mode = Mode::Insert;
+
+    Ok(())
+}
+
 pub fn display_current_scope(app: &mut Application) -> Result {
     let scope_display_buffer = {
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -300,10 +497,139 @@ pub fn display_current_scope(app: &mut Application) -> Result {
     util::add_buffer(scope_display_buffer, app)
 }
 
+/// Writes the buffer (or selection), highlighted using the current
+/// theme, to an HTML document alongside it (e.g. main.rs.html).
+pub fn export_html(app: &mut Application) -> Result {
+    export_to_file(app, ExportFormat::Html)
+}
+
+/// Writes the buffer (or selection), highlighted using the current theme,
+/// to a file of ANSI-coloured text alongside it (e.g. main.rs.ansi).
+pub fn export_ansi(app: &mut Application) -> Result {
+    export_to_file(app, ExportFormat::Ansi)
+}
+
+/// Writes the changes logged for the buffer (see `Preferences::edit_log`) to a
+/// series of patches alongside it (e.g. main.rs.patch), which `git am` applies.
+pub fn export_edit_log(app: &mut Application) -> Result {
+    ensure_unencrypted(app, "exported to a file")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or("Can't export the edit log of a buffer without a path")?;
+    let log = buffer.id
+        .and_then(|id| app.edit_logs.get(id))
+        .filter(|log| !log.entries().is_empty())
+        .ok_or("No changes have been logged for this buffer (is edit_log enabled?)")?;
+    let relative_path = path.strip_prefix(&app.workspace.path).unwrap_or(&path);
+    let series = log.patch_series(&relative_path.to_string_lossy())?;
+
+    let mut patch_path = path.clone().into_os_string();
+    patch_path.push(".patch");
+    fs::write(&patch_path, series).chain_err(|| "Unable to write edit log")?;
+    app.notification = Some(format!(
+        "Exported {} change{} to {}",
+        log.entries().len(),
+        if log.entries().len() == 1 { "" } else { "s" },
+        patch_path.to_string_lossy()
+    ));
+
+    Ok(())
+}
+
+pub fn copy_as_html(app: &mut Application) -> Result {
+    export_to_clipboard(app, ExportFormat::Html)
+}
+
+pub fn copy_as_ansi(app: &mut Application) -> Result {
+    export_to_clipboard(app, ExportFormat::Ansi)
+}
+
+#[derive(Clone, Copy)]
+enum ExportFormat {
+    Html,
+    Ansi,
+}
+
+fn export_to_file(app: &mut Application, format: ExportFormat) -> Result {
+    ensure_unencrypted(app, "exported to a file")?;
+    let mut path = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?
+        .path.clone()
+        .ok_or("Can't export a buffer without a path; copy it instead")?
+        .into_os_string();
+    path.push(match format {
+        ExportFormat::Html => ".html",
+        ExportFormat::Ansi => ".ansi",
+    });
+    let content = render_export(app, format)?;
+    fs::write(&path, content).chain_err(|| "Unable to write exported buffer")?;
+
+    app.notification = Some(format!("Exported to {}", path.to_string_lossy()));
+    finish_export(app)
+}
+
+fn export_to_clipboard(app: &mut Application, format: ExportFormat) -> Result {
+    let content = render_export(app, format)?;
+    app.clipboard.set_content(ClipboardContent::Inline(content))?;
+
+    finish_export(app)
+}
+
+// Renders the selection, if there is one, or otherwise the whole buffer.
+fn render_export(app: &mut Application, format: ExportFormat) -> errors::Result<String> {
+    let selection = match app.mode {
+        Mode::Select(_) | Mode::SelectLine(_) => Some(util::selected_range(app)?),
+        _ => None,
+    };
+    let preferences = app.preferences.borrow();
+    let theme = app.view.theme_set.themes
+        .get(preferences.theme())
+        .ok_or_else(|| format!("Couldn't find \"{}\" theme", preferences.theme()))?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+
+    // Highlighting needs the content preceding the selection for context,
+    // so the selection is described as a byte range within the buffer.
+    let range = selection.map(|selection| {
+        let start = buffer.read(&Range::new(Position::new(), selection.start())).unwrap_or_default().len();
+        let length = buffer.read(&selection).unwrap_or_default().len();
+
+        (start, start + length)
+    });
+    let syntax = buffer.syntax_definition.as_ref().ok_or("Buffer has no syntax definition")?;
+
+    Ok(match format {
+        ExportFormat::Html => {
+            let title = buffer.path.as_ref()
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("untitled"));
+            util::export::html(&data, range, syntax, theme, &title)
+        }
+        ExportFormat::Ansi => util::export::ansi(&data, range, syntax, theme),
+    })
+}
+
+// Leaves select modes, as copying does.
+fn finish_export(app: &mut Application) -> Result {
+    match app.mode {
+        Mode::Select(_) | Mode::SelectLine(_) => commands::application::switch_to_normal_mode(app),
+        _ => Ok(()),
+    }
+}
+
 /// Inserts a newline character at the current cursor position.
 /// Also performs automatic indentation, basing the indent off
 /// of the previous line's leading whitespace.
 pub fn insert_newline(app: &mut Application) -> Result {
+    // Extra cursors' lines copy their own line's indentation.
+    let inserted = edit_at_cursors(app, |data, offset| {
+        let line_start = data[..offset].rfind('\n').map_or(0, |index| index + 1);
+        let indent: String = data[line_start..offset].chars().take_while(|c| c.is_whitespace()).collect();
+        (offset, offset, format!("\n{}", indent))
+    })?;
+    if inserted {
+        return Ok(());
+    }
+
     if let Some(buffer) = app.workspace.current_buffer() {
         // Insert the newline character.
         buffer.insert("\n");
@@ -347,30 +673,22 @@ pub fn insert_newline(app: &mut Application) -> Result {
 
 pub fn indent_line(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
+    let tab_content = app.view.tab_content(buffer);
+    let indent_width = tab_content.chars().count();
 
     let target_position = match app.mode {
-        Mode::Insert => {
+        Mode::Insert | Mode::Select(_) => {
             Position {
                 line: buffer.cursor.line,
-                offset: buffer.cursor.offset + tab_content.chars().count(),
+                offset: buffer.cursor.offset + indent_width,
             }
         }
         _ => *buffer.cursor.clone(),
     };
 
-    // Get the range of lines we'll outdent based on
+    // Get the range of lines we'll indent based on
     // either the current selection or cursor line.
-    let lines = match app.mode {
-        Mode::SelectLine(ref mode) => {
-            if mode.anchor >= buffer.cursor.line {
-                buffer.cursor.line..mode.anchor + 1
-            } else {
-                mode.anchor..buffer.cursor.line + 1
-            }
-        }
-        _ => buffer.cursor.line..buffer.cursor.line + 1,
-    };
+    let lines = selected_lines(&app.mode, &buffer.cursor);
 
     // Move to the start of the current line and
     // insert the content, as a single operation.
@@ -387,28 +705,28 @@ pub fn indent_line(app: &mut Application) -> Result {
     // Move to the original position, shifted to compensate for the indent.
     buffer.cursor.move_to(target_position);
 
+    // Keep the selection anchored to the same content.
+    if let Mode::Select(ref mut mode) = app.mode {
+        mode.anchor.offset += indent_width;
+    }
+
     Ok(())
 }
 
 pub fn outdent_line(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
+    let tab_content = app.view.tab_content(buffer);
 
     // FIXME: Determine this based on file type and/or user config.
     let data = buffer.data();
 
     // Get the range of lines we'll outdent based on
     // either the current selection or cursor line.
-    let lines = match app.mode {
-        Mode::SelectLine(ref mode) => {
-            if mode.anchor >= buffer.cursor.line {
-                buffer.cursor.line..mode.anchor + 1
-            } else {
-                mode.anchor..buffer.cursor.line + 1
-            }
-        }
-        _ => buffer.cursor.line..buffer.cursor.line + 1,
-    };
+    let lines = selected_lines(&app.mode, &buffer.cursor);
+
+    // Track how much whitespace is removed from each line,
+    // so that the cursor and selection can be adjusted.
+    let mut removed_counts = HashMap::new();
 
     // Group the individual outdent operations as one.
     buffer.start_operation_group();
@@ -435,8 +753,7 @@ pub fn outdent_line(app: &mut Application) -> Result {
                 }
             }
 
-            // Remove leading whitespace, up to indent size,
-            // if we found any, and adjust cursor accordingly.
+            // Remove leading whitespace, up to indent size, if we found any.
             if space_char_count > 0 {
                 buffer.delete_range(Range::new(Position {
                                                    line,
@@ -446,18 +763,7 @@ pub fn outdent_line(app: &mut Application) -> Result {
                                                    line,
                                                    offset: space_char_count,
                                                }));
-
-                // Figure out where the cursor should sit, guarding against underflow.
-                let target_offset = buffer.cursor
-                                          .offset
-                                          .checked_sub(space_char_count)
-                                          .unwrap_or(0);
-                let target_line = buffer.cursor.line;
-
-                buffer.cursor.move_to(Position {
-                    line: target_line,
-                    offset: target_offset,
-                });
+                removed_counts.insert(line, space_char_count);
             }
         }
     }
@@ -465,9 +771,32 @@ pub fn outdent_line(app: &mut Application) -> Result {
     // Finish grouping the individual outdent operations as one.
     buffer.end_operation_group();
 
+    // Shift positions on outdented lines, guarding against underflow.
+    let outdented = |position: Position| Position {
+        line: position.line,
+        offset: position.offset.saturating_sub(*removed_counts.get(&position.line).unwrap_or(&0)),
+    };
+    buffer.cursor.move_to(outdented(*buffer.cursor));
+
+    // Keep the selection anchored to the same content.
+    if let Mode::Select(ref mut mode) = app.mode {
+        mode.anchor = outdented(mode.anchor);
+    }
+
     Ok(())
 }
 
+// The lines spanned by the current selection, or the cursor line.
+fn selected_lines(mode: &Mode, cursor: &Position) -> ops::Range<usize> {
+    let anchor = match *mode {
+        Mode::Select(ref mode) => mode.anchor.line,
+        Mode::SelectLine(ref mode) => mode.anchor,
+        _ => cursor.line,
+    };
+
+    cmp::min(anchor, cursor.line)..cmp::max(anchor, cursor.line) + 1
+}
+
 pub fn change_token(app: &mut Application) -> Result {
     commands::buffer::delete_token(app)?;
     commands::application::switch_to_insert_mode(app)?;
@@ -521,6 +850,8 @@ pub fn end_command_group(app: &mut Application) -> Result {
 }
 
 pub fn undo(app: &mut Application) -> Result {
+    // Extra cursors can't follow the buffer's content through its history.
+    commands::select::remove_extra_cursors(app)?;
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.undo();
     commands::view::scroll_to_cursor(app).chain_err(|| {
         "Couldn't scroll to cursor after undoing."
@@ -528,6 +859,7 @@ pub fn undo(app: &mut Application) -> Result {
 }
 
 pub fn redo(app: &mut Application) -> Result {
+    commands::select::remove_extra_cursors(app)?;
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.redo();
     commands::view::scroll_to_cursor(app).chain_err(|| {
         "Couldn't scroll to cursor after redoing."
@@ -535,81 +867,291 @@ pub fn redo(app: &mut Application) -> Result {
 }
 
 pub fn paste(app: &mut Application) -> Result {
-    let insert_below = match app.mode {
+    let replacing_selection = match app.mode {
         Mode::Select(_) | Mode::SelectLine(_) | Mode::Search(_) => {
             commands::selection::delete(app).chain_err(|| {
                 "Couldn't delete selection prior to pasting."
             })?;
-            false
+            true
         }
-        _ => true,
+        _ => false,
     };
+    let charwise_selection = matches!(app.mode, Mode::Select(_));
 
-    // TODO: Clean up duplicate buffer.insert(content.clone()) calls.
-    if let Some(buffer) = app.workspace.current_buffer() {
-        match *app.clipboard.get_content() {
-            ClipboardContent::Inline(ref content) => buffer.insert(content.clone()),
-            ClipboardContent::Block(ref content) => {
-                let original_cursor_position = *buffer.cursor.clone();
-                let line = original_cursor_position.line;
-
-                if insert_below {
-                    buffer.cursor.move_to(Position {
-                        line: line + 1,
-                        offset: 0,
-                    });
-
-                    if *buffer.cursor == original_cursor_position {
-                        // That didn't work because we're at the last line.
-                        // Move to the end of the line to insert the data.
-                        if let Some(line_content) = buffer.data().lines().nth(line) {
-                            buffer.cursor.move_to(Position {
-                                line,
-                                offset: line_content.len(),
-                            });
-                            buffer.insert(format!("\n{}", content));
-                            buffer.cursor.move_to(original_cursor_position);
-                        } else {
-                            // We're on a trailing newline, which doesn't
-                            // have any data; just insert the content here.
-                            buffer.insert(content.clone());
-                        }
-                    } else {
-                        buffer.insert(content.clone());
-                    }
-                } else {
-                    buffer.insert(content.clone());
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let pasted_content = match *app.clipboard.get_content() {
+        ClipboardContent::Inline(ref content) => {
+            buffer.insert(content.clone());
+            Some(content.clone())
+        }
+        ClipboardContent::Block(ref content) => {
+            if replacing_selection {
+                // Keep lines intact, rather than splicing
+                // them into the middle of the current line.
+                if charwise_selection && buffer.cursor.offset > 0 {
+                    let line = buffer.cursor.line;
+                    buffer.insert("\n");
+                    buffer.cursor.move_to(Position { line: line + 1, offset: 0 });
                 }
+                buffer.insert(content.clone());
+                Some(content.clone())
+            } else {
+                paste_below(buffer, content);
+                None
             }
-            ClipboardContent::None => (),
         }
-    } else {
-        bail!(BUFFER_MISSING);
+        ClipboardContent::None => None,
+    };
+
+    // Pasting over a selection leaves the pasted content selected,
+    // so that it can be adjusted further (e.g. indented).
+    if let Some(content) = pasted_content {
+        let start = *app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor;
+        select_pasted_content(app, start, &content)?;
     }
     commands::view::scroll_to_cursor(app)?;
 
     Ok(())
 }
 
-pub fn paste_above(app: &mut Application) -> Result {
+/// Pastes linewise content on the line above the cursor,
+/// and characterwise content at the cursor.
+pub fn paste_before(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
-    if let ClipboardContent::Block(ref content) = *app.clipboard.get_content() {
-        let mut start_of_line = Position {
-            line: buffer.cursor.line,
-            offset: 0,
+    match *app.clipboard.get_content() {
+        ClipboardContent::Inline(ref content) => buffer.insert(content.clone()),
+        ClipboardContent::Block(ref content) => paste_above_line(buffer, content),
+        ClipboardContent::None => (),
+    }
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Pastes linewise content on the line below the cursor,
+/// and characterwise content after the cursor's character.
+pub fn paste_after(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+    match *app.clipboard.get_content() {
+        ClipboardContent::Inline(ref content) => {
+            // Moving right is a no-op at the end of the line.
+            buffer.cursor.move_right();
+            buffer.insert(content.clone());
+        }
+        ClipboardContent::Block(ref content) => paste_below(buffer, content),
+        ClipboardContent::None => (),
+    }
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Pastes like `paste`, but re-indents multi-line content to match the
+/// current line, keeping its lines' indentation relative to one another.
+pub fn paste_and_indent(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let tab_content = app.view.tab_content(buffer);
+    let tab_width = app.view.tab_width(buffer);
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+    let indentation = &line[..line.len() - line.trim_start().len()];
+
+    match *app.clipboard.get_content() {
+        // The first line of inline content continues the current line.
+        ClipboardContent::Inline(ref content) => {
+            let (first_line, rest) = content.split_at(content.find('\n').map_or(content.len(), |i| i + 1));
+            let rest = reindent(rest, indentation, &tab_content, tab_width);
+            buffer.insert(format!("{}{}", first_line, rest));
+        }
+        ClipboardContent::Block(ref content) => {
+            paste_below(buffer, &reindent(content, indentation, &tab_content, tab_width));
+        }
+        ClipboardContent::None => (),
+    }
+    commands::view::scroll_to_cursor(app)
+}
+
+// Shifts lines so that the least indented starts at the specified
+// indentation, using the tab content to indent the others further.
+fn reindent(content: &str, indentation: &str, tab_content: &str, tab_width: usize) -> String {
+    let width = |line: &str| -> usize {
+        line.chars()
+            .take_while(|c| c.is_whitespace() && *c != '\n')
+            .map(|c| if c == '\t' { tab_width } else { 1 })
+            .sum()
+    };
+    let minimum = content
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(width)
+        .min()
+        .unwrap_or(0);
+
+    content.split_inclusive('\n').map(|line| {
+        let trimmed = line.trim_start_matches(|c: char| c.is_whitespace() && c != '\n');
+        if trimmed.trim().is_empty() {
+            return trimmed.to_string();
+        }
+
+        let extra = width(line) - minimum;
+        let extra_indentation = if tab_content.starts_with('\t') {
+            format!("{}{}", "\t".repeat(extra / tab_width), " ".repeat(extra % tab_width))
+        } else {
+            " ".repeat(extra)
         };
+        format!("{}{}{}", indentation, extra_indentation, trimmed)
+    }).collect()
+}
+
+fn paste_below(buffer: &mut Buffer, content: &str) {
+    let original_cursor_position = *buffer.cursor.clone();
+    let line = original_cursor_position.line;
+
+    buffer.cursor.move_to(Position {
+        line: line + 1,
+        offset: 0,
+    });
+
+    if *buffer.cursor == original_cursor_position {
+        // That didn't work because we're at the last line.
+        // Move to the end of the line to insert the data.
+        if let Some(line_content) = buffer.data().lines().nth(line) {
+            buffer.cursor.move_to(Position {
+                line,
+                offset: line_content.len(),
+            });
+            buffer.insert(format!("\n{}", content));
+            buffer.cursor.move_to(original_cursor_position);
+        } else {
+            // We're on a trailing newline, which doesn't
+            // have any data; just insert the content here.
+            buffer.insert(content);
+        }
+    } else {
+        buffer.insert(content);
+    }
+}
+
+fn paste_above_line(buffer: &mut Buffer, content: &str) {
+    let mut start_of_line = Position {
+        line: buffer.cursor.line,
+        offset: 0,
+    };
+
+    // Temporarily move the cursor to the start of the line to insert the clipboard content.
+    mem::swap(&mut *buffer.cursor, &mut start_of_line);
+    buffer.insert(content);
+    mem::swap(&mut *buffer.cursor, &mut start_of_line);
+}
+
+fn select_pasted_content(app: &mut Application, start: Position, content: &str) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let end = start + Distance::of_str(content);
+
+    match app.mode {
+        Mode::Select(ref mut mode) => {
+            mode.anchor = start;
+            buffer.cursor.move_to(end);
+        }
+        Mode::SelectLine(ref mut mode) => {
+            // Content ending in a newline doesn't extend onto the following line.
+            let last_line = if content.ends_with('\n') {
+                end.line.saturating_sub(1).max(start.line)
+            } else {
+                end.line
+            };
+            mode.anchor = start.line;
+            buffer.cursor.move_to(Position { line: last_line, offset: 0 });
+        }
+        _ => (),
+    }
+
+    Ok(())
+}
 
-        // Temporarily move the cursor to the start of the line
-        // to insert the clipboard content (without allocating).
-        mem::swap(&mut *buffer.cursor, &mut start_of_line);
-        buffer.insert(content.clone());
-        mem::swap(&mut *buffer.cursor, &mut start_of_line);
+pub fn paste_above(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+    if let ClipboardContent::Block(ref content) = *app.clipboard.get_content() {
+        paste_above_line(buffer, content);
     }
 
     Ok(())
 }
 
+/// Pastes tmux's most recent paste buffer, independent of the configured clipboard.
+pub fn paste_tmux_buffer(app: &mut Application) -> Result {
+    let content = application::tmux_buffer()?;
+    if let Mode::Select(_) | Mode::SelectLine(_) | Mode::Search(_) = app.mode {
+        commands::selection::delete(app).chain_err(|| {
+            "Couldn't delete selection prior to pasting."
+        })?;
+    }
+
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.insert(content);
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Inserts text pasted into the terminal at the cursor (or at each of them),
+/// replacing any selection, as one operation. The cursor is left after it.
+pub(crate) fn insert_pasted_text(app: &mut Application, content: &str) -> Result {
+    if let Mode::Select(_) | Mode::SelectLine(_) = app.mode {
+        commands::selection::delete(app).chain_err(|| {
+            "Couldn't delete selection prior to pasting."
+        })?;
+        commands::application::switch_to_normal_mode(app)?;
+    }
+    if edit_at_cursors(app, |_, offset| (offset, offset, content.to_string()))? {
+        return Ok(());
+    }
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let end = *buffer.cursor + Distance::of_str(content);
+    buffer.start_operation_group();
+    buffer.insert(content);
+    buffer.end_operation_group();
+    buffer.cursor.move_to(end);
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Inserts a snippet at the cursor, indenting its lines to match the cursor's
+/// line, and moving the cursor to its placeholder (or its end, without one).
+pub(crate) fn insert_snippet(app: &mut Application, snippet: &str) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let indentation: String = buffer.data()
+        .lines()
+        .nth(buffer.cursor.line)
+        .unwrap_or("")
+        .chars()
+        .take_while(|c| *c == ' ' || *c == '\t')
+        .collect();
+    let content = snippet
+        .split('\n')
+        .enumerate()
+        .map(|(index, line)| if index > 0 && !line.is_empty() {
+            format!("{}{}", indentation, line)
+        } else {
+            line.to_string()
+        })
+        .collect::<Vec<String>>()
+        .join("\n");
+    let (content, cursor_distance) = match content.find(SNIPPET_CURSOR) {
+        Some(index) => (
+            content.replacen(SNIPPET_CURSOR, "", 1),
+            Distance::of_str(&content[..index])
+        ),
+        None => {
+            let distance = Distance::of_str(&content);
+            (content, distance)
+        }
+    };
+
+    let end = *buffer.cursor + cursor_distance;
+    buffer.start_operation_group();
+    buffer.insert(content);
+    buffer.end_operation_group();
+    buffer.cursor.move_to(end);
+    commands::view::scroll_to_cursor(app)
+}
+
 pub fn remove_trailing_whitespace(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let mut line = 0;
@@ -707,12 +1249,114 @@ pub fn ensure_trailing_newline(app: &mut Application) -> Result {
 
 pub fn insert_tab(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let tab_content = app.preferences.borrow().tab_content(buffer.path.as_ref());
-    let tab_content_width = tab_content.chars().count();
-    buffer.insert(tab_content.clone());
+    let tab_content = app.view.tab_content(buffer);
+    if edit_at_cursors(app, |_, offset| (offset, offset, tab_content.clone()))? {
+        return Ok(());
+    }
+
+    insert_content(app, tab_content)
+}
+
+/// Replaces the number under the cursor with its
+/// representation in the next base (dec, hex, bin, oct).
+pub fn cycle_number_base(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (number, range) = number::number_at_cursor(buffer).ok_or("No number found under the cursor")?;
+
+    buffer.start_operation_group();
+    buffer.delete_range(range.clone());
+    buffer.cursor.move_to(range.start());
+    buffer.insert(number.format(number.base.next()));
+    buffer.end_operation_group();
+
+    Ok(())
+}
+
+pub fn insert_uuid(app: &mut Application) -> Result {
+    let uuid = generate::uuid()?;
+    insert_content(app, uuid)
+}
+
+pub fn insert_date(app: &mut Application) -> Result {
+    let date = generate::local_time(app.preferences.borrow().date_format())?;
+    insert_content(app, date)
+}
+
+pub fn insert_datetime(app: &mut Application) -> Result {
+    let datetime = generate::local_time(app.preferences.borrow().datetime_format())?;
+    insert_content(app, datetime)
+}
+
+pub fn insert_lorem_ipsum(app: &mut Application) -> Result {
+    insert_content(app, generate::LOREM_IPSUM.to_string())
+}
+
+/// Inserts single-line content at the cursor, moving the cursor after it.
+pub fn complete_word(app: &mut Application) -> Result {
+    app.word_index.update(&mut app.workspace, &mut app.view);
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+    let offset = line
+        .grapheme_indices(true)
+        .nth(buffer.cursor.offset)
+        .map(|(index, _)| index)
+        .unwrap_or_else(|| line.len());
+    let prefix = word_index::word_prefix(&line[..offset]);
+    if prefix.is_empty() {
+        bail!("No word to complete");
+    }
+
+    let completion = app.word_index
+        .completions(prefix)
+        .first()
+        .map(|word| word[prefix.len()..].to_string())
+        .ok_or_else(|| format!("No completions found for \"{}\"", prefix))?;
+
+    insert_content(app, completion)
+}
+
+// Applies an edit at each of the buffer's cursors, if it has extra ones,
+// returning whether it did so (otherwise, the caller makes the edit).
+fn edit_at_cursors<F>(app: &mut Application, edit: F) -> errors::Result<bool>
+    where F: Fn(&str, usize) -> (usize, usize, String)
+{
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let multi_cursors = &mut app.multi_cursors;
+    let cursors = match buffer.id.and_then(|id| multi_cursors.get_mut(&id)) {
+        Some(cursors) if !cursors.positions.is_empty() => cursors,
+        _ => return Ok(false),
+    };
+    cursors.edit(buffer, edit);
+    commands::view::scroll_to_cursor(app)?;
+
+    Ok(true)
+}
+
+// Closes a markup tag that was just completed, leaving the cursor inside the element.
+fn close_markup_tag(buffer: &mut Buffer) {
+    if !markup::is_markup(buffer.path.as_deref()) {
+        return;
+    }
+
+    let data = buffer.data();
+    let preceding_content: String = match data.lines().nth(buffer.cursor.line) {
+        Some(line) => line.graphemes(true).take(buffer.cursor.offset).collect(),
+        None => return,
+    };
+    if let Some(tag) = markup::closing_tag(&preceding_content) {
+        buffer.insert(tag);
+    }
+}
+
+fn insert_content(app: &mut Application, content: String) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let content_width = content.chars().count();
+    buffer.insert(content);
 
     // Move the cursor to the end of the inserted content.
-    for _ in 0..tab_content_width {
+    for _ in 0..content_width {
         buffer.cursor.move_right();
     }
 
@@ -722,11 +1366,126 @@ pub fn insert_tab(app: &mut Application) -> Result {
 #[cfg(test)]
 mod tests {
     use crate::commands;
+    use crate::input::Key;
     use crate::models::Application;
-    use crate::models::application::{ClipboardContent, Mode};
+    use crate::models::application::{ClipboardContent, Mode, Preferences};
     use scribe::Buffer;
     use scribe::buffer::Position;
-    use std::path::Path;
+    use crate::util;
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn switch_to_last_flips_between_the_two_most_recent_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        for _ in 0..3 {
+            app.workspace.add_buffer(Buffer::new());
+            let id = app.workspace.current_buffer().unwrap().id.unwrap();
+            app.recent_buffers.access(id);
+        }
+        let ids = util::buffer_ids(&mut app);
+
+        // Select the first buffer without going through the recent list.
+        util::select_buffer(ids[1], &mut app);
+        commands::buffer::switch_to_last(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, Some(ids[0]));
+        commands::buffer::switch_to_last(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, Some(ids[1]));
+    }
+
+    #[test]
+    fn close_others_keeps_pinned_buffers_open() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        for name in &["pinned", "closed", "current"] {
+            let mut buffer = Buffer::new();
+            buffer.path = Some(PathBuf::from(name));
+            app.workspace.add_buffer(buffer);
+        }
+        app.workspace.next_buffer();
+        commands::buffer::toggle_pin(&mut app).unwrap();
+        app.workspace.previous_buffer();
+
+        commands::buffer::close_others(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer_path(), Some(Path::new("current")));
+        app.workspace.next_buffer();
+        assert_eq!(app.workspace.current_buffer_path(), Some(Path::new("pinned")));
+        app.workspace.next_buffer();
+        assert_eq!(app.workspace.current_buffer_path(), Some(Path::new("current")));
+    }
+
+    #[test]
+    fn close_others_confirms_before_closing_modified_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("modified");
+        app.workspace.add_buffer(buffer);
+        app.workspace.add_buffer(Buffer::new());
+        app.workspace.add_buffer(Buffer::new());
+
+        commands::buffer::close_others(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::Confirm(_)));
+
+        commands::confirm::confirm_command(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.notification, Some(String::from("Closed 2 buffers")));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "");
+        app.workspace.next_buffer();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "");
+    }
+
+    #[test]
+    fn complete_word_inserts_the_most_frequent_completion_from_any_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("editable editor editor");
+        app.workspace.add_buffer(buffer);
+        let mut buffer = Buffer::new();
+        buffer.insert("amp ed");
+        buffer.cursor.move_to(Position { line: 0, offset: 6 });
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::complete_word(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amp editor");
+        assert_eq!(*buffer.cursor, Position { line: 0, offset: 10 });
+    }
+
+    #[test]
+    fn cycle_number_base_converts_the_number_under_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("size: 255u8;");
+        buffer.cursor.move_to(Position { line: 0, offset: 7 });
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::cycle_number_base(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "size: 0xffu8;");
+        commands::buffer::cycle_number_base(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "size: 0b11111111u8;");
+        commands::buffer::cycle_number_base(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "size: 0o377u8;");
+        commands::buffer::cycle_number_base(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "size: 255u8;");
+    }
+
+    #[test]
+    fn insert_lorem_ipsum_inserts_text_and_moves_the_cursor_past_it() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\"\"");
+        buffer.cursor.move_to(Position { line: 0, offset: 1 });
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::insert_lorem_ipsum(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert!(buffer.data().starts_with("\"Lorem ipsum dolor"));
+        assert!(buffer.data().ends_with("laborum.\""));
+        assert_eq!(buffer.cursor.offset, buffer.data().len() - 1);
+    }
 
     #[test]
     fn insert_newline_uses_current_line_indentation() {
@@ -901,6 +1660,26 @@ mod tests {
                    "amp\n  editor");
     }
 
+    #[test]
+    fn indent_line_matches_the_indentation_detected_in_the_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn main() {\n    run();\n}\n");
+        app.workspace.add_buffer(buffer);
+        app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+        super::indent_line(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "    fn main() {\n    run();\n}\n");
+
+        // Detection can be disabled in favour of the preferences.
+        app.preferences.borrow_mut().set("detect_indentation", "false").unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn main() {\n\trun();\n}\n");
+        app.workspace.add_buffer(buffer);
+        app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+        super::indent_line(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "  fn main() {\n\trun();\n}\n");
+    }
+
     #[test]
     fn indent_line_works_in_select_line_mode() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1166,6 +1945,52 @@ mod tests {
                    "amp\neditor\n");
     }
 
+    #[test]
+    fn save_leaves_files_alone_when_their_content_is_unchanged() {
+        let path = env::temp_dir().join("amp_save_unchanged_test.txt");
+        fs::write(&path, "amp\n").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(&path).unwrap();
+        app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+        let modified_at = fs::metadata(&path).unwrap().modified().unwrap();
+
+        // Reverting an edit by hand leaves the buffer's history modified.
+        let buffer = app.workspace.current_buffer().unwrap();
+        buffer.insert("a");
+        buffer.delete();
+        assert!(buffer.modified());
+        assert!(!app.view.modified(buffer));
+
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        super::save(&mut app).unwrap();
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified_at);
+
+        // Edits are still saved.
+        app.workspace.current_buffer().unwrap().insert("text ");
+        assert!(app.view.modified(app.workspace.current_buffer().unwrap()));
+        super::save(&mut app).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "text amp\n");
+        assert!(!app.view.modified(app.workspace.current_buffer().unwrap()));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn save_as_writes_unmodified_buffers_to_their_new_path() {
+        let path = env::temp_dir().join("amp_save_as_source_test.txt");
+        let new_path = env::temp_dir().join("amp_save_as_destination_test.txt");
+        fs::write(&path, "amp\n").unwrap();
+        let _ = fs::remove_file(&new_path);
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.open_buffer(&path).unwrap();
+        app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+
+        super::save_as(&mut app, new_path.to_str().unwrap()).unwrap();
+
+        assert_eq!(fs::read_to_string(&new_path).unwrap(), "amp\n");
+        fs::remove_file(&path).unwrap();
+        fs::remove_file(&new_path).unwrap();
+    }
+
     #[test]
     fn save_switches_to_path_mode_when_path_is_missing() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1484,6 +2309,136 @@ mod tests {
         // assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp");
     }
 
+    #[test]
+    fn paste_over_selection_selects_pasted_content() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.clipboard.set_content(ClipboardContent::Inline("text\neditor".to_string())).unwrap();
+        app.workspace.add_buffer(buffer);
+
+        // Select "amp" and paste over it.
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 3 });
+        commands::buffer::paste(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "text\neditor editor");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 6 });
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position { line: 0, offset: 0 }),
+            _ => panic!("Not in select mode"),
+        }
+    }
+
+    #[test]
+    fn paste_over_line_selection_selects_pasted_lines() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        app.clipboard.set_content(ClipboardContent::Block("paste\namp\n".to_string())).unwrap();
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::buffer::paste(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "paste\namp\neditor");
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 1);
+        match app.mode {
+            Mode::SelectLine(ref mode) => assert_eq!(mode.anchor, 0),
+            _ => panic!("Not in select line mode"),
+        }
+    }
+
+    #[test]
+    fn indent_and_outdent_preserve_selections() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(None);
+        let mut buffer = Buffer::new();
+        buffer.insert("  amp\n  editor");
+        buffer.cursor.move_to(Position { line: 0, offset: 3 });
+        app.workspace.add_buffer(buffer);
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 4 });
+
+        super::indent_line(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "    amp\n    editor");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 6 });
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position { line: 0, offset: 5 }),
+            _ => panic!("Not in select mode"),
+        }
+
+        super::outdent_line(&mut app).unwrap();
+        super::outdent_line(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 2 });
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position { line: 0, offset: 1 }),
+            _ => panic!("Not in select mode"),
+        }
+    }
+
+    #[test]
+    fn paste_with_block_content_keeps_lines_intact_when_replacing_part_of_a_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        buffer.cursor.move_to(Position { line: 0, offset: 4 });
+        app.clipboard.set_content(ClipboardContent::Block("text\n".to_string())).unwrap();
+        app.workspace.add_buffer(buffer);
+
+        // Select "edit" and paste over it.
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 8 });
+        commands::buffer::paste(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp \ntext\nor");
+    }
+
+    #[test]
+    fn paste_before_and_after_respect_content_type() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        buffer.cursor.move_to(Position { line: 1, offset: 1 });
+        app.workspace.add_buffer(buffer);
+
+        app.clipboard.set_content(ClipboardContent::Inline("_".to_string())).unwrap();
+        commands::buffer::paste_before(&mut app).unwrap();
+        commands::buffer::paste_after(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ne__ditor");
+
+        app.clipboard.set_content(ClipboardContent::Block("text\n".to_string())).unwrap();
+        commands::buffer::paste_before(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ntext\ne__ditor");
+        commands::buffer::paste_after(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\ntext\ntext\ne__ditor");
+    }
+
+    #[test]
+    fn paste_and_indent_matches_the_current_line_indentation() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("fn main() {\n    let a = ;\n}");
+        buffer.cursor.move_to(Position { line: 1, offset: 12 });
+        app.workspace.add_buffer(buffer);
+
+        app.clipboard.set_content(ClipboardContent::Block("  if b {\n    c();\n\n  }\n".to_string())).unwrap();
+        commands::buffer::paste_and_indent(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "fn main() {\n    let a = ;\n    if b {\n      c();\n\n    }\n}"
+        );
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 12 });
+        app.clipboard.set_content(ClipboardContent::Inline("vec![\n\t\t1,\n\t]".to_string())).unwrap();
+        commands::buffer::paste_and_indent(&mut app).unwrap();
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            "fn main() {\n    let a = vec![\n      1,\n    ];\n    if b {\n      c();\n\n    }\n}"
+        );
+    }
+
     #[test]
     fn paste_above_inserts_clipboard_contents_on_a_new_line_above() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -1654,4 +2609,121 @@ mod tests {
         app.workspace.next_buffer();
         assert_eq!(app.workspace.current_buffer().unwrap().data(), "two");
     }
+
+    #[test]
+    fn reload_reads_the_file_without_writing_it() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = Path::new(env!("OUT_DIR")).join("buffer_reload");
+        fs::write(&path, "amp\n").unwrap();
+        app.workspace.open_buffer(&path).unwrap();
+        app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+        fs::write(&path, "editor\n").unwrap();
+        let modified_at = fs::metadata(&path).unwrap().modified().unwrap();
+
+        commands::buffer::reload(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "editor\n");
+        assert!(!app.view.modified(buffer));
+        assert_eq!(fs::metadata(&path).unwrap().modified().unwrap(), modified_at);
+    }
+
+    #[test]
+    fn copy_as_ansi_copies_the_highlighted_selection() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        app.workspace.add_buffer(buffer);
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::buffer::copy_as_ansi(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Normal));
+        match *app.clipboard.get_content() {
+            ClipboardContent::Inline(ref content) => {
+                assert!(content.contains("editor"));
+                assert!(!content.contains("amp"));
+                assert!(content.ends_with("\x1b[0m"));
+            }
+            _ => panic!("expected inline clipboard content"),
+        }
+    }
+
+    #[test]
+    fn export_html_writes_a_document_alongside_the_buffer() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        buffer.path = Some(env::temp_dir().join("amp_export_test.txt"));
+        app.workspace.add_buffer(buffer);
+        commands::buffer::export_html(&mut app).unwrap();
+
+        let path = env::temp_dir().join("amp_export_test.txt.html");
+        let html = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(html.contains("<title>amp_export_test.txt</title>"));
+        assert!(html.contains(">amp</span>"));
+    }
+
+    #[test]
+    fn insert_char_closes_markup_tags() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(PathBuf::from("index.html"));
+        buffer.insert("<p class=\"a\"");
+        buffer.cursor.move_to(Position { line: 0, offset: 12 });
+        app.workspace.add_buffer(buffer);
+
+        app.view.last_key = Some(Key::Char('>'));
+        commands::buffer::insert_char(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "<p class=\"a\"></p>");
+        assert_eq!(*buffer.cursor, Position { line: 0, offset: 13 });
+    }
+
+    #[test]
+    fn rename_tag_places_cursors_in_both_tags() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("<div>\n  <p>amp</p>\n</div>");
+        buffer.cursor.move_to(Position { line: 1, offset: 6 });
+        app.workspace.add_buffer(buffer);
+
+        commands::buffer::rename_tag(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::Insert));
+        app.view.last_key = Some(Key::Char('b'));
+        commands::buffer::insert_char(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "<div>\n  <b>amp</b>\n</div>");
+    }
+
+    #[test]
+    fn set_local_changes_settings_for_the_current_buffer_until_reset() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        app.workspace.add_buffer(Buffer::new());
+
+        commands::buffer::set_local(&mut app, "tab_width=7").unwrap();
+        commands::buffer::set_local(&mut app, "syntax=rust").unwrap();
+        assert!(commands::buffer::set_local(&mut app, "syntax=klingon").is_err());
+        {
+            let buffer = app.workspace.current_buffer().unwrap();
+            assert_eq!(app.view.tab_width(buffer), 7);
+            assert_eq!(buffer.syntax_definition.as_ref().unwrap().name, "Rust");
+        }
+        commands::buffer::display_local_settings(&mut app).unwrap();
+        assert_eq!(app.notification, Some(String::from("Local settings: syntax=Rust, tab_width=7")));
+
+        // Other buffers are unaffected.
+        app.workspace.previous_buffer();
+        assert_ne!(app.view.tab_width(app.workspace.current_buffer().unwrap()), 7);
+        assert!(commands::buffer::reset_local_settings(&mut app).is_err());
+
+        app.workspace.next_buffer();
+        commands::buffer::reset_local_settings(&mut app).unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(app.view.tab_width(buffer), 2);
+        assert_eq!(buffer.syntax_definition.as_ref().unwrap().name, "Plain Text");
+    }
 }
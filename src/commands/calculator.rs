@@ -0,0 +1,66 @@
+use crate::errors::*;
+use crate::commands::{application, Result};
+use crate::models::application::{Application, Mode};
+use crate::util::expression;
+
+/// Inserts the result of the entered expression at the cursor.
+pub fn accept(app: &mut Application) -> Result {
+    let result = if let Mode::Calculator(ref mode) = app.mode {
+        let result = expression::evaluate(&mode.input)?;
+        mode.input.remember(&mut app.history);
+
+        result
+    } else {
+        bail!("Cannot accept expression outside of calculator mode");
+    };
+
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.insert(result);
+    application::switch_to_normal_mode(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn accept_inserts_the_result_at_the_cursor_and_switches_to_normal_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("offset: ");
+        buffer.cursor.move_to(Position { line: 0, offset: 8 });
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_calculator_mode(&mut app).unwrap();
+        if let Mode::Calculator(ref mut mode) = app.mode {
+            mode.input.set("0x10 * 2");
+        }
+        super::accept(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "offset: 32");
+        if let Mode::Normal = app.mode {
+        } else {
+            panic!("Not in normal mode");
+        }
+    }
+
+    #[test]
+    fn accept_doesnt_change_modes_for_invalid_expressions() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        commands::application::switch_to_calculator_mode(&mut app).unwrap();
+        if let Mode::Calculator(ref mut mode) = app.mode {
+            mode.input.set("2 *");
+        }
+
+        assert!(super::accept(&mut app).is_err());
+        if let Mode::Calculator(_) = app.mode {
+        } else {
+            panic!("Not in calculator mode");
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::util;
+use bloodhound::ExclusionPattern as Pattern;
+
+/// Closes unpinned buffers whose paths (relative to the workspace) match the
+/// glob pattern, asking for confirmation if any of them have unsaved changes.
+pub fn accept_input(app: &mut Application) -> Result {
+    let (pattern, confirmed) = match app.mode {
+        Mode::ClosePattern(ref mode) => {
+            let pattern = Pattern::new(&mode.input).chain_err(|| "Invalid glob pattern")?;
+
+            (pattern, mode.confirm)
+        }
+        _ => bail!("Can't accept pattern outside of close pattern mode."),
+    };
+
+    let ids = matching_buffer_ids(app, &pattern);
+    if ids.is_empty() {
+        bail!("No unpinned buffers match the pattern");
+    }
+    if !confirmed && util::any_modified(&ids, app) {
+        if let Mode::ClosePattern(ref mut mode) = app.mode {
+            mode.confirm = true;
+        }
+
+        return Ok(());
+    }
+
+    util::close_buffers(&ids, app)?;
+    commands::application::switch_to_normal_mode(app)
+}
+
+fn matching_buffer_ids(app: &mut Application, pattern: &Pattern) -> Vec<usize> {
+    let root = app.workspace.path.clone();
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+    let ids: Vec<usize> = util::buffer_ids(app)
+        .into_iter()
+        .filter(|id| {
+            !app.pinned_buffers.contains(id) &&
+                util::select_buffer(*id, app) &&
+                app.workspace.current_buffer_path().map(|path| {
+                    pattern.matches_path(path.strip_prefix(&root).unwrap_or(path))
+                }) == Some(true)
+        })
+        .collect();
+    if let Some(id) = current_id {
+        util::select_buffer(id, app);
+    }
+
+    ids
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+    use std::env;
+    use std::path::Path;
+
+    fn add_buffer(app: &mut Application, path: &str, content: &str) {
+        let mut buffer = Buffer::new();
+        buffer.path = Some(env::current_dir().unwrap().join(path));
+        if !content.is_empty() {
+            buffer.insert(content);
+        }
+        app.workspace.add_buffer(buffer);
+    }
+
+    fn buffer_paths(app: &mut Application) -> Vec<String> {
+        let mut paths = Vec::new();
+        for _ in 0..crate::util::buffer_ids(app).len() {
+            let path = app.workspace.current_buffer_path().unwrap();
+            paths.push(path.to_string_lossy().into_owned());
+            app.workspace.next_buffer();
+        }
+
+        paths.sort();
+
+        paths
+    }
+
+    #[test]
+    fn accept_input_closes_matching_unpinned_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        add_buffer(&mut app, "target/debug/build.rs", "");
+        add_buffer(&mut app, "target/pinned.rs", "");
+        commands::buffer::toggle_pin(&mut app).unwrap();
+        add_buffer(&mut app, "src/main.rs", "");
+
+        commands::application::switch_to_close_pattern_mode(&mut app).unwrap();
+        if let Mode::ClosePattern(ref mut mode) = app.mode {
+            mode.input.set("target/**");
+        }
+        commands::close_pattern::accept_input(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.notification, Some(String::from("Closed 1 buffer")));
+        assert_eq!(app.workspace.current_buffer_path(), Some(Path::new("src/main.rs")));
+        assert_eq!(buffer_paths(&mut app), vec!["src/main.rs", "target/pinned.rs"]);
+    }
+
+    #[test]
+    fn accept_input_requires_confirmation_for_modified_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        add_buffer(&mut app, "target/modified.rs", "amp");
+        add_buffer(&mut app, "target/unmodified.rs", "");
+
+        commands::application::switch_to_close_pattern_mode(&mut app).unwrap();
+        if let Mode::ClosePattern(ref mut mode) = app.mode {
+            mode.input.set("target/*.rs");
+        }
+        commands::close_pattern::accept_input(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::ClosePattern(ref mode) if mode.confirm));
+        assert_eq!(crate::util::buffer_ids(&mut app).len(), 2);
+
+        commands::close_pattern::accept_input(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::Normal));
+        assert!(app.workspace.current_buffer().is_none());
+    }
+}
@@ -0,0 +1,39 @@
+use crate::collab;
+use crate::commands::Result;
+use crate::models::application::Application;
+
+/// Waits for a collaborator to join, on the configured address.
+pub fn host(app: &mut Application) -> Result {
+    let address = app.preferences.borrow().collaboration_address().to_string();
+    app.collaboration.host(&address, app.event_channel.clone())?;
+    app.notification = Some(format!("Waiting for a collaborator on {}", address));
+
+    Ok(())
+}
+
+/// Joins a collaborator's session, on the configured address.
+pub fn join(app: &mut Application) -> Result {
+    let address = app.preferences.borrow().collaboration_address().to_string();
+    app.collaboration.join(&address, app.event_channel.clone())?;
+    app.notification = Some(format!("Collaborating with {}", address));
+
+    Ok(())
+}
+
+pub fn leave(app: &mut Application) -> Result {
+    if !app.collaboration.is_active() {
+        bail!("Not collaborating");
+    }
+    end_session(app);
+    app.notification = Some(String::from("Left collaboration session"));
+
+    Ok(())
+}
+
+/// Disconnects from the collaborator, and stops displaying their cursor.
+pub(crate) fn end_session(app: &mut Application) {
+    app.collaboration.leave();
+    if let Some(id) = app.collaboration.peer_buffer.take() {
+        app.view.clear_annotations(id, collab::ANNOTATION_PROVIDER);
+    }
+}
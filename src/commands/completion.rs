@@ -0,0 +1,169 @@
+use crate::errors::*;
+use crate::errors;
+use crate::commands::{self, Result};
+use crate::models::application::{word_index, Application, Completion};
+use scribe::buffer::{Position, Range};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Opens a popup listing completions for the word before the cursor,
+/// gathered from open buffers and the buffer's language server, if any.
+pub fn open(app: &mut Application) -> Result {
+    app.word_index.update(&mut app.workspace, &mut app.view);
+
+    let (start, prefix) = word_before_cursor(app)?;
+    let candidates = if prefix.is_empty() {
+        Vec::new()
+    } else {
+        app.word_index.completions(&prefix).into_iter().map(String::from).collect()
+    };
+    let mut completion = Completion::new(start, prefix, candidates);
+
+    // The server's candidates are added once it responds.
+    if let Ok((path, line, character)) = commands::lsp::cursor_location(app) {
+        completion.pending = app.language_servers.is_open(&path) &&
+            app.language_servers.completion(&path, line, character).is_ok();
+    }
+
+    if completion.is_empty() && !completion.pending {
+        if completion.prefix().is_empty() {
+            bail!("No word to complete");
+        }
+        bail!("No completions found for \"{}\"", completion.prefix());
+    }
+    app.completion = Some(completion);
+
+    Ok(())
+}
+
+/// Narrows the completions to those matching the word before the cursor,
+/// closing the popup once the cursor has left the word being completed.
+pub fn update(app: &mut Application) -> Result {
+    let (start, prefix) = word_before_cursor(app)?;
+    match app.completion {
+        Some(ref mut completion) if completion.start == start => completion.filter(prefix),
+        _ => app.completion = None,
+    }
+    close_if_empty(app);
+
+    Ok(())
+}
+
+pub fn select_next(app: &mut Application) -> Result {
+    app.completion.as_mut().ok_or("No completions available")?.select_next();
+
+    Ok(())
+}
+
+pub fn select_previous(app: &mut Application) -> Result {
+    app.completion.as_mut().ok_or("No completions available")?.select_previous();
+
+    Ok(())
+}
+
+/// Replaces the word being completed with the selected completion.
+pub fn accept(app: &mut Application) -> Result {
+    let completion = app.completion.take().ok_or("No completions available")?;
+    let candidate = completion.selection().ok_or("No completions available")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+    let end = *buffer.cursor;
+    buffer.delete_range(Range::new(completion.start, end));
+    buffer.cursor.move_to(completion.start);
+    buffer.insert(candidate);
+
+    // Move the cursor to the end of the inserted content.
+    buffer.cursor.move_to(Position {
+        line: completion.start.line,
+        offset: completion.start.offset + candidate.graphemes(true).count(),
+    });
+
+    commands::view::scroll_to_cursor(app)
+}
+
+pub fn close(app: &mut Application) -> Result {
+    app.completion = None;
+
+    Ok(())
+}
+
+/// Closes the popup if nothing matches (and nothing more is on the way).
+pub(crate) fn close_if_empty(app: &mut Application) {
+    if matches!(app.completion, Some(ref c) if c.is_empty() && !c.pending) {
+        app.completion = None;
+    }
+}
+
+// The position and content of the word preceding the cursor.
+fn word_before_cursor(app: &mut Application) -> errors::Result<(Position, String)> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+    let preceding: String = line.graphemes(true).take(buffer.cursor.offset).collect();
+    let prefix = word_index::word_prefix(&preceding);
+    let start = Position {
+        line: buffer.cursor.line,
+        offset: buffer.cursor.offset - prefix.graphemes(true).count(),
+    };
+
+    Ok((start, prefix.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, Mode};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn app_with_content(content: &str, cursor: Position) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        buffer.cursor.move_to(cursor);
+        app.workspace.add_buffer(buffer);
+        app.mode = Mode::Insert;
+
+        app
+    }
+
+    #[test]
+    fn open_lists_buffer_words_and_accept_replaces_the_prefix() {
+        let mut app = app_with_content("editor edition editor\ned", Position { line: 1, offset: 2 });
+        commands::completion::open(&mut app).unwrap();
+        assert_eq!(app.mode_str(), Some("completion"));
+        assert_eq!(
+            app.completion.as_ref().unwrap().matches().collect::<Vec<_>>(),
+            vec!["editor", "edition"]
+        );
+
+        commands::completion::select_next(&mut app).unwrap();
+        commands::completion::accept(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "editor edition editor\nedition");
+        assert_eq!(*buffer.cursor, Position { line: 1, offset: 7 });
+        assert!(app.completion.is_none());
+    }
+
+    #[test]
+    fn update_filters_completions_and_closes_once_the_word_ends() {
+        let mut app = app_with_content("editor edition\ned", Position { line: 1, offset: 2 });
+        commands::completion::open(&mut app).unwrap();
+
+        for &(c, remaining) in &[('i', 2), ('t', 2), ('o', 1), (' ', 0)] {
+            app.view.last_key = Some(Key::Char(c));
+            commands::buffer::insert_char(&mut app).unwrap();
+            commands::completion::update(&mut app).unwrap();
+            assert_eq!(app.completion.as_ref().map_or(0, |c| c.matches().count()), remaining);
+        }
+    }
+
+    #[test]
+    fn open_fails_without_a_word_or_completions() {
+        let mut app = app_with_content("amp ", Position { line: 0, offset: 4 });
+        assert!(commands::completion::open(&mut app).is_err());
+        assert!(app.completion.is_none());
+        assert_eq!(app.mode_str(), Some("insert"));
+    }
+}
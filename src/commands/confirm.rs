@@ -2,13 +2,38 @@ use crate::commands::{self, Result};
 use crate::models::application::{Application, Mode};
 
 pub fn confirm_command(app: &mut Application) -> Result {
-    let command =
+    let (command, trusting_workspace) =
       if let Mode::Confirm(ref mode) = app.mode {
-          mode.command
+          (mode.command, mode.trusting_workspace)
       } else {
           bail!("Can't confirm command outside of confirm mode");
       };
 
+    if trusting_workspace {
+        app.workspace_trust.decide(&app.workspace.path, true)?;
+    }
     command(app)?;
-    commands::application::switch_to_normal_mode(app)
+
+    // Leave any mode the command switched to (e.g. a picker) in place.
+    if let Mode::Confirm(_) = app.mode {
+        commands::application::switch_to_normal_mode(app)?;
+    }
+
+    Ok(())
+}
+
+/// Leaves confirm mode without running the command. If
+/// it was asking to trust the workspace, that's remembered.
+pub fn decline_command(app: &mut Application) -> Result {
+    let trusting_workspace = match app.mode {
+        Mode::Confirm(ref mode) => mode.trusting_workspace,
+        _ => bail!("Can't decline command outside of confirm mode"),
+    };
+
+    commands::application::switch_to_normal_mode(app)?;
+    if trusting_workspace {
+        app.workspace_trust.decide(&app.workspace.path, false)?;
+    }
+
+    Ok(())
 }
@@ -1,27 +1,56 @@
 use crate::errors::*;
 use crate::commands::{self, Result};
+use crate::util::{markup, PositionMap};
 use crate::util::token::{Direction, adjacent_token_position};
-use crate::models::application::Application;
-use scribe::buffer::Position;
+use crate::models::application::{Application, Column, GoalColumn, Mode};
+use scribe::buffer::{Buffer, Position};
 use super::{application, buffer};
+use unicode_segmentation::UnicodeSegmentation;
 
 pub fn move_up(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_up();
+    let line = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.line;
+    if line > 0 {
+        move_to_line(app, line - 1)?;
+    } else {
+        app.view.signal();
+    }
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_down(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_down();
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let line = buffer.cursor.line + 1;
+    if line < buffer.line_count() {
+        move_to_line(app, line)?;
+    } else {
+        app.view.signal();
+    }
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+/// Moves the cursor up a screen line, rather than a buffer line,
+/// when the current line is wrapped (see the line_wrapping preference).
+pub fn move_up_screen_line(app: &mut Application) -> Result {
+    move_to_screen_line(app, Direction::Backward)
+}
+
+/// Moves the cursor down a screen line, rather than a buffer line,
+/// when the current line is wrapped (see the line_wrapping preference).
+pub fn move_down_screen_line(app: &mut Application) -> Result {
+    move_to_screen_line(app, Direction::Forward)
+}
+
 pub fn move_left(app: &mut Application) -> Result {
     app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_left();
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
 pub fn move_right(app: &mut Application) -> Result {
-    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.move_right();
+    let past_end_of_line = cursor_may_pass_end_of_line(app);
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if past_end_of_line || buffer.cursor.offset + 1 < line_length(buffer, buffer.cursor.line) {
+        buffer.cursor.move_right();
+    }
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
@@ -35,11 +64,18 @@ pub fn move_to_start_of_line(app: &mut Application) -> Result {
 }
 
 pub fn move_to_end_of_line(app: &mut Application) -> Result {
-    app.workspace
-        .current_buffer()
-        .ok_or(BUFFER_MISSING)?
-        .cursor
-        .move_to_end_of_line();
+    let past_end_of_line = cursor_may_pass_end_of_line(app);
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    buffer.cursor.move_to_end_of_line();
+    if !past_end_of_line {
+        buffer.cursor.move_left();
+    }
+
+    // Follow line ends when subsequently moving vertically.
+    let goal = GoalColumn { position: *buffer.cursor, column: Column::EndOfLine };
+    app.goal_columns.insert(id, goal);
+
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
@@ -52,6 +88,25 @@ pub fn move_to_first_line(app: &mut Application) -> Result {
     commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
 }
 
+/// Moves from an opening markup tag to its closing tag, or
+/// from anywhere else in an element to its opening tag.
+pub fn move_to_matching_tag(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let positions = PositionMap::new(&data);
+    let offset = positions.offset(&buffer.cursor);
+    let (opening, closing) = markup::matching_tags(&data, offset).ok_or("No tag at the cursor")?;
+
+    let target = if opening.start <= offset && offset < opening.end {
+        closing.start
+    } else {
+        opening.start
+    };
+    buffer.cursor.move_to(positions.position(target));
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
 pub fn move_to_last_line(app: &mut Application) -> Result {
     app.workspace
         .current_buffer()
@@ -96,7 +151,11 @@ pub fn move_to_first_word_of_line(app: &mut Application) -> Result {
 }
 
 pub fn insert_at_end_of_line(app: &mut Application) -> Result {
-    move_to_end_of_line(app)?;
+    app.workspace
+        .current_buffer()
+        .ok_or(BUFFER_MISSING)?
+        .cursor
+        .move_to_end_of_line();
     application::switch_to_insert_mode(app)?;
     commands::view::scroll_to_cursor(app)?;
 
@@ -112,7 +171,11 @@ pub fn insert_at_first_word_of_line(app: &mut Application) -> Result {
 }
 
 pub fn insert_with_newline(app: &mut Application) -> Result {
-    move_to_end_of_line(app)?;
+    app.workspace
+        .current_buffer()
+        .ok_or(BUFFER_MISSING)?
+        .cursor
+        .move_to_end_of_line();
     buffer::start_command_group(app)?;
     buffer::insert_newline(app)?;
     application::switch_to_insert_mode(app)?;
@@ -194,11 +257,98 @@ pub fn append_to_current_token(app: &mut Application) -> Result {
     application::switch_to_insert_mode(app)
 }
 
+// Moves the cursor to the specified line, as close to the buffer's goal
+// column as possible, remembering the goal for subsequent vertical moves.
+fn move_to_line(app: &mut Application, line: usize) -> Result {
+    let past_end_of_line = cursor_may_pass_end_of_line(app);
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    let column = GoalColumn::column_at(app.goal_columns.get(&id), *buffer.cursor);
+
+    let length = line_length(buffer, line);
+    let last_offset = if past_end_of_line { length } else { length.saturating_sub(1) };
+    let offset = match column {
+        Column::Offset(offset) => offset.min(last_offset),
+        Column::EndOfLine => last_offset,
+    };
+    buffer.cursor.move_to(Position { line, offset });
+    app.goal_columns.insert(id, GoalColumn { position: *buffer.cursor, column });
+
+    Ok(())
+}
+
+// Moves the cursor to the adjacent screen line, keeping its screen column.
+// Lines that aren't wrapped each occupy a single screen line.
+fn move_to_screen_line(app: &mut Application, direction: Direction) -> Result {
+    let past_end_of_line = cursor_may_pass_end_of_line(app);
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let (line_wrapping, width) = app.view.line_wrapping(buffer)?;
+    let data = buffer.data();
+    let lines: Vec<&str> = data.lines().collect();
+    let content = |line: usize| lines.get(line).cloned().unwrap_or("");
+
+    let cursor = *buffer.cursor;
+    let (row, column) = line_wrapping.position(content(cursor.line), cursor.offset, width);
+    let (line, row) = match direction {
+        Direction::Forward if row + 1 < line_wrapping.row_count(content(cursor.line), width) => {
+            (cursor.line, row + 1)
+        }
+        Direction::Forward if cursor.line + 1 < buffer.line_count() => (cursor.line + 1, 0),
+        Direction::Backward if row > 0 => (cursor.line, row - 1),
+        Direction::Backward if cursor.line > 0 => {
+            (cursor.line - 1, line_wrapping.row_count(content(cursor.line - 1), width) - 1)
+        }
+        _ => {
+            app.view.signal();
+            return Ok(());
+        }
+    };
+
+    let length = content(line).graphemes(true).count();
+    let last_offset = if past_end_of_line { length } else { length.saturating_sub(1) };
+    let offset = line_wrapping.offset(content(line), row, column, width).min(last_offset);
+    buffer.cursor.move_to(Position { line, offset });
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+// The cursor always needs to be able to reach the end of
+// the line in insert mode, so that content can be appended.
+fn cursor_may_pass_end_of_line(app: &Application) -> bool {
+    match app.mode {
+        Mode::Insert => true,
+        _ => app.preferences.borrow().cursor_past_end_of_line(),
+    }
+}
+
+fn line_length(buffer: &Buffer, line: usize) -> usize {
+    buffer.data()
+        .lines()
+        .nth(line)
+        .map(|content| content.graphemes(true).count())
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::commands;
     use scribe::Buffer;
     use scribe::buffer::Position;
-    use crate::models::application::Application;
+    use crate::models::application::{Application, Preferences};
+    use crate::yaml::yaml::YamlLoader;
+
+    #[test]
+    fn move_to_matching_tag_moves_between_tags() {
+        let mut app = set_up_application("<ul>\n  <li>amp</li>\n</ul>");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 4 });
+
+        super::move_to_matching_tag(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 9 });
+
+        // From the closing tag (or content) back to the opening tag.
+        super::move_to_matching_tag(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 2 });
+    }
 
     #[test]
     fn move_to_first_word_of_line_works() {
@@ -373,6 +523,83 @@ mod tests {
         });
     }
 
+    #[test]
+    fn vertical_movement_returns_to_the_goal_column() {
+        let mut app = set_up_application("amp editor\nab\n\namp editor");
+        *app.preferences.borrow_mut() = Preferences::new(None);
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 6 });
+
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 2 });
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 0 });
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 3, offset: 6 });
+
+        // Moving horizontally sets a new goal.
+        super::move_left(&mut app).unwrap();
+        super::move_up(&mut app).unwrap();
+        super::move_up(&mut app).unwrap();
+        super::move_up(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 5 });
+    }
+
+    #[test]
+    fn vertical_movement_follows_line_ends_after_moving_to_end_of_line() {
+        let mut app = set_up_application("amp\namp editor\nab");
+        *app.preferences.borrow_mut() = Preferences::new(None);
+
+        super::move_to_end_of_line(&mut app).unwrap();
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 10 });
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 2 });
+    }
+
+    #[test]
+    fn cursor_stays_on_last_character_when_configured() {
+        let mut app = set_up_application("amp editor\nab");
+        let data = YamlLoader::load_from_str("cursor_past_end_of_line: false").unwrap();
+        *app.preferences.borrow_mut() = Preferences::new(data.into_iter().nth(0));
+
+        super::move_to_end_of_line(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 9 });
+        super::move_right(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 9 });
+        super::move_down(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 1 });
+
+        // Insert mode still allows appending to the line.
+        super::insert_at_end_of_line(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 2 });
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 1 });
+    }
+
+    #[test]
+    fn screen_line_movement_moves_between_the_rows_of_wrapped_lines() {
+        let mut app = set_up_application("abcdefghijklmnop\nxy");
+        let (line_wrapping, width) = app.view.line_wrapping(app.workspace.current_buffer().unwrap()).unwrap();
+        let rows = line_wrapping.row_count("abcdefghijklmnop", width);
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 1 });
+
+        super::move_down_screen_line(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: width + 1 });
+        super::move_up_screen_line(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 1 });
+
+        // Past the last row, onto the next line, and back.
+        for _ in 0..rows {
+            super::move_down_screen_line(&mut app).unwrap();
+        }
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 1 });
+        super::move_up_screen_line(&mut app).unwrap();
+        assert_eq!(
+            *app.workspace.current_buffer().unwrap().cursor,
+            Position { line: 0, offset: (rows - 1) * width + 1 }
+        );
+    }
+
     fn set_up_application(content: &str) -> Application {
         let mut app = Application::new(&Vec::new()).unwrap();
         let mut buffer = Buffer::new();
@@ -0,0 +1,49 @@
+use crate::errors::*;
+use crate::commands::Result;
+use crate::dap;
+use crate::models::application::Application;
+
+pub fn toggle_breakpoint(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let buffer_id = buffer.id.ok_or("Buffer ID doesn't exist")?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    app.debugger.toggle_breakpoint(&path, buffer.cursor.line)?;
+
+    let lines = app.debugger.breakpoints(&path);
+    app.view.set_annotations(
+        buffer_id,
+        dap::BREAKPOINT_ANNOTATION_PROVIDER,
+        dap::breakpoint_annotations(&lines)
+    );
+
+    Ok(())
+}
+
+pub fn continue_execution(app: &mut Application) -> Result {
+    app.debugger.resume("continue")
+}
+
+pub fn step_over(app: &mut Application) -> Result {
+    app.debugger.resume("next")
+}
+
+pub fn step_into(app: &mut Application) -> Result {
+    app.debugger.resume("stepIn")
+}
+
+pub fn step_out(app: &mut Application) -> Result {
+    app.debugger.resume("stepOut")
+}
+
+pub fn show_variables(app: &mut Application) -> Result {
+    app.debugger.request_variables()
+}
+
+pub fn stop(app: &mut Application) -> Result {
+    app.debugger.stop()?;
+    if let Some(id) = app.debugger.location_buffer.take() {
+        app.view.clear_annotations(id, dap::LOCATION_ANNOTATION_PROVIDER);
+    }
+
+    Ok(())
+}
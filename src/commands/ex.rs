@@ -0,0 +1,189 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::ExCommand;
+use crate::models::application::modes::ex::Substitution;
+use crate::util::{self, replacement};
+use regex::{Captures, Regex};
+use scribe::buffer::Position;
+use std::path::Path;
+
+/// Parses the entered command line and runs the amp commands it maps onto.
+pub fn accept_input(app: &mut Application) -> Result {
+    let command = if let Mode::Ex(ref mode) = app.mode {
+        let command = ExCommand::parse(&mode.input)?;
+        mode.input.remember(&mut app.history);
+
+        command
+    } else {
+        bail!("Can't accept ex input outside of ex mode.");
+    };
+
+    // Some commands switch modes themselves (e.g. saving a
+    // buffer without a path), so we need to leave this one first.
+    commands::application::switch_to_normal_mode(app)?;
+
+    match command {
+        ExCommand::Write(None) => commands::buffer::save(app),
+        ExCommand::Write(Some(path)) => commands::buffer::save_as(app, &path),
+        ExCommand::WriteQuit => {
+            commands::buffer::save(app)?;
+            commands::buffer::close(app)
+        }
+        ExCommand::Quit { force: false } => commands::buffer::close(app),
+        ExCommand::Quit { force: true } => util::close_current_buffer(app),
+        ExCommand::QuitAll => commands::application::exit(app),
+        ExCommand::Edit(None) => commands::buffer::reload(app),
+        ExCommand::Edit(Some(path)) => commands::passphrase::open(app, Path::new(&path)),
+        ExCommand::Alternate => commands::buffer::switch_to_last(app),
+        ExCommand::Line(line) => move_to_line(app, line),
+        ExCommand::Set(argument) => commands::application::set(app, &argument),
+        ExCommand::SetLocal(Some(argument)) => commands::buffer::set_local(app, &argument),
+        ExCommand::SetLocal(None) => commands::buffer::display_local_settings(app),
+        ExCommand::Substitute(substitution) => substitute(app, &substitution),
+    }
+}
+
+// Moves the cursor to the start of a (one-based) line,
+// clamping it to the buffer's first and last lines.
+fn move_to_line(app: &mut Application, line: usize) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let last_line = buffer.data().lines().count().saturating_sub(1);
+    buffer.cursor.move_to(Position { line: line.saturating_sub(1).min(last_line), offset: 0 });
+
+    commands::view::scroll_cursor_to_center(app)
+}
+
+// Replaces matches on the current line (or every line), in a single undoable edit.
+fn substitute(app: &mut Application, substitution: &Substitution) -> Result {
+    let regex = Regex::new(&substitution.pattern).chain_err(|| "Invalid substitution pattern")?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let limit = if substitution.global { 0 } else { 1 };
+    let mut replaced = 0;
+
+    // Lines are matched without their endings, so that "$" anchors work.
+    let content: String = data.split_inclusive('\n').enumerate().map(|(index, line)| {
+        if !substitution.whole_buffer && index != buffer.cursor.line {
+            return line.to_string();
+        }
+        let content = line.trim_end_matches('\n');
+        let ending = &line[content.len()..];
+        let result = regex.replacen(content, limit, |captures: &Captures| {
+            replaced += 1;
+            replacement::expand(&substitution.replacement, captures)
+        });
+
+        format!("{}{}", result, ending)
+    }).collect();
+
+    if replaced == 0 {
+        bail!(format!("Pattern not found: {}", substitution.pattern));
+    }
+    util::reload::replace_content(buffer, &content);
+    app.notification = Some(format!(
+        "Replaced {} occurrence{}",
+        replaced,
+        if replaced == 1 { "" } else { "s" }
+    ));
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::{ChangeSet, Mode};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn run(app: &mut Application, input: &str) -> commands::Result {
+        commands::application::switch_to_ex_mode(app).unwrap();
+        if let Mode::Ex(ref mut mode) = app.mode {
+            mode.input.set(input);
+        }
+
+        super::accept_input(app)
+    }
+
+    fn app_with_buffer(content: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    #[test]
+    fn accept_input_substitutes_matches_across_the_buffer() {
+        let mut app = app_with_buffer("foo foo\nbar\nfoo\n");
+
+        run(&mut app, "%s/f(o+)/b$1/g").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "boo boo\nbar\nboo\n");
+        assert_eq!(app.notification, Some(String::from("Replaced 3 occurrences")));
+        if let Mode::Normal = app.mode {
+        } else {
+            panic!("Not in normal mode");
+        }
+
+        // The substitution is undone as a whole.
+        app.workspace.current_buffer().unwrap().undo();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "foo foo\nbar\nfoo\n");
+    }
+
+    #[test]
+    fn accept_input_substitutes_the_first_match_on_the_current_line() {
+        let mut app = app_with_buffer("foo foo\nfoo foo\n");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 0 });
+
+        run(&mut app, "s/foo$/bar/").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "foo foo\nfoo bar\n");
+        assert!(run(&mut app, "s/baz/bar/").is_err());
+    }
+
+    #[test]
+    fn accept_input_moves_to_line_numbers_and_stays_in_ex_mode_on_errors() {
+        let mut app = app_with_buffer("amp\neditor\nlines\n");
+
+        run(&mut app, "2").unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 0 });
+        run(&mut app, "99").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 2);
+
+        assert!(run(&mut app, "frobnicate").is_err());
+        if let Mode::Ex(_) = app.mode {
+        } else {
+            panic!("Not in ex mode");
+        }
+    }
+
+    #[test]
+    fn accept_input_remembers_commands_in_history() {
+        let mut app = app_with_buffer("amp\n");
+        run(&mut app, "1").unwrap();
+
+        commands::application::switch_to_ex_mode(&mut app).unwrap();
+        commands::prompt::previous_history(&mut app).unwrap();
+        if let Mode::Ex(ref mode) = app.mode {
+            assert_eq!(&*mode.input, "1");
+        } else {
+            panic!("Not in ex mode");
+        }
+    }
+
+    #[test]
+    fn accept_input_force_quits_forgetting_the_buffer_like_closing_it() {
+        let mut app = app_with_buffer("amp\n");
+        let id = app.workspace.current_buffer().unwrap().id.unwrap();
+        app.encrypted_buffers.insert(id, String::from("passphrase"));
+        app.pending_changes.insert(id, ChangeSet::default());
+
+        run(&mut app, "q!").unwrap();
+
+        assert!(app.workspace.current_buffer().is_none());
+        assert!(app.encrypted_buffers.is_empty());
+        assert!(app.pending_changes.is_empty());
+    }
+}
@@ -0,0 +1,268 @@
+use crate::errors;
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, FileTree, Mode};
+use crate::models::application::modes::FileOperation;
+use crate::util;
+use std::fs::{self, OpenOptions};
+use std::path::{Path, PathBuf};
+
+const FILE_TREE_MISSING: &str = "The file tree isn't open";
+
+/// Closes the file tree sidebar, returning to normal mode if it was focused.
+pub fn hide(app: &mut Application) -> Result {
+    app.file_tree = None;
+    app.view.hide_sidebar();
+    if let Mode::FileTree(_) = app.mode {
+        commands::application::switch_to_normal_mode(app)?;
+    }
+
+    Ok(())
+}
+
+pub fn select_next(app: &mut Application) -> Result {
+    tree(app)?.select_next();
+
+    Ok(())
+}
+
+pub fn select_previous(app: &mut Application) -> Result {
+    tree(app)?.select_previous();
+
+    Ok(())
+}
+
+pub fn expand(app: &mut Application) -> Result {
+    tree(app)?.expand()
+}
+
+pub fn collapse(app: &mut Application) -> Result {
+    tree(app)?.collapse();
+
+    Ok(())
+}
+
+/// Expands or collapses the selected directory,
+/// or opens the selected file and leaves the tree.
+pub fn open(app: &mut Application) -> Result {
+    let tree = tree(app)?;
+    let entry = tree.selection().ok_or("No file selected")?.clone();
+    if entry.directory {
+        if entry.expanded {
+            tree.collapse();
+
+            return Ok(());
+        }
+
+        return tree.expand();
+    }
+
+    commands::jump_list::record(app);
+    commands::application::switch_to_normal_mode(app)?;
+    commands::passphrase::open(app, &entry.path)
+}
+
+/// Prompts for the name of a file to create alongside the selected entry
+/// (or within it, if it's a directory). Names ending with a slash
+/// create directories.
+pub fn create(app: &mut Application) -> Result {
+    start_operation(app, FileOperation::Create, String::new())
+}
+
+/// Prompts for a new name for the selected entry.
+pub fn rename(app: &mut Application) -> Result {
+    let name = tree(app)?.selection().ok_or("No file selected")?.name();
+
+    start_operation(app, FileOperation::Rename, name)
+}
+
+/// Asks for confirmation before deleting the selected entry.
+pub fn delete(app: &mut Application) -> Result {
+    tree(app)?.selection().ok_or("No file selected")?;
+
+    start_operation(app, FileOperation::Delete, String::new())
+}
+
+/// Carries out the pending operation, once it's been named or confirmed.
+pub fn accept_operation(app: &mut Application) -> Result {
+    let (operation, input) = match app.mode {
+        Mode::FileTree(ref mode) => (mode.operation, mode.input.to_string()),
+        _ => bail!("Can't accept a file operation outside of file tree mode"),
+    };
+    let tree = app.file_tree.as_mut().ok_or(FILE_TREE_MISSING)?;
+    let selection = tree.selection().cloned();
+
+    match operation {
+        Some(FileOperation::Create) => {
+            let name = input.trim();
+            if name.is_empty() {
+                bail!("Enter a name for the new file");
+            }
+            let directory = match selection {
+                Some(ref entry) if entry.directory => entry.path.clone(),
+                Some(ref entry) => entry.path.parent().map(PathBuf::from).unwrap_or_else(|| tree.root().to_path_buf()),
+                None => tree.root().to_path_buf(),
+            };
+            let path = directory.join(name.trim_end_matches('/'));
+            create_path(&path, name.ends_with('/'))?;
+            tree.refresh()?;
+            tree.reveal(&path)?;
+        }
+        Some(FileOperation::Rename) => {
+            let entry = selection.ok_or("No file selected")?;
+            let name = input.trim();
+            if name.is_empty() {
+                bail!("Enter a new name for {}", entry.name());
+            }
+            let path = entry.path.parent().ok_or("Can't rename the workspace")?.join(name);
+            if path.exists() {
+                bail!("{} already exists", path.display());
+            }
+            fs::rename(&entry.path, &path)
+                .chain_err(|| format!("Couldn't rename {}", entry.path.display()))?;
+            tree.refresh()?;
+            tree.reveal(&path)?;
+            update_buffer_paths(app, &entry.path, &path);
+        }
+        Some(FileOperation::Delete) => {
+            let entry = selection.ok_or("No file selected")?;
+            let result = if entry.directory {
+                fs::remove_dir_all(&entry.path)
+            } else {
+                fs::remove_file(&entry.path)
+            };
+            result.chain_err(|| format!("Couldn't delete {}", entry.path.display()))?;
+            tree.refresh()?;
+        }
+        None => bail!("No file operation in progress"),
+    }
+
+    cancel_operation(app)
+}
+
+/// Abandons the pending operation, returning to browsing the tree.
+pub fn cancel_operation(app: &mut Application) -> Result {
+    match app.mode {
+        Mode::FileTree(ref mut mode) => {
+            mode.operation = None;
+            mode.input.clear();
+        }
+        _ => bail!("Can't cancel a file operation outside of file tree mode"),
+    }
+
+    Ok(())
+}
+
+fn tree(app: &mut Application) -> errors::Result<&mut FileTree> {
+    Ok(app.file_tree.as_mut().ok_or(FILE_TREE_MISSING)?)
+}
+
+fn start_operation(app: &mut Application, operation: FileOperation, input: String) -> Result {
+    match app.mode {
+        Mode::FileTree(ref mut mode) => {
+            mode.operation = Some(operation);
+            mode.input.set(input);
+        }
+        _ => bail!("Can't change files outside of file tree mode"),
+    }
+
+    Ok(())
+}
+
+fn create_path(path: &Path, directory: bool) -> Result {
+    if path.exists() {
+        bail!("{} already exists", path.display());
+    }
+    let parent = if directory { Some(path) } else { path.parent() };
+    if let Some(parent) = parent {
+        fs::create_dir_all(parent)
+            .chain_err(|| format!("Couldn't create the {} directory", parent.display()))?;
+    }
+    if !directory {
+        OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)
+            .chain_err(|| format!("Couldn't create {}", path.display()))?;
+    }
+
+    Ok(())
+}
+
+// Points open buffers for renamed files (including
+// those in renamed directories) at their new paths.
+fn update_buffer_paths(app: &mut Application, from: &Path, to: &Path) {
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+    let workspace_path = app.workspace.path.clone();
+
+    for id in util::buffer_ids(app) {
+        if !util::select_buffer(id, app) {
+            continue;
+        }
+        if let Some(buffer) = app.workspace.current_buffer() {
+            let renamed_path = buffer.path
+                .as_ref()
+                .map(|path| workspace_path.join(path))
+                .and_then(|path| path.strip_prefix(from).map(Path::to_path_buf).ok())
+                .map(|suffix| if suffix.as_os_str().is_empty() { to.to_path_buf() } else { to.join(suffix) });
+            if renamed_path.is_some() {
+                buffer.path = renamed_path;
+            }
+        }
+    }
+    if let Some(id) = current_id {
+        util::select_buffer(id, app);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::{FileTree, Mode};
+    use crate::models::application::modes::FileTreeMode;
+    use std::env;
+    use std::fs;
+
+    fn enter(app: &mut Application, input: &str) {
+        if let Mode::FileTree(ref mut mode) = app.mode {
+            mode.input.set(input);
+        }
+        commands::file_tree::accept_operation(app).unwrap();
+    }
+
+    fn selected_name(app: &Application) -> Option<String> {
+        app.file_tree.as_ref().and_then(|tree| tree.selection()).map(|entry| entry.name())
+    }
+
+    #[test]
+    fn files_can_be_created_renamed_and_deleted() {
+        let root = env::temp_dir().join("amp_file_tree_commands");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.file_tree = Some(FileTree::new(root.clone(), None).unwrap());
+        app.mode = Mode::FileTree(FileTreeMode::new());
+
+        commands::file_tree::create(&mut app).unwrap();
+        enter(&mut app, "src/main.rs");
+        assert!(root.join("src/main.rs").is_file());
+        assert_eq!(selected_name(&app), Some(String::from("main.rs")));
+
+        commands::file_tree::rename(&mut app).unwrap();
+        enter(&mut app, "lib.rs");
+        assert!(!root.join("src/main.rs").exists());
+        assert_eq!(selected_name(&app), Some(String::from("lib.rs")));
+
+        // Deleting requires confirmation, which can be declined.
+        commands::file_tree::delete(&mut app).unwrap();
+        commands::file_tree::cancel_operation(&mut app).unwrap();
+        assert!(root.join("src/lib.rs").exists());
+        commands::file_tree::delete(&mut app).unwrap();
+        commands::file_tree::accept_operation(&mut app).unwrap();
+        assert!(!root.join("src/lib.rs").exists());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
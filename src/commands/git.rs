@@ -1,118 +1,383 @@
 use crate::errors::*;
 use crate::errors;
 use crate::commands::{self, Result};
-use crate::models::application::{Application, ClipboardContent, Mode};
-use git2;
-use regex::Regex;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::{BlameMode, ConfirmMode};
+use crate::util::{self, git_gutter::{GitGutter, Hunk}};
+use crate::util::git_blame::{self, BlameLine};
+use scribe::Buffer;
+use scribe::buffer::Position;
 
+/// Stages the current buffer's file. This happens in the background, as
+/// writing the index can take a while in large repositories; the status
+/// line notes that it's in progress.
 pub fn add(app: &mut Application) -> Result {
-    let repo = app.repository.as_ref().ok_or("No repository available")?;
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let mut index = repo.index().chain_err(|| "Couldn't get the repository index")?;
-    let buffer_path = buffer.path.as_ref().ok_or(BUFFER_PATH_MISSING)?;
-    let repo_path = repo.workdir().ok_or("No path found for the repository")?;
-    let relative_path = buffer_path.strip_prefix(repo_path).chain_err(|| {
-        "Failed to build a relative buffer path"
-    })?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
 
-    index.add_path(relative_path).chain_err(|| "Failed to add path to index.")?;
-    index.write().chain_err(|| "Failed to write index.")
+    app.git_worker.add(path)
 }
 
+/// Copies a GitHub URL for the current buffer's file (and its selected lines,
+/// if any) to the clipboard, once it's been built in the background.
 pub fn copy_remote_url(app: &mut Application) -> Result {
-    if let Some(ref mut repo) = app.repository {
-        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-        let buffer_path = buffer.path.as_ref().ok_or(BUFFER_PATH_MISSING)?;
-        let remote = repo.find_remote("origin").chain_err(|| {
-            "Couldn't find a remote \"origin\""
-        })?;
-        let url = remote.url().ok_or("No URL for remote/origin")?;
-
-        let gh_path = get_gh_path(url)?;
-
-        let repo_path = repo.workdir().ok_or("No path found for the repository")?;
-        let relative_path = buffer_path.strip_prefix(repo_path).chain_err(|| {
-            "Failed to build a relative buffer path"
-        })?;
-
-        let status = repo.status_file(relative_path).chain_err(|| {
-            "Couldn't get status info for the specified path"
-        })?;
-        if status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW) {
-            bail!("The provided path doesn't exist in the repository");
-        }
-
-        // We want to build URLs that point to an object ID, so that they'll
-        // refer to a snapshot of the file as it looks at this very moment.
-        let mut revisions = repo.revwalk().chain_err(|| {
-            "Couldn't build a list of revisions for the repository"
-        })?;
-
-        // We need to set a starting point for the commit graph we'll
-        // traverse. We want the most recent commit, so start at HEAD.
-        revisions.push_head().chain_err(|| "Failed to push HEAD to commit graph.")?;
-
-        // Pull the first revision (HEAD).
-        let last_oid = revisions.next().and_then(|revision| revision.ok()).ok_or(
-            "Couldn't find a git object ID for this file"
-        )?;
-
-        let line_range = match app.mode {
-            Mode::SelectLine(ref s) => {
-                // Avoid zero-based line numbers.
-                let line_1 = buffer.cursor.line + 1;
-                let line_2 = s.anchor + 1;
-
-                if line_1 < line_2 {
-                    format!("#L{}-L{}", line_1, line_2)
-                } else if line_2 < line_1 {
-                    format!("#L{}-L{}", line_2, line_1)
-                } else {
-                    format!("#L{}", line_1)
-                }
-            },
-            _ => String::new(),
-        };
-
-        let gh_url = format!(
-            "https://github.com/{}/blob/{:?}/{}{}",
-            gh_path,
-            last_oid,
-            relative_path.to_string_lossy(),
-            line_range
-        );
-
-        app.clipboard.set_content(
-            ClipboardContent::Inline(gh_url)
-        )?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let lines = match app.mode {
+        Mode::SelectLine(ref s) => {
+            // Avoid zero-based line numbers.
+            let line_1 = buffer.cursor.line + 1;
+            let line_2 = s.anchor + 1;
+
+            Some((line_1.min(line_2), line_1.max(line_2)))
+        },
+        _ => None,
+    };
+    app.git_worker.remote_url(path, lines)?;
+
+    commands::application::switch_to_normal_mode(app)
+}
+
+/// Stashes the working tree's changes, reloading buffers
+/// without unsaved changes once it's been done.
+pub fn stash(app: &mut Application) -> Result {
+    app.git_worker.stash()
+}
+
+/// Stages the block of changed lines under the cursor,
+/// leaving the rest of the file's changes unstaged.
+pub fn stage_hunk(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let hunk = current_hunk(&app.git_gutter, buffer)?;
+    let lines = hunk_lines(&hunk, &buffer.data())?.concat();
+
+    app.git_worker.stage_hunk(path, hunk, lines)
+}
+
+/// Restores the block of changed lines under the
+/// cursor to its staged content, after confirmation.
+pub fn revert_hunk(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    current_hunk(&app.git_gutter, buffer)?;
+    app.mode = Mode::Confirm(ConfirmMode::new(discard_hunk));
+
+    Ok(())
+}
+
+// Requests the staged lines that the hunk under the cursor replaces,
+// which it's restored to once they've been read (see `restore_hunk`).
+fn discard_hunk(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let hunk = current_hunk(&app.git_gutter, buffer)?;
+    hunk_lines(&hunk, &buffer.data())?;
+
+    app.git_worker.staged_hunk(id, path, hunk)
+}
+
+/// Replaces the buffer's hunk with its staged lines, leaving the buffer
+/// modified; its file is only reverted once the buffer's saved. The hunk
+/// is checked again, in case the buffer's changed since it was requested.
+pub(crate) fn restore_hunk(app: &mut Application, buffer_id: usize, hunk: &Hunk, staged_lines: &str) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if buffer.id != Some(buffer_id) || !app.git_gutter.hunks(buffer_id).contains(hunk) {
+        bail!("The changes have moved since they were reverted; try again");
+    }
+    let data = buffer.data();
+    let lines = hunk_lines(hunk, &data)?;
+
+    buffer.start_operation_group();
+    util::replace_lines(buffer, hunk.start, &lines, staged_lines);
+    buffer.end_operation_group();
+    buffer.cursor.move_to(Position { line: hunk.first_line(), offset: 0 });
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Moves the cursor to the next block of lines that differ from the index.
+pub fn next_hunk(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    let line = app.git_gutter
+        .hunks(id)
+        .iter()
+        .find(|hunk| hunk.first_line() > buffer.cursor.line)
+        .ok_or("No more changes below the cursor")?
+        .first_line();
+    buffer.cursor.move_to(Position { line, offset: 0 });
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Moves the cursor to the previous block of lines that differ from the index.
+pub fn previous_hunk(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    let line = app.git_gutter
+        .hunks(id)
+        .iter()
+        .rev()
+        .find(|hunk| hunk.last_line() < buffer.cursor.line)
+        .ok_or("No more changes above the cursor")?
+        .first_line();
+    buffer.cursor.move_to(Position { line, offset: 0 });
+
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Shows the commit that last changed the cursor's line in the status
+/// line, once the buffer's been blamed in the background.
+pub fn blame_line(app: &mut Application) -> Result {
+    let line = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.line;
+
+    request_blame(app, Some(line))
+}
+
+/// Opens the commit that last changed the cursor's line in a new buffer,
+/// including its message and changes, leaving blame mode.
+pub fn show_blamed_commit(app: &mut Application) -> Result {
+    let commit = if let Mode::Blame(ref mode) = app.mode {
+        let line = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.cursor.line;
+        mode.line(line).ok_or("The line hasn't been committed yet")?.commit
     } else {
-        bail!("No repository available");
+        bail!("Can't show a blamed commit outside of blame mode");
+    };
+    let date_format = app.preferences.borrow().date_format().to_string();
+    app.git_worker.commit_details(commit, date_format)?;
+
+    close_blame(app)
+}
+
+pub fn close_blame(app: &mut Application) -> Result {
+    if let Mode::Blame(ref mode) = app.mode {
+        app.view.clear_annotations(mode.buffer_id, git_blame::ANNOTATION_PROVIDER);
     }
 
-    commands::application::switch_to_normal_mode(app)?;
+    commands::application::switch_to_normal_mode(app)
+}
+
+/// Requests the commit that last changed each of the current buffer's lines,
+/// to describe the specified line, or to show them all in blame mode.
+pub(crate) fn request_blame(app: &mut Application, line: Option<usize>) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+
+    app.git_worker.blame(id, path, buffer.data(), line)
+}
+
+/// Shows the commit behind each of the buffer's lines in blame mode, unless
+/// another buffer or mode has been switched to since they were requested.
+pub(crate) fn show_blame(app: &mut Application, buffer_id: usize, lines: Vec<Option<BlameLine>>) -> Result {
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+    if current_id != Some(buffer_id) || !matches!(app.mode, Mode::Normal) {
+        return Ok(());
+    }
+
+    let annotations = git_blame::annotations(&lines, app.preferences.borrow().date_format())?;
+    app.view.set_annotations(buffer_id, git_blame::ANNOTATION_PROVIDER, annotations);
+    app.mode = Mode::Blame(BlameMode::new(buffer_id, lines));
+
+    Ok(())
+}
+
+/// Describes the commit that last changed the line in the status line.
+pub(crate) fn show_blamed_line(app: &mut Application, lines: &[Option<BlameLine>], line: usize) -> Result {
+    let date_format = app.preferences.borrow().date_format().to_string();
+
+    app.notification = Some(match lines.get(line).and_then(Option::as_ref) {
+        Some(blamed) => format!("{} {}", blamed.describe(&date_format)?, blamed.summary),
+        None => String::from("Not committed yet"),
+    });
 
     Ok(())
 }
 
-fn get_gh_path(url: &str) -> errors::Result<&str> {
-    lazy_static! {
-        static ref REGEX: Regex =
-            Regex::new(r"^(?:https://|git@)github.com(?::|/)(.*?)(?:.git)?$").unwrap();
+/// Opens a commit's details in a new buffer.
+pub(crate) fn show_commit(app: &mut Application, content: String) -> Result {
+    let mut buffer = Buffer::new();
+    buffer.insert(content);
+
+    util::add_buffer(buffer, app)
+}
+
+// The changed lines under the buffer's cursor.
+fn current_hunk(git_gutter: &GitGutter, buffer: &Buffer) -> errors::Result<Hunk> {
+    let id = buffer.id.ok_or(BUFFER_MISSING)?;
+
+    git_gutter
+        .hunks(id)
+        .iter()
+        .find(|hunk| hunk.contains(buffer.cursor.line))
+        .cloned()
+        .ok_or_else(|| "No changes found at the cursor".into())
+}
+
+// The hunk's lines in the buffer's content. Hunks are diffed in the
+// background, so they may not reflect the latest edits yet.
+fn hunk_lines<'a>(hunk: &Hunk, data: &'a str) -> errors::Result<Vec<&'a str>> {
+    let lines: Vec<&str> = data.split_inclusive('\n').collect();
+
+    match lines.get(hunk.start..hunk.start + hunk.lines) {
+        Some(lines) => Ok(lines.to_vec()),
+        None => bail!("The changes at the cursor have moved; try again once they're updated"),
     }
-    REGEX.captures(url).and_then(|c| c.at(1)).chain_err(|| {
-        "Failed to capture remote repo path"
-    })
+}
+
+// Opens a buffer for a file staged in a new repository, tracking its changes.
+#[cfg(test)]
+fn set_up_repository(name: &str, content: &str) -> Application {
+    use crate::models::application::GitWorker;
+    use git2::Repository;
+    use std::fs;
+    use std::path::Path;
+
+    let root = Path::new(env!("OUT_DIR")).join(name);
+    let _ = fs::remove_dir_all(&root);
+    fs::create_dir_all(&root).unwrap();
+    let repo = Repository::init(&root).unwrap();
+    let path = repo.workdir().unwrap().join("file");
+    fs::write(&path, content).unwrap();
+    let mut index = repo.index().unwrap();
+    index.add_path(Path::new("file")).unwrap();
+    index.write().unwrap();
+
+    let mut app = Application::new(&Vec::new()).unwrap();
+    app.workspace.add_buffer(Buffer::from_file(&path).unwrap());
+    app.git_worker = GitWorker::new(Some(&repo), app.event_channel.clone());
+
+    app
+}
+
+#[cfg(test)]
+fn edit_and_update_hunks(app: &mut Application, edit: fn(&mut Buffer)) {
+    let buffer = app.workspace.current_buffer().unwrap();
+    edit(buffer);
+    let (id, path) = (buffer.id.unwrap(), buffer.path.clone().unwrap());
+    let repo = git2::Repository::discover(&path).unwrap();
+    app.git_gutter.update(&repo, id, &path, &buffer.data()).unwrap();
 }
 
 #[test]
-fn test_get_gh_path() {
-    let cases = [
-        ("git@github.com:jmacdonald/amp.git", "jmacdonald/amp"),
-        ("https://github.com/jmacdonald/amp.git", "jmacdonald/amp"),
-        ("https://github.com/jmacdonald/amp", "jmacdonald/amp"),
-    ];
-
-    cases.iter().for_each(|(url, expected_gh_path)| {
-        assert_eq!(&get_gh_path(url).unwrap(), expected_gh_path)
-    })
+fn next_and_previous_hunk_move_between_changes() {
+    let mut app = set_up_repository("git_hunks", "amp\neditor\nrust\ntext\nbuffer\n");
+    edit_and_update_hunks(&mut app, |buffer| {
+        buffer.cursor.move_to(Position { line: 1, offset: 0 });
+        buffer.insert("changed ");
+        buffer.cursor.move_to(Position { line: 4, offset: 0 });
+        buffer.insert("new\n");
+        buffer.cursor.move_to(Position { line: 0, offset: 0 });
+    });
+
+    next_hunk(&mut app).unwrap();
+    assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 1);
+    next_hunk(&mut app).unwrap();
+    assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 4);
+    assert!(next_hunk(&mut app).is_err());
+    previous_hunk(&mut app).unwrap();
+    assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 1);
+}
+
+#[test]
+fn stage_hunk_stages_only_the_hunk_under_the_cursor() {
+    let mut app = set_up_repository("git_stage_hunk", "amp\neditor\nrust\ntext\n");
+    edit_and_update_hunks(&mut app, |buffer| {
+        buffer.cursor.move_to(Position { line: 3, offset: 0 });
+        buffer.insert("plain ");
+        buffer.delete_range(scribe::buffer::Range::new(
+            Position { line: 0, offset: 0 },
+            Position { line: 1, offset: 0 }
+        ));
+        buffer.cursor.move_to(Position { line: 2, offset: 0 });
+    });
+
+    stage_hunk(&mut app).unwrap();
+    app.complete_git_operations().unwrap();
+    let buffer = app.workspace.current_buffer().unwrap();
+    let (id, path) = (buffer.id.unwrap(), buffer.path.clone().unwrap());
+    let repo = git2::Repository::discover(&path).unwrap();
+    let entry = repo.index().unwrap().get_path(std::path::Path::new("file"), 0).unwrap();
+    assert_eq!(repo.find_blob(entry.id).unwrap().content(), b"amp\neditor\nrust\nplain text\n");
+
+    assert!(app.git_gutter.update(&repo, id, &path, &buffer.data()).unwrap());
+    assert_eq!(app.git_gutter.hunks(id).len(), 1);
+}
+
+#[test]
+fn revert_hunk_restores_staged_lines_after_confirmation() {
+    let mut app = set_up_repository("git_revert_hunk", "amp\neditor\nrust\n");
+    edit_and_update_hunks(&mut app, |buffer| {
+        buffer.cursor.move_to(Position { line: 1, offset: 0 });
+        buffer.insert("text\nchanged ");
+    });
+
+    revert_hunk(&mut app).unwrap();
+    assert!(matches!(app.mode, Mode::Confirm(_)));
+    commands::confirm::confirm_command(&mut app).unwrap();
+    app.complete_git_operations().unwrap();
+
+    let buffer = app.workspace.current_buffer().unwrap();
+    assert_eq!(buffer.data(), "amp\neditor\nrust\n");
+    assert!(buffer.modified());
+    buffer.undo();
+    assert_eq!(buffer.data(), "amp\ntext\nchanged editor\nrust\n");
+}
+
+#[test]
+fn hunk_commands_refuse_hunks_that_are_out_of_date() {
+    let mut app = set_up_repository("git_stale_hunk", "amp\neditor\nrust\n");
+    edit_and_update_hunks(&mut app, |buffer| {
+        buffer.cursor.move_to(Position { line: 2, offset: 0 });
+        buffer.insert("text\nchanged ");
+    });
+
+    // Remove the changed lines without diffing the buffer again.
+    let buffer = app.workspace.current_buffer().unwrap();
+    buffer.delete_range(scribe::buffer::Range::new(
+        Position { line: 1, offset: 0 },
+        Position { line: 3, offset: 0 }
+    ));
+    buffer.cursor.move_to(Position { line: 2, offset: 0 });
+
+    assert!(stage_hunk(&mut app).is_err());
+    assert!(discard_hunk(&mut app).is_err());
+    assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\nchanged rust\n");
+}
+
+#[test]
+fn blame_mode_shows_and_opens_the_commit_behind_each_line() {
+    let mut app = set_up_repository("git_blame", "amp\neditor\n");
+    let commit = {
+        let path = app.workspace.current_buffer().unwrap().path.clone().unwrap();
+        let repo = git2::Repository::discover(&path).unwrap();
+        let tree = repo.find_tree(repo.index().unwrap().write_tree().unwrap()).unwrap();
+        let signature = git2::Signature::new("Jordan", "jordan@example.com", &git2::Time::new(43_200, 0)).unwrap();
+        repo.commit(Some("HEAD"), &signature, &signature, "Add file", &tree, &[]).unwrap()
+    };
+    let buffer = app.workspace.current_buffer().unwrap();
+    buffer.insert("text\n");
+    buffer.cursor.move_to(Position { line: 2, offset: 0 });
+
+    commands::application::switch_to_blame_mode(&mut app).unwrap();
+    app.complete_git_operations().unwrap();
+    if let Mode::Blame(ref mode) = app.mode {
+        assert_eq!(mode.line(0), None);
+        assert_eq!(mode.line(2).map(|line| line.commit), Some(commit));
+        assert_eq!(mode.line(2).map(|line| line.author.as_str()), Some("Jordan"));
+    } else {
+        panic!("Not in blame mode");
+    }
+
+    blame_line(&mut app).unwrap();
+    app.complete_git_operations().unwrap();
+    assert!(app.notification.as_ref().unwrap().ends_with("Jordan 1970-01-01 Add file"));
+
+    show_blamed_commit(&mut app).unwrap();
+    app.complete_git_operations().unwrap();
+    assert_eq!(app.mode_str(), Some("normal"));
+    let content = app.workspace.current_buffer().unwrap().data();
+    assert!(content.starts_with(&format!("commit {}\nAuthor: Jordan <jordan@example.com>", commit)));
+    assert!(content.contains("+editor\n"));
 }
@@ -0,0 +1,85 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::Application;
+use crate::models::application::jump_list::Jump;
+use crate::util;
+use scribe::buffer::Position;
+
+/// Returns to the location the cursor last jumped from.
+pub fn back(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let jump = app.jump_list.back(buffer).ok_or("No earlier jumps")?;
+
+    restore(app, &jump)
+}
+
+/// Returns to the location last stepped back from.
+pub fn forward(app: &mut Application) -> Result {
+    let jump = app.jump_list.forward().ok_or("No later jumps")?;
+
+    restore(app, &jump)
+}
+
+/// Records the cursor's location before a jump (e.g. to a search result).
+pub(crate) fn record(app: &mut Application) {
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.jump_list.record(buffer);
+    }
+}
+
+/// Moves the cursor to the jump's location, reopening its buffer if need be.
+pub(crate) fn restore(app: &mut Application, jump: &Jump) -> Result {
+    if !util::select_buffer(jump.buffer_id, app) {
+        let path = jump.path.as_ref().ok_or("The jump's buffer has since been closed")?;
+        commands::passphrase::open(app, path)?;
+    }
+
+    // The buffer may have changed since the jump was made.
+    if let Some(buffer) = app.workspace.current_buffer() {
+        if !buffer.cursor.move_to(jump.position) &&
+            !buffer.cursor.move_to(Position { line: jump.position.line, offset: 0 }) {
+            buffer.cursor.move_to_last_line();
+        }
+    }
+
+    commands::view::scroll_cursor_to_center(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn back_and_forward_restore_the_buffer_and_position_jumped_from() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\namp\n");
+        app.workspace.add_buffer(buffer);
+        let first_id = app.workspace.current_buffer().unwrap().id;
+
+        // Jump to the second "amp" (the first after the cursor) by searching for it.
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input.set("amp");
+        }
+        commands::search::accept_query(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 0 });
+
+        // Stepping back from another buffer returns to the first,
+        // and stepping forward again returns to where it started.
+        app.workspace.add_buffer(Buffer::new());
+        let second_id = app.workspace.current_buffer().unwrap().id;
+        assert!(commands::jump_list::forward(&mut app).is_err());
+        commands::jump_list::back(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, first_id);
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 0 });
+
+        commands::jump_list::forward(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, second_id);
+        assert!(commands::jump_list::forward(&mut app).is_err());
+    }
+}
@@ -2,47 +2,22 @@ use crate::errors::*;
 use crate::input::Key;
 use crate::commands::{self, Result};
 use crate::models::application::{Application, Mode};
-use scribe::buffer::Position;
+use crate::models::application::modes::LineJumpTarget;
 
+/// Moves the cursor to the entered line (and column), offset, or percentage,
+/// briefly highlighting the line it lands on.
 pub fn accept_input(app: &mut Application) -> Result {
-    if let Mode::LineJump(ref mode) = app.mode {
-        // Try parsing an integer from the input.
-        let line_number = mode
-            .input
-            .parse::<usize>()
-            .chain_err(|| "Couldn't parse a line number from the provided input.")?;
-
-        // Ignore zero-value line numbers.
-        if line_number > 0 {
-            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-
-            // Input values won't be zero-indexed; map the value so
-            // that we can use it for a zero-indexed buffer position.
-            let target_line = line_number - 1;
-
-            // Build an ideal target position to which we'll try moving.
-            let mut target_position = Position {
-                line: target_line,
-                offset: buffer.cursor.offset,
-            };
-
-            if !buffer.cursor.move_to(target_position) {
-                // Moving to that position failed. It may be because the
-                // current offset doesn't exist there. Try falling back
-                // to the end of the target line.
-                let line_content = buffer
-                    .data()
-                    .lines()
-                    .nth(target_line)
-                    .map(|line| line.to_string())
-                    .ok_or("Couldn't find the specified line")?;
-
-                target_position.offset = line_content.len();
-                buffer.cursor.move_to(target_position);
-            }
-        }
+    let target = if let Mode::LineJump(ref mode) = app.mode {
+        LineJumpTarget::parse(&mode.input)?
     } else {
         bail!("Can't accept line jump input outside of line jump mode.");
+    };
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if let Some(position) = target.position(*buffer.cursor, &buffer.data()) {
+        app.jump_list.record(buffer);
+        buffer.cursor.move_to(position);
+        app.view.highlight_line(buffer, position.line);
     }
 
     commands::application::switch_to_normal_mode(app)?;
@@ -152,6 +127,29 @@ mod tests {
         });
     }
 
+    #[test]
+    fn accept_input_moves_cursor_to_relative_targets_and_highlights_the_line() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\namp\neditor");
+        app.workspace.add_buffer(buffer);
+
+        for (input, position) in &[("2:4", Position { line: 1, offset: 3 }),
+                                   ("+2", Position { line: 3, offset: 3 }),
+                                   ("-1", Position { line: 2, offset: 3 }),
+                                   ("25%", Position { line: 0, offset: 3 })] {
+            commands::application::switch_to_line_jump_mode(&mut app).unwrap();
+            if let Mode::LineJump(ref mut mode) = app.mode {
+                mode.input = input.to_string();
+            }
+            commands::line_jump::accept_input(&mut app).unwrap();
+
+            let buffer = app.workspace.current_buffer().unwrap();
+            assert_eq!(*buffer.cursor, *position);
+            assert_eq!(app.view.line_highlight(buffer).map(|range| range.start().line), Some(position.line));
+        }
+    }
+
     #[test]
     fn accept_input_ignores_zero_input() {
         let mut app = Application::new(&Vec::new()).unwrap();
@@ -0,0 +1,90 @@
+use crate::errors::*;
+use crate::errors;
+use crate::commands::{self, Result};
+use crate::lsp::document;
+use crate::models::application::{Application, Mode};
+use scribe::buffer::Position;
+use std::path::{Path, PathBuf};
+
+pub fn goto_definition(app: &mut Application) -> Result {
+    let (path, line, character) = cursor_location(app)?;
+
+    app.language_servers.goto_definition(&path, line, character)
+}
+
+pub fn hover(app: &mut Application) -> Result {
+    let (path, line, character) = cursor_location(app)?;
+
+    app.language_servers.hover(&path, line, character)
+}
+
+/// Prompts for a new name for the symbol under the cursor,
+/// which the buffer's language server renames once accepted.
+pub fn rename(app: &mut Application) -> Result {
+    let (path, _, _) = cursor_location(app)?;
+    if !app.language_servers.is_open(&path) {
+        bail!("No language server is running for this buffer");
+    }
+
+    commands::application::switch_to_rename_mode(app)?;
+    if let Mode::Rename(ref mut mode) = app.mode {
+        mode.language_server = true;
+    }
+
+    Ok(())
+}
+
+/// The current buffer's path and cursor position, with its
+/// offset converted into the UTF-16 units servers expect.
+pub(crate) fn cursor_location(app: &mut Application) -> errors::Result<(PathBuf, usize, usize)> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let line = buffer.data().lines().nth(buffer.cursor.line).map(String::from).unwrap_or_default();
+
+    Ok((path, buffer.cursor.line, document::character(&line, buffer.cursor.offset)))
+}
+
+/// Opens the path, moving the cursor to a (zero-based) line and
+/// UTF-16 offset within it, as reported by a language server.
+pub(crate) fn open_location(app: &mut Application, path: &Path, line: usize, character: usize) -> Result {
+    commands::jump_list::record(app);
+    app.workspace.open_buffer(path).chain_err(|| {
+        format!("Couldn't open {}", path.to_string_lossy())
+    })?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.initialize_buffer(buffer)?;
+
+    let offset = buffer.data().lines().nth(line).map(|data| document::offset(data, character)).unwrap_or(0);
+    buffer.cursor.move_to(Position { line, offset });
+
+    commands::view::scroll_cursor_to_center(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use scribe::buffer::Position;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn open_location_converts_utf16_offsets() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = Path::new(env!("OUT_DIR")).join("lsp_open_location.rs");
+        fs::write(&path, "fn main() {}\nlet s = \"😀\"; s\n").unwrap();
+
+        commands::lsp::open_location(&mut app, &path, 1, 14).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 13 });
+    }
+
+    #[test]
+    fn rename_requires_a_language_server() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = Path::new(env!("OUT_DIR")).join("lsp_rename.rs");
+        fs::write(&path, "fn main() {}\n").unwrap();
+        app.workspace.open_buffer(&path).unwrap();
+
+        assert!(commands::lsp::rename(&mut app).is_err());
+    }
+}
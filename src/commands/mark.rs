@@ -0,0 +1,94 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::input::Key;
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::MarkAction;
+
+/// Sets (or jumps to) the mark named by the last key pressed,
+/// depending on how mark mode was entered, and leaves mark mode.
+pub fn select(app: &mut Application) -> Result {
+    let name = match *app.view.last_key() {
+        Some(Key::Char(c)) if c.is_ascii_lowercase() => c,
+        _ => bail!("Marks are named a-z"),
+    };
+    let action = match app.mode {
+        Mode::Mark(ref mode) => mode.action,
+        _ => bail!("Can't select a mark outside of mark mode"),
+    };
+    app.mode = Mode::Normal;
+
+    match action {
+        MarkAction::Set => {
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            app.marks.set(name, buffer, &mut app.view);
+
+            Ok(())
+        }
+        MarkAction::Jump => jump_to(app, name),
+    }
+}
+
+/// Leaves mark mode without setting or jumping to a mark.
+pub fn cancel(app: &mut Application) -> Result {
+    if !matches!(app.mode, Mode::Mark(_)) {
+        bail!("Can't cancel mark selection outside of mark mode");
+    }
+    app.mode = Mode::Normal;
+
+    Ok(())
+}
+
+/// Moves the cursor to the named mark, recording the jump.
+pub(crate) fn jump_to(app: &mut Application, name: char) -> Result {
+    let location = app.marks
+        .get(name)
+        .map(|mark| mark.location.clone())
+        .ok_or_else(|| format!("Mark {} isn't set", name))?;
+    commands::jump_list::record(app);
+
+    commands::jump_list::restore(app, &location)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn press(app: &mut Application, key: Key) {
+        app.view.last_key = Some(key);
+        commands::application::handle_input(app).unwrap();
+    }
+
+    #[test]
+    fn marks_follow_edits_and_can_be_jumped_to_from_other_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        app.workspace.add_buffer(buffer);
+        app.view.initialize_buffer(app.workspace.current_buffer().unwrap()).unwrap();
+        let id = app.workspace.current_buffer().unwrap().id;
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 2 });
+        press(&mut app, Key::Char('~'));
+        press(&mut app, Key::Char('a'));
+        assert!(matches!(app.mode, Mode::Normal));
+
+        // Add a line above the mark, and then switch to another buffer.
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 0 });
+        press(&mut app, Key::Char('O'));
+        press(&mut app, Key::Escape);
+        app.workspace.add_buffer(Buffer::new());
+
+        press(&mut app, Key::Char('`'));
+        press(&mut app, Key::Char('a'));
+        assert_eq!(app.workspace.current_buffer().unwrap().id, id);
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 2 });
+
+        press(&mut app, Key::Char('`'));
+        app.view.last_key = Some(Key::Char('b'));
+        assert!(commands::application::handle_input(&mut app).is_err());
+    }
+}
@@ -4,16 +4,36 @@ use std::collections::HashMap;
 
 pub mod application;
 pub mod buffer;
+pub mod calculator;
+pub mod close_pattern;
+pub mod collab;
+pub mod completion;
 pub mod confirm;
 pub mod cursor;
+pub mod debug;
+pub mod ex;
+pub mod file_tree;
 pub mod git;
 pub mod jump;
+pub mod jump_list;
 pub mod line_jump;
+pub mod lsp;
+pub mod mark;
+pub mod mouse;
+pub mod passphrase;
 pub mod path;
 pub mod preferences;
+pub mod prompt;
+pub mod register;
+pub mod rename;
+pub mod replace;
 pub mod search;
+pub mod select;
+pub mod select_block;
 pub mod selection;
+pub mod tag;
 pub mod search_select;
+pub mod test;
 pub mod view;
 pub mod workspace;
 
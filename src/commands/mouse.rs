@@ -0,0 +1,130 @@
+use crate::errors::*;
+use crate::commands::{self, application, search_select, Result};
+use crate::input::Mouse;
+use crate::models::application::{Application, Mode};
+use scribe::buffer::Position;
+
+// The number of lines scrolled by each turn of the mouse wheel.
+const SCROLL_LINES: usize = 3;
+
+/// Responds to mouse input. Clicking moves the cursor (focusing the pane
+/// clicked in), dragging selects text, and the wheel scrolls. In modes that
+/// list results, clicking one accepts it, and the wheel changes the selection.
+pub(crate) fn handle(app: &mut Application, mouse: Mouse) -> Result {
+    if let Some("search_select") | Some("search_select_insert") = app.mode_str() {
+        return handle_search_select(app, mouse);
+    }
+
+    match mouse {
+        Mouse::Press(position) => press(app, &position),
+        Mouse::Drag(position) => drag(app, &position),
+        Mouse::Release(_) => Ok(()),
+        Mouse::ScrollUp(_) => {
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            app.view.scroll_up(buffer, SCROLL_LINES)
+        }
+        Mouse::ScrollDown(_) => {
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            app.view.scroll_down(buffer, SCROLL_LINES)
+        }
+    }
+}
+
+fn handle_search_select(app: &mut Application, mouse: Mouse) -> Result {
+    match mouse {
+        // Results are listed from the top of the screen.
+        Mouse::Press(position) => {
+            let list_height = app.preferences.borrow().search_select_config().max_results;
+            if position.line < list_height && search_select::select_index(app, position.line)? {
+                search_select::accept(app)?;
+            }
+
+            Ok(())
+        }
+        Mouse::ScrollUp(_) => search_select::select_previous(app),
+        Mouse::ScrollDown(_) => search_select::select_next(app),
+        Mouse::Drag(_) | Mouse::Release(_) => Ok(()),
+    }
+}
+
+fn press(app: &mut Application, position: &Position) -> Result {
+    match app.mode {
+        Mode::Normal | Mode::Insert => (),
+        // Moving the cursor would extend the selection, rather than end it.
+        Mode::Select(_) | Mode::SelectLine(_) => application::switch_to_normal_mode(app)?,
+        _ => return Ok(()),
+    }
+
+    commands::view::sync_focused_pane(app);
+    if app.view.focus_pane_at(position).is_some() {
+        commands::view::select_focused_pane_buffer(app)?;
+    }
+
+    move_cursor_to(app, position)
+}
+
+// Dragging selects from where the button was pressed (i.e. the cursor).
+fn drag(app: &mut Application, position: &Position) -> Result {
+    match app.mode {
+        Mode::Select(_) | Mode::SelectLine(_) => (),
+        Mode::Normal => application::switch_to_select_mode(app)?,
+        Mode::Insert => {
+            application::switch_to_normal_mode(app)?;
+            application::switch_to_select_mode(app)?;
+        }
+        _ => return Ok(()),
+    }
+
+    move_cursor_to(app, position)
+}
+
+// Moves the cursor to the buffer position drawn at the screen position,
+// keeping it on the narrowed lines, if the buffer's been narrowed.
+fn move_cursor_to(app: &mut Application, position: &Position) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let target = match app.view.buffer_position_at(buffer, position)? {
+        Some(target) => target,
+        None => return Ok(()),
+    };
+    let target = match app.view.narrowing(buffer) {
+        Some(narrowing) => narrowing.clamp(target, &buffer.data()),
+        None => target,
+    };
+    buffer.cursor.move_to(target);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::handle;
+    use crate::input::Mouse;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn clicking_moves_the_cursor_and_dragging_selects_from_there() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        app.workspace.add_buffer(buffer);
+
+        // Content follows the line number gutter (e.g. " 1  ").
+        handle(&mut app, Mouse::Press(Position { line: 1, offset: 5 })).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 1 });
+        assert!(matches!(app.mode, Mode::Normal));
+
+        handle(&mut app, Mouse::Drag(Position { line: 0, offset: 6 })).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 2 });
+        match app.mode {
+            Mode::Select(ref mode) => assert_eq!(mode.anchor, Position { line: 1, offset: 1 }),
+            _ => panic!("Expected select mode"),
+        }
+
+        handle(&mut app, Mouse::Press(Position { line: 0, offset: 4 })).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 0 });
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+}
@@ -0,0 +1,138 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::PassphraseMode;
+use crate::util::{self, encryption};
+use scribe::Buffer;
+use scribe::buffer::Position;
+use std::path::Path;
+
+/// Decrypts the file using the entered passphrase, and opens its plaintext in
+/// a buffer that's re-encrypted with the same passphrase whenever it's saved.
+/// Files that don't exist yet are opened empty, and encrypted when first saved.
+pub fn accept(app: &mut Application) -> Result {
+    let (path, passphrase) = if let Mode::Passphrase(ref mode) = app.mode {
+        if mode.input.is_empty() {
+            bail!("Please enter a passphrase");
+        }
+
+        (mode.path.clone(), mode.input.to_string())
+    } else {
+        bail!("Can't accept a passphrase outside of passphrase mode");
+    };
+
+    let content = if path.exists() {
+        encryption::decrypt(&path, &passphrase, &app.preferences.borrow())?
+    } else {
+        String::new()
+    };
+
+    let mut buffer = Buffer::new();
+    buffer.path = Some(path);
+    buffer.insert(content);
+    buffer.cursor.move_to(Position { line: 0, offset: 0 });
+    util::add_buffer(buffer, app)?;
+
+    // The buffer matches its file, though it can't be saved as it is.
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.record_saved_content(buffer);
+    app.encrypted_buffers.insert(buffer.id.ok_or(BUFFER_MISSING)?, passphrase);
+
+    commands::application::switch_to_normal_mode(app)
+}
+
+/// Opens a path, prompting for a passphrase first if its file type is encrypted
+/// (see `Preferences::encryption_commands`) and it isn't already open.
+pub(crate) fn open(app: &mut Application, path: &Path) -> Result {
+    let encrypted = encryption::is_encrypted(path, &app.preferences.borrow());
+    if encrypted && !app.workspace.contains_buffer_with_path(path) {
+        let path = path.canonicalize().unwrap_or_else(|_| app.workspace.path.join(path));
+        app.mode = Mode::Passphrase(PassphraseMode::new(path));
+
+        return Ok(());
+    }
+
+    app.workspace
+        .open_buffer(path)
+        .chain_err(|| format!("Couldn't open a buffer for {}", path.display()))?;
+    app.view.initialize_buffer(app.workspace.current_buffer().ok_or(BUFFER_MISSING)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::{Mode, Preferences};
+    use crate::yaml::yaml::YamlLoader;
+    use std::cell::RefCell;
+    use std::env;
+    use std::fs;
+    use std::rc::Rc;
+
+    // Encrypts with rot13, keyed by a passphrase that must be "amp".
+    fn app() -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let command = "[sh, -c, 'read key && [ \"$key\" = amp ] && tr a-z n-za-m']";
+        let data = YamlLoader::load_from_str(&format!(
+            "types:\n  rot:\n    decrypt_command: {}\n    encrypt_command: {}",
+            command, command
+        )).unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+
+        app
+    }
+
+    fn enter(app: &mut Application, passphrase: &str) -> commands::Result {
+        if let Mode::Passphrase(ref mut mode) = app.mode {
+            mode.input.set(passphrase);
+        }
+
+        super::accept(app)
+    }
+
+    #[test]
+    fn accept_decrypts_the_file_and_save_reencrypts_it() {
+        let mut app = app();
+        let path = env::temp_dir().join("amp_passphrase_accept.rot");
+        fs::write(&path, "frperg\n").unwrap();
+
+        super::open(&mut app, &path).unwrap();
+        assert!(enter(&mut app, "wrong").is_err());
+        if let Mode::Passphrase(_) = app.mode {
+        } else {
+            panic!("Not in passphrase mode");
+        }
+        enter(&mut app, "amp").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "secret\n");
+        assert!(!app.view.modified(app.workspace.current_buffer().unwrap()));
+
+        app.workspace.current_buffer().unwrap().insert("top ");
+        commands::buffer::save(&mut app).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "gbc frperg\n");
+        assert!(!app.view.modified(app.workspace.current_buffer().unwrap()));
+
+        // Opening the file again selects its buffer, without prompting.
+        commands::workspace::new_buffer(&mut app).unwrap();
+        super::open(&mut app, &path).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "top secret\n");
+        assert!(commands::buffer::reload(&mut app).is_err());
+        assert!(commands::buffer::save_as(&mut app, "plaintext.md").is_err());
+    }
+
+    #[test]
+    fn accept_opens_new_files_empty_and_requires_a_passphrase() {
+        let mut app = app();
+        let path = env::temp_dir().join("amp_passphrase_new.rot");
+        let _ = fs::remove_file(&path);
+
+        super::open(&mut app, &path).unwrap();
+        assert!(enter(&mut app, "").is_err());
+        enter(&mut app, "amp").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "");
+        assert!(!path.exists());
+
+        app.workspace.current_buffer().unwrap().insert("amp");
+        commands::buffer::save(&mut app).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "nzc\n");
+    }
+}
@@ -1,37 +1,13 @@
 use crate::errors::*;
 use crate::commands::{self, Result};
-use crate::input::Key;
 use crate::models::application::{Application, Mode};
 use std::path::PathBuf;
 
-pub fn push_char(app: &mut Application) -> Result {
-    let last_key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
-    if let Key::Char(c) = *last_key {
-        if let Mode::Path(ref mut mode) = app.mode {
-            mode.push_char(c);
-        } else {
-            bail!("Cannot push char outside of path mode");
-        }
-    } else {
-        bail!("Last key press wasn't a character");
-    }
-    Ok(())
-}
-
-pub fn pop_char(app: &mut Application) -> Result {
-    if let Mode::Path(ref mut mode) = app.mode {
-        mode.pop_char();
-    } else {
-        bail!("Cannot pop char outside of path mode");
-    }
-    Ok(())
-}
-
 pub fn accept_path(app: &mut Application) -> Result {
     let save_on_accept =
         if let Mode::Path(ref mut mode) = app.mode {
             let current_buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-            let path_name = mode.input.clone();
+            let path_name = mode.input.to_string();
             if path_name.is_empty() {
                 bail!("Please provide a non-empty path")
             }
@@ -71,7 +47,7 @@ mod tests {
         // Switch to the mode, add a name, and accept it.
         commands::application::switch_to_path_mode(&mut app).unwrap();
         if let Mode::Path(ref mut mode) = app.mode {
-            mode.input = String::from("new_path");
+            mode.input.set("new_path");
         }
         super::accept_path(&mut app).unwrap();
 
@@ -96,12 +72,12 @@ mod tests {
         // Switch to the mode, add a name, set the flag, and accept it.
         commands::application::switch_to_path_mode(&mut app).unwrap();
         if let Mode::Path(ref mut mode) = app.mode {
-            mode.input = Path::new(concat!(env!("OUT_DIR"), "new_path")).to_string_lossy().into();
+            mode.input.set(Path::new(concat!(env!("OUT_DIR"), "new_path")).to_string_lossy());
             mode.save_on_accept = true;
         }
         super::accept_path(&mut app).unwrap();
 
-        assert!(!app.workspace.current_buffer().unwrap().modified());
+        assert!(!app.view.modified(app.workspace.current_buffer().unwrap()));
     }
 
     #[test]
@@ -114,7 +90,7 @@ mod tests {
         // Switch to the mode, add a name, and accept it.
         commands::application::switch_to_path_mode(&mut app).unwrap();
         if let Mode::Path(ref mut mode) = app.mode {
-            mode.input = String::from("");
+            mode.input.set("");
         }
         let result = super::accept_path(&mut app);
         assert!(result.is_err());
@@ -137,7 +113,7 @@ mod tests {
         // Switch to the mode, add a name, and accept it.
         commands::application::switch_to_path_mode(&mut app).unwrap();
         if let Mode::Path(ref mut mode) = app.mode {
-            mode.input = String::from("path.rs");
+            mode.input.set("path.rs");
         }
         super::accept_path(&mut app).unwrap();
 
@@ -0,0 +1,229 @@
+use crate::errors;
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::input::Key;
+use crate::models::application::{Application, ClipboardContent, HistoryMatch, Mode, Prompt};
+use crate::models::application::modes::SearchSelectMode;
+
+/// Inserts the last key pressed at the prompt's cursor.
+pub fn insert_char(app: &mut Application) -> Result {
+    let c = match *app.view.last_key() {
+        Some(Key::Char(c)) => c,
+        _ => bail!("Last key press wasn't a character"),
+    };
+
+    edit_input(app, |prompt| prompt.insert(c))
+}
+
+pub fn delete_char(app: &mut Application) -> Result {
+    edit_input(app, Prompt::delete_char)
+}
+
+pub fn delete_word(app: &mut Application) -> Result {
+    edit_input(app, Prompt::delete_word)
+}
+
+pub fn delete_forward(app: &mut Application) -> Result {
+    edit_input(app, Prompt::delete_forward)
+}
+
+/// Inserts the clipboard's content at the cursor as-is,
+/// including any whitespace or control characters.
+pub fn paste(app: &mut Application) -> Result {
+    let content = match *app.clipboard.get_content() {
+        ClipboardContent::Inline(ref content) |
+        ClipboardContent::Block(ref content) => content.clone(),
+        ClipboardContent::None => bail!("Nothing to paste"),
+    };
+
+    edit_input(app, |prompt| prompt.insert_str(&content))
+}
+
+/// Inserts text pasted into the terminal at the prompt's cursor.
+pub(crate) fn insert_pasted_text(app: &mut Application, content: &str) -> Result {
+    edit_input(app, |prompt| prompt.insert_str(content))
+}
+
+pub fn move_left(app: &mut Application) -> Result {
+    current_prompt(app)?.move_left();
+
+    Ok(())
+}
+
+pub fn move_right(app: &mut Application) -> Result {
+    current_prompt(app)?.move_right();
+
+    Ok(())
+}
+
+pub fn move_to_start(app: &mut Application) -> Result {
+    current_prompt(app)?.move_to_start();
+
+    Ok(())
+}
+
+pub fn move_to_end(app: &mut Application) -> Result {
+    current_prompt(app)?.move_to_end();
+
+    Ok(())
+}
+
+/// Completes the input using the prompt's completer (e.g. file paths).
+pub fn complete(app: &mut Application) -> Result {
+    // Open mode suggests a past query when the current one matches nothing.
+    if let Mode::Open(ref mut mode) = app.mode {
+        if mode.accept_suggestion() {
+            return edit_input(app, |_| ());
+        }
+    }
+
+    if !current_prompt(app)?.complete() {
+        bail!("No completions available");
+    }
+
+    edit_input(app, |_| ())
+}
+
+/// Replaces the input with the previous entry in the prompt's history.
+pub fn previous_history(app: &mut Application) -> Result {
+    let history = &app.history;
+    if !prompt(&mut app.mode).ok_or(PROMPT_MISSING)?.recall_previous(history, HistoryMatch::All) {
+        bail!("No earlier entries in history");
+    }
+
+    edit_input(app, |_| ())
+}
+
+/// Replaces the input with the next entry in the prompt's history.
+pub fn next_history(app: &mut Application) -> Result {
+    let history = &app.history;
+    if !prompt(&mut app.mode).ok_or(PROMPT_MISSING)?.recall_next(history) {
+        bail!("No later entries in history");
+    }
+
+    edit_input(app, |_| ())
+}
+
+fn current_prompt(app: &mut Application) -> errors::Result<&mut Prompt> {
+    Ok(prompt(&mut app.mode).ok_or(PROMPT_MISSING)?)
+}
+
+// Applies an edit to the current mode's prompt, updating
+// anything derived from it (e.g. search results).
+fn edit_input<F: FnOnce(&mut Prompt)>(app: &mut Application, edit: F) -> Result {
+    edit(current_prompt(app)?);
+
+    match app.mode {
+        Mode::Search(ref mode) => {
+            app.search_query = mode.query().map(String::from);
+            commands::search::update_suggestions(app);
+        },
+        Mode::ClosePattern(ref mut mode) => mode.confirm = false,
+        Mode::Calculator(_) | Mode::Ex(_) | Mode::FileTree(_) | Mode::Passphrase(_) | Mode::Path(_) | Mode::Rename(_) | Mode::Replace(_) => (),
+        _ => commands::search_select::search(app)?,
+    }
+
+    Ok(())
+}
+
+fn prompt(mode: &mut Mode) -> Option<&mut Prompt> {
+    match *mode {
+        Mode::Search(ref mut mode) => Some(&mut mode.input),
+        Mode::Calculator(ref mut mode) => Some(&mut mode.input),
+        Mode::ClosePattern(ref mut mode) => Some(&mut mode.input),
+        Mode::Ex(ref mut mode) => Some(&mut mode.input),
+        Mode::FileTree(ref mut mode) if mode.entering_input() => Some(&mut mode.input),
+        Mode::Passphrase(ref mut mode) => Some(&mut mode.input),
+        Mode::Path(ref mut mode) => Some(&mut mode.input),
+        Mode::Rename(ref mut mode) => Some(&mut mode.input),
+        Mode::Replace(ref mut mode) => Some(mode.input()),
+        Mode::Branch(ref mut mode) => Some(mode.query()),
+        Mode::Buffer(ref mut mode) => Some(mode.query()),
+        Mode::Command(ref mut mode) => Some(mode.query()),
+        Mode::Debug(ref mut mode) => Some(mode.query()),
+        Mode::Diagnostic(ref mut mode) => Some(mode.query()),
+        Mode::Grep(ref mut mode) => Some(mode.query()),
+        Mode::Number(ref mut mode) => Some(mode.query()),
+        Mode::Open(ref mut mode) => Some(mode.query()),
+        Mode::Stash(ref mut mode) => Some(mode.query()),
+        Mode::UndoTree(ref mut mode) => Some(mode.query()),
+        Mode::JumpList(ref mut mode) => Some(mode.query()),
+        Mode::Marks(ref mut mode) => Some(mode.query()),
+        Mode::Tags(ref mut mode) => Some(mode.query()),
+        Mode::Registers(ref mut mode) => Some(mode.query()),
+        Mode::SymbolJump(ref mut mode) => Some(mode.query()),
+        Mode::Task(ref mut mode) => Some(mode.query()),
+        Mode::Theme(ref mut mode) => Some(mode.query()),
+        Mode::Transform(ref mut mode) => Some(mode.query()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, ClipboardContent, InputHistory, Mode};
+    use crate::models::application::modes::{PathMode, SearchMode};
+
+    #[test]
+    fn paste_appends_clipboard_content_to_the_input() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.mode = Mode::Path(PathMode::new(String::new()));
+        app.clipboard.set_content(ClipboardContent::Inline(String::from("src/main.rs\n"))).unwrap();
+
+        commands::prompt::paste(&mut app).unwrap();
+        commands::prompt::delete_char(&mut app).unwrap();
+        commands::prompt::delete_word(&mut app).unwrap();
+
+        if let Mode::Path(ref mode) = app.mode {
+            assert_eq!(&*mode.input, "src/main.");
+        } else {
+            panic!("Not in path mode");
+        }
+    }
+
+    #[test]
+    fn delete_word_updates_the_search_query() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.mode = Mode::Search(SearchMode::new(Some(String::from("amp editor"))));
+
+        commands::prompt::delete_word(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("amp ")));
+    }
+
+    #[test]
+    fn editing_happens_at_the_cursor_and_moves_it() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.mode = Mode::Path(PathMode::new(String::from("src/main.rs")));
+
+        commands::prompt::move_to_start(&mut app).unwrap();
+        commands::prompt::delete_forward(&mut app).unwrap();
+        commands::prompt::move_right(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('C'));
+        commands::prompt::insert_char(&mut app).unwrap();
+        commands::prompt::move_to_end(&mut app).unwrap();
+        commands::prompt::move_left(&mut app).unwrap();
+        commands::prompt::delete_char(&mut app).unwrap();
+
+        if let Mode::Path(ref mode) = app.mode {
+            assert_eq!((&*mode.input, mode.input.cursor()), ("rCc/main.s", 9));
+        } else {
+            panic!("Not in path mode");
+        }
+    }
+
+    #[test]
+    fn history_is_recalled_and_updates_the_search_query() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.history = InputHistory::default();
+        app.history.add("search", String::from("amp"));
+        app.mode = Mode::Search(SearchMode::new(None));
+
+        commands::prompt::previous_history(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("amp")));
+        assert!(commands::prompt::previous_history(&mut app).is_err());
+        commands::prompt::next_history(&mut app).unwrap();
+        assert_eq!(app.search_query, None);
+    }
+}
@@ -0,0 +1,84 @@
+use crate::input::Key;
+use std::mem;
+use crate::commands::Result;
+use crate::models::application::modes::jump::SelectModeOptions;
+use crate::models::application::{Application, Mode};
+
+/// Selects the register named by the last key pressed for the next copy
+/// or paste, and returns to the mode register mode was entered from.
+pub fn select(app: &mut Application) -> Result {
+    let name = match *app.view.last_key() {
+        Some(Key::Char(c)) => c,
+        _ => bail!("Last key press wasn't a character"),
+    };
+    if !matches!(app.mode, Mode::Register(_)) {
+        bail!("Can't select a register outside of register mode");
+    }
+    switch_to_previous_mode(app);
+
+    app.clipboard.select_register(name)
+}
+
+/// Leaves register mode without selecting a register.
+pub fn cancel(app: &mut Application) -> Result {
+    if !matches!(app.mode, Mode::Register(_)) {
+        bail!("Can't cancel register selection outside of register mode");
+    }
+    switch_to_previous_mode(app);
+
+    Ok(())
+}
+
+fn switch_to_previous_mode(app: &mut Application) {
+    let old_mode = mem::replace(&mut app.mode, Mode::Normal);
+
+    if let Mode::Register(register_mode) = old_mode {
+        match register_mode.select_mode {
+            SelectModeOptions::None => (),
+            SelectModeOptions::Select(select_mode) => {
+                app.mode = Mode::Select(select_mode);
+            }
+            SelectModeOptions::SelectLine(select_mode) => {
+                app.mode = Mode::SelectLine(select_mode);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::Application;
+    use crate::models::application::{ClipboardContent, Mode};
+    use scribe::Buffer;
+
+    #[test]
+    fn select_directs_the_next_copy_and_paste_to_the_register() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\n");
+        app.workspace.add_buffer(buffer);
+
+        // Copy the first line into register "a".
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::application::switch_to_register_mode(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('a'));
+        commands::register::select(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::SelectLine(_)));
+        commands::selection::copy(&mut app).unwrap();
+        app.clipboard.clear_register_selection();
+        assert_eq!(
+            app.clipboard.registers().collect::<Vec<_>>(),
+            vec![('a', &ClipboardContent::Block(String::from("amp\n")))]
+        );
+
+        // Paste it below the second line.
+        commands::cursor::move_down(&mut app).unwrap();
+        commands::application::switch_to_register_mode(&mut app).unwrap();
+        commands::register::select(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::Normal));
+        commands::buffer::paste(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\neditor\namp\n");
+    }
+}
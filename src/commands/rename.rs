@@ -0,0 +1,151 @@
+use crate::commands::{self, Result};
+use crate::models::application::{word_index, Application, ChangeSet, Mode};
+use crate::util;
+
+/// Previews the rename across all open buffers as a set of pending changes.
+pub fn accept_input(app: &mut Application) -> Result {
+    let (token, name, language_server) = match app.mode {
+        Mode::Rename(ref mode) => (mode.token.clone(), mode.input.to_string(), mode.language_server),
+        _ => bail!("Can't accept rename input outside of rename mode."),
+    };
+    if name.is_empty() {
+        bail!("Please provide a non-empty name");
+    }
+
+    // The server's edits are previewed once it responds.
+    if language_server {
+        commands::application::switch_to_normal_mode(app)?;
+        let (path, line, character) = commands::lsp::cursor_location(app)?;
+
+        return app.language_servers.rename(&path, line, character, &name);
+    }
+
+    // Encrypted buffers are left as they are, since
+    // applying changes would write their plaintext to disk.
+    let mut changes = ChangeSet::default();
+    let start_id = app.workspace.current_buffer().and_then(|b| b.id);
+    loop {
+        if let Some(buffer) = app.workspace.current_buffer() {
+            let encrypted = matches!(buffer.id, Some(id) if app.encrypted_buffers.contains_key(&id));
+            if let (Some(path), false) = (buffer.path.as_ref(), encrypted) {
+                let data = buffer.data();
+                let renamed = word_index::replace_word(&data, &token, &name);
+                changes.add(path.clone(), data, renamed);
+            }
+        }
+
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer().and_then(|b| b.id) == start_id {
+            break;
+        }
+    }
+
+    commands::application::switch_to_normal_mode(app)?;
+    util::preview_changes(changes, app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::{Mode, Preferences};
+    use crate::yaml::yaml::YamlLoader;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+
+    fn open_file(app: &mut Application, name: &str, content: &str) -> PathBuf {
+        let path = Path::new(env!("OUT_DIR")).join(name);
+        fs::write(&path, content).unwrap();
+        app.workspace.open_buffer(&path).unwrap();
+
+        path
+    }
+
+    #[test]
+    fn accept_input_previews_changes_that_are_applied_on_save() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let other_path = open_file(&mut app, "rename_other", "use editor;\n");
+        let path = open_file(&mut app, "rename_current", "let editor = editor_id;\n");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 5 });
+
+        commands::application::switch_to_rename_mode(&mut app).unwrap();
+        if let Mode::Rename(ref mut mode) = app.mode {
+            assert_eq!(&*mode.input, "editor");
+            mode.input.set("amp");
+        }
+        commands::rename::accept_input(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Normal));
+        let diff = app.workspace.current_buffer().unwrap().data();
+        assert!(diff.contains("-let editor = editor_id;\n+let amp = editor_id;\n"));
+        assert!(diff.contains("-use editor;\n+use amp;\n"));
+        assert_eq!(fs::read_to_string(&path).unwrap(), "let editor = editor_id;\n");
+
+        commands::buffer::save(&mut app).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "let amp = editor_id;\n");
+        assert_eq!(fs::read_to_string(&other_path).unwrap(), "use amp;\n");
+        assert!(app.pending_changes.is_empty());
+    }
+
+    #[test]
+    fn closing_a_preview_discards_its_changes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let path = open_file(&mut app, "rename_discarded", "editor\n");
+        let mut buffer = Buffer::new();
+        buffer.insert("unrelated editor");
+        app.workspace.add_buffer(buffer);
+        app.workspace.previous_buffer();
+
+        commands::application::switch_to_rename_mode(&mut app).unwrap();
+        if let Mode::Rename(ref mut mode) = app.mode {
+            mode.input.set("amp");
+        }
+        commands::rename::accept_input(&mut app).unwrap();
+        commands::buffer::close(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert!(app.pending_changes.is_empty());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "editor\n");
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\n");
+    }
+
+    #[test]
+    fn accept_input_leaves_encrypted_buffers_unchanged() {
+        // Encrypts with rot13, keyed by a passphrase that must be "amp".
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let command = "[sh, -c, 'read key && [ \\"$key\\" = amp ] && tr a-z n-za-m']";
+        let data = YamlLoader::load_from_str(&format!(
+            "types:\n  rot:\n    decrypt_command: {}\n    encrypt_command: {}",
+            command, command
+        )).unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+
+        let encrypted_path = Path::new(env!("OUT_DIR")).join("rename_encrypted.rot");
+        fs::write(&encrypted_path, "rqvgbe\n").unwrap();
+        commands::passphrase::open(&mut app, &encrypted_path).unwrap();
+        if let Mode::Passphrase(ref mut mode) = app.mode {
+            mode.input.set("amp");
+        }
+        commands::passphrase::accept(&mut app).unwrap();
+        let path = open_file(&mut app, "rename_unencrypted", "editor\n");
+
+        commands::application::switch_to_rename_mode(&mut app).unwrap();
+        if let Mode::Rename(ref mut mode) = app.mode {
+            mode.input.set("amp");
+        }
+        commands::rename::accept_input(&mut app).unwrap();
+
+        assert!(!app.workspace.current_buffer().unwrap().data().contains("rename_encrypted"));
+        commands::buffer::save(&mut app).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "amp\n");
+        assert_eq!(fs::read_to_string(&encrypted_path).unwrap(), "rqvgbe\n");
+        commands::passphrase::open(&mut app, &encrypted_path).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\n");
+    }
+}
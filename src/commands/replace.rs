@@ -0,0 +1,176 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::ReplaceStage;
+
+/// Moves from the pattern to the replacement input and, once that's accepted,
+/// starts walking through matches. Replacements are grouped into a single
+/// operation, so that the whole session can be undone at once.
+pub fn accept_input(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        match mode.stage {
+            ReplaceStage::Pattern => {
+                if mode.pattern.is_empty() {
+                    bail!(SEARCH_QUERY_MISSING);
+                }
+                mode.stage = ReplaceStage::Replacement;
+
+                return Ok(());
+            }
+            ReplaceStage::Replacement => mode.start()?,
+            ReplaceStage::Confirm => bail!("Replacement has already started"),
+        }
+    } else {
+        bail!("Can't accept replace input outside of replace mode");
+    }
+
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.start_operation_group();
+    move_to_next_match(app)
+}
+
+/// Replaces the current match and moves to the next one.
+pub fn replace(app: &mut Application) -> Result {
+    replace_current_match(app)?;
+
+    move_to_next_match(app)
+}
+
+/// Leaves the current match as-is and moves to the next one.
+pub fn skip(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        mode.skip();
+    } else {
+        bail!("Can't skip match outside of replace mode");
+    }
+
+    move_to_next_match(app)
+}
+
+/// Replaces the current match and all of those that follow it.
+pub fn replace_all(app: &mut Application) -> Result {
+    loop {
+        replace_current_match(app)?;
+
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        if let Mode::Replace(ref mut mode) = app.mode {
+            if mode.find_next(&buffer.data()).is_none() {
+                break;
+            }
+        }
+    }
+
+    finish(app)
+}
+
+/// Ends the replacement session, reporting how many matches were replaced.
+pub fn finish(app: &mut Application) -> Result {
+    let replaced = match app.mode {
+        Mode::Replace(ref mode) if mode.stage == ReplaceStage::Confirm => mode.replaced,
+        Mode::Replace(_) => return commands::application::switch_to_normal_mode(app),
+        _ => bail!("Can't finish replacing outside of replace mode"),
+    };
+
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.end_operation_group();
+    app.notification = Some(format!(
+        "Replaced {} occurrence{}",
+        replaced,
+        if replaced == 1 { "" } else { "s" }
+    ));
+
+    commands::application::switch_to_normal_mode(app)
+}
+
+fn replace_current_match(app: &mut Application) -> Result {
+    if let Mode::Replace(ref mut mode) = app.mode {
+        let (range, content) = mode.take_replacement().ok_or("No match to replace")?;
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+        buffer.delete_range(range.clone());
+        buffer.cursor.move_to(range.start());
+        buffer.insert(content);
+    } else {
+        bail!("Can't replace match outside of replace mode");
+    }
+
+    Ok(())
+}
+
+// Moves the cursor to the next match, finishing once there are none left.
+fn move_to_next_match(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let next_match = if let Mode::Replace(ref mut mode) = app.mode {
+        mode.find_next(&buffer.data())
+    } else {
+        bail!("Can't find next match outside of replace mode");
+    };
+
+    match next_match {
+        Some(range) => {
+            buffer.cursor.move_to(range.start());
+            commands::view::scroll_cursor_to_center(app)
+        }
+        None => finish(app),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+
+    fn replace_in(content: &str, pattern: &str, replacement: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        app.workspace.add_buffer(buffer);
+
+        commands::application::switch_to_replace_mode(&mut app).unwrap();
+        if let Mode::Replace(ref mut mode) = app.mode {
+            mode.pattern.set(pattern);
+        }
+        commands::replace::accept_input(&mut app).unwrap();
+        if let Mode::Replace(ref mut mode) = app.mode {
+            mode.replacement.set(replacement);
+        }
+        commands::replace::accept_input(&mut app).unwrap();
+
+        app
+    }
+
+    #[test]
+    fn replace_and_skip_walk_through_matches_as_a_single_undoable_change() {
+        let mut app = replace_in("amp\namp\namp", "amp", "editor");
+        commands::replace::replace(&mut app).unwrap();
+        commands::replace::skip(&mut app).unwrap();
+        commands::replace::replace(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.notification, Some(String::from("Replaced 2 occurrences")));
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "editor\namp\neditor");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "amp\namp\namp");
+    }
+
+    #[test]
+    fn replace_all_replaces_remaining_regex_matches() {
+        let mut app = replace_in("let snake_case = other_name;", "re:(\\w+)_(\\w+)", "$1\\u$2");
+        commands::replace::skip(&mut app).unwrap();
+        commands::replace::replace_all(&mut app).unwrap();
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.notification, Some(String::from("Replaced 1 occurrence")));
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "let snake_case = otherName;");
+    }
+
+    #[test]
+    fn accept_input_finishes_immediately_without_matches() {
+        let app = replace_in("amp", "editor", "amp");
+
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(app.notification, Some(String::from("Replaced 0 occurrences")));
+    }
+}
@@ -1,14 +1,12 @@
 use crate::errors::*;
-use crate::input::Key;
 use crate::commands::{self, Result};
-use crate::models::application::{Application, Mode};
+use crate::models::application::{Application, Bell, HistoryMatch, Mode};
+use crate::models::application::modes::search::{HISTORY_NAMESPACE, REGEX_PREFIX};
+
+const SUGGESTION_LIMIT: usize = 5;
 
 pub fn move_to_previous_result(app: &mut Application) -> Result {
-    if let Mode::Search(ref mut mode) = app.mode {
-        mode.results.as_mut().ok_or(NO_SEARCH_RESULTS)?.select_previous();
-    } else {
-        bail!("Can't move to search result outside of search mode");
-    }
+    select_adjacent_result(app, false)?;
 
     commands::view::scroll_cursor_to_center(app)
         .chain_err(|| SCROLL_TO_CURSOR_FAILED)?;
@@ -16,11 +14,7 @@ pub fn move_to_previous_result(app: &mut Application) -> Result {
 }
 
 pub fn move_to_next_result(app: &mut Application) -> Result {
-    if let Mode::Search(ref mut mode) = app.mode {
-        mode.results.as_mut().ok_or(NO_SEARCH_RESULTS)?.select_next();
-    } else {
-        bail!("Can't move to search result outside of search mode");
-    }
+    select_adjacent_result(app, true)?;
 
     commands::view::scroll_cursor_to_center(app)
         .chain_err(|| SCROLL_TO_CURSOR_FAILED)?;
@@ -30,12 +24,15 @@ pub fn move_to_next_result(app: &mut Application) -> Result {
 pub fn move_to_current_result(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-        let query = mode.input.as_ref().ok_or(SEARCH_QUERY_MISSING)?;
+        let query = mode.query().ok_or(SEARCH_QUERY_MISSING)?.to_string();
         let result = mode.results
             .as_mut()
             .ok_or(NO_SEARCH_RESULTS)?
             .selection()
             .ok_or_else(|| format!("No matches found for \"{}\"", query))?;
+        if result.start() != *buffer.cursor {
+            app.jump_list.record(buffer);
+        }
         buffer.cursor.move_to(result.start());
     } else {
         bail!("Can't move to search result outside of search mode");
@@ -51,6 +48,10 @@ pub fn accept_query(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         // Disable insert sub-mode.
         mode.insert = false;
+        mode.suggestions.clear();
+        if mode.query().is_some() {
+            mode.input.remember(&mut app.history);
+        }
     } else {
         bail!("Can't accept search query outside of search mode");
     }
@@ -61,7 +62,7 @@ pub fn accept_query(app: &mut Application) -> Result {
 
 pub fn clear_query(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
-        mode.input = None;
+        mode.input.clear();
         app.search_query = None;
     } else {
         bail!("Can't clear search outside of search mode");
@@ -70,33 +71,60 @@ pub fn clear_query(app: &mut Application) -> Result {
     Ok(())
 }
 
-pub fn push_search_char(app: &mut Application) -> Result {
-    let key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
-
-    if let Key::Char(c) = *key {
-        if let Mode::Search(ref mut mode) = app.mode {
-            let query = mode.input.get_or_insert(String::new());
-            query.push(c);
-            app.search_query = Some(query.clone());
-        } else {
-            bail!("Can't push search character outside of search mode");
+pub fn accept_suggestion(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        if mode.suggestions.is_empty() {
+            bail!("No matching queries in search history");
         }
+
+        let query = mode.suggestions.remove(0);
+        app.search_query = Some(query.clone());
+        mode.input.set(query);
     } else {
-        bail!("Last key press wasn't a character")
+        bail!("Can't accept search suggestion outside of search mode");
     }
+    update_suggestions(app);
 
     Ok(())
 }
 
-pub fn pop_search_char(app: &mut Application) -> Result {
+pub fn previous_query(app: &mut Application) -> Result {
+    recall_previous_query(app, HistoryMatch::All)
+}
+
+/// Recalls the previous query starting with the typed input.
+pub fn previous_matching_query(app: &mut Application) -> Result {
+    recall_previous_query(app, HistoryMatch::Prefix)
+}
+
+pub fn next_query(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
-        let query = mode.input.as_mut().ok_or(SEARCH_QUERY_MISSING)?;
+        if !mode.input.recall_next(&app.history) {
+            bail!("No later queries in search history");
+        }
+    } else {
+        bail!("Can't recall search query outside of search mode");
+    }
+    recalled_query(app);
 
-        query.pop();
-        app.search_query = Some(query.clone());
+    Ok(())
+}
+
+/// Toggles the query between a literal string and a regular expression.
+pub fn toggle_regex(app: &mut Application) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.input.edit(|query| {
+            if query.starts_with(REGEX_PREFIX) {
+                query.drain(..REGEX_PREFIX.len());
+            } else {
+                query.insert_str(0, REGEX_PREFIX);
+            }
+        });
+        app.search_query = mode.query().map(String::from);
     } else {
-        bail!("Can't pop search character outside of search mode");
-    };
+        bail!("Can't toggle regex search outside of search mode");
+    }
+    update_suggestions(app);
 
     Ok(())
 }
@@ -114,6 +142,77 @@ pub fn run(app: &mut Application) -> Result {
     move_to_current_result(app)
 }
 
+fn recall_previous_query(app: &mut Application, matching: HistoryMatch) -> Result {
+    if let Mode::Search(ref mut mode) = app.mode {
+        if !mode.input.recall_previous(&app.history, matching) {
+            bail!("No earlier queries in search history");
+        }
+    } else {
+        bail!("Can't recall search query outside of search mode");
+    }
+    recalled_query(app);
+
+    Ok(())
+}
+
+// Uses a query recalled from history. Suggestions are cleared,
+// since they'd otherwise just echo the surrounding history.
+fn recalled_query(app: &mut Application) {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.suggestions.clear();
+        app.search_query = mode.query().map(String::from);
+    }
+}
+
+// Refreshes the previous queries matching the current input.
+pub(crate) fn update_suggestions(app: &mut Application) {
+    if let Mode::Search(ref mut mode) = app.mode {
+        mode.suggestions = app.history.suggestions(HISTORY_NAMESPACE, &mode.input, SUGGESTION_LIMIT);
+    }
+}
+
+// Selects the next (or previous) result, wrapping around to the other end of
+// the buffer if the search mode allows it, and ringing the bell if it does.
+fn select_adjacent_result(app: &mut Application, forward: bool) -> Result {
+    let wrapped = if let Mode::Search(ref mut mode) = app.mode {
+        let results = mode.results.as_mut().ok_or(NO_SEARCH_RESULTS)?;
+        let boundary = if forward { results.len().saturating_sub(1) } else { 0 };
+        let wrapping = results.selected_index() == boundary;
+        if wrapping && !mode.wrap {
+            if forward {
+                bail!("No more matches below the cursor");
+            }
+            bail!("No more matches above the cursor");
+        }
+
+        if forward {
+            results.select_next();
+        } else {
+            results.select_previous();
+        }
+
+        wrapping && results.len() > 1
+    } else {
+        bail!("Can't move to search result outside of search mode");
+    };
+
+    if wrapped {
+        let bell = app.preferences.borrow().search_wrap_bell();
+        match bell {
+            Some(Bell::Audible) => app.view.bell(),
+            Some(Bell::Flash) => app.view.flash(),
+            Some(Bell::Visual) => app.notification = Some(String::from(if forward {
+                "Search wrapped to the top of the buffer"
+            } else {
+                "Search wrapped to the bottom of the buffer"
+            })),
+            None => (),
+        }
+    }
+
+    Ok(())
+}
+
 fn select_closest_result(app: &mut Application) -> Result {
     if let Mode::Search(ref mut mode) = app.mode {
         let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -137,9 +236,12 @@ mod tests {
     use scribe::Buffer;
     use scribe::buffer::Position;
     use crate::models::Application;
-    use crate::models::application::Mode;
+    use crate::models::application::{InputHistory, Mode, Preferences};
     use crate::commands;
-
+    use crate::input::Key;
+    use crate::yaml::yaml::YamlLoader;
+    use std::cell::RefCell;
+    use std::rc::Rc;
     #[test]
     fn move_to_previous_result_moves_cursor_to_previous_result() {
         // Build a workspace with a buffer and text.
@@ -155,7 +257,7 @@ mod tests {
         // Enter search mode and accept a query.
         commands::application::switch_to_search_mode(&mut app).unwrap();
         if let Mode::Search(ref mut mode) = app.mode {
-            mode.input = Some(String::from("ed"));
+            mode.input.set("ed");
         }
         commands::search::accept_query(&mut app).unwrap();
 
@@ -170,6 +272,21 @@ mod tests {
                    });
     }
 
+    #[test]
+    fn toggle_regex_adds_and_removes_the_regex_prefix() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input.set("ed.t");
+        }
+
+        commands::search::toggle_regex(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("re:ed.t")));
+        commands::search::toggle_regex(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("ed.t")));
+    }
+
     #[test]
     fn move_to_previous_result_wraps_to_the_end_of_the_document() {
         // Build a workspace with a buffer and text.
@@ -181,7 +298,7 @@ mod tests {
         // Enter search mode and accept a query.
         commands::application::switch_to_search_mode(&mut app).unwrap();
         if let Mode::Search(ref mut mode) = app.mode {
-            mode.input = Some(String::from("ed"));
+            mode.input.set("ed");
         }
         commands::search::accept_query(&mut app).unwrap();
 
@@ -207,7 +324,7 @@ mod tests {
         // Enter search mode and accept a query.
         commands::application::switch_to_search_mode(&mut app).unwrap();
         if let Mode::Search(ref mut mode) = app.mode {
-            mode.input = Some(String::from("ed"));
+            mode.input.set("ed");
         }
         commands::search::accept_query(&mut app).unwrap();
 
@@ -238,7 +355,7 @@ mod tests {
         // to just before the last match, this will select the last match.
         commands::application::switch_to_search_mode(&mut app).unwrap();
         if let Mode::Search(ref mut mode) = app.mode {
-            mode.input = Some(String::from("ed"));
+            mode.input.set("ed");
         }
         commands::search::accept_query(&mut app).unwrap();
 
@@ -251,6 +368,35 @@ mod tests {
                        line: 0,
                        offset: 4,
                    });
+        assert_eq!(app.notification.as_deref(), Some("Search wrapped to the top of the buffer"));
+    }
+
+    #[test]
+    fn move_to_next_result_stops_at_the_last_result_when_wrapping_is_disabled() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("search_wrap: false").unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nedit\nedit");
+        buffer.cursor.move_to(Position{ line: 1, offset: 3 });
+        app.workspace.add_buffer(buffer);
+
+        // Accepting the query selects the last match.
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input.set("ed");
+        }
+        commands::search::accept_query(&mut app).unwrap();
+
+        assert!(commands::search::move_to_next_result(&mut app).is_err());
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor,
+                   Position { line: 2, offset: 0 });
+        assert!(app.notification.is_none());
+
+        // Moving back towards the start is unaffected.
+        commands::search::move_to_previous_result(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor,
+                   Position { line: 1, offset: 0 });
     }
 
     #[test]
@@ -285,4 +431,86 @@ mod tests {
                        offset: 0,
                    });
     }
+
+    #[test]
+    fn accept_suggestion_uses_the_most_recent_matching_query() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+        app.history = InputHistory::default();
+        app.history.add("search", String::from("editor"));
+        app.history.add("search", String::from("edit"));
+
+        // Type a query matching both history entries.
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        for c in "ed".chars() {
+            app.view.last_key = Some(Key::Char(c));
+            commands::prompt::insert_char(&mut app).unwrap();
+        }
+        if let Mode::Search(ref mode) = app.mode {
+            assert_eq!(mode.suggestions, vec!["edit", "editor"]);
+        }
+
+        commands::search::accept_suggestion(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("edit")));
+        if let Mode::Search(ref mode) = app.mode {
+            assert_eq!(mode.suggestions, vec!["editor"]);
+        }
+    }
+
+    #[test]
+    fn previous_query_recalls_queries_and_next_query_restores_input() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+        app.history = InputHistory::default();
+        app.history.add("search", String::from("amp"));
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input.set("ed");
+        }
+
+        commands::search::previous_query(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("amp")));
+        commands::search::next_query(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("ed")));
+    }
+
+    #[test]
+    fn previous_matching_query_skips_queries_without_the_typed_prefix() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+        app.history = InputHistory::default();
+        app.history.add("search", String::from("editor"));
+        app.history.add("search", String::from("amp"));
+
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        if let Mode::Search(ref mut mode) = app.mode {
+            mode.input.set("ed");
+        }
+
+        commands::search::previous_matching_query(&mut app).unwrap();
+        assert_eq!(app.search_query, Some(String::from("editor")));
+        assert!(commands::search::previous_matching_query(&mut app).is_err());
+    }
+
+    #[test]
+    fn accept_query_adds_query_to_history() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor");
+        app.workspace.add_buffer(buffer);
+        app.history = InputHistory::default();
+
+        app.search_query = Some(String::from("ed"));
+        commands::application::switch_to_search_mode(&mut app).unwrap();
+        commands::search::accept_query(&mut app).unwrap();
+
+        assert_eq!(app.history.get("search").unwrap().entries(), &["ed"]);
+    }
 }
@@ -1,45 +1,193 @@
 use crate::errors::*;
-use crate::commands::{self, application, Result};
-use crate::input::Key;
+use crate::commands::{self, application, Command, Result};
+use scribe::buffer::{LineRange, Position};
 use std::mem;
+use std::time::Instant;
 use crate::models::application::modes::open::DisplayablePath;
-use crate::models::application::{Application, Mode};
-use crate::models::application::modes::SearchSelectMode;
+use crate::models::application::{job, Activity, Application, ClipboardContent, Job, Mode};
+use crate::util;
+use crate::models::application::modes::{LineCommand, LineOperation, ResultOrder, SearchSelectMode};
 
 pub fn accept(app: &mut Application) -> Result {
+    // Selecting a command that takes an argument fills in its
+    // name, rather than running it, so that the argument can follow.
+    if let Mode::Command(ref mut mode) = app.mode {
+        let needs_argument = mode.argument_command().is_none() &&
+            mode.selection().map(|selection| selection.command.is_none()) == Some(true);
+        if needs_argument {
+            let name = mode.selection().map(|selection| selection.description).unwrap_or_default();
+            mode.query().set(format!("{} ", name));
+            mode.search();
+
+            return Ok(());
+        }
+    }
+
     // Consume the application mode. This is necessary because the selection in
     // command mode needs to run against the application, but we can't hold the
     // reference to the selection and lend the app mutably to it at the time.
     let mut app_mode = mem::replace(&mut app.mode, Mode::Normal);
 
     match app_mode {
+        Mode::Branch(ref mut mode) => {
+            // Unmodified buffers are reloaded once it's been checked out.
+            let branch = mode.selection().ok_or("No branch selected")?;
+            app.git_worker.checkout(branch.clone())?;
+        },
+        Mode::Buffer(ref mode) => {
+            let id = mode.selection().ok_or("No buffer selected")?.id;
+            if !util::select_buffer(id, app) {
+                bail!("Couldn't find the selected buffer");
+            }
+        },
         Mode::Command(ref mode) => {
-            let selection = mode.selection().ok_or("No command selected")?;
+            if let Some(line_command) = mode.line_command() {
+                run_line_command(app, line_command)?;
+            } else if let Some(argument_command) = mode.argument_command() {
+                argument_command.run(app)?;
+            } else {
+                let selection = mode.selection().ok_or("No command selected")?;
 
-            // Run the selected command.
-            (selection.command)(app)?;
+                // Bind or run the selected command.
+                if let Some(ref binding) = mode.key_binding {
+                    app.preferences.borrow_mut().add_key_binding(&binding.mode, &binding.keys, selection.description)?;
+                    app.notification = Some(format!("Bound {} to {}", binding, selection.description));
+                } else {
+                    let command = selection.command.ok_or("The selected command needs an argument")?;
+                    match mode.line_range {
+                        Some(ref range) => run_for_each_line(app, command, range)?,
+                        None => command(app)?,
+                    }
+                }
+            }
         },
         Mode::Open(ref mut mode) => {
             let &DisplayablePath(ref path) = mode
                 .selection()
                 .ok_or("Couldn't find a selected path to open")?;
 
-            app.workspace
-                .open_buffer(&path)
-                .chain_err(|| "Couldn't open a buffer for the specified path.")?;
-            app.view.initialize_buffer(app.workspace.current_buffer().unwrap())?;
+            mode.input.remember(&mut app.history);
+            commands::jump_list::record(app);
+            let started_at = Instant::now();
+            commands::passphrase::open(app, path)?;
+            app.view.profiler.record(Activity::Io, started_at.elapsed());
+        },
+        Mode::Grep(ref mut mode) => {
+            let selection = mode.selection().ok_or("No match selected")?;
+            let path = app.workspace.path.join(&selection.path);
+
+            commands::jump_list::record(app);
+            let started_at = Instant::now();
+            commands::passphrase::open(app, &path)?;
+            app.view.profiler.record(Activity::Io, started_at.elapsed());
 
+            // Encrypted files are only opened once their passphrase is entered.
+            if let Mode::Passphrase(_) = app.mode {
+                return Ok(());
+            }
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            buffer.cursor.move_to(Position { line: selection.line, offset: selection.offset });
+        },
+        Mode::Debug(ref mut mode) => {
+            let configuration = mode.selection().ok_or("No debug configuration selected")?;
+            app.debugger.start(
+                configuration.clone(),
+                &app.workspace.path,
+                app.event_channel.clone(),
+            )?;
+        },
+        Mode::Diagnostic(ref mut mode) => {
+            let diagnostic = &mode.selection().ok_or("No diagnostic selected")?.diagnostic;
+            commands::lsp::open_location(app, &diagnostic.path, diagnostic.line, diagnostic.character)?;
+        },
+        Mode::Task(ref mut mode) => {
+            let task = mode.selection().ok_or("No task selected")?;
+            job::spawn(
+                Job::Task { command: task.command.clone() },
+                task.command.clone(),
+                app.workspace.path.clone(),
+                app.event_channel.clone(),
+            );
         },
         Mode::Theme(ref mut mode) => {
             let theme_key = mode.selection().ok_or("No theme selected")?;
             app.preferences.borrow_mut().set_theme(theme_key.as_str());
         },
+        Mode::Stash(ref mut mode) => {
+            // Unmodified buffers are reloaded once it's been applied.
+            let index = mode.selection().ok_or("No stash selected")?.index;
+            app.git_worker.apply_stash(index)?;
+        },
+        Mode::Registers(ref mut mode) => {
+            let name = mode.selection().ok_or("No register selected")?.name;
+            app.clipboard.select_register(name)?;
+            commands::buffer::paste(app)?;
+        },
+        Mode::UndoTree(ref mut mode) => {
+            let index = mode.selection().ok_or("No state selected")?.index;
+            let tree = app.undo_trees.get_mut(mode.buffer_id).ok_or("No edit history available")?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            if buffer.id != Some(mode.buffer_id) {
+                bail!("The buffer has changed since its history was listed");
+            }
+            if index < tree.states().len() {
+                util::reload::replace_content(buffer, &tree.content(index));
+                tree.set_current(index);
+            }
+        },
+        Mode::JumpList(ref mut mode) => {
+            let index = mode.selection().ok_or("No jump selected")?.index;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            let jump = app.jump_list.go_to(index, buffer).ok_or("Couldn't find the selected jump")?;
+            commands::jump_list::restore(app, &jump)?;
+        },
+        Mode::Marks(ref mut mode) => {
+            let name = mode.selection().ok_or("No mark selected")?.name;
+            commands::mark::jump_to(app, name)?;
+        },
+        Mode::Tags(ref mut mode) => {
+            let tag = mode.selection().ok_or("No tag selected")?.tag.clone();
+            commands::tag::jump_to(app, &tag)?;
+        },
+        Mode::Number(ref mut mode) => {
+            let conversion = mode.selection().ok_or("No conversion selected")?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+
+            buffer.start_operation_group();
+            buffer.delete_range(mode.range.clone());
+            buffer.cursor.move_to(mode.range.start());
+            buffer.insert(conversion.content.clone());
+            buffer.end_operation_group();
+        },
+        Mode::Transform(ref mut mode) => {
+            let transform = *mode.selection().ok_or("No transform selected")?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            let data = buffer.read(&mode.range).ok_or("Couldn't read selected data from buffer")?;
+
+            // Leave line selections' trailing newline in place.
+            let (content, newline) = if data.ends_with('\n') {
+                (&data[..data.len() - 1], "\n")
+            } else {
+                (&data[..], "")
+            };
+            let result = transform.apply(content)?;
+
+            buffer.start_operation_group();
+            buffer.delete_range(mode.range.clone());
+            buffer.cursor.move_to(mode.range.start());
+            buffer.insert(format!("{}{}", result, newline));
+            buffer.end_operation_group();
+        },
         Mode::SymbolJump(ref mut mode) => {
+            commands::jump_list::record(app);
+            let symbol = mode.selection().ok_or("Couldn't find a position for the selected symbol")?;
+            if let Some(id) = symbol.buffer {
+                if !util::select_buffer(id, app) {
+                    bail!("Couldn't find the selected symbol's buffer");
+                }
+            }
+            let position = symbol.position;
             let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-            let position = mode
-                .selection()
-                .ok_or("Couldn't find a position for the selected symbol")?
-                .position;
 
             if !buffer.cursor.move_to(position) {
                 bail!("Couldn't move to the selected symbol's position");
@@ -53,47 +201,326 @@ pub fn accept(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Runs the command with the cursor at the start of each line in the range.
+/// Lines are visited bottom-up, so that commands adding or removing lines
+/// don't shift the lines that have yet to be visited.
+fn run_for_each_line(app: &mut Application, command: Command, range: &LineRange) -> Result {
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.start_operation_group();
+
+    for line in (range.start()..=range.end()).rev() {
+        app.workspace
+            .current_buffer()
+            .ok_or(BUFFER_MISSING)?
+            .cursor
+            .move_to(Position { line, offset: 0 });
+        app.mode = Mode::Normal;
+
+        command(app).chain_err(|| format!("Command failed on line {}", line + 1))?;
+    }
+
+    app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.end_operation_group();
+    app.mode = Mode::Normal;
+
+    Ok(())
+}
+
+// Deletes, copies, or moves whole lines, leaving the cursor on
+// the first line following a deletion, or the first moved line.
+fn run_line_command(app: &mut Application, command: LineCommand) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    let lines: Vec<&str> = data.split_inclusive('\n').collect();
+    let range = command.line_range(buffer.cursor.line);
+    if range.end() >= lines.len() {
+        bail!("Line {} is past the end of the buffer", range.end() + 1);
+    }
+
+    match command.operation {
+        LineOperation::Yank | LineOperation::Delete => {
+            let content: String = lines[range.start()..=range.end()].concat();
+            if command.operation == LineOperation::Delete {
+                let deleted_range = util::inclusive_range(&range, buffer);
+                buffer.delete_range(deleted_range);
+                buffer.cursor.move_to(Position { line: range.start(), offset: 0 });
+            }
+            app.clipboard.set_content(ClipboardContent::Block(content))?;
+        },
+        LineOperation::Move(destination) => {
+            if destination > lines.len() {
+                bail!("Line {} is past the end of the buffer", destination);
+            }
+            if range.start() < destination && destination <= range.end() {
+                bail!("Can't move lines below one of themselves");
+            }
+
+            // Rearrange the span from the moved lines to the destination,
+            // keeping (or omitting) the span's final line ending as it was.
+            let (start, end, line) = if destination <= range.start() {
+                (destination, range.end() + 1, destination)
+            } else {
+                (range.start(), destination, destination - range.end() + range.start() - 1)
+            };
+            let moved = &lines[range.start()..=range.end()];
+            let others = if destination <= range.start() {
+                &lines[destination..range.start()]
+            } else {
+                &lines[range.end() + 1..destination]
+            };
+            let reordered: Vec<&str> = if destination <= range.start() {
+                moved.iter().chain(others).map(|line| line.trim_end_matches('\n')).collect()
+            } else {
+                others.iter().chain(moved).map(|line| line.trim_end_matches('\n')).collect()
+            };
+            let mut content = reordered.join("\n");
+            if lines[end - 1].ends_with('\n') {
+                content.push('\n');
+            }
+
+            buffer.start_operation_group();
+            util::replace_lines(buffer, start, &lines[start..end], &content);
+            buffer.end_operation_group();
+            buffer.cursor.move_to(Position { line, offset: 0 });
+        },
+    }
+
+    Ok(())
+}
+
+pub fn remove_selection(app: &mut Application) -> Result {
+    match app.mode {
+        Mode::Stash(ref mut mode) => {
+            // Stash indices shift once it's been dropped, so
+            // they're listed again, leaving the mode as it is.
+            let index = mode.selection().ok_or("No stash selected")?.index;
+            return app.git_worker.drop_stash(index);
+        }
+        Mode::Buffer(_) => return close_selected_buffer(app),
+        _ => bail!("Can't remove selection outside of stash or buffer mode."),
+    }
+
+    search(app)
+}
+
+// Closes the buffer selected in buffer mode, staying in the mode. Buffers with
+// unsaved changes are only closed if they're removed twice in a row.
+fn close_selected_buffer(app: &mut Application) -> Result {
+    let (id, title, confirmed) = match app.mode {
+        Mode::Buffer(ref mut mode) => {
+            let entry = mode.selection().ok_or("No buffer selected")?;
+            let (id, title) = (entry.id, entry.title.clone());
+
+            (id, title, mode.pending_close.take() == Some(id))
+        }
+        _ => bail!("Can't close selected buffer outside of buffer mode."),
+    };
+
+    if !confirmed && util::any_modified(&[id], app) {
+        if let Mode::Buffer(ref mut mode) = app.mode {
+            mode.pending_close = Some(id);
+        }
+        app.notification = Some(format!("{} has unsaved changes; remove it again to close it", title));
+
+        return Ok(());
+    }
+
+    util::close_buffers(&[id], app)?;
+    if let Mode::Buffer(ref mut mode) = app.mode {
+        mode.remove_entry(id);
+    }
+
+    Ok(())
+}
+
+pub fn sort_by_path(app: &mut Application) -> Result {
+    toggle_order(app, ResultOrder::Path)
+}
+
+pub fn sort_by_recency(app: &mut Application) -> Result {
+    toggle_order(app, ResultOrder::Recency)
+}
+
+pub fn sort_by_modification_time(app: &mut Application) -> Result {
+    toggle_order(app, ResultOrder::ModificationTime)
+}
+
+// Sorts the results by the specified order, or
+// by how well they match if it's already in use.
+fn toggle_order(app: &mut Application, order: ResultOrder) -> Result {
+    let order = match app.mode {
+        Mode::Buffer(ref mut mode) => {
+            let order = mode.order().toggle(order);
+            mode.set_order(order);
+
+            order
+        }
+        Mode::Open(ref mut mode) => {
+            let order = mode.order().toggle(order);
+            mode.set_order(order);
+
+            order
+        }
+        _ => bail!("Results can only be sorted in buffer and open modes."),
+    };
+    app.notification = Some(format!("Sorted by {}", order));
+
+    Ok(())
+}
+
+/// Lists files ignored by the workspace's repository in open mode,
+/// or stops listing them again. They're skipped by default.
+pub fn toggle_ignored_files(app: &mut Application) -> Result {
+    match app.mode {
+        Mode::Open(ref mut mode) => {
+            mode.toggle_ignored();
+            app.notification = Some(String::from(
+                if mode.includes_ignored() { "Showing ignored files" } else { "Hiding ignored files" }
+            ));
+        }
+        _ => bail!("Ignored files can only be shown in open mode."),
+    }
+
+    Ok(())
+}
+
 pub fn search(app: &mut Application) -> Result {
+    let started_at = Instant::now();
     match app.mode {
+        Mode::Branch(ref mut mode) => mode.search(),
         Mode::Command(ref mut mode) => mode.search(),
         Mode::Open(ref mut mode) => mode.search(),
         Mode::Theme(ref mut mode) => mode.search(),
+        Mode::Grep(ref mut mode) => mode.search(),
+        Mode::Buffer(ref mut mode) => mode.search(),
+        Mode::Number(ref mut mode) => mode.search(),
+        Mode::Transform(ref mut mode) => mode.search(),
+        Mode::Debug(ref mut mode) => mode.search(),
+        Mode::Diagnostic(ref mut mode) => mode.search(),
+        Mode::Task(ref mut mode) => mode.search(),
+        Mode::Stash(ref mut mode) => mode.search(),
+        Mode::UndoTree(ref mut mode) => mode.search(),
+        Mode::JumpList(ref mut mode) => mode.search(),
+        Mode::Marks(ref mut mode) => mode.search(),
+        Mode::Tags(ref mut mode) => mode.search(),
+        Mode::Registers(ref mut mode) => mode.search(),
         Mode::SymbolJump(ref mut mode) => mode.search(),
         _ => bail!("Can't search outside of search select mode."),
     };
+    app.view.profiler.record(Activity::Matching, started_at.elapsed());
+    preview_theme(app);
 
     Ok(())
 }
 
 pub fn select_next(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Branch(ref mut mode) => mode.select_next(),
         Mode::Command(ref mut mode) => mode.select_next(),
         Mode::Open(ref mut mode) => mode.select_next(),
         Mode::Theme(ref mut mode) => mode.select_next(),
+        Mode::Grep(ref mut mode) => mode.select_next(),
+        Mode::Buffer(ref mut mode) => mode.select_next(),
+        Mode::Number(ref mut mode) => mode.select_next(),
+        Mode::Transform(ref mut mode) => mode.select_next(),
+        Mode::Debug(ref mut mode) => mode.select_next(),
+        Mode::Diagnostic(ref mut mode) => mode.select_next(),
+        Mode::Task(ref mut mode) => mode.select_next(),
+        Mode::Stash(ref mut mode) => mode.select_next(),
+        Mode::UndoTree(ref mut mode) => mode.select_next(),
+        Mode::JumpList(ref mut mode) => mode.select_next(),
+        Mode::Marks(ref mut mode) => mode.select_next(),
+        Mode::Tags(ref mut mode) => mode.select_next(),
+        Mode::Registers(ref mut mode) => mode.select_next(),
         Mode::SymbolJump(ref mut mode) => mode.select_next(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
+    preview_theme(app);
 
     Ok(())
 }
 
 pub fn select_previous(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Branch(ref mut mode) => mode.select_previous(),
         Mode::Command(ref mut mode) => mode.select_previous(),
         Mode::Open(ref mut mode) => mode.select_previous(),
         Mode::Theme(ref mut mode) => mode.select_previous(),
+        Mode::Grep(ref mut mode) => mode.select_previous(),
+        Mode::Buffer(ref mut mode) => mode.select_previous(),
+        Mode::Number(ref mut mode) => mode.select_previous(),
+        Mode::Transform(ref mut mode) => mode.select_previous(),
+        Mode::Debug(ref mut mode) => mode.select_previous(),
+        Mode::Diagnostic(ref mut mode) => mode.select_previous(),
+        Mode::Task(ref mut mode) => mode.select_previous(),
+        Mode::Stash(ref mut mode) => mode.select_previous(),
+        Mode::UndoTree(ref mut mode) => mode.select_previous(),
+        Mode::JumpList(ref mut mode) => mode.select_previous(),
+        Mode::Marks(ref mut mode) => mode.select_previous(),
+        Mode::Tags(ref mut mode) => mode.select_previous(),
+        Mode::Registers(ref mut mode) => mode.select_previous(),
         Mode::SymbolJump(ref mut mode) => mode.select_previous(),
         _ => bail!("Can't change selection outside of search select mode."),
     }
+    preview_theme(app);
 
     Ok(())
 }
 
+/// Selects the listed result at the index, returning false if there isn't one.
+pub(crate) fn select_index(app: &mut Application, index: usize) -> crate::errors::Result<bool> {
+    let selected = match app.mode {
+        Mode::Branch(ref mut mode) => mode.select_index(index),
+        Mode::Command(ref mut mode) => mode.select_index(index),
+        Mode::Open(ref mut mode) => mode.select_index(index),
+        Mode::Theme(ref mut mode) => mode.select_index(index),
+        Mode::Grep(ref mut mode) => mode.select_index(index),
+        Mode::Buffer(ref mut mode) => mode.select_index(index),
+        Mode::Number(ref mut mode) => mode.select_index(index),
+        Mode::Transform(ref mut mode) => mode.select_index(index),
+        Mode::Debug(ref mut mode) => mode.select_index(index),
+        Mode::Diagnostic(ref mut mode) => mode.select_index(index),
+        Mode::Task(ref mut mode) => mode.select_index(index),
+        Mode::Stash(ref mut mode) => mode.select_index(index),
+        Mode::UndoTree(ref mut mode) => mode.select_index(index),
+        Mode::JumpList(ref mut mode) => mode.select_index(index),
+        Mode::Marks(ref mut mode) => mode.select_index(index),
+        Mode::Tags(ref mut mode) => mode.select_index(index),
+        Mode::Registers(ref mut mode) => mode.select_index(index),
+        Mode::SymbolJump(ref mut mode) => mode.select_index(index),
+        _ => bail!("Can't change selection outside of search select mode."),
+    };
+    preview_theme(app);
+
+    Ok(selected)
+}
+
+// Applies the selected theme provisionally while browsing themes (or the
+// original one, if nothing matches the query), so that it's seen in use.
+fn preview_theme(app: &mut Application) {
+    if let Mode::Theme(ref mode) = app.mode {
+        let theme = mode.selection().unwrap_or(&mode.original_theme);
+        app.preferences.borrow_mut().set_theme(theme.as_str());
+    }
+}
+
 pub fn enable_insert(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Branch(ref mut mode) => mode.set_insert_mode(true),
         Mode::Command(ref mut mode) => mode.set_insert_mode(true),
         Mode::Open(ref mut mode) => mode.set_insert_mode(true),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Grep(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Buffer(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Number(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Transform(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Debug(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Diagnostic(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Task(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Stash(ref mut mode) => mode.set_insert_mode(true),
+        Mode::UndoTree(ref mut mode) => mode.set_insert_mode(true),
+        Mode::JumpList(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Marks(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Tags(ref mut mode) => mode.set_insert_mode(true),
+        Mode::Registers(ref mut mode) => mode.set_insert_mode(true),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(true),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
@@ -103,9 +530,23 @@ pub fn enable_insert(app: &mut Application) -> Result {
 
 pub fn disable_insert(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Branch(ref mut mode) => mode.set_insert_mode(false),
         Mode::Command(ref mut mode) => mode.set_insert_mode(false),
         Mode::Open(ref mut mode) => mode.set_insert_mode(false),
         Mode::Theme(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Grep(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Buffer(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Number(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Transform(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Debug(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Diagnostic(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Task(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Stash(ref mut mode) => mode.set_insert_mode(false),
+        Mode::UndoTree(ref mut mode) => mode.set_insert_mode(false),
+        Mode::JumpList(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Marks(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Tags(ref mut mode) => mode.set_insert_mode(false),
+        Mode::Registers(ref mut mode) => mode.set_insert_mode(false),
         Mode::SymbolJump(ref mut mode) => mode.set_insert_mode(false),
         _ => bail!("Can't change search insert state outside of search select mode"),
     }
@@ -113,26 +554,25 @@ pub fn disable_insert(app: &mut Application) -> Result {
     Ok(())
 }
 
-pub fn push_search_char(app: &mut Application) -> Result {
-    if let Some(Key::Char(c)) = *app.view.last_key() {
-        match app.mode {
-            Mode::Command(ref mut mode) => mode.push_search_char(c),
-            Mode::Open(ref mut mode) => mode.push_search_char(c),
-            Mode::Theme(ref mut mode) => mode.push_search_char(c),
-            Mode::SymbolJump(ref mut mode) => mode.push_search_char(c),
-            _ => bail!("Can't push search character outside of search select mode"),
-        }
-    }
-
-    // Re-run the search.
-    search(app)
-}
-
 pub fn pop_search_token(app: &mut Application) -> Result {
     match app.mode {
+        Mode::Branch(ref mut mode) => mode.pop_search_token(),
         Mode::Command(ref mut mode) => mode.pop_search_token(),
         Mode::Open(ref mut mode) => mode.pop_search_token(),
         Mode::Theme(ref mut mode) => mode.pop_search_token(),
+        Mode::Grep(ref mut mode) => mode.pop_search_token(),
+        Mode::Buffer(ref mut mode) => mode.pop_search_token(),
+        Mode::Number(ref mut mode) => mode.pop_search_token(),
+        Mode::Transform(ref mut mode) => mode.pop_search_token(),
+        Mode::Debug(ref mut mode) => mode.pop_search_token(),
+        Mode::Diagnostic(ref mut mode) => mode.pop_search_token(),
+        Mode::Task(ref mut mode) => mode.pop_search_token(),
+        Mode::Stash(ref mut mode) => mode.pop_search_token(),
+        Mode::UndoTree(ref mut mode) => mode.pop_search_token(),
+        Mode::JumpList(ref mut mode) => mode.pop_search_token(),
+        Mode::Marks(ref mut mode) => mode.pop_search_token(),
+        Mode::Tags(ref mut mode) => mode.pop_search_token(),
+        Mode::Registers(ref mut mode) => mode.pop_search_token(),
         Mode::SymbolJump(ref mut mode) => mode.pop_search_token(),
         _ => bail!("Can't pop search token outside of search select mode"),
     }
@@ -143,10 +583,23 @@ pub fn pop_search_token(app: &mut Application) -> Result {
 
 pub fn step_back(app: &mut Application) -> Result {
     let result_count = match app.mode {
+        Mode::Branch(ref mut mode) => mode.results().count(),
         Mode::Command(ref mut mode) => mode.results().count(),
         Mode::Open(ref mut mode) => mode.results().count(),
         Mode::Theme(ref mut mode) => mode.results().count(),
+        Mode::Grep(ref mut mode) => mode.results().count(),
+        Mode::Buffer(ref mut mode) => mode.results().count(),
+        Mode::Number(ref mut mode) => mode.results().count(),
+        Mode::Transform(ref mut mode) => mode.results().count(),
+        Mode::Debug(ref mut mode) => mode.results().count(),
+        Mode::Diagnostic(ref mut mode) => mode.results().count(),
+        Mode::Task(ref mut mode) => mode.results().count(),
+        Mode::Stash(ref mut mode) => mode.results().count(),
+        Mode::UndoTree(ref mut mode) => mode.results().count(),
+        Mode::JumpList(ref mut mode) => mode.results().count(),
+        Mode::Registers(ref mut mode) => mode.results().count(),
         Mode::SymbolJump(ref mut mode) => mode.results().count(),
+        Mode::Tags(ref mut mode) => mode.results().count(),
         _ => bail!("Can't pop search token outside of search select mode"),
     };
 
@@ -156,3 +609,199 @@ pub fn step_back(app: &mut Application) -> Result {
         disable_insert(app)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::{ClipboardContent, Mode, Preferences};
+    use crate::models::application::modes::{GrepMatch, GrepMode, SearchSelectConfig, SearchSelectMode};
+    use crate::yaml::yaml::YamlLoader;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+
+    #[test]
+    fn theme_mode_previews_the_selected_theme_until_it_is_left() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let original_theme = app.preferences.borrow().theme().to_string();
+        commands::application::switch_to_theme_mode(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), original_theme);
+
+        commands::search_select::select_next(&mut app).unwrap();
+        let previewed_theme = app.preferences.borrow().theme().to_string();
+        assert_ne!(previewed_theme, original_theme);
+        commands::application::switch_to_normal_mode(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), original_theme);
+
+        commands::application::switch_to_theme_mode(&mut app).unwrap();
+        commands::search_select::select_next(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+        assert_eq!(app.preferences.borrow().theme(), previewed_theme);
+    }
+
+    #[test]
+    fn remove_selection_closes_buffers_in_buffer_mode_confirming_unsaved_changes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut modified = Buffer::new();
+        modified.insert("amp");
+        app.workspace.add_buffer(modified);
+        app.workspace.add_buffer(Buffer::new());
+        commands::application::switch_to_buffer_mode(&mut app).unwrap();
+
+        // The modified buffer is listed first, since the current buffer is listed last.
+        commands::search_select::remove_selection(&mut app).unwrap();
+        assert!(app.notification.as_ref().unwrap().contains("unsaved changes"));
+        commands::search_select::remove_selection(&mut app).unwrap();
+
+        assert_eq!(app.notification, Some(String::from("Closed 1 buffer")));
+        assert!(app.workspace.current_buffer().unwrap().data().is_empty());
+        if let Mode::Buffer(ref mode) = app.mode {
+            assert_eq!(mode.results().count(), 1);
+        } else {
+            panic!("Not in buffer mode");
+        }
+    }
+
+    #[test]
+    fn sort_by_path_toggles_sorting_buffer_mode_results_by_path() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        for path in &["b", "a"] {
+            let mut buffer = Buffer::new();
+            buffer.path = Some(PathBuf::from(path));
+            app.workspace.add_buffer(buffer);
+        }
+        commands::application::switch_to_buffer_mode(&mut app).unwrap();
+        let titles = |app: &Application| match app.mode {
+            Mode::Buffer(ref mode) => mode.results().map(|entry| entry.title.clone()).collect::<Vec<_>>(),
+            _ => panic!("Not in buffer mode"),
+        };
+        assert_eq!(titles(&app), vec!["b", "a"]);
+
+        commands::search_select::sort_by_path(&mut app).unwrap();
+        assert_eq!(titles(&app), vec!["a", "b"]);
+        assert_eq!(app.notification, Some(String::from("Sorted by path")));
+
+        commands::search_select::sort_by_path(&mut app).unwrap();
+        assert_eq!(titles(&app), vec!["b", "a"]);
+    }
+
+    #[test]
+    fn accept_restores_undone_changes_selected_in_undo_tree_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        let buffer = app.workspace.current_buffer().unwrap();
+        app.view.initialize_buffer(buffer).unwrap();
+        app.undo_trees.track(buffer);
+        buffer.insert("amp");
+        app.undo_trees.record(buffer, app.view.revision(buffer).unwrap());
+
+        // Replace the undone change with another.
+        buffer.undo();
+        app.undo_trees.record(buffer, app.view.revision(buffer).unwrap());
+        buffer.insert("text");
+        app.undo_trees.record(buffer, app.view.revision(buffer).unwrap());
+
+        commands::application::switch_to_undo_tree_mode(&mut app).unwrap();
+        commands::search_select::select_previous(&mut app).unwrap();
+        commands::search_select::accept(&mut app).unwrap();
+
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amp");
+        assert_eq!(app.undo_trees.get(buffer.id.unwrap()).unwrap().current(), 1);
+
+        // Restoring a state is itself undoable.
+        buffer.undo();
+        assert_eq!(buffer.data(), "text");
+    }
+
+    fn run_line_command(app: &mut Application, input: &str) -> crate::commands::Result {
+        commands::application::switch_to_command_mode(app).unwrap();
+        if let Mode::Command(ref mut mode) = app.mode {
+            mode.query().insert_str(input);
+        }
+        commands::search_select::search(app).unwrap();
+        commands::search_select::accept(app)
+    }
+
+    #[test]
+    fn accept_runs_line_commands_entered_in_command_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nrust\ntext\nbuffer");
+        app.workspace.add_buffer(buffer);
+
+        run_line_command(&mut app, "m 2,3 5").unwrap();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amp\ntext\nbuffer\neditor\nrust");
+        assert_eq!(buffer.cursor.line, 3);
+
+        run_line_command(&mut app, "m 0").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\namp\ntext\nbuffer\nrust");
+
+        run_line_command(&mut app, "d 2,3").unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\nbuffer\nrust");
+        assert_eq!(*app.clipboard.get_content(), ClipboardContent::Block(String::from("amp\ntext\n")));
+
+        run_line_command(&mut app, "y 3").unwrap();
+        assert_eq!(*app.clipboard.get_content(), ClipboardContent::Block(String::from("rust")));
+
+        assert!(run_line_command(&mut app, "d 4").is_err());
+        assert!(run_line_command(&mut app, "m 1,3 2").is_err());
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "editor\nbuffer\nrust");
+    }
+
+    #[test]
+    fn accept_runs_commands_with_arguments_entered_in_command_mode() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        // Selecting a command that takes an argument fills in its name.
+        run_line_command(&mut app, "application::set").unwrap();
+        match app.mode {
+            Mode::Command(ref mut mode) => assert_eq!(&**mode.query(), "application::set "),
+            _ => panic!("Not in command mode"),
+        }
+
+        commands::search_select::accept(&mut app).unwrap_err();
+        run_line_command(&mut app, "application::set tab_width=7").unwrap();
+        assert_eq!(app.preferences.borrow().tab_width(None), 7);
+        assert!(run_line_command(&mut app, "application::set theme=missing").is_err());
+    }
+
+    #[test]
+    fn accept_opens_grep_matches_prompting_for_encrypted_files_passphrases() {
+        // Encrypts with rot13, keyed by a passphrase that must be "amp".
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let command = "[sh, -c, 'read key && [ \\"$key\\" = amp ] && tr a-z n-za-m']";
+        let data = YamlLoader::load_from_str(&format!(
+            "types:\n  rot:\n    decrypt_command: {}\n    encrypt_command: {}",
+            command, command
+        )).unwrap();
+        app.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        let directory = Path::new(env!("OUT_DIR"));
+        fs::write(directory.join("grep_accept.txt"), "amp\neditor\n").unwrap();
+        fs::write(directory.join("grep_accept.rot"), "rqvgbe\n").unwrap();
+
+        for (path, offset) in &[("grep_accept.txt", 2), ("grep_accept.rot", 0)] {
+            let mut mode = GrepMode::new(
+                directory.to_path_buf(),
+                false,
+                app.event_channel.clone(),
+                SearchSelectConfig::default(),
+            );
+            let selection = GrepMatch { path: directory.join(path), line: 1, offset: *offset, preview: String::new() };
+            mode.add_results(0, vec![selection], true);
+            app.mode = Mode::Grep(mode);
+            commands::search_select::accept(&mut app).unwrap();
+        }
+
+        assert!(matches!(app.mode, Mode::Passphrase(_)));
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.data(), "amp\neditor\n");
+        assert_eq!(*buffer.cursor, Position { line: 1, offset: 2 });
+    }
+}
@@ -0,0 +1,158 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::search::REGEX_PREFIX;
+use crate::util::{self, PositionMap};
+use regex::{self, Regex};
+use scribe::buffer::Position;
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Adds a cursor at the start of the next match for the selection or, outside
+/// of select mode, the current search query. The selection becomes the search
+/// query, so that running the command again keeps adding cursors at matches.
+pub fn add_cursor_at_next_match(app: &mut Application) -> Result {
+    if let Mode::Select(_) = app.mode {
+        let range = util::selected_range(app)?;
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let query = buffer.read(&range).ok_or("Couldn't read the selection")?;
+        if query.is_empty() {
+            bail!(SEARCH_QUERY_MISSING);
+        }
+
+        buffer.cursor.move_to(range.start());
+        app.search_query = Some(query);
+        commands::application::switch_to_normal_mode(app)?;
+    }
+
+    let query = app.search_query.clone().ok_or(SEARCH_QUERY_MISSING)?;
+    let pattern = match query.strip_prefix(REGEX_PREFIX) {
+        Some(pattern) => format!("(?m){}", pattern),
+        None => regex::quote(&query),
+    };
+    let regex = Regex::new(&pattern).chain_err(|| format!("Invalid pattern: {}", query))?;
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or("Buffer has no ID")?;
+    let cursors = app.multi_cursors.entry(id).or_default();
+    let data = buffer.data();
+    let positions = PositionMap::new(&data);
+    let matches: Vec<Position> = regex
+        .find_iter(&data)
+        .filter(|&(start, end)| start < end)
+        .map(|(start, _)| positions.position(start))
+        .filter(|position| *position != *buffer.cursor && !cursors.positions.contains(position))
+        .collect();
+
+    // Continue after the last cursor, wrapping around to the top of the buffer.
+    let last_cursor = match cursors.positions.last() {
+        Some(last) if *last > *buffer.cursor => *last,
+        _ => *buffer.cursor,
+    };
+    let position = matches
+        .iter()
+        .find(|position| **position > last_cursor)
+        .or_else(|| matches.first())
+        .cloned()
+        .ok_or("No more matches")?;
+    cursors.add(position);
+
+    Ok(())
+}
+
+/// Adds a cursor to each selected line (other than the cursor's),
+/// at the same column as the cursor, where the line is long enough.
+pub fn add_cursors_to_lines(app: &mut Application) -> Result {
+    let anchor = match app.mode {
+        Mode::SelectLine(ref mode) => mode.anchor,
+        _ => bail!("Can't add cursors to lines outside of select line mode"),
+    };
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id.ok_or("Buffer has no ID")?;
+    let cursors = app.multi_cursors.entry(id).or_default();
+    let (first_line, last_line) = (cmp::min(anchor, buffer.cursor.line), cmp::max(anchor, buffer.cursor.line));
+    let column = buffer.cursor.offset;
+
+    for (line, content) in buffer.data().lines().enumerate().take(last_line + 1).skip(first_line) {
+        let length = content.graphemes(true).count();
+        if line != buffer.cursor.line && length >= column {
+            cursors.add(Position { line, offset: column });
+        }
+    }
+
+    commands::application::switch_to_normal_mode(app)
+}
+
+/// Removes the current buffer's extra cursors.
+pub fn remove_extra_cursors(app: &mut Application) -> Result {
+    let id = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?.id;
+    if let Some(id) = id {
+        app.multi_cursors.remove(&id);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn set_up_application(content: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    fn extra_cursors(app: &mut Application) -> Vec<Position> {
+        let id = app.workspace.current_buffer().unwrap().id.unwrap();
+        app.multi_cursors.get(&id).map(|cursors| cursors.positions.clone()).unwrap_or_default()
+    }
+
+    #[test]
+    fn add_cursor_at_next_match_uses_the_selection_and_wraps_around() {
+        let mut app = set_up_application("amp\nedit amp\namp");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 5 });
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        for _ in 0..3 {
+            commands::cursor::move_right(&mut app).unwrap();
+        }
+
+        commands::select::add_cursor_at_next_match(&mut app).unwrap();
+        assert!(matches!(app.mode, Mode::Normal));
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 1, offset: 5 });
+        assert_eq!(extra_cursors(&mut app), vec![Position { line: 2, offset: 0 }]);
+
+        commands::select::add_cursor_at_next_match(&mut app).unwrap();
+        assert_eq!(extra_cursors(&mut app), vec![Position { line: 0, offset: 0 }, Position { line: 2, offset: 0 }]);
+        assert!(commands::select::add_cursor_at_next_match(&mut app).is_err());
+    }
+
+    #[test]
+    fn add_cursors_to_lines_skips_short_lines_and_edits_apply_to_all_cursors() {
+        let mut app = set_up_application("amp\n\namp\namp");
+        commands::cursor::move_right(&mut app).unwrap();
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::cursor::move_to_last_line(&mut app).unwrap();
+        commands::select::add_cursors_to_lines(&mut app).unwrap();
+        assert_eq!(extra_cursors(&mut app), vec![Position { line: 0, offset: 1 }, Position { line: 2, offset: 1 }]);
+
+        commands::application::switch_to_insert_mode(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('x'));
+        commands::buffer::insert_char(&mut app).unwrap();
+        commands::buffer::backspace(&mut app).unwrap();
+        commands::buffer::insert_char(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "axmp\n\naxmp\naxmp");
+
+        commands::buffer::undo(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp\n\namp\namp");
+        assert!(extra_cursors(&mut app).is_empty());
+    }
+}
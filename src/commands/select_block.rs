@@ -0,0 +1,270 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::input::Key;
+use crate::models::application::{Application, ClipboardContent, Mode};
+use crate::models::application::modes::{BlockInsert, SelectBlockMode};
+use scribe::Buffer;
+use scribe::buffer::Position;
+use super::application;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub fn move_left(app: &mut Application) -> Result {
+    if let Mode::SelectBlock(ref mut mode) = app.mode {
+        mode.column = mode.column.saturating_sub(1);
+    } else {
+        bail!("Can't move block cursor outside of select block mode");
+    }
+    sync_cursor(app)
+}
+
+pub fn move_right(app: &mut Application) -> Result {
+    if let Mode::SelectBlock(ref mut mode) = app.mode {
+        // The column isn't limited by the line's length.
+        mode.column += 1;
+    } else {
+        bail!("Can't move block cursor outside of select block mode");
+    }
+    sync_cursor(app)
+}
+
+pub fn move_up(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let line = buffer.cursor.line.saturating_sub(1);
+    buffer.cursor.move_to(Position { line, offset: 0 });
+    sync_cursor(app)
+}
+
+pub fn move_down(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let line = buffer.cursor.line + 1;
+    if line < buffer.line_count() {
+        buffer.cursor.move_to(Position { line, offset: 0 });
+    }
+    sync_cursor(app)
+}
+
+pub fn copy(app: &mut Application) -> Result {
+    copy_to_clipboard(app)?;
+    application::switch_to_normal_mode(app)
+}
+
+pub fn delete(app: &mut Application) -> Result {
+    let _ = copy_to_clipboard(app);
+    delete_block(app)?;
+    application::switch_to_normal_mode(app)?;
+    commands::view::scroll_to_cursor(app)
+}
+
+/// Starts inserting content before the block on each of its lines.
+pub fn insert(app: &mut Application) -> Result {
+    if let Mode::SelectBlock(ref mut mode) = app.mode {
+        let (column, _) = mode.columns();
+        mode.insert = Some(BlockInsert { column, content: String::new() });
+    } else {
+        bail!("Can't insert outside of select block mode");
+    }
+
+    Ok(())
+}
+
+/// Starts inserting content after the block on each of its lines,
+/// padding those that don't extend that far with spaces.
+pub fn append(app: &mut Application) -> Result {
+    if let Mode::SelectBlock(ref mut mode) = app.mode {
+        let (_, column) = mode.columns();
+        mode.insert = Some(BlockInsert { column, content: String::new() });
+    } else {
+        bail!("Can't append outside of select block mode");
+    }
+
+    Ok(())
+}
+
+/// Replaces the block with content inserted on each of its lines.
+pub fn change(app: &mut Application) -> Result {
+    let _ = copy_to_clipboard(app);
+    delete_block(app)?;
+    insert(app)
+}
+
+pub fn push_char(app: &mut Application) -> Result {
+    let key = app.view.last_key().as_ref().ok_or("View hasn't tracked a key press")?;
+
+    if let Key::Char(c) = *key {
+        if let Mode::SelectBlock(SelectBlockMode { insert: Some(ref mut insert), .. }) = app.mode {
+            insert.content.push(c);
+        } else {
+            bail!("Can't push character outside of block insert mode");
+        }
+    } else {
+        bail!("Last key press wasn't a character")
+    }
+
+    Ok(())
+}
+
+pub fn pop_char(app: &mut Application) -> Result {
+    if let Mode::SelectBlock(SelectBlockMode { insert: Some(ref mut insert), .. }) = app.mode {
+        insert.content.pop();
+    } else {
+        bail!("Can't pop character outside of block insert mode");
+    }
+
+    Ok(())
+}
+
+/// Inserts the pending content on each line of the block.
+pub fn accept_insert(app: &mut Application) -> Result {
+    let (first_line, last_line, insert) = match app.mode {
+        Mode::SelectBlock(ref mut mode) => {
+            let insert = mode.insert.take().ok_or("Not inserting content")?;
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            let (first_line, last_line) = mode.lines(&buffer.cursor);
+
+            (first_line, last_line, insert)
+        }
+        _ => bail!("Can't insert content outside of select block mode"),
+    };
+
+    // Don't pad lines if there's nothing to insert.
+    if !insert.content.is_empty() {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        buffer.start_operation_group();
+        for line in first_line..=last_line {
+            insert_at_column(buffer, line, insert.column, &insert.content);
+        }
+        buffer.end_operation_group();
+        buffer.cursor.move_to(Position { line: first_line, offset: insert.column });
+    }
+
+    application::switch_to_normal_mode(app)?;
+    commands::view::scroll_to_cursor(app)
+}
+
+fn copy_to_clipboard(app: &mut Application) -> Result {
+    let content = match app.mode {
+        Mode::SelectBlock(ref mode) => {
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            mode.ranges(buffer)
+                .into_iter()
+                .map(|range| buffer.read(&range).unwrap_or_default())
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+        _ => bail!("Can't copy block outside of select block mode"),
+    };
+
+    app.clipboard.set_content(ClipboardContent::Inline(content))
+}
+
+fn delete_block(app: &mut Application) -> Result {
+    let ranges = match app.mode {
+        Mode::SelectBlock(ref mode) => {
+            let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+            mode.ranges(buffer)
+        }
+        _ => bail!("Can't delete block outside of select block mode"),
+    };
+    let start = ranges.first().map(|range| range.start()).ok_or("Block is empty")?;
+
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    buffer.start_operation_group();
+    for range in ranges.into_iter().rev() {
+        buffer.delete_range(range);
+    }
+    buffer.end_operation_group();
+    buffer.cursor.move_to(start);
+
+    Ok(())
+}
+
+// Inserts content at a virtual column, padding
+// the line with spaces if it's shorter than that.
+fn insert_at_column(buffer: &mut Buffer, line: usize, column: usize, content: &str) {
+    let length = buffer
+        .data()
+        .lines()
+        .nth(line)
+        .map(|content| content.graphemes(true).count())
+        .unwrap_or(0);
+    let padding = " ".repeat(column.saturating_sub(length));
+
+    buffer.cursor.move_to(Position { line, offset: column.min(length) });
+    buffer.insert(format!("{}{}", padding, content));
+}
+
+// Keeps the buffer cursor as close to the virtual column as the line allows.
+fn sync_cursor(app: &mut Application) -> Result {
+    if let Mode::SelectBlock(ref mode) = app.mode {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let line = buffer.cursor.line;
+        let length = buffer
+            .data()
+            .lines()
+            .nth(line)
+            .map(|content| content.graphemes(true).count())
+            .unwrap_or(0);
+        buffer.cursor.move_to(Position { line, offset: mode.column.min(length) });
+    }
+
+    commands::view::scroll_to_cursor(app).chain_err(|| SCROLL_TO_CURSOR_FAILED)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::input::Key;
+    use crate::models::application::{Application, ClipboardContent, Mode};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn set_up_application(content: &str) -> Application {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+        app.workspace.add_buffer(buffer);
+
+        app
+    }
+
+    #[test]
+    fn append_pads_short_lines_with_spaces() {
+        let mut app = set_up_application("amp editor\nab\nabc");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 2 });
+        commands::application::switch_to_select_block_mode(&mut app).unwrap();
+
+        // Extend the block beyond the end of the shorter lines.
+        commands::select_block::move_down(&mut app).unwrap();
+        commands::select_block::move_down(&mut app).unwrap();
+        for _ in 0..2 {
+            commands::select_block::move_right(&mut app).unwrap();
+        }
+        if let Mode::SelectBlock(ref mode) = app.mode {
+            assert_eq!(mode.columns(), (2, 5));
+        }
+
+        commands::select_block::append(&mut app).unwrap();
+        app.view.last_key = Some(Key::Char('|'));
+        commands::select_block::push_char(&mut app).unwrap();
+        commands::select_block::accept_insert(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "amp e|ditor\nab   |\nabc  |");
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+
+    #[test]
+    fn delete_removes_and_copies_block_content() {
+        let mut app = set_up_application("amp editor\nab\nabcde");
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 1 });
+        commands::application::switch_to_select_block_mode(&mut app).unwrap();
+        commands::select_block::move_down(&mut app).unwrap();
+        commands::select_block::move_down(&mut app).unwrap();
+        commands::select_block::move_right(&mut app).unwrap();
+
+        commands::select_block::delete(&mut app).unwrap();
+
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "a editor\na\nade");
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 1 });
+        assert_eq!(app.clipboard.get_content(), &ClipboardContent::Inline(String::from("mp\nb\nbc")));
+    }
+}
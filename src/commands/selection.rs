@@ -2,8 +2,9 @@ use crate::models::application::{Application, ClipboardContent, Mode};
 use scribe::buffer::{LineRange, Range};
 use super::application;
 use crate::errors::*;
+use crate::errors;
 use crate::commands::{self, Result};
-use crate::util;
+use crate::util::{self, expression};
 
 pub fn delete(app: &mut Application) -> Result {
     if let Some(buffer) = app.workspace.current_buffer() {
@@ -52,6 +53,15 @@ pub fn copy(app: &mut Application) -> Result {
     application::switch_to_normal_mode(app)
 }
 
+/// Adds the selection to the end of the clipboard's content, rather than
+/// replacing it, to gather several snippets to be pasted together.
+pub fn copy_append(app: &mut Application) -> Result {
+    let content = selected_content(app)?;
+    let existing = app.clipboard.get_content().clone();
+    app.clipboard.set_content(existing.append(content))?;
+    application::switch_to_normal_mode(app)
+}
+
 pub fn select_all(app: &mut Application) -> Result {
     app.workspace
         .current_buffer()
@@ -68,7 +78,47 @@ pub fn select_all(app: &mut Application) -> Result {
     Ok(())
 }
 
+/// Hides everything but the selected lines, protecting the rest
+/// of the buffer from editing until it's widened (view::widen).
+pub fn narrow(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let lines = match app.mode {
+        Mode::Select(ref mode) => LineRange::new(mode.anchor.line, buffer.cursor.line),
+        Mode::SelectLine(ref mode) => LineRange::new(mode.anchor, buffer.cursor.line),
+        _ => bail!("Can't narrow to a selection outside of select modes"),
+    };
+    app.view.narrow(buffer, &lines)?;
+
+    application::switch_to_normal_mode(app)?;
+    commands::view::scroll_to_cursor(app)
+}
+
+pub fn evaluate(app: &mut Application) -> Result {
+    let range = util::selected_range(app)?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.read(&range).ok_or("Couldn't read selected data from buffer")?;
+    let result = expression::evaluate(&data)?;
+
+    // Preserve trailing whitespace (e.g. line selections' newline).
+    let trailing_whitespace = &data[data.trim_end().len()..];
+
+    buffer.start_operation_group();
+    buffer.delete_range(range.clone());
+    buffer.cursor.move_to(range.start());
+    buffer.insert(format!("{}{}", result, trailing_whitespace));
+    buffer.end_operation_group();
+
+    application::switch_to_normal_mode(app)
+}
+
 fn copy_to_clipboard(app: &mut Application) -> Result {
+    let content = selected_content(app)?;
+    app.clipboard.set_content(content)
+}
+
+// The selected text, qualified as inline or block content
+// depending on whether whole lines are being selected.
+fn selected_content(app: &mut Application) -> errors::Result<ClipboardContent> {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
 
     match app.mode {
@@ -78,7 +128,7 @@ fn copy_to_clipboard(app: &mut Application) -> Result {
 
             let data = buffer.read(&selected_range.clone())
                 .ok_or("Couldn't read selected data from buffer")?;
-            app.clipboard.set_content(ClipboardContent::Inline(data))?;
+            Ok(ClipboardContent::Inline(data))
         }
         Mode::SelectLine(ref mode) => {
             let selected_range = util::inclusive_range(
@@ -92,18 +142,16 @@ fn copy_to_clipboard(app: &mut Application) -> Result {
 
             let data = buffer.read(&selected_range.clone())
                 .ok_or("Couldn't read selected data from buffer")?;
-            app.clipboard.set_content(ClipboardContent::Block(data))?;
+            Ok(ClipboardContent::Block(data))
         }
         _ => bail!("Can't copy data to clipboard outside of select modes"),
-    };
-
-    Ok(())
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::commands;
-    use crate::models::application::{Application, Mode};
+    use crate::models::application::{Application, ClipboardContent, Mode};
     use scribe::Buffer;
     use scribe::buffer::Position;
 
@@ -139,6 +187,53 @@ mod tests {
         assert_eq!(app.workspace.current_buffer().unwrap().cursor.line, 2);
     }
 
+    #[test]
+    fn evaluate_replaces_the_selection_with_its_result() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("width: 80 * 2;\n(1 + 2) / 2\namp");
+        buffer.cursor.move_to(Position { line: 0, offset: 7 });
+        app.workspace.add_buffer(buffer);
+
+        // Evaluate a partial line selection.
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 13 });
+        commands::selection::evaluate(&mut app).unwrap();
+
+        // Evaluate a full line selection, which includes its newline.
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 0 });
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::selection::evaluate(&mut app).unwrap();
+
+        assert_eq!(
+            app.workspace.current_buffer().unwrap().data(),
+            String::from("width: 160;\n1.5\namp")
+        );
+    }
+
+    #[test]
+    fn copy_append_adds_the_selection_to_the_clipboard() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nbuffer");
+        app.workspace.add_buffer(buffer);
+        app.clipboard.set_content(ClipboardContent::Inline(String::from("text "))).unwrap();
+
+        commands::application::switch_to_select_mode(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 0, offset: 3 });
+        commands::selection::copy_append(&mut app).unwrap();
+        assert_eq!(*app.clipboard.get_content(), ClipboardContent::Inline(String::from("text amp")));
+
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 2, offset: 0 });
+        commands::application::switch_to_select_line_mode(&mut app).unwrap();
+        commands::selection::copy_append(&mut app).unwrap();
+        assert_eq!(
+            *app.clipboard.get_content(),
+            ClipboardContent::Block(String::from("text amp\nbuffer"))
+        );
+        assert!(matches!(app.mode, Mode::Normal));
+    }
+
     #[test]
     fn delete_removes_the_selection_in_select_mode() {
         let mut app = Application::new(&Vec::new()).unwrap();
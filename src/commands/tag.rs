@@ -0,0 +1,98 @@
+use crate::errors::*;
+use crate::commands::{self, Result};
+use crate::models::application::{word_index, Application, Mode};
+use crate::models::application::modes::TagsMode;
+use crate::util::tags::{self, Tag};
+use scribe::buffer::Position;
+
+/// Jumps to the definition of the word under the cursor, as listed in
+/// the workspace's tags file. Names defined more than once are listed
+/// in tags mode, to choose which definition to jump to.
+pub fn jump_to_tag_under_cursor(app: &mut Application) -> Result {
+    let name = {
+        let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+        let line = buffer.data().lines().nth(buffer.cursor.line).map(String::from).ok_or(CURRENT_LINE_MISSING)?;
+        word_index::word_at(&line, buffer.cursor.offset).to_string()
+    };
+    if name.is_empty() {
+        bail!("No word under the cursor to look up");
+    }
+
+    let mut definitions: Vec<Tag> = tags::load(&app.workspace.path)?
+        .into_iter()
+        .filter(|tag| tag.name == name)
+        .collect();
+
+    match definitions.len() {
+        0 => bail!(format!("No tag found for \"{}\"", name)),
+        1 => jump_to(app, &definitions.remove(0)),
+        _ => {
+            let config = app.preferences.borrow().search_select_config();
+            app.mode = Mode::Tags(TagsMode::new(definitions, config));
+            commands::search_select::search(app)
+        }
+    }
+}
+
+/// Opens the tag's file and moves the cursor to its definition, recording the jump.
+pub(crate) fn jump_to(app: &mut Application, tag: &Tag) -> Result {
+    let path = app.workspace.path.join(&tag.path);
+    commands::jump_list::record(app);
+    app.workspace
+        .open_buffer(&path)
+        .chain_err(|| format!("Couldn't open a buffer for {}", path.display()))?;
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.initialize_buffer(buffer)?;
+
+    let line = tag.line(&buffer.data())
+        .ok_or_else(|| format!("Couldn't find the definition of \"{}\" in {}", tag.name, tag.path.display()))?;
+    if !buffer.cursor.move_to(Position { line, offset: 0 }) {
+        bail!(format!("{} no longer has a line {}", tag.path.display(), line + 1));
+    }
+
+    commands::view::scroll_cursor_to_center(app)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use crate::models::application::Mode;
+    use crate::models::application::modes::SearchSelectMode;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::{env, fs};
+
+    #[test]
+    fn jump_to_tag_under_cursor_lists_names_defined_more_than_once() {
+        let workspace = env::temp_dir().join("amp_tag_jump_test");
+        fs::create_dir_all(&workspace).unwrap();
+        fs::write(workspace.join("render.rs"), "use view;\n\nfn render() {\n}\n").unwrap();
+        fs::write(
+            workspace.join("tags"),
+            "render\trender.rs\t/^fn render() {$/;\"\tf\n\
+             draw\trender.rs\t3;\"\tf\n\
+             draw\tcanvas.rs\t1;\"\tf\n"
+        ).unwrap();
+
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.path = workspace.clone();
+        let mut buffer = Buffer::new();
+        buffer.insert("render();\ndraw();\n");
+        app.workspace.add_buffer(buffer);
+
+        commands::tag::jump_to_tag_under_cursor(&mut app).unwrap();
+        assert!(app.workspace.current_buffer().unwrap().data().starts_with("use view;"));
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 0 });
+
+        commands::jump_list::back(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 1, offset: 0 });
+        commands::tag::jump_to_tag_under_cursor(&mut app).unwrap();
+        match app.mode {
+            Mode::Tags(ref mode) => assert_eq!(mode.results().count(), 2),
+            _ => panic!("Expected tags mode"),
+        }
+
+        fs::remove_dir_all(&workspace).unwrap();
+    }
+}
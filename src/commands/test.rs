@@ -0,0 +1,55 @@
+use crate::errors::*;
+use crate::commands::Result;
+use crate::models::application::{job, Application, Job};
+use crate::util::{test_runner, tools};
+
+pub fn run_nearest(app: &mut Application) -> Result {
+    run(app, true)
+}
+
+pub fn run_file(app: &mut Application) -> Result {
+    run(app, false)
+}
+
+fn run(app: &mut Application, nearest_only: bool) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let buffer_id = buffer.id.ok_or("Buffer ID doesn't exist")?;
+    let path = buffer.path.clone().ok_or(BUFFER_PATH_MISSING)?;
+    let command = app.preferences
+        .borrow()
+        .test_command(Some(&path))
+        .ok_or("No test command configured for this file type")?;
+    if !tools::shell_command_available(&command) {
+        bail!("Can't run tests: {} isn't available", tools::program(&command).unwrap_or("test command"));
+    }
+
+    let mut tests = test_runner::test_functions(&buffer.data());
+    let filter = if nearest_only {
+        let test = test_runner::nearest_test(&tests, buffer.cursor.line)
+            .ok_or("Couldn't find a test at or above the cursor")?
+            .clone();
+        let name = test.name.clone();
+        tests = vec![test];
+
+        name
+    } else {
+        String::new()
+    };
+
+    // Names and paths are quoted, so that the shell doesn't interpret them.
+    let relative_path = path.strip_prefix(&app.workspace.path).unwrap_or(&path);
+    let filter = if filter.is_empty() { filter } else { tools::shell_quote(&filter) };
+    let command = command
+        .replace("{test}", &filter)
+        .replace("{path}", &tools::shell_quote(&relative_path.to_string_lossy()));
+
+    app.view.set_annotations(buffer_id, test_runner::ANNOTATION_PROVIDER, test_runner::pending_annotations(&tests));
+    job::spawn(
+        Job::Test { buffer_id, tests },
+        command,
+        app.workspace.path.clone(),
+        app.event_channel.clone(),
+    );
+
+    Ok(())
+}
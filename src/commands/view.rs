@@ -1,6 +1,8 @@
 use crate::errors::*;
 use crate::commands::Result;
 use crate::models::application::Application;
+use crate::util;
+use crate::view::SplitDirection;
 
 pub fn scroll_up(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
@@ -14,6 +16,18 @@ pub fn scroll_down(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn scroll_left(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_left(buffer, 10)?;
+    Ok(())
+}
+
+pub fn scroll_right(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    app.view.scroll_right(buffer, 10)?;
+    Ok(())
+}
+
 pub fn scroll_to_cursor(app: &mut Application) -> Result {
     let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     app.view.scroll_to_cursor(buffer)?;
@@ -25,3 +39,147 @@ pub fn scroll_cursor_to_center(app: &mut Application) -> Result {
     app.view.scroll_to_center(buffer)?;
     Ok(())
 }
+
+/// Shows the rest of a buffer narrowed to a selection (selection::narrow).
+pub fn widen(app: &mut Application) -> Result {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    if !app.view.widen(buffer) {
+        bail!("The buffer isn't narrowed");
+    }
+    app.view.scroll_to_cursor(buffer)?;
+
+    Ok(())
+}
+
+pub fn split_horizontal(app: &mut Application) -> Result {
+    split(app, SplitDirection::Horizontal);
+    Ok(())
+}
+
+pub fn split_vertical(app: &mut Application) -> Result {
+    split(app, SplitDirection::Vertical);
+    Ok(())
+}
+
+pub fn focus_next_pane(app: &mut Application) -> Result {
+    if app.view.pane_count() == 1 {
+        bail!("There's only one pane");
+    }
+
+    sync_focused_pane(app);
+    app.view.focus_next_pane();
+    select_focused_pane_buffer(app)
+}
+
+pub fn close_pane(app: &mut Application) -> Result {
+    if !app.view.close_pane() {
+        bail!("Can't close the only pane");
+    }
+
+    select_focused_pane_buffer(app)
+}
+
+/// Locks the focused pane's scrolling to the next pane's, so that they scroll
+/// together (keeping their current distance apart), or unlocks them.
+pub fn toggle_scroll_lock(app: &mut Application) -> Result {
+    sync_focused_pane(app);
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let locked = app.view.toggle_scroll_lock(buffer)?;
+    app.notification = Some(String::from(if locked { "Scrolling locked" } else { "Scrolling unlocked" }));
+
+    Ok(())
+}
+
+// The new pane displays the current buffer, too.
+fn split(app: &mut Application, direction: SplitDirection) {
+    sync_focused_pane(app);
+    app.view.split_pane(direction);
+}
+
+// Panes are updated to reflect buffer changes when rendered,
+// which may not have happened since the last buffer change. Where
+// the pane's been left in its buffer is recorded, too, since it's
+// about to be unfocused (or split).
+pub(crate) fn sync_focused_pane(app: &mut Application) {
+    let id = app.workspace.current_buffer().and_then(|b| b.id);
+    app.view.set_focused_pane_buffer(id);
+    if let Some(buffer) = app.workspace.current_buffer() {
+        let _ = app.view.store_viewport(buffer);
+    }
+}
+
+// Makes the newly focused pane's buffer current,
+// returning to where the pane was left in it.
+pub(crate) fn select_focused_pane_buffer(app: &mut Application) -> Result {
+    if let Some(id) = app.view.focused_pane_buffer() {
+        util::select_buffer(id, app);
+    }
+    if let Some(buffer) = app.workspace.current_buffer() {
+        app.view.restore_viewport(buffer)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::Application;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn focus_next_pane_switches_to_the_buffer_displayed_in_it() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        let first_id = app.workspace.current_buffer().unwrap().id;
+        assert!(commands::view::focus_next_pane(&mut app).is_err());
+
+        // Split and then display another buffer in the new pane.
+        commands::view::split_vertical(&mut app).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        let second_id = app.workspace.current_buffer().unwrap().id;
+
+        commands::view::focus_next_pane(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, first_id);
+        commands::view::focus_next_pane(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, second_id);
+
+        // Closing the pane gives focus back to the first one.
+        commands::view::close_pane(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().id, first_id);
+        assert!(commands::view::close_pane(&mut app).is_err());
+    }
+
+    #[test]
+    fn toggle_scroll_lock_locks_and_unlocks_split_panes() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        assert!(commands::view::toggle_scroll_lock(&mut app).is_err());
+
+        commands::view::split_vertical(&mut app).unwrap();
+        commands::view::toggle_scroll_lock(&mut app).unwrap();
+        assert_eq!(app.notification, Some(String::from("Scrolling locked")));
+        commands::view::toggle_scroll_lock(&mut app).unwrap();
+        assert_eq!(app.notification, Some(String::from("Scrolling unlocked")));
+    }
+
+    #[test]
+    fn panes_displaying_the_same_buffer_have_their_own_cursors() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\ntext\n");
+        app.workspace.add_buffer(buffer);
+        commands::view::split_horizontal(&mut app).unwrap();
+        app.workspace.current_buffer().unwrap().cursor.move_to(Position { line: 2, offset: 1 });
+
+        commands::view::focus_next_pane(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 0, offset: 0 });
+
+        // Edits made in either pane are shared.
+        commands::buffer::insert_newline(&mut app).unwrap();
+        commands::view::focus_next_pane(&mut app).unwrap();
+        assert_eq!(*app.workspace.current_buffer().unwrap().cursor, Position { line: 2, offset: 1 });
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "\namp\neditor\ntext\n");
+    }
+}
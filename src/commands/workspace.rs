@@ -1,6 +1,9 @@
-use crate::commands::Result;
-use scribe::Buffer;
-use crate::models::application::Application;
+use crate::errors::*;
+use crate::errors;
+use crate::commands::{Command, Result};
+use scribe::{Buffer, Workspace};
+use crate::models::application::{Application, Mode};
+use crate::models::application::modes::ConfirmMode;
 use crate::util;
 
 pub fn next_buffer(app: &mut Application) -> Result {
@@ -9,6 +12,241 @@ pub fn next_buffer(app: &mut Application) -> Result {
     Ok(())
 }
 
+pub fn next_buffer_in_group(app: &mut Application) -> Result {
+    cycle_group_buffers(app, Workspace::next_buffer)
+}
+
+pub fn previous_buffer_in_group(app: &mut Application) -> Result {
+    cycle_group_buffers(app, Workspace::previous_buffer)
+}
+
+/// Selects the most recently used buffer in the next group.
+pub fn next_group(app: &mut Application) -> Result {
+    app.buffer_groups.update(&mut app.workspace);
+    let groups = app.buffer_groups.groups();
+    let index = groups.iter().position(|&g| g == app.buffer_groups.current()).ok_or(BUFFER_MISSING)?;
+
+    select_group(app, groups[(index + 1) % groups.len()])
+}
+
+/// Selects the most recently used buffer in the previous group.
+pub fn previous_group(app: &mut Application) -> Result {
+    app.buffer_groups.update(&mut app.workspace);
+    let groups = app.buffer_groups.groups();
+    let index = groups.iter().position(|&g| g == app.buffer_groups.current()).ok_or(BUFFER_MISSING)?;
+
+    select_group(app, groups[(index + groups.len() - 1) % groups.len()])
+}
+
+/// Moves the current buffer to the next group, creating one if necessary.
+pub fn move_buffer_to_next_group(app: &mut Application) -> Result {
+    app.buffer_groups.update(&mut app.workspace);
+    let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+    let group = app.buffer_groups.current();
+    let last_group = app.buffer_groups.groups().last().cloned().unwrap_or(0);
+    if group == last_group && app.buffer_groups.buffer_count(group) == 1 {
+        bail!("Buffer is already alone in the last group");
+    }
+    app.buffer_groups.set_group(id, group + 1);
+
+    Ok(())
+}
+
+pub fn move_buffer_to_previous_group(app: &mut Application) -> Result {
+    app.buffer_groups.update(&mut app.workspace);
+    let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+    let group = app.buffer_groups.current();
+    if group == 0 {
+        bail!("Buffer is already in the first group");
+    }
+    app.buffer_groups.set_group(id, group - 1);
+
+    Ok(())
+}
+
 pub fn new_buffer(app: &mut Application) -> Result {
     util::add_buffer(Buffer::new(), app)
 }
+
+pub fn reload_unmodified_buffers(app: &mut Application) -> Result {
+    // Get the current buffer's ID so we know when we've visited every buffer.
+    let id = match app.workspace.current_buffer() {
+        Some(buf) => buf.id,
+        None => return Ok(()),
+    };
+
+    loop {
+        if let Some(buf) = app.workspace.current_buffer() {
+            // Buffers with local changes (or without a backing
+            // file, or whose file is encrypted) are left as they are.
+            let encrypted = matches!(buf.id, Some(id) if app.encrypted_buffers.contains_key(&id));
+            if buf.path.is_some() && !encrypted && !app.view.modified(buf) {
+                util::reload::reload(buf)?;
+                app.view.record_saved_content(buf);
+            }
+        }
+
+        app.workspace.next_buffer();
+        if app.workspace.current_buffer().map(|b| b.id) == Some(id) {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// Trusts the workspace, so that its own configuration (e.g. debug adapters,
+/// or tasks) can be used, running the commands it specifies.
+pub fn trust(app: &mut Application) -> Result {
+    app.workspace_trust.decide(&app.workspace.path, true)?;
+    app.notification = Some(String::from("Workspace trusted"));
+
+    Ok(())
+}
+
+/// Stops trusting the workspace, ignoring its own configuration.
+pub fn distrust(app: &mut Application) -> Result {
+    app.workspace_trust.decide(&app.workspace.path, false)?;
+    app.notification = Some(String::from("Workspace no longer trusted"));
+
+    Ok(())
+}
+
+/// Whether the workspace's own configuration can be used. If the user hasn't
+/// decided whether to trust it, they're asked to, running the command if they
+/// do; false is returned until then. Distrusted workspaces are reported.
+pub(crate) fn ensure_trusted(app: &mut Application, command: Command) -> errors::Result<bool> {
+    match app.workspace_trust.decision(&app.workspace.path) {
+        Some(true) => Ok(true),
+        Some(false) => bail!(WORKSPACE_UNTRUSTED),
+        None => {
+            app.mode = Mode::Confirm(ConfirmMode::trusting_workspace(command));
+
+            Ok(false)
+        }
+    }
+}
+
+// Advances through the workspace's buffers until
+// reaching another one in the current buffer's group.
+fn cycle_group_buffers(app: &mut Application, advance: fn(&mut Workspace)) -> Result {
+    app.buffer_groups.update(&mut app.workspace);
+    let group = app.buffer_groups.current();
+
+    loop {
+        advance(&mut app.workspace);
+        let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+        if app.buffer_groups.group_of(id) == Some(group) {
+            break;
+        }
+    }
+    app.buffer_groups.update(&mut app.workspace);
+
+    Ok(())
+}
+
+fn select_group(app: &mut Application, group: usize) -> Result {
+    if let Some(id) = app.buffer_groups.last_selected(group) {
+        if util::select_buffer(id, app) {
+            app.buffer_groups.update(&mut app.workspace);
+            return Ok(());
+        }
+    }
+
+    // Fall back to the first buffer found in the group.
+    let start_id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+    loop {
+        app.workspace.next_buffer();
+        let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+        if app.buffer_groups.group_of(id) == Some(group) {
+            break;
+        } else if id == start_id {
+            bail!("No buffers found in the group");
+        }
+    }
+    app.buffer_groups.update(&mut app.workspace);
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::commands;
+    use crate::models::application::{Application, Mode, WorkspaceTrust};
+    use scribe::Buffer;
+    use std::path::Path;
+
+    fn buffer_with_content(content: &str) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.insert(content);
+
+        buffer
+    }
+
+    #[test]
+    fn group_commands_cycle_within_and_between_groups() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(buffer_with_content("one"));
+        app.workspace.add_buffer(buffer_with_content("two"));
+        app.workspace.add_buffer(buffer_with_content("three"));
+
+        // Move "three" to a new group, and open "four" alongside it.
+        commands::workspace::move_buffer_to_next_group(&mut app).unwrap();
+        app.workspace.add_buffer(buffer_with_content("four"));
+        assert_eq!(app.buffer_groups.groups(), vec![0, 1]);
+
+        commands::workspace::next_buffer_in_group(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "three");
+        commands::workspace::next_buffer_in_group(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "four");
+
+        commands::workspace::previous_group(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "one");
+        commands::workspace::previous_buffer_in_group(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "two");
+        assert_eq!(app.buffer_groups.current(), 0);
+
+        // The group's most recently selected buffer is restored.
+        commands::workspace::next_group(&mut app).unwrap();
+        assert_eq!(app.workspace.current_buffer().unwrap().data(), "four");
+        assert_eq!(app.buffer_groups.tabs(), Some(String::from(" 1 [2] ")));
+    }
+
+    #[test]
+    fn move_buffer_to_next_group_doesnt_create_empty_groups() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+
+        assert!(commands::workspace::move_buffer_to_next_group(&mut app).is_err());
+        assert!(commands::workspace::move_buffer_to_previous_group(&mut app).is_err());
+        assert_eq!(app.buffer_groups.groups(), vec![0]);
+    }
+
+    #[test]
+    fn reload_unmodified_buffers_preserves_modified_buffers() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::from_file(Path::new("LICENSE")).unwrap();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+        app.workspace.add_buffer(Buffer::from_file(Path::new("LICENSE")).unwrap());
+
+        commands::workspace::reload_unmodified_buffers(&mut app).unwrap();
+
+        // The current buffer should be unchanged.
+        assert!(app.workspace.current_buffer().unwrap().data().starts_with("Copyright"));
+        app.workspace.previous_buffer();
+        assert!(app.workspace.current_buffer().unwrap().data().starts_with("amp"));
+    }
+
+    #[test]
+    fn ensure_trusted_asks_to_trust_undecided_workspaces() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace_trust = WorkspaceTrust::default();
+
+        assert!(!commands::workspace::ensure_trusted(&mut app, commands::application::switch_to_task_mode).unwrap());
+        match app.mode {
+            Mode::Confirm(ref mode) => assert!(mode.trusting_workspace),
+            _ => panic!("Not asked to trust the workspace"),
+        }
+    }
+}
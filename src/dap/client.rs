@@ -0,0 +1,76 @@
+use crate::errors::*;
+use crate::models::application::Event;
+use crate::util::json;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::thread;
+use crate::yaml::yaml::Yaml;
+
+/// Communicates with a debug adapter process over its standard input/output
+/// streams. Incoming messages are read in a separate thread and forwarded to
+/// the application as `DebugMessage` events.
+pub struct Client {
+    process: Child,
+    stdin: ChildStdin,
+    sequence: i64,
+}
+
+impl Client {
+    pub fn spawn(command: &[String], path: &Path, events: Sender<Event>) -> Result<Client> {
+        let (program, args) = command.split_first().ok_or("Debug adapter command is empty")?;
+        let mut process = Command::new(program)
+            .args(args)
+            .current_dir(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .chain_err(|| format!("Couldn't start debug adapter \"{}\"", program))?;
+        let stdin = process.stdin.take().ok_or("Couldn't write to debug adapter")?;
+        let stdout = process.stdout.take().ok_or("Couldn't read from debug adapter")?;
+
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Some(message) = json::read_message(&mut reader) {
+                // Skip over anything we can't parse, rather than ending the session.
+                let message = match message {
+                    Ok(message) => message,
+                    Err(_) => continue,
+                };
+                if events.send(Event::DebugMessage(message)).is_err() {
+                    return;
+                }
+            }
+
+            let _ = events.send(Event::DebugAdapterExited);
+        });
+
+        Ok(Client { process, stdin, sequence: 0 })
+    }
+
+    /// Sends a request to the adapter, returning its sequence number,
+    /// which the adapter's response will reference as `request_seq`.
+    pub fn request(&mut self, command: &str, arguments: Yaml) -> Result<i64> {
+        self.sequence += 1;
+        let message = json::object(vec![
+            ("seq", Yaml::Integer(self.sequence)),
+            ("type", json::string("request")),
+            ("command", json::string(command)),
+            ("arguments", arguments),
+        ]);
+
+        json::write_message(&mut self.stdin, &message)
+            .chain_err(|| "Couldn't write to debug adapter")?;
+
+        Ok(self.sequence)
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
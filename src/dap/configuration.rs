@@ -0,0 +1,111 @@
+use crate::errors::*;
+use fragment::matching::AsStr;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use crate::yaml::yaml::{Yaml, YamlLoader};
+
+/// The project file containing debug configurations, relative to the workspace.
+pub const CONFIGURATION_PATH: &str = ".amp/debug.yml";
+
+/// Describes how to start a debug adapter, and what it should debug.
+///
+/// ```yaml
+/// configurations:
+///   - name: amp
+///     adapter: lldb-vscode
+///     request: launch
+///     arguments:
+///       program: target/debug/amp
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct DebugConfiguration {
+    pub name: String,
+    pub adapter: Vec<String>,
+    pub request: String,
+    pub arguments: Yaml,
+}
+
+impl fmt::Display for DebugConfiguration {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({})", self.name, self.request)
+    }
+}
+
+impl AsStr for DebugConfiguration {
+    fn as_str(&self) -> &str {
+        &self.name
+    }
+}
+
+/// Loads debug configurations from the workspace's project file.
+pub fn load(workspace_path: &Path) -> Result<Vec<DebugConfiguration>> {
+    let path = workspace_path.join(CONFIGURATION_PATH);
+    let content = fs::read_to_string(&path)
+        .chain_err(|| format!("Couldn't read debug configurations from {}", path.to_string_lossy()))?;
+
+    parse(&content)
+}
+
+fn parse(content: &str) -> Result<Vec<DebugConfiguration>> {
+    let document = YamlLoader::load_from_str(content)
+        .chain_err(|| "Couldn't parse debug configurations")?
+        .into_iter()
+        .next()
+        .ok_or("No debug configurations found")?;
+    let entries = document["configurations"]
+        .as_vec()
+        .ok_or("Debug configurations must be a list under a \"configurations\" key")?;
+
+    entries.iter().map(|entry| {
+        let name = entry["name"].as_str().ok_or("Debug configuration is missing a name")?;
+        let adapter = match entry["adapter"] {
+            Yaml::String(ref command) => command.split_whitespace().map(String::from).collect(),
+            Yaml::Array(ref command) => {
+                command.iter().filter_map(|arg| arg.as_str().map(String::from)).collect()
+            },
+            _ => bail!("Debug configuration \"{}\" is missing an adapter command", name),
+        };
+        let request = entry["request"].as_str().unwrap_or("launch");
+        if request != "launch" && request != "attach" {
+            bail!("Debug configuration \"{}\" has an invalid request type (expected launch or attach)", name);
+        }
+        let arguments = match entry["arguments"] {
+            Yaml::Hash(_) => entry["arguments"].clone(),
+            _ => Yaml::Hash(Default::default()),
+        };
+
+        Ok(DebugConfiguration {
+            name: name.to_string(),
+            adapter,
+            request: request.to_string(),
+            arguments,
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use crate::yaml::yaml::Yaml;
+
+    #[test]
+    fn parse_reads_configurations() {
+        let configurations = parse(
+            "configurations:\n  - name: amp\n    adapter: lldb-vscode --port 0\n    arguments:\n      program: target/debug/amp\n  - name: server\n    adapter: [node, adapter.js]\n    request: attach\n"
+        ).unwrap();
+
+        assert_eq!(configurations.len(), 2);
+        assert_eq!(configurations[0].name, "amp");
+        assert_eq!(configurations[0].adapter, vec!["lldb-vscode", "--port", "0"]);
+        assert_eq!(configurations[0].request, "launch");
+        assert_eq!(configurations[0].arguments["program"], Yaml::String(String::from("target/debug/amp")));
+        assert_eq!(configurations[1].adapter, vec!["node", "adapter.js"]);
+        assert_eq!(configurations[1].request, "attach");
+    }
+
+    #[test]
+    fn parse_rejects_invalid_request_types() {
+        assert!(parse("configurations:\n  - name: amp\n    adapter: gdb\n    request: run\n").is_err());
+    }
+}
@@ -0,0 +1,353 @@
+use crate::errors::*;
+use crate::dap::{Client, DebugConfiguration};
+use crate::models::application::Event;
+use crate::util::json;
+use crate::view::{Annotation, AnnotationPosition, AnnotationProvider, Colors, RGBColor, Style};
+use std::collections::{BTreeSet, HashMap};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::Sender;
+use crate::yaml::yaml::Yaml;
+
+// Breakpoints and the paused location are tracked by line, so edits don't
+// invalidate them, and they take precedence over other gutter signs.
+pub const BREAKPOINT_ANNOTATION_PROVIDER: AnnotationProvider = AnnotationProvider {
+    name: "breakpoint",
+    priority: 30,
+    invalidated_by_edits: false,
+};
+pub const LOCATION_ANNOTATION_PROVIDER: AnnotationProvider = AnnotationProvider {
+    name: "debug_location",
+    priority: 40,
+    invalidated_by_edits: false,
+};
+
+/// Follow-up work for the application, produced while handling adapter messages.
+#[derive(Debug, PartialEq)]
+pub enum DebugAction {
+    /// Execution has paused at the specified path and (zero-based) line.
+    ShowLocation(PathBuf, usize),
+
+    /// Execution has resumed; the paused location is no longer relevant.
+    ClearLocation,
+
+    /// The variables for the current stack frame have been loaded.
+    ShowVariables(String),
+
+    /// The debuggee has terminated.
+    End,
+}
+
+/// Tracks breakpoints (which outlive debug sessions) and the active session.
+#[derive(Default)]
+pub struct Debugger {
+    breakpoints: HashMap<PathBuf, BTreeSet<usize>>,
+    session: Option<Session>,
+
+    /// The ID of the buffer displaying the paused location, if any.
+    pub location_buffer: Option<usize>,
+
+    /// The ID of the buffer used to display variables, if any.
+    pub variables_buffer: Option<usize>,
+}
+
+struct Session {
+    client: Client,
+    configuration: DebugConfiguration,
+    initialized: bool,
+    thread_id: Option<i64>,
+    frame_id: Option<i64>,
+    scope_requests: HashMap<i64, String>,
+    scopes: Vec<(String, String)>,
+}
+
+impl Debugger {
+    pub fn start(&mut self, configuration: DebugConfiguration, path: &Path, events: Sender<Event>) -> Result<()> {
+        if self.session.is_some() {
+            bail!("A debug session is already running");
+        }
+
+        let mut client = Client::spawn(&configuration.adapter, path, events)?;
+        client.request("initialize", json::object(vec![
+            ("clientID", json::string("amp")),
+            ("clientName", json::string("Amp")),
+            ("adapterID", json::string(configuration.name.as_str())),
+            ("pathFormat", json::string("path")),
+            ("linesStartAt1", Yaml::Boolean(true)),
+            ("columnsStartAt1", Yaml::Boolean(true)),
+        ]))?;
+
+        self.session = Some(Session {
+            client,
+            configuration,
+            initialized: false,
+            thread_id: None,
+            frame_id: None,
+            scope_requests: HashMap::new(),
+            scopes: Vec::new(),
+        });
+
+        Ok(())
+    }
+
+    pub fn stop(&mut self) -> Result<()> {
+        let mut session = self.session.take().ok_or("No debug session is running")?;
+
+        // The adapter is killed once the session (and its client) is dropped;
+        // ask it to clean up the debuggee before that happens.
+        session.client.request("disconnect", json::object(vec![
+            ("terminateDebuggee", Yaml::Boolean(true)),
+        ]))?;
+
+        Ok(())
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.session.is_some()
+    }
+
+    /// Adds or removes a breakpoint, returning whether one is now set.
+    pub fn toggle_breakpoint(&mut self, path: &Path, line: usize) -> Result<bool> {
+        let lines = self.breakpoints.entry(path.to_path_buf()).or_default();
+        let set = if lines.remove(&line) {
+            false
+        } else {
+            lines.insert(line);
+            true
+        };
+
+        if let Some(ref mut session) = self.session {
+            if session.initialized {
+                set_breakpoints(&mut session.client, path, lines)?;
+            }
+        }
+
+        Ok(set)
+    }
+
+    pub fn breakpoints(&self, path: &Path) -> Vec<usize> {
+        self.breakpoints
+            .get(path)
+            .map(|lines| lines.iter().cloned().collect())
+            .unwrap_or_default()
+    }
+
+    /// Sends an execution control request (e.g. "next" or "continue")
+    /// for the paused thread.
+    pub fn resume(&mut self, command: &str) -> Result<()> {
+        let session = self.session.as_mut().ok_or("No debug session is running")?;
+        let thread_id = session.thread_id.ok_or("Execution isn't paused")?;
+        session.client.request(command, json::object(vec![
+            ("threadId", Yaml::Integer(thread_id)),
+        ]))?;
+        session.thread_id = None;
+        session.frame_id = None;
+
+        Ok(())
+    }
+
+    pub fn request_variables(&mut self) -> Result<()> {
+        let session = self.session.as_mut().ok_or("No debug session is running")?;
+        let frame_id = session.frame_id.ok_or("Execution isn't paused")?;
+        session.client.request("scopes", json::object(vec![
+            ("frameId", Yaml::Integer(frame_id)),
+        ]))?;
+
+        Ok(())
+    }
+
+    /// Handles a message from the debug adapter, advancing the session.
+    pub fn handle_message(&mut self, message: &Yaml) -> Result<Vec<DebugAction>> {
+        let breakpoints = &self.breakpoints;
+        let session = match self.session {
+            Some(ref mut session) => session,
+            None => return Ok(Vec::new()),
+        };
+        let mut actions = Vec::new();
+        let body = &message["body"];
+
+        match message["type"].as_str() {
+            Some("response") => {
+                let command = message["command"].as_str().unwrap_or("");
+                if message["success"] != Yaml::Boolean(true) {
+                    let reason = message["message"].as_str().unwrap_or("unknown error");
+                    bail!("Debug adapter \"{}\" request failed: {}", command, reason);
+                }
+
+                match command {
+                    "initialize" => {
+                        // Now that the adapter knows who we are, start debugging.
+                        let request = session.configuration.request.clone();
+                        let arguments = session.configuration.arguments.clone();
+                        session.client.request(&request, arguments)?;
+                    },
+                    "stackTrace" => {
+                        let frame = &body["stackFrames"][0];
+                        session.frame_id = frame["id"].as_i64();
+
+                        if let (Some(path), Some(line)) = (frame["source"]["path"].as_str(), frame["line"].as_i64()) {
+                            let line = if line > 0 { line as usize - 1 } else { 0 };
+                            actions.push(DebugAction::ShowLocation(PathBuf::from(path), line));
+                        }
+                    },
+                    "scopes" => {
+                        session.scope_requests.clear();
+                        session.scopes.clear();
+                        if let Some(scopes) = body["scopes"].as_vec() {
+                            for scope in scopes {
+                                if let Some(reference) = scope["variablesReference"].as_i64() {
+                                    let name = scope["name"].as_str().unwrap_or("Scope").to_string();
+                                    let seq = session.client.request("variables", json::object(vec![
+                                        ("variablesReference", Yaml::Integer(reference)),
+                                    ]))?;
+                                    session.scope_requests.insert(seq, name);
+                                }
+                            }
+                        }
+                    },
+                    "variables" => {
+                        let request = message["request_seq"].as_i64().unwrap_or(0);
+                        if let Some(scope) = session.scope_requests.remove(&request) {
+                            session.scopes.push((scope, format_variables(&body["variables"])));
+
+                            if session.scope_requests.is_empty() {
+                                let content = session.scopes
+                                    .iter()
+                                    .map(|(name, variables)| format!("{}\n{}", name, variables))
+                                    .collect::<Vec<_>>()
+                                    .join("\n");
+                                actions.push(DebugAction::ShowVariables(content));
+                            }
+                        }
+                    },
+                    _ => (),
+                }
+            },
+            Some("event") => match message["event"].as_str() {
+                Some("initialized") => {
+                    session.initialized = true;
+                    for (path, lines) in breakpoints {
+                        set_breakpoints(&mut session.client, path, lines)?;
+                    }
+                    session.client.request("configurationDone", json::object(vec![]))?;
+                },
+                Some("stopped") => {
+                    let thread_id = body["threadId"].as_i64().unwrap_or(0);
+                    session.thread_id = Some(thread_id);
+                    session.client.request("stackTrace", json::object(vec![
+                        ("threadId", Yaml::Integer(thread_id)),
+                        ("startFrame", Yaml::Integer(0)),
+                        ("levels", Yaml::Integer(1)),
+                    ]))?;
+                },
+                Some("continued") => {
+                    session.thread_id = None;
+                    session.frame_id = None;
+                    actions.push(DebugAction::ClearLocation);
+                },
+                Some("terminated") | Some("exited") => actions.push(DebugAction::End),
+                _ => (),
+            },
+            _ => (),
+        }
+
+        if actions.contains(&DebugAction::End) {
+            self.session = None;
+        }
+
+        Ok(actions)
+    }
+
+    /// Discards the session after its adapter has exited unexpectedly.
+    pub fn end(&mut self) {
+        self.session = None;
+    }
+}
+
+pub fn breakpoint_annotations(lines: &[usize]) -> Vec<Annotation> {
+    lines.iter().map(|line| Annotation {
+        line: *line,
+        content: String::from("●"),
+        style: Style::Bold,
+        colors: Colors::CustomFocusedForeground(RGBColor(220, 50, 47)),
+        position: AnnotationPosition::Gutter,
+    }).collect()
+}
+
+pub fn location_annotations(line: usize) -> Vec<Annotation> {
+    vec![
+        Annotation {
+            line,
+            content: String::from("▶"),
+            style: Style::Bold,
+            colors: Colors::CustomFocusedForeground(RGBColor(240, 140, 20)),
+            position: AnnotationPosition::Gutter,
+        },
+        Annotation {
+            line,
+            content: String::from(" paused "),
+            style: Style::Default,
+            colors: Colors::Warning,
+            position: AnnotationPosition::EndOfLine,
+        },
+    ]
+}
+
+fn set_breakpoints(client: &mut Client, path: &Path, lines: &BTreeSet<usize>) -> Result<()> {
+    let breakpoints = lines.iter().map(|line| {
+        // Breakpoint lines are one-based.
+        json::object(vec![("line", Yaml::Integer(*line as i64 + 1))])
+    }).collect();
+
+    client.request("setBreakpoints", json::object(vec![
+        ("source", json::object(vec![("path", json::string(path.to_string_lossy()))])),
+        ("breakpoints", Yaml::Array(breakpoints)),
+    ]))?;
+
+    Ok(())
+}
+
+fn format_variables(variables: &Yaml) -> String {
+    variables.as_vec().map(|variables| {
+        variables.iter().map(|variable| {
+            format!(
+                "  {} = {}",
+                variable["name"].as_str().unwrap_or("?"),
+                variable["value"].as_str().unwrap_or("")
+            )
+        }).collect::<Vec<_>>().join("\n")
+    }).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_variables, Debugger};
+    use crate::util::json;
+    use std::path::Path;
+
+    #[test]
+    fn toggle_breakpoint_adds_and_removes_breakpoints() {
+        let mut debugger = Debugger::default();
+        let path = Path::new("src/main.rs");
+
+        assert!(debugger.toggle_breakpoint(path, 3).unwrap());
+        assert!(debugger.toggle_breakpoint(path, 1).unwrap());
+        assert_eq!(debugger.breakpoints(path), vec![1, 3]);
+        assert!(!debugger.toggle_breakpoint(path, 3).unwrap());
+        assert_eq!(debugger.breakpoints(path), vec![1]);
+    }
+
+    #[test]
+    fn handle_message_ignores_messages_without_a_session() {
+        let mut debugger = Debugger::default();
+        let message = json::parse("{\"type\":\"event\",\"event\":\"stopped\",\"body\":{\"threadId\":1}}").unwrap();
+
+        assert!(debugger.handle_message(&message).unwrap().is_empty());
+    }
+
+    #[test]
+    fn format_variables_lists_names_and_values() {
+        let variables = json::parse("[{\"name\":\"count\",\"value\":\"2\"},{\"name\":\"name\",\"value\":\"\\\"amp\\\"\"}]").unwrap();
+
+        assert_eq!(format_variables(&variables), "  count = 2\n  name = \"amp\"");
+    }
+}
@@ -0,0 +1,8 @@
+mod client;
+mod configuration;
+mod debugger;
+
+pub use self::client::Client;
+pub use self::configuration::{load as load_configurations, DebugConfiguration, CONFIGURATION_PATH};
+pub use self::debugger::{breakpoint_annotations, location_annotations, DebugAction, Debugger};
+pub use self::debugger::{BREAKPOINT_ANNOTATION_PROVIDER, LOCATION_ANNOTATION_PROVIDER};
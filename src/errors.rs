@@ -8,6 +8,10 @@ error_chain! {
 pub static SEARCH_QUERY_MISSING: &'static str = "No search query";
 pub static NO_SEARCH_RESULTS: &'static str = "No search results available";
 pub static BUFFER_MISSING: &'static str = "No buffer available";
+pub static BUFFER_READ_ONLY: &str = "The buffer is read-only; use buffer::toggle_read_only to edit it";
+pub static BUFFER_NARROWED: &str = "Only the narrowed lines can be edited; use view::widen to edit the rest";
 pub static BUFFER_PATH_MISSING: &'static str = "No path found for the current buffer";
 pub static CURRENT_LINE_MISSING: &'static str = "The current line couldn't be found in the buffer";
 pub static SCROLL_TO_CURSOR_FAILED: &'static str = "Failed to scroll to cursor position";
+pub static WORKSPACE_UNTRUSTED: &str = "The workspace isn't trusted, so its configuration isn't used; use workspace::trust to change that";
+pub static PROMPT_MISSING: &str = "Can't edit input outside of a prompt";
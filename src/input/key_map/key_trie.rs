@@ -0,0 +1,49 @@
+use crate::commands::Command;
+use crate::input::Key;
+use smallvec::SmallVec;
+use std::collections::HashMap;
+
+/// A mode's bindings, arranged by their keys, so that the bindings that
+/// continue a partially typed sequence can be found without scanning them.
+#[derive(Default)]
+pub struct KeyTrie {
+    commands: Option<SmallVec<[Command; 4]>>,
+    children: HashMap<Key, KeyTrie>,
+}
+
+impl KeyTrie {
+    pub fn insert(&mut self, keys: &[Key], commands: SmallVec<[Command; 4]>) {
+        match keys.split_first() {
+            Some((key, rest)) => self.children.entry(key.clone()).or_default().insert(rest, commands),
+            None => self.commands = Some(commands),
+        }
+    }
+
+    /// The node reached by following the keys from this one, if they're a
+    /// bound sequence or the start of one.
+    pub fn find(&self, keys: &[Key]) -> Option<&KeyTrie> {
+        keys.iter().try_fold(self, |node, key| node.children.get(key))
+    }
+
+    /// The commands bound to the sequence ending at this node.
+    pub fn commands(&self) -> Option<&SmallVec<[Command; 4]>> {
+        self.commands.as_ref()
+    }
+
+    /// The keys that continue the sequence ending at this node.
+    pub fn children(&self) -> impl Iterator<Item=(&Key, &KeyTrie)> {
+        self.children.iter()
+    }
+
+    pub fn has_children(&self) -> bool {
+        !self.children.is_empty()
+    }
+
+    /// The number of bound sequences that continue this node's sequence.
+    pub fn binding_count(&self) -> usize {
+        self.children
+            .values()
+            .map(|child| child.commands.iter().count() + child.binding_count())
+            .sum()
+    }
+}
@@ -1,14 +1,45 @@
 use crate::commands::{self, Command};
 use crate::errors::*;
 use crate::input::Key;
+use self::key_trie::KeyTrie;
 use smallvec::SmallVec;
 use std::collections::HashMap;
-use std::ops::{Deref, DerefMut};
+use std::ops::Deref;
+use std::slice;
 use std::convert::Into;
 use crate::yaml::yaml::{Hash, Yaml, YamlLoader};
 
-/// Nested HashMap newtype that provides a more ergonomic interface.
-pub struct KeyMap(HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>>);
+mod key_trie;
+
+const DEFAULT_PRESET: &str = "default";
+const PRESETS: [&str; 3] = [DEFAULT_PRESET, "emacs", "vscode"];
+
+/// One or more keys, pressed in order to run a binding's commands.
+pub type KeySequence = SmallVec<[Key; 2]>;
+
+/// How a sequence of key presses relates to a mode's bindings.
+#[derive(Debug)]
+pub enum Binding {
+    /// The sequence is bound, and doesn't start any longer sequences.
+    Complete(SmallVec<[Command; 4]>),
+
+    /// The sequence starts one or more longer bound sequences.
+    Partial,
+
+    /// The sequence is bound, but also starts a longer bound sequence.
+    /// Its commands are run if no further keys are pressed in time.
+    Ambiguous(SmallVec<[Command; 4]>),
+
+    Unbound,
+}
+
+/// Nested HashMap wrapper that provides a more ergonomic interface.
+pub struct KeyMap {
+    bindings: HashMap<String, HashMap<KeySequence, SmallVec<[Command; 4]>>>,
+
+    // The same bindings, arranged by key, for matching sequences as they're typed.
+    tries: HashMap<String, KeyTrie>,
+}
 
 impl KeyMap {
     /// Parses a Yaml tree of modes and their keybindings into a complete keymap.
@@ -36,7 +67,28 @@ impl KeyMap {
             keymap.insert(mode.to_string(), key_bindings);
         }
 
-        Ok(KeyMap(keymap))
+        Ok(KeyMap::with_bindings(keymap))
+    }
+
+    fn with_bindings(bindings: HashMap<String, HashMap<KeySequence, SmallVec<[Command; 4]>>>) -> KeyMap {
+        let mut keymap = KeyMap { bindings, tries: HashMap::new() };
+        let modes: Vec<String> = keymap.bindings.keys().cloned().collect();
+        for mode in modes {
+            keymap.build_trie(&mode);
+        }
+
+        keymap
+    }
+
+    fn build_trie(&mut self, mode: &str) {
+        let mut trie = KeyTrie::default();
+        if let Some(mode_keymap) = self.bindings.get(mode) {
+            for (sequence, commands) in mode_keymap {
+                trie.insert(sequence, commands.clone());
+            }
+        }
+
+        self.tries.insert(mode.to_string(), trie);
     }
 
     /// Searches the keymap for the specified key.
@@ -44,27 +96,67 @@ impl KeyMap {
     /// if the specific character binding cannot be found.
     ///
     pub fn commands_for(&self, mode: &str, key: &Key) -> Option<SmallVec<[Command; 4]>> {
-        self.0.get(mode).and_then(|mode_keymap| {
+        self.bindings.get(mode).and_then(|mode_keymap| {
+            let sequence = slice::from_ref(key);
             if let Key::Char(_) = *key {
                 // Look for a command for this specific character, falling
                 // back to another search for a wildcard character binding.
-                mode_keymap.get(key).or_else(|| mode_keymap.get(&Key::AnyChar))
+                mode_keymap.get(sequence).or_else(|| mode_keymap.get(&[Key::AnyChar][..]))
             } else {
-                mode_keymap.get(key)
+                mode_keymap.get(sequence)
             }
         }).map(|commands| (*commands).clone())
     }
 
+    /// Searches the keymap for a sequence of keys, determining whether it's
+    /// bound and/or the start of a longer sequence. Wildcard character
+    /// bindings only apply to single keys.
+    pub fn binding_for(&self, mode: &str, keys: &[Key]) -> Binding {
+        let node = self.tries.get(mode).and_then(|trie| trie.find(keys));
+        let commands = match keys {
+            [key] => self.commands_for(mode, key),
+            _ => node.and_then(|node| node.commands()).cloned(),
+        };
+        let partial = node.map(|node| node.has_children()) == Some(true);
+
+        match (commands, partial) {
+            (Some(commands), false) => Binding::Complete(commands),
+            (Some(commands), true) => Binding::Ambiguous(commands),
+            (None, true) => Binding::Partial,
+            (None, false) => Binding::Unbound,
+        }
+    }
+
+    /// Finds a key sequence bound to the command (on its own) in the mode,
+    /// preferring the shortest (and then alphabetically first) of several.
+    pub fn sequence_for(&self, mode: &str, command: Command) -> Option<&KeySequence> {
+        self.bindings.get(mode).and_then(|mode_keymap| {
+            mode_keymap
+                .iter()
+                .filter(|(_, commands)| commands.len() == 1 && commands[0] as usize == command as usize)
+                .map(|(sequence, _)| sequence)
+                .min_by_key(|sequence| {
+                    (sequence.len(), sequence.iter().map(|key| key.to_string()).collect::<Vec<_>>())
+                })
+        })
+    }
+
     /// Loads the default keymap from a static
     /// YAML document injected during the build.
     pub fn default() -> Result<KeyMap> {
-        let default_keymap_data = YamlLoader::load_from_str(KeyMap::default_data())
-            .chain_err(|| "Couldn't parse default keymap")?
+        KeyMap::preset(DEFAULT_PRESET)
+    }
+
+    /// Loads one of the complete keymaps injected during the build
+    /// (e.g. "emacs"), which users can select as a starting point.
+    pub fn preset(name: &str) -> Result<KeyMap> {
+        let keymap_data = YamlLoader::load_from_str(KeyMap::preset_data(name)?)
+            .chain_err(|| format!("Couldn't parse \"{}\" keymap", name))?
             .into_iter()
             .nth(0)
-            .ok_or("Couldn't locate a document in the default keymap")?;
+            .ok_or_else(|| format!("Couldn't locate a document in the \"{}\" keymap", name))?;
 
-        KeyMap::from(&default_keymap_data.as_hash().unwrap())
+        KeyMap::from(keymap_data.as_hash().unwrap())
     }
 
     /// Returns the default YAML keymap data as a string.
@@ -72,6 +164,20 @@ impl KeyMap {
         include_str!("default.yml")
     }
 
+    /// Returns a preset's YAML keymap data as a string.
+    pub fn preset_data(name: &str) -> Result<&'static str> {
+        Ok(match name {
+            DEFAULT_PRESET => KeyMap::default_data(),
+            "emacs" => include_str!("emacs.yml"),
+            "vscode" => include_str!("vscode.yml"),
+            _ => bail!(format!(
+                "Keymap preset \"{}\" doesn't exist (available presets: {})",
+                name,
+                PRESETS.join(", ")
+            )),
+        })
+    }
+
     /// Merges each of the passed key map's modes, consuming them in the process.
     /// Note: the mode must exist to be merged; unmatched modes are discarded.
     ///
@@ -94,17 +200,51 @@ impl KeyMap {
     ///       Key::Char('j') => commands::cursor::move_down
     ///   }
     ///
-    pub fn merge(&mut self, mut key_map: KeyMap) {
+    pub fn merge(&mut self, key_map: KeyMap) {
         // Step through the specified key map's modes.
-        for (mode, other_key_bindings) in key_map.iter_mut() {
+        for (mode, other_key_bindings) in key_map.bindings {
             // Fetch the current key bindings for the specified mode.
-            if let Some(key_bindings) = self.get_mut(mode) {
-                for (key, command) in other_key_bindings.drain() {
-                    key_bindings.insert(key, command);
-                }
+            if let Some(key_bindings) = self.bindings.get_mut(&mode) {
+                key_bindings.extend(other_key_bindings);
+                self.build_trie(&mode);
             }
         }
     }
+
+    /// Lists the keys that continue a partially typed sequence in the mode,
+    /// alongside the commands they run, or the number of bindings they lead
+    /// to if they're the start of longer sequences themselves.
+    pub fn continuations(&self, mode: &str, keys: &[Key]) -> Vec<(Key, String)> {
+        let node = match self.tries.get(mode).and_then(|trie| trie.find(keys)) {
+            Some(node) => node,
+            None => return Vec::new(),
+        };
+        let command_names: HashMap<usize, &str> = commands::hash_map()
+            .into_iter()
+            .map(|(name, command)| (command as usize, name))
+            .collect();
+
+        let mut continuations: Vec<(Key, String)> = node.children().map(|(key, child)| {
+            let mut description = child.commands().map(|commands| {
+                commands
+                    .iter()
+                    .map(|&command| command_names.get(&(command as usize)).cloned().unwrap_or("?"))
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            }).unwrap_or_default();
+            if child.has_children() {
+                if !description.is_empty() {
+                    description.push(' ');
+                }
+                description.push_str(&format!("(+{} more)", child.binding_count()));
+            }
+
+            (key.clone(), description)
+        }).collect();
+        continuations.sort_by_key(|(key, _)| key.to_string());
+
+        continuations
+    }
 }
 
 /// Parses the key bindings for a particular mode.
@@ -117,7 +257,7 @@ impl KeyMap {
 ///
 ///   Key::Char('k') => [commands::cursor::move_up]
 ///
-fn parse_mode_key_bindings(mode: &Yaml, commands: &HashMap<&str, Command>) -> Result<HashMap<Key, SmallVec<[Command; 4]>>> {
+fn parse_mode_key_bindings(mode: &Yaml, commands: &HashMap<&str, Command>) -> Result<HashMap<KeySequence, SmallVec<[Command; 4]>>> {
     let mode_key_bindings = mode.as_hash().ok_or(
         "Keymap mode config didn't return a hash of key bindings",
     )?;
@@ -125,9 +265,10 @@ fn parse_mode_key_bindings(mode: &Yaml, commands: &HashMap<&str, Command>) -> Re
     let mut key_bindings = HashMap::new();
     for (yaml_key, yaml_command) in mode_key_bindings {
         // Parse modifier/character from key component.
-        let key = parse_key(yaml_key.as_str().ok_or_else(||
+        let key_data = yaml_key.as_str().ok_or_else(||
             "A keymap key couldn't be parsed as a string".to_string()
-        )?)?;
+        )?;
+        let key = parse_key_sequence(key_data)?;
 
         let mut key_commands = SmallVec::new();
 
@@ -159,13 +300,39 @@ fn parse_mode_key_bindings(mode: &Yaml, commands: &HashMap<&str, Command>) -> Re
             _ => bail!(format!("Keymap command \"{:?}\" couldn't be parsed", yaml_command))
         }
 
-        // Add a key/command entry to the mapping.
-        key_bindings.insert(key, key_commands);
+        // Add a key/command entry to the mapping, refusing to silently
+        // replace an equivalent binding (e.g. "space" and " ").
+        if key_bindings.insert(key, key_commands).is_some() {
+            bail!(format!("Keymap key \"{}\" is bound more than once", key_data));
+        }
     }
 
     Ok(key_bindings)
 }
 
+/// Parses a str-based sequence of space-separated keys.
+///
+/// e.g.
+///
+///   g ctrl-r becomes [Key::Char('g'), Key::Ctrl('r')]
+///
+pub(crate) fn parse_key_sequence(data: &str) -> Result<KeySequence> {
+    // A lone space is a key, rather than a separator.
+    if data == " " {
+        return Ok(SmallVec::from_vec(vec![Key::Char(' ')]));
+    }
+
+    let sequence = data
+        .split_whitespace()
+        .map(parse_key)
+        .collect::<Result<KeySequence>>()?;
+    if sequence.is_empty() {
+        bail!("A keymap key is an empty string");
+    }
+
+    Ok(sequence)
+}
+
 /// Parses a str-based key into its Key equivalent.
 ///
 /// e.g.
@@ -180,10 +347,11 @@ fn parse_key(data: &str) -> Result<Key> {
 
     if let Some(key) = key_components.next() {
         // We have a modifier-qualified key; get the key.
-        let key_char = key.chars().nth(0).ok_or_else(|| format!(
-            "Keymap key \"{}\" is invalid",
-            key
-        ))?;
+        let mut key_chars = key.chars();
+        let key_char = match (key_chars.next(), key_chars.next()) {
+            (Some(key_char), None) => key_char,
+            _ => bail!(format!("Keymap key \"{}\" is invalid", data)),
+        };
 
         // Find the variant for the specified modifier.
         match component {
@@ -209,40 +377,39 @@ fn parse_key(data: &str) -> Result<Key> {
             "tab"       => Key::Tab,
             "enter"     => Key::Enter,
             "_"         => Key::AnyChar,
-            _           => Key::Char(
-                // It's not a keyword; take its first character, if available.
-                component.chars().nth(0).ok_or_else(||
-                    format!("Keymap key \"{}\" is invalid", component)
-                )?
-            ),
+            _           => {
+                // It's not a keyword; it should be a single character.
+                let mut chars = component.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => Key::Char(c),
+                    _ => bail!(format!(
+                        "Keymap key \"{}\" is invalid (separate keys in a sequence with spaces)",
+                        component
+                    )),
+                }
+            }
         })
     }
 }
 
 impl Deref for KeyMap {
-    type Target = HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>>;
-
-    fn deref(&self) -> &HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>> {
-        &self.0
-    }
-}
+    type Target = HashMap<String, HashMap<KeySequence, SmallVec<[Command; 4]>>>;
 
-impl DerefMut for KeyMap {
-    fn deref_mut(&mut self) -> &mut HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>> {
-        &mut self.0
+    fn deref(&self) -> &HashMap<String, HashMap<KeySequence, SmallVec<[Command; 4]>>> {
+        &self.bindings
     }
 }
 
-impl Into<HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>>> for KeyMap {
-    fn into(self) -> HashMap<String, HashMap<Key, SmallVec<[Command; 4]>>> {
-        self.0
+impl Into<HashMap<String, HashMap<KeySequence, SmallVec<[Command; 4]>>>> for KeyMap {
+    fn into(self) -> HashMap<String, HashMap<KeySequence, SmallVec<[Command; 4]>>> {
+        self.bindings
     }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::yaml::YamlLoader;
-    use super::KeyMap;
+    use super::{Binding, KeyMap};
     use crate::commands;
     use crate::input::Key;
 
@@ -420,4 +587,107 @@ mod tests {
             (commands::cursor::move_down as *const usize)
         );
     }
+
+    #[test]
+    fn presets_define_every_default_mode() {
+        let default = KeyMap::default().unwrap();
+
+        for name in super::PRESETS.iter() {
+            let preset = KeyMap::preset(name).unwrap();
+            let mut modes: Vec<&String> = preset.keys().collect();
+            let mut default_modes: Vec<&String> = default.keys().collect();
+            modes.sort();
+            default_modes.sort();
+
+            assert_eq!(modes, default_modes, "\"{}\" preset modes differ", name);
+        }
+        assert!(KeyMap::preset("unknown").is_err());
+    }
+
+    #[test]
+    fn emacs_preset_inserts_characters_typed_in_normal_mode() {
+        let keymap = KeyMap::preset("emacs").unwrap();
+
+        let command = keymap.commands_for("normal", &Key::Char('k')).unwrap();
+        assert_eq!(
+            (command[1] as *const usize),
+            (commands::buffer::insert_char as *const usize)
+        );
+        let command = keymap.commands_for("insert", &Key::Ctrl('e')).unwrap();
+        assert_eq!(
+            (command[0] as *const usize),
+            (commands::cursor::move_to_end_of_line as *const usize)
+        );
+    }
+
+    #[test]
+    fn keymap_parses_key_sequences_and_identifies_partial_bindings() {
+        let yaml_data = "normal:\n  g: cursor::move_up\n  g g: cursor::move_to_first_line\n  space ctrl-f: cursor::move_down";
+        let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+        let keymap = KeyMap::from(yaml[0].as_hash().unwrap()).unwrap();
+
+        match keymap.binding_for("normal", &[Key::Char('g')]) {
+            Binding::Ambiguous(commands) => assert_eq!(
+                (commands[0] as *const usize),
+                (commands::cursor::move_up as *const usize)
+            ),
+            binding => panic!("Unexpected binding: {:?}", binding),
+        }
+        assert!(matches!(
+            keymap.binding_for("normal", &[Key::Char('g'), Key::Char('g')]),
+            Binding::Complete(_)
+        ));
+        assert!(matches!(keymap.binding_for("normal", &[Key::Char(' ')]), Binding::Partial));
+        assert!(matches!(
+            keymap.binding_for("normal", &[Key::Char(' '), Key::Ctrl('f')]),
+            Binding::Complete(_)
+        ));
+        assert!(matches!(keymap.binding_for("normal", &[Key::Char('x')]), Binding::Unbound));
+    }
+
+    #[test]
+    fn keymap_lists_continuations_of_merged_key_sequences() {
+        let yaml_data = "normal:\n  space f: cursor::move_up\n  space b: cursor::move_down";
+        let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+        let mut keymap = KeyMap::from(yaml[0].as_hash().unwrap()).unwrap();
+
+        let other_yaml_data = "normal:\n  space f f: cursor::move_left\n  space f g: cursor::move_right";
+        let other_yaml = YamlLoader::load_from_str(other_yaml_data).unwrap();
+        keymap.merge(KeyMap::from(other_yaml[0].as_hash().unwrap()).unwrap());
+
+        assert_eq!(keymap.continuations("normal", &[Key::Char(' ')]), vec![
+            (Key::Char('b'), String::from("cursor::move_down")),
+            (Key::Char('f'), String::from("cursor::move_up (+2 more)")),
+        ]);
+        assert_eq!(keymap.continuations("normal", &[Key::Char(' '), Key::Char('f')]), vec![
+            (Key::Char('f'), String::from("cursor::move_left")),
+            (Key::Char('g'), String::from("cursor::move_right")),
+        ]);
+        assert!(matches!(
+            keymap.binding_for("normal", &[Key::Char(' '), Key::Char('f'), Key::Char('g')]),
+            Binding::Complete(_)
+        ));
+        assert!(keymap.continuations("normal", &[Key::Char('x')]).is_empty());
+    }
+
+    #[test]
+    fn keymap_rejects_conflicting_and_invalid_keys() {
+        for yaml_data in &[
+            "normal:\n  space: cursor::move_up\n  \" \": cursor::move_down",
+            "normal:\n  gg: cursor::move_up",
+            "normal:\n  ctrl-rr: cursor::move_up",
+        ] {
+            let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+            assert!(KeyMap::from(yaml[0].as_hash().unwrap()).is_err());
+        }
+    }
+
+    #[test]
+    fn displayed_keys_are_parsed_back_into_the_same_keys() {
+        let keys = vec![Key::Char(' '), Key::Char('g'), Key::Ctrl('r'), Key::PageDown, Key::Esc];
+        let data = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(" ");
+
+        assert_eq!(data, "space g ctrl-r page_down escape");
+        assert_eq!(super::parse_key_sequence(&data).unwrap().to_vec(), keys);
+    }
 }
@@ -1,6 +1,11 @@
-pub use self::key_map::KeyMap;
+pub use self::key_map::{Binding, KeyMap, KeySequence};
+pub use self::snippets::{SnippetBinding, Snippets};
+
+use scribe::buffer::Position;
+use std::fmt;
 
 mod key_map;
+mod snippets;
 
 #[derive(Clone, Debug, Eq, Hash, PartialEq)]
 pub enum Key {
@@ -22,3 +27,42 @@ pub enum Key {
     Char(char),
     Ctrl(char),
 }
+
+/// Mouse input, at a (zero-based) screen position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Mouse {
+    /// The left button was pressed.
+    Press(Position),
+
+    /// The pointer moved while a button was held.
+    Drag(Position),
+    Release(Position),
+    ScrollUp(Position),
+    ScrollDown(Position),
+}
+
+/// Formats keys using the notation understood by keymaps (e.g. "ctrl-r").
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Key::Backspace => write!(f, "backspace"),
+            Key::Left => write!(f, "left"),
+            Key::Right => write!(f, "right"),
+            Key::Up => write!(f, "up"),
+            Key::Down => write!(f, "down"),
+            Key::Home => write!(f, "home"),
+            Key::End => write!(f, "end"),
+            Key::PageUp => write!(f, "page_up"),
+            Key::PageDown => write!(f, "page_down"),
+            Key::Delete => write!(f, "delete"),
+            Key::Insert => write!(f, "insert"),
+            Key::Esc => write!(f, "escape"),
+            Key::Tab => write!(f, "tab"),
+            Key::Enter => write!(f, "enter"),
+            Key::AnyChar => write!(f, "_"),
+            Key::Char(' ') => write!(f, "space"),
+            Key::Char(c) => write!(f, "{}", c),
+            Key::Ctrl(c) => write!(f, "ctrl-{}", c),
+        }
+    }
+}
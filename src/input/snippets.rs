@@ -0,0 +1,141 @@
+use crate::errors::*;
+use crate::input::{Key, KeySequence};
+use super::key_map::parse_key_sequence;
+use std::collections::HashMap;
+use crate::yaml::yaml::{Hash, Yaml};
+
+const SNIPPETS_KEY: &str = "snippets";
+
+/// How a sequence of key presses relates to a file type's snippets.
+#[derive(Debug, PartialEq)]
+pub enum SnippetBinding {
+    /// The sequence is bound to the snippet's text.
+    Complete(String),
+
+    /// The sequence starts one or more longer bound sequences.
+    Partial,
+
+    Unbound,
+}
+
+/// Literal text bound to key sequences, by file type and mode, to be inserted
+/// in place of running the keymap's commands (e.g. a test function skeleton).
+#[derive(Default)]
+pub struct Snippets {
+    bindings: HashMap<String, HashMap<String, HashMap<KeySequence, String>>>,
+}
+
+impl Snippets {
+    /// Parses the snippets configured for each file type.
+    ///
+    /// e.g.
+    ///
+    ///  rs:
+    ///     snippets:
+    ///       insert:
+    ///         ctrl-t: "#[test]\nfn $0() {\n}\n"
+    ///
+    /// becomes this HashMap entry:
+    ///
+    ///   "rs" => { "insert" => { [Key::Ctrl('t')] => "#[test]\nfn $0() {\n}\n" } }
+    ///
+    pub fn from(types_data: &Hash) -> Result<Snippets> {
+        let mut bindings = HashMap::new();
+
+        for (yaml_type, type_data) in types_data {
+            let file_type = yaml_type.as_str().ok_or("A file type couldn't be parsed as a string")?;
+            let modes = match type_data[SNIPPETS_KEY] {
+                Yaml::Hash(ref modes) => modes,
+                Yaml::BadValue => continue,
+                _ => bail!(format!("Snippets for \"{}\" files aren't a hash of modes", file_type)),
+            };
+
+            let mut mode_bindings = HashMap::new();
+            for (yaml_mode, yaml_snippets) in modes {
+                let mode = yaml_mode.as_str().ok_or("A snippet mode couldn't be parsed as a string")?;
+                let snippets = parse_mode_snippets(yaml_snippets).chain_err(|| {
+                    format!("Failed to parse \"{}\" snippets for \"{}\" mode", file_type, mode)
+                })?;
+
+                mode_bindings.insert(mode.to_string(), snippets);
+            }
+            bindings.insert(file_type.to_string(), mode_bindings);
+        }
+
+        Ok(Snippets { bindings })
+    }
+
+    /// Searches the file type's snippets for a sequence of keys, determining
+    /// whether it's bound and/or the start of a longer sequence.
+    pub fn binding_for(&self, file_type: &str, mode: &str, keys: &[Key]) -> SnippetBinding {
+        let snippets = match self.bindings.get(file_type).and_then(|modes| modes.get(mode)) {
+            Some(snippets) => snippets,
+            None => return SnippetBinding::Unbound,
+        };
+
+        if let Some(snippet) = snippets.get(keys) {
+            SnippetBinding::Complete(snippet.clone())
+        } else if snippets.keys().any(|sequence| sequence.starts_with(keys)) {
+            SnippetBinding::Partial
+        } else {
+            SnippetBinding::Unbound
+        }
+    }
+}
+
+fn parse_mode_snippets(data: &Yaml) -> Result<HashMap<KeySequence, String>> {
+    let yaml_snippets = data.as_hash().ok_or("Snippet mode config didn't return a hash of snippets")?;
+
+    let mut snippets = HashMap::new();
+    for (yaml_key, yaml_snippet) in yaml_snippets {
+        let key_data = yaml_key.as_str().ok_or("A snippet key couldn't be parsed as a string")?;
+        let keys = parse_key_sequence(key_data)?;
+        let snippet = yaml_snippet.as_str().ok_or_else(|| {
+            format!("Snippet for \"{}\" couldn't be parsed as a string", key_data)
+        })?;
+
+        if snippets.insert(keys, snippet.to_string()).is_some() {
+            bail!(format!("Snippet key \"{}\" is bound more than once", key_data));
+        }
+    }
+
+    Ok(snippets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{SnippetBinding, Snippets};
+    use crate::input::Key;
+    use crate::yaml::YamlLoader;
+
+    #[test]
+    fn snippets_are_bound_by_file_type_and_mode() {
+        let yaml_data = "rs:\n  tab_width: 4\n  snippets:\n    insert:\n      ctrl-t: \"#[test]\\nfn $0() {\\n}\\n\"\n      space d: \"dbg!($0)\"\ngo:\n  tab_width: 8";
+        let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+        let snippets = Snippets::from(yaml[0].as_hash().unwrap()).unwrap();
+
+        assert_eq!(
+            snippets.binding_for("rs", "insert", &[Key::Ctrl('t')]),
+            SnippetBinding::Complete(String::from("#[test]\nfn $0() {\n}\n"))
+        );
+        assert_eq!(snippets.binding_for("rs", "insert", &[Key::Char(' ')]), SnippetBinding::Partial);
+        assert_eq!(
+            snippets.binding_for("rs", "insert", &[Key::Char(' '), Key::Char('d')]),
+            SnippetBinding::Complete(String::from("dbg!($0)"))
+        );
+        assert_eq!(snippets.binding_for("rs", "normal", &[Key::Ctrl('t')]), SnippetBinding::Unbound);
+        assert_eq!(snippets.binding_for("go", "insert", &[Key::Ctrl('t')]), SnippetBinding::Unbound);
+    }
+
+    #[test]
+    fn snippets_reject_invalid_bindings() {
+        for yaml_data in &[
+            "rs:\n  snippets: dbg",
+            "rs:\n  snippets:\n    insert:\n      ctrl-tt: dbg",
+            "rs:\n  snippets:\n    insert:\n      ctrl-t: [dbg]",
+        ] {
+            let yaml = YamlLoader::load_from_str(yaml_data).unwrap();
+            assert!(Snippets::from(yaml[0].as_hash().unwrap()).is_err());
+        }
+    }
+}
@@ -6,6 +6,7 @@ extern crate app_dirs;
 extern crate bloodhound;
 extern crate fragment;
 extern crate git2;
+extern crate glob;
 extern crate luthor;
 extern crate mio;
 extern crate pad;
@@ -16,6 +17,7 @@ extern crate syntect;
 extern crate unicode_segmentation;
 extern crate clipboard;
 extern crate yaml_rust as yaml;
+extern crate serde_json;
 extern crate smallvec;
 
 #[macro_use]
@@ -25,10 +27,13 @@ extern crate error_chain;
 extern crate lazy_static;
 
 // Private modules
+mod collab;
 mod commands;
+mod dap;
 mod errors;
 mod util;
 mod input;
+mod lsp;
 mod models;
 mod presenters;
 mod view;
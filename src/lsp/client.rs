@@ -0,0 +1,97 @@
+use crate::errors::*;
+use crate::models::application::Event;
+use crate::util::json;
+use std::io::BufReader;
+use std::path::Path;
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::Sender;
+use std::thread;
+use crate::yaml::yaml::Yaml;
+
+/// Communicates with a language server process using JSON-RPC over its
+/// standard input/output streams. Incoming messages are read in a separate
+/// thread and forwarded to the application as `LanguageServerMessage` events,
+/// tagged with the server's name.
+pub struct Client {
+    process: Child,
+    stdin: ChildStdin,
+    id: i64,
+}
+
+impl Client {
+    pub fn spawn(name: &str, command: &[String], path: &Path, events: Sender<Event>) -> Result<Client> {
+        let (program, args) = command.split_first().ok_or("Language server command is empty")?;
+        let mut process = Command::new(program)
+            .args(args)
+            .current_dir(path)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .chain_err(|| format!("Couldn't start language server \"{}\"", program))?;
+        let stdin = process.stdin.take().ok_or("Couldn't write to language server")?;
+        let stdout = process.stdout.take().ok_or("Couldn't read from language server")?;
+
+        let name = name.to_string();
+        thread::spawn(move || {
+            let mut reader = BufReader::new(stdout);
+            while let Some(message) = json::read_message(&mut reader) {
+                // Skip over anything we can't parse, rather than ending the session.
+                let message = match message {
+                    Ok(message) => message,
+                    Err(_) => continue,
+                };
+                if events.send(Event::LanguageServerMessage(name.clone(), message)).is_err() {
+                    return;
+                }
+            }
+
+            let _ = events.send(Event::LanguageServerExited(name));
+        });
+
+        Ok(Client { process, stdin, id: 0 })
+    }
+
+    /// Sends a request to the server, returning its ID,
+    /// which the server's response will reference.
+    pub fn request(&mut self, method: &str, params: Yaml) -> Result<i64> {
+        self.id += 1;
+        self.send(json::object(vec![
+            ("jsonrpc", json::string("2.0")),
+            ("id", Yaml::Integer(self.id)),
+            ("method", json::string(method)),
+            ("params", params),
+        ]))?;
+
+        Ok(self.id)
+    }
+
+    pub fn notify(&mut self, method: &str, params: Yaml) -> Result<()> {
+        self.send(json::object(vec![
+            ("jsonrpc", json::string("2.0")),
+            ("method", json::string(method)),
+            ("params", params),
+        ]))
+    }
+
+    /// Responds to a request made by the server.
+    pub fn respond(&mut self, id: Yaml, result: Yaml) -> Result<()> {
+        self.send(json::object(vec![
+            ("jsonrpc", json::string("2.0")),
+            ("id", id),
+            ("result", result),
+        ]))
+    }
+
+    fn send(&mut self, message: Yaml) -> Result<()> {
+        json::write_message(&mut self.stdin, &message)
+            .chain_err(|| "Couldn't write to language server")
+    }
+}
+
+impl Drop for Client {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
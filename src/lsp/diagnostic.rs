@@ -0,0 +1,180 @@
+use crate::lsp::document;
+use crate::view::{Annotation, AnnotationPosition, AnnotationProvider, Colors, RGBColor, Style};
+use std::fmt;
+use std::path::PathBuf;
+use crate::yaml::yaml::Yaml;
+
+// Diagnostics are stale once their lines have changed, until the server republishes them.
+pub const ANNOTATION_PROVIDER: AnnotationProvider = AnnotationProvider {
+    name: "diagnostics",
+    priority: 20,
+    invalidated_by_edits: true,
+};
+
+/// Diagnostic severities, from most to least severe.
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub enum Severity {
+    Error,
+    Warning,
+    Information,
+    Hint,
+}
+
+impl Severity {
+    fn color(self) -> RGBColor {
+        match self {
+            Severity::Error => RGBColor(220, 50, 47),
+            Severity::Warning => RGBColor(181, 137, 0),
+            Severity::Information | Severity::Hint => RGBColor(38, 139, 210),
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+            Severity::Information => write!(f, "info"),
+            Severity::Hint => write!(f, "hint"),
+        }
+    }
+}
+
+/// A problem reported by a language server, located using
+/// a zero-based line and a UTF-16 offset within it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    pub line: usize,
+    pub character: usize,
+
+    /// Where the diagnostic's range ends, if it's on the same line.
+    pub end_character: Option<usize>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Reads the diagnostics from a "textDocument/publishDiagnostics"
+    /// notification's parameters, most severe first.
+    pub fn parse_all(params: &Yaml) -> Option<(PathBuf, Vec<Diagnostic>)> {
+        let path = document::path(params["uri"].as_str()?)?;
+        let mut diagnostics: Vec<Diagnostic> = params["diagnostics"]
+            .as_vec()?
+            .iter()
+            .filter_map(|diagnostic| {
+                let start = &diagnostic["range"]["start"];
+                let end = &diagnostic["range"]["end"];
+                let severity = match diagnostic["severity"].as_i64() {
+                    Some(2) => Severity::Warning,
+                    Some(3) => Severity::Information,
+                    Some(4) => Severity::Hint,
+                    _ => Severity::Error,
+                };
+
+                let line = start["line"].as_i64()? as usize;
+                let end_character = if end["line"].as_i64() == Some(line as i64) {
+                    end["character"].as_i64().map(|character| character as usize)
+                } else {
+                    None
+                };
+
+                Some(Diagnostic {
+                    path: path.clone(),
+                    line,
+                    character: start["character"].as_i64()? as usize,
+                    end_character,
+                    severity,
+                    message: diagnostic["message"].as_str()?.to_string(),
+                })
+            })
+            .collect();
+        diagnostics.sort_by_key(|diagnostic| (diagnostic.severity, diagnostic.line));
+
+        Some((path, diagnostics))
+    }
+}
+
+/// Builds gutter markers for lines with diagnostics, along with the first
+/// line of their most severe message, and underlines their ranges.
+pub fn annotations(diagnostics: &[Diagnostic]) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+    let mut annotated_lines = Vec::new();
+
+    // Diagnostics are sorted by severity, so the first one for a line wins.
+    for diagnostic in diagnostics {
+        let color = diagnostic.severity.color();
+
+        // Empty ranges underline a single character, and those
+        // spanning several lines underline the rest of the first.
+        let start = diagnostic.character;
+        let end = match diagnostic.end_character {
+            Some(end) => end.max(start + 1),
+            None => usize::MAX,
+        };
+        annotations.push(Annotation {
+            line: diagnostic.line,
+            content: String::new(),
+            style: Style::Underlined,
+            colors: Colors::CustomForeground(color),
+            position: AnnotationPosition::Range { start, end },
+        });
+
+        if annotated_lines.contains(&diagnostic.line) {
+            continue;
+        }
+        annotated_lines.push(diagnostic.line);
+
+        annotations.push(Annotation {
+            line: diagnostic.line,
+            content: String::from("●"),
+            style: Style::Bold,
+            colors: Colors::CustomFocusedForeground(color),
+            position: AnnotationPosition::Gutter,
+        });
+        annotations.push(Annotation {
+            line: diagnostic.line,
+            content: format!(" {}", diagnostic.message.lines().next().unwrap_or("")),
+            style: Style::Italic,
+            colors: Colors::CustomForeground(color),
+            position: AnnotationPosition::EndOfLine,
+        });
+    }
+
+    annotations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotations, Diagnostic, Severity};
+    use crate::util::json;
+    use crate::view::AnnotationPosition;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_all_sorts_diagnostics_by_severity() {
+        let params = json::parse(
+            "{\"uri\":\"file:///src/main.rs\",\"diagnostics\":[\
+             {\"range\":{\"start\":{\"line\":1,\"character\":4},\"end\":{\"line\":1,\"character\":7}},\"severity\":2,\"message\":\"unused variable\"},\
+             {\"range\":{\"start\":{\"line\":1,\"character\":0},\"end\":{\"line\":2,\"character\":1}},\"message\":\"mismatched types\\nexpected i32\"}]}"
+        ).unwrap();
+        let (path, diagnostics) = Diagnostic::parse_all(&params).unwrap();
+
+        assert_eq!(path, PathBuf::from("/src/main.rs"));
+        assert_eq!(diagnostics[0].severity, Severity::Error);
+        assert_eq!(diagnostics[1].severity, Severity::Warning);
+        assert_eq!(diagnostics[1].character, 4);
+        assert_eq!(diagnostics[1].end_character, Some(7));
+        assert_eq!(diagnostics[0].end_character, None);
+
+        // Both ranges are underlined, but only the most severe
+        // diagnostic on the line is described.
+        let annotations = annotations(&diagnostics);
+        assert_eq!(annotations.len(), 4);
+        assert_eq!(annotations[0].position, AnnotationPosition::Range { start: 0, end: usize::MAX });
+        assert_eq!(annotations[1].position, AnnotationPosition::Gutter);
+        assert_eq!(annotations[2].content, " mismatched types");
+        assert_eq!(annotations[3].position, AnnotationPosition::Range { start: 4, end: 7 });
+    }
+}
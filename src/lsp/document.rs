@@ -0,0 +1,183 @@
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A replacement of the text between two positions, which (like all
+/// positions exchanged with language servers) are zero-based lines
+/// and UTF-16 code unit offsets within them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TextEdit {
+    pub start: (usize, usize),
+    pub end: (usize, usize),
+    pub new_text: String,
+}
+
+/// Converts an absolute path into a file URI, percent-encoding
+/// anything other than unreserved characters and separators.
+pub fn uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'/' | b'-' | b'_' | b'.' | b'~' => {
+                uri.push(byte as char)
+            },
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+/// Converts a file URI back into a path, returning
+/// None for URIs using any other scheme.
+pub fn path(uri: &str) -> Option<PathBuf> {
+    let encoded = uri.strip_prefix("file://")?.as_bytes();
+    let mut decoded = Vec::with_capacity(encoded.len());
+    let mut index = 0;
+
+    while index < encoded.len() {
+        let escaped = encoded.get(index + 1..index + 3)
+            .filter(|_| encoded[index] == b'%')
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+
+        match escaped {
+            Some(byte) => {
+                decoded.push(byte);
+                index += 3;
+            },
+            None => {
+                decoded.push(encoded[index]);
+                index += 1;
+            },
+        }
+    }
+
+    Some(PathBuf::from(String::from_utf8_lossy(&decoded).into_owned()))
+}
+
+/// The identifier servers use for the path's language, which for most
+/// languages outside of this list is the same as its extension.
+pub fn language_id(path: &Path) -> String {
+    let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+    let id = match extension {
+        "rs" => "rust",
+        "py" => "python",
+        "rb" => "ruby",
+        "js" | "mjs" => "javascript",
+        "jsx" => "javascriptreact",
+        "ts" => "typescript",
+        "tsx" => "typescriptreact",
+        "h" => "c",
+        "cc" | "cxx" | "hpp" => "cpp",
+        "cs" => "csharp",
+        "ex" | "exs" => "elixir",
+        "hs" => "haskell",
+        "md" => "markdown",
+        "sh" | "bash" => "shellscript",
+        "yml" => "yaml",
+        extension => extension,
+    };
+
+    id.to_string()
+}
+
+/// Converts a grapheme offset within a line into a UTF-16 offset.
+pub fn character(line: &str, offset: usize) -> usize {
+    line.graphemes(true)
+        .take(offset)
+        .map(|grapheme| grapheme.encode_utf16().count())
+        .sum()
+}
+
+/// Converts a UTF-16 offset within a line into a grapheme offset.
+pub fn offset(line: &str, character: usize) -> usize {
+    let mut units = 0;
+
+    line.graphemes(true)
+        .take_while(|grapheme| {
+            units += grapheme.encode_utf16().count();
+            units <= character
+        })
+        .count()
+}
+
+/// Applies edits (which servers provide relative to the original content,
+/// without overlapping one another) to the content.
+pub fn apply_edits(content: &str, edits: &[TextEdit]) -> String {
+    let mut edits: Vec<&TextEdit> = edits.iter().collect();
+    edits.sort_by_key(|edit| edit.start);
+
+    let mut result = String::with_capacity(content.len());
+    let mut copied = 0;
+    for edit in edits {
+        let start = byte_offset(content, edit.start).max(copied);
+        let end = byte_offset(content, edit.end).max(start);
+
+        result.push_str(&content[copied..start]);
+        result.push_str(&edit.new_text);
+        copied = end;
+    }
+    result.push_str(&content[copied..]);
+
+    result
+}
+
+// Finds the byte offset of a position, clamping it to its line (or the content).
+fn byte_offset(content: &str, (line, character): (usize, usize)) -> usize {
+    let line_start = match line {
+        0 => 0,
+        _ => match content.match_indices('\n').nth(line - 1) {
+            Some((index, _)) => index + 1,
+            None => return content.len(),
+        },
+    };
+    let line_end = content[line_start..].find('\n').map(|end| line_start + end).unwrap_or(content.len());
+
+    let mut units = 0;
+    for (index, c) in content[line_start..line_end].char_indices() {
+        if units >= character {
+            return line_start + index;
+        }
+        units += c.len_utf16();
+    }
+
+    line_end
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{apply_edits, character, offset, path, uri, TextEdit};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn uri_and_path_round_trip_special_characters() {
+        let file = Path::new("/home/amp/my project/naïve#1.rs");
+        let encoded = uri(file);
+
+        assert_eq!(encoded, "file:///home/amp/my%20project/na%C3%AFve%231.rs");
+        assert_eq!(path(&encoded), Some(file.to_path_buf()));
+        assert_eq!(path("file:///src/main.rs"), Some(PathBuf::from("/src/main.rs")));
+        assert_eq!(path("untitled:1"), None);
+    }
+
+    #[test]
+    fn character_and_offset_convert_between_graphemes_and_utf16() {
+        let line = "a😀é b";
+
+        assert_eq!(character(line, 2), 3);
+        assert_eq!(character(line, 10), 6);
+        assert_eq!(offset(line, 3), 2);
+        assert_eq!(offset(line, 6), 5);
+    }
+
+    #[test]
+    fn apply_edits_replaces_ranges_relative_to_the_original_content() {
+        let content = "let amp = 1;\nprint(amp);\n";
+        let edits = vec![
+            TextEdit { start: (1, 6), end: (1, 9), new_text: String::from("editor") },
+            TextEdit { start: (0, 4), end: (0, 7), new_text: String::from("editor") },
+        ];
+
+        assert_eq!(apply_edits(content, &edits), "let editor = 1;\nprint(editor);\n");
+    }
+}
@@ -0,0 +1,432 @@
+use crate::errors::*;
+use crate::lsp::{document, Client, Diagnostic, TextEdit};
+use crate::models::application::Event;
+use crate::util::json;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::mpsc::Sender;
+use crate::yaml::yaml::Yaml;
+
+/// Follow-up work for the application, produced while handling server messages.
+#[derive(Debug, PartialEq)]
+pub enum LspAction {
+    /// A definition was found at the specified path, (zero-based)
+    /// line, and UTF-16 offset within that line.
+    ShowLocation(PathBuf, usize, usize),
+
+    /// Hover information for the requested position.
+    ShowHover(String),
+
+    /// Edits to apply (once previewed) to complete a rename.
+    ApplyEdits(Vec<(PathBuf, Vec<TextEdit>)>),
+
+    /// Completions for the word at the requested position.
+    ShowCompletions(Vec<String>),
+}
+
+#[derive(Clone, Copy)]
+enum Request {
+    Initialize,
+    Definition,
+    Hover,
+    Rename,
+    Completion,
+}
+
+struct Server {
+    client: Client,
+    initialized: bool,
+    requests: HashMap<i64, Request>,
+
+    /// Open documents' versions, and the revision of the
+    /// buffer (see `View::revision`) whose content was last sent.
+    documents: HashMap<PathBuf, (i64, usize)>,
+}
+
+/// Starts language servers as files needing them are opened (one per
+/// configured command), keeping them in sync with the buffers' content.
+#[derive(Default)]
+pub struct LanguageServers {
+    servers: HashMap<String, Server>,
+    diagnostics: HashMap<PathBuf, Vec<Diagnostic>>,
+
+    /// Commands that couldn't be started, which aren't retried.
+    failed: HashSet<String>,
+}
+
+impl LanguageServers {
+    /// Opens the document with the server configured for it (starting the
+    /// server if necessary), or updates its content if its buffer's revision
+    /// has changed since. The content is only read when it's sent.
+    pub fn sync<F>(&mut self, command: &[String], path: &Path, revision: usize, content: F, root: &Path, events: &Sender<Event>) -> Result<()>
+        where F: FnOnce() -> String
+    {
+        let name = command.join(" ");
+        if self.failed.contains(&name) {
+            return Ok(());
+        }
+        if !self.servers.contains_key(&name) {
+            match start(&name, command, root, events) {
+                Ok(server) => self.servers.insert(name.clone(), server),
+                Err(error) => {
+                    self.failed.insert(name);
+                    return Err(error);
+                },
+            };
+        }
+
+        let server = self.servers.get_mut(&name).ok_or("Language server isn't running")?;
+        if !server.initialized {
+            // Documents are opened once the server's ready for them.
+            return Ok(());
+        }
+
+        let uri = document::uri(path);
+        match server.documents.get(path).cloned() {
+            None => {
+                server.client.notify("textDocument/didOpen", json::object(vec![
+                    ("textDocument", json::object(vec![
+                        ("uri", json::string(uri)),
+                        ("languageId", json::string(document::language_id(path))),
+                        ("version", Yaml::Integer(1)),
+                        ("text", json::string(content())),
+                    ])),
+                ]))?;
+                server.documents.insert(path.to_path_buf(), (1, revision));
+            },
+            Some((version, previous_revision)) if previous_revision != revision => {
+                server.client.notify("textDocument/didChange", json::object(vec![
+                    ("textDocument", json::object(vec![
+                        ("uri", json::string(uri)),
+                        ("version", Yaml::Integer(version + 1)),
+                    ])),
+                    ("contentChanges", Yaml::Array(vec![
+                        json::object(vec![("text", json::string(content()))]),
+                    ])),
+                ]))?;
+                server.documents.insert(path.to_path_buf(), (version + 1, revision));
+            },
+            _ => (),
+        }
+
+        Ok(())
+    }
+
+    /// Whether the document has been opened with a language server.
+    pub fn is_open(&self, path: &Path) -> bool {
+        self.servers.values().any(|server| server.documents.contains_key(path))
+    }
+
+    pub fn diagnostics(&self, path: &Path) -> &[Diagnostic] {
+        self.diagnostics.get(path).map(|d| d.as_slice()).unwrap_or(&[])
+    }
+
+    /// Diagnostics for all documents, grouped by path.
+    pub fn all_diagnostics(&self) -> Vec<Diagnostic> {
+        let mut paths: Vec<&PathBuf> = self.diagnostics.keys().collect();
+        paths.sort();
+
+        paths.into_iter().flat_map(|path| self.diagnostics[path].iter().cloned()).collect()
+    }
+
+    /// Requests the location of the definition for the symbol at the
+    /// (zero-based) line and UTF-16 offset, which is reported as a
+    /// `ShowLocation` action once the server responds.
+    pub fn goto_definition(&mut self, path: &Path, line: usize, character: usize) -> Result<()> {
+        self.request(Request::Definition, "textDocument/definition", path, line, character, vec![])
+    }
+
+    pub fn hover(&mut self, path: &Path, line: usize, character: usize) -> Result<()> {
+        self.request(Request::Hover, "textDocument/hover", path, line, character, vec![])
+    }
+
+    pub fn rename(&mut self, path: &Path, line: usize, character: usize, name: &str) -> Result<()> {
+        let params = vec![("newName", json::string(name))];
+
+        self.request(Request::Rename, "textDocument/rename", path, line, character, params)
+    }
+
+    pub fn completion(&mut self, path: &Path, line: usize, character: usize) -> Result<()> {
+        self.request(Request::Completion, "textDocument/completion", path, line, character, vec![])
+    }
+
+    fn request(&mut self, request: Request, method: &str, path: &Path, line: usize, character: usize, mut params: Vec<(&str, Yaml)>) -> Result<()> {
+        let server = self.servers
+            .values_mut()
+            .find(|server| server.documents.contains_key(path))
+            .ok_or("No language server is running for this buffer")?;
+        params.push(("textDocument", json::object(vec![("uri", json::string(document::uri(path)))])));
+        params.push(("position", position(line, character)));
+
+        let id = server.client.request(method, json::object(params))?;
+        server.requests.insert(id, request);
+
+        Ok(())
+    }
+
+    /// Handles a message from the named server, returning any follow-up work.
+    pub fn handle_message(&mut self, name: &str, message: &Yaml) -> Result<Vec<LspAction>> {
+        let server = match self.servers.get_mut(name) {
+            Some(server) => server,
+            None => return Ok(Vec::new()),
+        };
+        let mut actions = Vec::new();
+
+        match (message["method"].as_str(), message["id"].is_badvalue()) {
+            // Requests from the server; we don't support any,
+            // but servers may wait on a response regardless.
+            (Some(method), false) => {
+                let result = match method {
+                    "workspace/configuration" => {
+                        let items = message["params"]["items"].as_vec().map(|i| i.len()).unwrap_or(0);
+                        Yaml::Array(vec![Yaml::Null; items])
+                    },
+                    _ => Yaml::Null,
+                };
+                server.client.respond(message["id"].clone(), result)?;
+            },
+            (Some("textDocument/publishDiagnostics"), true) => {
+                if let Some((path, diagnostics)) = Diagnostic::parse_all(&message["params"]) {
+                    self.diagnostics.insert(path, diagnostics);
+                }
+            },
+            (None, false) => {
+                let request = message["id"].as_i64().and_then(|id| server.requests.remove(&id));
+                let request = match request {
+                    Some(request) => request,
+                    None => return Ok(actions),
+                };
+                if let Some(error) = message["error"]["message"].as_str() {
+                    bail!("Language server request failed: {}", error);
+                }
+                let result = &message["result"];
+
+                match request {
+                    Request::Initialize => {
+                        server.initialized = true;
+                        server.client.notify("initialized", json::object(vec![]))?;
+                    },
+                    Request::Definition => {
+                        // Servers respond with a location, a list of them, or a list of links.
+                        let location = match *result {
+                            Yaml::Array(ref locations) => locations.first(),
+                            Yaml::Hash(_) => Some(result),
+                            _ => None,
+                        }.ok_or("No definition found")?;
+                        let uri = location["uri"].as_str().or_else(|| location["targetUri"].as_str());
+                        let range = if location["range"].is_badvalue() {
+                            &location["targetSelectionRange"]
+                        } else {
+                            &location["range"]
+                        };
+                        let path = uri.and_then(document::path).ok_or("Definition isn't in a file")?;
+                        let (line, character) = read_position(&range["start"]).ok_or("Invalid definition location")?;
+
+                        actions.push(LspAction::ShowLocation(path, line, character));
+                    },
+                    Request::Hover => {
+                        let text = hover_text(&result["contents"]);
+                        if text.is_empty() {
+                            bail!("No hover information available");
+                        }
+
+                        actions.push(LspAction::ShowHover(text));
+                    },
+                    Request::Rename => {
+                        let edits = workspace_edits(result);
+                        if edits.is_empty() {
+                            bail!("Nothing to rename");
+                        }
+
+                        actions.push(LspAction::ApplyEdits(edits));
+                    },
+                    Request::Completion => {
+                        actions.push(LspAction::ShowCompletions(completion_items(result)));
+                    },
+                }
+            },
+            _ => (),
+        }
+
+        Ok(actions)
+    }
+
+    /// Discards a server that has exited, along with its diagnostics.
+    pub fn remove(&mut self, name: &str) {
+        if let Some(server) = self.servers.remove(name) {
+            for path in server.documents.keys() {
+                self.diagnostics.remove(path);
+            }
+        }
+    }
+}
+
+fn start(name: &str, command: &[String], root: &Path, events: &Sender<Event>) -> Result<Server> {
+    let mut client = Client::spawn(name, command, root, events.clone())?;
+    let id = client.request("initialize", json::object(vec![
+        ("processId", Yaml::Integer(i64::from(process::id()))),
+        ("rootUri", json::string(document::uri(root))),
+        ("clientInfo", json::object(vec![("name", json::string("amp"))])),
+        ("capabilities", json::object(vec![
+            ("textDocument", json::object(vec![
+                ("synchronization", json::object(vec![])),
+                ("publishDiagnostics", json::object(vec![])),
+                ("definition", json::object(vec![("linkSupport", Yaml::Boolean(true))])),
+                ("hover", json::object(vec![
+                    ("contentFormat", Yaml::Array(vec![json::string("plaintext")])),
+                ])),
+                ("rename", json::object(vec![])),
+                ("completion", json::object(vec![])),
+            ])),
+        ])),
+    ]))?;
+
+    let mut requests = HashMap::new();
+    requests.insert(id, Request::Initialize);
+
+    Ok(Server {
+        client,
+        initialized: false,
+        requests,
+        documents: HashMap::new(),
+    })
+}
+
+fn position(line: usize, character: usize) -> Yaml {
+    json::object(vec![
+        ("line", Yaml::Integer(line as i64)),
+        ("character", Yaml::Integer(character as i64)),
+    ])
+}
+
+fn read_position(position: &Yaml) -> Option<(usize, usize)> {
+    Some((position["line"].as_i64()? as usize, position["character"].as_i64()? as usize))
+}
+
+// Hover contents can be a string, a marked string with a language,
+// markup content, or a list of the former. Code fences are dropped,
+// leaving their content, so that the result reads well on one line.
+fn hover_text(contents: &Yaml) -> String {
+    let text = match *contents {
+        Yaml::String(ref text) => text.clone(),
+        Yaml::Hash(_) => contents["value"].as_str().unwrap_or("").to_string(),
+        Yaml::Array(ref contents) => {
+            contents.iter().map(hover_text).collect::<Vec<_>>().join("\n")
+        },
+        _ => String::new(),
+    };
+
+    text.lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with("```") && *line != "---")
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+// Completions are listed directly or (if incomplete) alongside a flag. Items
+// are inserted as plain text, so snippets fall back to their filter text.
+fn completion_items(result: &Yaml) -> Vec<String> {
+    let items = match *result {
+        Yaml::Array(ref items) => items,
+        _ => match result["items"].as_vec() {
+            Some(items) => items,
+            None => return Vec::new(),
+        },
+    };
+
+    items.iter().filter_map(|item| {
+        let snippet = item["insertTextFormat"].as_i64() == Some(2);
+        let text = item["insertText"]
+            .as_str()
+            .filter(|_| !snippet)
+            .or_else(|| item["filterText"].as_str())
+            .or_else(|| item["label"].as_str())?;
+
+        Some(text.trim().to_string()).filter(|text| !text.is_empty())
+    }).collect()
+}
+
+// Reads a workspace edit's changes, which are keyed by document
+// URI, or listed with their document (if using versioned edits).
+fn workspace_edits(edit: &Yaml) -> Vec<(PathBuf, Vec<TextEdit>)> {
+    let mut documents = Vec::new();
+    if let Some(changes) = edit["changes"].as_hash() {
+        for (uri, edits) in changes {
+            documents.push((uri.as_str(), edits));
+        }
+    }
+    if let Some(changes) = edit["documentChanges"].as_vec() {
+        for change in changes {
+            documents.push((change["textDocument"]["uri"].as_str(), &change["edits"]));
+        }
+    }
+
+    documents.into_iter().filter_map(|(uri, edits)| {
+        let path = uri.and_then(document::path)?;
+        let edits = edits.as_vec()?.iter().filter_map(|edit| {
+            Some(TextEdit {
+                start: read_position(&edit["range"]["start"])?,
+                end: read_position(&edit["range"]["end"])?,
+                new_text: edit["newText"].as_str()?.to_string(),
+            })
+        }).collect();
+
+        Some((path, edits))
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{completion_items, hover_text, workspace_edits, LanguageServers};
+    use crate::lsp::TextEdit;
+    use crate::util::json;
+    use std::path::PathBuf;
+
+    #[test]
+    fn handle_message_ignores_unknown_servers() {
+        let mut servers = LanguageServers::default();
+        let message = json::parse("{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":null}").unwrap();
+
+        assert!(servers.handle_message("rust-analyzer", &message).unwrap().is_empty());
+        assert!(servers.diagnostics(&PathBuf::from("/src/main.rs")).is_empty());
+    }
+
+    #[test]
+    fn hover_text_joins_content_without_code_fences() {
+        let contents = json::parse(
+            "{\"kind\":\"markdown\",\"value\":\"```rust\\nfn main()\\n```\\n\\n---\\n\\nStarts amp.\"}"
+        ).unwrap();
+
+        assert_eq!(hover_text(&contents), "fn main() Starts amp.");
+        assert_eq!(hover_text(&json::parse("[\"usize\",{\"language\":\"rust\",\"value\":\"let x\"}]").unwrap()), "usize let x");
+    }
+
+    #[test]
+    fn completion_items_prefers_plain_insert_text() {
+        let result = json::parse(
+            "{\"isIncomplete\":false,\"items\":[{\"label\":\"len()\",\"insertText\":\"len\"},\
+             {\"label\":\"push(…)\",\"filterText\":\"push\",\"insertText\":\"push(${1:value})\",\"insertTextFormat\":2},{\"label\":\"iter\"}]}"
+        ).unwrap();
+
+        assert_eq!(completion_items(&result), vec!["len", "push", "iter"]);
+        assert!(completion_items(&json::parse("null").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn workspace_edits_reads_both_edit_formats() {
+        let edit = json::parse(
+            "{\"documentChanges\":[{\"textDocument\":{\"uri\":\"file:///src/lib.rs\",\"version\":2},\
+             \"edits\":[{\"range\":{\"start\":{\"line\":0,\"character\":4},\"end\":{\"line\":0,\"character\":7}},\"newText\":\"editor\"}]}]}"
+        ).unwrap();
+
+        assert_eq!(
+            workspace_edits(&edit),
+            vec![(
+                PathBuf::from("/src/lib.rs"),
+                vec![TextEdit { start: (0, 4), end: (0, 7), new_text: String::from("editor") }]
+            )]
+        );
+        assert!(workspace_edits(&json::parse("{\"changes\":{}}").unwrap()).is_empty());
+    }
+}
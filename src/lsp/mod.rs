@@ -0,0 +1,10 @@
+mod client;
+pub mod diagnostic;
+pub mod document;
+mod language_servers;
+
+pub use self::client::Client;
+pub use self::diagnostic::{annotations as diagnostic_annotations, Diagnostic};
+pub use self::diagnostic::ANNOTATION_PROVIDER as DIAGNOSTIC_ANNOTATION_PROVIDER;
+pub use self::document::TextEdit;
+pub use self::language_servers::{LanguageServers, LspAction};
@@ -0,0 +1,144 @@
+use scribe::Workspace;
+use std::collections::{HashMap, HashSet};
+
+/// Organizes open buffers into numbered groups, displayed as tabs in the
+/// status line. Buffers join whichever group is current when they're opened.
+#[derive(Default)]
+pub struct BufferGroups {
+    /// Each open buffer's (zero-based) group, keyed by buffer ID.
+    groups: HashMap<usize, usize>,
+
+    /// The current buffer's group.
+    current: usize,
+
+    /// The buffer most recently selected in each group.
+    last_selected: HashMap<usize, usize>,
+}
+
+impl BufferGroups {
+    /// Brings the groups up to date with the workspace's buffers, assigning
+    /// new ones to the current group and forgetting those that have been closed.
+    pub fn update(&mut self, workspace: &mut Workspace) {
+        let current_id = workspace.current_buffer().and_then(|b| b.id);
+        let mut open_ids = HashSet::new();
+
+        if current_id.is_some() {
+            loop {
+                if let Some(id) = workspace.current_buffer().and_then(|b| b.id) {
+                    open_ids.insert(id);
+                }
+
+                workspace.next_buffer();
+                if workspace.current_buffer().and_then(|b| b.id) == current_id {
+                    break;
+                }
+            }
+        }
+
+        self.groups.retain(|id, _| open_ids.contains(id));
+        self.last_selected.retain(|_, id| open_ids.contains(id));
+        for id in open_ids {
+            self.groups.entry(id).or_insert(self.current);
+        }
+        if let Some(id) = current_id {
+            self.current = self.groups[&id];
+            self.last_selected.insert(self.current, id);
+        }
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn group_of(&self, id: usize) -> Option<usize> {
+        self.groups.get(&id).cloned()
+    }
+
+    /// Moves the buffer into the group, which becomes the current group.
+    pub fn set_group(&mut self, id: usize, group: usize) {
+        self.groups.insert(id, group);
+        self.current = group;
+        self.last_selected.retain(|_, selected| *selected != id);
+        self.last_selected.insert(group, id);
+    }
+
+    /// Groups containing at least one open buffer, in ascending order.
+    pub fn groups(&self) -> Vec<usize> {
+        let mut groups: Vec<usize> = self.groups.values().cloned().collect();
+        groups.sort();
+        groups.dedup();
+
+        groups
+    }
+
+    /// The number of open buffers in the group.
+    pub fn buffer_count(&self, group: usize) -> usize {
+        self.groups.values().filter(|&&g| g == group).count()
+    }
+
+    pub fn last_selected(&self, group: usize) -> Option<usize> {
+        self.last_selected.get(&group).cloned()
+    }
+
+    /// Describes the groups as tabs, with the current one in brackets.
+    /// Returns nothing if all buffers are in the same group.
+    pub fn tabs(&self) -> Option<String> {
+        let groups = self.groups();
+        if groups.len() < 2 {
+            return None;
+        }
+
+        let tabs: Vec<String> = groups
+            .into_iter()
+            .map(|group| if group == self.current {
+                format!("[{}]", group + 1)
+            } else {
+                format!("{}", group + 1)
+            })
+            .collect();
+
+        Some(format!(" {} ", tabs.join(" ")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BufferGroups;
+    use scribe::{Buffer, Workspace};
+    use std::path::Path;
+
+    #[test]
+    fn update_assigns_new_buffers_to_the_current_group_and_forgets_closed_ones() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        let mut groups = BufferGroups::default();
+        groups.update(&mut workspace);
+        let first_id = workspace.current_buffer().unwrap().id.unwrap();
+        groups.set_group(first_id, 1);
+
+        workspace.add_buffer(Buffer::new());
+        groups.update(&mut workspace);
+        let second_id = workspace.current_buffer().unwrap().id.unwrap();
+        assert_eq!(groups.group_of(second_id), Some(1));
+        assert_eq!(groups.buffer_count(1), 2);
+
+        workspace.close_current_buffer();
+        groups.update(&mut workspace);
+        assert_eq!(groups.group_of(second_id), None);
+        assert_eq!(groups.last_selected(1), Some(first_id));
+    }
+
+    #[test]
+    fn tabs_are_only_displayed_for_multiple_groups() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        workspace.add_buffer(Buffer::new());
+        let mut groups = BufferGroups::default();
+        groups.update(&mut workspace);
+        assert_eq!(groups.tabs(), None);
+
+        let id = workspace.current_buffer().unwrap().id.unwrap();
+        groups.set_group(id, 2);
+        assert_eq!(groups.tabs(), Some(String::from(" 1 [3] ")));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::errors::*;
+use crate::util::diff;
+use scribe::Workspace;
+use scribe::buffer::{Position, Range};
+use std::fs;
+use std::path::{Path, PathBuf};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Unchanged lines displayed around each change.
+const CONTEXT_LINES: usize = 3;
+
+struct FileChange {
+    path: PathBuf,
+    original: String,
+    modified: String,
+}
+
+/// A set of modifications spanning multiple files (e.g. a project-wide
+/// rename), previewed as a unified diff before being applied. Changes are
+/// applied as a unit: if any of the affected files have changed since the
+/// set was built, none of them are applied.
+#[derive(Default)]
+pub struct ChangeSet {
+    changes: Vec<FileChange>,
+}
+
+impl ChangeSet {
+    /// Records a file's original and modified content,
+    /// ignoring files whose content hasn't changed.
+    pub fn add(&mut self, path: PathBuf, original: String, modified: String) {
+        if original != modified {
+            self.changes.push(FileChange { path, original, modified });
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Describes the changes as a unified diff, with paths relative to the root.
+    pub fn diff(&self, root: &Path) -> String {
+        let mut output = String::new();
+        for change in &self.changes {
+            let path = display_path(&change.path, root);
+            output.push_str(&format!("--- a/{}\n+++ b/{}\n", path, path));
+            output.push_str(&diff::unified(&change.original, &change.modified, CONTEXT_LINES));
+        }
+
+        output
+    }
+
+    /// Applies changes to open buffers (saving them) and files on disk,
+    /// skipping files whose header has been removed from the provided diff.
+    /// Returns the number of files that were changed.
+    pub fn apply(&self, workspace: &mut Workspace, diff: &str) -> Result<usize> {
+        let root = workspace.path.clone();
+        let changes: Vec<&FileChange> = self.changes
+            .iter()
+            .filter(|change| {
+                let header = format!("+++ b/{}", display_path(&change.path, &root));
+                diff.lines().any(|line| line == header)
+            })
+            .collect();
+        let start_id = workspace.current_buffer().and_then(|b| b.id);
+
+        // Check everything up front, so that changes are applied all or nothing.
+        for change in &changes {
+            if current_content(workspace, &change.path)? != change.original {
+                restore_buffer(workspace, start_id);
+                bail!("{} has changed since the changes were prepared",
+                      display_path(&change.path, &root));
+            }
+        }
+
+        for change in &changes {
+            if workspace.contains_buffer_with_path(&change.path) {
+                workspace.open_buffer(&change.path)?;
+                let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                let data = buffer.data();
+                let end = Position {
+                    line: buffer.line_count() - 1,
+                    offset: data.rsplit('\n').next().unwrap_or("").graphemes(true).count(),
+                };
+                let cursor = *buffer.cursor.clone();
+                buffer.start_operation_group();
+                buffer.delete_range(Range::new(Position { line: 0, offset: 0 }, end));
+                buffer.cursor.move_to(Position { line: 0, offset: 0 });
+                buffer.insert(change.modified.clone());
+                buffer.end_operation_group();
+                buffer.cursor.move_to(cursor);
+                buffer.save().chain_err(|| "Unable to save buffer")?;
+            } else {
+                fs::write(&change.path, &change.modified)?;
+            }
+        }
+        restore_buffer(workspace, start_id);
+
+        Ok(changes.len())
+    }
+}
+
+// Reads a file's content, preferring that of its open buffer (if any).
+fn current_content(workspace: &mut Workspace, path: &Path) -> Result<String> {
+    if workspace.contains_buffer_with_path(path) {
+        workspace.open_buffer(path)?;
+        Ok(workspace.current_buffer().ok_or(BUFFER_MISSING)?.data())
+    } else {
+        fs::read_to_string(path).chain_err(|| format!("Couldn't read {}", path.display()))
+    }
+}
+
+fn restore_buffer(workspace: &mut Workspace, id: Option<usize>) {
+    if id.is_none() {
+        return;
+    }
+
+    while workspace.current_buffer().and_then(|b| b.id) != id {
+        workspace.next_buffer();
+    }
+}
+
+fn display_path(path: &Path, root: &Path) -> String {
+    path.strip_prefix(root).unwrap_or(path).to_string_lossy().into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ChangeSet;
+    use scribe::{Buffer, Workspace};
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn set_up_file(name: &str, content: &str) -> PathBuf {
+        let path = Path::new(env!("OUT_DIR")).join(name);
+        fs::write(&path, content).unwrap();
+
+        path.canonicalize().unwrap()
+    }
+
+    #[test]
+    fn diff_includes_a_header_and_hunks_for_each_file() {
+        let mut changes = ChangeSet::default();
+        changes.add(PathBuf::from("/project/src/lib.rs"), "amp\n".into(), "amp\neditor\n".into());
+        changes.add(PathBuf::from("/project/unchanged"), "amp\n".into(), "amp\n".into());
+
+        assert_eq!(
+            changes.diff(Path::new("/project")),
+            "--- a/src/lib.rs\n+++ b/src/lib.rs\n@@ -1 +1,2 @@\n amp\n+editor\n"
+        );
+    }
+
+    #[test]
+    fn apply_updates_open_buffers_and_files_listed_in_the_diff() {
+        let open_path = set_up_file("change_set_open", "amp\n");
+        let closed_path = set_up_file("change_set_closed", "amp\n");
+        let skipped_path = set_up_file("change_set_skipped", "amp\n");
+        let mut workspace = Workspace::new(Path::new(env!("OUT_DIR"))).unwrap();
+        workspace.add_buffer(Buffer::from_file(&open_path).unwrap());
+        workspace.add_buffer(Buffer::new());
+
+        let mut changes = ChangeSet::default();
+        for path in &[&open_path, &closed_path, &skipped_path] {
+            changes.add(path.to_path_buf(), "amp\n".into(), "editor\n".into());
+        }
+        let diff = changes.diff(&workspace.path).replace("+++ b/change_set_skipped", "");
+
+        assert_eq!(changes.apply(&mut workspace, &diff).unwrap(), 2);
+        assert_eq!(fs::read_to_string(&open_path).unwrap(), "editor\n");
+        assert_eq!(fs::read_to_string(&closed_path).unwrap(), "editor\n");
+        assert_eq!(fs::read_to_string(&skipped_path).unwrap(), "amp\n");
+        assert!(workspace.current_buffer().unwrap().path.is_none());
+        workspace.open_buffer(&open_path).unwrap();
+        assert_eq!(workspace.current_buffer().unwrap().data(), "editor\n");
+    }
+
+    #[test]
+    fn apply_changes_nothing_if_a_file_has_been_modified() {
+        let modified_path = set_up_file("change_set_modified", "amp\n");
+        let other_path = set_up_file("change_set_other", "amp\n");
+        let mut workspace = Workspace::new(Path::new(env!("OUT_DIR"))).unwrap();
+
+        let mut changes = ChangeSet::default();
+        changes.add(other_path.clone(), "amp\n".into(), "editor\n".into());
+        changes.add(modified_path.clone(), "amp\n".into(), "editor\n".into());
+        fs::write(&modified_path, "text\n").unwrap();
+        let diff = changes.diff(&workspace.path);
+
+        assert!(changes.apply(&mut workspace, &diff).is_err());
+        assert_eq!(fs::read_to_string(&other_path).unwrap(), "amp\n");
+    }
+}
@@ -1,24 +1,118 @@
 use crate::errors::*;
 use clipboard::{ClipboardContext, ClipboardProvider};
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use crate::view::Terminal;
 
 /// In-app content can be captured in both regular and full-line selection
 /// modes. This type describes the structure of said content, based on the
 /// context in which it was captured. When OS-level clipboard contents are
-/// used, they are always represented as inline, as we cannot infer block
-/// style without the copy context.
-#[derive(Debug, PartialEq)]
+/// used, we don't have the copy context; content ending with a newline is
+/// treated as a block (i.e. whole lines), and anything else as inline.
+#[derive(Clone, Debug, PartialEq)]
 pub enum ClipboardContent {
     Inline(String),
     Block(String),
     None,
 }
 
+impl ClipboardContent {
+    /// Combines the content with more that's been copied after it. Whole
+    /// lines stay whole: the combined content is a block if the newer content
+    /// is, in which case a newline is added between them if needed.
+    pub fn append(self, content: ClipboardContent) -> ClipboardContent {
+        match (self, content) {
+            (ClipboardContent::None, content) => content,
+            (existing, ClipboardContent::None) => existing,
+            (ClipboardContent::Inline(mut existing), ClipboardContent::Block(content)) => {
+                existing.push('\n');
+                existing.push_str(&content);
+                ClipboardContent::Block(existing)
+            }
+            (ClipboardContent::Inline(mut existing), ClipboardContent::Inline(content)) |
+            (ClipboardContent::Block(mut existing), ClipboardContent::Inline(content)) => {
+                existing.push_str(&content);
+                ClipboardContent::Inline(existing)
+            }
+            (ClipboardContent::Block(mut existing), ClipboardContent::Block(content)) => {
+                existing.push_str(&content);
+                ClipboardContent::Block(existing)
+            }
+        }
+    }
+
+    fn from_system(content: String) -> ClipboardContent {
+        if content.ends_with('\n') {
+            ClipboardContent::Block(content)
+        } else {
+            ClipboardContent::Inline(content)
+        }
+    }
+}
+
+/// The register backed by the system clipboard, used unless another is selected.
+pub const SYSTEM_REGISTER: char = '+';
+
 /// Qualifies in-app copy/paste content with structural information, and
 /// synchronizes said content with the OS-level clipboard (preferring it
 /// in scenarios where it differs from the in-app equivalent).
+///
+/// Content can also be kept in named registers (a-z), which are local to
+/// amp. Selecting one directs the next copy or paste to it, instead.
 pub struct Clipboard {
     content: ClipboardContent,
-    system_clipboard: Option<ClipboardContext>,
+    system_clipboard: Option<SystemClipboard>,
+    registers: BTreeMap<char, ClipboardContent>,
+    selected_register: Option<char>,
+}
+
+/// The external clipboard with which in-app content is synchronized.
+enum SystemClipboard {
+    Native(ClipboardContext),
+
+    /// Uses tmux's paste buffers, which are available on remote servers
+    /// without a display server (and, by extension, a native clipboard).
+    Tmux,
+
+    /// Uses the terminal's own clipboard (via OSC 52 escape sequences), which
+    /// reaches the local machine's clipboard over SSH. Reading it is requested
+    /// from the terminal, which sends its content back whenever it's focused;
+    /// until then, the content last copied or received is used.
+    Osc52 {
+        terminal: Arc<Box<Terminal + Sync + Send + 'static>>,
+        content: Option<String>,
+    },
+}
+
+impl SystemClipboard {
+    fn get_contents(&mut self) -> Result<String> {
+        match *self {
+            SystemClipboard::Native(ref mut clipboard) => clipboard
+                .get_contents()
+                .map_err(|_| Error::from("Failed to read system clipboard")),
+            SystemClipboard::Tmux => tmux_buffer(),
+            SystemClipboard::Osc52 { ref content, .. } => content
+                .clone()
+                .ok_or_else(|| Error::from("The terminal's clipboard hasn't been read")),
+        }
+    }
+
+    fn set_contents(&mut self, content: String) -> Result<()> {
+        match *self {
+            SystemClipboard::Native(ref mut clipboard) => clipboard
+                .set_contents(content)
+                .map_err(|_| Error::from("Failed to update system clipboard")),
+            SystemClipboard::Tmux => set_tmux_buffer(&content),
+            SystemClipboard::Osc52 { ref terminal, content: ref mut current_content } => {
+                terminal.set_clipboard(&content);
+                current_content.replace(content);
+
+                Ok(())
+            }
+        }
+    }
 }
 
 impl Default for Clipboard {
@@ -31,20 +125,59 @@ impl Clipboard {
     pub fn new() -> Clipboard {
         // Initialize and keep a reference to the system clipboard.
         let system_clipboard = match ClipboardProvider::new() {
-            Ok(clipboard) => Some(clipboard),
+            Ok(clipboard) => Some(SystemClipboard::Native(clipboard)),
             Err(_) => None,
         };
 
         Clipboard {
             content: ClipboardContent::None,
             system_clipboard,
+            registers: BTreeMap::new(),
+            selected_register: None,
+        }
+    }
+
+    /// Creates a clipboard synchronized with tmux's paste buffers,
+    /// rather than the native system clipboard.
+    pub fn tmux() -> Clipboard {
+        Clipboard {
+            content: ClipboardContent::None,
+            system_clipboard: Some(SystemClipboard::Tmux),
+            registers: BTreeMap::new(),
+            selected_register: None,
+        }
+    }
+
+    /// Creates a clipboard synchronized with the terminal's own clipboard,
+    /// asking the terminal for its content (which arrives as an event).
+    pub fn osc52(terminal: Arc<Box<Terminal + Sync + Send + 'static>>) -> Clipboard {
+        terminal.request_clipboard();
+
+        Clipboard {
+            content: ClipboardContent::None,
+            system_clipboard: Some(SystemClipboard::Osc52 { terminal, content: None }),
+            registers: BTreeMap::new(),
+            selected_register: None,
+        }
+    }
+
+    /// Keeps content read from the terminal's clipboard, to be
+    /// preferred over the in-app content if they differ.
+    pub fn receive_system_content(&mut self, content: String) {
+        if let Some(SystemClipboard::Osc52 { content: ref mut current_content, .. }) = self.system_clipboard {
+            current_content.replace(content);
         }
     }
 
     /// Returns the in-app clipboard content. However, if in-app content
     /// differs from the system clipboard, the system clipboard content will
     /// be saved to the in-app clipboard as inline data and returned instead.
+    /// If a named register is selected, its content is returned instead.
     pub fn get_content(&mut self) -> &ClipboardContent {
+        if let Some(name) = self.selected_register {
+            return self.registers.entry(name).or_insert(ClipboardContent::None);
+        }
+
         // Check the system clipboard for newer content.
         let new_content = match self.system_clipboard {
             Some(ref mut clipboard) => {
@@ -60,13 +193,13 @@ impl Clipboard {
                                     // We have in-app clipboard content, too. Prefer
                                     // the system clipboard content if they differ.
                                     if content != *app_content {
-                                        Some(ClipboardContent::Inline(content))
+                                        Some(ClipboardContent::from_system(content))
                                     } else {
                                         None
                                     }
                                 }
                                 // We have no in-app clipboard content. Use the system's.
-                                _ => Some(ClipboardContent::Inline(content)),
+                                _ => Some(ClipboardContent::from_system(content)),
                             }
                         }
                     }
@@ -84,8 +217,14 @@ impl Clipboard {
         &self.content
     }
 
-    // Updates the in-app and system clipboards with the specified content.
+    // Updates the in-app and system clipboards with the specified content,
+    // or the selected named register, leaving the clipboards untouched.
     pub fn set_content(&mut self, content: ClipboardContent) -> Result<()> {
+        if let Some(name) = self.selected_register {
+            self.registers.insert(name, content);
+            return Ok(());
+        }
+
         // Update the in-app clipboard.
         self.content = content;
 
@@ -94,9 +233,7 @@ impl Clipboard {
             ClipboardContent::Inline(ref app_content) |
             ClipboardContent::Block(ref app_content) => {
                 if let Some(ref mut clipboard) = self.system_clipboard {
-                    return clipboard
-                        .set_contents(app_content.clone())
-                        .map_err(|_| Error::from("Failed to update system clipboard"));
+                    return clipboard.set_contents(app_content.clone());
                 }
             }
             _ => (),
@@ -104,4 +241,115 @@ impl Clipboard {
 
         Ok(())
     }
+
+    /// Directs subsequent copies and pastes to the named register (a-z),
+    /// or back to the system clipboard (+), until the selection is cleared.
+    pub fn select_register(&mut self, name: char) -> Result<()> {
+        match name {
+            SYSTEM_REGISTER => self.selected_register = None,
+            'a'..='z' => self.selected_register = Some(name),
+            _ => bail!("Invalid register: use a-z or {}", SYSTEM_REGISTER),
+        }
+
+        Ok(())
+    }
+
+    pub fn selected_register(&self) -> char {
+        self.selected_register.unwrap_or(SYSTEM_REGISTER)
+    }
+
+    pub fn clear_register_selection(&mut self) {
+        self.selected_register = None;
+    }
+
+    /// The named registers holding content, in alphabetical order.
+    pub fn registers(&self) -> impl Iterator<Item = (char, &ClipboardContent)> {
+        self.registers
+            .iter()
+            .filter(|(_, content)| **content != ClipboardContent::None)
+            .map(|(name, content)| (*name, content))
+    }
+}
+
+/// Reads the contents of tmux's most recent paste buffer.
+pub fn tmux_buffer() -> Result<String> {
+    let output = Command::new("tmux")
+        .args(["save-buffer", "-"])
+        .output()
+        .chain_err(|| "Couldn't run tmux")?;
+    if !output.status.success() {
+        bail!("Couldn't read tmux buffer: {}", String::from_utf8_lossy(&output.stderr).trim());
+    }
+
+    String::from_utf8(output.stdout).chain_err(|| "tmux buffer isn't valid UTF-8")
+}
+
+fn set_tmux_buffer(content: &str) -> Result<()> {
+    let mut process = Command::new("tmux")
+        .args(["load-buffer", "-"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()
+        .chain_err(|| "Couldn't run tmux")?;
+    process
+        .stdin
+        .take()
+        .ok_or("Couldn't write to tmux")?
+        .write_all(content.as_bytes())
+        .chain_err(|| "Couldn't write to tmux")?;
+
+    // Closing stdin (above) signals the end of the content.
+    if !process.wait().chain_err(|| "Couldn't run tmux")?.success() {
+        bail!("Failed to update tmux buffer");
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Clipboard, ClipboardContent};
+    use crate::view;
+
+    #[test]
+    fn append_keeps_whole_lines_whole() {
+        let inline = |content: &str| ClipboardContent::Inline(String::from(content));
+        let block = |content: &str| ClipboardContent::Block(String::from(content));
+
+        assert_eq!(inline("amp").append(inline(" editor")), inline("amp editor"));
+        assert_eq!(inline("amp").append(block("editor\n")), block("amp\neditor\n"));
+        assert_eq!(block("amp\n").append(block("editor\n")), block("amp\neditor\n"));
+        assert_eq!(block("amp\n").append(inline("editor")), inline("amp\neditor"));
+        assert_eq!(ClipboardContent::None.append(inline("amp")), inline("amp"));
+    }
+
+    #[test]
+    fn selected_registers_hold_content_separately() {
+        let mut clipboard = Clipboard { system_clipboard: None, ..Clipboard::new() };
+        clipboard.set_content(ClipboardContent::Inline(String::from("amp"))).unwrap();
+        clipboard.select_register('a').unwrap();
+        assert_eq!(*clipboard.get_content(), ClipboardContent::None);
+        clipboard.set_content(ClipboardContent::Block(String::from("editor\n"))).unwrap();
+
+        clipboard.clear_register_selection();
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Inline(String::from("amp")));
+        assert_eq!(
+            clipboard.registers().collect::<Vec<_>>(),
+            vec![('a', &ClipboardContent::Block(String::from("editor\n")))]
+        );
+        assert!(clipboard.select_register('1').is_err());
+    }
+
+    #[test]
+    fn osc52_clipboard_prefers_content_received_from_the_terminal() {
+        let mut clipboard = Clipboard::osc52(view::build_terminal().unwrap());
+        assert_eq!(*clipboard.get_content(), ClipboardContent::None);
+
+        clipboard.set_content(ClipboardContent::Block(String::from("amp\n"))).unwrap();
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Block(String::from("amp\n")));
+
+        clipboard.receive_system_content(String::from("editor"));
+        assert_eq!(*clipboard.get_content(), ClipboardContent::Inline(String::from("editor")));
+    }
 }
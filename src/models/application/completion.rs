@@ -0,0 +1,138 @@
+use crate::util::SelectableVec;
+use scribe::buffer::Position;
+use std::slice::Iter;
+
+/// Candidates for completing the word before the cursor, offered in a popup
+/// while in insert mode. Words from open buffers are available straight
+/// away; those from a language server are added once it responds.
+pub struct Completion {
+    /// Where the word being completed starts.
+    pub start: Position,
+
+    /// Set while waiting on a language server's candidates.
+    pub pending: bool,
+    prefix: String,
+    candidates: Vec<String>,
+    matches: SelectableVec<String>,
+}
+
+impl Completion {
+    pub fn new(start: Position, prefix: String, candidates: Vec<String>) -> Completion {
+        let mut completion = Completion {
+            start,
+            pending: false,
+            prefix: String::new(),
+            candidates,
+            matches: SelectableVec::new(Vec::new()),
+        };
+        completion.filter(prefix);
+
+        completion
+    }
+
+    /// Adds a language server's candidates ahead of those from open
+    /// buffers (skipping any duplicates), selecting the first of them.
+    pub fn add(&mut self, candidates: Vec<String>) {
+        let mut combined = Vec::with_capacity(candidates.len() + self.candidates.len());
+        for candidate in candidates.into_iter().chain(self.candidates.drain(..)) {
+            if !combined.contains(&candidate) {
+                combined.push(candidate);
+            }
+        }
+        self.candidates = combined;
+        self.matches = SelectableVec::new(Vec::new());
+        self.pending = false;
+
+        let prefix = self.prefix.clone();
+        self.filter(prefix);
+    }
+
+    /// Narrows the candidates to those starting with (but not
+    /// equal to) the prefix, as the word is typed out further.
+    pub fn filter(&mut self, prefix: String) {
+        let selection = self.selection().map(String::from);
+        let matches: Vec<String> = self.candidates
+            .iter()
+            .filter(|candidate| candidate.starts_with(&prefix) && **candidate != prefix)
+            .cloned()
+            .collect();
+        let index = selection
+            .and_then(|selection| matches.iter().position(|candidate| *candidate == selection))
+            .unwrap_or(0);
+
+        self.matches = SelectableVec::new(matches);
+        self.matches.select(index);
+        self.prefix = prefix;
+    }
+
+    pub fn prefix(&self) -> &str {
+        &self.prefix
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.matches.is_empty()
+    }
+
+    pub fn matches(&self) -> Iter<String> {
+        self.matches.iter()
+    }
+
+    pub fn selection(&self) -> Option<&str> {
+        self.matches.selection().map(String::as_str)
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.matches.selected_index()
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.matches.is_empty() {
+            self.matches.select_next();
+        }
+    }
+
+    pub fn select_previous(&mut self) {
+        if !self.matches.is_empty() {
+            self.matches.select_previous();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Completion;
+    use scribe::buffer::Position;
+
+    fn completion(prefix: &str, candidates: &[&str]) -> Completion {
+        Completion::new(
+            Position { line: 0, offset: 0 },
+            String::from(prefix),
+            candidates.iter().map(|candidate| String::from(*candidate)).collect()
+        )
+    }
+
+    #[test]
+    fn filter_keeps_the_selected_candidate() {
+        let mut completion = completion("app", &["app", "application", "apply", "amp"]);
+        assert_eq!(completion.matches().collect::<Vec<_>>(), vec!["application", "apply"]);
+
+        completion.select_next();
+        completion.filter(String::from("appl"));
+        assert_eq!(completion.selection(), Some("apply"));
+
+        completion.filter(String::from("apply"));
+        assert!(completion.is_empty());
+        assert_eq!(completion.selection(), None);
+    }
+
+    #[test]
+    fn add_puts_new_candidates_first_without_duplicates() {
+        let mut completion = completion("ed", &["editor", "edit"]);
+        completion.pending = true;
+        completion.add(vec![String::from("edit"), String::from("edition"), String::from("amp")]);
+
+        assert!(!completion.pending);
+        assert_eq!(completion.matches().collect::<Vec<_>>(), vec!["edit", "edition", "editor"]);
+        assert_eq!(completion.selection(), Some("edit"));
+    }
+}
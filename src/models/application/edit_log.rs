@@ -0,0 +1,148 @@
+use crate::errors::*;
+use crate::util::{diff, generate};
+use scribe::Buffer;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// Unchanged lines included around each change, so that patches apply cleanly.
+const CONTEXT_LINES: usize = 3;
+
+/// A change made to a buffer by a single key press.
+pub struct EditLogEntry {
+    /// When the change was made, as a Unix timestamp.
+    pub time: i64,
+
+    /// The key that made the change, and the commands it ran.
+    pub operation: String,
+
+    /// The change, as a unified diff (without file headers).
+    pub diff: String,
+}
+
+/// An append-only record of the changes made to a buffer, kept so that
+/// they can be replayed (e.g. to show how a change was made) as a series
+/// of patches. Entries are never removed; undoing a change logs its reversal.
+pub struct EditLog {
+    entries: Vec<EditLogEntry>,
+    content: String,
+}
+
+impl EditLog {
+    pub fn new(content: &str) -> EditLog {
+        EditLog {
+            entries: Vec::new(),
+            content: content.to_string(),
+        }
+    }
+
+    /// Logs the operation if it changed the content since it was last
+    /// logged, returning true if so. Changes made while the buffer wasn't
+    /// tracked (e.g. by a reload) are attributed to the operation, too.
+    pub fn record(&mut self, content: &str, operation: &str) -> bool {
+        if content == self.content {
+            return false;
+        }
+
+        self.entries.push(EditLogEntry {
+            time: now(),
+            operation: operation.to_string(),
+            diff: diff::unified(&self.content, content, CONTEXT_LINES),
+        });
+        self.content = content.to_string();
+
+        true
+    }
+
+    pub fn entries(&self) -> &[EditLogEntry] {
+        &self.entries
+    }
+
+    /// Formats the log as a series of patches, in the mailbox format produced
+    /// by `git format-patch` (and applied in order by `git am`), for the
+    /// file at the specified path, relative to its repository or workspace.
+    pub fn patch_series(&self, path: &str) -> Result<String> {
+        let mut series = String::new();
+        for (index, entry) in self.entries.iter().enumerate() {
+            let date = generate::format_time(entry.time, "%a, %d %b %Y %H:%M:%S %z")?;
+            series.push_str(&format!(
+                "From 0000000000000000000000000000000000000000 Mon Sep 17 00:00:00 2001\n\
+                 From: amp <amp@localhost>\n\
+                 Date: {}\n\
+                 Subject: [PATCH {}/{}] {}\n\
+                 \n\
+                 ---\n\
+                 --- a/{}\n\
+                 +++ b/{}\n\
+                 {}\n",
+                date,
+                index + 1,
+                self.entries.len(),
+                entry.operation,
+                path,
+                path,
+                entry.diff
+            ));
+        }
+
+        Ok(series)
+    }
+}
+
+/// Edit logs for open buffers, keyed by buffer ID.
+#[derive(Default)]
+pub struct EditLogs {
+    logs: HashMap<usize, EditLog>,
+}
+
+impl EditLogs {
+    /// Starts logging the buffer's changes, if it isn't already.
+    pub fn track(&mut self, buffer: &Buffer) {
+        if let Some(id) = buffer.id {
+            self.logs.entry(id).or_insert_with(|| EditLog::new(&buffer.data()));
+        }
+    }
+
+    /// Logs the operation's changes to the buffer, if it's being tracked.
+    pub fn record(&mut self, buffer: &Buffer, operation: &str) {
+        if let Some(log) = buffer.id.and_then(|id| self.logs.get_mut(&id)) {
+            log.record(&buffer.data(), operation);
+        }
+    }
+
+    pub fn get(&self, buffer_id: usize) -> Option<&EditLog> {
+        self.logs.get(&buffer_id)
+    }
+
+    pub fn forget(&mut self, buffer_id: usize) {
+        self.logs.remove(&buffer_id);
+    }
+}
+
+fn now() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs() as i64)
+        .unwrap_or(0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EditLog;
+
+    #[test]
+    fn record_logs_changes_as_patches() {
+        let mut log = EditLog::new("amp\n");
+        assert!(!log.record("amp\n", "cursor::move_down (j)"));
+        assert!(log.record("amp\neditor\n", "buffer::paste (p)"));
+        assert!(log.record("amp\n", "buffer::undo (u)"));
+
+        let operations: Vec<&str> = log.entries().iter().map(|e| e.operation.as_str()).collect();
+        assert_eq!(operations, vec!["buffer::paste (p)", "buffer::undo (u)"]);
+        assert_eq!(log.entries()[1].diff, "@@ -1,2 +1 @@\n amp\n-editor\n");
+
+        let series = log.patch_series("notes.md").unwrap();
+        assert!(series.contains("Subject: [PATCH 1/2] buffer::paste (p)\n\n---\n--- a/notes.md\n+++ b/notes.md\n@@ -1 +1,2 @@\n amp\n+editor\n\n"));
+        assert!(series.contains("Subject: [PATCH 2/2] buffer::undo (u)\n"));
+        assert_eq!(series.matches("\nDate: ").count(), 2);
+    }
+}
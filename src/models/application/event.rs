@@ -1,9 +1,46 @@
-use crate::input::Key;
-use crate::models::application::modes::open::Index;
+use crate::collab::Message;
+use crate::input::{Key, Mouse};
+use crate::models::application::{GitResult, JobResult};
+use crate::models::application::modes::GrepMatch;
+use crate::yaml::yaml::Yaml;
+use std::path::PathBuf;
 
 #[derive(Debug, PartialEq)]
 pub enum Event {
     Key(Key),
+    Mouse(Mouse),
+
+    /// Text pasted into the terminal, received all at once.
+    Paste(String),
+
+    /// The content of the terminal's own clipboard, when requested.
+    Clipboard(String),
     Resize,
-    OpenModeIndexComplete(Index)
+
+    /// Files found by open mode's background indexer (relative to the
+    /// workspace). Complete is set once every file has been found.
+    OpenModeIndexProgress { generation: usize, paths: Vec<PathBuf>, complete: bool },
+
+    /// Matches found by grep mode's background worker. Complete
+    /// is set once the search has finished (or been abandoned).
+    GrepResults { generation: usize, matches: Vec<GrepMatch>, complete: bool },
+
+    /// The result of a git operation run in the background.
+    Git(GitResult),
+    JobComplete(JobResult),
+    DebugMessage(Yaml),
+    DebugAdapterExited,
+
+    /// A message from the language server with the specified name.
+    LanguageServerMessage(String, Yaml),
+    LanguageServerExited(String),
+
+    /// A collaborator has connected to the session being hosted (from the address).
+    CollaboratorConnected(String),
+
+    /// A message from the collaborator (in the specified session).
+    CollaborationMessage(usize, Message),
+
+    /// The collaborator (in the specified session) has disconnected.
+    CollaborationEnded(usize),
 }
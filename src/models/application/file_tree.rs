@@ -0,0 +1,286 @@
+use bloodhound::ExclusionPattern;
+use crate::errors::*;
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// A file or directory listed in the file tree.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FileTreeEntry {
+    pub path: PathBuf,
+    pub directory: bool,
+
+    /// How many directories the entry is nested within, below the root.
+    pub depth: usize,
+    pub expanded: bool,
+}
+
+impl FileTreeEntry {
+    pub fn name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+}
+
+impl fmt::Display for FileTreeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let indicator = match (self.directory, self.expanded) {
+            (true, true) => "▾ ",
+            (true, false) => "▸ ",
+            (false, _) => "  ",
+        };
+        let suffix = if self.directory { "/" } else { "" };
+
+        write!(f, "{}{}{}{}", "  ".repeat(self.depth), indicator, self.name(), suffix)
+    }
+}
+
+/// The workspace's files and directories, listed as they'd appear in a
+/// sidebar: directories are only read once they're first expanded, and
+/// their contents are listed beneath them, directories first.
+pub struct FileTree {
+    root: PathBuf,
+    exclusions: Vec<ExclusionPattern>,
+    entries: Vec<FileTreeEntry>,
+    selected_index: usize,
+}
+
+impl FileTree {
+    pub fn new(root: PathBuf, exclusions: Option<Vec<ExclusionPattern>>) -> Result<FileTree> {
+        let mut tree = FileTree {
+            root,
+            exclusions: exclusions.unwrap_or_default(),
+            entries: Vec::new(),
+            selected_index: 0,
+        };
+        tree.entries = tree.read_directory(&tree.root, 0)?;
+
+        Ok(tree)
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The visible entries, in the order they're listed.
+    pub fn entries(&self) -> &[FileTreeEntry] {
+        &self.entries
+    }
+
+    pub fn selected_index(&self) -> usize {
+        self.selected_index
+    }
+
+    pub fn selection(&self) -> Option<&FileTreeEntry> {
+        self.entries.get(self.selected_index)
+    }
+
+    pub fn select_previous(&mut self) {
+        self.selected_index = self.selected_index.saturating_sub(1);
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected_index + 1 < self.entries.len() {
+            self.selected_index += 1;
+        }
+    }
+
+    /// Lists the selected directory's contents beneath it, reading them
+    /// from disk. Does nothing if it isn't a directory, or is expanded.
+    pub fn expand(&mut self) -> Result<()> {
+        let (path, depth) = match self.selection() {
+            Some(entry) if entry.directory && !entry.expanded => (entry.path.clone(), entry.depth),
+            _ => return Ok(()),
+        };
+        let children = self.read_directory(&path, depth + 1)?;
+        let index = self.selected_index;
+        self.entries[index].expanded = true;
+        self.entries.splice(index + 1..index + 1, children);
+
+        Ok(())
+    }
+
+    /// Hides the selected directory's contents or, if it's
+    /// a file or collapsed directory, selects its parent.
+    pub fn collapse(&mut self) {
+        let index = self.selected_index;
+        let (depth, expanded) = match self.selection() {
+            Some(entry) => (entry.depth, entry.expanded),
+            None => return,
+        };
+
+        if expanded {
+            let end = self.subtree_end(index);
+            self.entries.drain(index + 1..end);
+            self.entries[index].expanded = false;
+        } else if let Some(parent) = self.entries[..index].iter().rposition(|entry| entry.depth < depth) {
+            self.selected_index = parent;
+        }
+    }
+
+    /// Re-reads the expanded directories (e.g. after files have been
+    /// created or removed), keeping the selection where possible.
+    pub fn refresh(&mut self) -> Result<()> {
+        let expanded: HashSet<PathBuf> = self.entries
+            .iter()
+            .filter(|entry| entry.expanded)
+            .map(|entry| entry.path.clone())
+            .collect();
+        let selected_path = self.selection().map(|entry| entry.path.clone());
+
+        let mut entries = self.read_directory(&self.root, 0)?;
+        let mut index = 0;
+        while index < entries.len() {
+            if entries[index].directory && expanded.contains(&entries[index].path) {
+                let children = self.read_directory(&entries[index].path, entries[index].depth + 1)?;
+                entries[index].expanded = true;
+                entries.splice(index + 1..index + 1, children);
+            }
+            index += 1;
+        }
+        self.entries = entries;
+
+        let previous_index = self.selected_index;
+        if !selected_path.map(|path| self.select_path(&path)).unwrap_or(false) {
+            self.selected_index = previous_index.min(self.entries.len().saturating_sub(1));
+        }
+
+        Ok(())
+    }
+
+    /// Expands the directories containing the path, and selects it.
+    /// Returns false if it isn't listed in the tree.
+    pub fn reveal(&mut self, path: &Path) -> Result<bool> {
+        let relative_path = match path.strip_prefix(&self.root) {
+            Ok(relative_path) => relative_path.to_path_buf(),
+            Err(_) => return Ok(false),
+        };
+
+        let mut directory = self.root.clone();
+        let mut components = relative_path.components().peekable();
+        while let Some(component) = components.next() {
+            if components.peek().is_none() {
+                break;
+            }
+            directory.push(component);
+            if !self.select_path(&directory) {
+                return Ok(false);
+            }
+            self.expand()?;
+        }
+
+        Ok(self.select_path(path))
+    }
+
+    // Selects the entry with the specified path, returning false if there isn't one.
+    fn select_path(&mut self, path: &Path) -> bool {
+        match self.entries.iter().position(|entry| entry.path == path) {
+            Some(index) => {
+                self.selected_index = index;
+                true
+            }
+            None => false,
+        }
+    }
+
+    // The index following the last entry nested within the one at the specified index.
+    fn subtree_end(&self, index: usize) -> usize {
+        let depth = self.entries[index].depth;
+
+        self.entries[index + 1..]
+            .iter()
+            .position(|entry| entry.depth <= depth)
+            .map(|offset| index + 1 + offset)
+            .unwrap_or_else(|| self.entries.len())
+    }
+
+    fn read_directory(&self, path: &Path, depth: usize) -> Result<Vec<FileTreeEntry>> {
+        let mut entries = Vec::new();
+        let listing = fs::read_dir(path)
+            .chain_err(|| format!("Couldn't read the {} directory", path.to_string_lossy()))?;
+
+        for dir_entry in listing.filter_map(|dir_entry| dir_entry.ok()) {
+            let path = dir_entry.path();
+            if self.exclusions.iter().any(|exclusion| exclusion.matches_path(&path)) {
+                continue;
+            }
+
+            entries.push(FileTreeEntry {
+                directory: path.is_dir(),
+                path,
+                depth,
+                expanded: false,
+            });
+        }
+        entries.sort_by(|a, b| b.directory.cmp(&a.directory).then_with(|| a.path.cmp(&b.path)));
+
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::FileTree;
+    use bloodhound::ExclusionPattern;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn workspace(name: &str) -> PathBuf {
+        let root = env::temp_dir().join(name);
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src/models")).unwrap();
+        fs::create_dir_all(root.join(".git")).unwrap();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("src/models/mod.rs"), "").unwrap();
+
+        root
+    }
+
+    fn labels(tree: &FileTree) -> Vec<String> {
+        tree.entries().iter().map(|entry| entry.to_string()).collect()
+    }
+
+    #[test]
+    fn directories_are_listed_first_and_read_once_expanded() {
+        let root = workspace("amp_file_tree_expand");
+        let exclusions = vec![ExclusionPattern::new("**/.git").unwrap()];
+        let mut tree = FileTree::new(root.clone(), Some(exclusions)).unwrap();
+        assert_eq!(labels(&tree), vec!["▸ src/", "  Cargo.toml"]);
+
+        tree.expand().unwrap();
+        assert_eq!(labels(&tree), vec!["▾ src/", "  ▸ models/", "    main.rs", "  Cargo.toml"]);
+
+        // Collapsing a file selects its directory, which collapses next.
+        tree.select_next();
+        tree.select_next();
+        tree.collapse();
+        assert_eq!(tree.selected_index(), 0);
+        tree.collapse();
+        assert_eq!(labels(&tree), vec!["▸ src/", "  Cargo.toml"]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn refresh_lists_new_files_in_expanded_directories() {
+        let root = workspace("amp_file_tree_refresh");
+        let mut tree = FileTree::new(root.clone(), None).unwrap();
+        assert!(tree.reveal(&root.join("src/models/mod.rs")).unwrap());
+        assert_eq!(tree.selection().map(|entry| entry.name()), Some(String::from("mod.rs")));
+
+        fs::write(root.join("src/lib.rs"), "").unwrap();
+        tree.refresh().unwrap();
+        assert_eq!(
+            labels(&tree),
+            vec!["▸ .git/", "▾ src/", "  ▾ models/", "      mod.rs", "    lib.rs", "    main.rs", "  Cargo.toml"]
+        );
+        assert_eq!(tree.selection().map(|entry| entry.name()), Some(String::from("mod.rs")));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
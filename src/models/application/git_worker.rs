@@ -0,0 +1,680 @@
+use crate::errors::*;
+use crate::models::application::Event;
+use crate::util::git_blame::{self, BlameLine};
+use crate::util::git_gutter::{GitGutter, Hunk};
+use crate::util::git_stash::{self, StashEntry};
+use crate::util::git_status;
+use git2::{self, BranchType, Index, IndexEntry, Oid, Repository};
+use git2::build::CheckoutBuilder;
+use regex::Regex;
+use scribe::Buffer;
+use std::collections::{HashMap, VecDeque};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::{Duration, SystemTime};
+
+// How often the worker checks whether the index or HEAD have changed
+// (e.g. from staging or switching branches, in or out of Amp).
+const REPOSITORY_CHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+enum GitRequest {
+    Status(Option<PathBuf>),
+    Hunks { buffer_id: usize, path: PathBuf, data: String },
+    Add(PathBuf),
+    Branches,
+    Checkout(String),
+    Stashes,
+    Stash,
+    ApplyStash(usize),
+    DropStash(usize),
+    Blame { buffer_id: usize, path: PathBuf, data: String, line: Option<usize> },
+    CommitDetails { commit: Oid, date_format: String },
+    StageHunk { path: PathBuf, hunk: Hunk, lines: String },
+    StagedHunk { buffer_id: usize, path: PathBuf, hunk: Hunk },
+    RemoteUrl { path: PathBuf, lines: Option<(usize, usize)> },
+}
+
+impl GitRequest {
+    // Describes the operation in the status line while it's in progress. Status
+    // and diff requests aren't described; they're made in the background, too.
+    fn description(&self) -> Option<&'static str> {
+        match *self {
+            GitRequest::Status(_) | GitRequest::Hunks { .. } => None,
+            GitRequest::Add(_) => Some("adding"),
+            GitRequest::Branches => Some("listing branches"),
+            GitRequest::Checkout(_) => Some("checking out"),
+            GitRequest::Stashes => Some("listing stashes"),
+            GitRequest::Stash => Some("stashing"),
+            GitRequest::ApplyStash(_) => Some("applying stash"),
+            GitRequest::DropStash(_) => Some("dropping stash"),
+            GitRequest::Blame { .. } => Some("blaming"),
+            GitRequest::CommitDetails { .. } => Some("loading commit"),
+            GitRequest::StageHunk { .. } => Some("staging"),
+            GitRequest::StagedHunk { .. } => Some("reverting"),
+            GitRequest::RemoteUrl { .. } => Some("building URL"),
+        }
+    }
+}
+
+/// The outcome of a git operation run in the background.
+#[derive(Debug, PartialEq)]
+pub enum GitResult {
+    /// The status line's description of the file's status (and the current branch).
+    Status { path: Option<PathBuf>, content: String },
+
+    /// The lines in the buffer that differ from its file's staged content,
+    /// sent only when they've been diffed again.
+    Hunks { buffer_id: usize, hunks: std::result::Result<Vec<Hunk>, String> },
+
+    /// The file has been staged, unless there was an error.
+    Added { path: PathBuf, error: Option<String> },
+
+    /// The repository's local branches.
+    Branches(std::result::Result<Vec<String>, String>),
+
+    /// The branch has been checked out, unless there was an error.
+    CheckedOut { error: Option<String> },
+
+    /// The repository's stashes, newest first.
+    Stashes(std::result::Result<Vec<StashEntry>, String>),
+
+    /// The working tree's changes have been stashed, unless there was an error.
+    Stashed { error: Option<String> },
+
+    /// The stash has been applied to the working tree, unless there was an error.
+    StashApplied { error: Option<String> },
+
+    /// The stash has been dropped, unless there was an error.
+    StashDropped { error: Option<String> },
+
+    /// The commit that last changed each of the buffer's lines, requested
+    /// for a single line (to describe it), or for all of them otherwise.
+    Blame { buffer_id: usize, line: Option<usize>, lines: std::result::Result<Vec<Option<BlameLine>>, String> },
+
+    /// A commit's message and changes.
+    CommitDetails(std::result::Result<String, String>),
+
+    /// The hunk has been staged, unless there was an error.
+    HunkStaged { error: Option<String> },
+
+    /// The staged lines that the buffer's hunk replaces, to revert it.
+    StagedHunk { buffer_id: usize, hunk: Hunk, lines: std::result::Result<String, String> },
+
+    /// A GitHub URL for the file (and lines) as of the last commit.
+    RemoteUrl(std::result::Result<String, String>),
+
+    /// The index or HEAD have changed, so statuses and diffs are out of date.
+    RepositoryChanged,
+}
+
+impl GitResult {
+    // Whether the result completes an operation described in the status line.
+    fn described(&self) -> bool {
+        !matches!(*self, GitResult::Status { .. } | GitResult::Hunks { .. } | GitResult::RepositoryChanged)
+    }
+}
+
+// What the status line's git status was last requested for.
+#[derive(Clone, Debug, PartialEq)]
+struct Snapshot {
+    path: Option<PathBuf>,
+    modified: bool,
+}
+
+// The index and HEAD files' modification times and sizes, which the worker
+// compares to notice changes to them, rather than reading the repository.
+type RepositoryState = [Option<(SystemTime, u64)>; 2];
+
+/// Runs git operations on a background thread, which has a handle on the
+/// repository of its own, so that they don't block the UI in large repositories.
+/// Results are posted back through the event loop as `Event::Git`.
+pub struct GitWorker {
+    requests: Option<Sender<GitRequest>>,
+    snapshot: Option<Snapshot>,
+
+    /// The latest description of a file's status, and the file it describes.
+    status: Option<(Option<PathBuf>, String)>,
+    refreshing: bool,
+
+    /// Descriptions of the operations in progress, in the order they were
+    /// requested (which is the order the worker completes them in).
+    operations: VecDeque<&'static str>,
+
+    /// The revision (see `View::revision`) each buffer was last sent to be diffed at.
+    diffed: HashMap<usize, usize>,
+}
+
+impl GitWorker {
+    /// Starts a worker for the repository, if there is one;
+    /// without one, requests are ignored (or refused).
+    pub fn new(repo: Option<&Repository>, events: Sender<Event>) -> GitWorker {
+        let requests = repo.map(|repo| {
+            let (requests, worker_requests) = mpsc::channel();
+            let git_dir = repo.path().to_path_buf();
+            thread::spawn(move || work(&git_dir, &worker_requests, &events));
+
+            requests
+        });
+
+        GitWorker {
+            requests,
+            snapshot: None,
+            status: None,
+            refreshing: false,
+            operations: VecDeque::new(),
+            diffed: HashMap::new(),
+        }
+    }
+
+    /// Requests the file's status if it's been modified or saved (or a different
+    /// file is current) since it was last requested, or the repository's changed.
+    pub fn refresh_status(&mut self, path: Option<&Path>, modified: bool) {
+        let snapshot = Snapshot { path: path.map(Path::to_path_buf), modified };
+        if self.snapshot.as_ref() == Some(&snapshot) {
+            return;
+        }
+
+        if self.send(GitRequest::Status(snapshot.path.clone())) {
+            self.refreshing = true;
+        }
+        self.snapshot = Some(snapshot);
+    }
+
+    /// Requests a diff of the buffer's content, at its current revision, if it's
+    /// been edited (or the repository's changed) since it was last diffed.
+    pub fn diff(&mut self, buffer: &Buffer, revision: Option<usize>) {
+        let (buffer_id, path) = match (buffer.id, buffer.path.as_ref()) {
+            (Some(id), Some(path)) => (id, path.clone()),
+            _ => return,
+        };
+        if revision.is_some() && self.diffed.get(&buffer_id) == revision.as_ref() {
+            return;
+        }
+
+        if let Some(revision) = revision {
+            self.diffed.insert(buffer_id, revision);
+        }
+        self.send(GitRequest::Hunks { buffer_id, path, data: buffer.data() });
+    }
+
+    /// Forgets when a closed buffer was last diffed, so
+    /// that a buffer reusing its ID is diffed afresh.
+    pub fn forget_buffer(&mut self, buffer_id: usize) {
+        self.diffed.remove(&buffer_id);
+    }
+
+    /// Requests that the file be staged.
+    pub fn add(&mut self, path: PathBuf) -> Result<()> {
+        self.start(GitRequest::Add(path))
+    }
+
+    /// Requests the repository's local branches.
+    pub fn list_branches(&mut self) -> Result<()> {
+        self.start(GitRequest::Branches)
+    }
+
+    /// Requests that the local branch be checked out, without
+    /// overwriting any changes in the working tree.
+    pub fn checkout(&mut self, branch: String) -> Result<()> {
+        self.start(GitRequest::Checkout(branch))
+    }
+
+    /// Requests the repository's stashes, along with their changes.
+    pub fn list_stashes(&mut self) -> Result<()> {
+        self.start(GitRequest::Stashes)
+    }
+
+    /// Requests that the working tree's changes be stashed.
+    pub fn stash(&mut self) -> Result<()> {
+        self.start(GitRequest::Stash)
+    }
+
+    /// Requests that the stash be applied to the working tree, keeping it.
+    pub fn apply_stash(&mut self, index: usize) -> Result<()> {
+        self.start(GitRequest::ApplyStash(index))
+    }
+
+    /// Requests that the stash be dropped.
+    pub fn drop_stash(&mut self, index: usize) -> Result<()> {
+        self.start(GitRequest::DropStash(index))
+    }
+
+    /// Requests the commit that last changed each of the buffer's lines,
+    /// to describe the specified line, or to show them all otherwise.
+    pub fn blame(&mut self, buffer_id: usize, path: PathBuf, data: String, line: Option<usize>) -> Result<()> {
+        self.start(GitRequest::Blame { buffer_id, path, data, line })
+    }
+
+    /// Requests the commit's message and changes.
+    pub fn commit_details(&mut self, commit: Oid, date_format: String) -> Result<()> {
+        self.start(GitRequest::CommitDetails { commit, date_format })
+    }
+
+    /// Requests that the hunk's staged lines be replaced with the specified ones.
+    pub fn stage_hunk(&mut self, path: PathBuf, hunk: Hunk, lines: String) -> Result<()> {
+        self.start(GitRequest::StageHunk { path, hunk, lines })
+    }
+
+    /// Requests the staged lines that the buffer's hunk replaces.
+    pub fn staged_hunk(&mut self, buffer_id: usize, path: PathBuf, hunk: Hunk) -> Result<()> {
+        self.start(GitRequest::StagedHunk { buffer_id, path, hunk })
+    }
+
+    /// Requests a GitHub URL for the file, and the (one-based) range of lines, if any.
+    pub fn remote_url(&mut self, path: PathBuf, lines: Option<(usize, usize)>) -> Result<()> {
+        self.start(GitRequest::RemoteUrl { path, lines })
+    }
+
+    /// Tracks the result's completion, keeping the latest status description.
+    pub fn complete(&mut self, result: &GitResult) {
+        if result.described() {
+            self.operations.pop_front();
+        }
+        match *result {
+            GitResult::Status { ref path, ref content } => {
+                if self.snapshot.as_ref().map(|snapshot| &snapshot.path) == Some(path) {
+                    self.refreshing = false;
+                }
+                self.status = Some((path.clone(), content.clone()));
+            }
+            GitResult::RepositoryChanged => {
+                // Statuses and diffs need to be requested again.
+                self.snapshot = None;
+                self.diffed.clear();
+            }
+            _ => (),
+        }
+    }
+
+    /// Whether any operations described in the status line are in progress.
+    #[cfg(test)]
+    pub fn busy(&self) -> bool {
+        !self.operations.is_empty()
+    }
+
+    /// Describes the file's status (and the current branch) for the status
+    /// line, noting the latest operation that's still in progress, if any.
+    pub fn status_line(&self, path: Option<&Path>) -> String {
+        let mut content = match self.status {
+            Some((ref status_path, ref content)) if status_path.as_deref() == path => content.clone(),
+            _ => String::new(),
+        };
+        if let Some(operation) = self.operations.back() {
+            content.push_str(&format!(" [{}]", operation));
+        } else if self.refreshing {
+            content.push_str(" [updating]");
+        }
+
+        content.trim_start().to_string()
+    }
+
+    // Sends the request to the worker, tracking it until it's complete.
+    fn start(&mut self, request: GitRequest) -> Result<()> {
+        let description = request.description();
+        if !self.send(request) {
+            bail!("No repository available");
+        }
+        self.operations.extend(description);
+
+        Ok(())
+    }
+
+    // Sends the request to the worker, returning false if there isn't one.
+    fn send(&mut self, request: GitRequest) -> bool {
+        let sent = self.requests.as_ref().map(|requests| requests.send(request).is_ok());
+        if sent == Some(false) {
+            self.requests = None;
+        }
+
+        sent == Some(true)
+    }
+}
+
+fn work(git_dir: &Path, requests: &Receiver<GitRequest>, events: &Sender<Event>) {
+    let mut repo = match Repository::open(git_dir) {
+        Ok(repo) => repo,
+        Err(_) => return,
+    };
+    let mut git_gutter = GitGutter::default();
+    let mut state = repository_state(git_dir);
+
+    loop {
+        match requests.recv_timeout(REPOSITORY_CHECK_INTERVAL) {
+            Ok(mut request) => {
+                // Only the latest of a run of diffs (or status requests) is worth
+                // handling; there may be a backlog of them while typing quickly.
+                while let Ok(next) = requests.try_recv() {
+                    if superseded(&request, &next) {
+                        request = next;
+                    } else {
+                        handle(&mut repo, &mut git_gutter, request, events);
+                        request = next;
+                    }
+                }
+                handle(&mut repo, &mut git_gutter, request, events);
+            }
+            Err(RecvTimeoutError::Timeout) => (),
+            Err(RecvTimeoutError::Disconnected) => return,
+        }
+
+        let current_state = repository_state(git_dir);
+        if current_state != state {
+            state = current_state;
+            let _ = events.send(Event::Git(GitResult::RepositoryChanged));
+        }
+    }
+}
+
+fn repository_state(git_dir: &Path) -> RepositoryState {
+    let file_state = |name| {
+        let metadata = fs::metadata(git_dir.join(name)).ok()?;
+
+        Some((metadata.modified().ok()?, metadata.len()))
+    };
+
+    [file_state("index"), file_state("HEAD")]
+}
+
+fn superseded(request: &GitRequest, next: &GitRequest) -> bool {
+    match (request, next) {
+        (GitRequest::Status(_), GitRequest::Status(_)) => true,
+        (GitRequest::Hunks { buffer_id, .. }, GitRequest::Hunks { buffer_id: next_id, .. }) => buffer_id == next_id,
+        _ => false,
+    }
+}
+
+fn handle(repo: &mut Repository, git_gutter: &mut GitGutter, request: GitRequest, events: &Sender<Event>) {
+    let result = match request {
+        GitRequest::Status(path) => GitResult::Status {
+            content: git_status::describe(repo, path.as_deref()),
+            path,
+        },
+        GitRequest::Hunks { buffer_id, path, data } => match git_gutter.update(repo, buffer_id, &path, &data) {
+            Ok(true) => GitResult::Hunks { buffer_id, hunks: Ok(git_gutter.hunks(buffer_id).to_vec()) },
+            Ok(false) => return,
+            Err(error) => GitResult::Hunks { buffer_id, hunks: Err(error.to_string()) },
+        },
+        GitRequest::Add(path) => GitResult::Added {
+            error: error_message(add(repo, &path)),
+            path,
+        },
+        GitRequest::Branches => GitResult::Branches(branches(repo).map_err(|error| error.to_string())),
+        GitRequest::Checkout(branch) => GitResult::CheckedOut { error: error_message(checkout(repo, &branch)) },
+        GitRequest::Stashes => GitResult::Stashes(git_stash::entries(repo).map_err(|error| error.to_string())),
+        GitRequest::Stash => GitResult::Stashed { error: error_message(git_stash::save(repo)) },
+        GitRequest::ApplyStash(index) => GitResult::StashApplied {
+            error: error_message(repo.stash_apply(index, None).chain_err(|| "Couldn't apply the selected stash")),
+        },
+        GitRequest::DropStash(index) => GitResult::StashDropped {
+            error: error_message(repo.stash_drop(index).chain_err(|| "Couldn't drop the selected stash")),
+        },
+        GitRequest::Blame { buffer_id, path, data, line } => GitResult::Blame {
+            buffer_id,
+            line,
+            lines: relative_path(repo, &path)
+                .and_then(|path| git_blame::blame(repo, &path, &data))
+                .map_err(|error| error.to_string()),
+        },
+        GitRequest::CommitDetails { commit, date_format } => GitResult::CommitDetails(
+            git_blame::commit_details(repo, commit, &date_format).map_err(|error| error.to_string())
+        ),
+        GitRequest::StageHunk { path, hunk, lines } => GitResult::HunkStaged {
+            error: error_message(stage_hunk(repo, &path, &hunk, &lines)),
+        },
+        GitRequest::StagedHunk { buffer_id, path, hunk } => GitResult::StagedHunk {
+            buffer_id,
+            lines: staged_hunk(repo, &path, &hunk).map_err(|error| error.to_string()),
+            hunk,
+        },
+        GitRequest::RemoteUrl { path, lines } => GitResult::RemoteUrl(
+            remote_url(repo, &path, lines).map_err(|error| error.to_string())
+        ),
+    };
+
+    let _ = events.send(Event::Git(result));
+}
+
+fn error_message(result: Result<()>) -> Option<String> {
+    result.err().map(|error| error.to_string())
+}
+
+fn add(repo: &Repository, path: &Path) -> Result<()> {
+    let relative_path = relative_path(repo, path)?;
+    let mut index = repo.index().chain_err(|| "Couldn't get the repository index")?;
+
+    index.add_path(&relative_path).chain_err(|| "Failed to add path to index.")?;
+    index.write().chain_err(|| "Failed to write index.")
+}
+
+fn branches(repo: &Repository) -> Result<Vec<String>> {
+    let branches = repo
+        .branches(Some(BranchType::Local))
+        .chain_err(|| "Couldn't list the repository's branches")?;
+
+    Ok(branches
+        .filter_map(|branch| branch.ok())
+        .filter_map(|(branch, _)| branch.name().ok().and_then(|n| n.map(String::from)))
+        .collect())
+}
+
+fn checkout(repo: &Repository, branch_name: &str) -> Result<()> {
+    let branch = repo
+        .find_branch(branch_name, BranchType::Local)
+        .chain_err(|| "Couldn't find the selected branch")?;
+    let reference = branch.get();
+    let tree = reference
+        .peel_to_tree()
+        .chain_err(|| "Couldn't find a tree for the selected branch")?;
+
+    // Use a safe checkout, so that we don't clobber local changes.
+    repo.checkout_tree(tree.as_object(), Some(CheckoutBuilder::new().safe()))
+        .chain_err(|| "Couldn't check out the selected branch")?;
+    repo.set_head(reference.name().ok_or("Invalid branch reference name")?)
+        .chain_err(|| "Couldn't update HEAD to the selected branch")
+}
+
+// Swaps the lines the hunk replaces in the staged file for the specified ones.
+fn stage_hunk(repo: &Repository, path: &Path, hunk: &Hunk, lines: &str) -> Result<()> {
+    let (mut index, mut entry, staged) = staged_file(repo, path)?;
+    let staged_lines = split_staged_lines(&staged, hunk)?;
+    let content = format!(
+        "{}{}{}",
+        staged_lines[..hunk.original_start].concat(),
+        lines,
+        staged_lines[hunk.original_start + hunk.original_lines..].concat()
+    );
+    entry.id = repo.blob(content.as_bytes()).chain_err(|| "Couldn't write the staged content")?;
+    entry.file_size = content.len() as u32;
+
+    index.add(&entry).chain_err(|| "Failed to add hunk to index.")?;
+    index.write().chain_err(|| "Failed to write index.")
+}
+
+// The staged lines that the hunk replaces.
+fn staged_hunk(repo: &Repository, path: &Path, hunk: &Hunk) -> Result<String> {
+    let (_, _, staged) = staged_file(repo, path)?;
+    let staged_lines = split_staged_lines(&staged, hunk)?;
+
+    Ok(staged_lines[hunk.original_start..hunk.original_start + hunk.original_lines].concat())
+}
+
+// The repository's index, and the file's entry and content in it.
+fn staged_file(repo: &Repository, path: &Path) -> Result<(Index, IndexEntry, String)> {
+    let mut index = repo.index().chain_err(|| "Couldn't get the repository index")?;
+    index.read(false).chain_err(|| "Couldn't read the repository index")?;
+    let entry = index
+        .get_path(&relative_path(repo, path)?, 0)
+        .ok_or("The file isn't tracked by the repository")?;
+    let blob = repo.find_blob(entry.id).chain_err(|| "Couldn't read the staged file")?;
+    let content = String::from_utf8_lossy(blob.content()).into_owned();
+
+    Ok((index, entry, content))
+}
+
+// Splits the staged content into lines, making sure that the hunk is within
+// them; the index may have changed since the hunk was found.
+fn split_staged_lines<'a>(staged: &'a str, hunk: &Hunk) -> Result<Vec<&'a str>> {
+    let lines: Vec<&str> = staged.split_inclusive('\n').collect();
+    if hunk.original_start + hunk.original_lines > lines.len() {
+        bail!("The file's staged content has changed; try again once its changes are updated");
+    }
+
+    Ok(lines)
+}
+
+// Builds a GitHub URL for the file as of the last commit, so that it refers
+// to a snapshot of the file as it looks at this very moment.
+fn remote_url(repo: &Repository, path: &Path, lines: Option<(usize, usize)>) -> Result<String> {
+    let remote = repo.find_remote("origin").chain_err(|| {
+        "Couldn't find a remote \"origin\""
+    })?;
+    let url = remote.url().ok_or("No URL for remote/origin")?;
+    let gh_path = get_gh_path(url)?;
+    let relative_path = relative_path(repo, path)?;
+
+    let status = repo.status_file(&relative_path).chain_err(|| {
+        "Couldn't get status info for the specified path"
+    })?;
+    if status.contains(git2::Status::WT_NEW) || status.contains(git2::Status::INDEX_NEW) {
+        bail!("The provided path doesn't exist in the repository");
+    }
+
+    // We need to set a starting point for the commit graph we'll
+    // traverse. We want the most recent commit, so start at HEAD.
+    let mut revisions = repo.revwalk().chain_err(|| {
+        "Couldn't build a list of revisions for the repository"
+    })?;
+    revisions.push_head().chain_err(|| "Failed to push HEAD to commit graph.")?;
+
+    // Pull the first revision (HEAD).
+    let last_oid = revisions.next().and_then(|revision| revision.ok()).ok_or(
+        "Couldn't find a git object ID for this file"
+    )?;
+
+    let line_range = match lines {
+        Some((first, last)) if first < last => format!("#L{}-L{}", first, last),
+        Some((line, _)) => format!("#L{}", line),
+        None => String::new(),
+    };
+
+    Ok(format!(
+        "https://github.com/{}/blob/{:?}/{}{}",
+        gh_path,
+        last_oid,
+        relative_path.to_string_lossy(),
+        line_range
+    ))
+}
+
+fn get_gh_path(url: &str) -> Result<&str> {
+    lazy_static! {
+        static ref REGEX: Regex =
+            Regex::new(r"^(?:https://|git@)github.com(?::|/)(.*?)(?:.git)?$").unwrap();
+    }
+    REGEX.captures(url).and_then(|c| c.at(1)).chain_err(|| {
+        "Failed to capture remote repo path"
+    })
+}
+
+fn relative_path(repo: &Repository, path: &Path) -> Result<PathBuf> {
+    let workdir = repo.workdir().ok_or("No path found for the repository")?;
+    let relative_path = path.strip_prefix(workdir).chain_err(|| "The file isn't in the repository")?;
+
+    Ok(relative_path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{get_gh_path, GitResult, GitWorker};
+    use crate::models::application::Event;
+    use git2::Repository;
+    use scribe::Buffer;
+    use scribe::buffer::{Position, Range};
+    use std::fs;
+    use std::path::Path;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn next_result(events: &mpsc::Receiver<Event>) -> GitResult {
+        match events.recv_timeout(Duration::from_secs(5)).unwrap() {
+            Event::Git(result) => result,
+            event => panic!("Unexpected event: {:?}", event),
+        }
+    }
+
+    #[test]
+    fn worker_describes_status_and_stages_files_in_the_background() {
+        let root = Path::new(env!("OUT_DIR")).join("git_worker");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let repo = Repository::init(&root).unwrap();
+        let path = repo.workdir().unwrap().join("file");
+        fs::write(&path, "amp\n").unwrap();
+
+        let (tx, events) = mpsc::channel();
+        let mut worker = GitWorker::new(Some(&repo), tx);
+        worker.refresh_status(Some(&path), false);
+        assert_eq!(worker.status_line(Some(&path)), "[updating]");
+
+        let result = next_result(&events);
+        worker.complete(&result);
+        assert_eq!(worker.status_line(Some(&path)), "[untracked]");
+
+        // Nothing's changed, so the status isn't requested again.
+        worker.refresh_status(Some(&path), false);
+        assert_eq!(worker.status_line(Some(&path)), "[untracked]");
+
+        worker.add(path.clone()).unwrap();
+        assert_eq!(worker.status_line(Some(&path)), "[untracked] [adding]");
+        let result = next_result(&events);
+        assert_eq!(result, GitResult::Added { path: path.clone(), error: None });
+        worker.complete(&result);
+
+        // The worker notices the index has changed, so the status is requested again.
+        let result = next_result(&events);
+        assert_eq!(result, GitResult::RepositoryChanged);
+        worker.complete(&result);
+        worker.refresh_status(Some(&path), false);
+        let result = next_result(&events);
+        worker.complete(&result);
+        assert_eq!(worker.status_line(Some(&path)), "[staged]");
+
+        // Buffers are only diffed once they've been edited.
+        let mut buffer = Buffer::new();
+        buffer.id = Some(0);
+        buffer.path = Some(path.clone());
+        buffer.insert("amp\neditor\n");
+        worker.diff(&buffer, Some(1));
+        assert!(matches!(next_result(&events), GitResult::Hunks { buffer_id: 0, hunks: Ok(ref hunks) } if hunks.len() == 1));
+        buffer.delete_range(Range::new(Position { line: 1, offset: 0 }, Position { line: 2, offset: 0 }));
+        worker.diff(&buffer, Some(1));
+        assert!(events.recv_timeout(Duration::from_millis(100)).is_err());
+        worker.diff(&buffer, Some(2));
+        assert!(matches!(next_result(&events), GitResult::Hunks { buffer_id: 0, hunks: Ok(ref hunks) } if hunks.is_empty()));
+    }
+
+    #[test]
+    fn worker_refuses_to_add_without_a_repository() {
+        let (tx, _) = mpsc::channel();
+        let mut worker = GitWorker::new(None, tx);
+        worker.refresh_status(None, false);
+
+        assert!(worker.add(Path::new("file").to_path_buf()).is_err());
+        assert_eq!(worker.status_line(None), "");
+    }
+
+    #[test]
+    fn test_get_gh_path() {
+        let cases = [
+            ("git@github.com:jmacdonald/amp.git", "jmacdonald/amp"),
+            ("https://github.com/jmacdonald/amp.git", "jmacdonald/amp"),
+            ("https://github.com/jmacdonald/amp", "jmacdonald/amp"),
+        ];
+
+        cases.iter().for_each(|(url, expected_gh_path)| {
+            assert_eq!(&get_gh_path(url).unwrap(), expected_gh_path)
+        })
+    }
+}
@@ -0,0 +1,28 @@
+use scribe::buffer::Position;
+
+/// The column the cursor tries to return to when moving vertically.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Column {
+    Offset(usize),
+
+    /// Follows the end of each line, regardless of its length.
+    EndOfLine,
+}
+
+/// A buffer's goal column, along with the cursor position it was
+/// recorded at. Once the cursor moves elsewhere (e.g. horizontally
+/// or by an edit), the goal is stale and the cursor's offset is used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GoalColumn {
+    pub position: Position,
+    pub column: Column,
+}
+
+impl GoalColumn {
+    pub fn column_at(goal: Option<&GoalColumn>, position: Position) -> Column {
+        match goal {
+            Some(goal) if goal.position == position => goal.column,
+            _ => Column::Offset(position.offset),
+        }
+    }
+}
@@ -0,0 +1,346 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::util::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+use crate::yaml::yaml::Yaml;
+
+const FILE_NAME: &str = "history.json";
+const MAX_ENTRIES: usize = 100;
+
+/// Previously entered input (e.g. search queries), grouped by namespace
+/// so that each prompt recalls only its own entries. Persisted between
+/// sessions in the user data directory.
+#[derive(Default)]
+pub struct InputHistory {
+    namespaces: HashMap<String, LineHistory>,
+}
+
+/// Which entries are visited when stepping through history.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HistoryMatch {
+    All,
+
+    /// Only entries starting with the input in place before navigating
+    /// (skipping those equal to it), like bash's history-search-backward.
+    Prefix,
+}
+
+impl HistoryMatch {
+    fn matches(self, entry: &str, prefix: &str) -> bool {
+        match self {
+            HistoryMatch::All => true,
+            HistoryMatch::Prefix => prefix.is_empty() || (entry != prefix && entry.starts_with(prefix)),
+        }
+    }
+}
+
+/// A namespace's entries, ordered from oldest to newest.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LineHistory {
+    entries: Vec<String>,
+}
+
+impl LineHistory {
+    /// Adds an entry as the newest, removing any earlier copies of it.
+    pub fn add(&mut self, entry: String) {
+        if entry.is_empty() {
+            return;
+        }
+
+        self.entries.retain(|existing| *existing != entry);
+        self.entries.push(entry);
+        if self.entries.len() > MAX_ENTRIES {
+            self.entries.remove(0);
+        }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Finds the newest matching entry older than the one at the specified
+    /// index (or the newest matching entry overall), returning its index.
+    pub fn previous_match(&self, before: Option<usize>, prefix: &str, matching: HistoryMatch) -> Option<usize> {
+        let end = before.unwrap_or(self.entries.len());
+
+        self.entries[..end].iter().rposition(|entry| matching.matches(entry, prefix))
+    }
+
+    /// Finds the oldest matching entry newer than the
+    /// one at the specified index, returning its index.
+    pub fn next_match(&self, after: usize, prefix: &str, matching: HistoryMatch) -> Option<usize> {
+        self.entries
+            .iter()
+            .skip(after + 1)
+            .position(|entry| matching.matches(entry, prefix))
+            .map(|offset| after + 1 + offset)
+    }
+
+    /// Returns the newest entries containing (but not equal to) the query,
+    /// preferring those that start with it.
+    pub fn suggestions(&self, query: &str, limit: usize) -> Vec<String> {
+        if query.is_empty() {
+            return Vec::new();
+        }
+
+        let candidates: Vec<&String> = self.entries
+            .iter()
+            .rev()
+            .filter(|entry| *entry != query && entry.contains(query))
+            .collect();
+        let (prefixed, others): (Vec<&String>, Vec<&String>) = candidates
+            .into_iter()
+            .partition(|entry| entry.starts_with(query));
+
+        prefixed
+            .into_iter()
+            .chain(others)
+            .take(limit)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the entry most similar to the query (the newest, if there's a
+    /// tie), provided it's close enough to be a correction of it: no more
+    /// than a third of the query's characters need to be changed.
+    pub fn nearest(&self, query: &str) -> Option<&str> {
+        let max_distance = (query.chars().count() / 3).max(1);
+
+        self.entries
+            .iter()
+            .rev()
+            .filter(|entry| *entry != query)
+            .map(|entry| (edit_distance(entry, query), entry))
+            .filter(|&(distance, _)| distance <= max_distance)
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, entry)| entry.as_str())
+    }
+}
+
+impl InputHistory {
+    /// Loads history from disk, falling back to an empty
+    /// history if it doesn't exist or can't be read.
+    pub fn load() -> InputHistory {
+        history_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| InputHistory::parse(&content))
+            .unwrap_or_default()
+    }
+
+    fn parse(content: &str) -> InputHistory {
+        let mut history = InputHistory::default();
+        if let Ok(Yaml::Hash(namespaces)) = json::parse(content) {
+            for (namespace, entries) in namespaces {
+                if let (Some(namespace), Some(entries)) = (namespace.as_str(), entries.as_vec()) {
+                    let entries = entries.iter().filter_map(|e| e.as_str().map(String::from)).collect();
+                    history.namespaces.insert(namespace.to_string(), LineHistory { entries });
+                }
+            }
+        }
+
+        history
+    }
+
+    pub fn save(&self) -> Result<()> {
+        fs::write(history_path()?, self.encode()).chain_err(|| "Couldn't write input history")
+    }
+
+    fn encode(&self) -> String {
+        let namespaces = self.namespaces.iter().map(|(namespace, history)| {
+            let entries = history.entries.iter().map(|e| json::string(e.as_str())).collect();
+            (namespace.as_str(), Yaml::Array(entries))
+        }).collect();
+
+        json::encode(&json::object(namespaces))
+    }
+
+    pub fn add(&mut self, namespace: &str, entry: String) {
+        self.namespaces.entry(namespace.to_string()).or_default().add(entry);
+    }
+
+    pub fn get(&self, namespace: &str) -> Option<&LineHistory> {
+        self.namespaces.get(namespace)
+    }
+
+    pub fn nearest(&self, namespace: &str, query: &str) -> Option<&str> {
+        self.get(namespace)?.nearest(query)
+    }
+
+    pub fn suggestions(&self, namespace: &str, query: &str, limit: usize) -> Vec<String> {
+        self.get(namespace)
+            .map(|history| history.suggestions(query, limit))
+            .unwrap_or_default()
+    }
+}
+
+/// A position in a namespace's history, used to step through
+/// its entries (e.g. using the up/down keys in a prompt).
+pub struct InputHistoryRef {
+    namespace: &'static str,
+    index: Option<usize>,
+    matching: HistoryMatch,
+
+    /// The input in place before navigating, restored after stepping
+    /// past the newest entry (and used as the prefix when matching).
+    original: Option<String>,
+}
+
+impl InputHistoryRef {
+    pub fn new(namespace: &'static str) -> InputHistoryRef {
+        InputHistoryRef { namespace, index: None, matching: HistoryMatch::All, original: None }
+    }
+
+    pub fn namespace(&self) -> &'static str {
+        self.namespace
+    }
+
+    /// Moves to the next-oldest matching entry, returning it. The current
+    /// input is needed to restore it when navigation ends; it's also the
+    /// prefix entries are matched against, when navigation starts here.
+    pub fn move_to_prev(&mut self, history: &InputHistory, input: &str, matching: HistoryMatch) -> Option<String> {
+        let history = history.get(self.namespace)?;
+        let prefix = self.original.as_deref().unwrap_or(input);
+        let index = match self.index {
+            Some(index) => history.previous_match(Some(index), prefix, matching)?,
+            None => {
+                let index = history.previous_match(None, prefix, matching)?;
+                self.original = Some(input.to_string());
+                index
+            }
+        };
+        self.index = Some(index);
+        self.matching = matching;
+
+        history.entries().get(index).cloned()
+    }
+
+    /// Moves to the next-newest entry matching the same way as the last move
+    /// to an older one, returning it (or the original input, once past the
+    /// newest entry).
+    pub fn move_to_next(&mut self, history: &InputHistory) -> Option<String> {
+        let history = history.get(self.namespace)?;
+        let prefix = self.original.as_deref().unwrap_or("");
+        match history.next_match(self.index?, prefix, self.matching) {
+            Some(index) => {
+                self.index = Some(index);
+                history.entries().get(index).cloned()
+            }
+            None => {
+                self.index = None;
+                self.original.take()
+            }
+        }
+    }
+
+    /// Forgets the position, so that navigation starts from the newest entry.
+    pub fn reset(&mut self) {
+        self.index = None;
+        self.original = None;
+    }
+}
+
+// The number of characters that need to be inserted,
+// removed, or replaced to turn one string into the other.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + if a_char == *b_char { 0 } else { 1 };
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+fn history_path() -> Result<PathBuf> {
+    Ok(Preferences::data_directory()?.join(FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{HistoryMatch, InputHistory, InputHistoryRef, LineHistory};
+
+    #[test]
+    fn add_moves_duplicates_to_the_end_and_skips_empty_entries() {
+        let mut history = LineHistory::default();
+        history.add(String::from("amp"));
+        history.add(String::from("editor"));
+        history.add(String::from("amp"));
+        history.add(String::new());
+
+        assert_eq!(history.entries(), &["editor", "amp"]);
+    }
+
+    #[test]
+    fn suggestions_prefer_newer_prefixed_entries() {
+        let mut history = LineHistory::default();
+        for entry in &["fn main", "main", "fn mainline", "pub fn main", "fn mai"] {
+            history.add(entry.to_string());
+        }
+
+        assert_eq!(history.suggestions("fn mai", 3), vec!["fn mainline", "fn main", "pub fn main"]);
+        assert!(history.suggestions("", 3).is_empty());
+    }
+
+    #[test]
+    fn nearest_returns_the_newest_entry_within_a_few_edits() {
+        let mut history = LineHistory::default();
+        for entry in &["src/models/application.rs", "src/commands/buffer.rs", "src/commands/bufer.rs"] {
+            history.add(entry.to_string());
+        }
+
+        assert_eq!(history.nearest("src/comands/buffer.rs"), Some("src/commands/buffer.rs"));
+        assert_eq!(history.nearest("src/commands/bufferr.rs"), Some("src/commands/buffer.rs"));
+        assert_eq!(history.nearest("src/commands/bufe.rs"), Some("src/commands/bufer.rs"));
+        assert_eq!(history.nearest("lib.rs"), None);
+    }
+
+    #[test]
+    fn encoded_history_can_be_parsed() {
+        let mut history = InputHistory::default();
+        history.add("search", String::from("amp \"editor\""));
+        history.add("search", String::from("buffer"));
+        let parsed = InputHistory::parse(&history.encode());
+
+        assert_eq!(parsed.get("search"), history.get("search"));
+    }
+
+    #[test]
+    fn history_ref_steps_through_entries_and_restores_input() {
+        let mut history = InputHistory::default();
+        history.add("search", String::from("amp"));
+        history.add("search", String::from("editor"));
+        let mut position = InputHistoryRef::new("search");
+
+        assert_eq!(position.move_to_prev(&history, "typed", HistoryMatch::All), Some(String::from("editor")));
+        assert_eq!(position.move_to_prev(&history, "editor", HistoryMatch::All), Some(String::from("amp")));
+        assert_eq!(position.move_to_prev(&history, "amp", HistoryMatch::All), None);
+        assert_eq!(position.move_to_next(&history), Some(String::from("editor")));
+        assert_eq!(position.move_to_next(&history), Some(String::from("typed")));
+        assert_eq!(position.move_to_next(&history), None);
+    }
+
+    #[test]
+    fn history_ref_can_step_through_entries_starting_with_the_input() {
+        let mut history = InputHistory::default();
+        for entry in &["fn main", "struct", "fn", "fn test", "impl"] {
+            history.add("search", entry.to_string());
+        }
+        let mut position = InputHistoryRef::new("search");
+
+        assert_eq!(position.move_to_prev(&history, "fn", HistoryMatch::Prefix), Some(String::from("fn test")));
+        assert_eq!(position.move_to_prev(&history, "fn test", HistoryMatch::Prefix), Some(String::from("fn main")));
+        assert_eq!(position.move_to_prev(&history, "fn main", HistoryMatch::Prefix), None);
+        assert_eq!(position.move_to_next(&history), Some(String::from("fn test")));
+        assert_eq!(position.move_to_next(&history), Some(String::from("fn")));
+        assert_eq!(position.move_to_next(&history), None);
+    }
+}
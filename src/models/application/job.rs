@@ -0,0 +1,43 @@
+use crate::models::application::Event;
+use crate::util::test_runner::TestFunction;
+use std::path::PathBuf;
+use std::process::Command;
+use std::sync::mpsc::Sender;
+use std::thread;
+
+/// Describes why a job was started, so that
+/// its result can be handled once it completes.
+#[derive(Debug, PartialEq)]
+pub enum Job {
+    Task { command: String },
+    Test { buffer_id: usize, tests: Vec<TestFunction> },
+}
+
+#[derive(Debug, PartialEq)]
+pub struct JobResult {
+    pub job: Job,
+    pub success: bool,
+    pub output: String,
+}
+
+/// Runs a shell command in a separate thread, sending a
+/// `JobComplete` event (with its output) when it exits.
+pub fn spawn(job: Job, command: String, path: PathBuf, events: Sender<Event>) {
+    thread::spawn(move || {
+        let result = match Command::new("sh").arg("-c").arg(&command).current_dir(path).output() {
+            Ok(output) => {
+                let mut content = String::from_utf8_lossy(&output.stdout).into_owned();
+                content.push_str(&String::from_utf8_lossy(&output.stderr));
+
+                JobResult { job, success: output.status.success(), output: content }
+            },
+            Err(error) => JobResult {
+                job,
+                success: false,
+                output: format!("Failed to run \"{}\": {}", command, error),
+            },
+        };
+
+        let _ = events.send(Event::JobComplete(result));
+    });
+}
@@ -0,0 +1,161 @@
+use scribe::Buffer;
+use scribe::buffer::Position;
+use std::path::PathBuf;
+
+// The oldest jumps are forgotten beyond this many.
+const MAX_JUMPS: usize = 100;
+
+/// A location the cursor jumped from.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Jump {
+    pub buffer_id: usize,
+
+    /// Used to reopen the buffer if it's since been closed.
+    pub path: Option<PathBuf>,
+    pub position: Position,
+}
+
+impl Jump {
+    pub fn at(buffer: &Buffer) -> Option<Jump> {
+        Some(Jump {
+            buffer_id: buffer.id?,
+            path: buffer.path.clone(),
+            position: *buffer.cursor,
+        })
+    }
+
+    // Jumps within a line are considered the same, as they're hardly worth returning to.
+    fn same_line(&self, other: &Jump) -> bool {
+        self.buffer_id == other.buffer_id && self.position.line == other.position.line
+    }
+}
+
+/// Locations the cursor has jumped from (e.g. by searching or opening a
+/// file), which can be walked back through, and then forward again. Making
+/// a new jump from an earlier location discards those that followed it.
+#[derive(Default)]
+pub struct JumpList {
+    jumps: Vec<Jump>,
+
+    /// The index of the jump last walked to; the length
+    /// of the list when it hasn't been walked through.
+    current: usize,
+}
+
+impl JumpList {
+    /// Records the buffer's cursor as a location being jumped from.
+    pub fn record(&mut self, buffer: &Buffer) {
+        let jump = match Jump::at(buffer) {
+            Some(jump) => jump,
+            None => return,
+        };
+        self.jumps.truncate(self.current);
+        if !matches!(self.jumps.last(), Some(last) if last.same_line(&jump)) {
+            self.jumps.push(jump);
+        }
+        if self.jumps.len() > MAX_JUMPS {
+            self.jumps.remove(0);
+        }
+        self.current = self.jumps.len();
+    }
+
+    /// Steps back to the previous location on another line, returning it.
+    pub fn back(&mut self, buffer: &Buffer) -> Option<Jump> {
+        let here = Jump::at(buffer)?;
+        self.remember(&here);
+        let index = self.jumps[..self.current].iter().rposition(|jump| !jump.same_line(&here))?;
+        self.current = index;
+
+        Some(self.jumps[index].clone())
+    }
+
+    /// Steps forward to the location that was stepped back from, returning it.
+    pub fn forward(&mut self) -> Option<Jump> {
+        if self.current + 1 >= self.jumps.len() {
+            return None;
+        }
+        self.current += 1;
+
+        Some(self.jumps[self.current].clone())
+    }
+
+    /// Walks directly to the jump at the index, returning it.
+    pub fn go_to(&mut self, index: usize, buffer: &Buffer) -> Option<Jump> {
+        if let Some(here) = Jump::at(buffer) {
+            self.remember(&here);
+        }
+        let jump = self.jumps.get(index)?.clone();
+        self.current = index;
+
+        Some(jump)
+    }
+
+    pub fn jumps(&self) -> &[Jump] {
+        &self.jumps
+    }
+
+    /// The index of the jump last walked to, if any.
+    pub fn current(&self) -> Option<usize> {
+        Some(self.current).filter(|&current| current < self.jumps.len())
+    }
+
+    // Adds the location a walk through the list starts
+    // from to its end, so that it can be returned to.
+    fn remember(&mut self, here: &Jump) {
+        if self.current < self.jumps.len() {
+            return;
+        }
+        if !matches!(self.jumps.last(), Some(last) if last.same_line(here)) {
+            self.jumps.push(here.clone());
+        }
+        self.current = self.jumps.len() - 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JumpList;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    fn buffer_at(id: usize, line: usize) -> Buffer {
+        let mut buffer = Buffer::new();
+        buffer.id = Some(id);
+        buffer.insert("amp\neditor\nfor\nthe\nterminal\n");
+        buffer.cursor.move_to(Position { line, offset: 0 });
+
+        buffer
+    }
+
+    #[test]
+    fn back_and_forward_walk_through_recorded_jumps() {
+        let mut list = JumpList::default();
+        list.record(&buffer_at(1, 0));
+        list.record(&buffer_at(1, 0));
+        list.record(&buffer_at(2, 3));
+        assert_eq!(list.jumps().len(), 2);
+
+        assert_eq!(list.back(&buffer_at(1, 4)).map(|jump| jump.buffer_id), Some(2));
+        assert_eq!(list.back(&buffer_at(2, 3)).map(|jump| jump.position.line), Some(0));
+        assert_eq!(list.back(&buffer_at(1, 0)), None);
+        assert_eq!(list.forward().map(|jump| jump.buffer_id), Some(2));
+        assert_eq!(list.forward().map(|jump| jump.position.line), Some(4));
+        assert_eq!(list.forward(), None);
+        assert_eq!(list.current(), Some(2));
+    }
+
+    #[test]
+    fn record_discards_jumps_that_were_walked_back_through() {
+        let mut list = JumpList::default();
+        list.record(&buffer_at(1, 0));
+        list.record(&buffer_at(1, 1));
+        list.back(&buffer_at(1, 2));
+        list.back(&buffer_at(1, 1));
+
+        list.record(&buffer_at(1, 0));
+        let lines: Vec<usize> = list.jumps().iter().map(|jump| jump.position.line).collect();
+        assert_eq!(lines, vec![0]);
+        assert_eq!(list.current(), None);
+        assert_eq!(list.forward(), None);
+    }
+}
@@ -0,0 +1,182 @@
+use crate::errors::*;
+use crate::models::application::{LineNumberMode, Preferences};
+use std::fmt;
+use std::path::PathBuf;
+
+const LINE_NUMBERS_KEY: &str = "line_numbers";
+const LINE_WRAPPING_KEY: &str = "line_wrapping";
+const SYNTAX_KEY: &str = "syntax";
+const TAB_WIDTH_KEY: &str = "tab_width";
+
+// Preferences that can be overridden for a single buffer,
+// and the values they accept, if they only accept a few.
+const LOCAL_SETTINGS: [(&str, &[&str]); 4] = [
+    (LINE_NUMBERS_KEY, &["absolute", "hybrid", "none", "relative"]),
+    (LINE_WRAPPING_KEY, &["false", "true"]),
+    (SYNTAX_KEY, &[]),
+    (TAB_WIDTH_KEY, &[]),
+];
+
+/// Preferences changed for a single buffer, taking precedence over its
+/// type's preferences (and those over the user's global preferences).
+/// Unset values are resolved through the preferences they're layered on.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LocalSettings {
+    line_numbers: Option<LineNumberMode>,
+    line_wrapping: Option<bool>,
+    syntax: Option<String>,
+    tab_width: Option<usize>,
+}
+
+impl LocalSettings {
+    /// The names of the preferences that can be changed for a single buffer.
+    pub fn settings() -> impl Iterator<Item=&'static str> {
+        LOCAL_SETTINGS.iter().map(|&(name, _)| name)
+    }
+
+    /// The values a local setting accepts, if it only accepts a few.
+    pub fn setting_values(setting: &str) -> &'static [&'static str] {
+        LOCAL_SETTINGS
+            .iter()
+            .find(|&&(name, _)| name == setting)
+            .map(|&(_, values)| values)
+            .unwrap_or(&[])
+    }
+
+    /// Overrides a preference for the buffer. Syntax names
+    /// aren't checked here, since they depend on the workspace.
+    pub fn set(&mut self, setting: &str, value: &str) -> Result<()> {
+        match setting {
+            LINE_NUMBERS_KEY => {
+                self.line_numbers = Some(match value {
+                    "absolute" | "true" => LineNumberMode::Absolute,
+                    "relative" => LineNumberMode::Relative,
+                    "hybrid" => LineNumberMode::Hybrid,
+                    "none" | "false" => LineNumberMode::Hidden,
+                    _ => bail!("\"{}\" must be absolute, relative, hybrid, or none", setting),
+                });
+            },
+            LINE_WRAPPING_KEY => {
+                self.line_wrapping = Some(
+                    value.parse().map_err(|_| format!("\"{}\" must be true or false", setting))?
+                );
+            },
+            SYNTAX_KEY => self.syntax = Some(value.to_string()),
+            TAB_WIDTH_KEY => {
+                self.tab_width = Some(
+                    value
+                        .parse()
+                        .ok()
+                        .filter(|&width| width > 0)
+                        .ok_or_else(|| format!("\"{}\" must be a positive number", setting))?
+                );
+            },
+            _ => bail!(format!(
+                "\"{}\" can't be set for a buffer (available settings: {})",
+                setting,
+                LocalSettings::settings().collect::<Vec<_>>().join(", ")
+            )),
+        }
+
+        Ok(())
+    }
+
+    pub fn is_empty(&self) -> bool {
+        *self == LocalSettings::default()
+    }
+
+    pub fn line_numbers(&self, preferences: &Preferences) -> LineNumberMode {
+        self.line_numbers.unwrap_or_else(|| preferences.line_numbers())
+    }
+
+    pub fn line_wrapping(&self, preferences: &Preferences) -> bool {
+        self.line_wrapping.unwrap_or_else(|| preferences.line_wrapping())
+    }
+
+    /// The name of the syntax definition used in place of the detected one.
+    pub fn syntax(&self) -> Option<&str> {
+        self.syntax.as_ref().map(String::as_str)
+    }
+
+    pub fn tab_width(&self, preferences: &Preferences, path: Option<&PathBuf>) -> usize {
+        self.tab_width.unwrap_or_else(|| preferences.tab_width(path))
+    }
+
+    /// The overridden tab width, if any.
+    pub fn local_tab_width(&self) -> Option<usize> {
+        self.tab_width
+    }
+}
+
+impl fmt::Display for LocalSettings {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut settings = Vec::new();
+        if let Some(mode) = self.line_numbers {
+            let value = match mode {
+                LineNumberMode::Absolute => "absolute",
+                LineNumberMode::Relative => "relative",
+                LineNumberMode::Hybrid => "hybrid",
+                LineNumberMode::Hidden => "none",
+            };
+            settings.push(format!("{}={}", LINE_NUMBERS_KEY, value));
+        }
+        if let Some(wrapping) = self.line_wrapping {
+            settings.push(format!("{}={}", LINE_WRAPPING_KEY, wrapping));
+        }
+        if let Some(ref syntax) = self.syntax {
+            settings.push(format!("{}={}", SYNTAX_KEY, syntax));
+        }
+        if let Some(width) = self.tab_width {
+            settings.push(format!("{}={}", TAB_WIDTH_KEY, width));
+        }
+
+        write!(f, "{}", settings.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LocalSettings;
+    use crate::models::application::{LineNumberMode, Preferences};
+    use crate::yaml::yaml::YamlLoader;
+    use std::path::PathBuf;
+
+    #[test]
+    fn unset_values_fall_back_to_type_preferences() {
+        let data = YamlLoader::load_from_str("tab_width: 2\ntypes:\n  rs:\n    tab_width: 4\nline_numbers: relative")
+            .unwrap()
+            .into_iter()
+            .next();
+        let preferences = Preferences::new(data);
+        let path = PathBuf::from("main.rs");
+        let mut settings = LocalSettings::default();
+
+        assert_eq!(settings.tab_width(&preferences, Some(&path)), 4);
+        assert_eq!(settings.line_numbers(&preferences), LineNumberMode::Relative);
+
+        settings.set("tab_width", "8").unwrap();
+        settings.set("line_numbers", "none").unwrap();
+        assert_eq!(settings.tab_width(&preferences, Some(&path)), 8);
+        assert_eq!(settings.line_numbers(&preferences), LineNumberMode::Hidden);
+        assert!(settings.line_wrapping(&preferences));
+    }
+
+    #[test]
+    fn set_rejects_unknown_settings_and_invalid_values() {
+        let mut settings = LocalSettings::default();
+
+        assert!(settings.set("theme", "solarized_dark").is_err());
+        assert!(settings.set("tab_width", "0").is_err());
+        assert!(settings.set("line_wrapping", "yes").is_err());
+        assert!(settings.is_empty());
+    }
+
+    #[test]
+    fn display_lists_overridden_settings() {
+        let mut settings = LocalSettings::default();
+        settings.set("syntax", "Rust").unwrap();
+        settings.set("line_wrapping", "false").unwrap();
+
+        assert_eq!(settings.to_string(), "line_wrapping=false, syntax=Rust");
+    }
+}
@@ -0,0 +1,168 @@
+use crate::models::application::jump_list::Jump;
+use crate::view::View;
+use scribe::Buffer;
+use scribe::buffer::Position;
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap};
+use std::rc::Rc;
+
+/// A named location in a buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Mark {
+    pub name: char,
+    pub location: Jump,
+
+    /// The content of the marked line, as of its buffer's last update.
+    pub preview: String,
+}
+
+/// Named locations across buffers, which can be jumped back to. Marks stay
+/// on the content they were set on as lines are added or removed above it.
+#[derive(Default)]
+pub struct Marks {
+    marks: BTreeMap<char, Mark>,
+
+    // The content of buffers with marks as of their last update.
+    contents: HashMap<usize, String>,
+
+    // The earliest position changed in buffers with marks since their last
+    // update, set by listening for their changes (see `View::on_change`).
+    changes: HashMap<usize, Rc<Cell<Option<Position>>>>,
+}
+
+impl Marks {
+    /// Marks the buffer's cursor, replacing any existing mark with the name.
+    pub fn set(&mut self, name: char, buffer: &Buffer, view: &mut View) {
+        let location = match Jump::at(buffer) {
+            Some(location) => location,
+            None => return,
+        };
+        let content = buffer.data();
+        let preview = line_preview(&content, location.position.line);
+
+        if !self.changes.contains_key(&location.buffer_id) {
+            let change = Rc::new(Cell::new(None));
+            let listener_change = change.clone();
+            view.on_change(buffer, move |position| match listener_change.get() {
+                Some(earliest) if earliest <= position => (),
+                _ => listener_change.set(Some(position)),
+            });
+            self.changes.insert(location.buffer_id, change);
+        }
+        self.contents.insert(location.buffer_id, content);
+        self.marks.insert(name, Mark { name, location, preview });
+    }
+
+    pub fn get(&self, name: char) -> Option<&Mark> {
+        self.marks.get(&name)
+    }
+
+    /// The marks, ordered by name.
+    pub fn iter(&self) -> impl Iterator<Item = &Mark> {
+        self.marks.values()
+    }
+
+    /// The lines surrounding the mark, as of its buffer's
+    /// last update, if its buffer is still open.
+    pub fn context(&self, mark: &Mark, lines: usize) -> Option<String> {
+        let content = self.contents.get(&mark.location.buffer_id)?;
+        let line = mark.location.position.line;
+        let first = line.saturating_sub(lines);
+        let context: Vec<&str> = content.lines().skip(first).take(line - first + lines + 1).collect();
+
+        Some(context.join("\n"))
+    }
+
+    /// Moves the buffer's marks to follow lines added or removed since its
+    /// last update, if it's been changed. Marks after the earliest change
+    /// are moved by the number of lines added or removed, but not before the
+    /// changed line, so those on removed lines end up on the line after them.
+    pub fn update(&mut self, buffer: &Buffer) {
+        let id = match buffer.id {
+            Some(id) => id,
+            None => return,
+        };
+        let change = match self.changes.get(&id).and_then(|change| change.take()) {
+            Some(change) => change,
+            None => return,
+        };
+        let content = buffer.data();
+        let previous_line_count = self.contents.get(&id).map_or(0, |content| content.matches('\n').count());
+        let line_delta = content.matches('\n').count() as isize - previous_line_count as isize;
+
+        for mark in self.marks.values_mut().filter(|mark| mark.location.buffer_id == id) {
+            let position = &mut mark.location.position;
+            if *position > change {
+                position.line = (position.line as isize + line_delta).max(change.line as isize) as usize;
+            }
+            mark.preview = line_preview(&content, position.line);
+        }
+        self.contents.insert(id, content);
+    }
+
+    /// Stops following changes to the buffer, once it's closed. Its marks
+    /// are kept, to reopen the buffer's file when they're jumped to.
+    pub fn forget_buffer(&mut self, buffer_id: usize) {
+        self.contents.remove(&buffer_id);
+        self.changes.remove(&buffer_id);
+    }
+}
+
+fn line_preview(content: &str, line: usize) -> String {
+    content.lines().nth(line).map(|line| line.trim().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Marks;
+    use crate::models::application::Preferences;
+    use crate::view::View;
+    use scribe::{Buffer, Workspace};
+    use scribe::buffer::{Position, Range};
+    use std::cell::RefCell;
+    use std::path::Path;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    #[test]
+    fn update_keeps_marks_on_their_lines_as_content_changes_around_them() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let mut view = View::new(preferences, mpsc::channel().0).unwrap();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        let buffer = workspace.current_buffer().unwrap();
+        view.initialize_buffer(buffer).unwrap();
+        let mut marks = Marks::default();
+        buffer.insert("amp\neditor\nfor\nthe\nterminal\n");
+        buffer.cursor.move_to(Position { line: 1, offset: 2 });
+        marks.set('a', buffer, &mut view);
+        buffer.cursor.move_to(Position { line: 3, offset: 0 });
+        marks.set('b', buffer, &mut view);
+
+        // Add two lines above the first mark.
+        buffer.cursor.move_to(Position { line: 0, offset: 0 });
+        buffer.insert("rust\ntext\n");
+        marks.update(buffer);
+        assert_eq!(marks.get('a').unwrap().location.position, Position { line: 3, offset: 2 });
+        assert_eq!(marks.get('b').unwrap().location.position, Position { line: 5, offset: 0 });
+
+        // Remove the line above the second mark, and then the second mark's line.
+        buffer.delete_range(Range::new(
+            Position { line: 4, offset: 0 },
+            Position { line: 5, offset: 0 },
+        ));
+        marks.update(buffer);
+        buffer.delete_range(Range::new(
+            Position { line: 4, offset: 0 },
+            Position { line: 5, offset: 0 },
+        ));
+        marks.update(buffer);
+
+        let a = marks.get('a').unwrap();
+        assert_eq!(a.location.position, Position { line: 3, offset: 2 });
+        assert_eq!(a.preview, "editor");
+        let b = marks.get('b').unwrap();
+        assert_eq!(b.location.position, Position { line: 4, offset: 0 });
+        assert_eq!(b.preview, "terminal");
+    }
+}
@@ -1,42 +1,129 @@
+mod buffer_groups;
+mod change_set;
 mod clipboard;
+mod completion;
+mod edit_log;
 mod event;
+mod file_tree;
+mod git_worker;
+mod goal_column;
+mod history;
+pub mod jump_list;
+pub mod job;
+mod local_settings;
+mod marks;
 pub mod modes;
+mod multi_cursor;
+mod narrowing;
 mod preferences;
+mod profiler;
+mod prompt;
+mod read_only;
+mod recent_buffers;
+mod undo_tree;
+pub mod word_index;
+mod workspace_trust;
 
 // Published API
-pub use self::clipboard::ClipboardContent;
+pub use self::buffer_groups::BufferGroups;
+pub use self::change_set::ChangeSet;
+pub use self::clipboard::{tmux_buffer, ClipboardContent, SYSTEM_REGISTER};
+pub use self::completion::Completion;
+pub use self::edit_log::EditLogs;
 pub use self::event::Event;
-pub use self::preferences::Preferences;
+pub use self::file_tree::{FileTree, FileTreeEntry};
+pub use self::git_worker::{GitResult, GitWorker};
+pub use self::goal_column::{Column, GoalColumn};
+pub use self::history::{HistoryMatch, InputHistory, InputHistoryRef, LineHistory};
+pub use self::jump_list::JumpList;
+pub use self::job::{Job, JobResult};
+pub use self::local_settings::LocalSettings;
+pub use self::marks::{Mark, Marks};
+pub use self::multi_cursor::MultiCursor;
+pub use self::narrowing::Narrowing;
+pub use self::preferences::{Bell, LineNumberMode, Preferences};
+pub use self::profiler::{Activity, Profiler};
+pub use self::prompt::Prompt;
+pub use self::read_only::ReadOnlyBuffers;
+pub use self::recent_buffers::RecentBuffers;
+pub use self::undo_tree::{UndoTree, UndoTrees};
+pub use self::word_index::WordIndex;
+pub use self::workspace_trust::WorkspaceTrust;
 
 use self::clipboard::Clipboard;
 use self::modes::*;
+use self::modes::open::Index;
+use crate::collab::{self, Collaboration, Message};
 use crate::commands;
+use crate::dap::{self, DebugAction, Debugger};
 use crate::errors::*;
+use crate::input::Key;
+use crate::lsp::{self, LanguageServers, LspAction};
 use git2::Repository;
 use crate::presenters;
+use crate::util::{self, encryption, test_runner, tools};
+use crate::util::git_gutter::{self, GitGutter};
 use scribe::{Buffer, Workspace};
+use scribe::buffer::{Position, Range};
 use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::env;
+use std::fs;
 use std::path::Path;
 use std::rc::Rc;
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::time::{Duration, Instant};
 use crate::view::View;
+use crate::yaml::yaml::Yaml;
+
+// How often buffers are checked for eviction, when enabled.
+const EVICTION_INTERVAL: Duration = Duration::from_secs(60);
+
+// How long to wait for further keys when recording a key sequence,
+// if the (equivalent) key sequence timeout has been disabled.
+const KEY_RECORDING_TIMEOUT: Duration = Duration::from_secs(1);
 
 pub enum Mode {
+    Grep(GrepMode),
+    Buffer(BufferMode),
+    Number(NumberMode),
+    Transform(TransformMode),
+    Debug(DebugMode),
+    Diagnostic(DiagnosticMode),
+    Task(TaskMode),
+    Stash(StashMode),
+    Blame(BlameMode),
+    Branch(BranchMode),
+    Calculator(CalculatorMode),
+    ClosePattern(ClosePatternMode),
     Confirm(ConfirmMode),
     Command(CommandMode),
+    Ex(ExMode),
     Exit,
+    FileTree(FileTreeMode),
     Insert,
     Jump(JumpMode),
+    KeyRecorder(KeyRecorderMode),
     LineJump(LineJumpMode),
+    Passphrase(PassphraseMode),
     Path(PathMode),
+    Register(RegisterMode),
+    Registers(RegistersMode),
     Normal,
     Open(OpenMode),
+    Rename(RenameMode),
+    Replace(ReplaceMode),
     Select(SelectMode),
     SelectLine(SelectLineMode),
+    SelectBlock(SelectBlockMode),
     Search(SearchMode),
     SymbolJump(SymbolJumpMode),
     Theme(ThemeMode),
+    UndoTree(UndoTreeMode),
+    JumpList(JumpListMode),
+    Mark(MarkMode),
+    Marks(MarksMode),
+    Tags(TagsMode),
 }
 
 pub struct Application {
@@ -45,9 +132,64 @@ pub struct Application {
     pub search_query: Option<String>,
     pub view: View,
     pub clipboard: Clipboard,
-    pub repository: Option<Repository>,
+
+    /// Lines in open buffers that differ from the repository's index.
+    pub git_gutter: GitGutter,
+
+    /// Runs git operations in the background (e.g. for the status line).
+    pub git_worker: GitWorker,
     pub error: Option<Error>,
+    pub notification: Option<String>,
     pub preferences: Rc<RefCell<Preferences>>,
+    pub debugger: Debugger,
+    pub language_servers: LanguageServers,
+    pub collaboration: Collaboration,
+    pub history: InputHistory,
+    pub word_index: WordIndex,
+
+    /// Whether workspaces' own configuration (which can run commands) is used.
+    pub workspace_trust: WorkspaceTrust,
+
+    /// Completions offered while in insert mode, if the popup is open.
+    pub completion: Option<Completion>,
+    pub goal_columns: HashMap<usize, GoalColumn>,
+    pub multi_cursors: HashMap<usize, MultiCursor>,
+    pub pending_changes: HashMap<usize, ChangeSet>,
+    pub buffer_groups: BufferGroups,
+    pub pinned_buffers: HashSet<usize>,
+
+    /// Buffers protected from editing (e.g. those holding vendored files).
+    pub read_only_buffers: ReadOnlyBuffers,
+    pub recent_buffers: RecentBuffers,
+
+    /// Passphrases for buffers holding decrypted files, by buffer ID.
+    pub encrypted_buffers: HashMap<usize, String>,
+
+    /// Edit histories for open buffers, including undone branches.
+    pub undo_trees: UndoTrees,
+
+    /// Locations the cursor has jumped from, across buffers.
+    pub jump_list: JumpList,
+
+    /// Named locations the cursor can return to, across buffers.
+    pub marks: Marks,
+
+    /// The workspace's files, listed in a sidebar while it's open.
+    pub file_tree: Option<FileTree>,
+
+    /// The files found the last time open mode was used, searched
+    /// the next time it's used while they're re-indexed.
+    pub open_mode_index: Option<Index>,
+
+    /// Changes made to buffers by each key press, when enabled.
+    pub edit_logs: EditLogs,
+
+    /// Keys pressed so far in an incomplete key sequence.
+    pub pending_keys: Vec<Key>,
+
+    /// How many of the pending keys have already been inserted as text,
+    /// to be removed if their sequence is completed.
+    pub inserted_pending_keys: usize,
     pub event_channel: Sender<Event>,
     events: Receiver<Event>,
 }
@@ -58,56 +200,279 @@ impl Application {
 
         let (event_channel, events) = mpsc::channel();
         let mut view = View::new(preferences.clone(), event_channel.clone())?;
-        let clipboard = Clipboard::new();
+
+        // Check for configured external tools up front, so that missing
+        // ones are reported once, rather than every time they're used.
+        let mut missing_tools = Vec::new();
+        let clipboard = if preferences.borrow().tmux_clipboard() {
+            if tools::available("tmux") {
+                Clipboard::tmux()
+            } else {
+                missing_tools.push(String::from("tmux (using the system clipboard instead)"));
+                Clipboard::new()
+            }
+        } else if preferences.borrow().osc52_clipboard() {
+            Clipboard::osc52(view.terminal())
+        } else {
+            Clipboard::new()
+        };
+        if let Some(command) = preferences.borrow().test_command(None) {
+            if !tools::shell_command_available(&command) {
+                let program = tools::program(&command).unwrap_or("test command");
+                missing_tools.push(format!("{} (tests can't be run)", program));
+            }
+        }
 
         // Set up a workspace in the current directory.
-        let workspace = create_workspace(&mut view, args)?;
+        let args = expand_path_args(args);
+        let opened_directory = args.get(1).map(|arg| Path::new(arg).is_dir()) == Some(true);
+
+        // Encrypted files are opened once their passphrase has been entered.
+        let (encrypted_args, args): (Vec<String>, Vec<String>) = args.into_iter().partition(|arg| {
+            encryption::is_encrypted(Path::new(arg), &preferences.borrow())
+        });
+        let workspace = create_workspace(&mut view, &args)?;
+        let repository = Repository::discover(&env::current_dir()?).ok();
+        let git_worker = GitWorker::new(repository.as_ref(), event_channel.clone());
 
-        Ok(Application {
+        let mut app = Application {
             mode: Mode::Normal,
             workspace,
             search_query: None,
             view,
             clipboard,
-            repository: Repository::discover(&env::current_dir()?).ok(),
+            git_gutter: GitGutter::default(),
+            git_worker,
             error: None,
+            notification: if missing_tools.is_empty() {
+                None
+            } else {
+                Some(format!("Unavailable: {}", missing_tools.join(", ")))
+            },
             preferences,
+            debugger: Debugger::default(),
+            language_servers: LanguageServers::default(),
+            collaboration: Collaboration::default(),
+            history: InputHistory::load(),
+            word_index: WordIndex::default(),
+            workspace_trust: WorkspaceTrust::load(),
+            completion: None,
+            goal_columns: HashMap::new(),
+            multi_cursors: HashMap::new(),
+            pending_changes: HashMap::new(),
+            buffer_groups: BufferGroups::default(),
+            pinned_buffers: HashSet::new(),
+            read_only_buffers: ReadOnlyBuffers::default(),
+            recent_buffers: RecentBuffers::default(),
+            encrypted_buffers: HashMap::new(),
+            undo_trees: UndoTrees::default(),
+            jump_list: JumpList::default(),
+            marks: Marks::default(),
+            file_tree: None,
+            open_mode_index: None,
+            edit_logs: EditLogs::default(),
+            pending_keys: Vec::new(),
+            inserted_pending_keys: 0,
             event_channel,
             events,
-        })
+        };
+
+        // Start browsing a directory passed without any files, or prompt for
+        // the passphrase of the first encrypted file (the others are skipped).
+        if let Some(path) = encrypted_args.first() {
+            commands::passphrase::open(&mut app, Path::new(path))?;
+        } else if opened_directory && app.workspace.current_buffer().is_none() {
+            commands::application::switch_to_open_mode(&mut app)?;
+        }
+
+        Ok(app)
     }
 
     pub fn run(&mut self) -> Result<()> {
+        let mut evicted_at = Instant::now();
         loop {
+            if let Err(error) = self.sync_language_server() {
+                self.error = Some(error);
+            }
+            if let Err(error) = self.sync_git() {
+                self.error = Some(error);
+            }
+            if let Err(error) = self.sync_collaboration() {
+                self.error = Some(error);
+            }
+            let read_only_directories = self.preferences.borrow().read_only_directories();
+            let workspace_path = self.workspace.path.clone();
+            if let Some(buffer) = self.workspace.current_buffer() {
+                self.read_only_buffers.track(buffer, &workspace_path, &read_only_directories);
+            }
             self.render();
             self.wait_for_event()?;
 
+            // Free up memory held by buffers that haven't been used in a while.
+            let idle = self.preferences.borrow().evict_idle_buffers_after();
+            if let Some(idle) = idle {
+                if evicted_at.elapsed() >= EVICTION_INTERVAL {
+                    util::memory::evict_idle_buffers(self, idle);
+                    evicted_at = Instant::now();
+                }
+            }
+
             if let Mode::Exit = self.mode {
                 break;
             }
         }
 
+        // Losing history isn't worth preventing a clean exit over.
+        let _ = self.history.save();
+
         Ok(())
     }
 
     fn render(&mut self) {
-        if let Err(error) = self.present() {
+        let started_at = Instant::now();
+        self.buffer_groups.update(&mut self.workspace);
+        if let Some(id) = self.workspace.current_buffer().and_then(|b| b.id) {
+            self.recent_buffers.access(id);
+        }
+        let key_hints = self.key_hints();
+        self.view.set_key_hints(key_hints);
+        if let Some(ref tree) = self.file_tree {
+            self.view.render_sidebar(tree, matches!(self.mode, Mode::FileTree(_)));
+        }
+        if let Err(error) = self.render_inactive_panes().and_then(|_| self.present()) {
             presenters::error::display(&mut self.workspace, &mut self.view, &error);
         } else if let Some(ref error) = self.error {
             // Display an error from previous command invocation, if one exists.
             presenters::error::display(&mut self.workspace, &mut self.view, error);
+        } else if let Some(ref notification) = self.notification {
+            presenters::notification::display(&mut self.workspace, &mut self.view, notification);
         }
+        self.view.profiler.record(Activity::Render, started_at.elapsed());
+    }
+
+    // Lists the keys that continue a partially typed sequence, headed by the
+    // keys typed so far. Sequences whose keys have already been inserted
+    // (e.g. "j k" in insert mode) are left alone, so as not to distract
+    // from typing.
+    fn key_hints(&self) -> Vec<String> {
+        if self.pending_keys.is_empty() || self.inserted_pending_keys > 0 {
+            return Vec::new();
+        }
+        let preferences = self.preferences.borrow();
+        let mode = match self.mode_str() {
+            Some(mode) if preferences.key_sequence_hints() => mode,
+            _ => return Vec::new(),
+        };
+
+        let continuations = preferences.keymap().continuations(mode, &self.pending_keys);
+        let key_width = continuations.iter().map(|(key, _)| key.to_string().len()).max().unwrap_or(0);
+        let typed: Vec<String> = self.pending_keys.iter().map(|key| key.to_string()).collect();
+        let mut hints = vec![typed.join(" ")];
+        hints.extend(continuations.into_iter().map(|(key, description)| {
+            format!("{:width$}  {}", key.to_string(), description, width = key_width)
+        }));
+
+        hints
+    }
+
+    // Renders the buffers displayed in unfocused panes, which need to be
+    // made current to do so. The mode presenters draw the current buffer
+    // into the focused pane, alongside these.
+    fn render_inactive_panes(&mut self) -> Result<()> {
+        let current_id = self.workspace.current_buffer().and_then(|b| b.id);
+        self.view.set_focused_pane_buffer(current_id);
+        self.view.clear_panes();
+
+        let result = self.view.inactive_panes().into_iter().try_for_each(|(pane, id)| {
+            if util::select_buffer(id, self) {
+                if let Some(buffer) = self.workspace.current_buffer() {
+                    self.view.render_pane(buffer, pane)?;
+                }
+            }
+
+            Ok(())
+        });
+        if let Some(id) = current_id {
+            util::select_buffer(id, self);
+        }
+
+        result
     }
 
     fn present(&mut self) -> Result<()> {
         match self.mode {
-            Mode::Confirm(_) => {
-                presenters::modes::confirm::display(&mut self.workspace, &mut self.view)
+            Mode::Blame(ref mode) => {
+                presenters::modes::blame::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Branch(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Calculator(ref mode) => {
+                presenters::modes::calculator::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::ClosePattern(ref mode) => {
+                presenters::modes::close_pattern::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Confirm(ref mode) => {
+                presenters::modes::confirm::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Ex(ref mode) => {
+                presenters::modes::ex::display(&mut self.workspace, mode, &mut self.view)
             }
             Mode::Command(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
-            Mode::Insert => presenters::modes::insert::display(&mut self.workspace, &mut self.view),
+            Mode::Grep(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Buffer(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Number(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Transform(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Debug(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Diagnostic(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Task(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Stash(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::UndoTree(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::JumpList(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Marks(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Tags(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Mark(ref mode) => {
+                presenters::modes::mark::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::FileTree(ref mode) => {
+                presenters::modes::file_tree::display(&mut self.workspace, self.file_tree.as_ref(), mode, &mut self.view)
+            }
+            Mode::Registers(ref mut mode) => {
+                presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Register(ref mode) => {
+                presenters::modes::register::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Insert => {
+                presenters::modes::insert::display(&mut self.workspace, &mut self.view, &self.multi_cursors, self.completion.as_ref())
+            }
             Mode::Open(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
@@ -117,12 +482,24 @@ impl Application {
             Mode::Jump(ref mut mode) => {
                 presenters::modes::jump::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::KeyRecorder(ref mode) => {
+                presenters::modes::key_recorder::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::LineJump(ref mode) => {
                 presenters::modes::line_jump::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Passphrase(ref mode) => {
+                presenters::modes::passphrase::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::Path(ref mode) => {
                 presenters::modes::path::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::Rename(ref mode) => {
+                presenters::modes::rename::display(&mut self.workspace, mode, &mut self.view)
+            }
+            Mode::Replace(ref mode) => {
+                presenters::modes::replace::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::SymbolJump(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
             }
@@ -132,10 +509,17 @@ impl Application {
             Mode::SelectLine(ref mode) => {
                 presenters::modes::select_line::display(&mut self.workspace, mode, &mut self.view)
             }
+            Mode::SelectBlock(ref mode) => {
+                presenters::modes::select_block::display(&mut self.workspace, mode, &mut self.view)
+            }
             Mode::Normal => presenters::modes::normal::display(
                 &mut self.workspace,
                 &mut self.view,
-                &self.repository,
+                &self.git_worker,
+                &self.buffer_groups,
+                &self.pinned_buffers,
+                &self.read_only_buffers,
+                &self.multi_cursors,
             ),
             Mode::Theme(ref mut mode) => {
                 presenters::modes::search_select::display(&mut self.workspace, mode, &mut self.view)
@@ -145,37 +529,589 @@ impl Application {
     }
 
     fn wait_for_event(&mut self) -> Result<()> {
-        let event = self
-            .events
-            .recv()
-            .chain_err(|| "Error receiving application event")?;
+        // Stop waiting for the rest of a pending (or recorded) key sequence
+        // after a while. Recording always ends once keys stop being pressed.
+        let timeout = self.preferences.borrow().key_sequence_timeout();
+        let timeout = match self.mode {
+            Mode::KeyRecorder(ref mode) if !mode.binding.keys.is_empty() => {
+                Some(timeout.unwrap_or(KEY_RECORDING_TIMEOUT))
+            }
+            _ if !self.pending_keys.is_empty() => timeout,
+            _ => None,
+        };
+        // A flash (or line highlight) needs to be drawn over once it
+        // ends, which takes priority over waiting for a pending key sequence.
+        let flash = self.view.redraw_remaining();
+        let event = match timeout {
+            Some(timeout) if flash.filter(|flash| *flash < timeout).is_none() => {
+                match self.events.recv_timeout(timeout) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => {
+                        self.error = if let Mode::KeyRecorder(_) = self.mode {
+                            self.finish_key_recording().err()
+                        } else {
+                            commands::application::resolve_pending_keys(self).err()
+                        };
+                        return Ok(());
+                    }
+                    Err(RecvTimeoutError::Disconnected) => bail!("Error receiving application event"),
+                }
+            }
+            _ => match flash {
+                Some(flash) => match self.events.recv_timeout(flash) {
+                    Ok(event) => event,
+                    Err(RecvTimeoutError::Timeout) => return Ok(()),
+                    Err(RecvTimeoutError::Disconnected) => bail!("Error receiving application event"),
+                },
+                None => self.events.recv().chain_err(|| "Error receiving application event")?,
+            },
+        };
         match event {
             Event::Key(key) => {
                 self.view.last_key = Some(key);
+                self.notification = None;
                 self.error = commands::application::handle_input(self).err();
+                if self.error.is_some() {
+                    self.view.signal();
+                }
+            }
+            Event::Mouse(mouse) => {
+                self.notification = None;
+                self.error = commands::mouse::handle(self, mouse).err();
+                if self.error.is_some() {
+                    self.view.signal();
+                }
             }
+            Event::Paste(content) => {
+                self.notification = None;
+                self.error = commands::application::handle_paste(self, &content).err();
+                if self.error.is_some() {
+                    self.view.signal();
+                }
+            }
+            Event::Clipboard(content) => self.clipboard.receive_system_content(content),
             Event::Resize => {}
-            Event::OpenModeIndexComplete(index) => {
+            Event::OpenModeIndexProgress { generation, paths, complete } => {
                 if let Mode::Open(ref mut open_mode) = self.mode {
-                    open_mode.set_index(index);
+                    if let Some(index) = open_mode.add_indexed_paths(generation, paths, complete) {
+                        self.open_mode_index = Some(index.clone());
+                    }
+                }
+            }
+            Event::GrepResults { generation, matches, complete } => {
+                if let Mode::Grep(ref mut grep_mode) = self.mode {
+                    grep_mode.add_results(generation, matches, complete);
+                }
+            }
+            Event::Git(result) => {
+                self.error = self.complete_git_operation(result).err();
+            }
+            Event::JobComplete(result) => {
+                self.error = self.complete_job(result).err();
+            }
+            Event::DebugMessage(message) => {
+                self.error = self.handle_debug_message(&message).err();
+            }
+            Event::DebugAdapterExited => {
+                if self.debugger.is_running() {
+                    self.debugger.end();
+                    self.error = Some("Debug adapter exited unexpectedly".into());
+                }
+                self.clear_debug_location();
+            }
+            Event::LanguageServerMessage(name, message) => {
+                self.error = self.handle_language_server_message(&name, &message).err();
+            }
+            Event::LanguageServerExited(name) => {
+                self.language_servers.remove(&name);
+                self.error = Some(format!("Language server \"{}\" exited", name).into());
+            }
+            Event::CollaboratorConnected(address) => {
+                self.notification = Some(format!("Collaborating with {}", address));
+            }
+            Event::CollaborationMessage(session, message) => {
+                // Messages read before leaving a session may still be queued.
+                if self.collaboration.is_current(session) {
+                    self.error = self.handle_collaboration_message(message).err();
+                }
+            }
+            Event::CollaborationEnded(session) => {
+                if self.collaboration.is_current(session) {
+                    commands::collab::end_session(self);
+                    self.error = Some("Collaborator disconnected".into());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Prompts for the command to bind the recorded key sequence to.
+    fn finish_key_recording(&mut self) -> Result<()> {
+        let binding = match self.mode {
+            Mode::KeyRecorder(ref mode) => mode.binding.clone(),
+            _ => bail!("Can't finish recording keys outside of key recorder mode"),
+        };
+        let config = self.preferences.borrow().search_select_config();
+        self.mode = Mode::Command(CommandMode::for_key_binding(binding, config));
+
+        commands::search_select::search(self)
+    }
+
+    fn complete_job(&mut self, result: JobResult) -> Result<()> {
+        match result.job {
+            Job::Task { command } => {
+                // Display the task's output in a new buffer.
+                let mut buffer = Buffer::new();
+                buffer.insert(result.output);
+                util::add_buffer(buffer, self)?;
+
+                if !result.success {
+                    bail!("\"{}\" failed", command);
+                }
+            }
+            Job::Test { buffer_id, tests } => {
+                let results = test_runner::parse_results(&result.output);
+                self.view.set_annotations(
+                    buffer_id,
+                    test_runner::ANNOTATION_PROVIDER,
+                    test_runner::result_annotations(&tests, &results)
+                );
+
+                if results.is_empty() && !result.success {
+                    bail!("Test command failed: {}", result.output.lines().last().unwrap_or(""));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_debug_message(&mut self, message: &Yaml) -> Result<()> {
+        for action in self.debugger.handle_message(message)? {
+            match action {
+                DebugAction::ShowLocation(path, line) => {
+                    self.clear_debug_location();
+                    self.workspace.open_buffer(&path).chain_err(|| {
+                        format!("Couldn't open {}", path.to_string_lossy())
+                    })?;
+                    self.view.initialize_buffer(self.workspace.current_buffer().unwrap())?;
+
+                    if let Some(buffer) = self.workspace.current_buffer() {
+                        buffer.cursor.move_to(Position { line, offset: 0 });
+                        if let Some(id) = buffer.id {
+                            self.view.set_annotations(
+                                id,
+                                dap::LOCATION_ANNOTATION_PROVIDER,
+                                dap::location_annotations(line)
+                            );
+                            self.debugger.location_buffer = Some(id);
+                        }
+                    }
+                    commands::view::scroll_cursor_to_center(self)?;
+                }
+                DebugAction::ShowVariables(content) => {
+                    let pane = self.debugger.variables_buffer;
+                    if pane.map(|id| util::select_buffer(id, self)) == Some(true) {
+                        let buffer = self.workspace.current_buffer().unwrap();
+                        let end = Position { line: buffer.line_count(), offset: 0 };
+                        buffer.delete_range(Range::new(Position { line: 0, offset: 0 }, end));
+                        buffer.insert(content);
+                    } else {
+                        let mut buffer = Buffer::new();
+                        buffer.insert(content);
+                        util::add_buffer(buffer, self)?;
+                        self.debugger.variables_buffer =
+                            self.workspace.current_buffer().and_then(|b| b.id);
+                    }
+                }
+                DebugAction::ClearLocation | DebugAction::End => self.clear_debug_location(),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn clear_debug_location(&mut self) {
+        if let Some(id) = self.debugger.location_buffer.take() {
+            self.view.clear_annotations(id, dap::LOCATION_ANNOTATION_PROVIDER);
+        }
+    }
+
+    // Keeps the current buffer's language server (if one is configured)
+    // up-to-date with its content, and displays its diagnostics.
+    fn sync_language_server(&mut self) -> Result<()> {
+        let root = self.workspace.path.clone();
+        let buffer = match self.workspace.current_buffer() {
+            Some(buffer) => buffer,
+            None => return Ok(()),
+        };
+        let (id, path) = match (buffer.id, buffer.path.clone()) {
+            (Some(id), Some(path)) => (id, path),
+            _ => return Ok(()),
+        };
+
+        // An encrypted buffer's plaintext mustn't leave the editor.
+        if self.encrypted_buffers.contains_key(&id) {
+            return Ok(());
+        }
+
+        let command = match self.preferences.borrow().language_server(Some(&path)) {
+            Some(command) => command,
+            None => return Ok(()),
+        };
+        let revision = match self.view.revision(buffer) {
+            Some(revision) => revision,
+            None => return Ok(()),
+        };
+
+        let result = self.language_servers.sync(
+            &command,
+            &path,
+            revision,
+            || buffer.data(),
+            &root,
+            &self.event_channel
+        );
+        self.view.set_annotations(
+            id,
+            lsp::DIAGNOSTIC_ANNOTATION_PROVIDER,
+            lsp::diagnostic_annotations(self.language_servers.diagnostics(&path))
+        );
+
+        result
+    }
+
+    // Requests the current buffer's git status (when it may have changed),
+    // and its lines that differ from the repository's index, for the gutter.
+    fn sync_git(&mut self) -> Result<()> {
+        let buffer = match self.workspace.current_buffer() {
+            Some(buffer) => buffer,
+            None => return Ok(()),
+        };
+        self.git_worker.refresh_status(buffer.path.as_deref(), self.view.modified(buffer));
+
+        if !self.preferences.borrow().git_gutter() {
+            return Ok(());
+        }
+
+        // An encrypted buffer's plaintext can't be compared to its repository's ciphertext.
+        if matches!(buffer.id, Some(id) if self.encrypted_buffers.contains_key(&id)) {
+            return Ok(());
+        }
+
+        self.git_worker.diff(buffer, self.view.revision(buffer));
+
+        Ok(())
+    }
+
+    // Keeps the results of git operations run in the background.
+    fn complete_git_operation(&mut self, result: GitResult) -> Result<()> {
+        self.git_worker.complete(&result);
+
+        match result {
+            GitResult::Hunks { buffer_id, hunks } => {
+                let hunks = hunks?;
+                let annotations = git_gutter::annotations(&hunks, &self.preferences.borrow());
+                self.view.set_annotations(buffer_id, git_gutter::ANNOTATION_PROVIDER, annotations);
+                self.git_gutter.set_hunks(buffer_id, hunks);
+            }
+            GitResult::Added { path, error: Some(error) } => {
+                bail!("Couldn't add {}: {}", path.to_string_lossy(), error)
+            }
+            GitResult::Branches(branches) => {
+                if let Mode::Branch(ref mut mode) = self.mode {
+                    mode.set_branches(branches?);
+                }
+            }
+            GitResult::Stashes(stashes) => {
+                if let Mode::Stash(ref mut mode) = self.mode {
+                    mode.set_stashes(stashes?);
+                }
+            }
+            GitResult::CheckedOut { error } | GitResult::Stashed { error } | GitResult::StashApplied { error } => {
+                if let Some(error) = error {
+                    bail!(error);
+                }
+                commands::workspace::reload_unmodified_buffers(self)?;
+            }
+            GitResult::StashDropped { error } => {
+                if let Some(error) = error {
+                    bail!(error);
+                }
 
-                    // Trigger a search, in case a query was
-                    // entered while we were indexing.
-                    open_mode.search();
+                // Stash indices have shifted; list them again.
+                if let Mode::Stash(_) = self.mode {
+                    self.git_worker.list_stashes()?;
                 }
             }
+            GitResult::Blame { buffer_id, line: None, lines } => {
+                commands::git::show_blame(self, buffer_id, lines?)?;
+            }
+            GitResult::Blame { line: Some(line), lines, .. } => {
+                commands::git::show_blamed_line(self, &lines?, line)?;
+            }
+            GitResult::CommitDetails(content) => commands::git::show_commit(self, content?)?,
+            GitResult::HunkStaged { error: Some(error) } => bail!(error),
+            GitResult::StagedHunk { buffer_id, hunk, lines } => {
+                commands::git::restore_hunk(self, buffer_id, &hunk, &lines?)?;
+            }
+            GitResult::RemoteUrl(url) => self.clipboard.set_content(ClipboardContent::Inline(url?))?,
+            GitResult::Added { .. }
+            | GitResult::HunkStaged { .. }
+            | GitResult::Status { .. }
+            | GitResult::RepositoryChanged => (),
         }
 
         Ok(())
     }
 
+    /// Waits for the git operations in progress to complete, keeping their results.
+    #[cfg(test)]
+    pub fn complete_git_operations(&mut self) -> Result<()> {
+        while self.git_worker.busy() {
+            match self.events.recv_timeout(Duration::from_secs(5)) {
+                Ok(Event::Git(result)) => self.complete_git_operation(result)?,
+                Ok(_) => (),
+                Err(_) => bail!("Timed out waiting for git operations to complete"),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_language_server_message(&mut self, name: &str, message: &Yaml) -> Result<()> {
+        for action in self.language_servers.handle_message(name, message)? {
+            match action {
+                LspAction::ShowLocation(path, line, character) => {
+                    commands::lsp::open_location(self, &path, line, character)?;
+                }
+                LspAction::ShowHover(content) => self.notification = Some(content),
+                LspAction::ShowCompletions(candidates) => {
+                    if let Some(ref mut completion) = self.completion {
+                        if completion.pending {
+                            completion.add(candidates);
+                        }
+                    }
+                    commands::completion::close_if_empty(self);
+                }
+                LspAction::ApplyEdits(edits) => {
+                    // Prefer open buffers' content, which the server has seen. Encrypted
+                    // buffers are skipped, as applying edits would write their plaintext.
+                    let mut changes = ChangeSet::default();
+                    for (path, edits) in edits {
+                        if self.buffer_id(&path).map_or(false, |id| self.encrypted_buffers.contains_key(&id)) {
+                            continue;
+                        }
+                        let original = self.buffer_content(&path).map(Ok).unwrap_or_else(|| {
+                            fs::read_to_string(&path)
+                                .chain_err(|| format!("Couldn't read {}", path.to_string_lossy()))
+                        })?;
+                        let modified = lsp::document::apply_edits(&original, &edits);
+                        changes.add(path, original, modified);
+                    }
+
+                    util::preview_changes(changes, self)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Sends the current buffer's changes and cursor to the collaborator, if any.
+    fn sync_collaboration(&mut self) -> Result<()> {
+        if !self.collaboration.is_connected() {
+            return Ok(());
+        }
+        let selection = match self.mode {
+            Mode::Select(ref mode) => Some(mode.anchor),
+            _ => None,
+        };
+        let workspace_path = self.workspace.path.clone();
+        let buffer = match self.workspace.current_buffer() {
+            Some(buffer) => buffer,
+            None => return Ok(()),
+        };
+
+        // Buffers outside of the workspace can't be shared, nor can encrypted ones.
+        if matches!(buffer.id, Some(id) if self.encrypted_buffers.contains_key(&id)) {
+            return Ok(());
+        }
+        let path = match buffer.path.as_ref().and_then(|path| path.strip_prefix(&workspace_path).ok()) {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let revision = match self.view.revision(buffer) {
+            Some(revision) => revision,
+            None => return Ok(()),
+        };
+
+        self.collaboration.sync(path, revision, || buffer.data(), *buffer.cursor, selection)
+    }
+
+    // Applies the collaborator's edits to, and shows their cursor in, the
+    // buffer open for the message's path. Other paths are ignored.
+    fn handle_collaboration_message(&mut self, message: Message) -> Result<()> {
+        match message {
+            Message::Content { path, content } => {
+                // Encrypted buffers aren't shared, so content for their paths isn't either.
+                let current_id = self.workspace.current_buffer().and_then(|b| b.id);
+                let id = self.buffer_id(&self.workspace.path.join(&path))
+                    .filter(|id| !self.encrypted_buffers.contains_key(id));
+                if id.map(|id| util::select_buffer(id, self)) == Some(true) {
+                    let buffer = self.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+                    util::reload::replace_content(buffer, &content);
+                    if let Some(revision) = self.view.revision(buffer) {
+                        self.collaboration.received(&path, revision);
+                    }
+                }
+                if let Some(id) = current_id {
+                    util::select_buffer(id, self);
+                }
+            }
+            Message::Cursor { path, position, selection } => {
+                if let Some(id) = self.collaboration.peer_buffer.take() {
+                    self.view.clear_annotations(id, collab::ANNOTATION_PROVIDER);
+                }
+                if let Some(id) = self.buffer_id(&self.workspace.path.join(&path)) {
+                    self.view.set_annotations(
+                        id,
+                        collab::ANNOTATION_PROVIDER,
+                        collab::cursor_annotations(position, selection)
+                    );
+                    self.collaboration.peer_buffer = Some(id);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    // Finds the ID of the buffer open for the path, if any.
+    fn buffer_id(&mut self, path: &Path) -> Option<usize> {
+        let current_id = self.workspace.current_buffer().and_then(|b| b.id);
+        let mut buffer_id = None;
+        for id in util::buffer_ids(self) {
+            if util::select_buffer(id, self) {
+                let buffer = self.workspace.current_buffer()?;
+                if buffer.path.as_deref() == Some(path) {
+                    buffer_id = Some(id);
+                    break;
+                }
+            }
+        }
+        if let Some(id) = current_id {
+            util::select_buffer(id, self);
+        }
+
+        buffer_id
+    }
+
+    // Reads the content of the buffer open for the path, if any.
+    fn buffer_content(&mut self, path: &Path) -> Option<String> {
+        let current_id = self.workspace.current_buffer().and_then(|b| b.id);
+        let mut content = None;
+        for id in util::buffer_ids(self) {
+            if util::select_buffer(id, self) {
+                let buffer = self.workspace.current_buffer()?;
+                if buffer.path.as_deref() == Some(path) {
+                    content = Some(buffer.data());
+                    break;
+                }
+            }
+        }
+        if let Some(id) = current_id {
+            util::select_buffer(id, self);
+        }
+
+        content
+    }
+
     pub fn mode_str(&self) -> Option<&'static str> {
         match self.mode {
+            Mode::Branch(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
             Mode::Command(ref mode) => if mode.insert_mode() {
                 Some("search_select_insert")
             } else {
                 Some("search_select")
             },
+            Mode::Grep(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Buffer(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Number(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Transform(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Debug(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Diagnostic(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Task(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Stash(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::UndoTree(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::JumpList(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Marks(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Tags(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Mark(_) => Some("mark"),
+            Mode::FileTree(ref mode) => match mode.operation {
+                Some(FileOperation::Delete) => Some("file_tree_confirm"),
+                Some(_) => Some("file_tree_input"),
+                None => Some("file_tree"),
+            },
+            Mode::Registers(ref mode) => if mode.insert_mode() {
+                Some("search_select_insert")
+            } else {
+                Some("search_select")
+            },
+            Mode::Register(_) => Some("register"),
             Mode::SymbolJump(ref mode) => if mode.insert_mode() {
                 Some("search_select_insert")
             } else {
@@ -192,13 +1128,34 @@ impl Application {
                 Some("search_select")
             },
             Mode::Normal => Some("normal"),
+            Mode::Passphrase(_) => Some("passphrase"),
             Mode::Path(_) => Some("path"),
+            Mode::Blame(_) => Some("blame"),
+            Mode::Calculator(_) => Some("calculator"),
+            Mode::ClosePattern(_) => Some("close_pattern"),
             Mode::Confirm(_) => Some("confirm"),
-            Mode::Insert => Some("insert"),
+            Mode::Ex(_) => Some("ex"),
+            Mode::Insert => if self.completion.is_some() {
+                Some("completion")
+            } else {
+                Some("insert")
+            },
             Mode::Jump(_) => Some("jump"),
+            Mode::KeyRecorder(_) => Some("key_recorder"),
             Mode::LineJump(_) => Some("line_jump"),
+            Mode::Rename(_) => Some("rename"),
+            Mode::Replace(ref mode) => if mode.stage == ReplaceStage::Confirm {
+                Some("replace")
+            } else {
+                Some("replace_input")
+            },
             Mode::Select(_) => Some("select"),
             Mode::SelectLine(_) => Some("select_line"),
+            Mode::SelectBlock(ref mode) => if mode.insert_mode() {
+                Some("select_block_insert")
+            } else {
+                Some("select_block")
+            },
             Mode::Search(ref mode) => if mode.insert_mode() {
                 Some("search_insert")
             } else {
@@ -215,7 +1172,41 @@ fn initialize_preferences() -> Rc<RefCell<Preferences>> {
     ))
 }
 
-fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
+// Expands glob patterns in path arguments, for shells that don't (or when
+// they're quoted). Like most shells, patterns without matches are left as-is,
+// as are arguments that exist verbatim (e.g. file names containing "*").
+fn expand_path_args(args: &[String]) -> Vec<String> {
+    let mut expanded = Vec::new();
+    let mut args = args.iter();
+    expanded.extend(args.next().cloned());
+
+    for arg in args {
+        let is_pattern = arg.contains(&['*', '?', '['][..]);
+        let matches: Vec<String> = if is_pattern && !Path::new(arg).exists() {
+            glob::glob(arg)
+                .map(|paths| {
+                    paths
+                        .filter_map(|path| path.ok())
+                        .filter(|path| path.is_file())
+                        .map(|path| path.to_string_lossy().into_owned())
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            Vec::new()
+        };
+
+        if matches.is_empty() {
+            expanded.push(arg.clone());
+        } else {
+            expanded.extend(matches);
+        }
+    }
+
+    expanded
+}
+
+fn create_workspace(view: &mut View, args: &[String]) -> Result<Workspace> {
     // Discard the executable portion of the argument list.
     let mut path_args = args.iter().skip(1).peekable();
 
@@ -278,10 +1269,15 @@ fn create_workspace(view: &mut View, args: &Vec<String>) -> Result<Workspace> {
 
 #[cfg(test)]
 mod tests {
-    use super::Application;
+    use super::{Application, Preferences};
+    use crate::collab::Message;
+    use crate::util;
+    use crate::yaml::yaml::YamlLoader;
     use scribe::Buffer;
+    use std::cell::RefCell;
     use std::env;
-    use std::path::Path;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
 
     #[test]
     fn application_uses_file_arguments_to_load_contents_into_buffers_when_files_exist() {
@@ -299,6 +1295,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn expand_path_args_expands_glob_patterns_into_matching_files() {
+        let args = vec![
+            String::from("amp"),
+            String::from("src/models/application/modes/open/*.rs"),
+            String::from("non_existent_*.rs"),
+        ];
+
+        assert_eq!(
+            super::expand_path_args(&args),
+            vec![
+                String::from("amp"),
+                String::from("src/models/application/modes/open/displayable_path.rs"),
+                String::from("src/models/application/modes/open/exclusions.rs"),
+                String::from("src/models/application/modes/open/mod.rs"),
+                String::from("non_existent_*.rs"),
+            ]
+        );
+    }
+
     #[test]
     fn application_uses_file_arguments_to_create_new_buffers_when_files_do_not_exist() {
         let mut application =
@@ -310,4 +1326,40 @@ mod tests {
         );
         assert_eq!(application.workspace.current_buffer().unwrap().data(), "");
     }
+
+    #[test]
+    fn collaboration_content_leaves_encrypted_buffers_unchanged() {
+        let mut application = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.path = Some(application.workspace.path.join("collab_encrypted"));
+        buffer.insert("amp");
+        application.workspace.add_buffer(buffer);
+        let id = application.workspace.current_buffer().unwrap().id.unwrap();
+        application.encrypted_buffers.insert(id, String::from("passphrase"));
+
+        application.handle_collaboration_message(Message::Content {
+            path: PathBuf::from("collab_encrypted"),
+            content: String::from("editor"),
+        }).unwrap();
+
+        assert_eq!(application.workspace.current_buffer().unwrap().data(), "amp");
+    }
+
+    #[test]
+    fn sync_language_server_skips_encrypted_buffers() {
+        let mut application = Application::new(&Vec::new()).unwrap();
+        let data = YamlLoader::load_from_str("types:\n  rs:\n    language_server: amp_missing_language_server").unwrap();
+        application.preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        let mut buffer = Buffer::new();
+        buffer.path = Some(application.workspace.path.join("encrypted.rs"));
+        util::add_buffer(buffer, &mut application).unwrap();
+        let id = application.workspace.current_buffer().unwrap().id.unwrap();
+
+        application.encrypted_buffers.insert(id, String::from("passphrase"));
+        assert!(application.sync_language_server().is_ok());
+
+        // Without encryption, the (missing) server is started for the buffer.
+        application.encrypted_buffers.remove(&id);
+        assert!(application.sync_language_server().is_err());
+    }
 }
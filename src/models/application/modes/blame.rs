@@ -0,0 +1,26 @@
+use crate::util::git_blame::BlameLine;
+use std::fmt;
+
+/// Shows the commit that last changed each of the current buffer's
+/// lines in a column alongside them, until another key is pressed.
+pub struct BlameMode {
+    pub buffer_id: usize,
+    pub lines: Vec<Option<BlameLine>>,
+}
+
+impl BlameMode {
+    pub fn new(buffer_id: usize, lines: Vec<Option<BlameLine>>) -> BlameMode {
+        BlameMode { buffer_id, lines }
+    }
+
+    /// The commit that last changed the line, unless it hasn't been committed.
+    pub fn line(&self, line: usize) -> Option<&BlameLine> {
+        self.lines.get(line).and_then(Option::as_ref)
+    }
+}
+
+impl fmt::Display for BlameMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BLAME")
+    }
+}
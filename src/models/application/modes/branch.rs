@@ -0,0 +1,111 @@
+use crate::util::{fuzzy, SelectableVec};
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+use crate::commands::{self, Command};
+
+pub struct BranchMode {
+    insert: bool,
+    input: Prompt,
+    branches: Vec<String>,
+    results: SelectableVec<String>,
+    config: SearchSelectConfig,
+
+    /// Whether the branches are still being listed in the background.
+    listing: bool,
+}
+
+impl BranchMode {
+    pub fn new(config: SearchSelectConfig) -> BranchMode {
+        BranchMode {
+            insert: true,
+            input: Prompt::default(),
+            branches: Vec::new(),
+            results: SelectableVec::new(Vec::new()),
+            config,
+            listing: true,
+        }
+    }
+
+    /// Lists the branches, once they've been found.
+    pub fn set_branches(&mut self, branches: Vec<String>) {
+        self.branches = branches;
+        self.listing = false;
+        self.search();
+    }
+}
+
+impl fmt::Display for BranchMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BRANCH")
+    }
+}
+
+impl SearchSelectMode<String> for BranchMode {
+    fn search(&mut self) {
+        // Find the branches we're looking for using the query.
+        let results = fuzzy::find(&self.input, &self.branches, self.config.max_results);
+
+        // We don't care about the result objects; we just want
+        // the underlying names. Map the collection to get these.
+        self.results = SelectableVec::new(
+            results
+            .into_iter()
+            .map(|r| r.clone())
+            .collect()
+        );
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<String> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&String> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("checkout", commands::search_select::accept)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.listing {
+            Some(String::from("Listing branches…"))
+        } else if self.query().is_empty() {
+            Some(String::from("Enter a search query to start."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
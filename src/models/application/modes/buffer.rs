@@ -0,0 +1,454 @@
+use fragment::matching::AsStr;
+use crate::util::{fuzzy, SelectableVec};
+use scribe::Workspace;
+use std::cmp::{Ordering, Reverse};
+use std::collections::HashSet;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::slice::Iter;
+use std::time::SystemTime;
+use crate::models::application::RecentBuffers;
+use crate::models::application::modes::{ResultOrder, SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+use crate::commands::{self, Command};
+use crate::view::View;
+
+/// Displayed alongside the titles of pinned buffers.
+pub const PIN_GLYPH: &str = "⚑";
+
+// Unnamed buffers are previewed using (at most) this many characters.
+const PREVIEW_LENGTH: usize = 50;
+
+pub struct BufferMode {
+    insert: bool,
+    input: Prompt,
+    buffers: Vec<BufferEntry>,
+    results: SelectableVec<BufferEntry>,
+    order: ResultOrder,
+    config: SearchSelectConfig,
+
+    /// A modified buffer that'll be closed if it's removed again.
+    pub pending_close: Option<usize>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct BufferEntry {
+    pub id: usize,
+    pub title: String,
+
+    /// The first non-empty line of an unnamed buffer.
+    pub preview: Option<String>,
+
+    /// The content matched against search queries.
+    pub search_str: String,
+    pub pinned: bool,
+
+    /// Whether the buffer has unsaved changes.
+    pub modified: bool,
+    pub len_lines: usize,
+
+    /// When the buffer was last used, relative to other buffers.
+    pub last_access: Option<u64>,
+
+    /// When the buffer's file was last modified on disk.
+    pub modified_at: Option<SystemTime>,
+
+    /// The characters of the search string that matched the query.
+    pub matched_indices: Vec<usize>,
+}
+
+impl BufferEntry {
+    /// Unnamed buffers are given a stable name (based on their ID),
+    /// and are previewed using their first non-empty line.
+    pub fn new(id: usize, path: Option<&Path>, data: &str, pinned: bool, modified: bool) -> BufferEntry {
+        let (title, preview) = match path {
+            Some(path) => (path.to_string_lossy().into_owned(), None),
+            None => {
+                let preview = data
+                    .lines()
+                    .map(str::trim)
+                    .find(|line| !line.is_empty())
+                    .map(|line| line.chars().take(PREVIEW_LENGTH).collect());
+
+                (format!("scratch-{}", id + 1), preview)
+            }
+        };
+        let search_str = match preview {
+            Some(ref preview) => format!("{} {}", title, preview),
+            None => title.clone(),
+        };
+
+        BufferEntry {
+            id,
+            title,
+            preview,
+            search_str,
+            pinned,
+            modified,
+            len_lines: data.lines().count(),
+            last_access: None,
+            modified_at: None,
+            matched_indices: Vec::new(),
+        }
+    }
+
+    // The ID and flags listed before the title.
+    fn label(&self) -> String {
+        let mut label = format!("#{} ", self.id + 1);
+        if self.pinned {
+            label.push_str(PIN_GLYPH);
+            label.push(' ');
+        }
+        if self.modified {
+            label.push_str("[+] ");
+        }
+
+        label
+    }
+}
+
+impl fmt::Display for BufferEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}{}", self.label(), self.title)?;
+        if let Some(ref preview) = self.preview {
+            write!(f, ": {}", preview)?;
+        }
+
+        write!(f, " ({} line{})", self.len_lines, if self.len_lines == 1 { "" } else { "s" })
+    }
+}
+
+impl AsStr for BufferEntry {
+    fn as_str(&self) -> &str {
+        &self.search_str
+    }
+}
+
+impl BufferMode {
+    /// Lists the workspace's buffers, from most to least recently used
+    /// (with pinned buffers first). The current buffer is listed last,
+    /// so that the previous one is selected by default.
+    pub fn new(workspace: &mut Workspace, view: &View, pinned_buffers: &HashSet<usize>, recent_buffers: &RecentBuffers, config: SearchSelectConfig) -> BufferMode {
+        let mut buffers = Vec::new();
+        let start_id = workspace.current_buffer().and_then(|b| b.id);
+        let root = workspace.path.clone();
+
+        if start_id.is_some() {
+            loop {
+                if let Some(buffer) = workspace.current_buffer() {
+                    if let Some(id) = buffer.id {
+                        let pinned = pinned_buffers.contains(&id);
+                        let path = buffer.path.as_ref().map(|path| path.strip_prefix(&root).unwrap_or(path));
+                        let mut entry = BufferEntry::new(id, path, &buffer.data(), pinned, view.modified(buffer));
+                        entry.last_access = recent_buffers.last_access(id);
+                        entry.modified_at = buffer.path
+                            .as_ref()
+                            .and_then(|path| fs::metadata(path).ok())
+                            .and_then(|metadata| metadata.modified().ok());
+                        buffers.push(entry);
+                    }
+                }
+
+                workspace.next_buffer();
+                if workspace.current_buffer().and_then(|b| b.id) == start_id {
+                    break;
+                }
+            }
+        }
+
+        buffers.sort_by_key(|entry| {
+            (Some(entry.id) == start_id, Reverse(entry.last_access))
+        });
+
+        BufferMode::with_entries(buffers, config)
+    }
+
+    fn with_entries(mut buffers: Vec<BufferEntry>, config: SearchSelectConfig) -> BufferMode {
+        // The sort is stable, so buffers otherwise keep their existing order.
+        buffers.sort_by_key(|entry| !entry.pinned);
+
+        BufferMode {
+            insert: true,
+            input: Prompt::default(),
+            buffers,
+            results: SelectableVec::new(Vec::new()),
+            order: ResultOrder::default(),
+            config,
+            pending_close: None,
+        }
+    }
+
+    pub fn order(&self) -> ResultOrder {
+        self.order
+    }
+
+    /// Re-sorts the results, keeping the selected buffer selected
+    /// (unless the default order is restored, which re-runs the search).
+    pub fn set_order(&mut self, order: ResultOrder) {
+        self.order = order;
+        if order == ResultOrder::Score {
+            self.search();
+        } else {
+            self.results.sort_by(|a, b| compare(order, a, b));
+        }
+    }
+
+    /// Removes a (closed) buffer from the list, keeping
+    /// the selection as close to where it was as possible.
+    pub fn remove_entry(&mut self, id: usize) {
+        let index = self.results.selected_index();
+        self.buffers.retain(|entry| entry.id != id);
+        self.search();
+        self.results.select(index);
+    }
+}
+
+impl fmt::Display for BufferMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BUFFER")
+    }
+}
+
+impl SearchSelectMode<BufferEntry> for BufferMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            self.buffers
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            let mut results: Vec<BufferEntry> =
+                fuzzy::find(&self.input, &self.buffers, self.config.max_results)
+                    .into_iter()
+                    .map(|result| {
+                        let mut entry = (*result).clone();
+                        entry.matched_indices = result.indices;
+
+                        entry
+                    })
+                    .collect();
+
+            // Pinned buffers take precedence over better matches.
+            results.sort_by_key(|entry| !entry.pinned);
+            results
+        };
+
+        self.results = SelectableVec::new(results);
+        let order = self.order;
+        if order != ResultOrder::Score {
+            self.results.sort_by(|a, b| compare(order, a, b));
+        }
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<BufferEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&BufferEntry> {
+        self.results.selection()
+    }
+
+    // Entries are listed with their ID and flags first, and their
+    // title and preview separated by a colon, rather than a space.
+    fn matched_indices(&self, index: usize) -> Vec<usize> {
+        let entry = match self.results.iter().nth(index) {
+            Some(entry) => entry,
+            None => return Vec::new(),
+        };
+        let offset = entry.label().chars().count();
+        let title_length = entry.title.chars().count();
+
+        entry.matched_indices
+            .iter()
+            .map(|&index| if index < title_length { offset + index } else { offset + index + 1 })
+            .collect()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("open", commands::search_select::accept), ("close", commands::search_select::remove_selection)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.buffers.is_empty() {
+            Some(String::from("No buffers open."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching buffers found."))
+        } else {
+            None
+        }
+    }
+}
+
+// Orders entries by the attribute, listing the most recently used
+// or modified buffers first. Entries without one are listed last.
+fn compare(order: ResultOrder, a: &BufferEntry, b: &BufferEntry) -> Ordering {
+    match order {
+        ResultOrder::Score => Ordering::Equal,
+        ResultOrder::Path => a.title.cmp(&b.title),
+        ResultOrder::Recency => b.last_access.cmp(&a.last_access),
+        ResultOrder::ModificationTime => b.modified_at.cmp(&a.modified_at),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{BufferEntry, BufferMode};
+    use crate::models::application::{Preferences, RecentBuffers};
+    use crate::models::application::modes::{ResultOrder, SearchSelectMode, SearchSelectConfig};
+    use crate::view::View;
+    use scribe::{Buffer, Workspace};
+    use std::cell::RefCell;
+    use std::collections::HashSet;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    fn entries() -> Vec<BufferEntry> {
+        vec![
+            BufferEntry::new(0, Some(Path::new("src/main.rs")), "fn main() {\n}\n", false, true),
+            BufferEntry::new(1, Some(Path::new("src/lib.rs")), "", false, false),
+            BufferEntry::new(2, Some(Path::new("Cargo.toml")), "[package]\n", true, false),
+            BufferEntry::new(3, None, "\n  fn scratch_test() {\n}", false, true),
+        ]
+    }
+
+    #[test]
+    fn search_lists_pinned_buffers_first() {
+        let mut mode = BufferMode::with_entries(entries(), SearchSelectConfig::default());
+        mode.search();
+
+        let ids: Vec<usize> = mode.results().map(|entry| entry.id).collect();
+        assert_eq!(ids, vec![2, 0, 1, 3]);
+    }
+
+    #[test]
+    fn new_lists_recently_used_buffers_first_and_the_current_buffer_last() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut recent = RecentBuffers::default();
+        for name in &["a", "b", "c", "d"] {
+            let mut buffer = Buffer::new();
+            buffer.path = Some(PathBuf::from(name));
+            workspace.add_buffer(buffer);
+        }
+        // Access "b", then "c", then "d" (the current buffer); "a" is never accessed.
+        for id in 1..4 {
+            recent.access(id);
+        }
+
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let view = View::new(preferences, mpsc::channel().0).unwrap();
+        let mut mode = BufferMode::new(&mut workspace, &view, &HashSet::new(), &recent, SearchSelectConfig::default());
+        mode.search();
+
+        let titles: Vec<String> = mode.results().map(|entry| entry.title.clone()).collect();
+        assert_eq!(titles, vec!["c", "b", "a", "d"]);
+    }
+
+    #[test]
+    fn search_filters_buffers_using_the_query() {
+        let mut mode = BufferMode::with_entries(entries(), SearchSelectConfig::default());
+        mode.query().insert_str("lib");
+        mode.search();
+
+        assert_eq!(mode.results().map(|entry| entry.id).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn matched_indices_locate_the_query_in_the_listed_entry() {
+        let mut mode = BufferMode::with_entries(entries(), SearchSelectConfig::default());
+        mode.query().insert_str("4 test");
+        mode.search();
+
+        let label = mode.results().next().unwrap().to_string();
+        let matched: String = mode.matched_indices(0)
+            .into_iter()
+            .filter_map(|index| label.chars().nth(index))
+            .collect();
+        assert_eq!(label, "#4 [+] scratch-4: fn scratch_test() { (3 lines)");
+        assert_eq!(matched, "4test");
+    }
+
+    #[test]
+    fn search_matches_unnamed_buffers_by_name_and_first_line() {
+        let mut mode = BufferMode::with_entries(entries(), SearchSelectConfig::default());
+        mode.query().insert_str("scratch-4");
+        mode.search();
+        assert_eq!(mode.results().map(|entry| entry.id).collect::<Vec<_>>(), vec![3]);
+
+        mode.query().clear();
+        mode.query().insert_str("test");
+        mode.search();
+        assert_eq!(mode.results().map(|entry| entry.id).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[test]
+    fn remove_entry_keeps_the_selection_in_place() {
+        let mut mode = BufferMode::with_entries(entries(), SearchSelectConfig::default());
+        mode.search();
+        mode.select_next();
+        mode.select_next();
+        mode.select_next();
+
+        mode.remove_entry(3);
+        assert_eq!(mode.selection().map(|entry| entry.id), Some(1));
+        mode.remove_entry(0);
+        assert_eq!(mode.selection().map(|entry| entry.id), Some(1));
+    }
+
+    #[test]
+    fn set_order_sorts_results_by_the_attribute_and_back() {
+        let mut entries = entries();
+        entries[1].last_access = Some(2);
+        entries[3].last_access = Some(1);
+        let mut mode = BufferMode::with_entries(entries, SearchSelectConfig::default());
+        mode.search();
+        mode.select_next();
+
+        mode.set_order(ResultOrder::Path);
+        assert_eq!(mode.results().map(|entry| entry.id).collect::<Vec<_>>(), vec![2, 3, 1, 0]);
+        assert_eq!(mode.selection().map(|entry| entry.id), Some(0));
+
+        mode.set_order(ResultOrder::Recency);
+        assert_eq!(mode.results().map(|entry| entry.id).collect::<Vec<_>>(), vec![1, 3, 2, 0]);
+
+        mode.set_order(ResultOrder::Score);
+        assert_eq!(mode.results().map(|entry| entry.id).collect::<Vec<_>>(), vec![2, 0, 1, 3]);
+    }
+
+    #[test]
+    fn display_includes_number_status_and_line_count() {
+        assert_eq!(entries()[0].to_string(), "#1 [+] src/main.rs (2 lines)");
+        assert_eq!(entries()[2].to_string(), "#3 ⚑ Cargo.toml (1 line)");
+        assert_eq!(entries()[3].to_string(), "#4 [+] scratch-4: fn scratch_test() { (3 lines)");
+    }
+}
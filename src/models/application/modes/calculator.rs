@@ -0,0 +1,26 @@
+use crate::models::application::Prompt;
+use crate::util::expression;
+use std::fmt;
+
+pub const HISTORY_NAMESPACE: &str = "calculator";
+
+pub struct CalculatorMode {
+    pub input: Prompt,
+}
+
+impl CalculatorMode {
+    pub fn new(input: String) -> CalculatorMode {
+        CalculatorMode { input: Prompt::new(input).with_history(HISTORY_NAMESPACE) }
+    }
+
+    /// Evaluates the input, returning its formatted result, if it's valid.
+    pub fn result(&self) -> Option<String> {
+        expression::evaluate(&self.input).ok()
+    }
+}
+
+impl fmt::Display for CalculatorMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CALC")
+    }
+}
@@ -0,0 +1,27 @@
+use crate::models::application::Prompt;
+use std::fmt;
+
+/// Prompts for a glob pattern, closing the open buffers
+/// whose paths match it once the pattern is accepted.
+pub struct ClosePatternMode {
+    pub input: Prompt,
+
+    /// Set when matching buffers have unsaved changes; the
+    /// pattern needs to be accepted again to close them.
+    pub confirm: bool,
+}
+
+impl ClosePatternMode {
+    pub fn new() -> ClosePatternMode {
+        ClosePatternMode {
+            input: Prompt::default(),
+            confirm: false,
+        }
+    }
+}
+
+impl fmt::Display for ClosePatternMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "CLOSE")
+    }
+}
@@ -0,0 +1,199 @@
+use crate::commands::{self, Result};
+use crate::models::application::{Application, LocalSettings, Preferences};
+use crate::util::prompt::complete_path;
+use std::fmt;
+
+/// The kinds of values commands take as arguments, which determine how
+/// they're completed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Parameter {
+    /// A file path, relative to the workspace (or absolute).
+    Path,
+
+    /// A preference and its value, as in "tab_width=4".
+    Setting,
+
+    /// A preference that can be changed for a single buffer, and its value.
+    LocalSetting,
+}
+
+impl Parameter {
+    pub fn usage(self) -> &'static str {
+        match self {
+            Parameter::Path => "<path>",
+            Parameter::Setting | Parameter::LocalSetting => "<setting>=<value>",
+        }
+    }
+
+    /// Candidates for completing a partially typed argument.
+    pub fn complete(self, argument: &str, themes: &[String]) -> Vec<String> {
+        match self {
+            Parameter::Path => complete_path(argument),
+            Parameter::Setting => {
+                complete_setting(argument, Preferences::settings(), |setting| {
+                    if setting == "theme" {
+                        themes.iter().map(String::as_str).collect()
+                    } else {
+                        Preferences::setting_values(setting).to_vec()
+                    }
+                })
+            },
+            Parameter::LocalSetting => {
+                complete_setting(argument, LocalSettings::settings(), |setting| {
+                    LocalSettings::setting_values(setting).to_vec()
+                })
+            },
+        }
+    }
+}
+
+// Completes a setting's name, or its value, once it's followed by "=".
+fn complete_setting<'a, I, F>(argument: &str, settings: I, values: F) -> Vec<String>
+    where I: Iterator<Item=&'static str>, F: FnOnce(&str) -> Vec<&'a str>
+{
+    let mut parts = argument.splitn(2, '=');
+    let setting = parts.next().unwrap_or("");
+    match parts.next() {
+        Some(value) => {
+            let mut candidates: Vec<String> = values(setting)
+                .into_iter()
+                .filter(|candidate| candidate.starts_with(value))
+                .map(|candidate| format!("{}={}", setting, candidate))
+                .collect();
+            candidates.sort();
+
+            candidates
+        },
+        None => settings
+            .filter(|name| name.starts_with(setting))
+            .map(|name| format!("{}=", name))
+            .collect(),
+    }
+}
+
+struct Signature {
+    name: &'static str,
+    parameter: Parameter,
+    run: fn(&mut Application, &str) -> Result,
+}
+
+// Commands that can't be run on their own, because they need an argument.
+const SIGNATURES: [Signature; 3] = [
+    Signature {
+        name: "application::set",
+        parameter: Parameter::Setting,
+        run: commands::application::set,
+    },
+    Signature {
+        name: "buffer::save_as",
+        parameter: Parameter::Path,
+        run: commands::buffer::save_as,
+    },
+    Signature {
+        name: "buffer::set_local",
+        parameter: Parameter::LocalSetting,
+        run: commands::buffer::set_local,
+    },
+];
+
+/// A command followed by its argument, typed into command mode in place of
+/// a command name (e.g. "buffer::save_as src/main.rs").
+#[derive(Clone, Debug, PartialEq)]
+pub struct ArgumentCommand {
+    pub name: &'static str,
+    pub parameter: Parameter,
+
+    /// Everything typed after the command's name, which
+    /// can include spaces (e.g. in paths).
+    pub argument: String,
+}
+
+impl ArgumentCommand {
+    pub fn parse(input: &str) -> Option<ArgumentCommand> {
+        let index = input.find(char::is_whitespace)?;
+        let (name, argument) = input.split_at(index);
+        let signature = SIGNATURES.iter().find(|signature| signature.name == name)?;
+
+        Some(ArgumentCommand {
+            name: signature.name,
+            parameter: signature.parameter,
+            argument: argument.trim_start().to_string(),
+        })
+    }
+
+    /// The names of commands that take an argument.
+    pub fn names() -> impl Iterator<Item=&'static str> {
+        SIGNATURES.iter().map(|signature| signature.name)
+    }
+
+    /// The parameter taken by the named command, if it takes one.
+    pub fn parameter_for(name: &str) -> Option<Parameter> {
+        SIGNATURES
+            .iter()
+            .find(|signature| signature.name == name)
+            .map(|signature| signature.parameter)
+    }
+
+    pub fn run(&self, app: &mut Application) -> Result {
+        let argument = self.argument.trim();
+        if argument.is_empty() {
+            bail!("{} needs an argument: {}", self.name, self.parameter.usage());
+        }
+        let signature = SIGNATURES
+            .iter()
+            .find(|signature| signature.name == self.name)
+            .ok_or("Couldn't find the command")?;
+
+        (signature.run)(app, argument)
+    }
+}
+
+impl fmt::Display for ArgumentCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.argument.trim().is_empty() {
+            write!(f, "{} {}", self.name, self.parameter.usage())
+        } else {
+            write!(f, "{} {}", self.name, self.argument.trim())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ArgumentCommand, Parameter};
+
+    #[test]
+    fn parse_splits_known_commands_from_their_arguments() {
+        assert_eq!(
+            ArgumentCommand::parse("buffer::save_as src/my file.rs"),
+            Some(ArgumentCommand {
+                name: "buffer::save_as",
+                parameter: Parameter::Path,
+                argument: String::from("src/my file.rs"),
+            })
+        );
+        assert_eq!(ArgumentCommand::parse("application::set ").map(|c| c.argument), Some(String::new()));
+        assert_eq!(ArgumentCommand::parse("buffer::save_as"), None);
+        assert_eq!(ArgumentCommand::parse("buffer::save src/main.rs"), None);
+    }
+
+    #[test]
+    fn setting_arguments_complete_names_and_values() {
+        let themes = vec![String::from("solarized_dark"), String::from("solarized_light")];
+
+        assert_eq!(Parameter::Setting.complete("tab_", &themes), vec!["tab_width="]);
+        assert_eq!(Parameter::Setting.complete("soft_tabs=", &themes), vec!["soft_tabs=false", "soft_tabs=true"]);
+        assert_eq!(
+            Parameter::Setting.complete("theme=sol", &themes),
+            vec!["theme=solarized_dark", "theme=solarized_light"]
+        );
+        assert!(Parameter::Setting.complete("tab_width=", &themes).is_empty());
+    }
+
+    #[test]
+    fn local_setting_arguments_complete_buffer_settings() {
+        assert_eq!(Parameter::LocalSetting.complete("line_", &[]), vec!["line_numbers=", "line_wrapping="]);
+        assert_eq!(Parameter::LocalSetting.complete("line_numbers=h", &[]), vec!["line_numbers=hybrid"]);
+        assert!(Parameter::LocalSetting.complete("theme", &[]).is_empty());
+    }
+}
@@ -1,15 +1,22 @@
 use std::fmt;
 use crate::commands::Command;
+use super::ArgumentCommand;
 
 // Utility type to make an Amp command function presentable (via the
 // Display trait), which is required for any type used in search/select mode.
 pub struct DisplayableCommand {
     pub description: &'static str,
-    pub command: Command,
+
+    /// Commands that take an argument don't have one to run on its own;
+    /// they're run once their argument has been typed.
+    pub command: Option<Command>,
 }
 
 impl fmt::Display for DisplayableCommand {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", self.description)
+        match ArgumentCommand::parameter_for(self.description) {
+            Some(parameter) => write!(f, "{} {}", self.description, parameter.usage()),
+            None => write!(f, "{}", self.description),
+        }
     }
 }
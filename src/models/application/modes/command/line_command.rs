@@ -0,0 +1,123 @@
+use scribe::buffer::LineRange;
+use std::fmt;
+
+/// An edit addressed by absolute (one-based) line numbers, typed into command
+/// mode in place of a command name: "d 10,20" deletes lines 10 through 20,
+/// "y 5" copies line 5, and "m 30" moves the cursor's line below line 30
+/// (moves can also be given a range to move, as in "m 10,20 30").
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineCommand {
+    pub operation: LineOperation,
+    pub range: Option<(usize, usize)>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineOperation {
+    Delete,
+    Yank,
+
+    /// Moves the lines below the specified line (or to the top, for zero).
+    Move(usize),
+}
+
+impl LineCommand {
+    pub fn parse(input: &str) -> Option<LineCommand> {
+        let mut arguments = input.split_whitespace();
+        let name = arguments.next()?;
+        let arguments: Vec<&str> = arguments.collect();
+
+        let (operation, range) = match (name, arguments.as_slice()) {
+            ("d", &[range]) => (LineOperation::Delete, Some(parse_range(range)?)),
+            ("y", &[range]) => (LineOperation::Yank, Some(parse_range(range)?)),
+            ("m", &[destination]) => (LineOperation::Move(destination.parse().ok()?), None),
+            ("m", &[range, destination]) => {
+                (LineOperation::Move(destination.parse().ok()?), Some(parse_range(range)?))
+            },
+            _ => return None,
+        };
+
+        Some(LineCommand { operation, range })
+    }
+
+    /// The (zero-based) lines the command applies to.
+    pub fn line_range(&self, cursor_line: usize) -> LineRange {
+        match self.range {
+            Some((start, end)) => LineRange::new(start - 1, end - 1),
+            None => LineRange::new(cursor_line, cursor_line),
+        }
+    }
+}
+
+impl fmt::Display for LineCommand {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let operation = match self.operation {
+            LineOperation::Delete => "Delete",
+            LineOperation::Yank => "Copy",
+            LineOperation::Move(_) => "Move",
+        };
+        match self.range {
+            Some((start, end)) if start == end => write!(f, "{} line {}", operation, start)?,
+            Some((start, end)) => write!(f, "{} lines {}-{}", operation, start, end)?,
+            None => write!(f, "{} the current line", operation)?,
+        }
+        match self.operation {
+            LineOperation::Move(0) => write!(f, " to the top"),
+            LineOperation::Move(line) => write!(f, " below line {}", line),
+            _ => Ok(()),
+        }
+    }
+}
+
+// Parses a single line number (e.g. "5") or an inclusive range ("10,20").
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let mut lines = range.splitn(2, ',');
+    let start: usize = lines.next()?.parse().ok()?;
+    let end: usize = match lines.next() {
+        Some(end) => end.parse().ok()?,
+        None => start,
+    };
+    if start == 0 || end == 0 {
+        return None;
+    }
+
+    Some((start.min(end), start.max(end)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LineCommand, LineOperation};
+
+    #[test]
+    fn parse_accepts_line_numbers_and_ranges() {
+        assert_eq!(
+            LineCommand::parse("d 10,20"),
+            Some(LineCommand { operation: LineOperation::Delete, range: Some((10, 20)) })
+        );
+        assert_eq!(
+            LineCommand::parse("y 5"),
+            Some(LineCommand { operation: LineOperation::Yank, range: Some((5, 5)) })
+        );
+        assert_eq!(
+            LineCommand::parse("m 30"),
+            Some(LineCommand { operation: LineOperation::Move(30), range: None })
+        );
+        assert_eq!(
+            LineCommand::parse("m 8,3 0"),
+            Some(LineCommand { operation: LineOperation::Move(0), range: Some((3, 8)) })
+        );
+    }
+
+    #[test]
+    fn parse_rejects_other_input() {
+        for input in &["", "d", "d 0", "d 1,x", "y 1 2", "m", "buffer::save", "delete 5"] {
+            assert_eq!(LineCommand::parse(input), None);
+        }
+    }
+
+    #[test]
+    fn display_describes_the_command() {
+        assert_eq!(LineCommand::parse("d 10,20").unwrap().to_string(), "Delete lines 10-20");
+        assert_eq!(LineCommand::parse("m 2").unwrap().to_string(), "Move the current line below line 2");
+        assert_eq!(LineCommand::parse("m 4 0").unwrap().to_string(), "Move line 4 to the top");
+    }
+}
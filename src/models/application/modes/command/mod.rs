@@ -1,69 +1,155 @@
+mod argument_command;
 mod displayable_command;
+mod line_command;
 
-use fragment;
-use crate::util::SelectableVec;
+use scribe::buffer::LineRange;
+use crate::util::{fuzzy, SelectableVec};
 use std::collections::HashMap;
 use std::fmt;
 use std::slice::Iter;
-use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::modes::{KeyBinding, SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
 use crate::commands::{self, Command};
+pub use self::argument_command::ArgumentCommand;
 pub use self::displayable_command::DisplayableCommand;
+pub use self::line_command::{LineCommand, LineOperation};
 
 pub struct CommandMode {
     insert: bool,
-    input: String,
+    input: Prompt,
     commands: HashMap<&'static str, Command>,
     results: SelectableVec<DisplayableCommand>,
+
+    /// The characters of each result that matched the query.
+    matches: Vec<Vec<usize>>,
     config: SearchSelectConfig,
+
+    /// When set, the selected command is run once per line in this range.
+    pub line_range: Option<LineRange>,
+
+    /// When set, the selected command is bound to these keys, rather than run.
+    pub key_binding: Option<KeyBinding>,
 }
 
 impl CommandMode {
     pub fn new(config: SearchSelectConfig) -> CommandMode {
         CommandMode {
             insert: true,
-            input: String::new(),
+            input: Prompt::default().with_completer(completer(true, Vec::new())),
             commands: commands::hash_map(),
             results: SelectableVec::new(Vec::new()),
+            matches: Vec::new(),
             config,
+            line_range: None,
+            key_binding: None,
+        }
+    }
+
+    /// Creates a command mode that runs its selection once per line in the range.
+    pub fn for_lines(line_range: LineRange, config: SearchSelectConfig) -> CommandMode {
+        CommandMode {
+            line_range: Some(line_range),
+            input: Prompt::default().with_completer(completer(false, Vec::new())),
+            ..CommandMode::new(config)
+        }
+    }
+
+    /// Creates a command mode that binds its selection to the recorded keys.
+    pub fn for_key_binding(key_binding: KeyBinding, config: SearchSelectConfig) -> CommandMode {
+        CommandMode {
+            key_binding: Some(key_binding),
+            input: Prompt::default().with_completer(completer(false, Vec::new())),
+            ..CommandMode::new(config)
         }
     }
+
+    /// Completes theme names when setting the theme.
+    pub fn with_themes(mut self, themes: Vec<String>) -> CommandMode {
+        let arguments = self.accepts_arguments();
+        self.input = Prompt::default().with_completer(completer(arguments, themes));
+        self
+    }
+
+    // Commands that take arguments can't be combined
+    // with a selected range of lines or bound to keys.
+    fn accepts_arguments(&self) -> bool {
+        self.line_range.is_none() && self.key_binding.is_none()
+    }
 }
 
 impl fmt::Display for CommandMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "COMMAND")
+        if let Some(ref binding) = self.key_binding {
+            return write!(f, "BIND {}", binding);
+        }
+
+        match self.line_range {
+            Some(ref range) => write!(f, "COMMAND ({} LINES)", range.end() - range.start() + 1),
+            None => write!(f, "COMMAND"),
+        }
+    }
+}
+
+impl CommandMode {
+    /// The line command entered in place of a command name, if any. These
+    /// can't be combined with a selected range of lines or bound to keys.
+    pub fn line_command(&self) -> Option<LineCommand> {
+        if !self.accepts_arguments() {
+            return None;
+        }
+
+        LineCommand::parse(&self.input)
+    }
+
+    /// The command entered alongside its argument, if any.
+    pub fn argument_command(&self) -> Option<ArgumentCommand> {
+        if !self.accepts_arguments() {
+            return None;
+        }
+
+        ArgumentCommand::parse(&self.input)
     }
 }
 
 impl SearchSelectMode<DisplayableCommand> for CommandMode {
     fn search(&mut self) {
-        let commands: Vec<&'static str> = self.commands.keys().map(|k| *k).collect();
+        if let Some(command) = self.argument_command() {
+            self.results = SelectableVec::new(vec![
+                DisplayableCommand { description: command.name, command: None }
+            ]);
+            self.matches.clear();
+            return;
+        }
+
+        let mut commands: Vec<&'static str> = self.commands.keys().map(|k| *k).collect();
+        if self.accepts_arguments() {
+            commands.extend(ArgumentCommand::names());
+        }
 
         // Find the commands we're looking for using the query.
-        let results = fragment::matching::find(
+        let results = fuzzy::find(
             &self.input,
             &commands,
             self.config.max_results
         );
+        self.matches = results.iter().map(|result| result.indices.clone()).collect();
 
         // We don't care about the result objects; we just want
         // the underlying commands. Map the collection to get these.
         self.results = SelectableVec::new(
             results
             .into_iter()
-            .filter_map(|result| {
-                self.commands.get(*result).map(|command| {
-                    DisplayableCommand{
-                      description: *result,
-                      command: *command
-                    }
-                })
+            .map(|result| {
+                DisplayableCommand{
+                  description: *result,
+                  command: self.commands.get(*result).cloned()
+                }
             })
             .collect()
         );
     }
 
-    fn query(&mut self) -> &mut String {
+    fn query(&mut self) -> &mut Prompt {
         &mut self.input
     }
 
@@ -83,6 +169,10 @@ impl SearchSelectMode<DisplayableCommand> for CommandMode {
         self.results.selection()
     }
 
+    fn matched_indices(&self, index: usize) -> Vec<usize> {
+        self.matches.get(index).cloned().unwrap_or_default()
+    }
+
     fn selected_index(&self) -> usize {
         self.results.selected_index()
     }
@@ -98,4 +188,59 @@ impl SearchSelectMode<DisplayableCommand> for CommandMode {
     fn config(&self) -> &SearchSelectConfig {
         &self.config
     }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("run", commands::search_select::accept)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if let Some(command) = self.line_command() {
+            return Some(format!("{} (press enter to run)", command));
+        }
+        if let Some(command) = self.argument_command() {
+            return Some(format!("{} (press enter to run)", command));
+        }
+
+        if self.query().is_empty() {
+            Some(String::from("Enter a search query to start."))
+        } else if self.results().count() == 0 {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
+
+// Builds a completer for command mode's input, which completes command names
+// and, once a command that takes an argument has been typed, its argument.
+fn completer(arguments: bool, themes: Vec<String>) -> impl Fn(&str) -> Vec<String> {
+    move |input| {
+        if arguments {
+            if let Some(command) = ArgumentCommand::parse(input) {
+                let name = &input[..input.len() - command.argument.len()];
+                return command.parameter
+                    .complete(&command.argument, &themes)
+                    .into_iter()
+                    .map(|argument| format!("{}{}", name, argument))
+                    .collect();
+            }
+        }
+
+        let mut candidates: Vec<String> = commands::hash_map()
+            .keys()
+            .filter(|name| name.starts_with(input))
+            .map(|name| name.to_string())
+            .collect();
+        if arguments {
+            // The argument follows the name, so completing the name adds a space.
+            candidates.extend(
+                ArgumentCommand::names()
+                    .filter(|name| name.starts_with(input))
+                    .map(|name| format!("{} ", name))
+            );
+        }
+        candidates.sort();
+
+        candidates
+    }
 }
@@ -2,10 +2,19 @@ use crate::commands::Command;
 
 pub struct ConfirmMode {
     pub command: Command,
+
+    /// Whether confirming (or declining) also decides whether the
+    /// workspace is trusted, before running the command.
+    pub trusting_workspace: bool,
 }
 
 impl ConfirmMode {
     pub fn new(command: Command) -> ConfirmMode {
-        ConfirmMode { command }
+        ConfirmMode { command, trusting_workspace: false }
+    }
+
+    /// Asks whether to trust the workspace, running the command if it is.
+    pub fn trusting_workspace(command: Command) -> ConfirmMode {
+        ConfirmMode { command, trusting_workspace: true }
     }
 }
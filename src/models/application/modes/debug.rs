@@ -0,0 +1,97 @@
+use crate::dap::DebugConfiguration;
+use crate::util::{fuzzy, SelectableVec};
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+
+pub struct DebugMode {
+    insert: bool,
+    input: Prompt,
+    configurations: Vec<DebugConfiguration>,
+    results: SelectableVec<DebugConfiguration>,
+    config: SearchSelectConfig,
+}
+
+impl DebugMode {
+    pub fn new(configurations: Vec<DebugConfiguration>, config: SearchSelectConfig) -> DebugMode {
+        DebugMode {
+            insert: true,
+            input: Prompt::default(),
+            configurations,
+            results: SelectableVec::new(Vec::new()),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for DebugMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DEBUG")
+    }
+}
+
+impl SearchSelectMode<DebugConfiguration> for DebugMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            // Projects rarely have more than a few configurations;
+            // list them before a query is entered.
+            self.configurations
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            fuzzy::find(&self.input, &self.configurations, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<DebugConfiguration> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&DebugConfiguration> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,194 @@
+use fragment::matching::AsStr;
+use crate::lsp::Diagnostic;
+use crate::util::{fuzzy, SelectableVec};
+use std::fmt;
+use std::path::Path;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+use crate::commands::{self, Command};
+use crate::util::preview;
+
+/// A diagnostic, labelled using its workspace-relative path.
+#[derive(Clone)]
+pub struct DiagnosticEntry {
+    pub diagnostic: Diagnostic,
+    label: String,
+}
+
+impl fmt::Display for DiagnosticEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for DiagnosticEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+pub struct DiagnosticMode {
+    insert: bool,
+    input: Prompt,
+    entries: Vec<DiagnosticEntry>,
+    results: SelectableVec<DiagnosticEntry>,
+    preview: Option<String>,
+    config: SearchSelectConfig,
+}
+
+impl DiagnosticMode {
+    pub fn new(diagnostics: Vec<Diagnostic>, root: &Path, config: SearchSelectConfig) -> DiagnosticMode {
+        let entries = diagnostics.into_iter().map(|diagnostic| {
+            let label = format!(
+                "{}:{}: {}: {}",
+                diagnostic.path.strip_prefix(root).unwrap_or(&diagnostic.path).to_string_lossy(),
+                diagnostic.line + 1,
+                diagnostic.severity,
+                diagnostic.message.lines().next().unwrap_or("")
+            );
+
+            DiagnosticEntry { diagnostic, label }
+        }).collect();
+
+        DiagnosticMode {
+            insert: true,
+            input: Prompt::default(),
+            entries,
+            results: SelectableVec::new(Vec::new()),
+            preview: None,
+            config,
+        }
+    }
+
+    // Pairs the selected diagnostic's full message with the lines it refers to.
+    fn update_preview(&mut self) {
+        self.preview = self.results.selection().map(|entry| {
+            let diagnostic = &entry.diagnostic;
+            let context = preview::file_context(&diagnostic.path, diagnostic.line).unwrap_or_default();
+
+            format!("{}\n\n{}", diagnostic.message, context)
+        });
+    }
+}
+
+impl fmt::Display for DiagnosticMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DIAGNOSTICS")
+    }
+}
+
+impl SearchSelectMode<DiagnosticEntry> for DiagnosticMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            // List everything (in path order) before a query is entered.
+            self.entries
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            fuzzy::find(&self.input, &self.entries, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+        self.update_preview();
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<DiagnosticEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&DiagnosticEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+        self.update_preview();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+        self.update_preview();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.entries.is_empty() {
+            Some(String::from("No diagnostics reported."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+
+    fn preview(&self) -> Option<&str> {
+        self.preview.as_deref()
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("jump", commands::search_select::accept)]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DiagnosticMode;
+    use crate::lsp::Diagnostic;
+    use crate::lsp::diagnostic::Severity;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn search_matches_paths_and_messages() {
+        let diagnostic = |path: &str, message: &str| Diagnostic {
+            path: PathBuf::from(path),
+            line: 2,
+            character: 0,
+            end_character: None,
+            severity: Severity::Warning,
+            message: message.to_string(),
+        };
+        let mut mode = DiagnosticMode::new(
+            vec![
+                diagnostic("/amp/src/lib.rs", "unused import"),
+                diagnostic("/amp/src/main.rs", "unused variable\nhelp: prefix it"),
+            ],
+            Path::new("/amp"),
+            SearchSelectConfig::default()
+        );
+
+        mode.search();
+        assert_eq!(mode.results().count(), 2);
+        assert_eq!(mode.selection().unwrap().to_string(), "src/lib.rs:3: warning: unused import");
+
+        mode.query().insert_str("main");
+        mode.search();
+        assert_eq!(mode.results().count(), 1);
+        assert_eq!(mode.selection().unwrap().diagnostic.path, PathBuf::from("/amp/src/main.rs"));
+    }
+}
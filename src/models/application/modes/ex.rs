@@ -0,0 +1,239 @@
+use crate::errors::*;
+use crate::models::application::Prompt;
+use crate::util::prompt::complete_path;
+use std::fmt;
+
+pub const HISTORY_NAMESPACE: &str = "ex";
+
+// Commands whose argument is a path, which is completed with tab.
+const PATH_COMMANDS: [&str; 4] = ["e", "edit", "w", "write"];
+
+/// Prompts for a vim-style command line (e.g. `w`, `e path`, `%s/foo/bar/g`, or
+/// `42`), which is parsed into an `ExCommand` and run using amp's own commands.
+pub struct ExMode {
+    pub input: Prompt,
+}
+
+impl ExMode {
+    pub fn new() -> ExMode {
+        ExMode {
+            input: Prompt::default()
+                .with_history(HISTORY_NAMESPACE)
+                .with_completer(complete),
+        }
+    }
+}
+
+impl fmt::Display for ExMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "EX")
+    }
+}
+
+/// A parsed ex command line.
+#[derive(Debug, PartialEq)]
+pub enum ExCommand {
+    /// Saves the current buffer, optionally to a new path (`w`, `w path`).
+    Write(Option<String>),
+
+    /// Saves and closes the current buffer (`wq`, `x`).
+    WriteQuit,
+
+    /// Closes the current buffer, discarding its changes if forced (`q`, `q!`).
+    Quit { force: bool },
+
+    /// Exits the application (`qa`, `qa!`).
+    QuitAll,
+
+    /// Opens a path, or reloads the current buffer if there isn't one (`e path`, `e`).
+    Edit(Option<String>),
+
+    /// Switches to the most recently used buffer other than the current one (`e #`, `b#`).
+    Alternate,
+
+    /// Moves the cursor to a (one-based) line number (`42`).
+    Line(usize),
+
+    /// Sets a preference for the session (`set tab_width=4`).
+    Set(String),
+
+    /// Sets a preference for the current buffer alone (`setlocal tab_width=4`),
+    /// or lists those already set for it (`setlocal`).
+    SetLocal(Option<String>),
+
+    /// Replaces pattern matches on the current line, or
+    /// the whole buffer (`s/foo/bar/`, `%s/foo/bar/g`).
+    Substitute(Substitution),
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Substitution {
+    pub whole_buffer: bool,
+
+    /// A regular expression, whose replacement can reference its capture groups.
+    pub pattern: String,
+    pub replacement: String,
+
+    /// Whether every match on a line is replaced, rather than only the first.
+    pub global: bool,
+}
+
+impl ExCommand {
+    pub fn parse(input: &str) -> Result<ExCommand> {
+        let input = input.trim();
+        if input.is_empty() {
+            bail!("Please enter a command");
+        }
+        if input.chars().all(|c| c.is_ascii_digit()) {
+            let line = input.parse().chain_err(|| "Invalid line number")?;
+
+            return Ok(ExCommand::Line(line));
+        }
+        if let Some(substitution) = input.strip_prefix("%s") {
+            return parse_substitution(substitution, true);
+        }
+
+        let mut parts = input.splitn(2, char::is_whitespace);
+        let name = parts.next().unwrap_or("");
+        let argument = parts.next().map(str::trim).filter(|argument| !argument.is_empty());
+
+        let command = match (name, argument) {
+            ("w", path) | ("write", path) => ExCommand::Write(path.map(String::from)),
+            ("wq", None) | ("x", None) => ExCommand::WriteQuit,
+            ("q", None) | ("quit", None) => ExCommand::Quit { force: false },
+            ("q!", None) | ("quit!", None) => ExCommand::Quit { force: true },
+            ("qa", None) | ("qa!", None) => ExCommand::QuitAll,
+            ("e", Some("#")) | ("edit", Some("#")) | ("b", Some("#")) | ("b#", None) => ExCommand::Alternate,
+            ("e", path) | ("edit", path) => ExCommand::Edit(path.map(String::from)),
+            ("set", Some(argument)) => ExCommand::Set(argument.to_string()),
+            ("set", None) => bail!("Usage: set <setting>=<value>"),
+            ("setlocal", argument) => ExCommand::SetLocal(argument.map(String::from)),
+            _ => match name.strip_prefix('s') {
+                Some(substitution) if substitution.starts_with(is_delimiter) => {
+                    return parse_substitution(&input[1..], false);
+                }
+                _ => bail!(format!("Unknown command: {}", input)),
+            },
+        };
+
+        Ok(command)
+    }
+}
+
+// Parses the "/pattern/replacement/flags" following a substitute command,
+// delimited by its first character. Delimiters can be escaped with a backslash.
+fn parse_substitution(input: &str, whole_buffer: bool) -> Result<ExCommand> {
+    let mut chars = input.chars();
+    let delimiter = chars.next().filter(|&c| is_delimiter(c)).ok_or("Usage: s/pattern/replacement/flags")?;
+    let mut fields = vec![String::new()];
+
+    while let Some(c) = chars.next() {
+        if c == delimiter {
+            fields.push(String::new());
+            continue;
+        }
+        let field = fields.last_mut().unwrap();
+        if c == '\\' {
+            match chars.next() {
+                Some(next) if next == delimiter => field.push(next),
+                Some(next) => {
+                    field.push(c);
+                    field.push(next);
+                }
+                None => field.push(c),
+            }
+        } else {
+            field.push(c);
+        }
+    }
+    if fields.len() > 3 {
+        bail!("Too many delimiters in substitution");
+    }
+
+    let mut fields = fields.into_iter();
+    let pattern = fields.next().unwrap_or_default();
+    let replacement = fields.next().unwrap_or_default();
+    let flags = fields.next().unwrap_or_default();
+    if pattern.is_empty() {
+        bail!(SEARCH_QUERY_MISSING);
+    }
+    if let Some(flag) = flags.chars().find(|&flag| flag != 'g') {
+        bail!(format!("Unsupported substitution flag: {}", flag));
+    }
+
+    Ok(ExCommand::Substitute(Substitution {
+        whole_buffer,
+        pattern,
+        replacement,
+        global: !flags.is_empty(),
+    }))
+}
+
+fn is_delimiter(c: char) -> bool {
+    !c.is_alphanumeric() && !c.is_whitespace() && c != '\\'
+}
+
+// Completes the path argument of commands that take one.
+fn complete(input: &str) -> Vec<String> {
+    let mut parts = input.splitn(2, ' ');
+    let name = parts.next().unwrap_or("");
+    let argument = match parts.next() {
+        Some(argument) if PATH_COMMANDS.contains(&name) => argument,
+        _ => return Vec::new(),
+    };
+
+    complete_path(argument)
+        .into_iter()
+        .map(|path| format!("{} {}", name, path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExCommand, Substitution};
+
+    #[test]
+    fn parse_maps_vim_commands() {
+        assert_eq!(ExCommand::parse("w").unwrap(), ExCommand::Write(None));
+        assert_eq!(ExCommand::parse("w notes.md").unwrap(), ExCommand::Write(Some(String::from("notes.md"))));
+        assert_eq!(ExCommand::parse("x").unwrap(), ExCommand::WriteQuit);
+        assert_eq!(ExCommand::parse("q").unwrap(), ExCommand::Quit { force: false });
+        assert_eq!(ExCommand::parse(" q! ").unwrap(), ExCommand::Quit { force: true });
+        assert_eq!(ExCommand::parse("e src/main.rs").unwrap(), ExCommand::Edit(Some(String::from("src/main.rs"))));
+        assert_eq!(ExCommand::parse("e").unwrap(), ExCommand::Edit(None));
+        assert_eq!(ExCommand::parse("e #").unwrap(), ExCommand::Alternate);
+        assert_eq!(ExCommand::parse("b#").unwrap(), ExCommand::Alternate);
+        assert_eq!(ExCommand::parse("42").unwrap(), ExCommand::Line(42));
+        assert_eq!(ExCommand::parse("set tab_width=4").unwrap(), ExCommand::Set(String::from("tab_width=4")));
+        assert_eq!(ExCommand::parse("setlocal syntax=rust").unwrap(), ExCommand::SetLocal(Some(String::from("syntax=rust"))));
+        assert_eq!(ExCommand::parse("setlocal").unwrap(), ExCommand::SetLocal(None));
+        assert!(ExCommand::parse("").is_err());
+        assert!(ExCommand::parse("wq now").is_err());
+        assert!(ExCommand::parse("frobnicate").is_err());
+    }
+
+    #[test]
+    fn parse_reads_substitutions_with_escaped_delimiters() {
+        assert_eq!(ExCommand::parse("%s/foo/bar/g").unwrap(), ExCommand::Substitute(Substitution {
+            whole_buffer: true,
+            pattern: String::from("foo"),
+            replacement: String::from("bar"),
+            global: true,
+        }));
+        assert_eq!(ExCommand::parse(r"s#a\#b#$1\u#").unwrap(), ExCommand::Substitute(Substitution {
+            whole_buffer: false,
+            pattern: String::from("a#b"),
+            replacement: String::from(r"$1\u"),
+            global: false,
+        }));
+        assert_eq!(ExCommand::parse("s/foo").unwrap(), ExCommand::Substitute(Substitution {
+            whole_buffer: false,
+            pattern: String::from("foo"),
+            replacement: String::new(),
+            global: false,
+        }));
+        assert!(ExCommand::parse("%s//bar/").is_err());
+        assert!(ExCommand::parse("%s/foo/bar/i").is_err());
+        assert!(ExCommand::parse("%s/a/b/g/c").is_err());
+    }
+}
@@ -0,0 +1,39 @@
+use crate::models::application::Prompt;
+use std::fmt;
+
+/// A change being made to the selected file tree entry, once it's been
+/// named (when creating or renaming) or confirmed (when deleting).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FileOperation {
+    Create,
+    Rename,
+    Delete,
+}
+
+/// Focuses the file tree sidebar, to browse and manage the workspace's files.
+pub struct FileTreeMode {
+    pub operation: Option<FileOperation>,
+
+    /// The name of the file being created, or the selected entry's new name.
+    pub input: Prompt,
+}
+
+impl FileTreeMode {
+    pub fn new() -> FileTreeMode {
+        FileTreeMode {
+            operation: None,
+            input: Prompt::default(),
+        }
+    }
+
+    /// Whether the operation's input is being entered.
+    pub fn entering_input(&self) -> bool {
+        matches!(self.operation, Some(FileOperation::Create) | Some(FileOperation::Rename))
+    }
+}
+
+impl fmt::Display for FileTreeMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FILES")
+    }
+}
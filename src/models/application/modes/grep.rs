@@ -0,0 +1,423 @@
+use git2::Repository;
+use regex::{self, Regex};
+use crate::util::SelectableVec;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::slice::Iter;
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::thread;
+use crate::models::application::Event;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+use crate::commands::{self, Command};
+use crate::util::{self, preview};
+use unicode_segmentation::UnicodeSegmentation;
+
+// Files containing a null byte within this many bytes are considered binary.
+const BINARY_CHECK_LENGTH: usize = 1024;
+
+// Previews longer than this (in bytes) are truncated.
+const MAX_PREVIEW_LENGTH: usize = 200;
+
+/// A line matching a grep query.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GrepMatch {
+    /// The matching file's path, relative to the searched directory.
+    pub path: PathBuf,
+    pub line: usize,
+
+    /// The start of the (first) match on the line, in graphemes.
+    pub offset: usize,
+    pub preview: String,
+}
+
+impl fmt::Display for GrepMatch {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.path.to_string_lossy(), self.line + 1, self.preview)
+    }
+}
+
+struct GrepQuery {
+    generation: usize,
+    pattern: String,
+    regex: bool,
+}
+
+/// Searches the contents of all (non-ignored) files in a directory. Searches
+/// are run by a background worker, which streams matches back as events, and
+/// abandons its current search as soon as a new query is entered.
+pub struct GrepMode {
+    insert: bool,
+    input: Prompt,
+    regex: bool,
+    path: PathBuf,
+    generation: usize,
+    searching: bool,
+    error: Option<String>,
+    results: SelectableVec<GrepMatch>,
+    preview: Option<String>,
+    queries: Sender<GrepQuery>,
+    config: SearchSelectConfig,
+}
+
+impl GrepMode {
+    pub fn new(path: PathBuf, regex: bool, events: Sender<Event>, config: SearchSelectConfig) -> GrepMode {
+        let (queries, worker_queries) = mpsc::channel();
+        let worker_path = path.clone();
+        let max_results = config.max_results;
+        thread::spawn(move || work(&worker_path, &worker_queries, &events, max_results));
+
+        GrepMode {
+            insert: true,
+            input: Prompt::default(),
+            regex,
+            path,
+            generation: 0,
+            searching: false,
+            error: None,
+            results: SelectableVec::new(Vec::new()),
+            preview: None,
+            queries,
+            config,
+        }
+    }
+
+    /// Adds matches streamed from the background worker, ignoring
+    /// those belonging to searches that have since been replaced.
+    pub fn add_results(&mut self, generation: usize, matches: Vec<GrepMatch>, complete: bool) {
+        if generation != self.generation {
+            return;
+        }
+
+        let selected_index = self.results.selected_index();
+        let mut results: Vec<GrepMatch> = self.results.iter().cloned().collect();
+        results.extend(matches);
+        results.truncate(self.config.max_results);
+        self.results = SelectableVec::new(results);
+        self.results.select(selected_index);
+        self.searching = !complete;
+        if self.preview.is_none() {
+            self.update_preview();
+        }
+    }
+
+    // Reads the lines surrounding the selected match from disk,
+    // so that they're not re-read every time the mode is rendered.
+    fn update_preview(&mut self) {
+        self.preview = self.results.selection().and_then(|selection| {
+            preview::file_context(&self.path.join(&selection.path), selection.line)
+        });
+    }
+}
+
+impl fmt::Display for GrepMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.regex {
+            write!(f, "GREP (REGEX)")
+        } else {
+            write!(f, "GREP")
+        }
+    }
+}
+
+impl SearchSelectMode<GrepMatch> for GrepMode {
+    fn search(&mut self) {
+        self.generation += 1;
+        self.results = SelectableVec::new(Vec::new());
+        self.preview = None;
+        self.searching = false;
+        self.error = None;
+
+        if self.input.is_empty() {
+            return;
+        }
+        if self.regex {
+            if let Err(error) = Regex::new(&self.input) {
+                self.error = Some(format!("Invalid pattern: {}", error));
+                return;
+            }
+        }
+
+        self.searching = self.queries.send(GrepQuery {
+            generation: self.generation,
+            pattern: self.input.to_string(),
+            regex: self.regex,
+        }).is_ok();
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<GrepMatch> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&GrepMatch> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+        self.update_preview();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+        self.update_preview();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if let Some(ref error) = self.error {
+            Some(error.clone())
+        } else if self.input.is_empty() {
+            Some(String::from("Enter a search query to start."))
+        } else if self.results.is_empty() && self.searching {
+            Some(format!("Searching {}", self.path.to_string_lossy()))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+
+    fn preview(&self) -> Option<&str> {
+        self.preview.as_deref()
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("open", commands::search_select::accept)]
+    }
+}
+
+// Why a search ended before visiting every file.
+enum Interruption {
+    Replaced(GrepQuery),
+    Closed,
+}
+
+// Runs queries until the mode (and with it, the query sender) is dropped.
+fn work(root: &Path, queries: &Receiver<GrepQuery>, events: &Sender<Event>, max_results: usize) {
+    let repo = Repository::discover(root).ok();
+    let mut query = match queries.recv() {
+        Ok(query) => query,
+        Err(_) => return,
+    };
+
+    loop {
+        query = match search(root, repo.as_ref(), &query, queries, events, max_results) {
+            Some(Interruption::Replaced(query)) => query,
+            Some(Interruption::Closed) => return,
+            None => match queries.recv() {
+                Ok(query) => query,
+                Err(_) => return,
+            },
+        };
+    }
+}
+
+fn search(root: &Path, repo: Option<&Repository>, query: &GrepQuery, queries: &Receiver<GrepQuery>, events: &Sender<Event>, max_results: usize) -> Option<Interruption> {
+    let pattern = if query.regex {
+        query.pattern.clone()
+    } else {
+        regex::quote(&query.pattern)
+    };
+    let regex = match Regex::new(&pattern) {
+        Ok(regex) => regex,
+        Err(_) => return complete(query, events),
+    };
+
+    let mut remaining = max_results;
+    let mut directories = vec![root.to_path_buf()];
+    while let Some(directory) = directories.pop() {
+        let mut entries: Vec<PathBuf> = match fs::read_dir(&directory) {
+            Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+            Err(_) => continue,
+        };
+        entries.sort();
+
+        // Search this directory's files before descending into
+        // its subdirectories, so that shallower matches come first.
+        let mut subdirectories = Vec::new();
+        for path in entries {
+            if path.file_name().map(|name| name == ".git") == Some(true) || util::git_ignored(repo, &path) {
+                continue;
+            }
+
+            // Don't follow symlinked directories, to avoid cycles.
+            let file_type = match fs::symlink_metadata(&path) {
+                Ok(metadata) => metadata.file_type(),
+                Err(_) => continue,
+            };
+            if file_type.is_dir() {
+                subdirectories.push(path);
+                continue;
+            }
+            if file_type.is_symlink() && !path.is_file() {
+                continue;
+            }
+
+            match queries.try_recv() {
+                Ok(mut replacement) => {
+                    // Skip straight to the latest query.
+                    while let Ok(latest) = queries.try_recv() {
+                        replacement = latest;
+                    }
+                    return Some(Interruption::Replaced(replacement));
+                }
+                Err(TryRecvError::Disconnected) => return Some(Interruption::Closed),
+                Err(TryRecvError::Empty) => (),
+            }
+
+            let mut matches = search_file(&path, &regex, remaining);
+            if matches.is_empty() {
+                continue;
+            }
+            for result in matches.iter_mut() {
+                result.path = path.strip_prefix(root).unwrap_or(&path).to_path_buf();
+            }
+            remaining -= matches.len();
+            let _ = events.send(Event::GrepResults {
+                generation: query.generation,
+                matches,
+                complete: remaining == 0,
+            });
+            if remaining == 0 {
+                return None;
+            }
+        }
+        directories.extend(subdirectories.into_iter().rev());
+    }
+
+    complete(query, events)
+}
+
+fn complete(query: &GrepQuery, events: &Sender<Event>) -> Option<Interruption> {
+    let _ = events.send(Event::GrepResults {
+        generation: query.generation,
+        matches: Vec::new(),
+        complete: true,
+    });
+
+    None
+}
+
+// Finds up to the specified number of matching lines, skipping
+// binary files and those that can't be read as UTF-8.
+fn search_file(path: &Path, regex: &Regex, max_results: usize) -> Vec<GrepMatch> {
+    let content = match fs::read(path) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+    if content.iter().take(BINARY_CHECK_LENGTH).any(|&byte| byte == 0) {
+        return Vec::new();
+    }
+    let content = match String::from_utf8(content) {
+        Ok(content) => content,
+        Err(_) => return Vec::new(),
+    };
+
+    content
+        .lines()
+        .enumerate()
+        .filter_map(|(line, data)| {
+            let (start, _) = regex.find(data)?;
+            let mut preview = data.trim().to_string();
+            if preview.len() > MAX_PREVIEW_LENGTH {
+                let end = (0..=MAX_PREVIEW_LENGTH).rev().find(|&i| preview.is_char_boundary(i)).unwrap_or(0);
+                preview.truncate(end);
+            }
+
+            Some(GrepMatch {
+                path: path.to_path_buf(),
+                line,
+                offset: data[..start].graphemes(true).count(),
+                preview,
+            })
+        })
+        .take(max_results)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{GrepMatch, GrepMode};
+    use crate::models::application::Event;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use std::env;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    fn grep_match(line: usize) -> GrepMatch {
+        GrepMatch { path: PathBuf::from("src/main.rs"), line, offset: 0, preview: String::from("fn main() {") }
+    }
+
+    #[test]
+    fn search_streams_literal_matches_from_the_background_worker() {
+        let (events, receiver) = mpsc::channel();
+        let mut mode = GrepMode::new(env::current_dir().unwrap(), false, events, SearchSelectConfig::default());
+        mode.query().insert_str("[dependencies]");
+        mode.search();
+        assert_eq!(mode.message(), Some(format!("Searching {}", env::current_dir().unwrap().to_string_lossy())));
+
+        match receiver.recv_timeout(Duration::from_secs(10)).unwrap() {
+            Event::GrepResults { generation, matches, complete } => {
+                assert_eq!(matches[0].path, PathBuf::from("Cargo.toml"));
+                assert_eq!(matches[0].preview, "[dependencies]");
+                mode.add_results(generation, matches, complete);
+            }
+            _ => panic!("expected grep results"),
+        }
+        assert_eq!(mode.message(), None);
+    }
+
+    #[test]
+    fn search_reports_invalid_regular_expressions() {
+        let (events, _receiver) = mpsc::channel();
+        let mut mode = GrepMode::new(env::current_dir().unwrap(), true, events, SearchSelectConfig::default());
+        mode.query().insert_str("fn (");
+        mode.search();
+
+        assert!(mode.message().unwrap().starts_with("Invalid pattern"));
+    }
+
+    #[test]
+    fn add_results_ignores_results_from_replaced_searches() {
+        let (events, _receiver) = mpsc::channel();
+        let mut mode = GrepMode::new(env::current_dir().unwrap(), false, events, SearchSelectConfig::default());
+        mode.query().insert_str("amp");
+        mode.search();
+        mode.search();
+
+        mode.add_results(1, vec![grep_match(0)], true);
+        assert_eq!(mode.results().count(), 0);
+        mode.add_results(2, vec![grep_match(0), grep_match(1)], false);
+        mode.select_next();
+        mode.add_results(2, vec![grep_match(2)], true);
+        assert_eq!(mode.results().count(), 3);
+        assert_eq!(mode.selected_index(), 1);
+    }
+
+    #[test]
+    fn display_includes_path_line_number_and_preview() {
+        assert_eq!(grep_match(4).to_string(), "src/main.rs:5: fn main() {");
+    }
+}
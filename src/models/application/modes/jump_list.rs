@@ -0,0 +1,193 @@
+use fragment::matching::AsStr;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::{JumpList, Prompt};
+use crate::commands::{self, Command};
+use crate::util::{fuzzy, SelectableVec};
+use std::fmt;
+use std::path::Path;
+use std::slice::Iter;
+
+/// A location in the jump list.
+#[derive(Clone, Debug, PartialEq)]
+pub struct JumpListEntry {
+    /// The jump's index in the list.
+    pub index: usize,
+    label: String,
+}
+
+impl fmt::Display for JumpListEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for JumpListEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Lists the locations in the jump list, most recent first, with the one
+/// that stepping back would return to selected. Selecting one jumps to it.
+pub struct JumpListMode {
+    insert: bool,
+    input: Prompt,
+    entries: Vec<JumpListEntry>,
+    results: SelectableVec<JumpListEntry>,
+    config: SearchSelectConfig,
+
+    /// The index of the entry stepping back would return to.
+    previous: usize,
+}
+
+impl JumpListMode {
+    /// Labels jumps with their paths, relative to the workspace.
+    pub fn new(jump_list: &JumpList, workspace_path: &Path, config: SearchSelectConfig) -> JumpListMode {
+        let current = jump_list.current();
+        let entries: Vec<JumpListEntry> = jump_list
+            .jumps()
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, jump)| {
+                let path = jump.path
+                    .as_ref()
+                    .map(|path| path.strip_prefix(workspace_path).unwrap_or(path).to_string_lossy().into_owned())
+                    .unwrap_or_else(|| String::from("untitled"));
+
+                JumpListEntry {
+                    index,
+                    label: format!(
+                        "{} {}:{}",
+                        if Some(index) == current { "●" } else { "○" },
+                        path,
+                        jump.position.line + 1
+                    ),
+                }
+            })
+            .collect();
+        let previous = current
+            .unwrap_or_else(|| jump_list.jumps().len())
+            .checked_sub(1)
+            .and_then(|index| entries.iter().position(|entry| entry.index == index))
+            .unwrap_or(0);
+
+        JumpListMode {
+            insert: false,
+            input: Prompt::default(),
+            entries,
+            results: SelectableVec::new(Vec::new()),
+            config,
+            previous,
+        }
+    }
+}
+
+impl fmt::Display for JumpListMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "JUMP LIST")
+    }
+}
+
+impl SearchSelectMode<JumpListEntry> for JumpListMode {
+    fn search(&mut self) {
+        if self.input.is_empty() {
+            let first = self.previous.saturating_sub(self.config.max_results / 2);
+            let first = first.min(self.entries.len().saturating_sub(self.config.max_results));
+            let results = self.entries
+                .iter()
+                .skip(first)
+                .take(self.config.max_results)
+                .cloned()
+                .collect();
+            self.results = SelectableVec::new(results);
+            self.results.select(self.previous - first);
+        } else {
+            let results = fuzzy::find(&self.input, &self.entries, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect();
+            self.results = SelectableVec::new(results);
+        }
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<JumpListEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&JumpListEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("jump", commands::search_select::accept)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.entries.is_empty() {
+            Some(String::from("No jumps have been made yet."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching jumps found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JumpListMode;
+    use crate::models::application::JumpList;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn search_lists_recent_jumps_first_with_the_previous_one_selected() {
+        let mut jump_list = JumpList::default();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.path = Some(PathBuf::from("/amp/src/main.rs"));
+        buffer.insert("amp\neditor\n");
+        jump_list.record(&buffer);
+        buffer.cursor.move_to(Position { line: 1, offset: 0 });
+        jump_list.record(&buffer);
+        buffer.cursor.move_to(Position { line: 2, offset: 0 });
+        jump_list.back(&buffer);
+
+        let mut mode = JumpListMode::new(&jump_list, Path::new("/amp"), SearchSelectConfig::default());
+        mode.search();
+        let labels: Vec<String> = mode.results().map(|entry| entry.to_string()).collect();
+        assert_eq!(labels, vec!["○ src/main.rs:3", "● src/main.rs:2", "○ src/main.rs:1"]);
+        assert_eq!(mode.selection().map(|entry| entry.index), Some(0));
+    }
+}
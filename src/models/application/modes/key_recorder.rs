@@ -0,0 +1,42 @@
+use crate::input::KeySequence;
+use smallvec::SmallVec;
+use std::fmt;
+
+/// A key sequence to bind in a particular mode.
+#[derive(Clone, Debug, PartialEq)]
+pub struct KeyBinding {
+    /// The name of the mode's keymap (e.g. "normal").
+    pub mode: String,
+    pub keys: KeySequence,
+}
+
+impl fmt::Display for KeyBinding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let keys = self.keys.iter().map(|key| key.to_string()).collect::<Vec<_>>();
+
+        write!(f, "{} ({})", keys.join(" "), self.mode)
+    }
+}
+
+/// Captures key presses (rather than running their commands) until they
+/// stop, after which the recorded sequence is bound to a chosen command.
+pub struct KeyRecorderMode {
+    pub binding: KeyBinding,
+}
+
+impl KeyRecorderMode {
+    pub fn new(mode: &str) -> KeyRecorderMode {
+        KeyRecorderMode {
+            binding: KeyBinding {
+                mode: mode.to_string(),
+                keys: SmallVec::new(),
+            },
+        }
+    }
+}
+
+impl fmt::Display for KeyRecorderMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RECORD KEYS")
+    }
+}
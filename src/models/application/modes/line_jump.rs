@@ -1,3 +1,7 @@
+use crate::errors::*;
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
+
 #[derive(Default)]
 pub struct LineJumpMode {
     pub input: String,
@@ -8,3 +12,102 @@ impl LineJumpMode {
         LineJumpMode::default()
     }
 }
+
+/// Where line jump mode moves the cursor.
+#[derive(Debug, PartialEq)]
+pub enum LineJumpTarget {
+    /// A one-based line, and optionally a one-based column (`42`, `42:7`).
+    Line { line: usize, column: Option<usize> },
+
+    /// A number of lines below or above the cursor (`+5`, `-5`).
+    Offset(isize),
+
+    /// A percentage of the way through the buffer (`50%`).
+    Percent(usize),
+}
+
+impl LineJumpTarget {
+    pub fn parse(input: &str) -> Result<LineJumpTarget> {
+        let input = input.trim();
+        let number = |value: &str| {
+            value
+                .parse::<usize>()
+                .chain_err(|| format!("Couldn't parse a line number from \"{}\".", input))
+        };
+
+        if let Some(percent) = input.strip_suffix('%') {
+            return Ok(LineJumpTarget::Percent(number(percent)?));
+        }
+        if let Some(offset) = input.strip_prefix('+') {
+            return Ok(LineJumpTarget::Offset(number(offset)? as isize));
+        }
+        if let Some(offset) = input.strip_prefix('-') {
+            return Ok(LineJumpTarget::Offset(-(number(offset)? as isize)));
+        }
+
+        let mut parts = input.splitn(2, ':');
+        let line = number(parts.next().unwrap_or(""))?;
+        let column = parts.next().map(number).transpose()?;
+
+        Ok(LineJumpTarget::Line { line, column })
+    }
+
+    /// The (zero-based) position the target refers to, clamped to the
+    /// buffer's content. The cursor keeps its offset where possible, unless
+    /// a column is specified. Line zero doesn't refer to anything.
+    pub fn position(&self, cursor: Position, data: &str) -> Option<Position> {
+        let lines: Vec<&str> = data.lines().collect();
+        let line = match *self {
+            LineJumpTarget::Line { line: 0, .. } => return None,
+            LineJumpTarget::Line { line, .. } => line - 1,
+            LineJumpTarget::Offset(offset) if offset < 0 => cursor.line.saturating_sub((-offset) as usize),
+            LineJumpTarget::Offset(offset) => cursor.line + offset as usize,
+            LineJumpTarget::Percent(percent) => {
+                // Round up, so that every percentage refers to a line.
+                ((lines.len() * percent.min(100) + 99) / 100).saturating_sub(1)
+            }
+        }.min(lines.len().saturating_sub(1));
+
+        let line_length = lines.get(line).map(|content| content.graphemes(true).count()).unwrap_or(0);
+        let offset = match *self {
+            LineJumpTarget::Line { column: Some(column), .. } => column.saturating_sub(1),
+            _ => cursor.offset,
+        }.min(line_length);
+
+        Some(Position { line, offset })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineJumpTarget;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn parse_understands_lines_columns_offsets_and_percentages() {
+        assert_eq!(LineJumpTarget::parse("42").unwrap(), LineJumpTarget::Line { line: 42, column: None });
+        assert_eq!(LineJumpTarget::parse("42:7").unwrap(), LineJumpTarget::Line { line: 42, column: Some(7) });
+        assert_eq!(LineJumpTarget::parse("+5").unwrap(), LineJumpTarget::Offset(5));
+        assert_eq!(LineJumpTarget::parse("-5").unwrap(), LineJumpTarget::Offset(-5));
+        assert_eq!(LineJumpTarget::parse("50%").unwrap(), LineJumpTarget::Percent(50));
+        assert!(LineJumpTarget::parse("").is_err());
+        assert!(LineJumpTarget::parse("42:").is_err());
+        assert!(LineJumpTarget::parse("amp").is_err());
+    }
+
+    #[test]
+    fn position_clamps_targets_to_the_content() {
+        let data = "amp\neditor\ntext\nbuffer";
+        let cursor = Position { line: 1, offset: 5 };
+
+        assert_eq!(LineJumpTarget::Line { line: 3, column: None }.position(cursor, data), Some(Position { line: 2, offset: 4 }));
+        assert_eq!(LineJumpTarget::Line { line: 2, column: Some(3) }.position(cursor, data), Some(Position { line: 1, offset: 2 }));
+        assert_eq!(LineJumpTarget::Line { line: 9, column: Some(9) }.position(cursor, data), Some(Position { line: 3, offset: 6 }));
+        assert_eq!(LineJumpTarget::Line { line: 0, column: None }.position(cursor, data), None);
+        assert_eq!(LineJumpTarget::Offset(2).position(cursor, data), Some(Position { line: 3, offset: 5 }));
+        assert_eq!(LineJumpTarget::Offset(-5).position(cursor, data), Some(Position { line: 0, offset: 3 }));
+        assert_eq!(LineJumpTarget::Percent(50).position(cursor, data), Some(Position { line: 1, offset: 5 }));
+        assert_eq!(LineJumpTarget::Percent(100).position(cursor, data), Some(Position { line: 3, offset: 5 }));
+        assert_eq!(LineJumpTarget::Percent(0).position(cursor, data), Some(Position { line: 0, offset: 3 }));
+    }
+}
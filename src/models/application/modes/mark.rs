@@ -0,0 +1,25 @@
+use std::fmt;
+
+/// What's done with the mark named by the next key press.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MarkAction {
+    Set,
+    Jump,
+}
+
+/// Waits for the name of a mark to set, or to jump to.
+pub struct MarkMode {
+    pub action: MarkAction,
+}
+
+impl MarkMode {
+    pub fn new(action: MarkAction) -> MarkMode {
+        MarkMode { action }
+    }
+}
+
+impl fmt::Display for MarkMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MARK")
+    }
+}
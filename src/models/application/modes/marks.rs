@@ -0,0 +1,182 @@
+use fragment::matching::AsStr;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::{Marks, Prompt};
+use crate::commands::{self, Command};
+use crate::util::{fuzzy, SelectableVec};
+use std::fmt;
+use std::path::Path;
+use std::slice::Iter;
+
+// How many lines either side of a mark are previewed.
+const CONTEXT_LINES: usize = 5;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MarkEntry {
+    pub name: char,
+
+    /// The mark name, followed by its location and a preview of its line.
+    label: String,
+
+    /// The lines surrounding the mark, if its buffer is open.
+    context: Option<String>,
+}
+
+impl fmt::Display for MarkEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for MarkEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Lists the marks that have been set, to pick one to jump to.
+pub struct MarksMode {
+    insert: bool,
+    input: Prompt,
+    marks: Vec<MarkEntry>,
+    results: SelectableVec<MarkEntry>,
+    config: SearchSelectConfig,
+}
+
+impl MarksMode {
+    /// Labels marks with their paths, relative to the workspace.
+    pub fn new(marks: &Marks, workspace_path: &Path, config: SearchSelectConfig) -> MarksMode {
+        let entries = marks
+            .iter()
+            .map(|mark| {
+                let path = mark.location.path
+                    .as_ref()
+                    .map(|path| path.strip_prefix(workspace_path).unwrap_or(path).to_string_lossy().into_owned())
+                    .unwrap_or_else(|| String::from("untitled"));
+
+                MarkEntry {
+                    name: mark.name,
+                    label: format!("{}  {}:{}  {}", mark.name, path, mark.location.position.line + 1, mark.preview),
+                    context: marks.context(mark, CONTEXT_LINES),
+                }
+            })
+            .collect();
+
+        MarksMode {
+            insert: true,
+            input: Prompt::default(),
+            marks: entries,
+            results: SelectableVec::new(Vec::new()),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for MarksMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MARKS")
+    }
+}
+
+impl SearchSelectMode<MarkEntry> for MarksMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            self.marks.iter().take(self.config.max_results).cloned().collect()
+        } else {
+            fuzzy::find(&self.input, &self.marks, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<MarkEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&MarkEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn preview(&self) -> Option<&str> {
+        self.selection().and_then(|entry| entry.context.as_deref())
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("jump", commands::search_select::accept)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.marks.is_empty() {
+            Some(String::from("No marks have been set."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching marks found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MarksMode;
+    use crate::models::application::{Marks, Preferences};
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use crate::view::View;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::cell::RefCell;
+    use std::path::{Path, PathBuf};
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    #[test]
+    fn search_lists_marks_by_name_with_their_locations_and_lines() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let mut view = View::new(preferences, mpsc::channel().0).unwrap();
+        let mut marks = Marks::default();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.path = Some(PathBuf::from("/amp/src/main.rs"));
+        buffer.insert("fn main() {\n    run();\n}\n");
+        buffer.cursor.move_to(Position { line: 1, offset: 4 });
+        marks.set('r', &buffer, &mut view);
+        buffer.cursor.move_to(Position { line: 0, offset: 0 });
+        marks.set('m', &buffer, &mut view);
+
+        let mut mode = MarksMode::new(&marks, Path::new("/amp"), SearchSelectConfig::default());
+        mode.search();
+        let labels: Vec<String> = mode.results().map(|entry| entry.to_string()).collect();
+        assert_eq!(labels, vec!["m  src/main.rs:1  fn main() {", "r  src/main.rs:2  run();"]);
+        assert_eq!(mode.preview(), Some("fn main() {\n    run();\n}"));
+    }
+}
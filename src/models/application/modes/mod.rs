@@ -1,25 +1,77 @@
+mod blame;
+mod branch;
+mod buffer;
+mod calculator;
+mod close_pattern;
 mod confirm;
 mod command;
+mod debug;
+mod diagnostic;
+pub mod ex;
+mod file_tree;
+mod grep;
 pub mod jump;
+mod jump_list;
+mod key_recorder;
 mod line_jump;
+mod mark;
+mod marks;
+mod number;
+pub mod passphrase;
 pub mod open;
 mod path;
-mod search;
+mod register;
+mod registers;
+mod rename;
+mod replace;
+pub mod search;
 mod search_select;
 mod select;
+mod select_block;
 mod select_line;
+mod stash;
 mod symbol_jump;
+mod tags;
+mod task;
 mod theme;
+mod transform;
+mod undo_tree;
 
+pub use self::blame::BlameMode;
+pub use self::branch::BranchMode;
+pub use self::buffer::{BufferMode, PIN_GLYPH};
+pub use self::calculator::CalculatorMode;
+pub use self::close_pattern::ClosePatternMode;
 pub use self::confirm::ConfirmMode;
-pub use self::command::CommandMode;
+pub use self::command::{CommandMode, LineCommand, LineOperation};
+pub use self::debug::DebugMode;
+pub use self::diagnostic::DiagnosticMode;
+pub use self::ex::{ExCommand, ExMode};
+pub use self::file_tree::{FileOperation, FileTreeMode};
+pub use self::grep::{GrepMatch, GrepMode};
 pub use self::jump::JumpMode;
-pub use self::line_jump::LineJumpMode;
+pub use self::jump_list::JumpListMode;
+pub use self::key_recorder::{KeyBinding, KeyRecorderMode};
+pub use self::line_jump::{LineJumpMode, LineJumpTarget};
+pub use self::mark::{MarkAction, MarkMode};
+pub use self::marks::{MarkEntry, MarksMode};
+pub use self::number::NumberMode;
+pub use self::passphrase::PassphraseMode;
 pub use self::path::PathMode;
 pub use self::open::OpenMode;
+pub use self::register::RegisterMode;
+pub use self::registers::RegistersMode;
+pub use self::rename::RenameMode;
+pub use self::replace::{ReplaceMode, ReplaceStage};
 pub use self::search::SearchMode;
-pub use self::search_select::{SearchSelectMode, SearchSelectConfig};
+pub use self::search_select::{ResultOrder, SearchSelectMode, SearchSelectConfig};
 pub use self::select::SelectMode;
+pub use self::select_block::{BlockInsert, SelectBlockMode};
 pub use self::select_line::SelectLineMode;
+pub use self::stash::StashMode;
 pub use self::symbol_jump::SymbolJumpMode;
+pub use self::tags::{TagEntry, TagsMode};
+pub use self::task::TaskMode;
 pub use self::theme::ThemeMode;
+pub use self::transform::TransformMode;
+pub use self::undo_tree::UndoTreeMode;
@@ -0,0 +1,97 @@
+use scribe::buffer::Range;
+use crate::util::{fuzzy, SelectableVec};
+use crate::util::number::Conversion;
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+
+pub struct NumberMode {
+    insert: bool,
+    input: Prompt,
+    conversions: Vec<Conversion>,
+    results: SelectableVec<Conversion>,
+    config: SearchSelectConfig,
+
+    /// The range occupied by the number being converted.
+    pub range: Range,
+}
+
+impl NumberMode {
+    pub fn new(conversions: Vec<Conversion>, range: Range, config: SearchSelectConfig) -> NumberMode {
+        NumberMode {
+            insert: true,
+            input: Prompt::default(),
+            conversions,
+            results: SelectableVec::new(Vec::new()),
+            config,
+            range,
+        }
+    }
+}
+
+impl fmt::Display for NumberMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NUMBER")
+    }
+}
+
+impl SearchSelectMode<Conversion> for NumberMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            // There are only a handful of conversions; list them all.
+            self.conversions.clone()
+        } else {
+            fuzzy::find(&self.input, &self.conversions, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<Conversion> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&Conversion> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
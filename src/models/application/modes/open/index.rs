@@ -0,0 +1,228 @@
+use bloodhound::ExclusionPattern;
+use fragment::matching::AsStr;
+use git2::Repository;
+use crate::models::application::Event;
+use crate::util;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::{Duration, Instant};
+
+// Found paths are delivered at most this often, so
+// that results aren't re-searched for every file.
+const BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A path (relative to the indexed directory) and the lowercased
+/// string it's matched against, built once when it's indexed.
+#[derive(Clone)]
+struct IndexedPath {
+    path: PathBuf,
+    search_str: String,
+}
+
+impl AsStr for IndexedPath {
+    fn as_str(&self) -> &str {
+        &self.search_str
+    }
+}
+
+/// The files beneath a directory. Indexes are populated by a background
+/// worker, and can be searched while the files are still being found.
+#[derive(Clone)]
+pub struct Index {
+    path: PathBuf,
+    entries: Vec<IndexedPath>,
+    complete: bool,
+}
+
+impl Index {
+    pub fn new(path: PathBuf) -> Index {
+        Index {
+            path,
+            entries: Vec::new(),
+            complete: false,
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether every file beneath the directory has been indexed.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    pub fn add(&mut self, paths: Vec<PathBuf>, complete: bool) {
+        self.entries.extend(paths.into_iter().map(|path| {
+            let search_str = path.to_string_lossy().to_lowercase();
+
+            IndexedPath { path, search_str }
+        }));
+        self.complete = complete;
+    }
+
+    /// Finds the best matching paths, along with their matched characters.
+    pub fn find(&self, query: &str, limit: usize) -> Vec<(&Path, Vec<usize>)> {
+        util::fuzzy::find(query, &self.entries, limit)
+            .into_iter()
+            .map(|result| (result.path.as_path(), result.indices))
+            .collect()
+    }
+}
+
+/// Finds the files beneath the directory in a background thread, sending them
+/// (relative to the directory) as events, in batches, until it's cancelled.
+/// Files ignored by the directory's repository are skipped, unless included.
+pub fn populate(path: PathBuf, exclusions: Option<Vec<ExclusionPattern>>, include_ignored: bool, generation: usize, cancelled: Arc<AtomicBool>, events: Sender<Event>) {
+    thread::spawn(move || {
+        let exclusions = exclusions.unwrap_or_default();
+        let repo = if include_ignored { None } else { Repository::discover(&path).ok() };
+        let mut batch = Vec::new();
+        let mut batch_started_at = Instant::now();
+        let mut directories = vec![path.clone()];
+
+        while let Some(directory) = directories.pop() {
+            if cancelled.load(Ordering::Relaxed) {
+                return;
+            }
+
+            let mut entries: Vec<PathBuf> = match fs::read_dir(&directory) {
+                Ok(entries) => entries.filter_map(|e| e.ok()).map(|e| e.path()).collect(),
+                Err(_) => continue,
+            };
+            entries.sort();
+
+            // Index this directory's files before descending into
+            // its subdirectories, so that shallower files come first.
+            let mut subdirectories = Vec::new();
+            for entry in entries {
+                if excluded(&exclusions, &entry) || util::git_ignored(repo.as_ref(), &entry) {
+                    continue;
+                }
+
+                // Don't follow symlinked directories, to avoid cycles.
+                let file_type = match fs::symlink_metadata(&entry) {
+                    Ok(metadata) => metadata.file_type(),
+                    Err(_) => continue,
+                };
+                if file_type.is_dir() {
+                    subdirectories.push(entry);
+                } else if file_type.is_file() || entry.is_file() {
+                    batch.push(entry.strip_prefix(&path).unwrap_or(&entry).to_path_buf());
+                }
+            }
+            directories.extend(subdirectories.into_iter().rev());
+
+            if batch_started_at.elapsed() >= BATCH_INTERVAL {
+                let paths = batch.split_off(0);
+                let _ = events.send(Event::OpenModeIndexProgress { generation, paths, complete: false });
+                batch_started_at = Instant::now();
+            }
+        }
+
+        let _ = events.send(Event::OpenModeIndexProgress { generation, paths: batch, complete: true });
+    });
+}
+
+fn excluded(exclusions: &[ExclusionPattern], path: &Path) -> bool {
+    let path = path.to_string_lossy();
+
+    exclusions.iter().any(|exclusion| exclusion.matches(&path))
+}
+
+#[cfg(test)]
+mod tests {
+    use bloodhound::ExclusionPattern;
+    use crate::models::application::Event;
+    use git2::Repository;
+    use super::{populate, Index};
+    use std::env;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+    use std::sync::Arc;
+    use std::sync::atomic::AtomicBool;
+    use std::sync::mpsc;
+
+    fn find<'a>(index: &'a Index, query: &str) -> Vec<&'a Path> {
+        index.find(query, 10).into_iter().map(|(path, _)| path).collect()
+    }
+
+    #[test]
+    fn populate_sends_files_outside_of_exclusions() {
+        let root = env::temp_dir().join("amp_open_mode_index");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("target")).unwrap();
+        fs::write(root.join("Cargo.toml"), "").unwrap();
+        fs::write(root.join("src/main.rs"), "").unwrap();
+        fs::write(root.join("target/amp"), "").unwrap();
+
+        let (events, received) = mpsc::channel();
+        let exclusions = vec![ExclusionPattern::new("**/target").unwrap()];
+        populate(root.clone(), Some(exclusions), false, 1, Arc::new(AtomicBool::new(false)), events);
+
+        let mut index = Index::new(root.clone());
+        while !index.is_complete() {
+            match received.recv().unwrap() {
+                Event::OpenModeIndexProgress { generation, paths, complete } => {
+                    assert_eq!(generation, 1);
+                    index.add(paths, complete);
+                }
+                _ => panic!("Unexpected event"),
+            }
+        }
+        assert_eq!(index.len(), 2);
+        assert_eq!(find(&index, "main"), vec![PathBuf::from("src/main.rs")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn populate_skips_files_ignored_by_the_repository_unless_included() {
+        let root = env::temp_dir().join("amp_open_mode_gitignore");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("node_modules")).unwrap();
+        Repository::init(&root).unwrap();
+        fs::write(root.join(".gitignore"), "node_modules/\n").unwrap();
+        fs::write(root.join("index.js"), "").unwrap();
+        fs::write(root.join("node_modules/left-pad.js"), "").unwrap();
+
+        for &include_ignored in &[false, true] {
+            let (events, received) = mpsc::channel();
+            let exclusions = vec![ExclusionPattern::new("**/.git").unwrap()];
+            populate(root.clone(), Some(exclusions), include_ignored, 1, Arc::new(AtomicBool::new(false)), events);
+
+            let mut index = Index::new(root.clone());
+            while !index.is_complete() {
+                if let Event::OpenModeIndexProgress { paths, complete, .. } = received.recv().unwrap() {
+                    index.add(paths, complete);
+                }
+            }
+            assert_eq!(!find(&index, "left-pad").is_empty(), include_ignored);
+            assert_eq!(find(&index, "index"), vec![PathBuf::from("index.js")]);
+        }
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn find_matches_paths_case_insensitively() {
+        let mut index = Index::new(PathBuf::from("."));
+        index.add(vec![PathBuf::from("README.md"), PathBuf::from("src/lib.rs")], false);
+
+        assert_eq!(index.find("readme", 10), vec![(Path::new("README.md"), vec![0, 1, 2, 3, 4, 5])]);
+        assert!(!index.is_complete());
+    }
+}
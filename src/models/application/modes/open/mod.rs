@@ -1,82 +1,270 @@
 mod displayable_path;
 pub mod exclusions;
+mod index;
 
+use std::collections::HashMap;
 use std::fmt;
+use std::fs;
 use std::path::PathBuf;
 use std::slice::Iter;
 use bloodhound::ExclusionPattern;
 use crate::util::SelectableVec;
-use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::modes::{ResultOrder, SearchSelectMode, SearchSelectConfig};
+use crate::models::application::{LineHistory, Prompt};
+use crate::commands::{self, Command};
 use crate::models::application::Event;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::sync::mpsc::Sender;
-use std::thread;
-pub use bloodhound::Index;
 pub use self::displayable_path::DisplayablePath;
+pub use self::index::Index;
 
-#[derive(PartialEq)]
-pub enum OpenModeIndex {
-    Complete(Index),
-    Indexing(PathBuf)
-}
+pub const HISTORY_NAMESPACE: &str = "open";
+
+// Identifies each mode's indexer, so that paths
+// sent by those of previous modes can be ignored.
+static INDEX_GENERATION: AtomicUsize = AtomicUsize::new(0);
 
 pub struct OpenMode {
     pub insert: bool,
-    pub input: String,
-    index: OpenModeIndex,
+    pub input: Prompt,
+    index: Index,
+
+    /// A replacement for a cached index, populated in the background.
+    refresh: Option<Index>,
+    generation: usize,
+    cancelled: Arc<AtomicBool>,
+    exclusions: Option<Vec<ExclusionPattern>>,
+
+    /// Whether files ignored by the workspace's repository are indexed.
+    include_ignored: bool,
+    events: Sender<Event>,
     pub results: SelectableVec<DisplayablePath>,
+
+    /// The characters of each result's path that matched the query,
+    /// kept by path, since results can be re-sorted.
+    matches: HashMap<PathBuf, Vec<usize>>,
+    root: PathBuf,
+    order: ResultOrder,
+
+    /// Open buffers' paths, and when they were last used.
+    recent_paths: HashMap<PathBuf, u64>,
+
+    /// Queries used to open files previously, and the one nearest
+    /// to the current query, when it doesn't match anything.
+    past_queries: LineHistory,
+    suggestion: Option<String>,
     config: SearchSelectConfig,
 }
 
 impl OpenMode {
-    pub fn new(path: PathBuf, exclusions: Option<Vec<ExclusionPattern>>, events: Sender<Event>, config: SearchSelectConfig) -> OpenMode {
-        // Build and populate the index in a separate thread.
-        let index_path = path.clone();
-        thread::spawn(move || {
-            let mut index = Index::new(index_path);
-            index.populate(exclusions, false);
-            let _ = events.send(
-                Event::OpenModeIndexComplete(index)
-            );
-        });
-
-        OpenMode {
+    /// Indexes the directory's files in the background, listing matches as
+    /// they're found. A complete index cached from a previous use is searched
+    /// straight away instead, until its replacement has been populated.
+    pub fn new(path: PathBuf, exclusions: Option<Vec<ExclusionPattern>>, cached_index: Option<Index>, events: Sender<Event>, config: SearchSelectConfig) -> OpenMode {
+        let (index, refresh) = match cached_index {
+            Some(index) if index.is_complete() && index.path() == path => {
+                (index, Some(Index::new(path.clone())))
+            }
+            _ => (Index::new(path.clone()), None),
+        };
+
+        let mut mode = OpenMode {
             insert: true,
-            input: String::new(),
-            index: OpenModeIndex::Indexing(path),
+            input: Prompt::default().with_history(HISTORY_NAMESPACE),
+            index,
+            refresh,
+            generation: 0,
+            cancelled: Arc::new(AtomicBool::new(false)),
+            exclusions,
+            include_ignored: false,
+            events,
             results: SelectableVec::new(Vec::new()),
+            matches: HashMap::new(),
+            root: path,
+            order: ResultOrder::default(),
+            recent_paths: HashMap::new(),
+            past_queries: LineHistory::default(),
+            suggestion: None,
             config,
+        };
+        mode.start_indexing();
+
+        mode
+    }
+
+    pub fn includes_ignored(&self) -> bool {
+        self.include_ignored
+    }
+
+    /// Re-indexes the directory from scratch, including (or once again
+    /// skipping) files ignored by the workspace's repository.
+    pub fn toggle_ignored(&mut self) {
+        self.include_ignored = !self.include_ignored;
+        self.index = Index::new(self.root.clone());
+        self.refresh = None;
+        self.start_indexing();
+        self.search();
+    }
+
+    // Starts a background indexer, abandoning any previous one.
+    fn start_indexing(&mut self) {
+        self.cancelled.store(true, Ordering::Relaxed);
+        self.cancelled = Arc::new(AtomicBool::new(false));
+        self.generation = INDEX_GENERATION.fetch_add(1, Ordering::Relaxed);
+        index::populate(
+            self.root.clone(),
+            self.exclusions.clone(),
+            self.include_ignored,
+            self.generation,
+            self.cancelled.clone(),
+            self.events.clone()
+        );
+    }
+
+    /// Adds paths found by the background indexer (ignoring those sent to
+    /// previous modes), and updates the results to include them. The index
+    /// is returned once it's complete, so that it can be cached (unless
+    /// it includes ignored files, which are only shown temporarily).
+    pub fn add_indexed_paths(&mut self, generation: usize, paths: Vec<PathBuf>, complete: bool) -> Option<&Index> {
+        if generation != self.generation {
+            return None;
+        }
+
+        if let Some(mut refresh) = self.refresh.take() {
+            refresh.add(paths, complete);
+            if !complete {
+                self.refresh = Some(refresh);
+
+                return None;
+            }
+            self.index = refresh;
+        } else {
+            self.index.add(paths, complete);
+        }
+
+        let selected_index = self.results.selected_index();
+        self.search();
+        self.results.select(selected_index);
+
+        if complete && !self.include_ignored {
+            Some(&self.index)
+        } else {
+            None
+        }
+    }
+
+    /// Provides the paths of open buffers, and when they were last
+    /// used, so that results can be sorted by recent use.
+    pub fn set_recent_paths(&mut self, recent_paths: HashMap<PathBuf, u64>) {
+        self.recent_paths = recent_paths;
+    }
+
+    /// Provides the queries used to open files previously, so that
+    /// one can be suggested when the current query matches nothing.
+    pub fn set_past_queries(&mut self, past_queries: LineHistory) {
+        self.past_queries = past_queries;
+    }
+
+    /// Replaces the query with the suggested one, if there is one.
+    pub fn accept_suggestion(&mut self) -> bool {
+        match self.suggestion.take() {
+            Some(suggestion) => {
+                self.input.set(suggestion);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn order(&self) -> ResultOrder {
+        self.order
+    }
+
+    /// Re-sorts the results, keeping the selected path selected
+    /// (unless the default order is restored, which re-runs the search).
+    pub fn set_order(&mut self, order: ResultOrder) {
+        self.order = order;
+        if order == ResultOrder::Score {
+            self.search();
+        } else {
+            self.sort_results();
+        }
+    }
+
+    // Sorts the results using the current order, listing the most recently
+    // used or modified paths first. Paths without one are listed last.
+    fn sort_results(&mut self) {
+        match self.order {
+            ResultOrder::Score => (),
+            ResultOrder::Path => self.results.sort_by(|a, b| a.0.cmp(&b.0)),
+            ResultOrder::Recency => {
+                let root = &self.root;
+                let recent_paths = &self.recent_paths;
+                let last_access = |path: &DisplayablePath| recent_paths.get(&root.join(&path.0)).copied();
+                self.results.sort_by(|a, b| last_access(b).cmp(&last_access(a)));
+            }
+            ResultOrder::ModificationTime => {
+                // Look up each path's modification time once, rather than on every comparison.
+                let modified_at: HashMap<PathBuf, _> = self.results
+                    .iter()
+                    .map(|path| {
+                        let modified_at = fs::metadata(self.root.join(&path.0))
+                            .and_then(|metadata| metadata.modified())
+                            .ok();
+
+                        (path.0.clone(), modified_at)
+                    })
+                    .collect();
+                self.results.sort_by(|a, b| modified_at[&b.0].cmp(&modified_at[&a.0]));
+            }
         }
     }
+}
 
-    pub fn set_index(&mut self, index: Index) {
-        self.index = OpenModeIndex::Complete(index)
+impl Drop for OpenMode {
+    fn drop(&mut self) {
+        // Stop indexing files that'll never be searched.
+        self.cancelled.store(true, Ordering::Relaxed);
     }
 }
 
 impl fmt::Display for OpenMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "OPEN")
+        write!(f, "OPEN")?;
+        if self.include_ignored {
+            write!(f, " (WITH IGNORED)")?;
+        }
+        if !self.index.is_complete() {
+            write!(f, " (INDEXING…)")?;
+        }
+
+        Ok(())
     }
 }
 
 impl SearchSelectMode<DisplayablePath> for OpenMode {
     fn search(&mut self) {
-        let results =
-            if let OpenModeIndex::Complete(ref index) = self.index {
-                index.find(
-                    &self.input.to_lowercase(),
-                    self.config.max_results
-                ).into_iter()
-                .map(|path| DisplayablePath(path.to_path_buf()))
-                .collect()
-            } else {
-                vec![]
-            };
+        let matches: Vec<(PathBuf, Vec<usize>)> = self.index
+            .find(&self.input.to_lowercase(), self.config.max_results)
+            .into_iter()
+            .map(|(path, indices)| (path.to_path_buf(), indices))
+            .collect();
+        let results = matches.iter().map(|(path, _)| DisplayablePath(path.clone())).collect();
 
         self.results = SelectableVec::new(results);
+        self.matches = matches.into_iter().collect();
+        self.sort_results();
+
+        // Files that haven't been indexed yet may still match.
+        self.suggestion = if self.results.is_empty() && self.index.is_complete() {
+            self.past_queries.nearest(&self.input).map(String::from)
+        } else {
+            None
+        };
     }
 
-    fn query(&mut self) -> &mut String {
+    fn query(&mut self) -> &mut Prompt {
         &mut self.input
     }
 
@@ -96,6 +284,15 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
         self.results.selection()
     }
 
+    fn matched_indices(&self, index: usize) -> Vec<usize> {
+        self.results
+            .iter()
+            .nth(index)
+            .and_then(|path| self.matches.get(&path.0))
+            .cloned()
+            .unwrap_or_default()
+    }
+
     fn selected_index(&self) -> usize {
         self.results.selected_index()
     }
@@ -112,15 +309,131 @@ impl SearchSelectMode<DisplayablePath> for OpenMode {
         &self.config
     }
 
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("open", commands::search_select::accept)]
+    }
+
     fn message(&mut self) -> Option<String> {
-        if let OpenModeIndex::Indexing(ref path) = self.index {
-            Some(format!("Indexing {}", path.to_string_lossy()))
-        } else if self.query().is_empty() {
-            Some(String::from("Enter a search query to start."))
+        let indexing = if self.index.is_complete() {
+            None
+        } else {
+            Some(format!("Indexing {}… ({} files found)", self.root.to_string_lossy(), self.index.len()))
+        };
+
+        if self.query().is_empty() {
+            Some(indexing.unwrap_or_else(|| String::from("Enter a search query to start.")))
         } else if self.results().count() == 0 {
-            Some(String::from("No matching entries found."))
+            Some(indexing.unwrap_or_else(|| match self.suggestion {
+                Some(ref suggestion) => format!("No matching entries found. Did you mean \"{}\"? Press tab to use it.", suggestion),
+                None => String::from("No matching entries found."),
+            }))
         } else {
             None
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::LineHistory;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use super::{Index, OpenMode};
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use std::sync::mpsc;
+
+    fn results(mode: &OpenMode) -> Vec<PathBuf> {
+        mode.results().map(|path| path.0.clone()).collect()
+    }
+
+    #[test]
+    fn add_indexed_paths_lists_matches_as_they_are_found() {
+        let root = env::temp_dir().join("amp_open_mode_partial");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let mut mode = OpenMode::new(root.clone(), None, None, mpsc::channel().0, SearchSelectConfig::default());
+        let generation = mode.generation;
+        mode.query().set("lib");
+
+        mode.add_indexed_paths(generation, vec![PathBuf::from("src/lib.rs")], false);
+        assert_eq!(results(&mode), vec![PathBuf::from("src/lib.rs")]);
+        assert_eq!(mode.to_string(), "OPEN (INDEXING…)");
+
+        // Paths sent to previous modes are ignored.
+        assert!(mode.add_indexed_paths(generation + 1, vec![PathBuf::from("lib/old.rs")], true).is_none());
+
+        let index = mode.add_indexed_paths(generation, vec![PathBuf::from("tests/lib.rs")], true);
+        assert_eq!(index.map(|index| index.len()), Some(2));
+        assert_eq!(results(&mode).len(), 2);
+        assert_eq!(mode.to_string(), "OPEN");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn search_suggests_the_nearest_past_query_when_nothing_matches() {
+        let root = env::temp_dir().join("amp_open_mode_suggestion");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let mut mode = OpenMode::new(root.clone(), None, None, mpsc::channel().0, SearchSelectConfig::default());
+        let generation = mode.generation;
+        let mut past_queries = LineHistory::default();
+        past_queries.add(String::from("application mod"));
+        mode.set_past_queries(past_queries);
+        mode.add_indexed_paths(generation, vec![PathBuf::from("src/models/application/mod.rs")], true);
+
+        mode.query().set("aplication mod");
+        mode.search();
+        assert!(mode.message().unwrap().contains("Did you mean \"application mod\"?"));
+        assert!(mode.accept_suggestion());
+        mode.search();
+        assert_eq!(results(&mode), vec![PathBuf::from("src/models/application/mod.rs")]);
+        assert!(!mode.accept_suggestion());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn toggle_ignored_re_indexes_the_directory() {
+        let root = env::temp_dir().join("amp_open_mode_ignored");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let mut mode = OpenMode::new(root.clone(), None, None, mpsc::channel().0, SearchSelectConfig::default());
+        let generation = mode.generation;
+        mode.add_indexed_paths(generation, vec![PathBuf::from("lib.rs")], true);
+
+        mode.toggle_ignored();
+        assert!(mode.includes_ignored());
+        assert_eq!(mode.to_string(), "OPEN (WITH IGNORED) (INDEXING…)");
+
+        // The previous indexer's paths are discarded, and complete
+        // indexes that include ignored files aren't cached.
+        assert!(mode.add_indexed_paths(generation, vec![PathBuf::from("old.rs")], true).is_none());
+        assert!(mode.add_indexed_paths(mode.generation, vec![PathBuf::from("lib.rs")], true).is_none());
+        assert_eq!(mode.to_string(), "OPEN (WITH IGNORED)");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn cached_indexes_are_searched_until_replaced() {
+        let root = env::temp_dir().join("amp_open_mode_cached");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        let mut cached = Index::new(root.clone());
+        cached.add(vec![PathBuf::from("old.rs")], true);
+        let mut mode = OpenMode::new(root.clone(), None, Some(cached), mpsc::channel().0, SearchSelectConfig::default());
+        let generation = mode.generation;
+        mode.query().set("rs");
+        mode.search();
+        assert_eq!(results(&mode), vec![PathBuf::from("old.rs")]);
+
+        mode.add_indexed_paths(generation, vec![PathBuf::from("new.rs")], false);
+        assert_eq!(results(&mode), vec![PathBuf::from("old.rs")]);
+        mode.add_indexed_paths(generation, Vec::new(), true);
+        assert_eq!(results(&mode), vec![PathBuf::from("new.rs")]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
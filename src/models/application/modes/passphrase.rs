@@ -0,0 +1,22 @@
+use crate::models::application::Prompt;
+use std::fmt;
+use std::path::PathBuf;
+
+/// Prompts for the passphrase used to decrypt (and later, re-encrypt) a file.
+/// The input is masked when displayed, and deliberately kept out of history.
+pub struct PassphraseMode {
+    pub path: PathBuf,
+    pub input: Prompt,
+}
+
+impl PassphraseMode {
+    pub fn new(path: PathBuf) -> PassphraseMode {
+        PassphraseMode { path, input: Prompt::default() }
+    }
+}
+
+impl fmt::Display for PassphraseMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PASSPHRASE")
+    }
+}
@@ -1,23 +1,19 @@
+use crate::models::application::Prompt;
+use crate::util::prompt::complete_path;
 use std::fmt;
 
 pub struct PathMode {
-    pub input: String,
+    pub input: Prompt,
     pub save_on_accept: bool,
 }
 
 impl PathMode {
     pub fn new(initial_path: String) -> PathMode {
         PathMode {
-            input: initial_path,
+            input: Prompt::new(initial_path).with_completer(complete_path),
             save_on_accept: false
         }
     }
-    pub fn push_char(&mut self, c: char) {
-        self.input.push(c);
-    }
-    pub fn pop_char(&mut self) {
-        self.input.pop();
-    }
 }
 
 impl fmt::Display for PathMode {
@@ -25,3 +21,24 @@ impl fmt::Display for PathMode {
         write!(f, "PATH")
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::PathMode;
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn input_completes_paths_within_its_directory() {
+        let directory = Path::new(env!("OUT_DIR")).join("path_mode_completion");
+        fs::create_dir_all(directory.join("models")).unwrap();
+        fs::write(directory.join("main.rs"), "").unwrap();
+
+        let mut mode = PathMode::new(format!("{}/mo", directory.to_string_lossy()));
+        assert!(mode.input.complete());
+        assert_eq!(&*mode.input, format!("{}/models/", directory.to_string_lossy()));
+
+        mode.input.set(format!("{}/m", directory.to_string_lossy()));
+        assert!(!mode.input.complete());
+    }
+}
@@ -0,0 +1,20 @@
+use crate::models::application::modes::jump::SelectModeOptions;
+use std::fmt;
+
+/// Waits for a register name, which the next copy or paste will use,
+/// returning to the select mode it was entered from (if any) afterwards.
+pub struct RegisterMode {
+    pub select_mode: SelectModeOptions,
+}
+
+impl RegisterMode {
+    pub fn new(select_mode: SelectModeOptions) -> RegisterMode {
+        RegisterMode { select_mode }
+    }
+}
+
+impl fmt::Display for RegisterMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REGISTER")
+    }
+}
@@ -0,0 +1,172 @@
+use fragment::matching::AsStr;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::{ClipboardContent, Prompt};
+use crate::commands::{self, Command};
+use crate::util::{fuzzy, SelectableVec};
+use std::fmt;
+use std::slice::Iter;
+
+/// Lists the registers holding content, to select one to paste from.
+pub struct RegistersMode {
+    insert: bool,
+    input: Prompt,
+    registers: Vec<RegisterEntry>,
+    results: SelectableVec<RegisterEntry>,
+    config: SearchSelectConfig,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RegisterEntry {
+    pub name: char,
+    pub content: String,
+
+    /// The register name followed by the first line of its content.
+    label: String,
+}
+
+impl RegisterEntry {
+    fn new(name: char, content: String) -> RegisterEntry {
+        let first_line = content.lines().find(|line| !line.trim().is_empty()).unwrap_or("");
+        let line_count = content.lines().count();
+        let label = if line_count > 1 {
+            format!("{}  {} ({} lines)", name, first_line.trim(), line_count)
+        } else {
+            format!("{}  {}", name, first_line.trim())
+        };
+
+        RegisterEntry { name, content, label }
+    }
+}
+
+impl fmt::Display for RegisterEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for RegisterEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+impl RegistersMode {
+    pub fn new(registers: Vec<(char, ClipboardContent)>, config: SearchSelectConfig) -> RegistersMode {
+        let registers = registers
+            .into_iter()
+            .filter_map(|(name, content)| match content {
+                ClipboardContent::Inline(content) |
+                ClipboardContent::Block(content) => Some(RegisterEntry::new(name, content)),
+                ClipboardContent::None => None,
+            })
+            .collect();
+
+        RegistersMode {
+            insert: false,
+            input: Prompt::default(),
+            registers,
+            results: SelectableVec::new(Vec::new()),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for RegistersMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REGISTERS")
+    }
+}
+
+impl SearchSelectMode<RegisterEntry> for RegistersMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            self.registers
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            fuzzy::find(&self.input, &self.registers, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<RegisterEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&RegisterEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("paste", commands::search_select::accept)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.registers.is_empty() {
+            Some(String::from("All registers are empty."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching registers found."))
+        } else {
+            None
+        }
+    }
+
+    fn preview(&self) -> Option<&str> {
+        self.selection().map(|entry| entry.content.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RegistersMode;
+    use crate::models::application::ClipboardContent;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+    #[test]
+    fn search_lists_registers_with_the_first_line_of_their_content() {
+        let mut mode = RegistersMode::new(vec![
+            ('+', ClipboardContent::Inline(String::from("amp"))),
+            ('a', ClipboardContent::Block(String::from("\n  fn main() {\n  }\n"))),
+            ('b', ClipboardContent::None),
+        ], SearchSelectConfig::default());
+        mode.search();
+
+        let labels: Vec<String> = mode.results().map(|entry| entry.to_string()).collect();
+        assert_eq!(labels, vec!["+  amp", "a  fn main() { (3 lines)"]);
+        assert_eq!(mode.preview(), Some("amp"));
+    }
+}
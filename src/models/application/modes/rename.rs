@@ -0,0 +1,27 @@
+use crate::models::application::Prompt;
+use std::fmt;
+
+/// Prompts for a new name for a token, which is
+/// replaced across all open buffers once accepted
+/// (or by the buffer's language server, if requested).
+pub struct RenameMode {
+    pub token: String,
+    pub input: Prompt,
+    pub language_server: bool,
+}
+
+impl RenameMode {
+    pub fn new(token: String) -> RenameMode {
+        RenameMode {
+            input: Prompt::new(token.clone()),
+            token,
+            language_server: false,
+        }
+    }
+}
+
+impl fmt::Display for RenameMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RENAME")
+    }
+}
@@ -0,0 +1,206 @@
+use crate::errors::*;
+use crate::models::application::modes::search::REGEX_PREFIX;
+use crate::models::application::Prompt;
+use crate::util::{replacement, PositionMap};
+use regex::{self, Regex};
+use scribe::buffer::Range;
+use std::fmt;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReplaceStage {
+    /// Entering the pattern to search for.
+    Pattern,
+
+    /// Entering its replacement.
+    Replacement,
+
+    /// Walking through matches, confirming each replacement.
+    Confirm,
+}
+
+/// A match awaiting confirmation, along with its (expanded) replacement.
+struct PendingMatch {
+    start: usize,
+    end: usize,
+    range: Range,
+    content: String,
+}
+
+/// Prompts for a pattern and replacement, then walks through the current
+/// buffer's matches, replacing or skipping each of them. Patterns with the
+/// regex prefix are regular expressions, whose replacements can reference
+/// capture groups (see `util::replacement`).
+pub struct ReplaceMode {
+    pub stage: ReplaceStage,
+    pub pattern: Prompt,
+    pub replacement: Prompt,
+    pub replaced: usize,
+    regex: Option<Regex>,
+    expand_replacement: bool,
+
+    /// Byte offset (in the buffer's current content) from which to find the next match.
+    next_offset: usize,
+    current: Option<PendingMatch>,
+}
+
+impl ReplaceMode {
+    /// Creates a new mode, skipping straight to the replacement if a pattern is provided.
+    pub fn new(pattern: Option<String>) -> ReplaceMode {
+        ReplaceMode {
+            stage: if pattern.is_some() { ReplaceStage::Replacement } else { ReplaceStage::Pattern },
+            pattern: Prompt::new(pattern.unwrap_or_default()),
+            replacement: Prompt::default(),
+            replaced: 0,
+            regex: None,
+            expand_replacement: false,
+            next_offset: 0,
+            current: None,
+        }
+    }
+
+    /// The input for the current stage.
+    pub fn input(&mut self) -> &mut Prompt {
+        if self.stage == ReplaceStage::Pattern {
+            &mut self.pattern
+        } else {
+            &mut self.replacement
+        }
+    }
+
+    /// Compiles the pattern and starts confirming replacements.
+    pub fn start(&mut self) -> Result<()> {
+        let (pattern, expand_replacement) = match self.pattern.strip_prefix(REGEX_PREFIX) {
+            Some(pattern) => (format!("(?m){}", pattern), true),
+            None => (regex::quote(&self.pattern), false),
+        };
+        if pattern.is_empty() {
+            bail!(SEARCH_QUERY_MISSING);
+        }
+
+        self.regex = Some(
+            Regex::new(&pattern).chain_err(|| format!("Invalid pattern: {}", self.pattern))?
+        );
+        self.expand_replacement = expand_replacement;
+        self.stage = ReplaceStage::Confirm;
+
+        Ok(())
+    }
+
+    /// Finds the first (non-empty) match that hasn't been handled yet,
+    /// returning its range, which is also available via `current_range`.
+    pub fn find_next(&mut self, data: &str) -> Option<Range> {
+        let next_offset = self.next_offset;
+        let regex = self.regex.as_ref()?;
+        let captures = regex.captures_iter(data).find(|captures| {
+            captures.pos(0).map(|(start, end)| start >= next_offset && start < end) == Some(true)
+        });
+
+        self.current = captures.and_then(|captures| {
+            let (start, end) = captures.pos(0)?;
+            let positions = PositionMap::new(data);
+            let content = if self.expand_replacement {
+                replacement::expand(&self.replacement, &captures)
+            } else {
+                self.replacement.to_string()
+            };
+
+            Some(PendingMatch {
+                start,
+                end,
+                range: Range::new(positions.position(start), positions.position(end)),
+                content,
+            })
+        });
+
+        self.current_range().cloned()
+    }
+
+    pub fn current_range(&self) -> Option<&Range> {
+        self.current.as_ref().map(|current| &current.range)
+    }
+
+    /// The current match's range and replacement, which is considered
+    /// handled; the next match is found after the replacement content.
+    pub fn take_replacement(&mut self) -> Option<(Range, String)> {
+        let current = self.current.take()?;
+        self.next_offset = current.start + current.content.len();
+        self.replaced += 1;
+
+        Some((current.range, current.content))
+    }
+
+    /// Leaves the current match as-is, moving past it.
+    pub fn skip(&mut self) {
+        if let Some(current) = self.current.take() {
+            self.next_offset = current.end;
+        }
+    }
+
+    /// The replacement for the current match, for display purposes.
+    pub fn current_replacement(&self) -> Option<&str> {
+        self.current.as_ref().map(|current| current.content.as_str())
+    }
+}
+
+impl fmt::Display for ReplaceMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "REPLACE")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ReplaceMode, ReplaceStage};
+    use scribe::buffer::{Position, Range};
+
+    #[test]
+    fn new_skips_to_the_replacement_when_given_a_pattern() {
+        let mut mode = ReplaceMode::new(Some(String::from("amp")));
+        assert_eq!(mode.stage, ReplaceStage::Replacement);
+
+        mode.input().insert_str("editor");
+        assert_eq!(&*mode.replacement, "editor");
+    }
+
+    #[test]
+    fn find_next_walks_literal_matches_after_those_handled() {
+        let mut mode = ReplaceMode::new(Some(String::from("a.")));
+        mode.replacement.set("a.a.");
+        mode.start().unwrap();
+
+        let data = "a. ab a.";
+        assert_eq!(
+            mode.find_next(data),
+            Some(Range::new(Position { line: 0, offset: 0 }, Position { line: 0, offset: 2 }))
+        );
+        assert_eq!(mode.take_replacement(), Some((Range::new(Position { line: 0, offset: 0 }, Position { line: 0, offset: 2 }), String::from("a.a."))));
+
+        // The replacement itself isn't matched again.
+        let data = "a.a. ab a.";
+        assert_eq!(
+            mode.find_next(data),
+            Some(Range::new(Position { line: 0, offset: 8 }, Position { line: 0, offset: 10 }))
+        );
+        mode.skip();
+        assert_eq!(mode.find_next(data), None);
+        assert_eq!(mode.replaced, 1);
+    }
+
+    #[test]
+    fn find_next_expands_regex_replacements() {
+        let mut mode = ReplaceMode::new(Some(String::from("re:(\\w+)_(\\w+)")));
+        mode.replacement.set("$1\\u$2");
+        mode.start().unwrap();
+
+        mode.find_next("let snake_case = 1;");
+        assert_eq!(mode.current_replacement(), Some("snakeCase"));
+    }
+
+    #[test]
+    fn start_rejects_invalid_regular_expressions() {
+        let mut mode = ReplaceMode::new(Some(String::from("re:(")));
+
+        assert!(mode.start().is_err());
+        assert_eq!(mode.stage, ReplaceStage::Replacement);
+    }
+}
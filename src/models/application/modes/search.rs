@@ -1,20 +1,44 @@
 use crate::errors::*;
-use crate::util::SelectableVec;
+use crate::models::application::Prompt;
+use crate::util::{PositionMap, SelectableVec};
+use regex::Regex;
 use std::fmt;
 use scribe::buffer::{Buffer, Distance, Range};
 
+pub const HISTORY_NAMESPACE: &str = "search";
+
+/// Queries starting with this are treated as regular expressions.
+pub const REGEX_PREFIX: &str = "re:";
+
 pub struct SearchMode {
     pub insert: bool,
-    pub input: Option<String>,
+    pub input: Prompt,
     pub results: Option<SelectableVec<Range>>,
+
+    /// Whether moving past the last (or first) result wraps around.
+    pub wrap: bool,
+
+    /// Previous queries matching the current input.
+    pub suggestions: Vec<String>,
 }
 
 impl SearchMode {
     pub fn new(query: Option<String>) -> SearchMode {
         SearchMode {
             insert: true,
-            input: query,
+            input: Prompt::new(query.unwrap_or_default()).with_history(HISTORY_NAMESPACE),
             results: None,
+            wrap: true,
+            suggestions: Vec::new(),
+        }
+    }
+
+    /// The input, unless it's empty.
+    pub fn query(&self) -> Option<&str> {
+        if self.input.is_empty() {
+            None
+        } else {
+            Some(&self.input)
         }
     }
 
@@ -22,10 +46,20 @@ impl SearchMode {
         self.insert
     }
 
+    /// Whether the query is a regular expression (i.e. has the regex prefix).
+    pub fn regex(&self) -> bool {
+        self.input.starts_with(REGEX_PREFIX)
+    }
+
     // Searches the specified buffer for the input string
     // and stores the result as a collection of ranges.
     pub fn search(&mut self, buffer: &Buffer) -> Result<()> {
-        let query = self.input.as_ref().ok_or(SEARCH_QUERY_MISSING)?;
+        let query = self.query().ok_or(SEARCH_QUERY_MISSING)?.to_string();
+        if let Some(pattern) = query.strip_prefix(REGEX_PREFIX) {
+            self.results = Some(SelectableVec::new(regex_search(&buffer.data(), pattern)?));
+
+            return Ok(());
+        }
         let distance = Distance::of_str(&query);
 
         // Buffer search returns match starting positions, but we'd like ranges.
@@ -46,10 +80,30 @@ impl SearchMode {
 
 impl fmt::Display for SearchMode {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "SEARCH")
+        if self.regex() {
+            write!(f, "SEARCH (REGEX)")
+        } else {
+            write!(f, "SEARCH")
+        }
     }
 }
 
+// Finds the (non-empty) matches for a regular expression, which is run against
+// the buffer as a whole, so that patterns can span multiple lines. The "^" and
+// "$" anchors match at the start and end of each line, rather than the buffer.
+fn regex_search(data: &str, pattern: &str) -> Result<Vec<Range>> {
+    let regex = Regex::new(&format!("(?m){}", pattern))
+        .chain_err(|| format!("Invalid regular expression: {}", pattern))?;
+
+    let positions = PositionMap::new(data);
+
+    Ok(regex
+        .find_iter(data)
+        .filter(|&(start, end)| start < end)
+        .map(|(start, end)| Range::new(positions.position(start), positions.position(end)))
+        .collect())
+}
+
 #[cfg(test)]
 mod tests {
     use scribe::buffer::{Buffer, Position, Range};
@@ -77,4 +131,36 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn search_treats_prefixed_queries_as_multi_line_regular_expressions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nédit\nedit");
+
+        let mut mode = SearchMode::new(Some(String::from("re:^e?dit$|or\\né")));
+        mode.search(&buffer).unwrap();
+
+        assert_eq!(
+            *mode.results.unwrap(),
+            vec![
+                Range::new(
+                    Position{ line: 0, offset: 8 },
+                    Position{ line: 1, offset: 1 },
+                ),
+                Range::new(
+                    Position{ line: 2, offset: 0 },
+                    Position{ line: 2, offset: 4 },
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn search_rejects_invalid_regular_expressions() {
+        let buffer = Buffer::new();
+        let mut mode = SearchMode::new(Some(String::from("re:(")));
+
+        assert!(mode.search(&buffer).is_err());
+        assert_eq!(mode.to_string(), "SEARCH (REGEX)");
+    }
 }
@@ -1,4 +1,6 @@
-use std::fmt::Display;
+use crate::commands::{self, Command};
+use crate::models::application::Prompt;
+use std::fmt::{self, Display};
 use std::slice::Iter;
 
 #[derive(Clone)]
@@ -14,10 +16,49 @@ impl Default for SearchSelectConfig {
     }
 }
 
+/// How results are ordered: by how well they match the query (or the
+/// mode's own order, without one), or by one of their attributes.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ResultOrder {
+    Score,
+    Path,
+    Recency,
+    ModificationTime,
+}
+
+impl ResultOrder {
+    /// Switches to the order, or back to the
+    /// default if it's already being used.
+    pub fn toggle(self, order: ResultOrder) -> ResultOrder {
+        if self == order {
+            ResultOrder::Score
+        } else {
+            order
+        }
+    }
+}
+
+impl Default for ResultOrder {
+    fn default() -> ResultOrder {
+        ResultOrder::Score
+    }
+}
+
+impl Display for ResultOrder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ResultOrder::Score => write!(f, "match"),
+            ResultOrder::Path => write!(f, "path"),
+            ResultOrder::Recency => write!(f, "recent use"),
+            ResultOrder::ModificationTime => write!(f, "modification time"),
+        }
+    }
+}
+
 /// This trait will become vastly simpler if/when fields are added to traits.
 /// See: https://github.com/rust-lang/rfcs/pull/1546
 pub trait SearchSelectMode<T: Display>: Display {
-    fn query(&mut self) -> &mut String;
+    fn query(&mut self) -> &mut Prompt;
     fn search(&mut self);
     fn insert_mode(&self) -> bool;
     fn set_insert_mode(&mut self, insert_mode: bool);
@@ -26,6 +67,25 @@ pub trait SearchSelectMode<T: Display>: Display {
     fn selected_index(&self) -> usize;
     fn select_previous(&mut self);
     fn select_next(&mut self);
+
+    /// Selects the listed result at the index (e.g. when it's clicked),
+    /// returning false if there isn't one.
+    fn select_index(&mut self, index: usize) -> bool {
+        let count = self.results().count();
+        if index >= count {
+            return false;
+        }
+
+        // Selections wrap around, so this stops within one pass.
+        for _ in 0..count {
+            if self.selected_index() == index {
+                break;
+            }
+            self.select_next();
+        }
+
+        true
+    }
     fn config(&self) -> &SearchSelectConfig;
     fn message(&mut self) -> Option<String> {
         if self.query().is_empty() {
@@ -37,37 +97,56 @@ pub trait SearchSelectMode<T: Display>: Display {
         }
     }
 
-    fn push_search_char(&mut self, c: char) {
-        self.query().push(c);
+    /// The character indices of the listed result's text that matched
+    /// the query, which are highlighted. Modes that don't track them
+    /// list their results without highlighting.
+    fn matched_indices(&self, _index: usize) -> Vec<usize> {
+        Vec::new()
     }
 
-    fn pop_search_token(&mut self) {
-        let query = self.query();
-
-        // Find the last word boundary (transition to/from whitespace), using
-        // using fold to carry the previous character's type forward.
-        let mut boundary_index = 0;
-        query.char_indices().fold(true, |was_whitespace, (index, c)| {
-            if c.is_whitespace() != was_whitespace {
-                boundary_index = index;
-            }
+    /// Supplementary content for the current selection, displayed
+    /// beside the results (or beneath the search input, if there isn't room).
+    fn preview(&self) -> Option<&str> {
+        None
+    }
 
-            c.is_whitespace()
-        });
+    /// The commands that act on the selection, and what they do,
+    /// listed (alongside their keys) at the end of the search input.
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("select", commands::search_select::accept)]
+    }
 
-        query.truncate(boundary_index);
+    fn pop_search_token(&mut self) {
+        self.query().delete_before(pop_token);
     }
 }
 
+// Removes the last token (a run of either whitespace or non-whitespace).
+fn pop_token(query: &mut String) {
+    // Find the last word boundary (transition to/from whitespace), using
+    // using fold to carry the previous character's type forward.
+    let mut boundary_index = 0;
+    query.char_indices().fold(true, |was_whitespace, (index, c)| {
+        if c.is_whitespace() != was_whitespace {
+            boundary_index = index;
+        }
+
+        c.is_whitespace()
+    });
+
+    query.truncate(boundary_index);
+}
+
 #[cfg(test)]
 mod tests {
     use std::fmt;
     use std::slice::Iter;
-    use super::{SearchSelectMode, SearchSelectConfig};
+    use super::{ResultOrder, SearchSelectMode, SearchSelectConfig};
+    use crate::models::application::Prompt;
 
     #[derive(Default)]
     struct TestMode {
-        input: String,
+        input: Prompt,
         selection: String,
         results: Vec<String>,
         config: SearchSelectConfig,
@@ -80,7 +159,7 @@ mod tests {
     }
 
     impl SearchSelectMode<String> for TestMode {
-        fn query(&mut self) -> &mut String {
+        fn query(&mut self) -> &mut Prompt {
             &mut self.input
         }
 
@@ -96,30 +175,38 @@ mod tests {
     }
 
     #[test]
-    fn push_search_char_updates_query() {
+    fn query_insert_updates_query() {
         let mut mode = TestMode{ .. Default::default() };
-        mode.push_search_char('a');
-        assert_eq!(mode.query(), "a");
+        mode.query().insert('a');
+        assert_eq!(&**mode.query(), "a");
     }
 
     #[test]
     fn pop_search_token_pops_all_characters_when_on_only_token() {
-        let mut mode = TestMode{ input: String::from("amp"), .. Default::default() };
+        let mut mode = TestMode{ input: Prompt::new("amp"), .. Default::default() };
         mode.pop_search_token();
-        assert_eq!(mode.query(), "");
+        assert_eq!(&**mode.query(), "");
     }
 
     #[test]
     fn pop_search_token_pops_all_adjacent_non_whitespace_characters_when_on_non_whitespace_character() {
-        let mut mode = TestMode{ input: String::from("amp editor"), .. Default::default() };
+        let mut mode = TestMode{ input: Prompt::new("amp editor"), .. Default::default() };
         mode.pop_search_token();
-        assert_eq!(mode.query(), "amp ");
+        assert_eq!(&**mode.query(), "amp ");
     }
 
     #[test]
     fn pop_search_token_pops_all_whitespace_characters_when_on_whitespace_character() {
-        let mut mode = TestMode{ input: String::from("amp  "), .. Default::default() };
+        let mut mode = TestMode{ input: Prompt::new("amp  "), .. Default::default() };
         mode.pop_search_token();
-        assert_eq!(mode.query(), "amp");
+        assert_eq!(&**mode.query(), "amp");
+    }
+
+    #[test]
+    fn toggling_the_current_result_order_restores_the_default() {
+        let order = ResultOrder::default().toggle(ResultOrder::Path);
+        assert_eq!(order, ResultOrder::Path);
+        assert_eq!(order.toggle(ResultOrder::Recency), ResultOrder::Recency);
+        assert_eq!(order.toggle(ResultOrder::Path), ResultOrder::Score);
     }
 }
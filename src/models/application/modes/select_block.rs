@@ -0,0 +1,92 @@
+use scribe::Buffer;
+use scribe::buffer::{Position, Range};
+use std::cmp;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// A rectangular selection. Its columns are virtual: they can extend
+/// beyond the end of shorter lines, which are padded with spaces when
+/// content is inserted past their end.
+pub struct SelectBlockMode {
+    pub anchor: Position,
+
+    /// The cursor's virtual column, which (unlike the buffer
+    /// cursor's offset) isn't limited by the line's length.
+    pub column: usize,
+
+    /// Content being inserted on each line of the block, if any.
+    pub insert: Option<BlockInsert>,
+}
+
+pub struct BlockInsert {
+    /// The virtual column at which the content will be inserted.
+    pub column: usize,
+    pub content: String,
+}
+
+impl SelectBlockMode {
+    pub fn new(anchor: Position) -> SelectBlockMode {
+        SelectBlockMode { anchor, column: anchor.offset, insert: None }
+    }
+
+    pub fn insert_mode(&self) -> bool {
+        self.insert.is_some()
+    }
+
+    /// The first and last (inclusive) lines in the block.
+    pub fn lines(&self, cursor: &Position) -> (usize, usize) {
+        (cmp::min(self.anchor.line, cursor.line), cmp::max(self.anchor.line, cursor.line))
+    }
+
+    /// The first and last (exclusive) virtual columns in the block.
+    pub fn columns(&self) -> (usize, usize) {
+        (cmp::min(self.anchor.offset, self.column), cmp::max(self.anchor.offset, self.column) + 1)
+    }
+
+    /// The block's content on each line, limited to
+    /// existing content (i.e. excluding virtual space).
+    pub fn ranges(&self, buffer: &Buffer) -> Vec<Range> {
+        let (first_line, last_line) = self.lines(&buffer.cursor);
+        let (start, end) = self.columns();
+        let data = buffer.data();
+
+        data.lines()
+            .chain(Some(""))
+            .enumerate()
+            .skip(first_line)
+            .take(last_line - first_line + 1)
+            .map(|(line, content)| {
+                let length = content.graphemes(true).count();
+                Range::new(
+                    Position { line, offset: cmp::min(start, length) },
+                    Position { line, offset: cmp::min(end, length) },
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SelectBlockMode;
+    use scribe::Buffer;
+    use scribe::buffer::{Position, Range};
+
+    #[test]
+    fn ranges_are_limited_to_existing_content() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nab\namp");
+        buffer.cursor.move_to(Position { line: 2, offset: 1 });
+        let mut mode = SelectBlockMode::new(Position { line: 0, offset: 1 });
+        mode.column = 3;
+
+        assert_eq!(mode.columns(), (1, 4));
+        assert_eq!(
+            mode.ranges(&buffer),
+            vec![
+                Range::new(Position { line: 0, offset: 1 }, Position { line: 0, offset: 4 }),
+                Range::new(Position { line: 1, offset: 1 }, Position { line: 1, offset: 2 }),
+                Range::new(Position { line: 2, offset: 1 }, Position { line: 2, offset: 3 }),
+            ]
+        );
+    }
+}
@@ -0,0 +1,190 @@
+use crate::util::{fuzzy, SelectableVec};
+use crate::util::git_stash::StashEntry;
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+use crate::commands::{self, Command};
+
+pub struct StashMode {
+    insert: bool,
+    input: Prompt,
+    stashes: Vec<StashEntry>,
+    results: SelectableVec<StashEntry>,
+    config: SearchSelectConfig,
+
+    /// Whether the stashes are still being listed in the background.
+    listing: bool,
+}
+
+impl StashMode {
+    pub fn new(config: SearchSelectConfig) -> StashMode {
+        StashMode {
+            insert: true,
+            input: Prompt::default(),
+            stashes: Vec::new(),
+            results: SelectableVec::new(Vec::new()),
+            config,
+            listing: true,
+        }
+    }
+
+    /// Lists the stashes, once they've been found (or found again, e.g. once
+    /// one's been dropped, shifting the others' indices), keeping the query,
+    /// insert mode, and selected position as they are.
+    pub fn set_stashes(&mut self, stashes: Vec<StashEntry>) {
+        let selected_index = self.results.selected_index();
+        self.stashes = stashes;
+        self.listing = false;
+        self.search();
+        self.results.select(selected_index);
+    }
+}
+
+impl fmt::Display for StashMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "STASH")
+    }
+}
+
+impl SearchSelectMode<StashEntry> for StashMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            // There are usually only a handful of stashes;
+            // list them all (newest first) before a query is entered.
+            self.stashes
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            fuzzy::find(&self.input, &self.stashes, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<StashEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&StashEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("apply", commands::search_select::accept), ("drop", commands::search_select::remove_selection)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.listing {
+            Some(String::from("Listing stashes…"))
+        } else if self.stashes.is_empty() {
+            Some(String::from("No stashes found."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+
+    fn preview(&self) -> Option<&str> {
+        self.selection().map(|stash| stash.diff.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StashMode;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use crate::util::git_stash::StashEntry;
+
+    fn entries() -> Vec<StashEntry> {
+        vec![
+            StashEntry { index: 0, message: String::from("On master: renderer"), diff: String::from("+one") },
+            StashEntry { index: 1, message: String::from("On master: preferences"), diff: String::from("+two") },
+        ]
+    }
+
+    fn mode() -> StashMode {
+        let mut mode = StashMode::new(SearchSelectConfig::default());
+        mode.set_stashes(entries());
+
+        mode
+    }
+
+    #[test]
+    fn search_lists_all_stashes_without_a_query() {
+        let mut mode = mode();
+        mode.search();
+
+        assert_eq!(mode.results().count(), 2);
+        assert_eq!(mode.message(), None);
+    }
+
+    #[test]
+    fn search_filters_stashes_using_the_query() {
+        let mut mode = mode();
+        mode.query().insert_str("pref");
+        mode.search();
+
+        assert_eq!(mode.results().collect::<Vec<_>>(), vec![&entries()[1]]);
+    }
+
+    #[test]
+    fn set_stashes_keeps_the_query_insert_mode_and_selected_position() {
+        let mut mode = StashMode::new(SearchSelectConfig::default());
+        assert_eq!(mode.message(), Some(String::from("Listing stashes…")));
+        mode.set_stashes(entries());
+        mode.query().insert_str("On");
+        mode.search();
+        mode.select_next();
+        mode.set_insert_mode(false);
+
+        mode.set_stashes(vec![entries().remove(0)]);
+
+        assert_eq!(&**mode.query(), "On");
+        assert!(!mode.insert_mode());
+        assert_eq!(mode.selection(), Some(&entries()[0]));
+    }
+
+    #[test]
+    fn preview_returns_selected_stash_diff() {
+        let mut mode = mode();
+        mode.search();
+        mode.select_next();
+
+        assert_eq!(mode.preview(), Some("+two"));
+    }
+}
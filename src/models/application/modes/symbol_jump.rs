@@ -1,49 +1,49 @@
-use fragment;
 use fragment::matching::AsStr;
+use scribe::Workspace;
 use scribe::buffer::{Position, Token, TokenSet};
 use syntect::highlighting::ScopeSelectors;
-use crate::util::SelectableVec;
+use crate::util::{fuzzy, SelectableVec};
 use std::fmt;
 use std::iter::Iterator;
-use std::clone::Clone;
 use std::str::FromStr;
 use std::slice::Iter;
 use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
 
 pub struct SymbolJumpMode {
     insert: bool,
-    input: String,
+    input: Prompt,
     symbols: Vec<Symbol>,
     results: SelectableVec<Symbol>,
+
+    /// The characters of each result that matched the query.
+    matches: Vec<Vec<usize>>,
     config: SearchSelectConfig,
 }
 
-#[derive(PartialEq, Debug)]
+#[derive(Clone, PartialEq, Debug)]
 pub struct Symbol {
     pub token: String,
     pub position: Position,
+
+    /// The buffer (ID) defining the symbol, when symbols from
+    /// every open buffer are listed, rather than the current one's.
+    pub buffer: Option<usize>,
+
+    /// What's displayed and searched: the token, prefixed with its
+    /// buffer's path (e.g. "src/main.rs:main") across buffers.
+    label: String,
 }
 
 impl fmt::Display for Symbol {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", &self.token)
-    }
-}
-
-impl Clone for Symbol {
-    fn clone(&self) -> Symbol {
-        Symbol{ token: self.token.clone(), position: self.position }
-    }
-
-    fn clone_from(&mut self, source: &Self) {
-        self.token = source.token.clone();
-        self.position = source.position;
+        write!(f, "{}", &self.label)
     }
 }
 
 impl AsStr for Symbol {
     fn as_str(&self) -> &str {
-        &self.token
+        &self.label
     }
 }
 
@@ -53,9 +53,50 @@ impl SymbolJumpMode {
 
         SymbolJumpMode {
             insert: true,
-            input: String::new(),
+            input: Prompt::default(),
             symbols,
             results: SelectableVec::new(Vec::new()),
+            matches: Vec::new(),
+            config,
+        }
+    }
+
+    /// Lists the symbols defined in every open buffer with syntax support,
+    /// labelled with their buffer's path (relative to the workspace).
+    pub fn across_buffers(workspace: &mut Workspace, config: SearchSelectConfig) -> SymbolJumpMode {
+        let mut entries = Vec::new();
+        let start_id = workspace.current_buffer().and_then(|b| b.id);
+        let root = workspace.path.clone();
+
+        if start_id.is_some() {
+            loop {
+                if let Some(buffer) = workspace.current_buffer() {
+                    if let (Some(id), Ok(tokens)) = (buffer.id, buffer.tokens()) {
+                        let file = match buffer.path {
+                            Some(ref path) => path.strip_prefix(&root).unwrap_or(path).to_string_lossy().into_owned(),
+                            None => format!("scratch-{}", id + 1),
+                        };
+                        entries.extend(symbols(tokens.iter()).into_iter().map(|symbol| Symbol {
+                            buffer: Some(id),
+                            label: format!("{}:{}", file, symbol.token),
+                            ..symbol
+                        }));
+                    }
+                }
+
+                workspace.next_buffer();
+                if workspace.current_buffer().and_then(|b| b.id) == start_id {
+                    break;
+                }
+            }
+        }
+
+        SymbolJumpMode {
+            insert: true,
+            input: Prompt::default(),
+            symbols: entries,
+            results: SelectableVec::new(Vec::new()),
+            matches: Vec::new(),
             config,
         }
     }
@@ -70,14 +111,17 @@ impl fmt::Display for SymbolJumpMode {
 impl SearchSelectMode<Symbol> for SymbolJumpMode {
     fn search(&mut self) {
         // Find the symbols we're looking for using the query.
-        let results = fragment::matching::find(&self.input, &self.symbols, self.config.max_results);
+        let (results, matches): (Vec<Symbol>, _) =
+            fuzzy::find(&self.input, &self.symbols, self.config.max_results)
+                .into_iter()
+                .map(|result| ((*result).clone(), result.indices))
+                .unzip();
 
-        // We don't care about the result objects; we just want
-        // the underlying symbols. Map the collection to get these.
-        self.results = SelectableVec::new(results.into_iter().map(|r| r.clone()).collect());
+        self.results = SelectableVec::new(results);
+        self.matches = matches;
     }
 
-    fn query(&mut self) -> &mut String {
+    fn query(&mut self) -> &mut Prompt {
         &mut self.input
     }
 
@@ -97,6 +141,10 @@ impl SearchSelectMode<Symbol> for SymbolJumpMode {
         self.results.selection()
     }
 
+    fn matched_indices(&self, index: usize) -> Vec<usize> {
+        self.matches.get(index).cloned().unwrap_or_default()
+    }
+
     fn selected_index(&self) -> usize {
         self.results.selected_index()
     }
@@ -125,6 +173,8 @@ fn symbols<'a, T>(tokens: T) -> Vec<Symbol> where T: Iterator<Item=Token<'a>> {
                   return Some(Symbol {
                       token: lexeme.value.to_string(),
                       position: lexeme.position,
+                      buffer: None,
+                      label: lexeme.value.to_string(),
                   })
               }
           }
@@ -137,7 +187,10 @@ fn symbols<'a, T>(tokens: T) -> Vec<Symbol> where T: Iterator<Item=Token<'a>> {
 mod tests {
     use scribe::buffer::{Lexeme, Position, ScopeStack, Token};
     use std::str::FromStr;
-    use super::{Symbol, symbols};
+    use crate::models::application::modes::{SearchSelectConfig, SearchSelectMode};
+    use scribe::{Buffer, Workspace};
+    use std::path::{Path, PathBuf};
+    use super::{SymbolJumpMode, symbols};
 
     #[test]
     fn symbols_are_limited_to_functions() {
@@ -176,6 +229,31 @@ mod tests {
 
         let results = symbols(tokens.into_iter());
         assert_eq!(results.len(), 1);
-        assert_eq!(results.first().unwrap(), &Symbol{ token: "function".to_string(), position: Position{ line: 1, offset: 0 }});
+        assert_eq!(results.first().unwrap().token, "function");
+        assert_eq!(results.first().unwrap().position, Position{ line: 1, offset: 0 });
+        assert_eq!(results.first().unwrap().to_string(), "function");
+    }
+
+    #[test]
+    fn across_buffers_labels_symbols_with_their_buffer_paths() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        for (path, content) in &[("src/main.rs", "fn main() {}\n"), ("src/lib.rs", "fn run() {}\n")] {
+            let mut buffer = Buffer::new();
+            buffer.insert(*content);
+            buffer.path = Some(PathBuf::from(path));
+            workspace.add_buffer(buffer);
+        }
+        let current_id = workspace.current_buffer().unwrap().id;
+
+        let mut mode = SymbolJumpMode::across_buffers(&mut workspace, SearchSelectConfig::default());
+        mode.query().set("run");
+        mode.search();
+        let symbol = mode.selection().unwrap();
+        assert_eq!(symbol.to_string(), "src/lib.rs:run");
+        assert_eq!(symbol.buffer, current_id);
+        assert_eq!(mode.results().count(), 1);
+
+        // The current buffer is left as it was.
+        assert_eq!(workspace.current_buffer().unwrap().id, current_id);
     }
 }
@@ -0,0 +1,160 @@
+use fragment::matching::AsStr;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+use crate::commands::{self, Command};
+use crate::util::{fuzzy, SelectableVec};
+use crate::util::tags::Tag;
+use std::fmt;
+use std::slice::Iter;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct TagEntry {
+    pub tag: Tag,
+
+    /// The tag name, followed by its defining file.
+    label: String,
+}
+
+impl fmt::Display for TagEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for TagEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Lists the workspace's tags, to pick a definition to jump to. Also used to
+/// choose between the definitions of a name that's defined more than once.
+pub struct TagsMode {
+    insert: bool,
+    input: Prompt,
+    tags: Vec<TagEntry>,
+    results: SelectableVec<TagEntry>,
+
+    /// The characters of each result that matched the query.
+    matches: Vec<Vec<usize>>,
+    config: SearchSelectConfig,
+}
+
+impl TagsMode {
+    pub fn new(tags: Vec<Tag>, config: SearchSelectConfig) -> TagsMode {
+        let entries = tags
+            .into_iter()
+            .map(|tag| TagEntry {
+                label: format!("{}  {}", tag.name, tag.path.to_string_lossy()),
+                tag,
+            })
+            .collect();
+
+        TagsMode {
+            insert: true,
+            input: Prompt::default(),
+            tags: entries,
+            results: SelectableVec::new(Vec::new()),
+            matches: Vec::new(),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for TagsMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TAGS")
+    }
+}
+
+impl SearchSelectMode<TagEntry> for TagsMode {
+    fn search(&mut self) {
+        let (results, matches) = if self.input.is_empty() {
+            (self.tags.iter().take(self.config.max_results).cloned().collect(), Vec::new())
+        } else {
+            fuzzy::find(&self.input, &self.tags, self.config.max_results)
+                .into_iter()
+                .map(|result| ((*result).clone(), result.indices))
+                .unzip()
+        };
+
+        self.results = SelectableVec::new(results);
+        self.matches = matches;
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<TagEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&TagEntry> {
+        self.results.selection()
+    }
+
+    fn matched_indices(&self, index: usize) -> Vec<usize> {
+        self.matches.get(index).cloned().unwrap_or_default()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("jump", commands::search_select::accept)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.tags.is_empty() {
+            Some(String::from("No tags found (generate a tags file with ctags -R)."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching tags found."))
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::TagsMode;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use crate::util::tags;
+
+    #[test]
+    fn search_lists_every_tag_until_queried() {
+        let tags = tags::parse("main\tsrc/main.rs\t1\nrender\tsrc/view/mod.rs\t/^fn render() {$/\n");
+        let mut mode = TagsMode::new(tags, SearchSelectConfig::default());
+
+        mode.search();
+        let labels: Vec<String> = mode.results().map(|entry| entry.to_string()).collect();
+        assert_eq!(labels, vec!["main  src/main.rs", "render  src/view/mod.rs"]);
+
+        mode.query().set("rend");
+        mode.search();
+        let names: Vec<&str> = mode.results().map(|entry| entry.tag.name.as_str()).collect();
+        assert_eq!(names, vec!["render"]);
+    }
+}
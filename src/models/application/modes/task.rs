@@ -0,0 +1,278 @@
+use fragment::matching::AsStr;
+use regex::Regex;
+use crate::util::{fuzzy, tools, SelectableVec};
+use std::fmt;
+use std::fs;
+use std::path::Path;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+use crate::yaml::yaml::YamlLoader;
+
+pub struct TaskMode {
+    insert: bool,
+    input: Prompt,
+    tasks: Vec<Task>,
+    results: SelectableVec<Task>,
+    config: SearchSelectConfig,
+}
+
+/// A named task defined by a Makefile, justfile, or package.json script.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Task {
+    pub name: String,
+    pub command: String,
+}
+
+impl fmt::Display for Task {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.command)
+    }
+}
+
+impl AsStr for Task {
+    fn as_str(&self) -> &str {
+        &self.command
+    }
+}
+
+impl TaskMode {
+    pub fn new(path: &Path, config: SearchSelectConfig) -> TaskMode {
+        TaskMode::with_tasks(tasks(path), config)
+    }
+
+    fn with_tasks(tasks: Vec<Task>, config: SearchSelectConfig) -> TaskMode {
+        TaskMode {
+            insert: true,
+            input: Prompt::default(),
+            tasks,
+            results: SelectableVec::new(Vec::new()),
+            config,
+        }
+    }
+}
+
+impl fmt::Display for TaskMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TASK")
+    }
+}
+
+impl SearchSelectMode<Task> for TaskMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            // Projects usually only define a handful of
+            // tasks; list them all before a query is entered.
+            self.tasks
+                .iter()
+                .take(self.config.max_results)
+                .cloned()
+                .collect()
+        } else {
+            // We don't care about the result objects; we just want
+            // the underlying tasks. Map the collection to get these.
+            fuzzy::find(&self.input, &self.tasks, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<Task> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&Task> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.tasks.is_empty() {
+            Some(String::from("No Makefile, justfile, or package.json scripts found."))
+        } else if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
+
+/// Collects the tasks defined in the specified directory. Their names
+/// come from the project's files, so they're quoted in the commands.
+fn tasks(path: &Path) -> Vec<Task> {
+    let mut tasks = Vec::new();
+
+    if let Ok(content) = fs::read_to_string(path.join("Makefile")) {
+        tasks.extend(make_targets(&content).into_iter().map(|name| {
+            Task { command: format!("make {}", tools::shell_quote(&name)), name }
+        }));
+    }
+
+    let justfile = ["justfile", "Justfile"]
+        .iter()
+        .filter_map(|name| fs::read_to_string(path.join(name)).ok())
+        .next();
+    if let Some(content) = justfile {
+        tasks.extend(just_recipes(&content).into_iter().map(|name| {
+            Task { command: format!("just {}", tools::shell_quote(&name)), name }
+        }));
+    }
+
+    if let Ok(content) = fs::read_to_string(path.join("package.json")) {
+        tasks.extend(package_scripts(&content).into_iter().map(|name| {
+            Task { command: format!("npm run {}", tools::shell_quote(&name)), name }
+        }));
+    }
+
+    tasks
+}
+
+fn make_targets(content: &str) -> Vec<String> {
+    lazy_static! {
+        static ref TARGET: Regex = Regex::new(r"^([A-Za-z0-9_][A-Za-z0-9_.-]*)\s*:").unwrap();
+    }
+
+    content.lines().filter_map(|line| {
+        TARGET.captures(line).and_then(|captures| {
+            // Skip variable assignments (e.g. "CC := gcc").
+            let name = captures.at(1)?;
+            if line[name.len()..].trim_start().starts_with(":=") {
+                None
+            } else {
+                Some(name.to_string())
+            }
+        })
+    }).collect()
+}
+
+fn just_recipes(content: &str) -> Vec<String> {
+    lazy_static! {
+        static ref RECIPE: Regex = Regex::new(r"^@?([A-Za-z0-9_-]+)[^:=]*:").unwrap();
+    }
+
+    content.lines().filter_map(|line| {
+        let captures = RECIPE.captures(line)?;
+        let name = captures.at(1)?;
+
+        // Skip variable assignments (e.g. "version := 1").
+        if line.contains(":=") {
+            None
+        } else {
+            Some(name.to_string())
+        }
+    }).collect()
+}
+
+fn package_scripts(content: &str) -> Vec<String> {
+    // JSON is (for our purposes) a subset of YAML.
+    YamlLoader::load_from_str(content)
+        .ok()
+        .and_then(|documents| documents.into_iter().next())
+        .and_then(|document| {
+            document["scripts"].as_hash().map(|scripts| {
+                scripts.keys().filter_map(|key| key.as_str().map(String::from)).collect()
+            })
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{just_recipes, make_targets, package_scripts, tasks, Task, TaskMode};
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+    use std::fs;
+    use std::path::Path;
+
+    #[test]
+    fn tasks_quote_names_in_their_commands() {
+        let root = Path::new(env!("OUT_DIR")).join("task_names");
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("package.json"), "{\"scripts\": {\"x; rm -rf ~\": \"true\"}}").unwrap();
+
+        assert_eq!(tasks(&root), vec![
+            Task { name: String::from("x; rm -rf ~"), command: String::from("npm run 'x; rm -rf ~'") },
+        ]);
+    }
+
+    #[test]
+    fn make_targets_skips_variables_and_special_targets() {
+        let content = "CC := gcc\n.PHONY: build\nbuild: main.o\n\tcc main.o\nclean:\n\trm -f *.o\n";
+
+        assert_eq!(make_targets(content), vec!["build", "clean"]);
+    }
+
+    #[test]
+    fn just_recipes_includes_recipes_with_arguments() {
+        let content = "version := \"1.0\"\n\ntest:\n    cargo test\n\n@release tag:\n    git tag {{tag}}\n";
+
+        assert_eq!(just_recipes(content), vec!["test", "release"]);
+    }
+
+    #[test]
+    fn package_scripts_returns_script_names() {
+        let content = "{\n  \"name\": \"amp\",\n  \"scripts\": {\n    \"build\": \"webpack\",\n    \"test\": \"jest\"\n  }\n}";
+
+        assert_eq!(package_scripts(content), vec!["build", "test"]);
+    }
+
+    #[test]
+    fn package_scripts_handles_invalid_content() {
+        assert!(package_scripts("{").is_empty());
+    }
+
+    #[test]
+    fn search_lists_all_tasks_without_a_query() {
+        let tasks = vec![
+            Task { name: String::from("build"), command: String::from("make build") },
+            Task { name: String::from("test"), command: String::from("npm run test") },
+        ];
+        let mut mode = TaskMode::with_tasks(tasks.clone(), SearchSelectConfig::default());
+        mode.search();
+
+        assert_eq!(mode.results().cloned().collect::<Vec<_>>(), tasks);
+        assert_eq!(mode.message(), None);
+    }
+
+    #[test]
+    fn search_matches_task_commands() {
+        let tasks = vec![
+            Task { name: String::from("build"), command: String::from("make build") },
+            Task { name: String::from("test"), command: String::from("npm run test") },
+        ];
+        let mut mode = TaskMode::with_tasks(tasks, SearchSelectConfig::default());
+        mode.query().insert_str("npm");
+        mode.search();
+
+        assert_eq!(mode.selection().map(|t| t.name.as_str()), Some("test"));
+    }
+}
@@ -1,22 +1,25 @@
-use fragment;
-use crate::util::SelectableVec;
+use crate::util::{fuzzy, SelectableVec};
 use std::fmt;
 use std::slice::Iter;
 use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
 
 pub struct ThemeMode {
+    /// The theme in use before browsing, restored if none is accepted.
+    pub original_theme: String,
     insert: bool,
-    input: String,
+    input: Prompt,
     themes: Vec<String>,
     results: SelectableVec<String>,
     config: SearchSelectConfig,
 }
 
 impl ThemeMode {
-    pub fn new(themes: Vec<String>, config: SearchSelectConfig) -> ThemeMode {
+    pub fn new(themes: Vec<String>, original_theme: String, config: SearchSelectConfig) -> ThemeMode {
         ThemeMode {
+            original_theme,
             insert: true,
-            input: String::new(),
+            input: Prompt::default(),
             themes,
             results: SelectableVec::new(Vec::new()),
             config,
@@ -33,7 +36,7 @@ impl fmt::Display for ThemeMode {
 impl SearchSelectMode<String> for ThemeMode {
     fn search(&mut self) {
         // Find the themes we're looking for using the query.
-        let results = fragment::matching::find(&self.input, &self.themes, self.config.max_results);
+        let results = fuzzy::find(&self.input, &self.themes, self.config.max_results);
 
         // We don't care about the result objects; we just want
         // the underlying symbols. Map the collection to get these.
@@ -45,7 +48,7 @@ impl SearchSelectMode<String> for ThemeMode {
         );
     }
 
-    fn query(&mut self) -> &mut String {
+    fn query(&mut self) -> &mut Prompt {
         &mut self.input
     }
 
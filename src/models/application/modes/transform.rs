@@ -0,0 +1,97 @@
+use scribe::buffer::Range;
+use crate::util::{fuzzy, SelectableVec};
+use crate::util::transform::Transform;
+use std::fmt;
+use std::slice::Iter;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::Prompt;
+
+pub struct TransformMode {
+    insert: bool,
+    input: Prompt,
+    transforms: Vec<Transform>,
+    results: SelectableVec<Transform>,
+    config: SearchSelectConfig,
+
+    /// The selection to which the chosen transform will be applied.
+    pub range: Range,
+}
+
+impl TransformMode {
+    pub fn new(range: Range, config: SearchSelectConfig) -> TransformMode {
+        TransformMode {
+            insert: true,
+            input: Prompt::default(),
+            transforms: Transform::all(),
+            results: SelectableVec::new(Vec::new()),
+            config,
+            range,
+        }
+    }
+}
+
+impl fmt::Display for TransformMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "TRANSFORM")
+    }
+}
+
+impl SearchSelectMode<Transform> for TransformMode {
+    fn search(&mut self) {
+        let results = if self.input.is_empty() {
+            // There are only a handful of transforms; list them all.
+            self.transforms.clone()
+        } else {
+            fuzzy::find(&self.input, &self.transforms, self.config.max_results)
+                .into_iter()
+                .map(|r| **r)
+                .collect()
+        };
+
+        self.results = SelectableVec::new(results);
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<Transform> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&Transform> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.results.is_empty() {
+            Some(String::from("No matching entries found."))
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,225 @@
+use crate::errors::*;
+use fragment::matching::AsStr;
+use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+use crate::models::application::{Prompt, UndoTree};
+use crate::commands::{self, Command};
+use crate::util::diff;
+use crate::util::{fuzzy, generate, SelectableVec};
+use std::collections::HashMap;
+use std::fmt;
+use std::slice::Iter;
+
+/// A state in a buffer's undo tree, indented to show the branch it's on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct UndoTreeEntry {
+    /// The state's index in the tree.
+    pub index: usize,
+    pub label: String,
+
+    /// The changes made by the state, relative to the one it branched from.
+    pub diff: String,
+}
+
+impl fmt::Display for UndoTreeEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.label)
+    }
+}
+
+impl AsStr for UndoTreeEntry {
+    fn as_str(&self) -> &str {
+        &self.label
+    }
+}
+
+/// Lists the states in the current buffer's undo tree, including those on
+/// branches abandoned by undoing and then editing. Selecting one restores it.
+pub struct UndoTreeMode {
+    insert: bool,
+    input: Prompt,
+    entries: Vec<UndoTreeEntry>,
+    results: SelectableVec<UndoTreeEntry>,
+    config: SearchSelectConfig,
+
+    /// The index of the current state's entry.
+    current: usize,
+    pub buffer_id: usize,
+}
+
+impl UndoTreeMode {
+    pub fn new(buffer_id: usize, tree: &UndoTree, time_format: &str, config: SearchSelectConfig) -> Result<UndoTreeMode> {
+        let mut entries = Vec::new();
+        let mut current = 0;
+        let layout = tree.layout();
+
+        // States are laid out after their parents, so each one's content is
+        // built from its parent's, which is only kept until its children are.
+        let mut remaining_children = vec![0; layout.len()];
+        for state in tree.states() {
+            if let Some(parent) = state.parent {
+                remaining_children[parent] += 1;
+            }
+        }
+        let mut contents: HashMap<usize, String> = HashMap::new();
+
+        for (index, depth) in layout {
+            let state = &tree.states()[index];
+            let original = state.parent.and_then(|parent| contents.get(&parent));
+            let content = match original {
+                Some(original) => state.apply_to(original),
+                None => tree.content(index),
+            };
+            let (added, removed) = state.changed_line_counts();
+            let summary = if state.parent.is_none() {
+                String::from("original")
+            } else {
+                format!("+{} -{} lines", added, removed)
+            };
+            if index == tree.current() {
+                current = entries.len();
+            }
+
+            entries.push(UndoTreeEntry {
+                index,
+                label: format!(
+                    "{}{} {} {}",
+                    "  ".repeat(depth),
+                    if index == tree.current() { "●" } else { "○" },
+                    generate::format_time(state.time, time_format)?,
+                    summary
+                ),
+                diff: original
+                    .map(|original| diff::unified(original, &content, 2))
+                    .unwrap_or_default(),
+            });
+
+            if let Some(parent) = state.parent {
+                remaining_children[parent] -= 1;
+                if remaining_children[parent] == 0 {
+                    contents.remove(&parent);
+                }
+            }
+            if remaining_children[index] > 0 {
+                contents.insert(index, content);
+            }
+        }
+
+        Ok(UndoTreeMode::with_entries(buffer_id, entries, current, config))
+    }
+
+    fn with_entries(buffer_id: usize, entries: Vec<UndoTreeEntry>, current: usize, config: SearchSelectConfig) -> UndoTreeMode {
+        UndoTreeMode {
+            insert: false,
+            input: Prompt::default(),
+            entries,
+            results: SelectableVec::new(Vec::new()),
+            config,
+            current,
+            buffer_id,
+        }
+    }
+}
+
+impl fmt::Display for UndoTreeMode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "UNDO TREE")
+    }
+}
+
+impl SearchSelectMode<UndoTreeEntry> for UndoTreeMode {
+    fn search(&mut self) {
+        if self.input.is_empty() {
+            // List the states surrounding the current one, with it selected.
+            let first = self.current.saturating_sub(self.config.max_results / 2);
+            let first = first.min(self.entries.len().saturating_sub(self.config.max_results));
+            let results = self.entries
+                .iter()
+                .skip(first)
+                .take(self.config.max_results)
+                .cloned()
+                .collect();
+            self.results = SelectableVec::new(results);
+            self.results.select(self.current - first);
+        } else {
+            let results = fuzzy::find(&self.input, &self.entries, self.config.max_results)
+                .into_iter()
+                .map(|r| r.clone())
+                .collect();
+            self.results = SelectableVec::new(results);
+        }
+    }
+
+    fn query(&mut self) -> &mut Prompt {
+        &mut self.input
+    }
+
+    fn insert_mode(&self) -> bool {
+        self.insert
+    }
+
+    fn set_insert_mode(&mut self, insert_mode: bool) {
+        self.insert = insert_mode;
+    }
+
+    fn results(&self) -> Iter<UndoTreeEntry> {
+        self.results.iter()
+    }
+
+    fn selection(&self) -> Option<&UndoTreeEntry> {
+        self.results.selection()
+    }
+
+    fn selected_index(&self) -> usize {
+        self.results.selected_index()
+    }
+
+    fn select_previous(&mut self) {
+        self.results.select_previous();
+    }
+
+    fn select_next(&mut self) {
+        self.results.select_next();
+    }
+
+    fn config(&self) -> &SearchSelectConfig {
+        &self.config
+    }
+
+    fn actions(&self) -> Vec<(&'static str, Command)> {
+        vec![("restore", commands::search_select::accept)]
+    }
+
+    fn message(&mut self) -> Option<String> {
+        if self.results.is_empty() {
+            Some(String::from("No matching states found."))
+        } else {
+            None
+        }
+    }
+
+    fn preview(&self) -> Option<&str> {
+        self.selection().map(|entry| entry.diff.as_str()).filter(|diff| !diff.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::UndoTreeMode;
+    use crate::models::application::UndoTree;
+    use crate::models::application::modes::{SearchSelectMode, SearchSelectConfig};
+
+    #[test]
+    fn search_lists_states_by_branch_with_the_current_one_selected() {
+        let mut tree = UndoTree::new("amp\n");
+        tree.record("amp\neditor\n");
+        tree.record("amp\n");
+        tree.record("text\n");
+        let mut mode = UndoTreeMode::new(0, &tree, "", SearchSelectConfig::default()).unwrap();
+        mode.search();
+
+        let labels: Vec<String> = mode.results().map(|entry| entry.label.clone()).collect();
+        assert_eq!(labels, vec!["○  original", "○  +1 -0 lines", "  ●  +1 -1 lines"]);
+        assert_eq!(mode.selection().map(|entry| entry.index), Some(2));
+        assert_eq!(mode.preview(), Some("@@ -1 +1 @@\n-amp\n+text\n"));
+    }
+}
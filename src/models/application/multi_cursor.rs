@@ -0,0 +1,137 @@
+use crate::util::PositionMap;
+use scribe::Buffer;
+use scribe::buffer::{Position, Range};
+
+/// Cursors placed in a buffer in addition to its own (primary) cursor.
+/// Edits made through `edit` are applied at every cursor at once, and
+/// are grouped so that they're undone together.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MultiCursor {
+    pub positions: Vec<Position>,
+}
+
+impl MultiCursor {
+    /// Adds a cursor, unless there's already one at the position.
+    pub fn add(&mut self, position: Position) {
+        if !self.positions.contains(&position) {
+            self.positions.push(position);
+            self.positions.sort_by_key(|position| (position.line, position.offset));
+        }
+    }
+
+    /// Single-character ranges at each of the extra cursors, used to draw them.
+    pub fn highlights(&self) -> Vec<Range> {
+        self.positions
+            .iter()
+            .map(|position| {
+                Range::new(*position, Position { line: position.line, offset: position.offset + 1 })
+            })
+            .collect()
+    }
+
+    /// Replaces content around each cursor (including the buffer's own). Given
+    /// the buffer's content and a cursor's byte offset, the closure returns the
+    /// byte range to replace and its replacement; the cursor is left after it.
+    pub fn edit<F>(&mut self, buffer: &mut Buffer, edit: F)
+        where F: Fn(&str, usize) -> (usize, usize, String)
+    {
+        let data = buffer.data();
+        let positions = PositionMap::new(&data);
+
+        // Work out each cursor's edit, with the buffer's cursor first,
+        // ordered by position so that their ranges don't overlap.
+        let mut edits: Vec<(usize, usize, usize, String)> = Some(*buffer.cursor)
+            .into_iter()
+            .chain(self.positions.iter().cloned())
+            .enumerate()
+            .map(|(cursor, position)| {
+                let (start, end, content) = edit(&data, positions.offset(&position));
+                (start, end, cursor, content)
+            })
+            .collect();
+        edits.sort_by_key(|&(start, end, cursor, _)| (start, end, cursor));
+        let mut previous_end = 0;
+        for edit in edits.iter_mut() {
+            edit.0 = edit.0.max(previous_end);
+            edit.1 = edit.1.max(edit.0);
+            previous_end = edit.1;
+        }
+
+        // Apply the edits last to first, so that earlier positions remain valid.
+        buffer.start_operation_group();
+        for &(start, end, _, ref content) in edits.iter().rev() {
+            let start_position = positions.position(start);
+            if end > start {
+                buffer.delete_range(Range::new(start_position, positions.position(end)));
+            }
+            if !content.is_empty() {
+                buffer.cursor.move_to(start_position);
+                buffer.insert(content.clone());
+            }
+        }
+        buffer.end_operation_group();
+
+        // Move each cursor past its replacement, accounting
+        // for the content added or removed before it.
+        let data = buffer.data();
+        let positions = PositionMap::new(&data);
+        let mut cursors = vec![Position::new(); edits.len()];
+        let mut shift: isize = 0;
+        for &(start, end, cursor, ref content) in edits.iter() {
+            let offset = (start as isize + shift) as usize + content.len();
+            cursors[cursor] = positions.position(offset);
+            shift += content.len() as isize - (end - start) as isize;
+        }
+
+        buffer.cursor.move_to(cursors[0]);
+        self.positions.clear();
+        for position in cursors.into_iter().skip(1) {
+            if position != *buffer.cursor {
+                self.add(position);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::MultiCursor;
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn edit_applies_changes_at_every_cursor_as_a_single_operation() {
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\namp\namp");
+        buffer.cursor.move_to(Position { line: 1, offset: 3 });
+        let mut cursors = MultiCursor::default();
+        cursors.add(Position { line: 2, offset: 3 });
+        cursors.add(Position { line: 0, offset: 3 });
+
+        cursors.edit(&mut buffer, |_, offset| (offset, offset, String::from("s\n")));
+        assert_eq!(buffer.data(), "amps\n\namps\n\namps\n");
+        assert_eq!(*buffer.cursor, Position { line: 3, offset: 0 });
+        assert_eq!(cursors.positions, vec![Position { line: 1, offset: 0 }, Position { line: 5, offset: 0 }]);
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "amp\namp\namp");
+    }
+
+    #[test]
+    fn edit_merges_overlapping_deletions() {
+        let mut buffer = Buffer::new();
+        buffer.insert("ampé");
+        buffer.cursor.move_to(Position { line: 0, offset: 4 });
+        let mut cursors = MultiCursor::default();
+        cursors.add(Position { line: 0, offset: 3 });
+
+        // Deleting the two graphemes before each cursor overlaps.
+        cursors.edit(&mut buffer, |data, offset| {
+            let start = data[..offset].char_indices().rev().nth(1).map(|(i, _)| i).unwrap_or(0);
+            (start, offset, String::new())
+        });
+        assert_eq!(buffer.data(), "a");
+        assert_eq!(*buffer.cursor, Position { line: 0, offset: 1 });
+        assert!(cursors.positions.is_empty());
+    }
+}
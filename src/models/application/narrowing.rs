@@ -0,0 +1,117 @@
+use scribe::buffer::{LineRange, Position};
+
+/// A buffer narrowed to some of its lines. The content before and after
+/// them is hidden, and protected from editing, until the buffer is widened.
+/// It's tracked as the hidden content itself (rather than line numbers),
+/// so that the narrowed lines can be edited freely, adding or removing lines.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Narrowing {
+    before: String,
+    after: String,
+}
+
+impl Narrowing {
+    /// Narrows the content to the lines, inclusively.
+    pub fn new(data: &str, lines: &LineRange) -> Narrowing {
+        let start = line_offset(data, lines.start());
+        let end = line_offset(data, lines.end() + 1);
+
+        Narrowing {
+            before: data[..start].to_string(),
+            after: data[end..].to_string(),
+        }
+    }
+
+    /// Whether the hidden content is unchanged, and still on lines of its own.
+    pub fn intact(&self, data: &str) -> bool {
+        data.len() >= self.before.len() + self.after.len()
+            && data.starts_with(&self.before)
+            && data.ends_with(&self.after)
+            && (self.after.is_empty() || data[..data.len() - self.after.len()].ends_with('\n'))
+    }
+
+    /// The narrowed lines of the (intact) content.
+    pub fn lines(&self, data: &str) -> LineRange {
+        let start = self.before.matches('\n').count();
+        let hidden_after = if self.after.is_empty() {
+            0
+        } else {
+            self.after.matches('\n').count() + 1
+        };
+        let line_count = data.matches('\n').count() + 1;
+
+        LineRange::new(start, line_count.saturating_sub(hidden_after + 1).max(start))
+    }
+
+    /// Moves the position onto the narrowed lines, if it's outside of them.
+    pub fn clamp(&self, position: Position, data: &str) -> Position {
+        let lines = self.lines(data);
+        if position.line < lines.start() {
+            Position { line: lines.start(), offset: 0 }
+        } else if position.line > lines.end() {
+            Position { line: lines.end(), offset: 0 }
+        } else {
+            position
+        }
+    }
+}
+
+// The byte offset at which the line starts, or the
+// end of the content, if it doesn't have that many.
+fn line_offset(data: &str, line: usize) -> usize {
+    if line == 0 {
+        return 0;
+    }
+
+    data.match_indices('\n')
+        .nth(line - 1)
+        .map(|(index, _)| index + 1)
+        .unwrap_or(data.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Narrowing;
+    use scribe::buffer::{LineRange, Position};
+
+    const DATA: &str = "use std;\n\nfn main() {\n    run();\n}\n\nfn run() {}\n";
+
+    #[test]
+    fn lines_follow_edits_within_the_narrowed_lines() {
+        let narrowing = Narrowing::new(DATA, &LineRange::new(4, 2));
+        assert_eq!(narrowing.lines(DATA), LineRange::new(2, 4));
+
+        let edited = DATA.replace("    run();\n", "    run();\n    run();\n");
+        assert!(narrowing.intact(&edited));
+        assert_eq!(narrowing.lines(&edited), LineRange::new(2, 5));
+    }
+
+    #[test]
+    fn intact_rejects_changes_to_hidden_content() {
+        let narrowing = Narrowing::new(DATA, &LineRange::new(2, 4));
+
+        assert!(!narrowing.intact(&DATA.replace("use std;", "use core;")));
+        assert!(!narrowing.intact(&DATA.replace("fn run", "fn go")));
+
+        // Joining the last narrowed line with the next one changes
+        // the latter, even though its content is still there.
+        assert!(!narrowing.intact(&DATA.replace("}\n\nfn run", "}\nfn run")));
+    }
+
+    #[test]
+    fn narrowing_to_the_last_line_hides_nothing_after_it() {
+        let narrowing = Narrowing::new(DATA, &LineRange::new(6, 7));
+
+        assert!(narrowing.intact(&format!("{}// done\n", DATA)));
+        assert_eq!(narrowing.lines(DATA), LineRange::new(6, 7));
+    }
+
+    #[test]
+    fn clamp_moves_positions_onto_the_narrowed_lines() {
+        let narrowing = Narrowing::new(DATA, &LineRange::new(2, 4));
+
+        assert_eq!(narrowing.clamp(Position { line: 0, offset: 3 }, DATA), Position { line: 2, offset: 0 });
+        assert_eq!(narrowing.clamp(Position { line: 3, offset: 4 }, DATA), Position { line: 3, offset: 4 });
+        assert_eq!(narrowing.clamp(Position { line: 6, offset: 1 }, DATA), Position { line: 4, offset: 0 });
+    }
+}
@@ -1,12 +1,13 @@
 use app_dirs::{app_dir, app_root, get_app_root, AppDataType, AppInfo};
 use bloodhound::ExclusionPattern;
 use crate::errors::*;
-use crate::input::KeyMap;
+use crate::input::{Key, KeyMap, SnippetBinding, Snippets};
 use crate::models::application::modes::open;
 use scribe::Buffer;
-use std::fs::OpenOptions;
+use std::fs::{self, OpenOptions};
 use std::io::Read;
 use std::path::PathBuf;
+use std::time::Duration;
 use crate::yaml::yaml::{Hash, Yaml, YamlLoader};
 use crate::models::application::modes::SearchSelectConfig;
 
@@ -14,21 +15,125 @@ const APP_INFO: AppInfo = AppInfo {
     name: "amp",
     author: "Jordan MacDonald",
 };
+const BELL_KEY: &str = "bell";
+const CLIPBOARD_KEY: &str = "clipboard";
+const COLLABORATION_ADDRESS_DEFAULT: &str = "127.0.0.1:4242";
+const COLLABORATION_ADDRESS_KEY: &str = "collaboration_address";
+const CURSOR_PAST_END_OF_LINE_DEFAULT: bool = true;
+const CURSOR_PAST_END_OF_LINE_KEY: &str = "cursor_past_end_of_line";
+const DATE_FORMAT_DEFAULT: &str = "%Y-%m-%d";
+const DATE_FORMAT_KEY: &str = "date_format";
+const DATETIME_FORMAT_DEFAULT: &str = "%Y-%m-%d %H:%M:%S";
+const DATETIME_FORMAT_KEY: &str = "datetime_format";
+const DECRYPT_COMMAND_KEY: &str = "decrypt_command";
+const DETECT_INDENTATION_KEY: &str = "detect_indentation";
+const EDIT_LOG_KEY: &str = "edit_log";
+const ENCRYPT_COMMAND_KEY: &str = "encrypt_command";
+const EVICT_IDLE_BUFFERS_AFTER_KEY: &str = "evict_idle_buffers_after";
 const FILE_NAME: &str = "config.yml";
+const FILE_TREE_WIDTH_DEFAULT: usize = 30;
+const FILE_TREE_WIDTH_KEY: &str = "file_tree_width";
+const GIT_GUTTER_KEY: &str = "git_gutter";
+const KEY_SEQUENCE_TIMEOUT_DEFAULT: u64 = 1000;
+const KEY_SEQUENCE_HINTS_DEFAULT: bool = true;
+const KEY_SEQUENCE_HINTS_KEY: &str = "key_sequence_hints";
+const KEY_SEQUENCE_TIMEOUT_KEY: &str = "key_sequence_timeout";
+const KEYMAP_KEY: &str = "keymap";
+const KEYMAP_PRESET_DEFAULT: &str = "default";
+const KEYMAP_PRESET_KEY: &str = "keymap_preset";
+const LANGUAGE_SERVER_KEY: &str = "language_server";
 const LINE_LENGTH_GUIDE_DEFAULT: usize = 80;
 const LINE_LENGTH_GUIDE_KEY: &str = "line_length_guide";
+const LINE_NUMBERS_KEY: &str = "line_numbers";
 const LINE_WRAPPING_DEFAULT: bool = true;
 const LINE_WRAPPING_KEY: &str = "line_wrapping";
+const MOUSE_DEFAULT: bool = true;
+const MOUSE_KEY: &str = "mouse";
+const PROFILE_WINDOW_DEFAULT: u64 = 30;
+const READ_ONLY_DIRECTORIES_DEFAULT: [&str; 3] = ["node_modules", "target", "vendor"];
+const READ_ONLY_DIRECTORIES_KEY: &str = "read_only_directories";
+const PROFILE_WINDOW_KEY: &str = "profile_window";
 const SEARCH_SELECT_KEY: &str = "search_select";
+const SEARCH_WRAP_BELL_KEY: &str = "search_wrap_bell";
+const SEARCH_WRAP_DEFAULT: bool = true;
+const SEARCH_WRAP_KEY: &str = "search_wrap";
 const SOFT_TABS_DEFAULT: bool = true;
 const SOFT_TABS_KEY: &str = "soft_tabs";
 const SYNTAX_PATH: &str = "syntaxes";
+const TEST_COMMAND_KEY: &str = "test_command";
 const TAB_WIDTH_DEFAULT: usize = 2;
 const TAB_WIDTH_KEY: &str = "tab_width";
 const THEME_DEFAULT: &str = "solarized_dark";
 const THEME_KEY: &str = "theme";
 const THEME_PATH: &str = "themes";
 const TYPES_KEY: &str = "types";
+const UNBOUND_KEY_HINT_DEFAULT: bool = true;
+const UNBOUND_KEY_HINT_KEY: &str = "unbound_key_hint";
+const WORD_COUNT_KEY: &str = "word_count";
+
+// File types whose word counts are shown when the status line segment is enabled.
+const PROSE_EXTENSIONS: [&str; 4] = ["markdown", "md", "text", "txt"];
+
+// Preferences that can be changed for the rest of the session, and whether
+// they're switches (i.e. only accept true or false).
+const SESSION_SETTINGS: [(&str, bool); 15] = [
+    (BELL_KEY, false),
+    (CURSOR_PAST_END_OF_LINE_KEY, true),
+    (DETECT_INDENTATION_KEY, true),
+    (EDIT_LOG_KEY, true),
+    (KEY_SEQUENCE_HINTS_KEY, true),
+    (KEY_SEQUENCE_TIMEOUT_KEY, false),
+    (LINE_LENGTH_GUIDE_KEY, false),
+    (LINE_NUMBERS_KEY, false),
+    (LINE_WRAPPING_KEY, false),
+    (SEARCH_WRAP_KEY, true),
+    (SOFT_TABS_KEY, true),
+    (TAB_WIDTH_KEY, false),
+    (THEME_KEY, false),
+    (UNBOUND_KEY_HINT_KEY, true),
+    (WORD_COUNT_KEY, true),
+];
+
+/// How the editor signals something the user may not have expected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Bell {
+    /// Rings the terminal's bell.
+    Audible,
+
+    /// Shows a message in the status line.
+    Visual,
+
+    /// Briefly inverts the status line.
+    Flash,
+}
+
+impl Bell {
+    fn parse(value: &Yaml) -> Option<Bell> {
+        match value.as_str() {
+            Some("audible") => Some(Bell::Audible),
+            Some("visual") => Some(Bell::Visual),
+            Some("flash") => Some(Bell::Flash),
+            _ => None,
+        }
+    }
+}
+
+/// How buffer lines are numbered.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineNumberMode {
+    /// Numbers every line from the start of the buffer.
+    Absolute,
+
+    /// Numbers lines by their distance from the cursor line, which is zero.
+    Relative,
+
+    /// Numbers lines by their distance from the cursor
+    /// line, which is numbered from the start of the buffer.
+    Hybrid,
+
+    /// Leaves lines unnumbered.
+    Hidden,
+}
 
 /// Loads, creates, and provides default values for application preferences.
 /// Values are immutable once loaded, with the exception of those that provide
@@ -36,15 +141,18 @@ const TYPES_KEY: &str = "types";
 pub struct Preferences {
     data: Option<Yaml>,
     keymap: KeyMap,
+    snippets: Snippets,
     theme: Option<String>,
 }
 
 impl Preferences {
-    /// Builds a new in-memory instance with default values.
+    /// Builds a new in-memory instance from the provided data, using
+    /// default values for anything it doesn't specify.
     pub fn new(data: Option<Yaml>) -> Preferences {
         Preferences {
+            keymap: load_keymap(data.as_ref()).expect("Failed to load keymap!"),
+            snippets: load_snippets(data.as_ref()).expect("Failed to load snippets!"),
             data,
-            keymap: KeyMap::default().expect("Failed to load default keymap!"),
             theme: None
         }
     }
@@ -52,38 +160,76 @@ impl Preferences {
     /// Loads preferences from disk, returning any filesystem or parse errors.
     pub fn load() -> Result<Preferences> {
         let data = load_document()?;
-        let keymap = load_keymap(
-            data.as_ref().and_then(|data| data["keymap"].as_hash())
-        )?;
+        let keymap = load_keymap(data.as_ref())?;
+        let snippets = load_snippets(data.as_ref())?;
 
-        Ok(Preferences { data, keymap, theme: None })
+        Ok(Preferences { data, keymap, snippets, theme: None })
     }
 
     /// Reloads all user preferences from disk and merges them with defaults.
     pub fn reload(&mut self) -> Result<()> {
         let data = load_document()?;
-        let keymap = load_keymap(
-            data.as_ref().and_then(|data| data["keymap"].as_hash())
-        )?;
+        let keymap = load_keymap(data.as_ref())?;
+        let snippets = load_snippets(data.as_ref())?;
 
         self.data = data;
         self.keymap = keymap;
+        self.snippets = snippets;
         self.theme = None;
 
         Ok(())
     }
 
+    /// Binds a key sequence to a command in the user's config file, so that
+    /// it takes effect right away and in future sessions. The file is only
+    /// written if the resulting keymap loads successfully.
+    pub fn add_key_binding(&mut self, mode: &str, keys: &[Key], command: &str) -> Result<()> {
+        let mut config_path = Preferences::directory()?;
+        config_path.push(FILE_NAME);
+        let content = fs::read_to_string(&config_path).unwrap_or_default();
+
+        let keys = keys.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(" ");
+        let content = insert_key_binding(&content, mode, &keys, command)?;
+        let data = YamlLoader::load_from_str(&content)
+            .chain_err(|| "Couldn't parse config file with the new key binding")?
+            .into_iter()
+            .nth(0);
+        let keymap = load_keymap(data.as_ref())?;
+        fs::write(&config_path, content).chain_err(|| "Couldn't write config file")?;
+
+        self.data = data;
+        self.keymap = keymap;
+
+        Ok(())
+    }
+
     /// Read-only keymap accessor method.
     pub fn keymap(&self) -> &KeyMap {
         &self.keymap
     }
 
+    /// Searches the snippets configured for the path's type for
+    /// a sequence of keys typed in the mode (see `Snippets::binding_for`).
+    pub fn snippet_for(&self, path: Option<&PathBuf>, mode: &str, keys: &[Key]) -> SnippetBinding {
+        match path_extension(path) {
+            Some(extension) => self.snippets.binding_for(extension, mode, keys),
+            None => SnippetBinding::Unbound,
+        }
+    }
+
     /// A path pointing to the user preferences directory.
     pub fn directory() -> Result<PathBuf> {
         app_root(AppDataType::UserConfig, &APP_INFO)
             .chain_err(|| "Couldn't create preferences directory or build a path to it.")
     }
 
+    /// A path pointing to the user data directory, used
+    /// for state persisted between sessions (e.g. history).
+    pub fn data_directory() -> Result<PathBuf> {
+        app_root(AppDataType::UserData, &APP_INFO)
+            .chain_err(|| "Couldn't create data directory or build a path to it.")
+    }
+
     /// A path pointing to the user syntax definition directory.
     pub fn syntax_path() -> Result<PathBuf> {
         app_dir(AppDataType::UserConfig, &APP_INFO, SYNTAX_PATH)
@@ -137,6 +283,60 @@ impl Preferences {
         self.theme = Some(theme.into());
     }
 
+    /// The names of the preferences that can be changed for the rest of the session.
+    pub fn settings() -> impl Iterator<Item=&'static str> {
+        SESSION_SETTINGS.iter().map(|&(name, _)| name)
+    }
+
+    /// The values a setting accepts, if it only accepts a few (e.g. switches).
+    pub fn setting_values(setting: &str) -> &'static [&'static str] {
+        match SESSION_SETTINGS.iter().find(|&&(name, _)| name == setting) {
+            Some(&(_, true)) => &["false", "true"],
+            _ => &[],
+        }
+    }
+
+    /// Changes a preference for the rest of the session (or until preferences
+    /// are reloaded), in place of its value in the config file. Values are
+    /// parsed as they would be there (e.g. "4" is a number).
+    pub fn set(&mut self, setting: &str, value: &str) -> Result<()> {
+        let switch = match SESSION_SETTINGS.iter().find(|&&(name, _)| name == setting) {
+            Some(&(_, switch)) => switch,
+            None => bail!(format!(
+                "\"{}\" can't be set (available settings: {})",
+                setting,
+                Preferences::settings().collect::<Vec<_>>().join(", ")
+            )),
+        };
+        if setting == THEME_KEY {
+            self.set_theme(value);
+            return Ok(());
+        }
+
+        let value = YamlLoader::load_from_str(value)
+            .ok()
+            .and_then(|documents| documents.into_iter().next())
+            .filter(|value| match *value {
+                Yaml::Hash(_) | Yaml::Array(_) => false,
+                Yaml::Boolean(_) => true,
+                _ => !switch,
+            })
+            .ok_or_else(|| if switch {
+                format!("\"{}\" must be true or false", setting)
+            } else {
+                format!("Couldn't parse a value for \"{}\"", setting)
+            })?;
+        let data = self.data.get_or_insert_with(|| Yaml::Hash(Hash::new()));
+        match *data {
+            Yaml::Hash(ref mut data) => {
+                data.insert(Yaml::String(setting.to_string()), value);
+            },
+            _ => bail!("Couldn't update preferences"),
+        }
+
+        Ok(())
+    }
+
     pub fn tab_width(&self, path: Option<&PathBuf>) -> usize {
         self.data
             .as_ref()
@@ -156,6 +356,17 @@ impl Preferences {
             .unwrap_or(TAB_WIDTH_DEFAULT)
     }
 
+    /// How far back (in seconds) `application::display_profile` reports on.
+    pub fn profile_window(&self) -> Duration {
+        let seconds = self.data
+            .as_ref()
+            .and_then(|data| data[PROFILE_WINDOW_KEY].as_i64())
+            .map(|seconds| seconds.max(1) as u64)
+            .unwrap_or(PROFILE_WINDOW_DEFAULT);
+
+        Duration::from_secs(seconds)
+    }
+
     pub fn search_select_config(&self) -> SearchSelectConfig {
         let mut result = SearchSelectConfig::default();
         if let Some(ref data) = self.data {
@@ -185,6 +396,74 @@ impl Preferences {
             .unwrap_or(SOFT_TABS_DEFAULT)
     }
 
+    /// Whether buffers are indented to match their content, rather than
+    /// using the `soft_tabs` and `tab_width` preferences, when opened.
+    pub fn detect_indentation(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[DETECT_INDENTATION_KEY].as_bool())
+            .unwrap_or(true)
+    }
+
+    /// How many columns the file tree sidebar takes up, at most.
+    pub fn file_tree_width(&self) -> usize {
+        self.data
+            .as_ref()
+            .and_then(|data| data[FILE_TREE_WIDTH_KEY].as_i64())
+            .filter(|&width| width > 0)
+            .map(|width| width as usize)
+            .unwrap_or(FILE_TREE_WIDTH_DEFAULT)
+    }
+
+    /// Directories whose files are opened read-only, as changes to them are
+    /// lost when they're regenerated (e.g. by a build). Any directory with
+    /// one of these names within the workspace matches.
+    pub fn read_only_directories(&self) -> Vec<String> {
+        match self.data.as_ref().map(|data| &data[READ_ONLY_DIRECTORIES_KEY]) {
+            Some(Yaml::Array(ref directories)) => directories
+                .iter()
+                .filter_map(|directory| directory.as_str().map(String::from))
+                .collect(),
+            _ => READ_ONLY_DIRECTORIES_DEFAULT.iter().map(|directory| directory.to_string()).collect(),
+        }
+    }
+
+    /// How long (configured in minutes) a buffer can go unused before its
+    /// undo history and highlighting cache are discarded. Disabled by default.
+    pub fn evict_idle_buffers_after(&self) -> Option<Duration> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[EVICT_IDLE_BUFFERS_AFTER_KEY].as_i64())
+            .filter(|&minutes| minutes > 0)
+            .map(|minutes| Duration::from_secs(minutes as u64 * 60))
+    }
+
+    /// How long to wait for the next key of a sequence, when the keys pressed
+    /// so far are bound but also start a longer sequence. A value of zero
+    /// waits indefinitely.
+    /// Whether the keys that continue a partially typed
+    /// sequence are listed while waiting for the next one.
+    pub fn key_sequence_hints(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[KEY_SEQUENCE_HINTS_KEY].as_bool())
+            .unwrap_or(KEY_SEQUENCE_HINTS_DEFAULT)
+    }
+
+    pub fn key_sequence_timeout(&self) -> Option<Duration> {
+        let timeout = self.data
+            .as_ref()
+            .and_then(|data| data[KEY_SEQUENCE_TIMEOUT_KEY].as_i64())
+            .map(|timeout| timeout.max(0) as u64)
+            .unwrap_or(KEY_SEQUENCE_TIMEOUT_DEFAULT);
+
+        if timeout == 0 {
+            None
+        } else {
+            Some(Duration::from_millis(timeout))
+        }
+    }
+
     pub fn line_length_guide(&self) -> Option<usize> {
         self.data
             .as_ref()
@@ -201,17 +480,148 @@ impl Preferences {
                       })
     }
 
+    /// Whether the cursor may rest after the last character of
+    /// a line outside of insert mode.
+    pub fn cursor_past_end_of_line(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[CURSOR_PAST_END_OF_LINE_KEY].as_bool())
+            .unwrap_or(CURSOR_PAST_END_OF_LINE_DEFAULT)
+    }
+
+    /// Whether lines that differ from the git index are marked in the gutter.
+    pub fn git_gutter(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[GIT_GUTTER_KEY].as_bool())
+            .unwrap_or(true)
+    }
+
+    /// The character and (hex) color configured for a kind of git gutter
+    /// sign ("added", "modified", or "removed"), either of which may be unset.
+    /// The character can also be configured on its own, as a string.
+    pub fn git_gutter_sign(&self, change: &str) -> (Option<&str>, Option<&str>) {
+        let sign = match self.data {
+            Some(ref data) => &data[GIT_GUTTER_KEY][change],
+            None => return (None, None),
+        };
+
+        match *sign {
+            Yaml::String(ref character) => (Some(character.as_str()), None),
+            _ => (sign["character"].as_str(), sign["color"].as_str()),
+        }
+    }
+
+    /// Whether moving past the last (or first) search match
+    /// wraps around to the other end of the buffer.
+    pub fn search_wrap(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[SEARCH_WRAP_KEY].as_bool())
+            .unwrap_or(SEARCH_WRAP_DEFAULT)
+    }
+
+    /// How search wrapping is signalled: "audible", "visual" (the default),
+    /// "flash", or not at all if set to anything else (e.g. false).
+    pub fn search_wrap_bell(&self) -> Option<Bell> {
+        match self.data.as_ref().map(|data| &data[SEARCH_WRAP_BELL_KEY]) {
+            Some(Yaml::BadValue) | None => Some(Bell::Visual),
+            Some(bell) => Bell::parse(bell),
+        }
+    }
+
+    /// How errors and boundary conditions (e.g. moving past the first line)
+    /// are signalled: "flash" (the default), "audible", a list of both, or
+    /// not at all if set to anything else (e.g. false).
+    pub fn bell(&self) -> Vec<Bell> {
+        match self.data.as_ref().map(|data| &data[BELL_KEY]) {
+            Some(Yaml::BadValue) | None => vec![Bell::Flash],
+            Some(Yaml::Array(ref bells)) => bells.iter().filter_map(Bell::parse).collect(),
+            Some(bell) => Bell::parse(bell).into_iter().collect(),
+        }
+    }
+
+    /// Whether the changes made by each key press are logged, so that they
+    /// can be exported as a series of patches. Disabled by default.
+    pub fn edit_log(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[EDIT_LOG_KEY].as_bool())
+            .unwrap_or(false)
+    }
+
+    /// Whether pressing an unbound key in normal mode
+    /// says so in the status line, rather than doing nothing.
+    pub fn unbound_key_hint(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[UNBOUND_KEY_HINT_KEY].as_bool())
+            .unwrap_or(UNBOUND_KEY_HINT_DEFAULT)
+    }
+
+    /// Whether the mouse can be used to move the cursor, select text, and
+    /// scroll. Disabling it leaves mouse input to the terminal (e.g. to
+    /// select text to copy through the terminal itself).
+    pub fn mouse(&self) -> bool {
+        self.data
+            .as_ref()
+            .and_then(|data| data[MOUSE_KEY].as_bool())
+            .unwrap_or(MOUSE_DEFAULT)
+    }
+
+    /// How lines are numbered: "absolute" (the default), "relative",
+    /// "hybrid", or not at all if set to "none" (or false).
+    pub fn line_numbers(&self) -> LineNumberMode {
+        match self.data.as_ref().map(|data| &data[LINE_NUMBERS_KEY]) {
+            Some(Yaml::String(ref mode)) if mode == "relative" => LineNumberMode::Relative,
+            Some(Yaml::String(ref mode)) if mode == "hybrid" => LineNumberMode::Hybrid,
+            Some(Yaml::String(ref mode)) if mode == "none" => LineNumberMode::Hidden,
+            Some(Yaml::Boolean(false)) => LineNumberMode::Hidden,
+            _ => LineNumberMode::Absolute,
+        }
+    }
+
     pub fn line_wrapping(&self) -> bool {
         self.data
             .as_ref()
-            .and_then(|data| if let Yaml::Boolean(wrapping) = data[LINE_WRAPPING_KEY] {
-                          Some(wrapping)
-                      } else {
-                          None
+            .and_then(|data| match data[LINE_WRAPPING_KEY] {
+                          Yaml::Boolean(wrapping) => Some(wrapping),
+                          Yaml::Integer(_) => Some(true),
+                          _ => None,
                       })
             .unwrap_or(LINE_WRAPPING_DEFAULT)
     }
 
+    /// The column lines wrap at, when narrower than the pane. Unless
+    /// set (e.g. `line_wrapping: 100`), lines wrap at the pane's edge.
+    pub fn line_wrap_column(&self) -> Option<usize> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[LINE_WRAPPING_KEY].as_i64())
+            .filter(|&column| column > 0)
+            .map(|column| column as usize)
+    }
+
+    /// Whether the status line shows the buffer's word count and reading
+    /// time. Once enabled, it's shown for prose (markdown and text files),
+    /// and other types can opt in (or prose types out) individually.
+    pub fn word_count(&self, path: Option<&PathBuf>) -> bool {
+        let data = match self.data {
+            Some(ref data) => data,
+            None => return false,
+        };
+        if !data[WORD_COUNT_KEY].as_bool().unwrap_or(false) {
+            return false;
+        }
+
+        match path_extension(path) {
+            Some(extension) => data[TYPES_KEY][extension][WORD_COUNT_KEY]
+                .as_bool()
+                .unwrap_or_else(|| PROSE_EXTENSIONS.contains(&extension)),
+            None => false,
+        }
+    }
+
     pub fn tab_content(&self, path: Option<&PathBuf>) -> String {
         if self.soft_tabs(path) {
             format!("{:1$}", "", self.tab_width(path))
@@ -220,6 +630,83 @@ impl Preferences {
         }
     }
 
+    /// The shell command used to run tests for the specified path. Its `{test}`
+    /// placeholder is replaced with a test name (or removed to run all tests),
+    /// and `{path}` is replaced with the file's path.
+    pub fn test_command(&self, path: Option<&PathBuf>) -> Option<String> {
+        self.data
+            .as_ref()
+            .and_then(|data| {
+                if let Some(extension) = path_extension(path) {
+                    if let Yaml::String(ref command) = data[TYPES_KEY][extension][TEST_COMMAND_KEY] {
+                        return Some(command.clone());
+                    }
+                }
+                if let Yaml::String(ref command) = data[TEST_COMMAND_KEY] {
+                    return Some(command.clone());
+                }
+
+                None
+            })
+            .or_else(|| default_test_command(path))
+    }
+
+    /// The command used to start a language server for the specified path,
+    /// configured per file type as a string or a list of arguments.
+    pub fn language_server(&self, path: Option<&PathBuf>) -> Option<Vec<String>> {
+        let extension = path_extension(path)?;
+
+        command_value(&self.data.as_ref()?[TYPES_KEY][extension][LANGUAGE_SERVER_KEY])
+    }
+
+    /// The commands used to decrypt and encrypt files of the path's type,
+    /// configured per file type as strings or lists of arguments. Files
+    /// with both are decrypted when opened, and encrypted when saved.
+    pub fn encryption_commands(&self, path: Option<&PathBuf>) -> Option<(Vec<String>, Vec<String>)> {
+        let extension = path_extension(path)?;
+        let configured = self.data.as_ref().and_then(|data| {
+            let decrypt = command_value(&data[TYPES_KEY][extension][DECRYPT_COMMAND_KEY])?;
+            let encrypt = command_value(&data[TYPES_KEY][extension][ENCRYPT_COMMAND_KEY])?;
+
+            Some((decrypt, encrypt))
+        });
+
+        configured.or_else(|| default_encryption_commands(extension))
+    }
+
+    /// Whether the clipboard should be synchronized with tmux's paste
+    /// buffers, rather than the native system clipboard.
+    pub fn tmux_clipboard(&self) -> bool {
+        self.string_value(CLIPBOARD_KEY) == Some("tmux")
+    }
+
+    /// Whether the clipboard should be synchronized with the terminal's own
+    /// clipboard (using OSC 52), rather than the native system clipboard.
+    pub fn osc52_clipboard(&self) -> bool {
+        self.string_value(CLIPBOARD_KEY) == Some("osc52")
+    }
+
+    /// The strftime-style format used by the `buffer::insert_date` command.
+    pub fn date_format(&self) -> &str {
+        self.string_value(DATE_FORMAT_KEY).unwrap_or(DATE_FORMAT_DEFAULT)
+    }
+
+    /// The strftime-style format used by the `buffer::insert_datetime` command.
+    pub fn datetime_format(&self) -> &str {
+        self.string_value(DATETIME_FORMAT_KEY).unwrap_or(DATETIME_FORMAT_DEFAULT)
+    }
+
+    /// The address collaboration sessions are hosted on, and joined at.
+    pub fn collaboration_address(&self) -> &str {
+        self.string_value(COLLABORATION_ADDRESS_KEY).unwrap_or(COLLABORATION_ADDRESS_DEFAULT)
+    }
+
+    fn string_value(&self, key: &str) -> Option<&str> {
+        self.data
+            .as_ref()
+            .and_then(|data| data[key].as_str())
+    }
+
     pub fn open_mode_exclusions(&self) -> Result<Option<Vec<ExclusionPattern>>> {
         if let Some(exclusion_data) = self.data.as_ref().map(|data| &data["open_mode"]["exclusions"]) {
             match *exclusion_data {
@@ -264,18 +751,118 @@ fn load_document() -> Result<Option<Yaml>> {
     Ok(parsed_data.into_iter().nth(0))
 }
 
-/// Loads default keymaps, merging in the provided overrides.
-fn load_keymap(keymap_overrides: Option<&Hash>) -> Result<KeyMap> {
-    let mut keymap = KeyMap::default()?;
+/// Loads the preferred keymap preset, merging in the user's overrides.
+fn load_keymap(data: Option<&Yaml>) -> Result<KeyMap> {
+    let preset = data
+        .and_then(|data| data[KEYMAP_PRESET_KEY].as_str())
+        .unwrap_or(KEYMAP_PRESET_DEFAULT);
+    let mut keymap = KeyMap::preset(preset)?;
 
-    // Merge user-defined keymaps into defaults.
-    if let Some(keymap_data) = keymap_overrides {
+    // Merge user-defined keymaps into the preset.
+    if let Some(keymap_data) = data.and_then(|data| data[KEYMAP_KEY].as_hash()) {
         KeyMap::from(keymap_data).map(|data| keymap.merge(data))?;
     }
 
     Ok(keymap)
 }
 
+/// Loads the snippets configured for each file type.
+fn load_snippets(data: Option<&Yaml>) -> Result<Snippets> {
+    match data.and_then(|data| data[TYPES_KEY].as_hash()) {
+        Some(types_data) => Snippets::from(types_data),
+        None => Ok(Snippets::default()),
+    }
+}
+
+/// Adds a binding to the end of a mode's bindings in a config file's content,
+/// creating the mode (and keymap) entries if needed. Later bindings take
+/// precedence, and the rest of the content (e.g. comments) is left as-is.
+fn insert_key_binding(data: &str, mode: &str, keys: &str, command: &str) -> Result<String> {
+    let mut lines: Vec<String> = data.lines().map(String::from).collect();
+    let binding = format!(
+        "\"{}\": {}",
+        keys.replace('\\', "\\\\").replace('"', "\\\""),
+        command
+    );
+
+    let keymap_line = match lines.iter().position(|line| line.starts_with(&format!("{}:", KEYMAP_KEY))) {
+        Some(index) => index,
+        None => {
+            lines.push(format!("{}:", KEYMAP_KEY));
+            lines.push(format!("  {}:", mode));
+            lines.push(format!("    {}", binding));
+
+            return Ok(format!("{}\n", lines.join("\n")));
+        }
+    };
+    if !is_block_key(&lines[keymap_line], KEYMAP_KEY) {
+        bail!("Couldn't add the key binding to the config file's keymap; add it manually instead");
+    }
+
+    // Find the mode's bindings within the keymap.
+    let keymap_end = block_end(&lines, keymap_line);
+    let mode_indent = lines[keymap_line + 1..keymap_end]
+        .iter()
+        .find(|line| is_content(line))
+        .map(|line| indentation(line))
+        .unwrap_or(2);
+    let mode_line = (keymap_line + 1..keymap_end).find(|&index| {
+        indentation(&lines[index]) == mode_indent && is_block_key(&lines[index], mode)
+    });
+
+    match mode_line {
+        Some(mode_line) => {
+            let mode_end = block_end(&lines, mode_line);
+            let binding_indent = lines[mode_line + 1..mode_end]
+                .iter()
+                .find(|line| is_content(line))
+                .map(|line| indentation(line))
+                .unwrap_or(mode_indent + 2);
+            let position = last_content_line(&lines, mode_line, mode_end) + 1;
+            lines.insert(position, format!("{}{}", " ".repeat(binding_indent), binding));
+        }
+        None => {
+            let position = last_content_line(&lines, keymap_line, keymap_end) + 1;
+            lines.insert(position, format!("{}{}:", " ".repeat(mode_indent), mode));
+            lines.insert(position + 1, format!("{}{}", " ".repeat(mode_indent + 2), binding));
+        }
+    }
+
+    Ok(format!("{}\n", lines.join("\n")))
+}
+
+// Whether the line is a key whose (block) value is on the following lines.
+fn is_block_key(line: &str, key: &str) -> bool {
+    line.trim_start()
+        .strip_prefix(key)
+        .and_then(|rest| rest.strip_prefix(':'))
+        .map(|rest| rest.trim().is_empty() || rest.trim_start().starts_with('#'))
+        == Some(true)
+}
+
+fn is_content(line: &str) -> bool {
+    let line = line.trim();
+
+    !line.is_empty() && !line.starts_with('#')
+}
+
+fn indentation(line: &str) -> usize {
+    line.len() - line.trim_start().len()
+}
+
+// The index of the first line after the key's nested lines.
+fn block_end(lines: &[String], key_line: usize) -> usize {
+    let key_indent = indentation(&lines[key_line]);
+
+    (key_line + 1..lines.len())
+        .find(|&index| is_content(&lines[index]) && indentation(&lines[index]) <= key_indent)
+        .unwrap_or(lines.len())
+}
+
+fn last_content_line(lines: &[String], start: usize, end: usize) -> usize {
+    (start..end).rev().find(|&index| is_content(&lines[index])).unwrap_or(start)
+}
+
 /// Maps a path to its file extension.
 fn path_extension(path: Option<&PathBuf>) -> Option<&str> {
     path
@@ -283,6 +870,45 @@ fn path_extension(path: Option<&PathBuf>) -> Option<&str> {
         .and_then(|e| e.to_str())
 }
 
+// Reads a command configured as a string or a list of arguments.
+fn command_value(value: &Yaml) -> Option<Vec<String>> {
+    let command: Vec<String> = match *value {
+        Yaml::String(ref command) => command.split_whitespace().map(String::from).collect(),
+        Yaml::Array(ref command) => {
+            command.iter().filter_map(|arg| arg.as_str().map(String::from)).collect()
+        },
+        _ => return None,
+    };
+
+    if command.is_empty() {
+        None
+    } else {
+        Some(command)
+    }
+}
+
+// GnuPG reads the passphrase from the first line of its standard input.
+fn default_encryption_commands(extension: &str) -> Option<(Vec<String>, Vec<String>)> {
+    let gpg = |operation: &str| -> Vec<String> {
+        ["gpg", "--batch", "--quiet", "--pinentry-mode", "loopback", "--passphrase-fd", "0", operation]
+            .iter()
+            .map(|arg| arg.to_string())
+            .collect()
+    };
+
+    match extension {
+        "gpg" => Some((gpg("--decrypt"), gpg("--symmetric"))),
+        _ => None,
+    }
+}
+
+fn default_test_command(path: Option<&PathBuf>) -> Option<String> {
+    match path_extension(path) {
+        Some("rs") => Some(String::from("cargo test {test}")),
+        _ => None,
+    }
+}
+
 fn default_open_mode_exclusions() -> Result<Option<Vec<ExclusionPattern>>> {
     let default_pattern = ExclusionPattern::new("**/.git")
         .chain_err(|| "Failed to parse default git directory exclusion pattern")?;
@@ -291,9 +917,11 @@ fn default_open_mode_exclusions() -> Result<Option<Vec<ExclusionPattern>>> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ExclusionPattern, Preferences, YamlLoader};
+    use super::{Bell, ExclusionPattern, LineNumberMode, Preferences, YamlLoader};
     use std::path::PathBuf;
-    use crate::input::KeyMap;
+    use std::time::Duration;
+    use crate::commands;
+    use crate::input::{Key, KeyMap};
     use crate::yaml::yaml::Hash;
 
     #[test]
@@ -312,6 +940,21 @@ mod tests {
         assert_eq!(preferences.theme(), "new_in_memory_theme");
     }
 
+    #[test]
+    fn set_overrides_settings_with_parsed_values() {
+        let data = YamlLoader::load_from_str("tab_width: 12").unwrap();
+        let mut preferences = Preferences::new(data.into_iter().next());
+        preferences.set("tab_width", "4").unwrap();
+        preferences.set("soft_tabs", "false").unwrap();
+        preferences.set("theme", "base16-ocean.dark").unwrap();
+
+        assert_eq!(preferences.tab_width(None), 4);
+        assert!(!preferences.soft_tabs(None));
+        assert_eq!(preferences.theme(), "base16-ocean.dark");
+        assert!(preferences.set("soft_tabs", "4").is_err());
+        assert!(preferences.set("keymap", "{}").is_err());
+    }
+
     #[test]
     fn tab_width_returns_user_defined_data() {
         let data = YamlLoader::load_from_str("tab_width: 12").unwrap();
@@ -395,6 +1038,45 @@ mod tests {
         assert_eq!(preferences.line_length_guide(), Some(80));
     }
 
+    #[test]
+    fn tmux_clipboard_returns_user_defined_data() {
+        let data = YamlLoader::load_from_str("clipboard: tmux").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(preferences.tmux_clipboard());
+        assert!(!preferences.osc52_clipboard());
+        assert!(!Preferences::new(None).tmux_clipboard());
+
+        let data = YamlLoader::load_from_str("clipboard: osc52").unwrap();
+        assert!(Preferences::new(data.into_iter().nth(0)).osc52_clipboard());
+    }
+
+    #[test]
+    fn date_formats_return_user_defined_data() {
+        let data = YamlLoader::load_from_str("date_format: \"%d/%m/%Y\"\ndatetime_format: \"%c\"").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.date_format(), "%d/%m/%Y");
+        assert_eq!(preferences.datetime_format(), "%c");
+    }
+
+    #[test]
+    fn date_formats_return_defaults_when_not_set() {
+        let preferences = Preferences::new(None);
+
+        assert_eq!(preferences.date_format(), "%Y-%m-%d");
+        assert_eq!(preferences.datetime_format(), "%Y-%m-%d %H:%M:%S");
+    }
+
+    #[test]
+    fn collaboration_address_returns_user_defined_data_or_default() {
+        let data = YamlLoader::load_from_str("collaboration_address: \"0.0.0.0:5000\"").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+
+        assert_eq!(preferences.collaboration_address(), "0.0.0.0:5000");
+        assert_eq!(Preferences::new(None).collaboration_address(), "127.0.0.1:4242");
+    }
+
     #[test]
     fn preferences_returns_user_defined_line_wrapping() {
         let data = YamlLoader::load_from_str("line_wrapping: false").unwrap();
@@ -403,6 +1085,44 @@ mod tests {
         assert_eq!(preferences.line_wrapping(), false);
     }
 
+    #[test]
+    fn line_wrap_column_returns_user_defined_column() {
+        let data = YamlLoader::load_from_str("line_wrapping: 100").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+
+        assert!(preferences.line_wrapping());
+        assert_eq!(preferences.line_wrap_column(), Some(100));
+        assert_eq!(Preferences::new(None).line_wrap_column(), None);
+    }
+
+    #[test]
+    fn git_gutter_sign_accepts_a_character_or_mapping() {
+        let data = YamlLoader::load_from_str(
+            "git_gutter:\n  added: \"|\"\n  removed:\n    color: \"#dc322f\"\n"
+        ).unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+
+        assert!(preferences.git_gutter());
+        assert_eq!(preferences.git_gutter_sign("added"), (Some("|"), None));
+        assert_eq!(preferences.git_gutter_sign("removed"), (None, Some("#dc322f")));
+        assert_eq!(preferences.git_gutter_sign("modified"), (None, None));
+    }
+
+    #[test]
+    fn cursor_past_end_of_line_defaults_to_true() {
+        let preferences = Preferences::new(None);
+
+        assert!(preferences.cursor_past_end_of_line());
+    }
+
+    #[test]
+    fn preferences_returns_user_defined_cursor_past_end_of_line() {
+        let data = YamlLoader::load_from_str("cursor_past_end_of_line: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert!(!preferences.cursor_past_end_of_line());
+    }
+
     #[test]
     fn tab_content_uses_tab_width_spaces_when_soft_tabs_are_enabled() {
         let data = YamlLoader::load_from_str("soft_tabs: true\ntab_width: 5").unwrap();
@@ -504,4 +1224,198 @@ mod tests {
         preferences.reload().unwrap();
         assert!(preferences.keymap().get("normal").is_some());
     }
+
+    #[test]
+    fn key_sequence_timeout_defaults_to_one_second_and_can_be_disabled() {
+        let preferences = Preferences::new(None);
+        assert_eq!(preferences.key_sequence_timeout(), Some(Duration::from_millis(1000)));
+
+        let data = YamlLoader::load_from_str("key_sequence_timeout: 0").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+        assert_eq!(preferences.key_sequence_timeout(), None);
+    }
+
+    #[test]
+    fn evict_idle_buffers_after_is_disabled_by_default() {
+        let preferences = Preferences::new(None);
+        assert_eq!(preferences.evict_idle_buffers_after(), None);
+
+        let data = YamlLoader::load_from_str("evict_idle_buffers_after: 15").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+        assert_eq!(preferences.evict_idle_buffers_after(), Some(Duration::from_secs(900)));
+    }
+
+    #[test]
+    fn read_only_directories_can_be_replaced_or_cleared() {
+        let preferences = Preferences::new(None);
+        assert_eq!(preferences.read_only_directories(), vec!["node_modules", "target", "vendor"]);
+
+        let data = YamlLoader::load_from_str("read_only_directories: [dist]").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+        assert_eq!(preferences.read_only_directories(), vec!["dist"]);
+
+        let data = YamlLoader::load_from_str("read_only_directories: []").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+        assert!(preferences.read_only_directories().is_empty());
+    }
+
+    #[test]
+    fn profile_window_defaults_to_thirty_seconds() {
+        let preferences = Preferences::new(None);
+        assert_eq!(preferences.profile_window(), Duration::from_secs(30));
+
+        let data = YamlLoader::load_from_str("profile_window: 120").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+        assert_eq!(preferences.profile_window(), Duration::from_secs(120));
+    }
+
+    #[test]
+    fn load_keymap_merges_overrides_into_the_preferred_preset() {
+        let data = YamlLoader::load_from_str("keymap_preset: vscode\nkeymap:\n  normal:\n    ctrl-q: buffer::save").unwrap();
+        let keymap = super::load_keymap(data.first()).unwrap();
+
+        let command = keymap.commands_for("normal", &Key::Ctrl('s')).unwrap();
+        assert_eq!(command[0] as *const usize, commands::buffer::save as *const usize);
+        let command = keymap.commands_for("normal", &Key::Ctrl('q')).unwrap();
+        assert_eq!(command[0] as *const usize, commands::buffer::save as *const usize);
+
+        let data = YamlLoader::load_from_str("keymap_preset: unknown").unwrap();
+        assert!(super::load_keymap(data.first()).is_err());
+    }
+
+    #[test]
+    fn test_command_returns_user_defined_type_specific_data() {
+        let data = YamlLoader::load_from_str("test_command: \"make test\"\ntypes:\n  py:\n    test_command: \"pytest -v {path}\"").unwrap();
+        let preferences = Preferences::new(data.into_iter().nth(0));
+
+        assert_eq!(preferences.test_command(Some(PathBuf::from("test_amp.py")).as_ref()),
+                   Some(String::from("pytest -v {path}")));
+        assert_eq!(preferences.test_command(Some(PathBuf::from("amp_test.go")).as_ref()),
+                   Some(String::from("make test")));
+    }
+
+    #[test]
+    fn test_command_returns_default_for_rust_files() {
+        let preferences = Preferences::new(None);
+
+        assert_eq!(preferences.test_command(Some(PathBuf::from("preferences.rs")).as_ref()),
+                   Some(String::from("cargo test {test}")));
+        assert_eq!(preferences.test_command(Some(PathBuf::from("test_amp.py")).as_ref()), None);
+    }
+
+    #[test]
+    fn language_server_returns_user_defined_type_specific_commands() {
+        let data = YamlLoader::load_from_str("types:\n  rs:\n    language_server: rust-analyzer\n  py:\n    language_server: [pylsp, -v]").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+
+        assert_eq!(preferences.language_server(Some(PathBuf::from("main.rs")).as_ref()),
+                   Some(vec![String::from("rust-analyzer")]));
+        assert_eq!(preferences.language_server(Some(PathBuf::from("amp.py")).as_ref()),
+                   Some(vec![String::from("pylsp"), String::from("-v")]));
+        assert_eq!(preferences.language_server(Some(PathBuf::from("amp.go")).as_ref()), None);
+    }
+
+    #[test]
+    fn encryption_commands_return_user_defined_commands_or_gpg_defaults() {
+        let data = YamlLoader::load_from_str("types:\n  age:\n    decrypt_command: age -d\n    encrypt_command: [age, -p]\n  enc:\n    decrypt_command: openssl").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+
+        assert_eq!(
+            preferences.encryption_commands(Some(PathBuf::from("notes.age")).as_ref()),
+            Some((vec![String::from("age"), String::from("-d")], vec![String::from("age"), String::from("-p")]))
+        );
+        let (decrypt, encrypt) = preferences.encryption_commands(Some(PathBuf::from("notes.md.gpg")).as_ref()).unwrap();
+        assert_eq!((decrypt[0].as_str(), decrypt.last().unwrap().as_str()), ("gpg", "--decrypt"));
+        assert_eq!(encrypt.last().unwrap(), "--symmetric");
+
+        // Both commands are needed.
+        assert_eq!(preferences.encryption_commands(Some(PathBuf::from("notes.enc")).as_ref()), None);
+        assert_eq!(preferences.encryption_commands(Some(PathBuf::from("notes.md")).as_ref()), None);
+    }
+
+    #[test]
+    fn line_numbers_default_to_absolute_and_can_be_hidden() {
+        assert_eq!(Preferences::new(None).line_numbers(), LineNumberMode::Absolute);
+
+        for &(value, mode) in &[
+            ("relative", LineNumberMode::Relative),
+            ("hybrid", LineNumberMode::Hybrid),
+            ("none", LineNumberMode::Hidden),
+            ("false", LineNumberMode::Hidden),
+            ("absolute", LineNumberMode::Absolute),
+        ] {
+            let data = YamlLoader::load_from_str(&format!("line_numbers: {}", value)).unwrap();
+            let preferences = Preferences::new(data.into_iter().next());
+            assert_eq!(preferences.line_numbers(), mode);
+        }
+    }
+
+    #[test]
+    fn word_count_is_shown_for_prose_types_once_enabled() {
+        let notes = PathBuf::from("notes.md");
+        let source = PathBuf::from("main.rs");
+        assert!(!Preferences::new(None).word_count(Some(&notes)));
+
+        let data = YamlLoader::load_from_str("word_count: true\ntypes:\n  rs:\n    word_count: true\n  txt:\n    word_count: false").unwrap();
+        let preferences = Preferences::new(data.into_iter().next());
+        assert!(preferences.word_count(Some(&notes)));
+        assert!(preferences.word_count(Some(&source)));
+        assert!(!preferences.word_count(Some(&PathBuf::from("todo.txt"))));
+        assert!(!preferences.word_count(Some(&PathBuf::from("lib.py"))));
+        assert!(!preferences.word_count(None));
+    }
+
+    #[test]
+    fn search_wrap_bell_defaults_to_visual_and_can_be_disabled() {
+        assert_eq!(Preferences::new(None).search_wrap_bell(), Some(Bell::Visual));
+
+        for &(value, bell) in &[("audible", Some(Bell::Audible)), ("false", None), ("none", None)] {
+            let data = YamlLoader::load_from_str(&format!("search_wrap_bell: {}", value)).unwrap();
+            let preferences = Preferences::new(data.into_iter().next());
+            assert_eq!(preferences.search_wrap_bell(), bell);
+        }
+    }
+
+    #[test]
+    fn bell_defaults_to_flash_and_accepts_a_list() {
+        assert_eq!(Preferences::new(None).bell(), vec![Bell::Flash]);
+
+        for &(value, ref bells) in &[
+            ("audible", vec![Bell::Audible]),
+            ("[audible, flash]", vec![Bell::Audible, Bell::Flash]),
+            ("false", vec![]),
+        ] {
+            let data = YamlLoader::load_from_str(&format!("bell: {}", value)).unwrap();
+            let preferences = Preferences::new(data.into_iter().next());
+            assert_eq!(&preferences.bell(), bells);
+        }
+    }
+
+    #[test]
+    fn insert_key_binding_adds_a_keymap_to_config_without_one() {
+        let data = super::insert_key_binding("theme: solarized_dark\n", "normal", "g g", "cursor::move_to_first_line").unwrap();
+
+        assert_eq!(data, "theme: solarized_dark\nkeymap:\n  normal:\n    \"g g\": cursor::move_to_first_line\n");
+    }
+
+    #[test]
+    fn insert_key_binding_appends_to_existing_modes_and_preserves_comments() {
+        let data = "keymap:\n    # Custom bindings\n    normal:\n        k: cursor::move_up\n\n    insert:\n        escape: application::switch_to_normal_mode\ntheme: solarized_dark\n";
+
+        let result = super::insert_key_binding(data, "normal", "\"", "buffer::save").unwrap();
+        assert_eq!(result, "keymap:\n    # Custom bindings\n    normal:\n        k: cursor::move_up\n        \"\\\"\": buffer::save\n\n    insert:\n        escape: application::switch_to_normal_mode\ntheme: solarized_dark\n");
+
+        let result = super::insert_key_binding(data, "search", "ctrl-r", "search::toggle_regex").unwrap();
+        assert_eq!(result, "keymap:\n    # Custom bindings\n    normal:\n        k: cursor::move_up\n\n    insert:\n        escape: application::switch_to_normal_mode\n    search:\n      \"ctrl-r\": search::toggle_regex\ntheme: solarized_dark\n");
+
+        // The result should still load, with the new binding in place.
+        let document = YamlLoader::load_from_str(&result).unwrap();
+        let keymap = super::load_keymap(document.first()).unwrap();
+        assert!(keymap.commands_for("search", &Key::Ctrl('r')).is_some());
+    }
+
+    #[test]
+    fn insert_key_binding_rejects_inline_keymaps() {
+        assert!(super::insert_key_binding("keymap: {}\n", "normal", "k", "cursor::move_up").is_err());
+    }
 }
@@ -0,0 +1,117 @@
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+// Samples older than this are discarded.
+const RETENTION: Duration = Duration::from_secs(300);
+
+/// Internal activities whose durations are tracked by the profiler.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Activity {
+    /// Drawing the screen (including highlighting).
+    Render,
+
+    /// Parsing buffer content for syntax highlighting.
+    Highlight,
+
+    /// Filtering search/select results.
+    Matching,
+
+    /// Reading and writing files.
+    Io,
+}
+
+const ACTIVITIES: [Activity; 4] = [Activity::Render, Activity::Highlight, Activity::Matching, Activity::Io];
+
+impl fmt::Display for Activity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match *self {
+            Activity::Render => "render",
+            Activity::Highlight => "highlight",
+            Activity::Matching => "matching",
+            Activity::Io => "I/O",
+        };
+
+        write!(f, "{}", name)
+    }
+}
+
+struct Sample {
+    activity: Activity,
+    recorded_at: Instant,
+    duration: Duration,
+}
+
+/// Records how long internal activities take, so that performance issues
+/// can be reported with real numbers. Samples are only kept in memory (for
+/// a few minutes), and never leave the machine.
+#[derive(Default)]
+pub struct Profiler {
+    samples: VecDeque<Sample>,
+}
+
+impl Profiler {
+    pub fn record(&mut self, activity: Activity, duration: Duration) {
+        let now = Instant::now();
+        while self.samples.front().map(|s| now.duration_since(s.recorded_at) > RETENTION) == Some(true) {
+            self.samples.pop_front();
+        }
+
+        self.samples.push_back(Sample { activity, recorded_at: now, duration });
+    }
+
+    /// Summarizes the time spent on each activity within the window.
+    pub fn report(&self, window: Duration) -> String {
+        let now = Instant::now();
+        let mut totals: HashMap<Activity, (Duration, u32, Duration)> = HashMap::new();
+        for sample in self.samples.iter().filter(|s| now.duration_since(s.recorded_at) <= window) {
+            let entry = totals.entry(sample.activity).or_insert((Duration::from_secs(0), 0, Duration::from_secs(0)));
+            entry.0 += sample.duration;
+            entry.1 += 1;
+            entry.2 = entry.2.max(sample.duration);
+        }
+
+        let mut report = format!(
+            "Time spent in the last {} seconds (render includes highlight):\n\n{:<10} {:>10} {:>6} {:>10} {:>10}\n",
+            window.as_secs(), "activity", "total", "count", "average", "max"
+        );
+        for activity in ACTIVITIES.iter() {
+            let (total, count, max) = totals
+                .get(activity)
+                .cloned()
+                .unwrap_or((Duration::from_secs(0), 0, Duration::from_secs(0)));
+            let average = if count > 0 { total / count } else { total };
+            report.push_str(&format!(
+                "{:<10} {:>10} {:>6} {:>10} {:>10}\n",
+                activity.to_string(), milliseconds(total), count, milliseconds(average), milliseconds(max)
+            ));
+        }
+
+        report
+    }
+}
+
+fn milliseconds(duration: Duration) -> String {
+    format!("{:.1}ms", duration.as_secs_f64() * 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Activity, Profiler};
+    use std::time::Duration;
+
+    #[test]
+    fn report_summarizes_each_activity() {
+        let mut profiler = Profiler::default();
+        profiler.record(Activity::Render, Duration::from_millis(10));
+        profiler.record(Activity::Render, Duration::from_millis(20));
+        profiler.record(Activity::Io, Duration::from_millis(5));
+
+        let report = profiler.report(Duration::from_secs(10));
+        let lines: Vec<&str> = report.lines().collect();
+        assert_eq!(lines[0], "Time spent in the last 10 seconds (render includes highlight):");
+        assert_eq!(lines[3], "render         30.0ms      2     15.0ms     20.0ms");
+        assert_eq!(lines[4], "highlight       0.0ms      0      0.0ms      0.0ms");
+        assert_eq!(lines[6], "I/O             5.0ms      1      5.0ms      5.0ms");
+    }
+}
@@ -0,0 +1,296 @@
+use crate::models::application::{HistoryMatch, InputHistory, InputHistoryRef};
+use crate::util::prompt;
+use std::fmt;
+use std::ops::Deref;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Provides candidates for completing a prompt's input, given the input
+/// preceding the cursor. Candidates replace that input entirely.
+pub type Completer = Box<dyn Fn(&str) -> Vec<String>>;
+
+/// A single line of input with a cursor, used by every mode that prompts for
+/// text, so that they all edit, recall, complete, and render it the same way.
+/// Dereferences to the input itself, for modes that only need to read it.
+#[derive(Default)]
+pub struct Prompt {
+    input: String,
+
+    /// The cursor's byte offset, which is always on a grapheme boundary.
+    cursor: usize,
+    history: Option<InputHistoryRef>,
+    completer: Option<Completer>,
+}
+
+impl Prompt {
+    /// Creates a prompt with the cursor after the initial input.
+    pub fn new<T: Into<String>>(input: T) -> Prompt {
+        let input = input.into();
+
+        Prompt { cursor: input.len(), input, history: None, completer: None }
+    }
+
+    /// Enables recalling previous input stored under the history namespace.
+    pub fn with_history(mut self, namespace: &'static str) -> Prompt {
+        self.history = Some(InputHistoryRef::new(namespace));
+        self
+    }
+
+    pub fn with_completer<F>(mut self, completer: F) -> Prompt
+        where F: Fn(&str) -> Vec<String> + 'static
+    {
+        self.completer = Some(Box::new(completer));
+        self
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    /// Replaces the input, moving the cursor after it.
+    pub fn set<T: Into<String>>(&mut self, input: T) {
+        self.input = input.into();
+        self.cursor = self.input.len();
+    }
+
+    pub fn clear(&mut self) {
+        self.set(String::new());
+    }
+
+    /// Runs a function against the input directly, moving the cursor after
+    /// it (e.g. for modes that add or remove a prefix).
+    pub fn edit<F: FnOnce(&mut String)>(&mut self, edit: F) {
+        edit(&mut self.input);
+        self.cursor = self.input.len();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.input.insert(self.cursor, c);
+        self.cursor += c.len_utf8();
+        self.stop_recalling();
+    }
+
+    pub fn insert_str(&mut self, content: &str) {
+        self.input.insert_str(self.cursor, content);
+        self.cursor += content.len();
+        self.stop_recalling();
+    }
+
+    /// Removes the character (grapheme) before the cursor.
+    pub fn delete_char(&mut self) {
+        self.delete_before(prompt::pop_char);
+    }
+
+    /// Removes the word before the cursor (see `util::prompt::pop_word`).
+    pub fn delete_word(&mut self) {
+        self.delete_before(prompt::pop_word);
+    }
+
+    /// Runs a function that removes content from the end of its
+    /// argument against the input preceding the cursor.
+    pub fn delete_before(&mut self, delete: fn(&mut String)) {
+        let mut before = self.input[..self.cursor].to_string();
+        delete(&mut before);
+        self.input.replace_range(before.len()..self.cursor, "");
+        self.cursor = before.len();
+        self.stop_recalling();
+    }
+
+    /// Removes the character (grapheme) after the cursor.
+    pub fn delete_forward(&mut self) {
+        let length = self.input[self.cursor..].graphemes(true).next().map(str::len).unwrap_or(0);
+        self.input.replace_range(self.cursor..self.cursor + length, "");
+        self.stop_recalling();
+    }
+
+    pub fn move_left(&mut self) {
+        let length = self.input[..self.cursor].graphemes(true).next_back().map(str::len).unwrap_or(0);
+        self.cursor -= length;
+    }
+
+    pub fn move_right(&mut self) {
+        let length = self.input[self.cursor..].graphemes(true).next().map(str::len).unwrap_or(0);
+        self.cursor += length;
+    }
+
+    pub fn move_to_start(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn move_to_end(&mut self) {
+        self.cursor = self.input.len();
+    }
+
+    /// Adds the input to the prompt's history (if it has one).
+    pub fn remember(&self, history: &mut InputHistory) {
+        if let Some(ref position) = self.history {
+            history.add(position.namespace(), self.input.clone());
+        }
+    }
+
+    /// Replaces the input with an older entry from its history,
+    /// returning false if there are none (or no history at all).
+    pub fn recall_previous(&mut self, history: &InputHistory, matching: HistoryMatch) -> bool {
+        let entry = match self.history {
+            Some(ref mut position) => position.move_to_prev(history, &self.input, matching),
+            None => None,
+        };
+
+        self.recall(entry)
+    }
+
+    /// Replaces the input with a newer entry from its history, restoring
+    /// the original input once past the newest one.
+    pub fn recall_next(&mut self, history: &InputHistory) -> bool {
+        let entry = match self.history {
+            Some(ref mut position) => position.move_to_next(history),
+            None => None,
+        };
+
+        self.recall(entry)
+    }
+
+    /// Completes the input preceding the cursor as far as its candidates
+    /// agree, returning false if there's nothing to add.
+    pub fn complete(&mut self) -> bool {
+        let candidates = match self.completer {
+            Some(ref completer) => completer(&self.input[..self.cursor]),
+            None => return false,
+        };
+        let completion = match common_prefix(&candidates) {
+            Some(completion) if completion.len() > self.cursor => completion,
+            _ => return false,
+        };
+
+        self.input.replace_range(..self.cursor, &completion);
+        self.cursor = completion.len();
+        self.stop_recalling();
+
+        true
+    }
+
+    /// The input as it should be displayed (with control characters
+    /// escaped), along with the cursor's column (in graphemes) within it.
+    pub fn render(&self) -> (String, usize) {
+        let before = prompt::escape(&self.input[..self.cursor]);
+        let after = prompt::escape(&self.input[self.cursor..]);
+
+        (format!("{}{}", before, after), before.graphemes(true).count())
+    }
+
+    fn recall(&mut self, entry: Option<String>) -> bool {
+        match entry {
+            Some(entry) => {
+                self.input = entry;
+                self.cursor = self.input.len();
+                true
+            },
+            None => false,
+        }
+    }
+
+    // Editing ends any history navigation, so that it starts over next time.
+    fn stop_recalling(&mut self) {
+        if let Some(ref mut position) = self.history {
+            position.reset();
+        }
+    }
+}
+
+impl Deref for Prompt {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.input
+    }
+}
+
+impl fmt::Display for Prompt {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.input)
+    }
+}
+
+// The longest prefix (ending on a character boundary) shared by the candidates.
+fn common_prefix(candidates: &[String]) -> Option<String> {
+    let (first, rest) = candidates.split_first()?;
+    let mut length = first.len();
+    for candidate in rest {
+        length = first
+            .char_indices()
+            .zip(candidate.chars())
+            .find(|&((_, a), b)| a != b)
+            .map(|((index, _), _)| index)
+            .unwrap_or_else(|| first.len().min(candidate.len()))
+            .min(length);
+    }
+
+    Some(first[..length].to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Prompt;
+    use crate::models::application::{HistoryMatch, InputHistory};
+
+    #[test]
+    fn editing_happens_at_the_cursor() {
+        let mut prompt = Prompt::new("amp editor");
+        prompt.move_to_start();
+        prompt.move_right();
+        prompt.insert('é');
+        prompt.insert_str("x ");
+        assert_eq!(&*prompt, "aéx mp editor");
+
+        prompt.delete_word();
+        prompt.delete_char();
+        prompt.delete_forward();
+        assert_eq!(&*prompt, "aép editor");
+        assert_eq!(prompt.cursor(), 3);
+
+        prompt.move_left();
+        prompt.move_left();
+        assert_eq!(prompt.cursor(), 0);
+    }
+
+    #[test]
+    fn render_escapes_control_characters_and_locates_the_cursor() {
+        let mut prompt = Prompt::new("a\tb");
+        prompt.move_left();
+
+        assert_eq!(prompt.render(), (String::from("a^Ib"), 3));
+    }
+
+    #[test]
+    fn recall_steps_through_history_and_restores_the_input() {
+        let mut history = InputHistory::default();
+        let mut prompt = Prompt::new("first").with_history("test");
+        prompt.remember(&mut history);
+        prompt.set("second");
+        prompt.remember(&mut history);
+        prompt.set("draft");
+
+        assert!(prompt.recall_previous(&history, HistoryMatch::All));
+        assert_eq!(&*prompt, "second");
+        assert!(prompt.recall_previous(&history, HistoryMatch::All));
+        assert_eq!(&*prompt, "first");
+        assert!(prompt.recall_next(&history));
+        assert!(prompt.recall_next(&history));
+        assert_eq!(&*prompt, "draft");
+        assert!(!Prompt::new("amp").recall_previous(&history, HistoryMatch::All));
+    }
+
+    #[test]
+    fn complete_extends_the_input_to_the_candidates_common_prefix() {
+        let mut prompt = Prompt::new("src/m").with_completer(|_| {
+            vec![String::from("src/models/"), String::from("src/main.rs")]
+        });
+
+        assert!(!prompt.complete());
+        assert_eq!(&*prompt, "src/m");
+        assert!(!Prompt::new("src").complete());
+
+        let mut prompt = Prompt::new("do").with_completer(|_| vec![String::from("documentation/")]);
+        assert!(prompt.complete());
+        assert_eq!((&*prompt, prompt.cursor()), ("documentation/", 14));
+    }
+}
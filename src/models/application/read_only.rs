@@ -0,0 +1,73 @@
+use scribe::Buffer;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Tracks which open buffers are protected from editing, by ID. Files in
+/// generated or vendored directories (e.g. `target` or `node_modules`) are
+/// by default, since changes to them are lost when they're next rebuilt.
+#[derive(Default)]
+pub struct ReadOnlyBuffers {
+    buffers: HashMap<usize, bool>,
+}
+
+impl ReadOnlyBuffers {
+    /// Classifies the buffer by whether its path (relative to the workspace,
+    /// if it's within it) passes through one of the specified directories.
+    /// Buffers that have already been classified are left as they are.
+    pub fn track(&mut self, buffer: &Buffer, workspace_path: &Path, directories: &[String]) {
+        if let Some(id) = buffer.id {
+            self.buffers.entry(id).or_insert_with(|| {
+                buffer.path.as_ref().is_some_and(|path| {
+                    let relative_path = path.strip_prefix(workspace_path).unwrap_or(path);
+
+                    relative_path
+                        .parent()
+                        .into_iter()
+                        .flat_map(Path::components)
+                        .any(|component| directories.iter().any(|dir| component.as_os_str() == dir.as_str()))
+                })
+            });
+        }
+    }
+
+    pub fn contains(&self, buffer: &Buffer) -> bool {
+        matches!(buffer.id.and_then(|id| self.buffers.get(&id)), Some(true))
+    }
+
+    pub fn set(&mut self, buffer_id: usize, read_only: bool) {
+        self.buffers.insert(buffer_id, read_only);
+    }
+
+    pub fn forget(&mut self, buffer_id: usize) {
+        self.buffers.remove(&buffer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ReadOnlyBuffers;
+    use scribe::Buffer;
+    use std::path::{Path, PathBuf};
+
+    #[test]
+    fn track_marks_buffers_in_the_specified_workspace_directories() {
+        let directories = vec![String::from("node_modules"), String::from("target")];
+        let mut read_only_buffers = ReadOnlyBuffers::default();
+        let mut buffers = Vec::new();
+        for (id, path) in ["/target/amp/src/main.rs", "/target/amp/target/debug/build.rs", "/target/amp/web/node_modules/lib.js", "/target/amp/target"].iter().enumerate() {
+            let mut buffer = Buffer::new();
+            buffer.id = Some(id);
+            buffer.path = Some(PathBuf::from(path));
+            read_only_buffers.track(&buffer, Path::new("/target/amp"), &directories);
+            buffers.push(buffer);
+        }
+
+        let read_only: Vec<bool> = buffers.iter().map(|buffer| read_only_buffers.contains(buffer)).collect();
+        assert_eq!(read_only, vec![false, true, true, false]);
+
+        // Overrides survive later tracking.
+        read_only_buffers.set(1, false);
+        read_only_buffers.track(&buffers[1], Path::new("/target/amp"), &directories);
+        assert!(!read_only_buffers.contains(&buffers[1]));
+    }
+}
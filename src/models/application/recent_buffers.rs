@@ -0,0 +1,94 @@
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+
+/// Records the order in which buffers were last accessed, so that
+/// they can be listed from most to least recently used. Accesses are
+/// timestamped using a counter, rather than the clock, to keep the
+/// ordering unambiguous; the clock is only used to detect idle buffers.
+#[derive(Default)]
+pub struct RecentBuffers {
+    accessed: HashMap<usize, u64>,
+    accessed_at: HashMap<usize, Instant>,
+    clock: u64,
+
+    /// Idle buffers that have already been reported by `take_idle`.
+    idle: HashSet<usize>,
+}
+
+impl RecentBuffers {
+    /// Records an access to the buffer, unless it's already the most recent.
+    pub fn access(&mut self, id: usize) {
+        if self.accessed.get(&id) != Some(&self.clock) {
+            self.clock += 1;
+            self.accessed.insert(id, self.clock);
+        }
+        self.accessed_at.insert(id, Instant::now());
+        self.idle.remove(&id);
+    }
+
+    pub fn forget(&mut self, id: usize) {
+        self.accessed.remove(&id);
+        self.accessed_at.remove(&id);
+        self.idle.remove(&id);
+    }
+
+    /// Buffers that haven't been accessed for the specified duration. Each
+    /// is only returned once, until it's accessed (and goes idle) again.
+    pub fn take_idle(&mut self, duration: Duration) -> Vec<usize> {
+        let now = Instant::now();
+        let idle: Vec<usize> = self.accessed_at
+            .iter()
+            .filter(|&(id, &at)| !self.idle.contains(id) && now.duration_since(at) >= duration)
+            .map(|(&id, _)| id)
+            .collect();
+        self.idle.extend(idle.iter().cloned());
+
+        idle
+    }
+
+    /// When the buffer was last accessed; higher values are more recent.
+    pub fn last_access(&self, id: usize) -> Option<u64> {
+        self.accessed.get(&id).cloned()
+    }
+
+    /// Accessed buffer IDs, from most to least recently used.
+    pub fn ids(&self) -> Vec<usize> {
+        let mut ids: Vec<(usize, u64)> = self.accessed.iter().map(|(&id, &time)| (id, time)).collect();
+        ids.sort_by_key(|&(_, time)| std::cmp::Reverse(time));
+
+        ids.into_iter().map(|(id, _)| id).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::RecentBuffers;
+    use std::time::Duration;
+
+    #[test]
+    fn ids_are_ordered_from_most_to_least_recently_accessed() {
+        let mut recent = RecentBuffers::default();
+        recent.access(0);
+        recent.access(1);
+        recent.access(2);
+        recent.access(0);
+        recent.access(0);
+        recent.forget(1);
+
+        assert_eq!(recent.ids(), vec![0, 2]);
+        assert!(recent.last_access(0) > recent.last_access(2));
+        assert_eq!(recent.last_access(1), None);
+    }
+
+    #[test]
+    fn take_idle_returns_each_idle_buffer_once_until_accessed_again() {
+        let mut recent = RecentBuffers::default();
+        recent.access(0);
+        assert_eq!(recent.take_idle(Duration::from_secs(60)), Vec::<usize>::new());
+        assert_eq!(recent.take_idle(Duration::from_secs(0)), vec![0]);
+        assert_eq!(recent.take_idle(Duration::from_secs(0)), Vec::<usize>::new());
+
+        recent.access(0);
+        assert_eq!(recent.take_idle(Duration::from_secs(0)), vec![0]);
+    }
+}
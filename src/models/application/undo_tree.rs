@@ -0,0 +1,323 @@
+use crate::util::content_hash;
+use crate::util::diff::{self, Edit};
+use scribe::Buffer;
+use std::collections::HashMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// A point in a buffer's history, stored as the changes made to its parent's
+/// content (see `UndoTree::content` for reconstructing it).
+pub struct UndoState {
+    pub parent: Option<usize>,
+    changes: Vec<Change>,
+
+    /// When the state was first recorded, as a Unix timestamp.
+    pub time: i64,
+    hash: u64,
+}
+
+// A run of consecutive lines changed from a state's parent: the index of its
+// first line in the parent, and the lines (with their endings) it replaces.
+struct Change {
+    line: usize,
+    removed: Vec<String>,
+    inserted: Vec<String>,
+}
+
+/// A buffer's edit history as a tree of states. The buffer's own history is
+/// linear: undoing and then editing discards the undone changes. Here, the
+/// edit starts a new branch instead, leaving the undone states available to
+/// return to. Only the current state's content is kept in full.
+pub struct UndoTree {
+    states: Vec<UndoState>,
+    current: usize,
+    content: String,
+}
+
+impl UndoTree {
+    pub fn new(content: &str) -> UndoTree {
+        UndoTree {
+            states: vec![UndoState::new(None, Vec::new(), content)],
+            current: 0,
+            content: content.to_string(),
+        }
+    }
+
+    /// Brings the tree up to date with the buffer's content, returning true
+    /// if it has changed. Content matching an existing state (e.g. after an
+    /// undo or redo) returns to that state; anything else is added as a new
+    /// state, branching off from the current one.
+    pub fn record(&mut self, content: &str) -> bool {
+        if self.content == content {
+            return false;
+        }
+
+        // States are only reconstructed to compare them if their hashes match.
+        let hash = content_hash(content);
+        let existing = (0..self.states.len())
+            .find(|&index| self.states[index].hash == hash && self.content(index) == content);
+        match existing {
+            Some(index) => self.current = index,
+            None => {
+                let changes = changes(&self.content, content);
+                self.states.push(UndoState::new(Some(self.current), changes, content));
+                self.current = self.states.len() - 1;
+            },
+        }
+        self.content = content.to_string();
+
+        true
+    }
+
+    pub fn states(&self) -> &[UndoState] {
+        &self.states
+    }
+
+    pub fn current(&self) -> usize {
+        self.current
+    }
+
+    pub fn set_current(&mut self, index: usize) {
+        if index < self.states.len() {
+            self.content = self.content(index);
+            self.current = index;
+        }
+    }
+
+    /// Reconstructs a state's content, by reverting the current state's
+    /// changes back to the closest state they share, and then applying
+    /// those leading to the requested state.
+    pub fn content(&self, index: usize) -> String {
+        let current_path = self.path(self.current);
+        let path = self.path(index);
+        let shared = current_path.iter().zip(path.iter()).take_while(|(a, b)| a == b).count();
+
+        let mut lines: Vec<String> = self.content.split_inclusive('\n').map(String::from).collect();
+        for &state in current_path[shared..].iter().rev() {
+            self.states[state].revert(&mut lines);
+        }
+        for &state in &path[shared..] {
+            self.states[state].apply(&mut lines);
+        }
+
+        lines.concat()
+    }
+
+    // The indices of the states leading from the original one to a state.
+    fn path(&self, mut index: usize) -> Vec<usize> {
+        let mut path = vec![index];
+        while let Some(parent) = self.states[index].parent {
+            path.push(parent);
+            index = parent;
+        }
+        path.reverse();
+
+        path
+    }
+
+    /// The states in display order, along with their branch depth. Each
+    /// state is followed by its first child at the same depth, with any
+    /// later children (i.e. branches) listed after, one level deeper.
+    pub fn layout(&self) -> Vec<(usize, usize)> {
+        let mut children: Vec<Vec<usize>> = vec![Vec::new(); self.states.len()];
+        for (index, state) in self.states.iter().enumerate() {
+            if let Some(parent) = state.parent {
+                children[parent].push(index);
+            }
+        }
+
+        let mut layout = Vec::with_capacity(self.states.len());
+        let mut pending = vec![(0, 0)];
+        while let Some((index, depth)) = pending.pop() {
+            layout.push((index, depth));
+
+            // Pushed in reverse, so that the first child is visited next.
+            for (position, &child) in children[index].iter().enumerate().rev() {
+                pending.push((child, if position == 0 { depth } else { depth + 1 }));
+            }
+        }
+
+        layout
+    }
+}
+
+impl UndoState {
+    fn new(parent: Option<usize>, changes: Vec<Change>, content: &str) -> UndoState {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs() as i64)
+            .unwrap_or(0);
+
+        UndoState {
+            parent,
+            changes,
+            time,
+            hash: content_hash(content),
+        }
+    }
+
+    /// Applies the state's changes to its parent's content.
+    pub fn apply_to(&self, content: &str) -> String {
+        let mut lines: Vec<String> = content.split_inclusive('\n').map(String::from).collect();
+        self.apply(&mut lines);
+
+        lines.concat()
+    }
+
+    /// The number of lines added and removed by the state's changes.
+    pub fn changed_line_counts(&self) -> (usize, usize) {
+        self.changes.iter().fold((0, 0), |(added, removed), change| {
+            (added + change.inserted.len(), removed + change.removed.len())
+        })
+    }
+
+    // Changes are applied last to first, so that earlier line indices stay valid.
+    fn apply(&self, lines: &mut Vec<String>) {
+        for change in self.changes.iter().rev() {
+            let end = change.line + change.removed.len();
+            lines.splice(change.line..end, change.inserted.iter().cloned());
+        }
+    }
+
+    // Reverted first to last, each change's lines start where they did in
+    // the parent, since the lines preceding them have already been reverted.
+    fn revert(&self, lines: &mut Vec<String>) {
+        for change in &self.changes {
+            let end = change.line + change.inserted.len();
+            lines.splice(change.line..end, change.removed.iter().cloned());
+        }
+    }
+}
+
+// The runs of lines changed between two versions of content.
+fn changes(original: &str, modified: &str) -> Vec<Change> {
+    let original: Vec<&str> = original.split_inclusive('\n').collect();
+    let modified: Vec<&str> = modified.split_inclusive('\n').collect();
+    let mut changes: Vec<Change> = Vec::new();
+    let mut line = 0;
+    let mut changing = false;
+
+    for edit in diff::line_edits(&original, &modified) {
+        if let Edit::Equal(_) = edit {
+            line += 1;
+            changing = false;
+            continue;
+        }
+        if !changing {
+            changes.push(Change { line, removed: Vec::new(), inserted: Vec::new() });
+            changing = true;
+        }
+        let change = changes.last_mut().unwrap();
+        match edit {
+            Edit::Delete(content) => {
+                change.removed.push(content.to_string());
+                line += 1;
+            },
+            Edit::Insert(content) => change.inserted.push(content.to_string()),
+            Edit::Equal(_) => (),
+        }
+    }
+
+    changes
+}
+
+/// Undo trees for open buffers, keyed by buffer ID.
+#[derive(Default)]
+pub struct UndoTrees {
+    trees: HashMap<usize, UndoTree>,
+
+    // The revisions of buffers (see `View::revision`) last recorded.
+    revisions: HashMap<usize, usize>,
+}
+
+impl UndoTrees {
+    /// Starts tracking the buffer's history, if it isn't already.
+    pub fn track(&mut self, buffer: &Buffer) {
+        if let Some(id) = buffer.id {
+            self.trees.entry(id).or_insert_with(|| UndoTree::new(&buffer.data()));
+        }
+    }
+
+    /// Records the buffer's content in its tree, if it's being tracked
+    /// and has been edited since the revision last recorded.
+    pub fn record(&mut self, buffer: &Buffer, revision: usize) {
+        if let Some(id) = buffer.id {
+            if let Some(tree) = self.trees.get_mut(&id) {
+                if self.revisions.insert(id, revision) != Some(revision) {
+                    tree.record(&buffer.data());
+                }
+            }
+        }
+    }
+
+    pub fn get(&self, buffer_id: usize) -> Option<&UndoTree> {
+        self.trees.get(&buffer_id)
+    }
+
+    pub fn get_mut(&mut self, buffer_id: usize) -> Option<&mut UndoTree> {
+        self.trees.get_mut(&buffer_id)
+    }
+
+    pub fn forget_buffer(&mut self, buffer_id: usize) {
+        self.trees.remove(&buffer_id);
+        self.revisions.remove(&buffer_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{UndoTree, UndoTrees};
+    use scribe::Buffer;
+
+    #[test]
+    fn record_branches_after_returning_to_an_earlier_state() {
+        let mut tree = UndoTree::new("amp");
+        assert!(tree.record("amp editor"));
+        assert!(tree.record("amp editor text"));
+
+        // Undo twice, then make a different edit.
+        assert!(tree.record("amp editor"));
+        assert!(tree.record("amp"));
+        assert!(!tree.record("amp"));
+        assert!(tree.record("amp buffer"));
+
+        assert_eq!(tree.states().len(), 4);
+        assert_eq!(tree.current(), 3);
+        assert_eq!(tree.states()[3].parent, Some(0));
+        assert_eq!(tree.layout(), vec![(0, 0), (1, 0), (2, 0), (3, 1)]);
+    }
+
+    #[test]
+    fn content_reconstructs_states_on_other_branches() {
+        let mut tree = UndoTree::new("amp\neditor\n");
+        tree.record("amp\ntext\neditor\n");
+        tree.record("amp\ntext\neditor");
+        tree.record("amp\neditor\n");
+        tree.record("rust\namp\neditor\nbuffer\n");
+
+        assert_eq!(tree.content(0), "amp\neditor\n");
+        assert_eq!(tree.content(2), "amp\ntext\neditor");
+        assert_eq!(tree.content(3), "rust\namp\neditor\nbuffer\n");
+        assert_eq!(tree.states()[3].changed_line_counts(), (2, 0));
+
+        tree.set_current(2);
+        assert_eq!(tree.content(1), "amp\ntext\neditor\n");
+        assert_eq!(tree.content(3), "rust\namp\neditor\nbuffer\n");
+        assert!(!tree.record("amp\ntext\neditor"));
+    }
+
+    #[test]
+    fn record_skips_buffers_whose_revision_is_unchanged() {
+        let mut buffer = Buffer::new();
+        buffer.id = Some(0);
+        let mut trees = UndoTrees::default();
+        trees.track(&buffer);
+        buffer.insert("amp");
+        trees.record(&buffer, 1);
+        buffer.insert("text ");
+        trees.record(&buffer, 1);
+        assert_eq!(trees.get(0).unwrap().states().len(), 2);
+
+        trees.record(&buffer, 2);
+        assert_eq!(trees.get(0).unwrap().states().len(), 3);
+    }
+}
@@ -0,0 +1,311 @@
+use crate::view::View;
+use scribe::{Buffer, Workspace};
+use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::rc::Rc;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Shorter words aren't worth completing.
+const MIN_WORD_LENGTH: usize = 3;
+
+/// An index of the identifiers found across all open buffers, used
+/// for completion. Buffers are re-indexed lazily when queried, starting
+/// from the first line changed since they were last indexed.
+#[derive(Default)]
+pub struct WordIndex {
+    buffers: HashMap<usize, IndexedBuffer>,
+
+    /// The number of occurrences of each word, across all buffers.
+    counts: HashMap<String, usize>,
+}
+
+struct IndexedBuffer {
+    lines: Vec<Vec<String>>,
+
+    /// The first line modified since the buffer was last indexed,
+    /// set by listening for changes to the buffer.
+    invalidated_line: Rc<Cell<Option<usize>>>,
+}
+
+impl IndexedBuffer {
+    /// Listens for changes to the buffer through the view (whose listeners,
+    /// unlike its change callback, survive the buffer being initialized
+    /// again) to track modified lines. New buffers need a full index.
+    fn track(buffer: &Buffer, view: &mut View) -> IndexedBuffer {
+        let invalidated_line = Rc::new(Cell::new(Some(0)));
+        let marker = invalidated_line.clone();
+        view.on_change(buffer, move |position| {
+            let line = marker.get().map_or(position.line, |line| line.min(position.line));
+            marker.set(Some(line));
+        });
+
+        IndexedBuffer { lines: Vec::new(), invalidated_line }
+    }
+}
+
+impl WordIndex {
+    /// Brings the index up to date with the workspace's buffers,
+    /// forgetting the words of any that have since been closed.
+    pub fn update(&mut self, workspace: &mut Workspace, view: &mut View) {
+        let start_id = workspace.current_buffer().and_then(|b| b.id);
+        let mut open_ids = HashSet::new();
+
+        if start_id.is_some() {
+            loop {
+                if let Some(buffer) = workspace.current_buffer() {
+                    if let Some(id) = buffer.id {
+                        open_ids.insert(id);
+                        self.update_buffer(id, buffer, view);
+                    }
+                }
+
+                workspace.next_buffer();
+                if workspace.current_buffer().and_then(|b| b.id) == start_id {
+                    break;
+                }
+            }
+        }
+
+        let closed_ids: Vec<usize> = self.buffers
+            .keys()
+            .filter(|id| !open_ids.contains(id))
+            .cloned()
+            .collect();
+        for id in closed_ids {
+            if let Some(buffer) = self.buffers.remove(&id) {
+                for words in &buffer.lines {
+                    remove_words(&mut self.counts, words);
+                }
+            }
+        }
+    }
+
+    fn update_buffer(&mut self, id: usize, buffer: &Buffer, view: &mut View) {
+        let counts = &mut self.counts;
+        let indexed = self.buffers.entry(id).or_insert_with(|| IndexedBuffer::track(buffer, view));
+        let first_line = match indexed.invalidated_line.take() {
+            Some(line) => line.min(indexed.lines.len()),
+            None => return,
+        };
+
+        // Changes can shift subsequent lines, so everything
+        // from the first modified line onward is re-indexed.
+        for words in indexed.lines.drain(first_line..) {
+            remove_words(counts, &words);
+        }
+        for line in buffer.data().lines().skip(first_line) {
+            let words = words(line);
+            for word in &words {
+                *counts.entry(word.clone()).or_insert(0) += 1;
+            }
+            indexed.lines.push(words);
+        }
+    }
+
+    /// Forgets the words of a closed buffer, and stops tracking its changes.
+    pub fn forget_buffer(&mut self, buffer_id: usize) {
+        if let Some(buffer) = self.buffers.remove(&buffer_id) {
+            for words in &buffer.lines {
+                remove_words(&mut self.counts, words);
+            }
+        }
+    }
+
+    /// Returns indexed words that start with (but aren't equal to)
+    /// the prefix, ordered by their frequency across all buffers.
+    pub fn completions(&self, prefix: &str) -> Vec<&str> {
+        let mut completions: Vec<(&str, usize)> = self.counts
+            .iter()
+            .filter(|(word, _)| word.starts_with(prefix) && word.as_str() != prefix)
+            .map(|(word, count)| (word.as_str(), *count))
+            .collect();
+        completions.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+
+        completions.into_iter().map(|(word, _)| word).collect()
+    }
+}
+
+fn remove_words(counts: &mut HashMap<String, usize>, words: &[String]) {
+    for word in words {
+        if let Some(count) = counts.get_mut(word) {
+            *count -= 1;
+            if *count == 0 {
+                counts.remove(word);
+            }
+        }
+    }
+}
+
+fn words(line: &str) -> Vec<String> {
+    line.split(|c: char| !is_word_char(c))
+        .filter(|word| word.chars().count() >= MIN_WORD_LENGTH)
+        .filter(|word| !word.starts_with(|c: char| c.is_numeric()))
+        .map(String::from)
+        .collect()
+}
+
+/// Returns the trailing part of the content that could be completed.
+pub fn word_prefix(content: &str) -> &str {
+    let start = content
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word_char(c))
+        .last()
+        .map(|(index, _)| index)
+        .unwrap_or_else(|| content.len());
+
+    &content[start..]
+}
+
+/// Returns the word surrounding the (grapheme) offset, if any.
+pub fn word_at(line: &str, offset: usize) -> &str {
+    let index = line
+        .grapheme_indices(true)
+        .nth(offset)
+        .map(|(index, _)| index)
+        .unwrap_or_else(|| line.len());
+    let start = word_prefix(&line[..index]).len();
+    let end = line[index..]
+        .find(|c: char| !is_word_char(c))
+        .unwrap_or_else(|| line.len() - index);
+
+    &line[index - start..index + end]
+}
+
+/// Replaces whole-word occurrences of the word (i.e.
+/// excluding those that are part of a longer word).
+pub fn replace_word(content: &str, word: &str, replacement: &str) -> String {
+    let mut output = String::with_capacity(content.len());
+    let mut last_end = 0;
+
+    for (index, _) in content.match_indices(word) {
+        let end = index + word.len();
+        let preceded = content[..index].ends_with(is_word_char);
+        let followed = content[end..].starts_with(is_word_char);
+        if preceded || followed || index < last_end {
+            continue;
+        }
+
+        output.push_str(&content[last_end..index]);
+        output.push_str(replacement);
+        last_end = end;
+    }
+    output.push_str(&content[last_end..]);
+
+    output
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{replace_word, word_at, word_prefix, WordIndex};
+    use crate::models::application::Preferences;
+    use crate::view::View;
+    use scribe::{Buffer, Workspace};
+    use scribe::buffer::Position;
+    use std::cell::RefCell;
+    use std::path::Path;
+    use std::rc::Rc;
+    use std::sync::mpsc;
+
+    fn view() -> View {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+
+        View::new(preferences, mpsc::channel().0).unwrap()
+    }
+
+    #[test]
+    fn completions_include_words_from_all_buffers_ordered_by_frequency() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("editor edited\n42abc");
+        workspace.add_buffer(buffer);
+        let mut buffer = Buffer::new();
+        buffer.insert("edited ed");
+        workspace.add_buffer(buffer);
+
+        let mut index = WordIndex::default();
+        index.update(&mut workspace, &mut view());
+
+        assert_eq!(index.completions("ed"), vec!["edited", "editor"]);
+        assert!(index.completions("42").is_empty());
+    }
+
+    #[test]
+    fn word_prefix_returns_trailing_word_characters() {
+        assert_eq!(word_prefix("let text_ed"), "text_ed");
+        assert_eq!(word_prefix("value("), "");
+    }
+
+    #[test]
+    fn word_at_returns_the_word_surrounding_the_offset() {
+        assert_eq!(word_at("let text_editor = 1;", 6), "text_editor");
+        assert_eq!(word_at("let text_editor = 1;", 4), "text_editor");
+        assert_eq!(word_at("let text_editor = 1;", 15), "text_editor");
+        assert_eq!(word_at("let text_editor = 1;", 16), "");
+    }
+
+    #[test]
+    fn replace_word_only_replaces_whole_words() {
+        assert_eq!(
+            replace_word("editor(editor_id, self.editor);", "editor", "amp"),
+            "amp(editor_id, self.amp);"
+        );
+    }
+
+    #[test]
+    fn update_reindexes_modified_lines_and_closed_buffers() {
+        let mut view = view();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        workspace.add_buffer(buffer);
+        view.initialize_buffer(workspace.current_buffer().unwrap()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("buffer");
+        workspace.add_buffer(buffer);
+        view.initialize_buffer(workspace.current_buffer().unwrap()).unwrap();
+
+        let mut index = WordIndex::default();
+        index.update(&mut workspace, &mut view);
+        assert_eq!(index.completions("buf"), vec!["buffer"]);
+
+        // Modify the second line, and close the other buffer.
+        workspace.close_current_buffer();
+        let buffer = workspace.current_buffer().unwrap();
+        buffer.cursor.move_to(Position { line: 1, offset: 0 });
+        buffer.insert("text_");
+        index.update(&mut workspace, &mut view);
+
+        assert_eq!(index.completions("text"), vec!["text_editor"]);
+        assert!(index.completions("edi").is_empty());
+        assert!(index.completions("buf").is_empty());
+        assert_eq!(index.completions("am"), vec!["amp"]);
+    }
+
+    #[test]
+    fn update_reindexes_lines_modified_after_buffers_are_initialized_again() {
+        let mut view = view();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor");
+        workspace.add_buffer(buffer);
+        view.initialize_buffer(workspace.current_buffer().unwrap()).unwrap();
+
+        let mut index = WordIndex::default();
+        index.update(&mut workspace, &mut view);
+
+        // Initializing the buffer again replaces its change callback.
+        let buffer = workspace.current_buffer().unwrap();
+        view.initialize_buffer(buffer).unwrap();
+        buffer.cursor.move_to(Position { line: 1, offset: 0 });
+        buffer.insert("text_");
+        index.update(&mut workspace, &mut view);
+
+        assert_eq!(index.completions("text"), vec!["text_editor"]);
+        assert!(index.completions("edi").is_empty());
+    }
+}
@@ -0,0 +1,100 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::util::json;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use crate::yaml::yaml::Yaml;
+
+const FILE_NAME: &str = "trusted_workspaces.json";
+
+/// Whether the user trusts each workspace they've been asked about, which
+/// determines whether project-local configuration that runs commands (e.g.
+/// debug adapters, or tasks) is used there. Decisions are made once per
+/// workspace, and persisted between sessions in the user data directory.
+#[derive(Default)]
+pub struct WorkspaceTrust {
+    decisions: HashMap<PathBuf, bool>,
+}
+
+impl WorkspaceTrust {
+    /// Loads decisions from disk, falling back to none
+    /// having been made if they can't be read.
+    pub fn load() -> WorkspaceTrust {
+        trust_path()
+            .ok()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|content| WorkspaceTrust::parse(&content))
+            .unwrap_or_default()
+    }
+
+    fn parse(content: &str) -> WorkspaceTrust {
+        let mut trust = WorkspaceTrust::default();
+        if let Ok(Yaml::Hash(decisions)) = json::parse(content) {
+            for (path, trusted) in decisions {
+                if let (Some(path), Some(trusted)) = (path.as_str(), trusted.as_bool()) {
+                    trust.decisions.insert(PathBuf::from(path), trusted);
+                }
+            }
+        }
+
+        trust
+    }
+
+    fn encode(&self) -> String {
+        let entries: Vec<(String, bool)> = self.decisions
+            .iter()
+            .map(|(path, &trusted)| (path.to_string_lossy().into_owned(), trusted))
+            .collect();
+        let decisions = entries
+            .iter()
+            .map(|&(ref path, trusted)| (path.as_str(), Yaml::Boolean(trusted)))
+            .collect();
+
+        json::encode(&json::object(decisions))
+    }
+
+    /// Whether the workspace is trusted, or None if the user hasn't decided.
+    pub fn decision(&self, workspace: &Path) -> Option<bool> {
+        self.decisions.get(&normalize(workspace)).cloned()
+    }
+
+    /// Records whether the workspace is trusted, saving the decision.
+    pub fn decide(&mut self, workspace: &Path, trusted: bool) -> Result<()> {
+        self.decisions.insert(normalize(workspace), trusted);
+
+        self.save()
+    }
+
+    fn save(&self) -> Result<()> {
+        fs::write(trust_path()?, self.encode()).chain_err(|| "Couldn't save workspace trust decisions")
+    }
+}
+
+// Workspaces are identified by their canonical path, so
+// that they're recognized however they're opened.
+fn normalize(workspace: &Path) -> PathBuf {
+    workspace.canonicalize().unwrap_or_else(|_| workspace.to_path_buf())
+}
+
+fn trust_path() -> Result<PathBuf> {
+    Ok(Preferences::data_directory()?.join(FILE_NAME))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::WorkspaceTrust;
+    use std::path::Path;
+
+    #[test]
+    fn decisions_survive_encoding() {
+        let mut trust = WorkspaceTrust::default();
+        trust.decisions.insert(Path::new("/src/amp").to_path_buf(), true);
+        trust.decisions.insert(Path::new("/tmp/cloned").to_path_buf(), false);
+
+        let trust = WorkspaceTrust::parse(&trust.encode());
+        assert_eq!(trust.decision(Path::new("/src/amp")), Some(true));
+        assert_eq!(trust.decision(Path::new("/tmp/cloned")), Some(false));
+        assert_eq!(trust.decision(Path::new("/src")), None);
+    }
+}
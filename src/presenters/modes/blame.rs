@@ -0,0 +1,34 @@
+use crate::errors::*;
+use scribe::Workspace;
+use crate::models::application::modes::BlameMode;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &BlameMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+    presenter.print_buffer(buf, &data, None, None)?;
+
+    // Summarize the commit behind the cursor's line.
+    let commit = match mode.line(buf.cursor.line) {
+        Some(line) => format!(" {} {}", line.short_id(), line.summary),
+        None => String::from(" Not committed yet"),
+    };
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: format!(" {} ", mode),
+            style: Style::Default,
+            colors: Colors::SearchMode,
+        },
+        StatusLineData {
+            content: commit,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
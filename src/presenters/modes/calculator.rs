@@ -0,0 +1,57 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::CalculatorMode;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &CalculatorMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Draw the visible set of tokens to the terminal.
+    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    presenter.print_buffer(buffer, &data, None, None)?;
+
+    let mode_display = format!(" {} ", mode);
+    let (input, input_cursor) = mode.input.render();
+    let expression_input = format!(" {}", input);
+    let result = mode.result()
+        .map(|result| format!(" = {} ", result))
+        .unwrap_or_default();
+
+    let cursor_offset =
+        mode_display.graphemes(true).count() + 1 + input_cursor;
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: mode_display,
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        StatusLineData {
+            content: expression_input,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        StatusLineData {
+            content: result,
+            style: Style::Bold,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Move the cursor to its position in the expression input.
+    {
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset
+        }));
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -1,17 +1,29 @@
 use crate::errors::*;
+use crate::models::application::modes::ConfirmMode;
 use scribe::Workspace;
 use crate::view::{Colors, StatusLineData, Style, View};
 
-pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
+pub fn display(workspace: &mut Workspace, mode: &ConfirmMode, view: &mut View) -> Result<()> {
     let mut presenter = view.build_presenter()?;
-    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
-    let data = buf.data();
 
-    // Draw the visible set of tokens to the terminal.
-    presenter.print_buffer(buf, &data, None, None)?;
+    // Draw the visible set of tokens to the terminal. Trusting a
+    // workspace can be confirmed before any buffers are open.
+    let data = workspace.current_buffer().map(|buf| buf.data());
+    if let (Some(buf), Some(data)) = (workspace.current_buffer(), data.as_ref()) {
+        presenter.print_buffer(buf, data, None, None)?;
+    } else if !mode.trusting_workspace {
+        bail!(BUFFER_MISSING);
+    }
 
     // Draw the status line as a search prompt.
-    let confirmation = "Are you sure? (y/n)".to_string();
+    let confirmation = if mode.trusting_workspace {
+        format!(
+            "Trust {}? Its configuration can run commands. (y/n)",
+            workspace.path.to_string_lossy()
+        )
+    } else {
+        "Are you sure? (y/n)".to_string()
+    };
     presenter.print_status_line(&[
         StatusLineData {
             content: confirmation,
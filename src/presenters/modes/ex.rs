@@ -0,0 +1,49 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::ExMode;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &ExMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+
+    // Draw the visible set of tokens to the terminal.
+    let buffer = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buffer.data();
+    presenter.print_buffer(buffer, &data, None, None)?;
+
+    let mode_display = format!(" {} ", mode);
+    let (input, input_cursor) = mode.input.render();
+    let command_input = format!(" :{}", input);
+
+    let cursor_offset =
+        mode_display.graphemes(true).count() + 2 + input_cursor;
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: mode_display,
+            style: Style::Default,
+            colors: Colors::Inverted,
+        },
+        StatusLineData {
+            content: command_input,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Move the cursor to its position in the command input.
+    {
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset
+        }));
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -0,0 +1,74 @@
+use crate::errors::*;
+use crate::models::application::FileTree;
+use crate::models::application::modes::{FileOperation, FileTreeMode};
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, tree: Option<&FileTree>, mode: &FileTreeMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let data = workspace.current_buffer().map(|buf| buf.data());
+    if let (Some(buf), Some(data)) = (workspace.current_buffer(), data.as_ref()) {
+        presenter.print_buffer(buf, data, None, None)?;
+    }
+
+    // The tree has focus, rather than the buffer.
+    presenter.set_cursor(None);
+
+    let selection = tree
+        .and_then(|tree| tree.selection().map(|entry| (tree.root(), entry)))
+        .map(|(root, entry)| entry.path.strip_prefix(root).unwrap_or(&entry.path).to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let (label, colors) = match mode.operation {
+        Some(FileOperation::Create) => (String::from("New file (end with / for a directory): "), Colors::Default),
+        Some(FileOperation::Rename) => (format!("Rename {} to: ", selection), Colors::Default),
+        Some(FileOperation::Delete) => (format!("Delete {}? y/n", selection), Colors::Warning),
+        None => (String::new(), Colors::Default),
+    };
+
+    if mode.entering_input() {
+        // Draw the status line as an input prompt.
+        let (input, input_cursor) = mode.input.render();
+        let cursor_offset = label.chars().count() + input_cursor;
+        presenter.print_status_line(&[
+            StatusLineData {
+                content: format!("{}{}", label, input),
+                style: Style::Default,
+                colors,
+            }
+        ]);
+
+        // Move the cursor to its position in the name input.
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset,
+        }));
+    } else if mode.operation.is_some() {
+        presenter.print_status_line(&[
+            StatusLineData {
+                content: label,
+                style: Style::Default,
+                colors,
+            }
+        ]);
+    } else {
+        presenter.print_status_line(&[
+            StatusLineData {
+                content: format!(" {} ", mode),
+                style: Style::Default,
+                colors: Colors::SelectMode,
+            },
+            StatusLineData {
+                content: format!(" {}", selection),
+                style: Style::Default,
+                colors: Colors::Focused,
+            }
+        ]);
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -1,25 +1,45 @@
 use crate::errors::*;
-use crate::presenters::current_buffer_status_line_data;
+use crate::models::application::{Completion, MultiCursor};
+use crate::presenters::{current_buffer_status_line_data, word_count_status_line_data};
 use scribe::Workspace;
-use crate::view::{Colors, StatusLineData, Style, View};
+use std::collections::HashMap;
+use crate::view::{Colors, Overlay, StatusLineData, Style, View};
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
 
-pub fn display(workspace: &mut Workspace, view: &mut View) -> Result<()> {
+pub fn display(workspace: &mut Workspace, view: &mut View, multi_cursors: &HashMap<usize, MultiCursor>, completion: Option<&Completion>) -> Result<()> {
     let mut presenter = view.build_presenter()?;
-    let buffer_status = current_buffer_status_line_data(workspace);
+    let buffer_status = current_buffer_status_line_data(workspace, presenter.view);
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let data = buf.data();
+    let cursors = buf.id.and_then(|id| multi_cursors.get(&id)).map(|cursors| cursors.highlights());
 
     // Draw the visible set of tokens to the terminal.
-    presenter.print_buffer(buf, &data, None, None)?;
+    presenter.print_buffer(buf, &data, cursors.as_deref(), None)?;
 
-    presenter.print_status_line(&[
+    // Draw completions beneath the word being completed, lined up with it.
+    if let (Some(completion), Some(cursor)) = (completion, presenter.cursor()) {
+        let overlay = Overlay {
+            entries: completion.matches().cloned().collect(),
+            selected_index: completion.selected_index(),
+        };
+        let anchor = Position {
+            line: cursor.line,
+            offset: cursor.offset.saturating_sub(completion.prefix().graphemes(true).count()),
+        };
+        presenter.print_overlay(&overlay, anchor);
+    }
+
+    let mut status_line = vec![
         StatusLineData {
             content: " INSERT ".to_string(),
             style: Style::Default,
             colors: Colors::Insert,
         },
         buffer_status
-    ]);
+    ];
+    status_line.extend(word_count_status_line_data(buf, &data, presenter.view));
+    presenter.print_status_line(&status_line);
 
     // Render the changes to the screen.
     presenter.present();
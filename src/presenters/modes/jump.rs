@@ -6,7 +6,7 @@ use crate::view::{Colors, StatusLineData, Style, View};
 
 pub fn display(workspace: &mut Workspace, mode: &mut JumpMode, view: &mut View) -> Result<()> {
     let mut presenter = view.build_presenter()?;
-    let buffer_status = current_buffer_status_line_data(workspace);
+    let buffer_status = current_buffer_status_line_data(workspace, presenter.view);
     let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
     let data = buf.data();
 
@@ -0,0 +1,37 @@
+use crate::errors::*;
+use scribe::Workspace;
+use crate::models::application::modes::KeyRecorderMode;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &KeyRecorderMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let data;
+
+    if let Some(buf) = workspace.current_buffer() {
+        data = buf.data();
+        presenter.print_buffer(buf, &data, None, None)?;
+    }
+
+    let prompt = if mode.binding.keys.is_empty() {
+        format!(" Press the keys to bind in {} mode", mode.binding.mode)
+    } else {
+        format!(" Recorded {}", mode.binding)
+    };
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: format!(" {} ", mode),
+            style: Style::Default,
+            colors: Colors::SearchMode,
+        },
+        StatusLineData {
+            content: prompt,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -11,7 +11,7 @@ pub fn display(workspace: &mut Workspace, mode: &LineJumpMode, view: &mut View)
     presenter.print_buffer(buf, &data, None, None)?;
 
     // Draw the status line as an input prompt.
-    let input_prompt = format!("Go to line: {}", mode.input);
+    let input_prompt = format!("Go to line (42, 42:7, +5, -5, 50%): {}", mode.input);
     let input_prompt_len = input_prompt.len();
     presenter.print_status_line(&[
         StatusLineData {
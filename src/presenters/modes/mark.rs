@@ -0,0 +1,36 @@
+use crate::errors::*;
+use crate::models::application::modes::{MarkAction, MarkMode};
+use scribe::Workspace;
+use crate::presenters::current_buffer_status_line_data;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &MarkMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buffer_status = current_buffer_status_line_data(workspace, presenter.view);
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+    presenter.print_buffer(buf, &data, None, None)?;
+
+    let hint = match mode.action {
+        MarkAction::Set => " Press a-z to name the mark",
+        MarkAction::Jump => " Press a-z to jump to a mark, or ` to list them",
+    };
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: format!(" {} ", mode),
+            style: Style::Default,
+            colors: Colors::SelectMode,
+        },
+        StatusLineData {
+            content: hint.to_string(),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        buffer_status
+    ]);
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -1,10 +1,22 @@
+pub mod blame;
+pub mod calculator;
+pub mod close_pattern;
 pub mod confirm;
+pub mod ex;
+pub mod file_tree;
 pub mod insert;
 pub mod jump;
+pub mod key_recorder;
 pub mod line_jump;
+pub mod mark;
+pub mod passphrase;
 pub mod path;
+pub mod register;
 pub mod normal;
+pub mod rename;
+pub mod replace;
 pub mod search;
 pub mod search_select;
 pub mod select;
+pub mod select_block;
 pub mod select_line;
@@ -1,36 +1,67 @@
 use crate::errors::*;
 use scribe::Workspace;
 use scribe::buffer::Position;
-use crate::presenters::{current_buffer_status_line_data, git_status_line_data};
-use git2::Repository;
+use crate::models::application::{BufferGroups, GitWorker, MultiCursor, ReadOnlyBuffers};
+use crate::models::application::modes::PIN_GLYPH;
+use crate::presenters::{current_buffer_status_line_data, git_status_line_data, word_count_status_line_data};
 use crate::view::{Colors, StatusLineData, Style, View};
+use std::collections::{HashMap, HashSet};
 
-pub fn display(workspace: &mut Workspace, view: &mut View, repo: &Option<Repository>) -> Result<()> {
+pub fn display(workspace: &mut Workspace, view: &mut View, git_worker: &GitWorker, buffer_groups: &BufferGroups, pinned_buffers: &HashSet<usize>, read_only_buffers: &ReadOnlyBuffers, multi_cursors: &HashMap<usize, MultiCursor>) -> Result<()> {
     let mut presenter = view.build_presenter()?;
-    let buffer_status = current_buffer_status_line_data(workspace);
+    let mut buffer_status = current_buffer_status_line_data(workspace, presenter.view);
 
     if let Some(buf) = workspace.current_buffer() {
-        // Draw the visible set of tokens to the terminal.
+        // Draw the visible set of tokens to the terminal,
+        // highlighting any extra cursors.
         let data = buf.data();
-        presenter.print_buffer(buf, &data, None, None)?;
+        let cursors = buf.id.and_then(|id| multi_cursors.get(&id)).map(|cursors| cursors.highlights());
+        presenter.print_buffer(buf, &data, cursors.as_deref(), None)?;
+
+        if buf.id.map(|id| pinned_buffers.contains(&id)) == Some(true) {
+            buffer_status.content = format!(" {}{}", PIN_GLYPH, buffer_status.content);
+        }
+
+        if read_only_buffers.contains(buf) {
+            buffer_status.content.push_str(" [read-only]");
+        }
+        if presenter.view.narrowing(buf).is_some() {
+            buffer_status.content.push_str(" [narrowed]");
+        }
+
+        // Show the indentation detected in the buffer, which
+        // may differ from the one configured for its type.
+        if let Some(indentation) = presenter.view.indentation(buf) {
+            buffer_status.content.push_str(&format!(" [{}]", indentation));
+        }
 
         // Determine mode display color based on buffer modification status.
-        let colors = if buf.modified() {
+        let colors = if presenter.view.modified(buf) {
             Colors::Warning
         } else {
             Colors::Inverted
         };
 
-        // Build the status line mode and buffer title display.
-        presenter.print_status_line(&[
+        // Build the status line mode and buffer title display,
+        // with buffer group tabs when more than one is in use.
+        let mut status_line = vec![
             StatusLineData {
                 content: " NORMAL ".to_string(),
                 style: Style::Default,
                 colors,
-            },
-            buffer_status,
-            git_status_line_data(&repo, &buf.path)
-        ]);
+            }
+        ];
+        if let Some(tabs) = buffer_groups.tabs() {
+            status_line.push(StatusLineData {
+                content: tabs,
+                style: Style::Default,
+                colors: Colors::Default,
+            });
+        }
+        status_line.push(buffer_status);
+        status_line.push(git_status_line_data(git_worker, &buf.path));
+        status_line.extend(word_count_status_line_data(buf, &data, presenter.view));
+        presenter.print_status_line(&status_line);
 
         presenter.present();
     } else {
@@ -0,0 +1,51 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::PassphraseMode;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &PassphraseMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let data;
+
+    // Draw the visible set of tokens to the terminal. There mightn't be a
+    // buffer yet, when prompting for a file passed on the command line.
+    if let Some(buffer) = workspace.current_buffer() {
+        data = buffer.data();
+        presenter.print_buffer(buffer, &data, None, None)?;
+    }
+
+    // Mask the passphrase, revealing only its length.
+    let mode_display = format!(" {} ", mode);
+    let passphrase_input = format!(" {}", "*".repeat(mode.input.graphemes(true).count()));
+    let cursor_offset =
+        mode_display.graphemes(true).count() + 1 + mode.input[..mode.input.cursor()].graphemes(true).count();
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: mode_display,
+            style: Style::Default,
+            colors: Colors::Warning,
+        },
+        StatusLineData {
+            content: passphrase_input,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Move the cursor to its position in the passphrase input.
+    {
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset
+        }));
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -14,14 +14,11 @@ pub fn display(workspace: &mut Workspace, mode: &PathMode, view: &mut View) -> R
     presenter.print_buffer(buffer, &data, None, None)?;
 
     let mode_display = format!(" {} ", mode);
-    let search_input = format!(
-        " {}",
-        mode.input
-    );
+    let (input, input_cursor) = mode.input.render();
+    let search_input = format!(" {}", input);
 
     let cursor_offset =
-        mode_display.graphemes(true).count() +
-        search_input.graphemes(true).count();
+        mode_display.graphemes(true).count() + 1 + input_cursor;
 
     presenter.print_status_line(&[
         StatusLineData {
@@ -36,7 +33,7 @@ pub fn display(workspace: &mut Workspace, mode: &PathMode, view: &mut View) -> R
         },
     ]);
 
-    // Move the cursor to the end of the search query input.
+    // Move the cursor to its position in the path input.
     {
         let cursor_line = presenter.height() - 1;
         presenter.set_cursor(Some(Position {
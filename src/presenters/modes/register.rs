@@ -0,0 +1,42 @@
+use crate::errors::*;
+use crate::models::application::modes::jump::SelectModeOptions;
+use crate::models::application::modes::RegisterMode;
+use crate::models::application::SYSTEM_REGISTER;
+use scribe::Workspace;
+use scribe::buffer::Range;
+use crate::presenters::current_buffer_status_line_data;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &RegisterMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buffer_status = current_buffer_status_line_data(workspace, presenter.view);
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+
+    // Keep the selection we'll return to highlighted.
+    let selected_range = match mode.select_mode {
+        SelectModeOptions::None => None,
+        SelectModeOptions::Select(ref select_mode) => Some(Range::new(select_mode.anchor, *buf.cursor.clone())),
+        SelectModeOptions::SelectLine(ref select_mode) => Some(select_mode.to_range(&buf.cursor)),
+    };
+    presenter.print_buffer(buf, &data, selected_range.as_ref().map(std::slice::from_ref), None)?;
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: format!(" {} ", mode),
+            style: Style::Default,
+            colors: Colors::SelectMode,
+        },
+        StatusLineData {
+            content: format!(" Press a-z to select a named register, or {} for the clipboard", SYSTEM_REGISTER),
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+        buffer_status
+    ]);
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -0,0 +1,37 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::RenameMode;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &RenameMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+    presenter.print_buffer(buf, &data, None, None)?;
+
+    // Draw the status line as an input prompt.
+    let label = format!("Rename {} to: ", mode.token);
+    let (input, input_cursor) = mode.input.render();
+    let cursor_offset = label.chars().count() + input_cursor;
+    let input_prompt = format!("{}{}", label, input);
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: input_prompt,
+            style: Style::Default,
+            colors: Colors::Default,
+        }
+    ]);
+
+    // Move the cursor to its position in the new name input.
+    let cursor_line = presenter.height() - 1;
+    presenter.set_cursor(Some(Position {
+        line: cursor_line,
+        offset: cursor_offset,
+    }));
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
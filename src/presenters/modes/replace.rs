@@ -0,0 +1,58 @@
+use crate::errors::*;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::models::application::modes::{ReplaceMode, ReplaceStage};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &ReplaceMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let data = buf.data();
+
+    // Highlight the match awaiting confirmation, as search mode does.
+    let highlight = mode.current_range().cloned().into_iter().collect::<Vec<_>>();
+    presenter.print_buffer(buf, &data, Some(&highlight), None)?;
+
+    let mode_display = format!(" {} ", mode);
+    let (label, (input, input_cursor)) = match mode.stage {
+        ReplaceStage::Pattern => (String::from(" Replace: "), mode.pattern.render()),
+        ReplaceStage::Replacement => (format!(" Replace {} with: ", mode.pattern.render().0), mode.replacement.render()),
+        ReplaceStage::Confirm => (format!(
+            " Replace with \"{}\"? (y)es, (n)o, (a)ll, (q)uit",
+            mode.current_replacement().unwrap_or("")
+        ), (String::new(), 0)),
+    };
+    let cursor_offset =
+        mode_display.graphemes(true).count() +
+        label.graphemes(true).count() +
+        input_cursor;
+    let prompt = format!("{}{}", label, input);
+
+    presenter.print_status_line(&[
+        StatusLineData {
+            content: mode_display,
+            style: Style::Default,
+            colors: Colors::SearchMode,
+        },
+        StatusLineData {
+            content: prompt,
+            style: Style::Default,
+            colors: Colors::Focused,
+        },
+    ]);
+
+    // Move the cursor to its position in the input, while it's being entered.
+    if mode.stage != ReplaceStage::Confirm {
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset,
+        }));
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
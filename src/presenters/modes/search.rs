@@ -1,4 +1,5 @@
 use crate::errors::*;
+use pad::PadStr;
 use scribe::Workspace;
 use scribe::buffer::Position;
 use crate::models::application::modes::SearchMode;
@@ -14,25 +15,24 @@ pub fn display(workspace: &mut Workspace, mode: &SearchMode, view: &mut View) ->
     presenter.print_buffer(buffer, &data, mode.results.as_ref().map(|r| r.as_slice()), None)?;
 
     let mode_display = format!(" {} ", mode);
-    let search_input = format!(
-        " {}",
-        mode.input.as_ref().unwrap_or(&String::new())
-    );
+    let (input, input_cursor) = mode.input.render();
+    let search_input = format!(" {}", input);
     let result_display = if mode.insert {
         String::new()
     } else if let Some(ref results) = mode.results {
+        // Let the user know whether moving past the end will wrap around.
+        let wrap_display = if mode.wrap { "wraps" } else { "no wrap" };
         if results.len() == 1 {
-            String::from("1 match")
+            format!("1 match, {}", wrap_display)
         } else {
-            format!("{} of {} matches", results.selected_index() + 1, results.len())
+            format!("{} of {} matches, {}", results.selected_index() + 1, results.len(), wrap_display)
         }
     } else {
         String::new()
     };
 
     let cursor_offset =
-        mode_display.graphemes(true).count() +
-        search_input.graphemes(true).count();
+        mode_display.graphemes(true).count() + 1 + input_cursor;
 
     presenter.print_status_line(&[
         StatusLineData {
@@ -52,7 +52,20 @@ pub fn display(workspace: &mut Workspace, mode: &SearchMode, view: &mut View) ->
         },
     ]);
 
-    // Move the cursor to the end of the search query input.
+    // Draw matching previous queries just above the status line,
+    // with the one that'd be accepted (using tab) closest to the input.
+    if mode.insert {
+        let width = presenter.width();
+        let status_line = presenter.height() - 1;
+        for (index, suggestion) in mode.suggestions.iter().enumerate().take(status_line) {
+            presenter.print(&Position{ line: status_line - 1 - index, offset: 0 },
+                       Style::Default,
+                       if index == 0 { Colors::Focused } else { Colors::Default },
+                       format!(" {}", suggestion).with_exact_width(width));
+        }
+    }
+
+    // Move the cursor to its position in the search query input.
     if mode.insert {
         let cursor_line = presenter.height() - 1;
         presenter.set_cursor(Some(Position {
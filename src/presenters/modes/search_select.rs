@@ -1,23 +1,15 @@
 use crate::errors::*;
-use std::cmp;
 use std::fmt::Display;
 use crate::models::application::modes::{SearchSelectMode};
-use pad::PadStr;
 use crate::presenters::current_buffer_status_line_data;
 use scribe::Workspace;
-use scribe::buffer::Position;
-use crate::view::{Colors, StatusLineData, Style, View};
-use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, ListPreview, StatusLineData, Style, View};
 
 pub fn display<T: Display>(workspace: &mut Workspace, mode: &mut SearchSelectMode<T>, view: &mut View) -> Result<()> {
     let mut presenter = view.build_presenter()?;
-    let mode_config = mode.config().clone();
-    let mut padded_message = String::new();
-    let mut padded_content = Vec::new();
-    let mut remaining_lines = Vec::new();
     let mut data;
 
-    let buffer_status = current_buffer_status_line_data(workspace);
+    let buffer_status = current_buffer_status_line_data(workspace, presenter.view);
 
     if let Some(buf) = workspace.current_buffer() {
         data = buf.data();
@@ -33,64 +25,28 @@ pub fn display<T: Display>(workspace: &mut Workspace, mode: &mut SearchSelectMod
         ]);
     }
 
-    if let Some(message) = mode.message() {
-        padded_message = message.pad_to_width(presenter.width());
-        presenter.print(&Position{ line: 0, offset: 0 },
-                   Style::Default,
-                   Colors::Default,
-                   &padded_message);
-    } else {
-        // Draw the list of search results.
-        for (line, result) in mode.results().enumerate() {
-            let (content, colors, style) = if line == mode.selected_index() {
-                (format!("> {}", result), Colors::Focused, Style::Bold)
-            } else {
-                (format!("  {}", result), Colors::Default, Style::Default)
-            };
-            padded_content.push((
-                Position{ line, offset: 0 },
-                style,
-                colors,
-                content.pad_to_width(presenter.width())
-            ));
-        }
-
-        for (position, style, colors, content) in padded_content.iter() {
-            presenter.print(position, *style, *colors, content);
-        }
-    }
-
-    // Clear any remaining lines in the result display area.
-    for line in cmp::max(mode.results().len(), 1)..mode_config.max_results {
-        remaining_lines.push((
-           Position{ line, offset: 0 },
-           Style::Default,
-           Colors::Default,
-           String::new().pad_to_width(presenter.width())
-        ));
-    }
-    for (position, style, colors, content) in remaining_lines.iter() {
-        presenter.print(position, *style, *colors, content);
-    }
-
-    // Draw the divider.
-    let line = mode_config.max_results;
-    let colors = if mode.insert_mode() {
-        Colors::Insert
-    } else {
-        Colors::Inverted
+    // List actions using the keys bound to them in the current keymap.
+    let keymap_mode = if mode.insert_mode() { "search_select_insert" } else { "search_select" };
+    let actions = mode.actions()
+        .into_iter()
+        .filter_map(|(action, command)| {
+            presenter.view.key_for(keymap_mode, command).map(|key| (key, action))
+        })
+        .collect();
+
+    let message = mode.message();
+    let list = ListPreview {
+        entries: mode.results().map(|result| result.to_string()).collect(),
+        selected_index: mode.selected_index(),
+        matches: (0..mode.results().count()).map(|index| mode.matched_indices(index)).collect(),
+        message,
+        query: mode.query().render(),
+        insert: mode.insert_mode(),
+        preview: mode.preview(),
+        actions,
+        list_height: mode.config().max_results,
     };
-    let padded_content = mode.query().pad_to_width(presenter.width());
-    presenter.print(&Position{ line, offset: 0 },
-               Style::Bold,
-               colors,
-               &padded_content);
-
-    // Place the cursor on the search input line, right after its contents.
-    presenter.set_cursor(Some(Position {
-        line: mode_config.max_results,
-        offset: mode.query().graphemes(true).count(),
-    }));
+    presenter.print_list_preview(&list);
 
     // Render the changes to the screen.
     presenter.present();
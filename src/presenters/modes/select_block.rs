@@ -0,0 +1,67 @@
+use crate::errors::*;
+use crate::models::application::modes::SelectBlockMode;
+use scribe::Workspace;
+use scribe::buffer::Position;
+use crate::presenters::current_buffer_status_line_data;
+use unicode_segmentation::UnicodeSegmentation;
+use crate::view::{Colors, StatusLineData, Style, View};
+
+pub fn display(workspace: &mut Workspace, mode: &SelectBlockMode, view: &mut View) -> Result<()> {
+    let mut presenter = view.build_presenter()?;
+    let buffer_status = current_buffer_status_line_data(workspace, presenter.view);
+    let buf = workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let selected_ranges = mode.ranges(buf);
+    let (first_line, last_line) = mode.lines(&buf.cursor);
+    let (start, end) = mode.columns();
+    let data = buf.data();
+
+    // Draw the visible set of tokens to the terminal.
+    presenter.print_buffer(buf, &data, Some(&selected_ranges), None)?;
+
+    if let Some(ref insert) = mode.insert {
+        let mode_display = String::from(" BLOCK INSERT ");
+        let input = format!(" {}", insert.content);
+        let cursor_offset = mode_display.graphemes(true).count() + input.graphemes(true).count();
+
+        presenter.print_status_line(&[
+            StatusLineData {
+                content: mode_display,
+                style: Style::Default,
+                colors: Colors::Insert,
+            },
+            StatusLineData {
+                content: input,
+                style: Style::Default,
+                colors: Colors::Focused,
+            },
+        ]);
+
+        // Move the cursor to the end of the pending content.
+        let cursor_line = presenter.height() - 1;
+        presenter.set_cursor(Some(Position {
+            line: cursor_line,
+            offset: cursor_offset,
+        }));
+    } else {
+        presenter.print_status_line(&[
+            StatusLineData {
+                content: " SELECT BLOCK ".to_string(),
+                style: Style::Default,
+                colors: Colors::SelectMode,
+            },
+            // The block's columns can extend beyond the cursor,
+            // which is limited to the line's content; describe it.
+            StatusLineData {
+                content: format!(" {}x{} (col {})", last_line - first_line + 1, end - start, mode.column + 1),
+                style: Style::Default,
+                colors: Colors::Focused,
+            },
+            buffer_status
+        ]);
+    }
+
+    // Render the changes to the screen.
+    presenter.present();
+
+    Ok(())
+}
@@ -0,0 +1,304 @@
+/// A line-level difference between two pieces of content.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Edit<'a> {
+    Equal(&'a str),
+    Delete(&'a str),
+    Insert(&'a str),
+}
+
+// The most edits between two pieces of content that are worked out line by
+// line. Finding them takes time proportional to the content's length times
+// the number of edits, so beyond this, the changed lines are replaced wholesale.
+const MAX_EDIT_DISTANCE: usize = 4_000;
+
+/// Computes the shortest sequence of line edits transforming the original
+/// content into the modified content (using Myers' algorithm, in linear
+/// space). Content that differs too much to diff quickly has all of its
+/// changed lines deleted and then inserted again, instead.
+pub fn edits<'a>(original: &'a str, modified: &'a str) -> Vec<Edit<'a>> {
+    let original: Vec<&str> = original.lines().collect();
+    let modified: Vec<&str> = modified.lines().collect();
+
+    line_edits(&original, &modified)
+}
+
+/// Like `edits`, but for content that has already been split into lines
+/// (e.g. with their line endings, for edits that must reproduce them).
+pub fn line_edits<'a>(original: &[&'a str], modified: &[&'a str]) -> Vec<Edit<'a>> {
+    let (prefix, suffix) = common_ends(original, modified);
+    let (a, b) = (&original[prefix..original.len() - suffix], &modified[prefix..modified.len() - suffix]);
+
+    let mut edits: Vec<Edit> = original[..prefix].iter().map(|line| Edit::Equal(line)).collect();
+    if !diff_lines(a, b, &mut edits) {
+        edits.truncate(prefix);
+        edits.extend(a.iter().map(|line| Edit::Delete(line)));
+        edits.extend(b.iter().map(|line| Edit::Insert(line)));
+    }
+    edits.extend(original[original.len() - suffix..].iter().map(|line| Edit::Equal(line)));
+
+    edits
+}
+
+// The number of lines the content has in common at its start and end.
+fn common_ends(a: &[&str], b: &[&str]) -> (usize, usize) {
+    let prefix = a.iter().zip(b.iter()).take_while(|(a, b)| a == b).count();
+    let suffix = a[prefix..]
+        .iter()
+        .rev()
+        .zip(b[prefix..].iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    (prefix, suffix)
+}
+
+// Adds the edits transforming one set of lines into the other, by finding the
+// middle snake of the shortest edit path and diffing the lines on either side
+// of it. Returns false if there are more edits than are worth working out.
+fn diff_lines<'a>(a: &[&'a str], b: &[&'a str], edits: &mut Vec<Edit<'a>>) -> bool {
+    let (prefix, suffix) = common_ends(a, b);
+    edits.extend(a[..prefix].iter().map(|line| Edit::Equal(line)));
+    let (a_middle, b_middle) = (&a[prefix..a.len() - suffix], &b[prefix..b.len() - suffix]);
+
+    if a_middle.is_empty() || b_middle.is_empty() {
+        edits.extend(a_middle.iter().map(|line| Edit::Delete(line)));
+        edits.extend(b_middle.iter().map(|line| Edit::Insert(line)));
+    } else {
+        // Both sides differ at their first and last lines, so there are at
+        // least two edits, and each half of the path has at least one of them.
+        let (x, y, u, v) = match middle_snake(a_middle, b_middle) {
+            Some(snake) => snake,
+            None => return false,
+        };
+        if !diff_lines(&a_middle[..x], &b_middle[..y], edits) {
+            return false;
+        }
+        edits.extend(a_middle[x..u].iter().map(|line| Edit::Equal(line)));
+        if !diff_lines(&a_middle[u..], &b_middle[v..], edits) {
+            return false;
+        }
+    }
+    edits.extend(a[a.len() - suffix..].iter().map(|line| Edit::Equal(line)));
+
+    true
+}
+
+// Finds the middle snake of the shortest edit path: a (possibly empty) run of
+// equal lines from (x, y) to (u, v) with half of the path's edits on either
+// side of it. Paths are followed from both ends at once, so that only the
+// furthest point reached on each diagonal needs to be kept.
+fn middle_snake(a: &[&str], b: &[&str]) -> Option<(usize, usize, usize, usize)> {
+    let (n, m) = (a.len() as isize, b.len() as isize);
+    let delta = n - m;
+    let odd = delta % 2 != 0;
+    let max = (n + m + 1) / 2;
+    let index = |k: isize| (k + max + 1) as usize;
+    let mut forward = vec![0isize; 2 * max as usize + 3];
+    let mut backward = vec![0isize; 2 * max as usize + 3];
+
+    for d in 0..=max.min(MAX_EDIT_DISTANCE as isize / 2 + 1) {
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && forward[index(k - 1)] < forward[index(k + 1)]) {
+                forward[index(k + 1)]
+            } else {
+                forward[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            forward[index(k)] = x;
+
+            // Backward paths are measured from the end, on diagonal delta - k.
+            if odd && (delta - k).abs() < d && x + backward[index(delta - k)] >= n {
+                return Some((start_x as usize, start_y as usize, x as usize, y as usize));
+            }
+        }
+
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && backward[index(k - 1)] < backward[index(k + 1)]) {
+                backward[index(k + 1)]
+            } else {
+                backward[index(k - 1)] + 1
+            };
+            let mut y = x - k;
+            let (start_x, start_y) = (x, y);
+            while x < n && y < m && a[(n - x - 1) as usize] == b[(m - y - 1) as usize] {
+                x += 1;
+                y += 1;
+            }
+            backward[index(k)] = x;
+
+            if !odd && (delta - k).abs() <= d && x + forward[index(delta - k)] >= n {
+                return Some(((n - x) as usize, (m - y) as usize, (n - start_x) as usize, (m - start_y) as usize));
+            }
+        }
+    }
+
+    None
+}
+
+/// Maps an original line index to its index once the edits have been applied;
+/// removed lines map to wherever their replacement (if any) starts.
+pub fn new_line_index(edits: &[Edit], line: usize) -> usize {
+    let mut original_line = 0;
+    let mut new_line = 0;
+
+    for edit in edits {
+        match *edit {
+            Edit::Equal(_) | Edit::Delete(_) if original_line == line => break,
+            Edit::Equal(_) => {
+                original_line += 1;
+                new_line += 1;
+            },
+            Edit::Delete(_) => original_line += 1,
+            Edit::Insert(_) => new_line += 1,
+        }
+    }
+
+    new_line
+}
+
+/// Formats the differences between two pieces of content as
+/// unified diff hunks, with the specified number of context lines.
+pub fn unified(original: &str, modified: &str, context: usize) -> String {
+    let edits = edits(original, modified);
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, edit)| !matches!(edit, Edit::Equal(_)))
+        .map(|(index, _)| index)
+        .collect();
+
+    // Group changes whose context overlaps into hunks.
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for &index in &changes {
+        let start = index.saturating_sub(context);
+        let end = (index + context + 1).min(edits.len());
+        match hunks.last_mut() {
+            Some(hunk) if start <= hunk.1 => hunk.1 = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+
+    let mut output = String::new();
+    for (start, end) in hunks {
+        // Line numbers are one-based, and count the lines preceding the hunk.
+        let preceding = &edits[..start];
+        let original_start = preceding.iter().filter(|e| !matches!(e, Edit::Insert(_))).count();
+        let modified_start = preceding.iter().filter(|e| !matches!(e, Edit::Delete(_))).count();
+        let hunk = &edits[start..end];
+        let original_count = hunk.iter().filter(|e| !matches!(e, Edit::Insert(_))).count();
+        let modified_count = hunk.iter().filter(|e| !matches!(e, Edit::Delete(_))).count();
+
+        output.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(original_start, original_count),
+            hunk_range(modified_start, modified_count)
+        ));
+        for edit in hunk {
+            let (prefix, line) = match *edit {
+                Edit::Equal(line) => (' ', line),
+                Edit::Delete(line) => ('-', line),
+                Edit::Insert(line) => ('+', line),
+            };
+            output.push(prefix);
+            output.push_str(line);
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+fn hunk_range(preceding: usize, count: usize) -> String {
+    match count {
+        0 => format!("{},0", preceding),
+        1 => format!("{}", preceding + 1),
+        _ => format!("{},{}", preceding + 1, count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{edits, unified, Edit};
+
+    fn apply<'a>(edits: &[Edit<'a>]) -> (Vec<&'a str>, Vec<&'a str>) {
+        let mut original = Vec::new();
+        let mut modified = Vec::new();
+        for edit in edits {
+            match *edit {
+                Edit::Equal(line) => {
+                    original.push(line);
+                    modified.push(line);
+                }
+                Edit::Delete(line) => original.push(line),
+                Edit::Insert(line) => modified.push(line),
+            }
+        }
+
+        (original, modified)
+    }
+
+    #[test]
+    fn edits_finds_minimal_line_changes() {
+        assert_eq!(
+            edits("a\nb\nc\nd\n", "a\nc\nx\nd\n"),
+            vec![
+                Edit::Equal("a"),
+                Edit::Delete("b"),
+                Edit::Equal("c"),
+                Edit::Insert("x"),
+                Edit::Equal("d"),
+            ]
+        );
+        assert_eq!(edits("", "a"), vec![Edit::Insert("a")]);
+        assert_eq!(edits("a\nb", ""), vec![Edit::Delete("a"), Edit::Delete("b")]);
+    }
+
+    #[test]
+    fn edits_finds_shortest_paths_through_interleaved_changes() {
+        let original = "a\nb\nc\na\nb\nb\na";
+        let modified = "c\nb\na\nb\na\nc";
+        let result = edits(original, modified);
+        let changes = result.iter().filter(|edit| match edit {
+            Edit::Equal(_) => false,
+            _ => true,
+        });
+
+        assert_eq!(changes.count(), 5);
+        assert_eq!(apply(&result), (original.lines().collect(), modified.lines().collect()));
+    }
+
+    #[test]
+    fn edits_replaces_large_fully_changed_content_wholesale() {
+        let original: String = (0..20_000).map(|line| format!("{}\n", line)).collect();
+        let modified: String = (0..20_000).map(|line| format!("changed {}\n", line)).collect();
+        let result = edits(&original, &modified);
+
+        assert_eq!(result.len(), 40_000);
+        assert!(result[..20_000].iter().all(|edit| match edit {
+            Edit::Delete(_) => true,
+            _ => false,
+        }));
+        assert_eq!(apply(&result), (original.lines().collect(), modified.lines().collect()));
+    }
+
+    #[test]
+    fn unified_groups_nearby_changes_into_hunks() {
+        let original = "1\n2\n3\n4\n5\n6\n7\n8\n9\n10\n";
+        let modified = "1\ntwo\n3\n4\n5\n6\n7\n8\n9\n10\n11\n";
+
+        assert_eq!(
+            unified(original, modified, 1),
+            "@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n@@ -10 +10,2 @@\n 10\n+11\n"
+        );
+        assert_eq!(
+            unified(original, modified, 3),
+            "@@ -1,5 +1,5 @@\n 1\n-2\n+two\n 3\n 4\n 5\n@@ -8,3 +8,4 @@\n 8\n 9\n 10\n+11\n"
+        );
+        assert!(unified(original, original, 3).is_empty());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Whether files at the path are decrypted when opened (see
+/// `Preferences::encryption_commands`), rather than read as they are.
+pub fn is_encrypted(path: &Path, preferences: &Preferences) -> bool {
+    preferences.encryption_commands(Some(&path.to_path_buf())).is_some()
+}
+
+/// Decrypts the file's content, which is only ever held in memory.
+pub fn decrypt(path: &Path, passphrase: &str, preferences: &Preferences) -> Result<String> {
+    let (command, _) = preferences.encryption_commands(Some(&path.to_path_buf())).ok_or("No decryption command configured")?;
+    let content = fs::read(path).chain_err(|| format!("Couldn't read {}", path.display()))?;
+    let plaintext = run(&command, passphrase, &content).chain_err(|| "Couldn't decrypt the file")?;
+
+    String::from_utf8(plaintext).chain_err(|| "The decrypted file isn't valid UTF-8")
+}
+
+/// Encrypts the content and writes it to the file; the
+/// plaintext is handed to the command, never to the disk.
+pub fn encrypt(path: &Path, passphrase: &str, content: &str, preferences: &Preferences) -> Result<()> {
+    let (_, command) = preferences.encryption_commands(Some(&path.to_path_buf())).ok_or("No encryption command configured")?;
+    let ciphertext = run(&command, passphrase, content.as_bytes()).chain_err(|| "Couldn't encrypt the buffer")?;
+
+    // Don't replace the file with nothing if the command misbehaves.
+    if ciphertext.is_empty() {
+        bail!("The encryption command produced no output");
+    }
+
+    fs::write(path, ciphertext).chain_err(|| format!("Couldn't write {}", path.display()))
+}
+
+// Runs the command, sending the passphrase as the first line of its
+// standard input, followed by the content, and returns its output.
+fn run(command: &[String], passphrase: &str, content: &[u8]) -> Result<Vec<u8>> {
+    let (program, args) = command.split_first().ok_or("Empty encryption command")?;
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .chain_err(|| format!("Couldn't run {}", program))?;
+
+    // Write from another thread, so that a command producing output
+    // before it's read all of its input can't deadlock us.
+    let mut stdin = child.stdin.take().ok_or("Couldn't write to the encryption command")?;
+    let input = [passphrase.as_bytes(), b"\n", content].concat();
+    let writer = thread::spawn(move || stdin.write_all(&input));
+
+    let output = child.wait_with_output().chain_err(|| format!("Couldn't run {}", program))?;
+    let _ = writer.join();
+    if !output.status.success() {
+        bail!(format!("{} failed: {}", program, String::from_utf8_lossy(&output.stderr).trim()));
+    }
+
+    Ok(output.stdout)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::application::Preferences;
+    use std::env;
+    use std::fs;
+    use std::path::PathBuf;
+    use crate::yaml::yaml::YamlLoader;
+
+    // A stand-in for a real cipher: rot13, keyed by a passphrase that must be "amp".
+    fn preferences() -> Preferences {
+        let command = "[sh, -c, 'read key && [ \"$key\" = amp ] && tr a-z n-za-m']";
+        let data = YamlLoader::load_from_str(&format!(
+            "types:\n  rot:\n    decrypt_command: {}\n    encrypt_command: {}",
+            command, command
+        )).unwrap();
+
+        Preferences::new(data.into_iter().next())
+    }
+
+    #[test]
+    fn encrypt_and_decrypt_round_trip_through_the_configured_commands() {
+        let preferences = preferences();
+        let path = env::temp_dir().join("amp_encryption_round_trip.rot");
+
+        super::encrypt(&path, "amp", "secret\n", &preferences).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "frperg\n");
+        assert_eq!(super::decrypt(&path, "amp", &preferences).unwrap(), "secret\n");
+        assert!(super::decrypt(&path, "wrong", &preferences).is_err());
+
+        // Failures don't touch the file.
+        assert!(super::encrypt(&path, "wrong", "other\n", &preferences).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "frperg\n");
+
+        assert!(super::is_encrypted(&path, &preferences));
+        assert!(!super::is_encrypted(&PathBuf::from("notes.md"), &preferences));
+    }
+}
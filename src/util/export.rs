@@ -0,0 +1,97 @@
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Color, Style, Theme};
+use syntect::html::{self, IncludeBackground};
+use syntect::parsing::SyntaxDefinition;
+use syntect::util::as_24_bit_terminal_escaped;
+
+// Resets terminal colours, so that they don't bleed into whatever follows.
+const ANSI_RESET: &str = "\x1b[0m";
+
+/// Renders content as a standalone HTML document, coloured using the theme.
+/// If a byte range is provided, only that portion of the content is included,
+/// though it's still highlighted in the context of the content preceding it.
+pub fn html(data: &str, range: Option<(usize, usize)>, syntax: &SyntaxDefinition, theme: &Theme, title: &str) -> String {
+    let regions = highlight(data, range, syntax, theme);
+    let background = theme.settings.background.unwrap_or(Color::WHITE);
+
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{}{}\n</pre>\n</body>\n</html>\n",
+        escape(title),
+        html::start_coloured_html_snippet(theme),
+        html::styles_to_coloured_html(&regions, IncludeBackground::IfDifferent(background))
+    )
+}
+
+/// Renders content as text coloured using 24-bit terminal escape sequences.
+/// Ranges are handled as they are by `html`.
+pub fn ansi(data: &str, range: Option<(usize, usize)>, syntax: &SyntaxDefinition, theme: &Theme) -> String {
+    let regions = highlight(data, range, syntax, theme);
+
+    format!("{}{}", as_24_bit_terminal_escaped(&regions, false), ANSI_RESET)
+}
+
+// Highlights content line by line (the workspace's syntaxes expect trailing
+// newlines), discarding styled regions that fall outside of the range.
+fn highlight<'a>(data: &'a str, range: Option<(usize, usize)>, syntax: &SyntaxDefinition, theme: &Theme) -> Vec<(Style, &'a str)> {
+    let (start, end) = range.unwrap_or((0, data.len()));
+    let mut highlighter = HighlightLines::new(syntax, theme);
+    let mut regions = Vec::new();
+    let mut offset = 0;
+
+    for line in data.split_inclusive('\n') {
+        if offset >= end {
+            break;
+        }
+
+        for (style, text) in highlighter.highlight(line) {
+            let (region_start, region_end) = (offset, offset + text.len());
+            offset = region_end;
+
+            if region_end <= start || region_start >= end {
+                continue;
+            }
+            let from = start.saturating_sub(region_start);
+            let to = text.len() - region_end.saturating_sub(end);
+            regions.push((style, &text[from..to]));
+        }
+    }
+
+    regions
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use syntect::highlighting::ThemeSet;
+    use syntect::parsing::SyntaxSet;
+
+    #[test]
+    fn html_renders_a_standalone_document() {
+        let syntaxes = SyntaxSet::load_defaults_newlines();
+        let themes = ThemeSet::load_defaults();
+        let syntax = syntaxes.find_syntax_by_extension("rs").unwrap();
+        let html = super::html("fn main() {}\n", None, syntax, &themes.themes["base16-ocean.dark"], "<main.rs>");
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(html.contains("<title>&lt;main.rs&gt;</title>"));
+        assert!(html.contains("<pre style=\"background-color:#2b303b\">"));
+        assert!(html.contains(">fn"));
+    }
+
+    #[test]
+    fn ansi_only_includes_the_range_and_resets_colours() {
+        let syntaxes = SyntaxSet::load_defaults_newlines();
+        let themes = ThemeSet::load_defaults();
+        let syntax = syntaxes.find_syntax_by_extension("rs").unwrap();
+        let data = "// comment\nfn main() {}\n";
+        let ansi = super::ansi(data, Some((14, 18)), syntax, &themes.themes["base16-ocean.dark"]);
+
+        assert!(ansi.ends_with("\x1b[0m"));
+        assert!(ansi.contains("main"));
+        assert!(!ansi.contains("fn"));
+        assert!(!ansi.contains("comment"));
+    }
+}
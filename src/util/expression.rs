@@ -0,0 +1,186 @@
+use crate::errors::*;
+use std::iter::Peekable;
+use std::str::Chars;
+
+/// Evaluates an arithmetic expression, returning its formatted result.
+/// Supports `+`, `-`, `*`, `/`, `%`, `^` (exponentiation), parentheses,
+/// and decimal, hexadecimal (`0x`), octal (`0o`), and binary (`0b`) numbers.
+pub fn evaluate(expression: &str) -> Result<String> {
+    let mut parser = Parser { chars: expression.chars().peekable() };
+    let value = parser.expression()?;
+
+    parser.skip_whitespace();
+    if let Some(c) = parser.chars.peek() {
+        bail!("Unexpected \"{}\" in expression", c);
+    }
+    if !value.is_finite() {
+        bail!("Expression doesn't have a finite result");
+    }
+
+    Ok(format(value))
+}
+
+fn format(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < 1e15 {
+        format!("{}", value as i64)
+    } else {
+        format!("{}", value)
+    }
+}
+
+struct Parser<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl<'a> Parser<'a> {
+    // expression = term (("+" | "-") term)*
+    fn expression(&mut self) -> Result<f64> {
+        let mut value = self.term()?;
+        loop {
+            match self.next_operator(&['+', '-']) {
+                Some('+') => value += self.term()?,
+                Some('-') => value -= self.term()?,
+                _ => return Ok(value),
+            }
+        }
+    }
+
+    // term = factor (("*" | "/" | "%") factor)*
+    fn term(&mut self) -> Result<f64> {
+        let mut value = self.factor()?;
+        loop {
+            match self.next_operator(&['*', '/', '%']) {
+                Some('*') => value *= self.factor()?,
+                Some(operator) => {
+                    let divisor = self.factor()?;
+                    if divisor == 0.0 {
+                        bail!("Division by zero");
+                    }
+
+                    if operator == '/' {
+                        value /= divisor;
+                    } else {
+                        value %= divisor;
+                    }
+                }
+                None => return Ok(value),
+            }
+        }
+    }
+
+    // factor = ("-" | "+") factor | operand ("^" factor)?
+    fn factor(&mut self) -> Result<f64> {
+        match self.next_operator(&['-', '+']) {
+            Some('-') => return Ok(-self.factor()?),
+            Some(_) => return self.factor(),
+            None => (),
+        }
+
+        let base = self.operand()?;
+        if self.next_operator(&['^']).is_some() {
+            Ok(base.powf(self.factor()?))
+        } else {
+            Ok(base)
+        }
+    }
+
+    // operand = number | "(" expression ")"
+    fn operand(&mut self) -> Result<f64> {
+        self.skip_whitespace();
+        match self.chars.peek().cloned() {
+            Some('(') => {
+                self.chars.next();
+                let value = self.expression()?;
+                if self.next_operator(&[')']).is_none() {
+                    bail!("Missing closing parenthesis");
+                }
+
+                Ok(value)
+            }
+            Some(c) if c.is_ascii_digit() || c == '.' => self.number(),
+            Some(c) => bail!("Unexpected \"{}\" in expression", c),
+            None => bail!("Incomplete expression"),
+        }
+    }
+
+    fn number(&mut self) -> Result<f64> {
+        let mut literal = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '_' {
+                if c != '_' {
+                    literal.push(c);
+                }
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+
+        let radix = match literal.get(0..2) {
+            Some("0x") | Some("0X") => 16,
+            Some("0o") | Some("0O") => 8,
+            Some("0b") | Some("0B") => 2,
+            _ => 10,
+        };
+        let value = if radix == 10 {
+            literal.parse::<f64>().ok()
+        } else {
+            i64::from_str_radix(&literal[2..], radix).ok().map(|value| value as f64)
+        };
+
+        value.ok_or_else(|| format!("Invalid number \"{}\"", literal).into())
+    }
+
+    /// Consumes and returns the next character if it's one of the specified operators.
+    fn next_operator(&mut self, operators: &[char]) -> Option<char> {
+        self.skip_whitespace();
+        let c = *self.chars.peek()?;
+        if operators.contains(&c) {
+            self.chars.next();
+            Some(c)
+        } else {
+            None
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.chars.peek().map(|c| c.is_whitespace()) == Some(true) {
+            self.chars.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::evaluate;
+
+    #[test]
+    fn evaluate_respects_operator_precedence() {
+        assert_eq!(evaluate("1 + 2 * 3").unwrap(), "7");
+        assert_eq!(evaluate("(1 + 2) * 3").unwrap(), "9");
+        assert_eq!(evaluate("2 ^ 3 ^ 2").unwrap(), "512");
+        assert_eq!(evaluate("-2 ^ 2").unwrap(), "-4");
+        assert_eq!(evaluate("10 - 4 - 3").unwrap(), "3");
+    }
+
+    #[test]
+    fn evaluate_formats_fractional_results() {
+        assert_eq!(evaluate("7 / 2").unwrap(), "3.5");
+        assert_eq!(evaluate("10 % 4").unwrap(), "2");
+        assert_eq!(evaluate(".5 * 3").unwrap(), "1.5");
+    }
+
+    #[test]
+    fn evaluate_supports_number_prefixes() {
+        assert_eq!(evaluate("0x10 + 0b11 + 0o7 + 1_000").unwrap(), "1026");
+    }
+
+    #[test]
+    fn evaluate_rejects_invalid_expressions() {
+        assert!(evaluate("1 +").is_err());
+        assert!(evaluate("(1 + 2").is_err());
+        assert!(evaluate("1 / 0").is_err());
+        assert!(evaluate("2 x 3").is_err());
+        assert!(evaluate("").is_err());
+    }
+}
@@ -0,0 +1,179 @@
+use fragment::matching::AsStr;
+use std::ops::Deref;
+
+// Every matched character is worth the same, with a bonus for each one that
+// follows another within a term, so that longer contiguous runs rank higher.
+const CHARACTER_SCORE: i64 = 16;
+const CONSECUTIVE_BONUS: i64 = 16;
+
+// Where a term occurs matters as much as its length.
+const PREFIX_BONUS: i64 = 64;
+const BOUNDARY_BONUS: i64 = 32;
+const BASENAME_BONUS: i64 = 48;
+const EXACT_BONUS: i64 = 128;
+
+// Terms found in the same order as the query's.
+const ORDER_BONUS: i64 = 8;
+
+/// A search result, along with its score and the
+/// character indices of its string that were matched.
+#[derive(Debug, PartialEq)]
+pub struct Match<T> {
+    object: T,
+    pub score: i64,
+    pub indices: Vec<usize>,
+}
+
+impl<T> Deref for Match<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.object
+    }
+}
+
+/// Like `fragment::matching::find`, every space-separated term in the query
+/// must occur in an entry, but results are ranked the way fzf ranks them:
+/// terms at the start of the entry, of a word, or of a path's basename
+/// score higher, as do longer terms, terms in query order, and shorter
+/// entries. Results are ordered best first, limited to `max_results`.
+pub fn find<'a, T: AsStr, I: IntoIterator<Item=&'a T>>(query: &str, haystack: I, max_results: usize) -> Vec<Match<&'a T>> {
+    let terms: Vec<&str> = query.split_whitespace().collect();
+    let mut results: Vec<Match<&'a T>> = haystack
+        .into_iter()
+        .filter_map(|object| {
+            score(&terms, object.as_str()).map(|(score, indices)| Match { object, score, indices })
+        })
+        .collect();
+
+    // Ties keep the haystack's order.
+    results.sort_by(|a, b| b.score.cmp(&a.score));
+    results.truncate(max_results);
+
+    results
+}
+
+// Scores the best occurrence of each term in the candidate, returning
+// the total and the matched character indices, provided all are found.
+fn score(terms: &[&str], candidate: &str) -> Option<(i64, Vec<usize>)> {
+    if terms.is_empty() {
+        return None;
+    }
+
+    let trimmed = candidate.trim_end_matches('/');
+    let basename_start = trimmed.rfind('/').map_or(0, |index| index + 1);
+    let mut total = -(candidate.len() as i64);
+    let mut indices = Vec::new();
+    let mut previous_end = None;
+
+    for term in terms {
+        let (position, term_score) = candidate
+            .match_indices(term)
+            .map(|(position, _)| {
+                let mut score = occurrence_score(candidate, position, term);
+                if position >= basename_start {
+                    score += BASENAME_BONUS;
+                    if position == basename_start && position + term.len() == trimmed.len() {
+                        score += EXACT_BONUS;
+                    }
+                }
+
+                (position, score)
+            })
+            .fold(None, |best: Option<(usize, i64)>, (position, score)| match best {
+                Some((_, best_score)) if best_score >= score => best,
+                _ => Some((position, score)),
+            })?;
+
+        total += term_score;
+        match previous_end {
+            Some(end) if position == end => total += CONSECUTIVE_BONUS,
+            Some(end) if position > end => total += ORDER_BONUS,
+            _ => (),
+        }
+        previous_end = Some(position + term.len());
+
+        let start = candidate[..position].chars().count();
+        indices.extend(start..start + term.chars().count());
+    }
+    indices.sort_unstable();
+    indices.dedup();
+
+    Some((total, indices))
+}
+
+fn occurrence_score(candidate: &str, position: usize, term: &str) -> i64 {
+    let length = term.chars().count() as i64;
+    let mut score = CHARACTER_SCORE * length + CONSECUTIVE_BONUS * (length - 1);
+
+    let first = term.chars().next();
+    match (candidate[..position].chars().next_back(), first) {
+        (None, _) => score += PREFIX_BONUS,
+        (Some(previous), Some(first)) if boundary(previous, first) => score += BOUNDARY_BONUS,
+        _ => (),
+    }
+
+    score
+}
+
+// Whether the character starts a word, given the one before it:
+// after punctuation or whitespace, or a camel case hump.
+fn boundary(previous: char, current: char) -> bool {
+    !previous.is_alphanumeric() || (previous.is_lowercase() && current.is_uppercase())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::find;
+
+    fn ranked<'a>(query: &str, haystack: &'a [&'a str]) -> Vec<&'a str> {
+        find(query, haystack, haystack.len()).into_iter().map(|result| **result).collect()
+    }
+
+    #[test]
+    fn find_requires_every_term() {
+        let haystack = ["src/main.rs", "src/lib.rs"];
+
+        assert_eq!(ranked("rs main", &haystack), vec!["src/main.rs"]);
+        assert!(ranked("src zzz", &haystack).is_empty());
+        assert!(ranked("  ", &haystack).is_empty());
+    }
+
+    #[test]
+    fn find_prefers_basename_matches() {
+        let haystack = ["main/lib.rs", "src/main.rs"];
+
+        assert_eq!(ranked("main", &haystack), vec!["src/main.rs", "main/lib.rs"]);
+    }
+
+    #[test]
+    fn find_prefers_prefix_and_word_boundary_matches() {
+        assert_eq!(ranked("test", &["contested.rs", "run_tests.rs"]), vec!["run_tests.rs", "contested.rs"]);
+        assert_eq!(ranked("view", &["preview", "viewport"]), vec!["viewport", "preview"]);
+        assert_eq!(ranked("Pos", &["APosition", "aPosition"]), vec!["aPosition", "APosition"]);
+    }
+
+    #[test]
+    fn find_prefers_longer_runs_and_exact_basenames() {
+        assert_eq!(ranked("mod.rs", &["src/mod.rs.orig", "src/mod.rs"]), vec!["src/mod.rs", "src/mod.rs.orig"]);
+        assert_eq!(ranked("src", &["a/src/b", "src"]), vec!["src", "a/src/b"]);
+    }
+
+    #[test]
+    fn find_prefers_shorter_entries_when_matches_are_otherwise_equal() {
+        assert_eq!(ranked("frag", &["lib/fragments.rs", "src/fragment.rs"]), vec!["src/fragment.rs", "lib/fragments.rs"]);
+    }
+
+    #[test]
+    fn find_limits_results() {
+        assert_eq!(find("rs", &["a.rs", "b.rs", "c.rs"], 2).len(), 2);
+    }
+
+    #[test]
+    fn find_returns_matched_character_indices() {
+        let haystack = ["src/main.rs", "café/über.rs"];
+
+        assert_eq!(find("rs main", &haystack[..1], 1)[0].indices, vec![4, 5, 6, 7, 9, 10]);
+        assert_eq!(find("über", &haystack[1..], 1)[0].indices, vec![5, 6, 7, 8]);
+    }
+}
@@ -0,0 +1,106 @@
+use crate::errors::*;
+use std::ffi::CString;
+use std::fs::File;
+use std::io::Read;
+use std::ptr;
+
+/// Placeholder text, for filling out layouts and fixtures.
+pub const LOREM_IPSUM: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, \
+sed do eiusmod tempor incididunt ut labore et dolore magna aliqua. Ut enim ad minim \
+veniam, quis nostrud exercitation ullamco laboris nisi ut aliquip ex ea commodo \
+consequat. Duis aute irure dolor in reprehenderit in voluptate velit esse cillum dolore \
+eu fugiat nulla pariatur. Excepteur sint occaecat cupidatat non proident, sunt in culpa \
+qui officia deserunt mollit anim id est laborum.";
+
+// Not exposed by the libc crate.
+extern "C" {
+    fn strftime(
+        output: *mut libc::c_char,
+        max_length: libc::size_t,
+        format: *const libc::c_char,
+        time: *const libc::tm
+    ) -> libc::size_t;
+}
+
+/// Generates a random (version 4) UUID, in its hyphenated form.
+pub fn uuid() -> Result<String> {
+    let mut bytes = [0u8; 16];
+    File::open("/dev/urandom")
+        .and_then(|mut file| file.read_exact(&mut bytes))
+        .chain_err(|| "Couldn't read random data for UUID")?;
+
+    Ok(format_uuid(bytes))
+}
+
+fn format_uuid(mut bytes: [u8; 16]) -> String {
+    // Set the version (4) and variant (RFC 4122) bits.
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+    format!("{}-{}-{}-{}-{}", &hex[0..8], &hex[8..12], &hex[12..16], &hex[16..20], &hex[20..32])
+}
+
+/// Formats the current local time using a strftime-style format string.
+pub fn local_time(format: &str) -> Result<String> {
+    format_time(unsafe { libc::time(ptr::null_mut()) }, format)
+}
+
+/// Formats a Unix timestamp (in local time) using a strftime-style format string.
+pub fn format_time(timestamp: i64, format: &str) -> Result<String> {
+    let format = CString::new(format).chain_err(|| "Invalid time format")?;
+    let mut output = vec![0u8; 256];
+
+    let length = unsafe {
+        let timestamp = timestamp as libc::time_t;
+        let mut time: libc::tm = std::mem::zeroed();
+        if libc::localtime_r(&timestamp, &mut time).is_null() {
+            bail!("Couldn't determine the local time");
+        }
+
+        strftime(
+            output.as_mut_ptr() as *mut libc::c_char,
+            output.len(),
+            format.as_ptr(),
+            &time
+        )
+    };
+
+    // strftime returns zero for empty output, which is only
+    // expected if the format itself is empty.
+    if length == 0 && !format.as_bytes().is_empty() {
+        bail!("Time format produced no output");
+    }
+    output.truncate(length);
+
+    String::from_utf8(output).chain_err(|| "Formatted time isn't valid UTF-8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_uuid, local_time, uuid};
+
+    #[test]
+    fn format_uuid_sets_version_and_variant() {
+        assert_eq!(format_uuid([0xff; 16]), "ffffffff-ffff-4fff-bfff-ffffffffffff");
+        assert_eq!(format_uuid([0; 16]), "00000000-0000-4000-8000-000000000000");
+    }
+
+    #[test]
+    fn uuid_generates_unique_values() {
+        let first = uuid().unwrap();
+
+        assert_eq!(first.len(), 36);
+        assert_ne!(first, uuid().unwrap());
+    }
+
+    #[test]
+    fn local_time_uses_the_specified_format() {
+        let date = local_time("%Y-%m-%d").unwrap();
+        let parts: Vec<&str> = date.split('-').collect();
+
+        assert_eq!(parts.len(), 3);
+        assert_eq!(parts[0].len(), 4);
+        assert!(parts.iter().all(|part| part.parse::<u32>().is_ok()));
+    }
+}
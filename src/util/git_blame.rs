@@ -0,0 +1,206 @@
+use crate::errors::*;
+use crate::util::diff::{self, Edit};
+use crate::util::generate;
+use crate::view::{Annotation, AnnotationPosition, AnnotationProvider, Colors, Style};
+use git2::{Oid, Repository};
+use pad::PadStr;
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
+use std::path::Path;
+use unicode_segmentation::UnicodeSegmentation;
+
+pub const ANNOTATION_PROVIDER: AnnotationProvider = AnnotationProvider {
+    name: "blame",
+    priority: 0,
+    invalidated_by_edits: false,
+};
+
+// Long author names are truncated to keep the blame column narrow.
+const AUTHOR_WIDTH: usize = 16;
+const UNCOMMITTED: &str = "Not committed yet";
+
+/// The commit that last changed a line.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlameLine {
+    pub commit: Oid,
+    pub author: String,
+    pub summary: String,
+
+    /// When the commit was authored, as a Unix timestamp.
+    pub time: i64,
+}
+
+impl BlameLine {
+    /// The abbreviated commit hash.
+    pub fn short_id(&self) -> String {
+        self.commit.to_string().chars().take(7).collect()
+    }
+
+    /// The abbreviated commit hash, author, and date (using the specified format).
+    pub fn describe(&self, date_format: &str) -> Result<String> {
+        Ok(format!(
+            "{} {} {}",
+            self.short_id(),
+            self.author,
+            generate::format_time(self.time, date_format)?
+        ))
+    }
+}
+
+/// Finds the commit that last changed each line of a file's content,
+/// given its path relative to the repository's working directory.
+/// Lines that differ from the last commit (i.e. added or modified
+/// since then) don't have one.
+pub fn blame(repo: &Repository, path: &Path, content: &str) -> Result<Vec<Option<BlameLine>>> {
+    let committed = committed_content(repo, path)?;
+    let blame = repo.blame_file(path, None).chain_err(|| "Couldn't blame the file")?;
+    let mut commits = HashMap::new();
+
+    // Blame only covers the committed content, so
+    // map the content's lines back to those lines.
+    let committed_lines: Vec<&str> = committed.lines().collect();
+    let lines: Vec<&str> = content.lines().collect();
+    let mut committed_line = 0;
+    let mut blamed_lines = Vec::with_capacity(lines.len());
+    for edit in diff::line_edits(&committed_lines, &lines) {
+        match edit {
+            Edit::Equal(_) => {
+                committed_line += 1;
+                let hunk = blame.get_line(committed_line).ok_or("Couldn't blame the file")?;
+                let commit = hunk.final_commit_id();
+                let blamed_line = match commits.entry(commit) {
+                    Entry::Occupied(entry) => entry.into_mut(),
+                    Entry::Vacant(entry) => entry.insert(blame_line(repo, commit)?),
+                };
+                blamed_lines.push(Some(blamed_line.clone()));
+            },
+            Edit::Delete(_) => committed_line += 1,
+            Edit::Insert(_) => blamed_lines.push(None),
+        }
+    }
+
+    Ok(blamed_lines)
+}
+
+/// Builds a column annotation for every line, describing the commit
+/// that last changed it, with each of the descriptions' parts aligned.
+pub fn annotations(lines: &[Option<BlameLine>], date_format: &str) -> Result<Vec<Annotation>> {
+    let author_width = lines
+        .iter()
+        .flatten()
+        .map(|line| line.author.graphemes(true).count())
+        .max()
+        .unwrap_or(0)
+        .min(AUTHOR_WIDTH);
+
+    let mut annotations = Vec::with_capacity(lines.len());
+    for (index, line) in lines.iter().enumerate() {
+        let content = match *line {
+            Some(ref line) => format!(
+                "{} {} {}",
+                line.short_id(),
+                line.author.with_exact_width(author_width),
+                generate::format_time(line.time, date_format)?
+            ),
+            None => String::from(UNCOMMITTED),
+        };
+
+        annotations.push(Annotation {
+            line: index,
+            content,
+            style: Style::Default,
+            colors: Colors::Focused,
+            position: AnnotationPosition::Column,
+        });
+    }
+
+    Ok(annotations)
+}
+
+/// Describes a commit in full, including its message and changes.
+pub fn commit_details(repo: &Repository, commit: Oid, date_format: &str) -> Result<String> {
+    let commit = repo.find_commit(commit).chain_err(|| "Couldn't find the commit")?;
+    let author = commit.author();
+    let mut content = format!(
+        "commit {}\nAuthor: {} <{}>\nDate:   {}\n\n",
+        commit.id(),
+        author.name().unwrap_or(""),
+        author.email().unwrap_or(""),
+        generate::format_time(author.when().seconds(), date_format)?
+    );
+    for line in commit.message().unwrap_or("").lines() {
+        content.push_str(format!("    {}", line).trim_end());
+        content.push('\n');
+    }
+    content.push('\n');
+
+    // The first commit in a repository doesn't have a parent to compare against.
+    let tree = commit.tree().chain_err(|| "Couldn't find the commit's tree")?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().chain_err(|| "Couldn't find the parent commit's tree")?),
+        Err(_) => None,
+    };
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .chain_err(|| "Couldn't build a diff for the commit")?;
+    diff.print(git2::DiffFormat::Patch, |_, _, line| {
+        match line.origin() {
+            '+' | '-' | ' ' => content.push(line.origin()),
+            _ => (),
+        }
+        content.push_str(&String::from_utf8_lossy(line.content()));
+
+        true
+    }).chain_err(|| "Couldn't format the commit diff")?;
+
+    Ok(content)
+}
+
+fn blame_line(repo: &Repository, commit: Oid) -> Result<BlameLine> {
+    let commit = repo.find_commit(commit).chain_err(|| "Couldn't find a blamed commit")?;
+    let author = commit.author();
+
+    Ok(BlameLine {
+        commit: commit.id(),
+        author: author.name().unwrap_or("").to_string(),
+        summary: commit.summary().unwrap_or("").to_string(),
+        time: author.when().seconds(),
+    })
+}
+
+// The file's content as of the last commit.
+fn committed_content(repo: &Repository, path: &Path) -> Result<String> {
+    let tree = repo
+        .head()
+        .and_then(|head| head.peel_to_tree())
+        .chain_err(|| "Couldn't find the last commit")?;
+    let entry = tree
+        .get_path(path)
+        .chain_err(|| format!("{} hasn't been committed", path.display()))?;
+    let blob = repo.find_blob(entry.id()).chain_err(|| "Couldn't read the committed file")?;
+
+    Ok(String::from_utf8_lossy(blob.content()).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotations, BlameLine};
+    use git2::Oid;
+
+    #[test]
+    fn annotations_align_authors_and_mark_uncommitted_lines() {
+        let line = |author: &str| Some(BlameLine {
+            commit: Oid::from_str("0123456789abcdef0123456789abcdef01234567").unwrap(),
+            author: String::from(author),
+            summary: String::from("Add blame"),
+            time: 0,
+        });
+        let lines = vec![line("Jo"), None, line("Jordan")];
+        let annotations = annotations(&lines, "%Y").unwrap();
+
+        assert_eq!(annotations.len(), 3);
+        assert!(annotations[0].content.starts_with("0123456 Jo     "));
+        assert!(annotations[2].content.starts_with("0123456 Jordan "));
+        assert_eq!(annotations[1].content, "Not committed yet");
+    }
+}
@@ -0,0 +1,284 @@
+use crate::errors::*;
+use crate::models::application::Preferences;
+use crate::util::content_hash;
+use crate::view::{Annotation, AnnotationPosition, AnnotationProvider, Colors, RGBColor, Style};
+use git2::{Oid, Patch, Repository};
+use std::collections::HashMap;
+use std::path::Path;
+
+// Hunks are recomputed whenever the buffer changes, replacing these.
+pub const ANNOTATION_PROVIDER: AnnotationProvider = AnnotationProvider {
+    name: "git",
+    priority: 10,
+    invalidated_by_edits: false,
+};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum LineChange {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl LineChange {
+    /// The name used to configure the change's sign.
+    pub fn name(self) -> &'static str {
+        match self {
+            LineChange::Added => "added",
+            LineChange::Modified => "modified",
+            LineChange::Removed => "removed",
+        }
+    }
+
+    fn default_sign(self) -> (&'static str, RGBColor) {
+        match self {
+            LineChange::Added => ("+", RGBColor(133, 153, 0)),
+            LineChange::Modified => ("~", RGBColor(181, 137, 0)),
+            LineChange::Removed => ("_", RGBColor(220, 50, 47)),
+        }
+    }
+}
+
+/// A run of buffer lines that differ from the index.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hunk {
+    /// The first buffer line in the hunk. Removed lines have no buffer
+    /// lines of their own; they start where they used to be.
+    pub start: usize,
+
+    /// The number of buffer lines in the hunk (zero for removals).
+    pub lines: usize,
+
+    /// The first line the hunk replaces in the index.
+    pub original_start: usize,
+
+    /// The number of lines the hunk replaces in the index (zero for additions).
+    pub original_lines: usize,
+    pub change: LineChange,
+}
+
+impl Hunk {
+    /// The first buffer line marked as part of the hunk;
+    /// removals are marked on the line above them.
+    pub fn first_line(&self) -> usize {
+        if self.lines == 0 {
+            self.start.saturating_sub(1)
+        } else {
+            self.start
+        }
+    }
+
+    /// The last buffer line marked as part of the hunk.
+    pub fn last_line(&self) -> usize {
+        self.first_line() + self.lines.max(1) - 1
+    }
+
+    pub fn contains(&self, line: usize) -> bool {
+        self.first_line() <= line && line <= self.last_line()
+    }
+}
+
+/// Tracks how open buffers differ from their files' staged content. Buffers
+/// are only diffed again when their content (or staged content) changes.
+#[derive(Default)]
+pub struct GitGutter {
+    hunks: HashMap<usize, Vec<Hunk>>,
+
+    /// The content hash and staged blob each buffer was last diffed with.
+    diffed: HashMap<usize, (u64, Oid)>,
+}
+
+impl GitGutter {
+    /// Brings the buffer's hunks up to date, given its path and content,
+    /// returning true if they needed to be diffed again.
+    pub fn update(&mut self, repo: &Repository, buffer_id: usize, path: &Path, data: &str) -> Result<bool> {
+        let relative_path = match repo.workdir().map(|workdir| path.strip_prefix(workdir)) {
+            Some(Ok(relative_path)) => relative_path.to_path_buf(),
+            _ => return Ok(false),
+        };
+
+        let mut index = repo.index().chain_err(|| "Couldn't get the repository index")?;
+        index.read(false).chain_err(|| "Couldn't read the repository index")?;
+        let blob_id = match index.get_path(&relative_path, 0) {
+            Some(entry) => entry.id,
+            // Untracked files don't have anything to compare against.
+            None => {
+                self.diffed.remove(&buffer_id);
+                return Ok(self.hunks.remove(&buffer_id).is_some());
+            }
+        };
+
+        let hash = content_hash(data);
+        if self.diffed.get(&buffer_id) == Some(&(hash, blob_id)) {
+            return Ok(false);
+        }
+
+        let blob = repo.find_blob(blob_id).chain_err(|| "Couldn't read the staged file")?;
+        let staged = String::from_utf8_lossy(blob.content());
+        self.hunks.insert(buffer_id, hunks(&staged, data)?);
+        self.diffed.insert(buffer_id, (hash, blob_id));
+
+        Ok(true)
+    }
+
+    /// Replaces the buffer's hunks with those diffed elsewhere (i.e. in the background).
+    pub fn set_hunks(&mut self, buffer_id: usize, hunks: Vec<Hunk>) {
+        self.diffed.remove(&buffer_id);
+        self.hunks.insert(buffer_id, hunks);
+    }
+
+    pub fn hunks(&self, buffer_id: usize) -> &[Hunk] {
+        self.hunks.get(&buffer_id).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    pub fn forget_buffer(&mut self, buffer_id: usize) {
+        self.hunks.remove(&buffer_id);
+        self.diffed.remove(&buffer_id);
+    }
+}
+
+/// Finds the lines added, modified, or removed between two versions of a file.
+pub fn hunks(original: &str, modified: &str) -> Result<Vec<Hunk>> {
+    let patch = Patch::from_buffers(original.as_bytes(), None, modified.as_bytes(), None, None)
+        .chain_err(|| "Couldn't diff the buffer")?;
+
+    // Patch hunks include context lines, and may span several runs of
+    // changed lines; each of the latter is treated as a hunk of its own.
+    let mut hunks = Vec::new();
+    for hunk_index in 0..patch.num_hunks() {
+        let line_count = patch.num_lines_in_hunk(hunk_index).chain_err(|| "Couldn't diff the buffer")?;
+        let mut run = Run::default();
+        for line_index in 0..line_count {
+            let line = patch.line_in_hunk(hunk_index, line_index)
+                .chain_err(|| "Couldn't diff the buffer")?;
+
+            // Line numbers are one-based, so they refer to the index of the following line.
+            let new_line = line.new_lineno().unwrap_or(0) as usize;
+            let old_line = line.old_lineno().unwrap_or(0) as usize;
+            match line.origin() {
+                '+' => {
+                    run.start.get_or_insert(new_line - 1);
+                    run.added += 1;
+                },
+                '-' => {
+                    run.original_start.get_or_insert(old_line - 1);
+                    run.removed += 1;
+                },
+                ' ' => {
+                    hunks.extend(run.hunk());
+                    run = Run { following: (new_line, old_line), ..Run::default() };
+                },
+                // End of file newline markers don't affect the lines themselves.
+                _ => (),
+            }
+        }
+        hunks.extend(run.hunk());
+    }
+
+    Ok(hunks)
+}
+
+// Consecutive changed lines within a patch hunk.
+#[derive(Default)]
+struct Run {
+    // The (new and original) lines following the preceding context line,
+    // which is where the run starts on the side where it has no lines.
+    following: (usize, usize),
+    start: Option<usize>,
+    original_start: Option<usize>,
+    added: usize,
+    removed: usize,
+}
+
+impl Run {
+    fn hunk(&self) -> Option<Hunk> {
+        let change = match (self.added, self.removed) {
+            (0, 0) => return None,
+            (_, 0) => LineChange::Added,
+            (0, _) => LineChange::Removed,
+            _ => LineChange::Modified,
+        };
+
+        Some(Hunk {
+            start: self.start.unwrap_or(self.following.0),
+            lines: self.added,
+            original_start: self.original_start.unwrap_or(self.following.1),
+            original_lines: self.removed,
+            change,
+        })
+    }
+}
+
+/// Builds gutter signs for every line in the hunks,
+/// using the characters and colors from preferences.
+pub fn annotations(hunks: &[Hunk], preferences: &Preferences) -> Vec<Annotation> {
+    let mut annotations = Vec::new();
+    for hunk in hunks {
+        let (default_content, default_color) = hunk.change.default_sign();
+        let (content, color) = preferences.git_gutter_sign(hunk.change.name());
+        let content = content.unwrap_or(default_content);
+        let color = color.and_then(parse_color).unwrap_or(default_color);
+
+        for line in hunk.first_line()..=hunk.last_line() {
+            annotations.push(Annotation {
+                line,
+                content: content.to_string(),
+                style: Style::Default,
+                colors: Colors::CustomFocusedForeground(color),
+                position: AnnotationPosition::Gutter,
+            });
+        }
+    }
+
+    annotations
+}
+
+// Parses hex colors (e.g. "#859900").
+fn parse_color(color: &str) -> Option<RGBColor> {
+    let hex = color.trim_start_matches('#');
+    if hex.len() != 6 {
+        return None;
+    }
+    let component = |index: usize| u8::from_str_radix(hex.get(index..index + 2)?, 16).ok();
+
+    Some(RGBColor(component(0)?, component(2)?, component(4)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{annotations, hunks, Hunk, LineChange};
+    use crate::models::application::Preferences;
+    use crate::view::{Colors, RGBColor};
+    use crate::yaml::yaml::YamlLoader;
+
+    #[test]
+    fn hunks_classify_added_modified_and_removed_lines() {
+        let original = "amp\neditor\nrust\ntext\nbuffer\n";
+        let modified = "header\namp\nEDITOR\nrust\nbuffer\n";
+
+        assert_eq!(hunks(original, modified).unwrap(), vec![
+            Hunk { start: 0, lines: 1, original_start: 0, original_lines: 0, change: LineChange::Added },
+            Hunk { start: 2, lines: 1, original_start: 1, original_lines: 1, change: LineChange::Modified },
+            Hunk { start: 4, lines: 0, original_start: 3, original_lines: 1, change: LineChange::Removed },
+        ]);
+        assert!(hunks(original, original).unwrap().is_empty());
+    }
+
+    #[test]
+    fn annotations_use_configured_signs() {
+        let data = YamlLoader::load_from_str(
+            "git_gutter:\n  added:\n    character: \"|\"\n    color: \"#010203\"\n"
+        ).unwrap().into_iter().next();
+        let preferences = Preferences::new(data);
+        let hunks = vec![
+            Hunk { start: 1, lines: 2, original_start: 1, original_lines: 0, change: LineChange::Added },
+            Hunk { start: 5, lines: 0, original_start: 3, original_lines: 1, change: LineChange::Removed },
+        ];
+        let annotations = annotations(&hunks, &preferences);
+
+        assert_eq!(annotations.iter().map(|a| a.line).collect::<Vec<_>>(), vec![1, 2, 4]);
+        assert_eq!(annotations[0].content, "|");
+        assert_eq!(annotations[0].colors, Colors::CustomFocusedForeground(RGBColor(1, 2, 3)));
+        assert_eq!(annotations[2].content, "_");
+    }
+}
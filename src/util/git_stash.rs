@@ -0,0 +1,85 @@
+use crate::errors::*;
+use fragment::matching::AsStr;
+use git2::{DiffFormat, Oid, Repository};
+use std::fmt;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct StashEntry {
+    pub index: usize,
+    pub message: String,
+    pub diff: String,
+}
+
+impl fmt::Display for StashEntry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "stash@{{{}}}: {}", self.index, self.message)
+    }
+}
+
+impl AsStr for StashEntry {
+    fn as_str(&self) -> &str {
+        &self.message
+    }
+}
+
+/// Lists the repository's stashes (newest first), along with their changes.
+pub fn entries(repo: &mut Repository) -> Result<Vec<StashEntry>> {
+    let mut stashes: Vec<(usize, String, Oid)> = Vec::new();
+    repo.stash_foreach(|index, message, oid| {
+        stashes.push((index, message.to_string(), *oid));
+        true
+    }).chain_err(|| "Couldn't list the repository's stashes")?;
+
+    stashes.into_iter().map(|(index, message, oid)| {
+        Ok(StashEntry { index, message, diff: stash_diff(repo, oid)? })
+    }).collect()
+}
+
+/// Stashes the working tree's changes.
+pub fn save(repo: &mut Repository) -> Result<()> {
+    let signature = repo.signature().chain_err(|| {
+        "Couldn't determine a signature for the stash; is user.name configured?"
+    })?;
+    repo.stash_save(&signature, "Stashed from amp", None)
+        .chain_err(|| "Couldn't stash the working tree")?;
+
+    Ok(())
+}
+
+fn stash_diff(repo: &Repository, oid: Oid) -> Result<String> {
+    // A stash is a commit whose first parent is the commit it was created on.
+    let commit = repo.find_commit(oid).chain_err(|| "Couldn't find stash commit")?;
+    let tree = commit.tree().chain_err(|| "Couldn't find stash tree")?;
+    let base_tree = commit
+        .parent(0)
+        .and_then(|parent| parent.tree())
+        .chain_err(|| "Couldn't find the stash's base tree")?;
+    let diff = repo
+        .diff_tree_to_tree(Some(&base_tree), Some(&tree), None)
+        .chain_err(|| "Couldn't build a diff for the stash")?;
+
+    let mut content = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        match line.origin() {
+            '+' | '-' | ' ' => content.push(line.origin()),
+            _ => (),
+        }
+        content.push_str(&String::from_utf8_lossy(line.content()));
+
+        true
+    }).chain_err(|| "Couldn't format the stash diff")?;
+
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::StashEntry;
+
+    #[test]
+    fn display_includes_stash_index() {
+        let entry = StashEntry { index: 1, message: String::from("On master: preferences"), diff: String::new() };
+
+        assert_eq!(entry.to_string(), "stash@{1}: On master: preferences");
+    }
+}
@@ -0,0 +1,139 @@
+use git2::{self, Branch, Repository, Status};
+use std::path::Path;
+
+/// Describes the file's git status, followed by the current branch
+/// (along with its upstream divergence), for the status line.
+pub fn describe(repo: &Repository, path: Option<&Path>) -> String {
+    let mut content = String::new();
+    if let (Some(path), Some(repo_path)) = (path, repo.workdir()) {
+        if let Ok(relative_path) = path.strip_prefix(repo_path) {
+            if let Ok(status) = repo.status_file(relative_path) {
+                content = presentable_status(&status).to_string();
+            }
+        }
+    }
+
+    if let Some(branch) = branch_status(repo) {
+        if !content.is_empty() {
+            content.push(' ');
+        }
+        content.push_str(&branch);
+    }
+
+    content
+}
+
+fn branch_status(repo: &Repository) -> Option<String> {
+    let head = repo.head().ok()?;
+    if !head.is_branch() {
+        return None;
+    }
+    let name = head.shorthand()?.to_string();
+
+    // Compare against the upstream branch, if one is configured.
+    let (ahead, behind) = head.target().and_then(|local| {
+        let branch = Branch::wrap(head);
+        let upstream = branch.upstream().ok()?.get().target()?;
+
+        repo.graph_ahead_behind(local, upstream).ok()
+    }).unwrap_or((0, 0));
+
+    Some(presentable_branch(&name, ahead, behind))
+}
+
+fn presentable_branch(name: &str, ahead: usize, behind: usize) -> String {
+    let mut content = name.to_string();
+    if ahead > 0 {
+        content.push_str(&format!(" +{}", ahead));
+    }
+    if behind > 0 {
+        content.push_str(&format!(" -{}", behind));
+    }
+
+    content
+}
+
+fn presentable_status(status: &Status) -> &str {
+    if status.contains(git2::Status::WT_NEW) {
+        if status.contains(git2::Status::INDEX_NEW) {
+            // Parts of the file are staged as new in the index.
+            "[partially staged]"
+        } else {
+            // The file has never been added to the repository.
+            "[untracked]"
+        }
+    } else if status.contains(git2::Status::INDEX_NEW) {
+        // The complete file is staged as new in the index.
+        "[staged]"
+    } else if status.contains(git2::Status::WT_MODIFIED) {
+        if status.contains(git2::Status::INDEX_MODIFIED) {
+            // The file has both staged and unstaged modifications.
+            "[partially staged]"
+        } else {
+            // The file has unstaged modifications.
+            "[modified]"
+        }
+    } else if status.contains(git2::Status::INDEX_MODIFIED) {
+        // The file has staged modifications.
+        "[staged]"
+    } else {
+        // The file is tracked, but has no modifications.
+        "[ok]"
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use git2;
+    use super::{presentable_branch, presentable_status};
+
+    #[test]
+    pub fn presentable_status_returns_untracked_when_status_is_locally_new() {
+        let status = git2::Status::WT_NEW;
+        assert_eq!(presentable_status(&status), "[untracked]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_ok_when_status_unmodified() {
+        let status = git2::Status::CURRENT;
+        assert_eq!(presentable_status(&status), "[ok]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_staged_when_only_modified_in_index() {
+        let status = git2::Status::INDEX_MODIFIED;
+        assert_eq!(presentable_status(&status), "[staged]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_staged_when_new_in_index() {
+        let status = git2::Status::INDEX_NEW;
+        assert_eq!(presentable_status(&status), "[staged]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_partially_staged_when_modified_locally_and_in_index() {
+        let status = git2::Status::WT_MODIFIED | git2::Status::INDEX_MODIFIED;
+        assert_eq!(presentable_status(&status),
+                   "[partially staged]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_status_returns_partially_staged_when_new_locally_and_in_index() {
+        let status = git2::Status::WT_NEW | git2::Status::INDEX_NEW;
+        assert_eq!(presentable_status(&status),
+                   "[partially staged]".to_string());
+    }
+
+    #[test]
+    pub fn presentable_branch_returns_name_when_in_sync_with_upstream() {
+        assert_eq!(presentable_branch("master", 0, 0), "master");
+    }
+
+    #[test]
+    pub fn presentable_branch_includes_ahead_and_behind_counts() {
+        assert_eq!(presentable_branch("master", 2, 0), "master +2");
+        assert_eq!(presentable_branch("master", 0, 3), "master -3");
+        assert_eq!(presentable_branch("master", 2, 3), "master +2 -3");
+    }
+}
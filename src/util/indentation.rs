@@ -0,0 +1,107 @@
+use std::collections::HashMap;
+use std::fmt;
+
+// Large files are judged by their beginning alone.
+const MAX_SAMPLED_LINES: usize = 1000;
+
+// Widths considered when detecting space indentation; changes of a single
+// space are more often alignment (e.g. block comments) than indentation.
+const SPACE_WIDTHS: [usize; 4] = [2, 3, 4, 8];
+
+/// How a file's lines are indented.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Indentation {
+    Tabs,
+    Spaces(usize),
+}
+
+impl Indentation {
+    /// Infers a file's indentation from its lines, based on whether more of
+    /// them start with tabs or spaces and, for the latter, the most common
+    /// increase in indentation from one indented line to the next. Returns
+    /// None if there isn't enough indentation to tell.
+    pub fn detect(content: &str) -> Option<Indentation> {
+        let (mut tab_lines, mut space_lines) = (0, 0);
+        let mut increases: HashMap<usize, usize> = HashMap::new();
+        let mut previous_spaces = 0;
+
+        for line in content.lines().take(MAX_SAMPLED_LINES) {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with('\t') {
+                tab_lines += 1;
+                previous_spaces = 0;
+                continue;
+            }
+
+            let spaces = line.len() - line.trim_start_matches(' ').len();
+            if spaces > 0 {
+                space_lines += 1;
+            }
+            if spaces > previous_spaces {
+                *increases.entry(spaces - previous_spaces).or_insert(0) += 1;
+            }
+            previous_spaces = spaces;
+        }
+
+        if tab_lines > space_lines {
+            return Some(Indentation::Tabs);
+        }
+
+        // Prefer the narrower of equally common widths.
+        SPACE_WIDTHS
+            .iter()
+            .filter_map(|width| increases.get(width).map(|&count| (count, *width)))
+            .max_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)))
+            .map(|(_, width)| Indentation::Spaces(width))
+    }
+
+    /// The content inserted for a single level of indentation.
+    pub fn content(&self) -> String {
+        match *self {
+            Indentation::Tabs => String::from("\t"),
+            Indentation::Spaces(width) => " ".repeat(width),
+        }
+    }
+}
+
+impl fmt::Display for Indentation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Indentation::Tabs => write!(f, "tabs"),
+            Indentation::Spaces(width) => write!(f, "spaces: {}", width),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Indentation;
+
+    #[test]
+    fn detect_finds_the_most_common_indentation() {
+        assert_eq!(
+            Indentation::detect("fn main() {\n    if true {\n        run();\n    }\n}\n"),
+            Some(Indentation::Spaces(4))
+        );
+        assert_eq!(
+            Indentation::detect("a:\n  b:\n    c: 1\n  d:\n    e: 2\n"),
+            Some(Indentation::Spaces(2))
+        );
+        assert_eq!(
+            Indentation::detect("func main() {\n\tif x {\n\t\trun()\n\t}\n}\n"),
+            Some(Indentation::Tabs)
+        );
+    }
+
+    #[test]
+    fn detect_ignores_alignment_and_unindented_content() {
+        assert_eq!(Indentation::detect("/**\n * Docs\n */\nfn main() {}\n"), None);
+        assert_eq!(Indentation::detect("amp\n\neditor\n"), None);
+        assert_eq!(
+            Indentation::detect("/**\n * Docs\n */\nfn main() {\n  run();\n}\n"),
+            Some(Indentation::Spaces(2))
+        );
+    }
+}
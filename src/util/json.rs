@@ -0,0 +1,256 @@
+use crate::errors::*;
+use crate::yaml::yaml::{Hash, Yaml};
+use serde_json::{self, Value};
+use std::io::{self, BufRead, Read, Write};
+
+// JSON values are represented using the YAML data model used for
+// configuration, so that they can be handled the same way. Parsing
+// is left to a dedicated JSON parser, whose values are converted.
+// These helpers make building values less verbose.
+
+// The largest message we'll read; anything longer is skipped.
+const MAX_MESSAGE_LENGTH: usize = 64 * 1024 * 1024;
+
+pub fn object(entries: Vec<(&str, Yaml)>) -> Yaml {
+    let mut hash = Hash::new();
+    for (key, value) in entries {
+        hash.insert(Yaml::String(key.to_string()), value);
+    }
+
+    Yaml::Hash(hash)
+}
+
+pub fn string<T: Into<String>>(value: T) -> Yaml {
+    Yaml::String(value.into())
+}
+
+pub fn parse(content: &str) -> Result<Yaml> {
+    let value = serde_json::from_str(content).chain_err(|| "Couldn't parse JSON")?;
+
+    Ok(convert(value))
+}
+
+pub fn encode(value: &Yaml) -> String {
+    let mut content = String::new();
+    encode_into(value, &mut content);
+
+    content
+}
+
+/// Reads a single message, which is framed using a Content-Length header
+/// (as used by the debug adapter and language server protocols). Returns
+/// `None` once the stream ends (or can't be read), and an error for messages
+/// that are too large or can't be parsed, which are skipped over.
+pub fn read_message<R: BufRead>(reader: &mut R) -> Option<Result<Yaml>> {
+    let mut content_length = None;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header).ok()? == 0 {
+            return None;
+        }
+
+        let header = header.trim();
+        if header.is_empty() {
+            break;
+        }
+
+        let mut parts = header.splitn(2, ':');
+        if parts.next().map(|name| name.trim().eq_ignore_ascii_case("Content-Length")) == Some(true) {
+            content_length = parts.next().and_then(|value| value.trim().parse::<usize>().ok());
+        }
+    }
+
+    // Without a length, there's no telling where the message ends.
+    let content_length = content_length?;
+    if content_length > MAX_MESSAGE_LENGTH {
+        let skipped = io::copy(&mut reader.by_ref().take(content_length as u64), &mut io::sink()).ok()?;
+        if skipped < content_length as u64 {
+            return None;
+        }
+
+        return Some(Err(format!("Message length ({} bytes) exceeds the limit", content_length).into()));
+    }
+
+    let mut content = vec![0; content_length];
+    reader.read_exact(&mut content).ok()?;
+
+    Some(
+        String::from_utf8(content)
+            .chain_err(|| "Message isn't valid UTF-8")
+            .and_then(|content| parse(&content))
+    )
+}
+
+/// Writes a message, framed using a Content-Length header.
+pub fn write_message<W: Write>(writer: &mut W, value: &Yaml) -> io::Result<()> {
+    let message = encode(value);
+    write!(writer, "Content-Length: {}\r\n\r\n{}", message.len(), message)?;
+
+    writer.flush()
+}
+
+fn convert(value: Value) -> Yaml {
+    match value {
+        Value::Null => Yaml::Null,
+        Value::Bool(boolean) => Yaml::Boolean(boolean),
+        Value::Number(number) => match number.as_i64() {
+            Some(integer) => Yaml::Integer(integer),
+            None => Yaml::Real(number.to_string()),
+        },
+        Value::String(string) => Yaml::String(string),
+        Value::Array(values) => Yaml::Array(values.into_iter().map(convert).collect()),
+        Value::Object(entries) => {
+            let mut hash = Hash::new();
+            for (key, value) in entries {
+                hash.insert(Yaml::String(key), convert(value));
+            }
+
+            Yaml::Hash(hash)
+        }
+    }
+}
+
+fn encode_into(value: &Yaml, content: &mut String) {
+    match *value {
+        Yaml::Real(ref number) => content.push_str(number),
+        Yaml::Integer(number) => content.push_str(&number.to_string()),
+        Yaml::String(ref string) => encode_string(string, content),
+        Yaml::Boolean(boolean) => content.push_str(if boolean { "true" } else { "false" }),
+        Yaml::Array(ref values) => {
+            content.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    content.push(',');
+                }
+                encode_into(value, content);
+            }
+            content.push(']');
+        }
+        Yaml::Hash(ref hash) => {
+            content.push('{');
+            for (index, (key, value)) in hash.iter().enumerate() {
+                if index > 0 {
+                    content.push(',');
+                }
+
+                // JSON only supports string keys.
+                match *key {
+                    Yaml::String(ref key) => encode_string(key, content),
+                    _ => encode_string(&encode(key), content),
+                }
+                content.push(':');
+                encode_into(value, content);
+            }
+            content.push('}');
+        }
+        _ => content.push_str("null"),
+    }
+}
+
+fn encode_string(value: &str, content: &mut String) {
+    content.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => content.push_str("\\\""),
+            '\\' => content.push_str("\\\\"),
+            '\n' => content.push_str("\\n"),
+            '\r' => content.push_str("\\r"),
+            '\t' => content.push_str("\\t"),
+            c if (c as u32) < 0x20 => content.push_str(&format!("\\u{:04x}", c as u32)),
+            c => content.push(c),
+        }
+    }
+    content.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::BufReader;
+
+    #[test]
+    fn encode_escapes_strings() {
+        assert_eq!(encode(&string("a \"quoted\"\tvalue\n")), "\"a \\\"quoted\\\"\\tvalue\\n\"");
+    }
+
+    #[test]
+    fn encode_handles_nested_values() {
+        let value = object(vec![
+            ("lines", Yaml::Array(vec![Yaml::Integer(1), Yaml::Integer(2)])),
+            ("source", object(vec![("path", string("src/main.rs"))])),
+            ("verbose", Yaml::Boolean(false)),
+            ("missing", Yaml::Null),
+        ]);
+
+        assert_eq!(
+            encode(&value),
+            "{\"lines\":[1,2],\"missing\":null,\"source\":{\"path\":\"src/main.rs\"},\"verbose\":false}"
+        );
+    }
+
+    #[test]
+    fn parse_round_trips_encoded_values() {
+        let value = object(vec![("body", object(vec![("threadId", Yaml::Integer(1))]))]);
+
+        assert_eq!(parse(&encode(&value)).unwrap(), value);
+    }
+
+    #[test]
+    fn parse_handles_json_escapes() {
+        assert_eq!(parse(r#"{"a":"\/"}"#).unwrap()["a"].as_str(), Some("/"));
+        assert_eq!(parse(r#""\ud83d\ude00""#).unwrap().as_str(), Some("\u{1f600}"));
+        assert_eq!(parse("1.5").unwrap(), Yaml::Real("1.5".to_string()));
+    }
+
+    #[test]
+    fn parse_rejects_invalid_json() {
+        assert!(parse("{\"a\": }").is_err());
+        assert!(parse("a: b").is_err());
+    }
+
+    #[test]
+    fn read_message_parses_framed_content() {
+        let data = "Content-Length: 24\r\n\r\n{\"seq\":1,\"type\":\"event\"}Content-Length: 2\r\n\r\n{}";
+        let mut reader = data.as_bytes();
+        let message = read_message(&mut reader).unwrap().unwrap();
+
+        assert_eq!(message["seq"], Yaml::Integer(1));
+        assert_eq!(message["type"].as_str(), Some("event"));
+        assert!(read_message(&mut reader).unwrap().is_ok());
+        assert!(read_message(&mut reader).is_none());
+    }
+
+    #[test]
+    fn read_message_skips_invalid_content() {
+        let data = "Content-Length: 7\r\n\r\n{\"a\": }Content-Length: 2\r\n\r\n{}";
+        let mut reader = data.as_bytes();
+
+        assert!(read_message(&mut reader).unwrap().is_err());
+        assert_eq!(read_message(&mut reader).unwrap().unwrap(), object(vec![]));
+    }
+
+    #[test]
+    fn read_message_rejects_oversized_content() {
+        let length = MAX_MESSAGE_LENGTH + 1;
+        let header = format!("Content-Length: {}\r\n\r\n", length);
+        let mut reader = BufReader::new(
+            header.as_bytes()
+                .chain(io::repeat(b' ').take(length as u64))
+                .chain("Content-Length: 2\r\n\r\n{}".as_bytes())
+        );
+
+        assert!(read_message(&mut reader).unwrap().is_err());
+        assert_eq!(read_message(&mut reader).unwrap().unwrap(), object(vec![]));
+
+        let mut reader = "Content-Length: 99999999999\r\n\r\n{}".as_bytes();
+        assert!(read_message(&mut reader).is_none());
+    }
+
+    #[test]
+    fn write_message_frames_encoded_content() {
+        let mut output = Vec::new();
+        write_message(&mut output, &object(vec![("id", Yaml::Integer(1))])).unwrap();
+
+        assert_eq!(String::from_utf8(output).unwrap(), "Content-Length: 8\r\n\r\n{\"id\":1}");
+    }
+}
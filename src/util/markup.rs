@@ -0,0 +1,193 @@
+use std::path::Path;
+
+const MARKUP_EXTENSIONS: [&str; 8] = ["html", "htm", "xhtml", "xml", "svg", "jsx", "tsx", "vue"];
+
+// HTML elements that never have a closing tag.
+const VOID_ELEMENTS: [&str; 14] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input",
+    "link", "meta", "param", "source", "track", "wbr",
+];
+
+/// An opening or closing tag, located using byte offsets.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub closing: bool,
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Tag {
+    /// The byte offset of the tag's name, following "<" or "</".
+    pub fn name_start(&self) -> usize {
+        self.start + if self.closing { 2 } else { 1 }
+    }
+
+    fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset < self.end
+    }
+}
+
+/// Whether or not the file is written using HTML, XML, or JSX tags.
+pub fn is_markup(path: Option<&Path>) -> bool {
+    path.and_then(|path| path.extension())
+        .and_then(|extension| extension.to_str())
+        .map(|extension| MARKUP_EXTENSIONS.contains(&extension.to_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Given content ending with a ">" that was just typed, returns the tag
+/// that closes it, if it completes an opening (non-self-closing) tag.
+pub fn closing_tag(content: &str) -> Option<String> {
+    let tag = &content[content.rfind('<')?..];
+
+    // The ">" needs to end the tag, rather than be part of an attribute value.
+    if tag_end(tag) != Some(tag.len()) || tag.ends_with("/>") {
+        return None;
+    }
+
+    let name = tag_name(&tag[1..]);
+    if name.is_empty() || VOID_ELEMENTS.contains(&name.to_lowercase().as_str()) {
+        return None;
+    }
+
+    Some(format!("</{}>", name))
+}
+
+/// Finds the opening and closing tags of the element at the offset: the one
+/// whose tags include it, or failing that, the innermost element around it.
+pub fn matching_tags(data: &str, offset: usize) -> Option<(Tag, Tag)> {
+    let pairs = pairs(tags(data));
+
+    pairs
+        .iter()
+        .find(|(opening, closing)| opening.contains(offset) || closing.contains(offset))
+        .or_else(|| {
+            pairs
+                .iter()
+                .filter(|(opening, closing)| opening.end <= offset && offset < closing.start)
+                .max_by_key(|(opening, _)| opening.start)
+        })
+        .cloned()
+}
+
+// Pairs opening tags with closing ones, discarding those left unclosed.
+fn pairs(tags: Vec<Tag>) -> Vec<(Tag, Tag)> {
+    let mut pairs = Vec::new();
+    let mut open_tags: Vec<Tag> = Vec::new();
+
+    for tag in tags {
+        if !tag.closing {
+            open_tags.push(tag);
+        } else if let Some(index) = open_tags.iter().rposition(|opening| opening.name == tag.name) {
+            // Anything opened since the matching tag was left unclosed.
+            open_tags.truncate(index + 1);
+            pairs.push((open_tags.pop().unwrap(), tag));
+        }
+    }
+
+    pairs
+}
+
+// Scans content for opening and closing tags, skipping
+// comments, declarations, and self-closing/void elements.
+fn tags(data: &str) -> Vec<Tag> {
+    let mut tags = Vec::new();
+    let mut offset = 0;
+
+    while let Some(index) = data[offset..].find('<') {
+        let start = offset + index;
+        let rest = &data[start..];
+
+        if rest.starts_with("<!--") {
+            offset = rest.find("-->").map(|end| start + end + 3).unwrap_or(data.len());
+            continue;
+        }
+        let end = match tag_end(rest) {
+            Some(end) => start + end,
+            None => break,
+        };
+        offset = end;
+
+        let closing = rest.starts_with("</");
+        let name = tag_name(&rest[if closing { 2 } else { 1 }..]);
+        let self_closing = data[..end - 1].ends_with('/') ||
+            VOID_ELEMENTS.contains(&name.to_lowercase().as_str());
+        if name.is_empty() || (self_closing && !closing) {
+            offset = if name.is_empty() { start + 1 } else { end };
+            continue;
+        }
+
+        tags.push(Tag{ name: name.to_string(), closing, start, end });
+    }
+
+    tags
+}
+
+// The byte offset following a tag's closing ">", ignoring quoted attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (index, character) in tag.char_indices() {
+        match (quote, character) {
+            (None, '"') | (None, '\'') => quote = Some(character),
+            (Some(q), c) if q == c => quote = None,
+            (None, '>') => return Some(index + 1),
+            _ => (),
+        }
+    }
+
+    None
+}
+
+fn tag_name(content: &str) -> &str {
+    let end = content
+        .find(|c: char| !(c.is_alphanumeric() || "-_:.".contains(c)))
+        .unwrap_or(content.len());
+
+    &content[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use super::{closing_tag, is_markup, matching_tags};
+
+    #[test]
+    fn is_markup_checks_the_file_extension() {
+        assert!(is_markup(Some(Path::new("index.HTML"))));
+        assert!(is_markup(Some(Path::new("src/app.jsx"))));
+        assert!(!is_markup(Some(Path::new("main.rs"))));
+        assert!(!is_markup(None));
+    }
+
+    #[test]
+    fn closing_tag_closes_opening_tags_only() {
+        assert_eq!(closing_tag("  <div class=\"a\">"), Some(String::from("</div>")));
+        assert_eq!(closing_tag("<my-app:view>"), Some(String::from("</my-app:view>")));
+        assert_eq!(closing_tag("<a title=\"b>"), None);
+        assert_eq!(closing_tag("<div></div>"), None);
+        assert_eq!(closing_tag("<br>"), None);
+        assert_eq!(closing_tag("<Item />"), None);
+        assert_eq!(closing_tag("<!DOCTYPE html>"), None);
+        assert_eq!(closing_tag("a -> b"), None);
+    }
+
+    #[test]
+    fn matching_tags_finds_the_element_at_the_offset() {
+        let data = "<ul>\n  <li title=\"a>b\">one<br/></li>\n  <!-- <li> -->\n  <li>two</li>\n</ul>\n";
+
+        // Within the first list item's closing tag.
+        let (opening, closing) = matching_tags(data, 32).unwrap();
+        assert_eq!((opening.name.as_str(), opening.start, closing.start), ("li", 7, 31));
+        assert_eq!(closing.name_start(), 33);
+
+        // Within the second list item's content.
+        let (opening, closing) = matching_tags(data, 60).unwrap();
+        assert_eq!((opening.start, closing.start), (55, 62));
+
+        // Between the list items, within a comment.
+        let (opening, closing) = matching_tags(data, 40).unwrap();
+        assert_eq!((opening.name.as_str(), opening.start, closing.start), ("ul", 0, 68));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::models::Application;
+use crate::util;
+use std::fs;
+use std::time::Duration;
+
+/// Describes the memory used by each open buffer's text and highlighting
+/// cache, along with totals. Sizes are approximate, and the undo history
+/// isn't included, since scribe doesn't expose its size.
+pub fn usage_report(app: &mut Application) -> String {
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+    let mut rows = Vec::new();
+    let (mut text_total, mut cache_total) = (0, 0);
+
+    for id in util::buffer_ids(app) {
+        if !util::select_buffer(id, app) {
+            continue;
+        }
+        if let Some(buffer) = app.workspace.current_buffer() {
+            let title = buffer.path
+                .as_ref()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_else(|| String::from("untitled"));
+            let text = buffer.data().len();
+            let cache = app.view.render_cache_size(id);
+            text_total += text;
+            cache_total += cache;
+            rows.push(format_row(&format!("#{} {}", id + 1, title), text, cache));
+        }
+    }
+    if let Some(id) = current_id {
+        util::select_buffer(id, app);
+    }
+
+    format!(
+        "Approximate memory used by open buffers (excluding undo history):\n\n{:<40} {:>10} {:>10} {:>10}\n{}{}",
+        "buffer", "text", "highlight", "total",
+        rows.concat(),
+        format_row("total", text_total, cache_total)
+    )
+}
+
+/// Discards the highlighting caches of buffers that haven't been used for the
+/// specified duration, as well as the undo histories of those without unsaved
+/// changes. Histories are discarded by reloading the buffer, so buffers that
+/// have changed on disk are left alone. Returns the number of buffers evicted.
+pub fn evict_idle_buffers(app: &mut Application, idle: Duration) -> usize {
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+    let idle_ids = app.recent_buffers.take_idle(idle);
+    let mut evicted = 0;
+
+    for id in idle_ids {
+        if Some(id) == current_id || !util::select_buffer(id, app) {
+            continue;
+        }
+        app.view.clear_render_cache(id);
+        if let Some(buffer) = app.workspace.current_buffer() {
+            let unchanged_on_disk = !app.view.modified(buffer) && buffer.path
+                .as_ref()
+                .and_then(|path| fs::read_to_string(path).ok())
+                .map(|content| content == buffer.data()) == Some(true);
+            if unchanged_on_disk {
+                let position = *buffer.cursor;
+                if buffer.reload().is_ok() {
+                    buffer.cursor.move_to(position);
+                }
+            }
+        }
+        evicted += 1;
+    }
+    if let Some(id) = current_id {
+        util::select_buffer(id, app);
+    }
+
+    evicted
+}
+
+fn format_row(label: &str, text: usize, cache: usize) -> String {
+    format!(
+        "{:<40} {:>10} {:>10} {:>10}\n",
+        label, format_size(text), format_size(cache), format_size(text + cache)
+    )
+}
+
+fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::Application;
+    use scribe::Buffer;
+    use std::path::Path;
+    use std::time::Duration;
+
+    #[test]
+    fn format_size_uses_the_largest_sensible_unit() {
+        assert_eq!(super::format_size(512), "512 B");
+        assert_eq!(super::format_size(1536), "1.5 KB");
+        assert_eq!(super::format_size(3 * 1024 * 1024), "3.0 MB");
+    }
+
+    #[test]
+    fn usage_report_lists_buffers_and_totals() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp");
+        app.workspace.add_buffer(buffer);
+
+        let report = super::usage_report(&mut app);
+        let lines: Vec<&str> = report.lines().collect();
+        assert!(lines[3].ends_with("3 B        0 B        3 B"));
+        assert!(lines[4].starts_with("total"));
+    }
+
+    #[test]
+    fn evict_idle_buffers_discards_unmodified_undo_history_and_keeps_the_cursor() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        let buffer = Buffer::from_file(Path::new("Cargo.toml")).unwrap();
+        app.workspace.add_buffer(buffer);
+        let id = app.workspace.current_buffer().unwrap().id.unwrap();
+        {
+            let buffer = app.workspace.current_buffer().unwrap();
+            buffer.insert("amp");
+            buffer.undo();
+            buffer.cursor.move_down();
+        }
+        app.recent_buffers.access(id);
+        app.workspace.add_buffer(Buffer::new());
+
+        assert_eq!(super::evict_idle_buffers(&mut app, Duration::from_secs(0)), 1);
+        app.workspace.previous_buffer();
+        let buffer = app.workspace.current_buffer().unwrap();
+        assert_eq!(buffer.cursor.line, 1);
+
+        // Without history, there's nothing to redo.
+        let data = buffer.data();
+        buffer.redo();
+        assert_eq!(buffer.data(), data);
+    }
+}
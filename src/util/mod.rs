@@ -1,12 +1,60 @@
+pub use self::position_map::PositionMap;
 pub use self::selectable_vec::SelectableVec;
 
+pub mod diff;
+pub mod encryption;
+pub mod export;
+pub mod expression;
+pub mod fuzzy;
+pub mod generate;
+pub mod git_blame;
+pub mod git_gutter;
+pub mod git_stash;
+pub mod git_status;
+pub mod indentation;
+pub mod json;
+pub mod markup;
+pub mod memory;
 pub mod movement_lexer;
+pub mod number;
+mod position_map;
+pub mod preview;
+pub mod prompt;
+pub mod reload;
+pub mod replacement;
 mod selectable_vec;
+pub mod tags;
+pub mod test_runner;
 pub mod token;
+pub mod tools;
+pub mod transform;
 
 use crate::errors::*;
 use crate::models::Application;
+use crate::models::application::{ChangeSet, Mode};
+use git2::Repository;
 use scribe::buffer::{Buffer, LineRange, Position, Range};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Hashes content to cheaply tell whether it has changed.
+pub fn content_hash(content: &str) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    content.hash(&mut hasher);
+
+    hasher.finish()
+}
+
+/// Whether the repository (if there is one) ignores the path,
+/// according to its .gitignore files and exclusion settings.
+pub fn git_ignored(repo: Option<&Repository>, path: &Path) -> bool {
+    repo.and_then(|repo| {
+        let relative_path = path.strip_prefix(repo.workdir()?).ok()?;
+        repo.is_path_ignored(relative_path).ok()
+    }) == Some(true)
+}
 
 /// Translates a line range to a regular range, including its last line.
 /// Handles ranges including and end line without trailing newline character.
@@ -49,6 +97,25 @@ pub fn inclusive_range(line_range: &LineRange, buffer: &mut Buffer) -> Range {
                end_position)
 }
 
+/// Replaces whole lines (including their line endings) starting at the
+/// specified line with new content. The content should end with a newline,
+/// unless it replaces the buffer's last line and that doesn't have one.
+pub fn replace_lines(buffer: &mut Buffer, line: usize, lines: &[&str], content: &str) {
+    let start = Position { line, offset: 0 };
+    if let Some(last) = lines.last() {
+        let end = if last.ends_with('\n') {
+            Position { line: line + lines.len(), offset: 0 }
+        } else {
+            Position { line: line + lines.len() - 1, offset: last.graphemes(true).count() }
+        };
+        buffer.delete_range(Range::new(start, end));
+    }
+    if !content.is_empty() {
+        buffer.cursor.move_to(start);
+        buffer.insert(content);
+    }
+}
+
 /// Convenience method to initialize and add a buffer to the workspace.
 pub fn add_buffer(buffer: Buffer, app: &mut Application) -> Result<()> {
     app.workspace.add_buffer(buffer);
@@ -57,8 +124,150 @@ pub fn add_buffer(buffer: Buffer, app: &mut Application) -> Result<()> {
     Ok(())
 }
 
+/// Opens a buffer previewing the changes as a unified diff. Saving
+/// the buffer applies the changes, and closing it discards them.
+pub fn preview_changes(changes: ChangeSet, app: &mut Application) -> Result<()> {
+    if changes.is_empty() {
+        bail!("No changes to preview");
+    }
+
+    let mut buffer = Buffer::new();
+    buffer.insert(changes.diff(&app.workspace.path));
+    add_buffer(buffer, app)?;
+    let id = app.workspace.current_buffer().and_then(|b| b.id).ok_or(BUFFER_MISSING)?;
+    app.pending_changes.insert(id, changes);
+
+    Ok(())
+}
+
+/// Returns the range selected in the current buffer, when in a select mode.
+pub fn selected_range(app: &mut Application) -> Result<Range> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let range = match app.mode {
+        Mode::Select(ref mode) => Range::new(*buffer.cursor.clone(), mode.anchor),
+        Mode::SelectLine(ref mode) => {
+            inclusive_range(&LineRange::new(mode.anchor, buffer.cursor.line), buffer)
+        }
+        _ => bail!("Can't read a selection outside of select modes"),
+    };
+
+    Ok(range)
+}
+
+/// Selects the buffer with the specified ID, returning
+/// false if it's no longer open in the workspace.
+pub fn select_buffer(id: usize, app: &mut Application) -> bool {
+    let start_id = match app.workspace.current_buffer().and_then(|b| b.id) {
+        Some(id) => id,
+        None => return false,
+    };
+
+    let mut current_id = start_id;
+    while current_id != id {
+        app.workspace.next_buffer();
+        current_id = app.workspace.current_buffer().and_then(|b| b.id).unwrap_or(start_id);
+        if current_id == start_id {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Returns the IDs of all open buffers, starting with the current one.
+pub fn buffer_ids(app: &mut Application) -> Vec<usize> {
+    let mut ids = Vec::new();
+    let start_id = app.workspace.current_buffer().and_then(|b| b.id);
+
+    if start_id.is_some() {
+        loop {
+            if let Some(id) = app.workspace.current_buffer().and_then(|b| b.id) {
+                ids.push(id);
+            }
+
+            app.workspace.next_buffer();
+            if app.workspace.current_buffer().and_then(|b| b.id) == start_id {
+                break;
+            }
+        }
+    }
+
+    ids
+}
+
+/// Whether any of the buffers have unsaved (non-empty) modifications.
+pub fn any_modified(ids: &[usize], app: &mut Application) -> bool {
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+    let modified = ids.iter().any(|&id| {
+        if !select_buffer(id, app) {
+            return false;
+        }
+        let view = &app.view;
+        app.workspace.current_buffer().map(|buf| {
+            view.modified(buf) && !buf.data().is_empty()
+        }) == Some(true)
+    });
+    if let Some(id) = current_id {
+        select_buffer(id, app);
+    }
+
+    modified
+}
+
+/// Closes the current buffer (discarding unsaved changes), forgetting
+/// everything kept about it. Scribe reuses the IDs of closed buffers, so
+/// anything left behind would be attributed to the next buffer opened.
+/// Marks are the exception: they're kept, to reopen the buffer's file.
+pub fn close_current_buffer(app: &mut Application) -> Result<()> {
+    let buffer = app.workspace.current_buffer().ok_or(BUFFER_MISSING)?;
+    let id = buffer.id;
+
+    // Clean up view-related data for the buffer.
+    app.view.forget_buffer(buffer)?;
+    app.workspace.close_current_buffer();
+    if let Some(id) = id {
+        app.encrypted_buffers.remove(&id);
+        app.pending_changes.remove(&id);
+        app.edit_logs.forget(id);
+        app.marks.forget_buffer(id);
+        app.read_only_buffers.forget(id);
+        app.recent_buffers.forget(id);
+        app.goal_columns.remove(&id);
+        app.multi_cursors.remove(&id);
+        app.pinned_buffers.remove(&id);
+        app.undo_trees.forget_buffer(id);
+        app.word_index.forget_buffer(id);
+        app.git_gutter.forget_buffer(id);
+        app.git_worker.forget_buffer(id);
+    }
+
+    Ok(())
+}
+
+/// Closes the buffers (discarding unsaved changes), reporting how many were
+/// closed in a notification. The current buffer is kept selected if it's open.
+pub fn close_buffers(ids: &[usize], app: &mut Application) -> Result<()> {
+    let current_id = app.workspace.current_buffer().and_then(|b| b.id);
+    let mut closed = 0;
+
+    for &id in ids {
+        if select_buffer(id, app) {
+            close_current_buffer(app)?;
+            closed += 1;
+        }
+    }
+    if let Some(id) = current_id {
+        select_buffer(id, app);
+    }
+    app.notification = Some(format!("Closed {} buffer{}", closed, if closed == 1 { "" } else { "s" }));
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
+    use crate::models::Application;
+    use crate::models::application::{Column, GoalColumn, MultiCursor};
     use scribe::Buffer;
     use scribe::buffer::{LineRange, Position, Range};
 
@@ -95,4 +304,24 @@ mod tests {
                                   offset: 0,
                               }));
     }
+
+    #[test]
+    fn close_current_buffer_forgets_state_that_would_carry_over_to_reused_ids() {
+        let mut app = Application::new(&Vec::new()).unwrap();
+        app.workspace.add_buffer(Buffer::new());
+        let buffer = app.workspace.current_buffer().unwrap();
+        let id = buffer.id.unwrap();
+        app.undo_trees.track(buffer);
+        app.recent_buffers.access(id);
+        app.pinned_buffers.insert(id);
+        app.goal_columns.insert(id, GoalColumn { position: Position::new(), column: Column::EndOfLine });
+        app.multi_cursors.insert(id, MultiCursor::default());
+
+        super::close_current_buffer(&mut app).unwrap();
+        assert!(app.undo_trees.get(id).is_none());
+        assert!(!app.recent_buffers.ids().contains(&id));
+        assert!(app.pinned_buffers.is_empty());
+        assert!(app.goal_columns.is_empty());
+        assert!(app.multi_cursors.is_empty());
+    }
 }
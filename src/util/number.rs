@@ -0,0 +1,217 @@
+use scribe::Buffer;
+use scribe::buffer::{Position, Range};
+use std::fmt;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The bases in which numbers can be represented, in cycling order.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Base {
+    Decimal,
+    Hexadecimal,
+    Binary,
+    Octal,
+}
+
+impl Base {
+    pub fn all() -> [Base; 4] {
+        [Base::Decimal, Base::Hexadecimal, Base::Binary, Base::Octal]
+    }
+
+    pub fn next(self) -> Base {
+        match self {
+            Base::Decimal => Base::Hexadecimal,
+            Base::Hexadecimal => Base::Binary,
+            Base::Binary => Base::Octal,
+            Base::Octal => Base::Decimal,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Base::Decimal => "dec",
+            Base::Hexadecimal => "hex",
+            Base::Binary => "bin",
+            Base::Octal => "oct",
+        }
+    }
+
+    fn radix(self) -> u32 {
+        match self {
+            Base::Decimal => 10,
+            Base::Hexadecimal => 16,
+            Base::Binary => 2,
+            Base::Octal => 8,
+        }
+    }
+}
+
+/// A number literal found in a line of text.
+#[derive(Debug, PartialEq)]
+pub struct Number {
+    /// The byte offset at which the literal starts.
+    pub start: usize,
+
+    /// The byte offset immediately after the literal (excluding any suffix).
+    pub end: usize,
+
+    pub value: u64,
+    pub base: Base,
+
+    /// Whether the literal used an uppercase prefix (e.g. `0X`) or digits
+    /// (e.g. `0xFF`), so that conversions can follow suit.
+    pub uppercase_prefix: bool,
+    pub uppercase_digits: bool,
+}
+
+impl Number {
+    /// Formats the number in the specified base, including its prefix.
+    pub fn format(&self, base: Base) -> String {
+        let (prefix, digits) = match base {
+            Base::Decimal => return self.value.to_string(),
+            Base::Hexadecimal if self.uppercase_digits => ("0x", format!("{:X}", self.value)),
+            Base::Hexadecimal => ("0x", format!("{:x}", self.value)),
+            Base::Binary => ("0b", format!("{:b}", self.value)),
+            Base::Octal => ("0o", format!("{:o}", self.value)),
+        };
+
+        if self.uppercase_prefix {
+            format!("{}{}", prefix.to_uppercase(), digits)
+        } else {
+            format!("{}{}", prefix, digits)
+        }
+    }
+}
+
+/// A number formatted in a particular base, for display in a picker.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conversion {
+    pub base: Base,
+    pub content: String,
+}
+
+impl fmt::Display for Conversion {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}  {}", self.base.name(), self.content)
+    }
+}
+
+impl fragment::matching::AsStr for Conversion {
+    fn as_str(&self) -> &str {
+        self.base.name()
+    }
+}
+
+/// Finds the number literal at the specified byte offset in a line.
+/// Supports `0x`, `0b`, and `0o` prefixes and `_` separators; type
+/// suffixes (e.g. `u8`) are left out of the literal's range.
+pub fn number_at(line: &str, offset: usize) -> Option<Number> {
+    let is_word_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let offset = offset.min(line.len());
+
+    // Find the surrounding word.
+    let start = line[..offset]
+        .char_indices()
+        .rev()
+        .take_while(|&(_, c)| is_word_char(c))
+        .last()
+        .map(|(index, _)| index)
+        .unwrap_or(offset);
+    let end = line[offset..]
+        .char_indices()
+        .find(|&(_, c)| !is_word_char(c))
+        .map(|(index, _)| offset + index)
+        .unwrap_or_else(|| line.len());
+    let word = &line[start..end];
+
+    if !word.starts_with(|c: char| c.is_ascii_digit()) {
+        return None;
+    }
+
+    let (base, prefix_length) = match word.get(0..2) {
+        Some("0x") | Some("0X") => (Base::Hexadecimal, 2),
+        Some("0b") | Some("0B") => (Base::Binary, 2),
+        Some("0o") | Some("0O") => (Base::Octal, 2),
+        _ => (Base::Decimal, 0),
+    };
+    let digits: String = word[prefix_length..]
+        .chars()
+        .take_while(|&c| c.is_digit(base.radix()) || c == '_')
+        .collect();
+    let value = u64::from_str_radix(&digits.replace('_', ""), base.radix()).ok()?;
+    let literal = &word[..prefix_length + digits.len()];
+
+    Some(Number {
+        start,
+        end: start + literal.len(),
+        value,
+        base,
+        uppercase_prefix: literal[..prefix_length].chars().any(|c| c.is_ascii_uppercase()),
+        uppercase_digits: digits.chars().any(|c| c.is_ascii_uppercase()),
+    })
+}
+
+/// Finds the number literal under the buffer's cursor,
+/// along with the buffer range it occupies.
+pub fn number_at_cursor(buffer: &Buffer) -> Option<(Number, Range)> {
+    let line_number = buffer.cursor.line;
+    let data = buffer.data();
+    let line = data.lines().nth(line_number)?;
+
+    // Cursor offsets are measured in graphemes, rather than bytes.
+    let offset = line
+        .grapheme_indices(true)
+        .nth(buffer.cursor.offset)
+        .map(|(index, _)| index)
+        .unwrap_or_else(|| line.len());
+    let number = number_at(line, offset)?;
+    let position = |index: usize| Position {
+        line: line_number,
+        offset: line[..index].graphemes(true).count(),
+    };
+    let range = Range::new(position(number.start), position(number.end));
+
+    Some((number, range))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{number_at, Base};
+
+    #[test]
+    fn number_at_finds_numbers_around_the_offset() {
+        let line = "let mask = 0xFF_00u32 + 42;";
+
+        let number = number_at(line, 13).unwrap();
+        assert_eq!((number.start, number.end), (11, 18));
+        assert_eq!(number.value, 0xff00);
+        assert_eq!(number.base, Base::Hexadecimal);
+        assert!(!number.uppercase_prefix);
+        assert!(number.uppercase_digits);
+
+        let number = number_at(line, 26).unwrap();
+        assert_eq!((number.start, number.end, number.value), (24, 26, 42));
+        assert_eq!(number.base, Base::Decimal);
+    }
+
+    #[test]
+    fn number_at_ignores_identifiers() {
+        assert!(number_at("let value2 = 1;", 9).is_none());
+        assert!(number_at("let value2 = 1;", 2).is_none());
+    }
+
+    #[test]
+    fn format_includes_prefixes_and_preserves_case() {
+        let number = number_at("0X2a", 0).unwrap();
+        assert_eq!(number.format(Base::Decimal), "42");
+        assert_eq!(number.format(Base::Hexadecimal), "0X2a");
+        assert_eq!(number.format(Base::Binary), "0B101010");
+
+        let number = number_at("0x2A", 0).unwrap();
+        assert_eq!(number.format(Base::Hexadecimal), "0x2A");
+
+        let number = number_at("42", 0).unwrap();
+        assert_eq!(number.format(Base::Hexadecimal), "0x2a");
+        assert_eq!(number.format(Base::Binary), "0b101010");
+        assert_eq!(number.format(Base::Octal), "0o52");
+    }
+}
@@ -0,0 +1,78 @@
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Maps byte offsets in a buffer's content (e.g. those of regex matches)
+/// to positions, whose offsets are measured in graphemes, and vice versa.
+pub struct PositionMap<'a> {
+    data: &'a str,
+
+    /// Byte offsets at which each line starts.
+    line_starts: Vec<usize>,
+}
+
+impl<'a> PositionMap<'a> {
+    pub fn new(data: &'a str) -> PositionMap<'a> {
+        PositionMap {
+            data,
+            line_starts: Some(0)
+                .into_iter()
+                .chain(data.match_indices('\n').map(|(index, _)| index + 1))
+                .collect(),
+        }
+    }
+
+    pub fn position(&self, offset: usize) -> Position {
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next_line) => next_line - 1,
+        };
+
+        Position {
+            line,
+            offset: self.data[self.line_starts[line]..offset].graphemes(true).count(),
+        }
+    }
+
+    /// Maps a position back to a byte offset, limiting it to the end of
+    /// its line (and to the end of the content for lines past the last).
+    pub fn offset(&self, position: &Position) -> usize {
+        let line_start = match self.line_starts.get(position.line) {
+            Some(&line_start) => line_start,
+            None => return self.data.len(),
+        };
+        let line = self.data[line_start..].split('\n').next().unwrap_or("");
+
+        line_start + line
+            .grapheme_indices(true)
+            .nth(position.offset)
+            .map(|(index, _)| index)
+            .unwrap_or_else(|| line.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PositionMap;
+    use scribe::buffer::Position;
+
+    #[test]
+    fn position_counts_graphemes_from_the_start_of_the_line() {
+        let map = PositionMap::new("amp\néditor\n");
+
+        assert_eq!(map.position(0), Position { line: 0, offset: 0 });
+        assert_eq!(map.position(3), Position { line: 0, offset: 3 });
+        assert_eq!(map.position(4), Position { line: 1, offset: 0 });
+        assert_eq!(map.position(7), Position { line: 1, offset: 2 });
+        assert_eq!(map.position(12), Position { line: 2, offset: 0 });
+    }
+
+    #[test]
+    fn offset_is_limited_to_the_end_of_the_line() {
+        let map = PositionMap::new("amp\néditor\n");
+
+        assert_eq!(map.offset(&Position { line: 1, offset: 2 }), 7);
+        assert_eq!(map.offset(&Position { line: 0, offset: 10 }), 3);
+        assert_eq!(map.offset(&Position { line: 2, offset: 0 }), 12);
+        assert_eq!(map.offset(&Position { line: 5, offset: 0 }), 12);
+    }
+}
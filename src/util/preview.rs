@@ -0,0 +1,47 @@
+use std::fs;
+use std::path::Path;
+
+// Lines shown on either side of the previewed line.
+const CONTEXT_LINES: usize = 5;
+
+/// The lines surrounding one of a file's (zero-based) lines, for previewing
+/// list entries that refer to it (e.g. search matches or diagnostics).
+pub fn file_context(path: &Path, line: usize) -> Option<String> {
+    let content = fs::read_to_string(path).ok()?;
+
+    Some(context(&content, line))
+}
+
+/// The lines surrounding one of the content's (zero-based) lines, numbered,
+/// with the line itself marked.
+pub fn context(content: &str, line: usize) -> String {
+    let first = line.saturating_sub(CONTEXT_LINES);
+    let lines: Vec<&str> = content
+        .lines()
+        .skip(first)
+        .take(line - first + CONTEXT_LINES + 1)
+        .collect();
+    let number_width = (first + lines.len()).to_string().len();
+
+    lines.iter().enumerate().map(|(index, content)| {
+        let number = first + index;
+        let marker = if number == line { '>' } else { ' ' };
+
+        format!("{} {:>width$} {}\n", marker, number + 1, content, width = number_width)
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::context;
+
+    #[test]
+    fn context_numbers_the_surrounding_lines_and_marks_the_line() {
+        let content: String = (1..=20).map(|line| format!("line {}\n", line)).collect();
+
+        assert_eq!(context(&content, 1), "  1 line 1\n> 2 line 2\n  3 line 3\n  4 line 4\n  5 line 5\n  6 line 6\n  7 line 7\n");
+        assert!(context(&content, 12).starts_with("   8 line 8\n"));
+        assert!(context(&content, 12).contains("> 13 line 13\n"));
+        assert!(context(&content, 12).ends_with("  18 line 18\n"));
+    }
+}
@@ -0,0 +1,126 @@
+use std::borrow::Cow;
+use std::fs;
+use std::path::Path;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// Removes the last character (grapheme) from the input.
+pub fn pop_char(input: &mut String) {
+    let length = input.graphemes(true).next_back().map(|grapheme| grapheme.len()).unwrap_or(0);
+    let new_length = input.len() - length;
+
+    input.truncate(new_length);
+}
+
+/// Removes the last word from the input. Runs of whitespace, word characters,
+/// and punctuation (e.g. path separators) are each considered words.
+pub fn pop_word(input: &mut String) {
+    let kind = |c: char| {
+        if c.is_whitespace() {
+            0
+        } else if c.is_alphanumeric() || c == '_' {
+            1
+        } else {
+            2
+        }
+    };
+
+    let last_kind = match input.chars().next_back() {
+        Some(c) => kind(c),
+        None => return,
+    };
+    let new_length = input
+        .char_indices()
+        .rev()
+        .find(|&(_, c)| kind(c) != last_kind)
+        .map(|(index, c)| index + c.len_utf8())
+        .unwrap_or(0);
+
+    input.truncate(new_length);
+}
+
+/// Replaces control characters (e.g. pasted tabs and newlines) with their
+/// caret notation (e.g. "^I" and "^J"), so that they're visible in prompts.
+pub fn escape(input: &str) -> Cow<str> {
+    if !input.chars().any(char::is_control) {
+        return Cow::Borrowed(input);
+    }
+
+    let mut escaped = String::with_capacity(input.len());
+    for c in input.chars() {
+        match c as u32 {
+            code @ 0..=0x1f => {
+                escaped.push('^');
+                escaped.push((code as u8 + b'@') as char);
+            },
+            0x7f => escaped.push_str("^?"),
+            _ if c.is_control() => escaped.extend(c.escape_unicode()),
+            _ => escaped.push(c),
+        }
+    }
+
+    Cow::Owned(escaped)
+}
+
+/// Lists the entries in the input's directory whose names start with the
+/// input's final component, with a trailing separator for directories.
+pub fn complete_path(input: &str) -> Vec<String> {
+    let (directory, name) = match input.rfind('/') {
+        Some(index) => input.split_at(index + 1),
+        None => ("", input),
+    };
+    let entries = match fs::read_dir(if directory.is_empty() { Path::new(".") } else { Path::new(directory) }) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut candidates: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let entry_name = entry.file_name().into_string().ok()?;
+            if !entry_name.starts_with(name) {
+                return None;
+            }
+            let separator = if entry.path().is_dir() { "/" } else { "" };
+
+            Some(format!("{}{}{}", directory, entry_name, separator))
+        })
+        .collect();
+    candidates.sort();
+
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape, pop_char, pop_word};
+
+    #[test]
+    fn pop_char_removes_the_last_grapheme() {
+        let mut input = String::from("amp é");
+        pop_char(&mut input);
+        assert_eq!(input, "amp ");
+
+        let mut input = String::new();
+        pop_char(&mut input);
+        assert_eq!(input, "");
+    }
+
+    #[test]
+    fn pop_word_removes_runs_of_the_same_kind_of_character() {
+        let mut input = String::from("src/models  ");
+        pop_word(&mut input);
+        assert_eq!(input, "src/models");
+        pop_word(&mut input);
+        assert_eq!(input, "src/");
+        pop_word(&mut input);
+        assert_eq!(input, "src");
+        pop_word(&mut input);
+        assert_eq!(input, "");
+    }
+
+    #[test]
+    fn escape_uses_caret_notation_for_control_characters() {
+        assert_eq!(escape("amp"), "amp");
+        assert_eq!(escape("a\tb\nc\u{7f}"), "a^Ib^Jc^?");
+    }
+}
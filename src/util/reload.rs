@@ -0,0 +1,165 @@
+use crate::errors::*;
+use crate::util::{self, diff::{self, Edit}};
+use scribe::Buffer;
+use scribe::buffer::Position;
+use std::fs;
+
+// Content with more lines than this (before and after, combined) is replaced
+// wholesale, since diffing it could stall the editor.
+const MAX_DIFFED_LINES: usize = 20_000;
+
+// A run of consecutive changed lines: the index of its first original line,
+// the original lines it removes, and the content it inserts in their place.
+struct Hunk<'a> {
+    line: usize,
+    deleted: Vec<&'a str>,
+    inserted: String,
+}
+
+/// Brings a buffer in line with its file on disk by applying only the lines
+/// that differ (see `replace_content`), rather than replacing its content
+/// wholesale, so that the reload can be undone.
+///
+/// The file is only read: its buffer's history still differs from what was
+/// saved afterwards, so callers mark the buffer as matching its file with
+/// `View::record_saved_content`.
+pub fn reload(buffer: &mut Buffer) -> Result<()> {
+    let path = buffer.path.clone().ok_or("Buffer has no path to reload from")?;
+    let content = fs::read_to_string(&path)
+        .chain_err(|| format!("Couldn't read {}", path.display()))?;
+    if content != buffer.data() {
+        replace_content(buffer, &content);
+    }
+
+    Ok(())
+}
+
+/// Replaces a buffer's content by applying only the lines that differ as
+/// buffer operations, grouped so that a single undo reverts them. The cursor
+/// is kept on the same line, adjusted for lines added or removed above it.
+/// Large content is replaced wholesale (still as a single undoable group).
+pub fn replace_content(buffer: &mut Buffer, content: &str) {
+    let data = buffer.data();
+
+    // Lines keep their endings, so that edits reproduce them exactly.
+    let original: Vec<&str> = data.split_inclusive('\n').collect();
+    let modified: Vec<&str> = content.split_inclusive('\n').collect();
+    let cursor = *buffer.cursor;
+    let (edits, cursor_line) = if original.len() + modified.len() > MAX_DIFFED_LINES {
+        let mut edits: Vec<Edit> = original.iter().map(|line| Edit::Delete(line)).collect();
+        edits.extend(modified.iter().map(|line| Edit::Insert(line)));
+
+        (edits, cursor.line.min(content.matches('\n').count()))
+    } else {
+        let edits = diff::line_edits(&original, &modified);
+        let cursor_line = diff::new_line_index(&edits, cursor.line);
+
+        (edits, cursor_line)
+    };
+
+    // Hunks are applied last to first, so that earlier line indices stay valid.
+    buffer.start_operation_group();
+    for hunk in hunks(&edits).iter().rev() {
+        util::replace_lines(buffer, hunk.line, &hunk.deleted, &hunk.inserted);
+    }
+    buffer.end_operation_group();
+
+    // Keep the cursor's offset if the line is still long enough.
+    let position = Position { line: cursor_line, offset: cursor.offset };
+    if !buffer.cursor.move_to(position) {
+        buffer.cursor.move_to(Position { line: cursor_line, offset: 0 });
+        buffer.cursor.move_to_end_of_line();
+    }
+}
+
+fn hunks<'a>(edits: &[Edit<'a>]) -> Vec<Hunk<'a>> {
+    let mut hunks: Vec<Hunk> = Vec::new();
+    let mut line = 0;
+    let mut changing = false;
+
+    for edit in edits {
+        if let Edit::Equal(_) = *edit {
+            line += 1;
+            changing = false;
+            continue;
+        }
+        if !changing {
+            hunks.push(Hunk { line, deleted: Vec::new(), inserted: String::new() });
+            changing = true;
+        }
+        let hunk = hunks.last_mut().unwrap();
+        match *edit {
+            Edit::Delete(content) => {
+                hunk.deleted.push(content);
+                line += 1;
+            },
+            Edit::Insert(content) => hunk.inserted.push_str(content),
+            Edit::Equal(_) => (),
+        }
+    }
+
+    hunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{reload, MAX_DIFFED_LINES};
+    use scribe::Buffer;
+    use scribe::buffer::Position;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn set_up_buffer(name: &str, content: &str) -> (PathBuf, Buffer) {
+        let path = Path::new(env!("OUT_DIR")).join(name);
+        fs::write(&path, content).unwrap();
+
+        (path.clone(), Buffer::from_file(&path).unwrap())
+    }
+
+    #[test]
+    fn reload_applies_changes_preserving_the_cursor_and_undo() {
+        let (path, mut buffer) = set_up_buffer("reload_diff", "amp\neditor\nrust\ntext\n");
+        buffer.cursor.move_to(Position { line: 2, offset: 2 });
+        fs::write(&path, "header\namp\ntext editor\nrust\n").unwrap();
+
+        reload(&mut buffer).unwrap();
+        assert_eq!(buffer.data(), "header\namp\ntext editor\nrust\n");
+        assert_eq!(*buffer.cursor, Position { line: 3, offset: 2 });
+        assert_eq!(fs::read_to_string(&path).unwrap(), "header\namp\ntext editor\nrust\n");
+
+        buffer.undo();
+        assert_eq!(buffer.data(), "amp\neditor\nrust\ntext\n");
+    }
+
+    #[test]
+    fn reload_replaces_large_content_wholesale() {
+        let original: String = (0..MAX_DIFFED_LINES).map(|line| format!("{}\n", line)).collect();
+        let modified: String = (0..MAX_DIFFED_LINES).map(|line| format!("changed {}\n", line)).collect();
+        let (path, mut buffer) = set_up_buffer("reload_wholesale", &original);
+        buffer.cursor.move_to(Position { line: 100, offset: 2 });
+        fs::write(&path, &modified).unwrap();
+
+        reload(&mut buffer).unwrap();
+        assert_eq!(buffer.data(), modified);
+        assert_eq!(*buffer.cursor, Position { line: 100, offset: 2 });
+
+        buffer.undo();
+        assert_eq!(buffer.data(), original);
+    }
+
+    #[test]
+    fn reload_handles_missing_trailing_newlines() {
+        for &(original, modified) in &[
+            ("amp", "editor"),
+            ("amp\neditor", "amp\neditor\n"),
+            ("amp\n", "amp\neditor"),
+            ("amp\neditor\n", ""),
+        ] {
+            let (path, mut buffer) = set_up_buffer("reload_newlines", original);
+            fs::write(&path, modified).unwrap();
+
+            reload(&mut buffer).unwrap();
+            assert_eq!(buffer.data(), modified);
+        }
+    }
+}
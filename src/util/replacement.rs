@@ -0,0 +1,138 @@
+use regex::Captures;
+use std::iter::Peekable;
+use std::str::Chars;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Case {
+    Upper,
+    Lower,
+}
+
+/// Expands a replacement template using a regular expression match.
+///
+/// Supports the following sequences:
+///
+/// * `$1`, `$2`, etc. insert the corresponding capture group (`$0` being
+///   the entire match), while `${10}` and `${name}` are also supported.
+/// * `$$` inserts a literal dollar sign.
+/// * `\u` and `\l` uppercase/lowercase the following character.
+/// * `\U` and `\L` uppercase/lowercase everything up to a `\E`.
+/// * `\\` inserts a literal backslash.
+///
+/// References to non-existent capture groups are replaced with nothing.
+pub fn expand(template: &str, captures: &Captures) -> String {
+    let mut expansion = Expansion { content: String::new(), case: None, next_case: None };
+    let mut chars = template.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '$' => match chars.peek().cloned() {
+                Some('$') => {
+                    chars.next();
+                    expansion.push('$');
+                }
+                Some('{') => {
+                    chars.next();
+                    let reference: String = chars.by_ref().take_while(|&c| c != '}').collect();
+                    expansion.push_str(capture(captures, &reference));
+                }
+                Some(c) if c.is_ascii_digit() => {
+                    let reference = take_digits(&mut chars);
+                    expansion.push_str(capture(captures, &reference));
+                }
+                _ => expansion.push('$'),
+            },
+            '\\' => match chars.peek().cloned() {
+                Some('u') => { chars.next(); expansion.next_case = Some(Case::Upper); }
+                Some('l') => { chars.next(); expansion.next_case = Some(Case::Lower); }
+                Some('U') => { chars.next(); expansion.case = Some(Case::Upper); }
+                Some('L') => { chars.next(); expansion.case = Some(Case::Lower); }
+                Some('E') => { chars.next(); expansion.case = None; }
+                Some('\\') => { chars.next(); expansion.push('\\'); }
+                _ => expansion.push('\\'),
+            },
+            c => expansion.push(c),
+        }
+    }
+
+    expansion.content
+}
+
+/// Looks up a capture group by index or name.
+fn capture<'t>(captures: &Captures<'t>, reference: &str) -> &'t str {
+    let value = match reference.parse::<usize>() {
+        Ok(index) => captures.at(index),
+        Err(_) => captures.name(reference),
+    };
+
+    value.unwrap_or("")
+}
+
+fn take_digits(chars: &mut Peekable<Chars>) -> String {
+    let mut digits = String::new();
+    while let Some(&c) = chars.peek() {
+        if !c.is_ascii_digit() {
+            break;
+        }
+        digits.push(c);
+        chars.next();
+    }
+
+    digits
+}
+
+/// Accumulates expanded content, applying any active case modifiers.
+struct Expansion {
+    content: String,
+    case: Option<Case>,
+    next_case: Option<Case>,
+}
+
+impl Expansion {
+    fn push(&mut self, c: char) {
+        // A one-off modifier takes precedence over a persistent one.
+        match self.next_case.take().or(self.case) {
+            Some(Case::Upper) => self.content.extend(c.to_uppercase()),
+            Some(Case::Lower) => self.content.extend(c.to_lowercase()),
+            None => self.content.push(c),
+        }
+    }
+
+    fn push_str(&mut self, value: &str) {
+        for c in value.chars() {
+            self.push(c);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::expand;
+    use regex::Regex;
+
+    fn replace(pattern: &str, content: &str, template: &str) -> String {
+        let regex = Regex::new(pattern).unwrap();
+        let captures = regex.captures(content).unwrap();
+
+        expand(template, &captures)
+    }
+
+    #[test]
+    fn expand_inserts_capture_groups() {
+        assert_eq!(replace(r"(\w+) (\w+)", "amp editor", "$2 $1 ($0)"), "editor amp (amp editor)");
+        assert_eq!(replace(r"(?P<key>\w+)=(\d+)", "size=12", "${key}: ${2}px"), "size: 12px");
+    }
+
+    #[test]
+    fn expand_handles_literal_and_missing_references() {
+        assert_eq!(replace(r"(\d+)", "42", "$$$1 $3 \\\\ $ \\n"), "$42  \\ $ \\n");
+    }
+
+    #[test]
+    fn expand_applies_case_modifiers() {
+        assert_eq!(replace(r"(\w+)_(\w+)", "snake_case", "$1\\u$2"), "snakeCase");
+        assert_eq!(replace(r"(\w+)", "Amp", "\\l$1"), "amp");
+        assert_eq!(replace(r"(\w+) (\w+)", "amp editor", "\\U$1\\E $2"), "AMP editor");
+        assert_eq!(replace(r"(\w+)", "EDITOR", "\\L\\u$1"), "Editor");
+    }
+}
@@ -1,3 +1,4 @@
+use std::cmp::Ordering;
 use std::ops::Deref;
 
 /// A simple decorator around a Vec that allows a single element to be selected.
@@ -24,6 +25,11 @@ impl<T> SelectableVec<T> {
         self.set.get(self.selected_index)
     }
 
+    /// Selects the element at the index, or the last one if it's out of bounds.
+    pub fn select(&mut self, index: usize) {
+        self.selected_index = index.min(self.set.len().saturating_sub(1));
+    }
+
     pub fn select_previous(&mut self) {
         if self.selected_index > 0 {
             self.selected_index -= 1;
@@ -39,6 +45,18 @@ impl<T> SelectableVec<T> {
             self.selected_index = 0;
         }
     }
+
+    /// Reorders the set using the comparator, keeping the selected element
+    /// selected. The sort is stable, so equal elements keep their order.
+    pub fn sort_by<F>(&mut self, mut compare: F) where F: FnMut(&T, &T) -> Ordering {
+        let mut indexed: Vec<(usize, T)> = self.set.drain(..).enumerate().collect();
+        indexed.sort_by(|(_, a), (_, b)| compare(a, b));
+        self.selected_index = indexed
+            .iter()
+            .position(|&(index, _)| index == self.selected_index)
+            .unwrap_or(0);
+        self.set = indexed.into_iter().map(|(_, element)| element).collect();
+    }
 }
 
 impl<T> Deref for SelectableVec<T> {
@@ -80,4 +98,13 @@ mod tests {
         selectable_vec.select_previous();
         assert_eq!(selectable_vec.selection(), Some(&1));
     }
+
+    #[test]
+    fn sort_by_reorders_elements_and_keeps_the_selection() {
+        let mut selectable_vec: SelectableVec<usize> = SelectableVec::new(vec![2, 0, 1]);
+        selectable_vec.select_next();
+        selectable_vec.sort_by(|a, b| a.cmp(b));
+        assert_eq!(*selectable_vec, vec![0, 1, 2]);
+        assert_eq!(selectable_vec.selection(), Some(&0));
+    }
 }
@@ -0,0 +1,139 @@
+use crate::errors::*;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// The tags file read from the workspace root, as generated by ctags.
+pub const FILE_NAME: &str = "tags";
+
+/// A definition listed in a tags file.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Tag {
+    pub name: String,
+
+    /// The defining file, as written in the tags file
+    /// (usually relative to the workspace root).
+    pub path: PathBuf,
+    address: Address,
+}
+
+/// Where a tag's definition is found in its file.
+#[derive(Clone, Debug, PartialEq)]
+enum Address {
+    /// A zero-based line number.
+    Line(usize),
+
+    /// The defining line's content, when anchored to its start and
+    /// end, or a prefix of it, when only anchored to its start.
+    Pattern { content: String, whole_line: bool },
+}
+
+impl Tag {
+    /// The (zero-based) line defining the tag in its file's content. Patterns
+    /// are matched against every line, since they outlive edits better than
+    /// line numbers; when a pattern no longer matches, there's no line.
+    pub fn line(&self, content: &str) -> Option<usize> {
+        match self.address {
+            Address::Line(line) => Some(line),
+            Address::Pattern { content: ref pattern, whole_line } => {
+                content.lines().position(|line| {
+                    if whole_line {
+                        line == pattern
+                    } else {
+                        line.starts_with(pattern.as_str())
+                    }
+                })
+            }
+        }
+    }
+}
+
+/// Reads the tags defined in the workspace's tags file.
+pub fn load(workspace: &Path) -> Result<Vec<Tag>> {
+    let content = fs::read_to_string(workspace.join(FILE_NAME))
+        .chain_err(|| format!("Couldn't read a {} file from the workspace (generate one with ctags -R)", FILE_NAME))?;
+
+    Ok(parse(&content))
+}
+
+/// Parses tags, one per line, in the "name<tab>file<tab>address" format, with any
+/// extension fields following the address (after a `;"` separator). Metadata
+/// (lines starting with "!_TAG_") and lines that can't be read are skipped.
+pub fn parse(content: &str) -> Vec<Tag> {
+    content
+        .lines()
+        .filter(|line| !line.starts_with("!_TAG_"))
+        .filter_map(parse_line)
+        .collect()
+}
+
+fn parse_line(line: &str) -> Option<Tag> {
+    let mut fields = line.splitn(3, '\t');
+    let name = fields.next().filter(|name| !name.is_empty())?;
+    let path = fields.next().filter(|path| !path.is_empty())?;
+    let address = parse_address(fields.next()?)?;
+
+    Some(Tag {
+        name: name.to_string(),
+        path: PathBuf::from(path),
+        address,
+    })
+}
+
+fn parse_address(field: &str) -> Option<Address> {
+    let delimiter = match field.chars().next()? {
+        '/' => '/',
+        '?' => '?',
+        _ => {
+            let digits: String = field.chars().take_while(|c| c.is_ascii_digit()).collect();
+
+            return digits.parse::<usize>().ok().filter(|&line| line > 0).map(|line| Address::Line(line - 1));
+        }
+    };
+
+    // Find the closing delimiter, skipping escaped characters.
+    let mut pattern = String::new();
+    let mut characters = field[1..].chars();
+    loop {
+        match characters.next()? {
+            '\\' => pattern.push(characters.next()?),
+            c if c == delimiter => break,
+            c => pattern.push(c),
+        }
+    }
+
+    // Patterns are literal, other than their anchors.
+    let pattern = pattern.strip_prefix('^').unwrap_or(&pattern);
+    let (content, whole_line) = match pattern.strip_suffix('$') {
+        Some(content) => (content, true),
+        None => (pattern, false),
+    };
+
+    Some(Address::Pattern { content: content.to_string(), whole_line })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse;
+    use std::path::PathBuf;
+
+    #[test]
+    fn parse_reads_line_and_pattern_addresses_skipping_metadata() {
+        let tags = parse(
+            "!_TAG_FILE_FORMAT\t2\t/extended format/\n\
+             Application\tsrc/models/application/mod.rs\t/^pub struct Application {$/;\"\ts\n\
+             main\tsrc/main.rs\t12;\"\tf\n\
+             path\tsrc/util.rs\t/^fn path() -> \\/tmp {/;\"\tf\n\
+             malformed line\n"
+        );
+
+        let names: Vec<&str> = tags.iter().map(|tag| tag.name.as_str()).collect();
+        assert_eq!(names, vec!["Application", "main", "path"]);
+        assert_eq!(tags[0].path, PathBuf::from("src/models/application/mod.rs"));
+
+        let content = "use std;\n\npub struct Application {\n}\n";
+        assert_eq!(tags[0].line(content), Some(2));
+        assert_eq!(tags[0].line("pub struct Application {}"), None);
+        assert_eq!(tags[1].line(content), Some(11));
+        assert_eq!(tags[2].line("\nfn path() -> /tmp {}\n"), Some(1));
+    }
+}
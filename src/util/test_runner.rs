@@ -0,0 +1,190 @@
+use regex::Regex;
+use std::collections::HashMap;
+use crate::view::{Annotation, AnnotationPosition, AnnotationProvider, Colors, Style};
+
+// Results are stale once the code they describe has changed.
+pub const ANNOTATION_PROVIDER: AnnotationProvider = AnnotationProvider {
+    name: "test",
+    priority: 15,
+    invalidated_by_edits: true,
+};
+
+/// A test function definition found in a buffer.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TestFunction {
+    pub line: usize,
+    pub name: String,
+}
+
+/// Finds test function definitions, recognizing Rust's `#[test]` attribute,
+/// as well as Python (`def test_*`) and Go (`func Test*`) naming conventions.
+pub fn test_functions(data: &str) -> Vec<TestFunction> {
+    lazy_static! {
+        static ref RUST_FN: Regex = Regex::new(r"^\s*(?:pub\s+)?fn\s+(\w+)").unwrap();
+        static ref NAMED_TEST: Regex =
+            Regex::new(r"^\s*(?:def\s+(test\w*)|func\s+(Test\w*))").unwrap();
+    }
+
+    let mut tests = Vec::new();
+    let mut attribute_seen = false;
+    for (line, content) in data.lines().enumerate() {
+        if content.trim_start().starts_with("#[test]") {
+            attribute_seen = true;
+            continue;
+        }
+
+        if attribute_seen {
+            if let Some(name) = RUST_FN.captures(content).and_then(|c| c.at(1)) {
+                tests.push(TestFunction { line, name: name.to_string() });
+                attribute_seen = false;
+            } else if !content.trim_start().starts_with("#[") {
+                // Only other attributes can sit between #[test] and the function.
+                attribute_seen = false;
+            }
+        } else if let Some(captures) = NAMED_TEST.captures(content) {
+            if let Some(name) = captures.at(1).or_else(|| captures.at(2)) {
+                tests.push(TestFunction { line, name: name.to_string() });
+            }
+        }
+    }
+
+    tests
+}
+
+/// Finds the test function enclosing (or immediately preceding) the specified line.
+pub fn nearest_test(tests: &[TestFunction], line: usize) -> Option<&TestFunction> {
+    tests.iter().rev().find(|test| test.line <= line)
+}
+
+/// Extracts per-test outcomes from test runner output, keyed by function name.
+/// Understands the output formats of `cargo test`, `pytest -v`, and `go test -v`.
+pub fn parse_results(output: &str) -> HashMap<String, bool> {
+    lazy_static! {
+        static ref CARGO: Regex = Regex::new(r"^test (\S+) \.\.\. (ok|FAILED)").unwrap();
+        static ref PYTEST: Regex = Regex::new(r"::(\w+) (PASSED|FAILED)").unwrap();
+        static ref GO: Regex = Regex::new(r"^\s*--- (PASS|FAIL): (\w+)").unwrap();
+    }
+
+    let mut results = HashMap::new();
+    for line in output.lines() {
+        let outcome = if let Some(captures) = CARGO.captures(line) {
+            captures.at(1).map(|path| (path.rsplit("::").next().unwrap_or(path), captures.at(2) == Some("ok")))
+        } else if let Some(captures) = PYTEST.captures(line) {
+            captures.at(1).map(|name| (name, captures.at(2) == Some("PASSED")))
+        } else if let Some(captures) = GO.captures(line) {
+            captures.at(2).map(|name| (name, captures.at(1) == Some("PASS")))
+        } else {
+            None
+        };
+
+        if let Some((name, passed)) = outcome {
+            results.insert(name.to_string(), passed);
+        }
+    }
+
+    results
+}
+
+/// Builds annotations indicating that the specified tests are running.
+pub fn pending_annotations(tests: &[TestFunction]) -> Vec<Annotation> {
+    tests.iter().map(|test| Annotation {
+        line: test.line,
+        content: String::from("running"),
+        style: Style::Italic,
+        colors: Colors::Focused,
+        position: AnnotationPosition::EndOfLine,
+    }).collect()
+}
+
+/// Builds pass/fail annotations for tests with a known outcome.
+pub fn result_annotations(tests: &[TestFunction], results: &HashMap<String, bool>) -> Vec<Annotation> {
+    tests.iter().filter_map(|test| {
+        results.get(&test.name).map(|passed| {
+            if *passed {
+                Annotation {
+                    line: test.line,
+                    content: String::from(" passed "),
+                    style: Style::Default,
+                    colors: Colors::Insert,
+                    position: AnnotationPosition::EndOfLine,
+                }
+            } else {
+                Annotation {
+                    line: test.line,
+                    content: String::from(" failed "),
+                    style: Style::Bold,
+                    colors: Colors::Warning,
+                    position: AnnotationPosition::EndOfLine,
+                }
+            }
+        })
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_functions_finds_rust_tests() {
+        let data = "fn helper() {}\n\n#[test]\nfn first() {}\n\n#[test]\n#[should_panic]\npub fn second() {}\n";
+
+        assert_eq!(test_functions(data), vec![
+            TestFunction { line: 3, name: String::from("first") },
+            TestFunction { line: 7, name: String::from("second") },
+        ]);
+    }
+
+    #[test]
+    fn test_functions_finds_python_and_go_tests() {
+        let data = "def helper():\n    pass\ndef test_amp():\n    pass\nfunc TestAmp(t *testing.T) {}\n";
+
+        assert_eq!(test_functions(data), vec![
+            TestFunction { line: 2, name: String::from("test_amp") },
+            TestFunction { line: 4, name: String::from("TestAmp") },
+        ]);
+    }
+
+    #[test]
+    fn nearest_test_returns_closest_preceding_test() {
+        let tests = vec![
+            TestFunction { line: 3, name: String::from("first") },
+            TestFunction { line: 7, name: String::from("second") },
+        ];
+
+        assert_eq!(nearest_test(&tests, 1), None);
+        assert_eq!(nearest_test(&tests, 5).map(|t| t.name.as_str()), Some("first"));
+        assert_eq!(nearest_test(&tests, 7).map(|t| t.name.as_str()), Some("second"));
+    }
+
+    #[test]
+    fn parse_results_understands_cargo_output() {
+        let results = parse_results("running 2 tests\ntest util::tests::first ... ok\ntest util::tests::second ... FAILED\n");
+
+        assert_eq!(results.get("first"), Some(&true));
+        assert_eq!(results.get("second"), Some(&false));
+    }
+
+    #[test]
+    fn parse_results_understands_pytest_and_go_output() {
+        let results = parse_results("test_amp.py::test_amp PASSED\n--- FAIL: TestAmp (0.00s)\n");
+
+        assert_eq!(results.get("test_amp"), Some(&true));
+        assert_eq!(results.get("TestAmp"), Some(&false));
+    }
+
+    #[test]
+    fn result_annotations_skips_tests_without_outcomes() {
+        let tests = vec![
+            TestFunction { line: 3, name: String::from("first") },
+            TestFunction { line: 7, name: String::from("second") },
+        ];
+        let mut results = HashMap::new();
+        results.insert(String::from("second"), false);
+        let annotations = result_annotations(&tests, &results);
+
+        assert_eq!(annotations.len(), 1);
+        assert_eq!(annotations[0].line, 7);
+        assert_eq!(annotations[0].colors, Colors::Warning);
+    }
+}
@@ -0,0 +1,88 @@
+use std::env;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+/// Whether a program can be run, either by locating it in one of the
+/// PATH's directories or, if it's a path itself, checking that it exists.
+pub fn available(program: &str) -> bool {
+    if program.contains('/') {
+        return executable(Path::new(program));
+    }
+
+    env::var_os("PATH")
+        .map(|paths| env::split_paths(&paths).any(|dir| executable(&dir.join(program))))
+        .unwrap_or(false)
+}
+
+/// The program a shell command runs (i.e. its first word).
+pub fn program(command: &str) -> Option<&str> {
+    command.split_whitespace().next()
+}
+
+/// Quotes the value so that the shell reads it as a single
+/// word, regardless of any spaces or special characters in it.
+pub fn shell_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+/// Whether the program a shell command runs is available, asking
+/// the shell, so that builtins (e.g. "cd") are accounted for.
+pub fn shell_command_available(command: &str) -> bool {
+    let program = match program(command) {
+        Some(program) => program,
+        None => return false,
+    };
+
+    Command::new("sh")
+        .args(["-c", "command -v \"$1\"", "sh", program])
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+#[cfg(unix)]
+fn executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+
+    path.metadata()
+        .map(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+#[cfg(not(unix))]
+fn executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{available, program, shell_command_available, shell_quote};
+
+    #[test]
+    fn available_finds_programs_in_the_path() {
+        assert!(available("sh"));
+        assert!(available("/bin/sh"));
+        assert!(!available("amp-nonexistent-tool"));
+        assert!(!available("/bin"));
+    }
+
+    #[test]
+    fn shell_quote_keeps_values_as_single_words() {
+        assert_eq!(shell_quote("tests/a b.rs"), "'tests/a b.rs'");
+        assert_eq!(shell_quote("$(rm -rf ~); it's"), "'$(rm -rf ~); it'\\''s'");
+    }
+
+    #[test]
+    fn program_returns_the_first_word_of_a_command() {
+        assert_eq!(program("  cargo test {test}"), Some("cargo"));
+        assert_eq!(program(""), None);
+    }
+
+    #[test]
+    fn shell_command_available_accounts_for_builtins() {
+        assert!(shell_command_available("cd src && make"));
+        assert!(!shell_command_available("amp-nonexistent-tool --all"));
+    }
+}
@@ -0,0 +1,291 @@
+use crate::errors::*;
+use crate::util::json;
+use fragment::matching::AsStr;
+use std::fmt;
+
+const BASE64_ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// A conversion that can be applied to selected text.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Transform {
+    Base64Encode,
+    Base64Decode,
+    UrlEncode,
+    UrlDecode,
+    JsonEscape,
+    JsonUnescape,
+    HexDump,
+}
+
+impl Transform {
+    pub fn all() -> Vec<Transform> {
+        vec![
+            Transform::Base64Encode,
+            Transform::Base64Decode,
+            Transform::UrlEncode,
+            Transform::UrlDecode,
+            Transform::JsonEscape,
+            Transform::JsonUnescape,
+            Transform::HexDump,
+        ]
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Transform::Base64Encode => "base64 encode",
+            Transform::Base64Decode => "base64 decode",
+            Transform::UrlEncode => "url encode",
+            Transform::UrlDecode => "url decode",
+            Transform::JsonEscape => "json escape",
+            Transform::JsonUnescape => "json unescape",
+            Transform::HexDump => "hex dump",
+        }
+    }
+
+    pub fn apply(self, content: &str) -> Result<String> {
+        match self {
+            Transform::Base64Encode => Ok(base64_encode(content.as_bytes())),
+            Transform::Base64Decode => utf8(base64_decode(content)?),
+            Transform::UrlEncode => Ok(url_encode(content)),
+            Transform::UrlDecode => utf8(url_decode(content)?),
+            Transform::JsonEscape => json_escape(content),
+            Transform::JsonUnescape => json_unescape(content),
+            Transform::HexDump => Ok(hex_dump(content.as_bytes())),
+        }
+    }
+}
+
+impl fmt::Display for Transform {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl AsStr for Transform {
+    fn as_str(&self) -> &str {
+        self.name()
+    }
+}
+
+fn utf8(data: Vec<u8>) -> Result<String> {
+    String::from_utf8(data).chain_err(|| "Decoded content isn't valid UTF-8")
+}
+
+pub fn base64_encode(data: &[u8]) -> String {
+    let mut encoded = String::new();
+    for chunk in data.chunks(3) {
+        let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+        let group = (u32::from(bytes[0]) << 16) | (u32::from(bytes[1]) << 8) | u32::from(bytes[2]);
+
+        for index in 0..4 {
+            if index <= chunk.len() {
+                let sextet = (group >> (18 - index * 6)) & 0x3f;
+                encoded.push(BASE64_ALPHABET[sextet as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
+    }
+
+    encoded
+}
+
+pub fn base64_decode(content: &str) -> Result<Vec<u8>> {
+    let mut decoded = Vec::new();
+    let mut group = 0u32;
+    let mut bits = 0;
+
+    // Whitespace is commonly used to wrap long values; ignore it.
+    for c in content.chars().filter(|c| !c.is_whitespace()) {
+        if c == '=' {
+            break;
+        }
+
+        let value = BASE64_ALPHABET
+            .iter()
+            .position(|&b| b as char == c)
+            .ok_or_else(|| format!("Invalid base64 character \"{}\"", c))?;
+        group = (group << 6) | value as u32;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            decoded.push((group >> bits) as u8);
+            group &= (1 << bits) - 1;
+        }
+    }
+
+    Ok(decoded)
+}
+
+fn url_encode(content: &str) -> String {
+    let mut encoded = String::new();
+    for byte in content.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    encoded
+}
+
+fn url_decode(content: &str) -> Result<Vec<u8>> {
+    let bytes = content.as_bytes();
+    let mut decoded = Vec::new();
+    let mut index = 0;
+
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let byte = content
+                .get(index + 1..index + 3)
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok())
+                .ok_or("Invalid percent-encoded sequence")?;
+            decoded.push(byte);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+
+    Ok(decoded)
+}
+
+fn json_escape(content: &str) -> Result<String> {
+    let encoded = json::encode(&json::string(content));
+
+    // Drop the surrounding quotes.
+    Ok(encoded[1..encoded.len() - 1].to_string())
+}
+
+// Decodes the escape sequences described in RFC 8259 (section 7). Other
+// characters, including literal newlines, are kept as they are.
+fn json_unescape(content: &str) -> Result<String> {
+    let mut decoded = String::new();
+    let mut chars = content.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            decoded.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('"') => decoded.push('"'),
+            Some('\\') => decoded.push('\\'),
+            Some('/') => decoded.push('/'),
+            Some('b') => decoded.push('\u{8}'),
+            Some('f') => decoded.push('\u{c}'),
+            Some('n') => decoded.push('\n'),
+            Some('r') => decoded.push('\r'),
+            Some('t') => decoded.push('\t'),
+            Some('u') => {
+                let unit = json_code_unit(&mut chars)?;
+                let code_point = match unit {
+                    0xD800..=0xDBFF => {
+                        // Characters outside the BMP are escaped as a UTF-16 surrogate pair.
+                        if chars.next() != Some('\\') || chars.next() != Some('u') {
+                            bail!("Unpaired surrogate \"\\u{:04x}\"", unit);
+                        }
+                        let low = json_code_unit(&mut chars)?;
+                        if !(0xDC00..=0xDFFF).contains(&low) {
+                            bail!("Unpaired surrogate \"\\u{:04x}\"", unit);
+                        }
+
+                        0x10000 + ((unit - 0xD800) << 10) + (low - 0xDC00)
+                    }
+                    0xDC00..=0xDFFF => bail!("Unpaired surrogate \"\\u{:04x}\"", unit),
+                    unit => unit,
+                };
+                decoded.push(std::char::from_u32(code_point).ok_or("Invalid unicode escape")?);
+            }
+            Some(c) => bail!("Invalid JSON escape sequence \"\\{}\"", c),
+            None => bail!("Content ends with an incomplete escape sequence"),
+        }
+    }
+
+    Ok(decoded)
+}
+
+// Reads the four hex digits of a \u escape sequence.
+fn json_code_unit<I: Iterator<Item = char>>(chars: &mut I) -> Result<u32> {
+    let digits: String = chars.take(4).collect();
+    if digits.len() != 4 || !digits.chars().all(|c| c.is_ascii_hexdigit()) {
+        bail!("Invalid unicode escape \"\\u{}\"", digits);
+    }
+
+    Ok(u32::from_str_radix(&digits, 16).unwrap())
+}
+
+fn hex_dump(data: &[u8]) -> String {
+    data.chunks(16).enumerate().map(|(index, chunk)| {
+        let hex = chunk
+            .chunks(2)
+            .map(|pair| pair.iter().map(|byte| format!("{:02x}", byte)).collect::<String>())
+            .collect::<Vec<_>>()
+            .join(" ");
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| if byte.is_ascii_graphic() || byte == b' ' { byte as char } else { '.' })
+            .collect();
+
+        format!("{:08x}: {:<40} {}", index * 16, hex, ascii)
+    }).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Transform;
+
+    #[test]
+    fn base64_transforms_round_trip() {
+        for (plain, encoded) in &[("", ""), ("a", "YQ=="), ("am", "YW0="), ("amp", "YW1w"), ("amp editor", "YW1wIGVkaXRvcg==")] {
+            assert_eq!(Transform::Base64Encode.apply(plain).unwrap(), *encoded);
+            assert_eq!(Transform::Base64Decode.apply(encoded).unwrap(), *plain);
+        }
+    }
+
+    #[test]
+    fn base64_decode_rejects_invalid_characters() {
+        assert!(Transform::Base64Decode.apply("YW1w!").is_err());
+    }
+
+    #[test]
+    fn url_transforms_round_trip() {
+        let encoded = Transform::UrlEncode.apply("a b/c?d=é").unwrap();
+
+        assert_eq!(encoded, "a%20b%2Fc%3Fd%3D%C3%A9");
+        assert_eq!(Transform::UrlDecode.apply(&encoded).unwrap(), "a b/c?d=é");
+        assert!(Transform::UrlDecode.apply("%zz").is_err());
+    }
+
+    #[test]
+    fn json_transforms_round_trip() {
+        let escaped = Transform::JsonEscape.apply("say \"hi\"\n\tback\\slash").unwrap();
+
+        assert_eq!(escaped, "say \\\"hi\\\"\\n\\tback\\\\slash");
+        assert_eq!(Transform::JsonUnescape.apply(&escaped).unwrap(), "say \"hi\"\n\tback\\slash");
+    }
+
+    #[test]
+    fn json_unescape_follows_rfc_8259() {
+        assert_eq!(Transform::JsonUnescape.apply("a\\/b").unwrap(), "a/b");
+        assert_eq!(Transform::JsonUnescape.apply("\\u00e9 \\ud83d\\ude00").unwrap(), "é \u{1f600}");
+        assert_eq!(Transform::JsonUnescape.apply("line\none\\n").unwrap(), "line\none\n");
+        assert!(Transform::JsonUnescape.apply("\\x41").is_err());
+        assert!(Transform::JsonUnescape.apply("\\ud83d").is_err());
+        assert!(Transform::JsonUnescape.apply("\\u12").is_err());
+    }
+
+    #[test]
+    fn hex_dump_lists_offsets_bytes_and_characters() {
+        assert_eq!(
+            Transform::HexDump.apply("amp editor, by jmacdonald\n").unwrap(),
+            "00000000: 616d 7020 6564 6974 6f72 2c20 6279 206a  amp editor, by j\n\
+             00000010: 6d61 6364 6f6e 616c 640a                 macdonald."
+        );
+    }
+}
@@ -0,0 +1,140 @@
+use crate::view::{Colors, Style};
+
+/// Virtual content rendered alongside a buffer line. Annotations
+/// aren't part of the buffer's data, and so can't be edited or selected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Annotation {
+    pub line: usize,
+    pub content: String,
+    pub style: Style,
+    pub colors: Colors,
+    pub position: AnnotationPosition,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum AnnotationPosition {
+    /// A single-character sign, drawn between the line number and content.
+    Gutter,
+
+    /// Text drawn after the line's content.
+    EndOfLine,
+
+    /// Text drawn in a column to the left of the line number. The column is
+    /// as wide as the buffer's widest such annotation (e.g. git blame details).
+    Column,
+
+    /// Restyles the line's content, and the space after it. The content
+    /// is ignored; only the annotation's style and colors are used.
+    Line,
+
+    /// Restyles the line's content between two (grapheme) offsets, e.g.
+    /// underlining a diagnostic's range. The content is ignored; default
+    /// colors leave the content's syntax highlighting intact.
+    Range { start: usize, end: usize },
+}
+
+/// A subsystem (e.g. git or a language server) that annotates buffers.
+/// Each provider's annotations are replaced as a set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AnnotationProvider {
+    pub name: &'static str,
+
+    /// Where annotations compete for the same spot (e.g. a line's gutter
+    /// sign), those belonging to the highest-priority provider are drawn.
+    pub priority: u8,
+
+    /// Whether the provider's annotations on and after an edited line are
+    /// discarded as soon as the buffer changes, rather than being kept until
+    /// the provider replaces them. Suits providers whose annotations describe
+    /// content (e.g. diagnostics), rather than state tracked by line number.
+    pub invalidated_by_edits: bool,
+}
+
+/// A buffer's annotations, grouped by provider and ordered by priority.
+#[derive(Default)]
+pub struct Annotations {
+    providers: Vec<(AnnotationProvider, Vec<Annotation>)>,
+}
+
+impl Annotations {
+    /// Replaces the provider's annotations.
+    pub fn set(&mut self, provider: AnnotationProvider, annotations: Vec<Annotation>) {
+        self.clear(provider);
+
+        // Providers with equal priority are ordered by name, so that
+        // which of them wins doesn't depend on when they were set.
+        let index = self.providers
+            .iter()
+            .position(|(other, _)| (other.priority, other.name) < (provider.priority, provider.name))
+            .unwrap_or(self.providers.len());
+        self.providers.insert(index, (provider, annotations));
+    }
+
+    pub fn clear(&mut self, provider: AnnotationProvider) {
+        self.providers.retain(|(other, _)| other.name != provider.name);
+    }
+
+    /// Discards annotations on and after an edited line, for
+    /// providers whose annotations are invalidated by edits.
+    pub fn invalidate_from(&mut self, line: usize) {
+        for (provider, annotations) in self.providers.iter_mut() {
+            if provider.invalidated_by_edits {
+                annotations.retain(|annotation| annotation.line < line);
+            }
+        }
+    }
+
+    /// Every provider's annotations, highest priority first.
+    pub fn all(&self) -> Vec<Annotation> {
+        self.providers
+            .iter()
+            .flat_map(|(_, annotations)| annotations.iter().cloned())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Annotation, AnnotationPosition, AnnotationProvider, Annotations};
+    use crate::view::{Colors, Style};
+
+    fn provider(name: &'static str, priority: u8, invalidated_by_edits: bool) -> AnnotationProvider {
+        AnnotationProvider { name, priority, invalidated_by_edits }
+    }
+
+    fn sign(line: usize, content: &str) -> Annotation {
+        Annotation {
+            line,
+            content: content.to_string(),
+            style: Style::Default,
+            colors: Colors::Default,
+            position: AnnotationPosition::Gutter,
+        }
+    }
+
+    #[test]
+    fn all_orders_annotations_by_provider_priority() {
+        let mut annotations = Annotations::default();
+        annotations.set(provider("git", 10, false), vec![sign(0, "|")]);
+        annotations.set(provider("diagnostics", 20, true), vec![sign(0, "●")]);
+        annotations.set(provider("blame", 10, false), vec![sign(0, "b")]);
+        annotations.set(provider("git", 10, false), vec![sign(0, "_")]);
+
+        let contents: Vec<String> = annotations.all().into_iter().map(|a| a.content).collect();
+        assert_eq!(contents, vec!["●", "_", "b"]);
+
+        annotations.clear(provider("diagnostics", 20, true));
+        assert_eq!(annotations.all()[0].content, "_");
+    }
+
+    #[test]
+    fn invalidate_from_discards_annotations_from_providers_invalidated_by_edits() {
+        let mut annotations = Annotations::default();
+        annotations.set(provider("breakpoints", 30, false), vec![sign(1, "●"), sign(5, "●")]);
+        annotations.set(provider("diagnostics", 20, true), vec![sign(1, "E"), sign(5, "W")]);
+
+        annotations.invalidate_from(3);
+        let contents: Vec<String> = annotations.all().into_iter().map(|a| a.content).collect();
+        assert_eq!(contents, vec!["●", "●", "E"]);
+    }
+}
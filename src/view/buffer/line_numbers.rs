@@ -1,3 +1,4 @@
+use crate::models::application::LineNumberMode;
 use scribe::Buffer;
 use std::iter::Iterator;
 
@@ -5,18 +6,33 @@ pub const PADDING_WIDTH: usize = 2;
 
 pub struct LineNumbers {
     current_number: usize,
+    cursor_number: usize,
     buffer_line_count_width: usize,
+    mode: LineNumberMode,
 }
 
 impl LineNumbers {
     pub fn new(buffer: &Buffer, offset: Option<usize>) -> LineNumbers {
         LineNumbers{
             current_number: offset.unwrap_or(0),
-            buffer_line_count_width: buffer.line_count().to_string().len()
+            cursor_number: buffer.cursor.line + 1,
+            buffer_line_count_width: buffer.line_count().to_string().len(),
+            mode: LineNumberMode::Absolute,
         }
     }
 
+    /// Numbers lines relative to the cursor line, or hides
+    /// them, rather than numbering them from the first line.
+    pub fn with_mode(mut self, mode: LineNumberMode) -> LineNumbers {
+        self.mode = mode;
+        self
+    }
+
     pub fn width(&self) -> usize {
+        if self.mode == LineNumberMode::Hidden {
+            return 0;
+        }
+
         self.buffer_line_count_width + PADDING_WIDTH
     }
 }
@@ -26,10 +42,18 @@ impl Iterator for LineNumbers {
 
     fn next(&mut self) -> Option<String> {
         self.current_number += 1;
+        let distance = self.current_number.abs_diff(self.cursor_number);
+        let number = match self.mode {
+            LineNumberMode::Absolute => self.current_number,
+            LineNumberMode::Hybrid if distance == 0 => self.current_number,
+            LineNumberMode::Relative | LineNumberMode::Hybrid => distance,
+            LineNumberMode::Hidden => return Some(String::new()),
+        };
+
         Some(
             format!(
                 " {:>width$} ",
-                self.current_number,
+                number,
                 width = self.buffer_line_count_width
             )
         )
@@ -99,6 +123,35 @@ mod tests {
         assert_eq!(next_number, 2);
     }
 
+    #[test]
+    fn line_numbers_count_from_the_cursor_line_in_relative_modes() {
+        let mut buffer = Buffer::new();
+        for _ in 0..11 {
+            buffer.insert("\n");
+        }
+        buffer.cursor.move_to(scribe::buffer::Position{ line: 9, offset: 0 });
+
+        let relative: Vec<String> = LineNumbers::new(&buffer, Some(7))
+            .with_mode(LineNumberMode::Relative)
+            .take(4)
+            .collect();
+        assert_eq!(relative, vec!["  2 ", "  1 ", "  0 ", "  1 "]);
+
+        let hybrid: Vec<String> = LineNumbers::new(&buffer, Some(7))
+            .with_mode(LineNumberMode::Hybrid)
+            .take(4)
+            .collect();
+        assert_eq!(hybrid, vec!["  2 ", "  1 ", " 10 ", "  1 "]);
+    }
+
+    #[test]
+    fn hidden_line_numbers_take_up_no_space() {
+        let buffer = Buffer::new();
+        let mut line_numbers = LineNumbers::new(&buffer, None).with_mode(LineNumberMode::Hidden);
+        assert_eq!(line_numbers.width(), 0);
+        assert_eq!(line_numbers.next().unwrap(), "");
+    }
+
     #[test]
     fn line_numbers_are_left_padded_based_on_buffer_line_count_width() {
         let mut buffer = Buffer::new();
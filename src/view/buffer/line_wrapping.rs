@@ -0,0 +1,153 @@
+use crate::models::application::{LocalSettings, Preferences};
+use crate::view::buffer::placeholder;
+use std::path::PathBuf;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// How a buffer's long lines are displayed: wrapped onto following screen
+/// lines at the edge of their pane (or a narrower, configured column),
+/// or truncated. Positions are given as (row, column) pairs, relative to
+/// the start of the line's content on screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LineWrapping {
+    enabled: bool,
+    column: Option<usize>,
+    tab_width: usize,
+}
+
+impl Default for LineWrapping {
+    fn default() -> LineWrapping {
+        LineWrapping { enabled: true, column: None, tab_width: 2 }
+    }
+}
+
+impl LineWrapping {
+    pub fn new(preferences: &Preferences, settings: &LocalSettings, path: Option<&PathBuf>) -> LineWrapping {
+        LineWrapping {
+            enabled: settings.line_wrapping(preferences),
+            column: preferences.line_wrap_column(),
+            tab_width: settings.tab_width(preferences, path),
+        }
+    }
+
+    /// The width lines wrap at, given the width available to
+    /// their content, or None if they're truncated instead.
+    pub fn width(&self, content_width: usize) -> Option<usize> {
+        if !self.enabled {
+            return None;
+        }
+
+        Some(self.column.map_or(content_width, |column| column.min(content_width)).max(1))
+    }
+
+    /// The number of screen lines the line occupies.
+    pub fn row_count(&self, line: &str, content_width: usize) -> usize {
+        self.positions(line, content_width)
+            .iter()
+            .rev()
+            .nth(1)
+            .map_or(1, |&(row, _)| row + 1)
+    }
+
+    /// Where the line's (grapheme) offset is drawn.
+    pub fn position(&self, line: &str, offset: usize, content_width: usize) -> (usize, usize) {
+        let positions = self.positions(line, content_width);
+
+        positions.get(offset).or_else(|| positions.last()).cloned().unwrap_or((0, 0))
+    }
+
+    /// The offset drawn at (or closest to) the screen position,
+    /// staying on the specified row when it's shorter than the column.
+    pub fn offset(&self, line: &str, row: usize, column: usize, content_width: usize) -> usize {
+        let positions = self.positions(line, content_width);
+        let mut offsets = positions.iter().enumerate().filter(|(_, position)| position.0 == row);
+        let first = match offsets.next() {
+            Some((offset, _)) => offset,
+            None => return positions.len() - 1,
+        };
+
+        // Graphemes (e.g. tabs) can span several columns, so find
+        // the last one starting at or before the target column.
+        offsets
+            .take_while(|(_, position)| position.1 <= column)
+            .last()
+            .map_or(first, |(offset, _)| offset)
+    }
+
+    // The screen position of each of the line's graphemes, and of the offset
+    // after them (where the cursor rests at the end of the line). Graphemes
+    // that would start beyond the wrap width are moved to the next row, as the
+    // renderer does; tabs expand to the next tab stop, relative to the row,
+    // and placeholders (e.g. for control characters) span several columns,
+    // moving to the next row unless they'd start it.
+    fn positions(&self, line: &str, content_width: usize) -> Vec<(usize, usize)> {
+        let width = self.width(content_width);
+        let mut positions = Vec::new();
+        let (mut row, mut column) = (0, 0);
+
+        for grapheme in line.trim_end_matches('\n').graphemes(true) {
+            let grapheme_width = if grapheme == "\t" { 1 } else { placeholder::width(grapheme) };
+            if width.is_some_and(|width| column > 0 && column + grapheme_width > width) {
+                row += 1;
+                column = 0;
+            }
+            positions.push((row, column));
+
+            column = if grapheme == "\t" {
+                let tab_stop = (column / self.tab_width.max(1) + 1) * self.tab_width.max(1);
+                width.map_or(tab_stop, |width| tab_stop.min(width))
+            } else {
+                column + grapheme_width
+            };
+        }
+        positions.push((row, column));
+
+        positions
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineWrapping;
+
+    fn line_wrapping(enabled: bool, column: Option<usize>) -> LineWrapping {
+        LineWrapping { enabled, column, tab_width: 4 }
+    }
+
+    #[test]
+    fn width_wraps_at_the_configured_column_when_it_fits() {
+        assert_eq!(line_wrapping(true, None).width(80), Some(80));
+        assert_eq!(line_wrapping(true, Some(60)).width(80), Some(60));
+        assert_eq!(line_wrapping(true, Some(100)).width(80), Some(80));
+        assert_eq!(line_wrapping(false, Some(60)).width(80), None);
+    }
+
+    #[test]
+    fn positions_wrap_graphemes_and_expand_tabs() {
+        let wrapping = line_wrapping(true, None);
+
+        assert_eq!(wrapping.row_count("", 4), 1);
+        assert_eq!(wrapping.row_count("abcd", 4), 1);
+        assert_eq!(wrapping.row_count("abcde", 4), 2);
+        assert_eq!(wrapping.row_count("a\tbcdef\n", 4), 3);
+        assert_eq!(line_wrapping(false, None).row_count("abcde", 4), 1);
+
+        assert_eq!(wrapping.position("abcdefg", 5, 4), (1, 1));
+        assert_eq!(wrapping.position("abcd", 4, 4), (0, 4));
+        assert_eq!(wrapping.position("a\tb", 2, 8), (0, 4));
+        assert_eq!(wrapping.position("abc", 10, 4), (0, 3));
+        assert_eq!(wrapping.position("a\u{1b}b", 2, 8), (0, 7));
+        assert_eq!(wrapping.position("a\u{1b}b", 1, 6), (1, 0));
+        assert_eq!(wrapping.row_count("\u{1b}\u{1b}", 4), 2);
+    }
+
+    #[test]
+    fn offset_finds_the_grapheme_at_a_screen_position() {
+        let wrapping = line_wrapping(true, None);
+
+        assert_eq!(wrapping.offset("abcdefg", 1, 2, 4), 6);
+        assert_eq!(wrapping.offset("abcdefg", 1, 9, 4), 7);
+        assert_eq!(wrapping.offset("abcdefgh", 0, 9, 4), 3);
+        assert_eq!(wrapping.offset("a\tb", 0, 3, 8), 1);
+        assert_eq!(wrapping.offset("abc", 3, 0, 4), 3);
+    }
+}
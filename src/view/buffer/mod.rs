@@ -1,13 +1,21 @@
+mod annotation;
 mod renderer;
 mod render_cache;
 mod render_state;
 mod lexeme_mapper;
 mod line_numbers;
+mod line_wrapping;
+mod placeholder;
 mod scrollable_region;
+mod word_count;
 
+pub use self::annotation::{Annotation, AnnotationPosition, AnnotationProvider, Annotations};
 pub use self::renderer::BufferRenderer;
 pub use self::render_cache::RenderCache;
 pub use self::render_state::RenderState;
 pub use self::lexeme_mapper::{LexemeMapper, MappedLexeme};
 pub use self::line_numbers::LineNumbers;
+pub use self::line_wrapping::LineWrapping;
+pub use self::placeholder::sanitize;
 pub use self::scrollable_region::ScrollableRegion;
+pub use self::word_count::{word_count_summary, WordCount};
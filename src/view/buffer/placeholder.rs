@@ -0,0 +1,94 @@
+use std::borrow::Cow;
+use unicode_segmentation::UnicodeSegmentation;
+
+/// The scope placeholders are styled with, so that
+/// themes distinguish them from the surrounding content.
+pub const SCOPE: &str = "invalid";
+
+/// What's displayed in place of a grapheme that can't be printed as-is, if
+/// anything. Control characters (other than tabs and newlines) would be
+/// interpreted by the terminal, corrupting the display or worse, so they're
+/// shown as their code (e.g. "<0x1b>"). Replacement characters, which stand
+/// in for invalid UTF-8 read from files, are shown as themselves, but are
+/// still treated as placeholders, to be styled distinctly.
+pub fn placeholder(grapheme: &str) -> Option<String> {
+    if grapheme == "\u{FFFD}" {
+        return Some(grapheme.to_string());
+    }
+    if !grapheme.chars().any(is_unprintable) {
+        return None;
+    }
+
+    // Control characters end graphemes, save for carriage returns before
+    // newlines; the newline is left to be handled like any other.
+    Some(
+        grapheme
+            .chars()
+            .filter(|&c| c != '\n')
+            .map(|c| if is_unprintable(c) {
+                format!("<0x{:02x}>", c as u32)
+            } else {
+                c.to_string()
+            })
+            .collect()
+    )
+}
+
+/// The number of columns the grapheme occupies, once replaced by
+/// its placeholder. Tabs aren't considered, as they're expanded to
+/// the next tab stop, which depends on where they're drawn.
+pub fn width(grapheme: &str) -> usize {
+    placeholder(grapheme).map_or(1, |placeholder| placeholder.chars().count())
+}
+
+/// Replaces anything in text drawn outside of buffers (e.g. file names, search
+/// results, or command output shown in the status line) that can't be printed
+/// as-is with its placeholder, so that escape sequences in it can't control
+/// the terminal. Tabs and line endings aren't laid out there, so they're spaces.
+pub fn sanitize(content: &str) -> Cow<str> {
+    if !content.chars().any(char::is_control) {
+        return Cow::Borrowed(content);
+    }
+
+    Cow::Owned(
+        content
+            .graphemes(true)
+            .map(|grapheme| match grapheme {
+                "\t" | "\n" | "\r\n" => String::from(" "),
+                _ => placeholder(grapheme).unwrap_or_else(|| grapheme.to_string()),
+            })
+            .collect()
+    )
+}
+
+fn is_unprintable(c: char) -> bool {
+    c.is_control() && c != '\t' && c != '\n'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{placeholder, sanitize, width};
+
+    #[test]
+    fn placeholder_replaces_control_characters_with_their_codes() {
+        assert_eq!(placeholder("a"), None);
+        assert_eq!(placeholder("\t"), None);
+        assert_eq!(placeholder("\n"), None);
+        assert_eq!(placeholder("\u{1b}"), Some(String::from("<0x1b>")));
+        assert_eq!(placeholder("\u{9b}"), Some(String::from("<0x9b>")));
+        assert_eq!(placeholder("\u{7f}"), Some(String::from("<0x7f>")));
+        assert_eq!(placeholder("\r\n"), Some(String::from("<0x0d>")));
+        assert_eq!(placeholder("\u{FFFD}"), Some(String::from("\u{FFFD}")));
+
+        assert_eq!(width("a"), 1);
+        assert_eq!(width("\u{FFFD}"), 1);
+        assert_eq!(width("\u{0}"), 6);
+    }
+
+    #[test]
+    fn sanitize_replaces_escape_sequences_and_whitespace_control_characters() {
+        assert_eq!(sanitize("src/main.rs:1: fn main() {"), "src/main.rs:1: fn main() {");
+        assert_eq!(sanitize("\u{1b}]0;owned\u{7}title"), "<0x1b>]0;owned<0x07>title");
+        assert_eq!(sanitize("failed:\n\texit 1\r\n"), "failed:  exit 1 ");
+    }
+}
@@ -1,9 +1,10 @@
-use crate::models::application::Preferences;
+use crate::models::application::{LocalSettings, Preferences};
 use scribe::buffer::{Buffer, Position, Range};
 use scribe::util::LineIterator;
-use crate::view::buffer::{LexemeMapper, MappedLexeme, RenderState};
+use crate::view::buffer::{Annotation, AnnotationPosition, LexemeMapper, LineWrapping, MappedLexeme, RenderState};
 use crate::view::buffer::line_numbers::*;
-use crate::view::{Colors, RENDER_CACHE_FREQUENCY, RGBColor, Style};
+use crate::view::buffer::placeholder;
+use crate::view::{Area, Colors, RENDER_CACHE_FREQUENCY, RGBColor, Style};
 use crate::view::color::to_rgb_color;
 use crate::view::terminal::{Cell, Terminal, TerminalBuffer};
 use std::borrow::Cow;
@@ -11,6 +12,7 @@ use std::cell::RefCell;
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::str::FromStr;
+use std::time::{Duration, Instant};
 use syntect::highlighting::{Highlighter, HighlightIterator, Theme};
 use syntect::highlighting::Style as ThemeStyle;
 use syntect::parsing::ScopeStack;
@@ -20,18 +22,36 @@ use crate::errors::*;
 /// A one-time-use type that encapsulates all of the
 /// details involved in rendering a buffer to the screen.
 pub struct BufferRenderer<'a, 'p> {
+    annotations: Option<&'a [Annotation]>,
+    area: Area,
     buffer: &'a Buffer,
     buffer_position: Position,
+
+    /// The number of content columns scrolled over, when lines aren't wrapped.
+    column_offset: usize,
+    column_width: usize,
+
+    /// The current line's annotations that restyle its content, highest priority first.
+    content_annotations: Vec<&'a Annotation>,
     cursor_position: Option<Position>,
     gutter_width: usize,
     highlights: Option<&'a [Range]>,
+    highlight_time: Duration,
+
+    /// Lines after this one aren't rendered (e.g. those hidden by narrowing).
+    last_line: Option<usize>,
     stylist: Highlighter<'a>,
     current_style: ThemeStyle,
+
+    /// The style of placeholders for content that can't be printed as-is.
+    placeholder_style: ThemeStyle,
     line_numbers: LineNumbers,
+    line_wrapping: LineWrapping,
     preferences: &'a Preferences,
     render_cache: &'a Rc<RefCell<HashMap<usize, RenderState>>>,
     screen_position: Position,
     scroll_offset: usize,
+    tab_width: usize,
     terminal: &'a Terminal,
     terminal_buffer: &'a mut TerminalBuffer<'p>,
     theme: &'a Theme,
@@ -39,37 +59,91 @@ pub struct BufferRenderer<'a, 'p> {
 
 impl<'a, 'p> BufferRenderer<'a, 'p> {
     pub fn new(buffer: &'a Buffer, highlights: Option<&'a [Range]>,
-    scroll_offset: usize, terminal: &'a Terminal, theme: &'a Theme,
+    annotations: Option<&'a [Annotation]>, scroll_offset: usize, terminal: &'a Terminal, theme: &'a Theme,
     preferences: &'a Preferences,
     render_cache: &'a Rc<RefCell<HashMap<usize, RenderState>>>,
     terminal_buffer: &'a mut TerminalBuffer<'p>) -> BufferRenderer<'a, 'p> {
-        let line_numbers = LineNumbers::new(&buffer, Some(scroll_offset));
-        let gutter_width = line_numbers.width() + 1;
+        let line_numbers = LineNumbers::new(&buffer, Some(scroll_offset)).with_mode(preferences.line_numbers());
+        let column_width = column_width(annotations);
+        let gutter_width = column_width + line_numbers.width() + 1;
 
         // Build an initial style to start with,
         // which we'll modify as we highlight tokens.
         let stylist = Highlighter::new(theme);
         let current_style = stylist.get_default();
+        let placeholder_style = stylist.style_for_stack(
+            ScopeStack::from_str(placeholder::SCOPE)
+            .unwrap_or_default()
+            .as_slice()
+        );
+
+        // Fill the screen, save for the status line, unless told otherwise.
+        let area = Area{
+            top: 0,
+            left: 0,
+            width: terminal.width(),
+            height: terminal.height().saturating_sub(1),
+        };
 
         BufferRenderer{
+            annotations,
+            area,
             buffer,
+            column_offset: 0,
+            column_width,
+            content_annotations: Vec::new(),
             cursor_position: None,
             gutter_width,
             highlights,
+            highlight_time: Duration::from_secs(0),
+            last_line: None,
             stylist,
             current_style,
+            placeholder_style,
             line_numbers,
+            line_wrapping: LineWrapping::new(preferences, &LocalSettings::default(), buffer.path.as_ref()),
             buffer_position: Position{ line: 0, offset: 0 },
             preferences,
             render_cache,
             screen_position: Position{ line: 0, offset: 0 },
             scroll_offset,
+            tab_width: preferences.tab_width(buffer.path.as_ref()),
             terminal,
             terminal_buffer,
             theme,
         }
     }
 
+    /// Restricts rendering to a section of the screen (e.g. a pane).
+    pub fn set_area(&mut self, area: Area) {
+        self.area = area;
+    }
+
+    /// Renders using the buffer's own settings, where they've been changed.
+    /// Lines are numbered and wrapped accordingly, so this should precede
+    /// `set_column_offset`.
+    pub fn set_local_settings(&mut self, settings: &LocalSettings) {
+        let path = self.buffer.path.as_ref();
+        self.line_numbers = LineNumbers::new(self.buffer, Some(self.scroll_offset))
+            .with_mode(settings.line_numbers(self.preferences));
+        self.gutter_width = self.column_width + self.line_numbers.width() + 1;
+        self.line_wrapping = LineWrapping::new(self.preferences, settings, path);
+        self.tab_width = settings.tab_width(self.preferences, path);
+    }
+
+    /// Scrolls lines horizontally, hiding their first columns. Ignored when
+    /// lines are wrapped, as they never extend beyond the render area.
+    pub fn set_column_offset(&mut self, column_offset: usize) {
+        if self.wrap_offset().is_none() {
+            self.column_offset = column_offset;
+        }
+    }
+
+    /// Stops rendering after the line, leaving the rest of the area empty.
+    pub fn set_last_line(&mut self, line: usize) {
+        self.last_line = Some(line);
+    }
+
     fn on_cursor_line(&self) -> bool {
         self.buffer_position.line == self.buffer.cursor.line
     }
@@ -77,9 +151,16 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     fn print_rest_of_line(&mut self) {
         let on_cursor_line = self.on_cursor_line();
         let guide_offset = self.length_guide_offset();
-
-        for offset in self.screen_position.offset..self.terminal.width() {
-            let colors = if on_cursor_line || guide_offset.map(|go| go == offset).unwrap_or(false) {
+        let line_colors = self.content_annotations
+            .iter()
+            .find(|a| a.position == AnnotationPosition::Line && a.colors != Colors::Default)
+            .map(|a| a.colors);
+
+        let start = self.scrolled_offset(self.screen_position.offset).unwrap_or(self.gutter_width);
+        for offset in start..self.area.width {
+            let colors = if let Some(colors) = line_colors {
+                colors
+            } else if on_cursor_line || guide_offset.map(|go| go == offset + self.column_offset).unwrap_or(false) {
                 Colors::Focused
             } else {
                 Colors::Default
@@ -92,14 +173,123 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         }
     }
 
+    fn print_annotations(&mut self) {
+        let line = self.buffer_position.line;
+        let annotations = match self.annotations {
+            Some(annotations) => annotations,
+            None => return,
+        };
+
+        let end_of_line_annotations = annotations.iter().filter(|a| {
+            a.line == line && a.position == AnnotationPosition::EndOfLine
+        });
+        for annotation in end_of_line_annotations {
+            // Leave a one-column gap between the line content and annotation.
+            self.screen_position.offset += 1;
+
+            for character in annotation.content.graphemes(true) {
+                if self.screen_position.offset >= self.area.width + self.column_offset {
+                    return;
+                }
+
+                self.print_content(self.screen_position,
+                           annotation.style,
+                           annotation.colors,
+                           character.to_string());
+                self.screen_position.offset += 1;
+            }
+        }
+    }
+
+    // Marks lines with content scrolled off either side of the render area.
+    fn print_overflow_indicators(&mut self, line_end: usize) {
+        if self.wrap_offset().is_some() {
+            return;
+        }
+
+        if self.column_offset > 0 && line_end > self.gutter_width {
+            self.print(
+                Position{ line: self.screen_position.line, offset: self.gutter_width },
+                Style::Bold,
+                Colors::Focused,
+                "«"
+            );
+        }
+        if line_end > self.area.width + self.column_offset {
+            self.print(
+                Position{ line: self.screen_position.line, offset: self.area.width.saturating_sub(1) },
+                Style::Bold,
+                Colors::Focused,
+                "»"
+            );
+        }
+    }
+
+    // Where an offset on the current line is drawn, given the column offset:
+    // gutter offsets are unaffected, and content is shifted to the left.
+    // Content that's been scrolled off of the render area has no offset.
+    fn scrolled_offset(&self, offset: usize) -> Option<usize> {
+        if offset < self.gutter_width {
+            Some(offset)
+        } else {
+            offset.checked_sub(self.column_offset).filter(|&offset| offset >= self.gutter_width)
+        }
+    }
+
+    // The screen offset at which lines are wrapped, if they are.
+    fn wrap_offset(&self) -> Option<usize> {
+        let content_width = self.area.width.saturating_sub(self.gutter_width);
+
+        self.line_wrapping
+            .width(content_width)
+            .map(|width| self.gutter_width + width)
+    }
+
+    // Continues the current line on the next screen line,
+    // leaving its line number column blank.
+    fn wrap_line(&mut self) {
+        self.print_rest_of_line();
+        self.screen_position.line += 1;
+        if self.column_width > 0 {
+            self.print(
+                Position{ line: self.screen_position.line, offset: 0 },
+                Style::Default,
+                Colors::Default,
+                format!("{:1$}", "", self.column_width)
+            );
+        }
+        self.print(
+            Position{ line: self.screen_position.line, offset: self.column_width },
+            Style::Default,
+            Colors::Focused,
+            format!("{:1$}", "", self.gutter_width - self.column_width - 1)
+        );
+        let gap_color = if self.on_cursor_line() {
+            Colors::Focused
+        } else {
+            Colors::Default
+        };
+        self.print(
+            Position{ line: self.screen_position.line, offset: self.gutter_width - 1 },
+            Style::Default,
+            gap_color,
+            " "
+        );
+
+        self.screen_position.offset = self.gutter_width;
+    }
+
     fn length_guide_offset(&self) -> Option<usize> {
         self.preferences.line_length_guide().map(|offset| self.gutter_width + offset)
     }
 
     fn advance_to_next_line(&mut self) {
         if self.inside_visible_content() {
+            let line_end = self.screen_position.offset;
             self.set_cursor();
+            self.print_annotations();
             self.print_rest_of_line();
+            self.print_overflow_indicators(line_end);
 
             // It's important to only increase this once we've entered the
             // visible area. Otherwise, we're moving the screen location even
@@ -120,43 +310,51 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     // which will compensate for scrolling, tab expansion, etc.
     fn set_cursor(&mut self) {
         if self.inside_visible_content() && *self.buffer.cursor == self.buffer_position {
-            self.cursor_position = Some(self.screen_position);
+            // The cursor is hidden when it's been scrolled out of view.
+            let offset = self.scrolled_offset(self.screen_position.offset)
+                .filter(|&offset| self.wrap_offset().is_some() || offset < self.area.width);
+            if let Some(offset) = offset {
+                self.cursor_position = Some(self.area.translate(Position{ line: self.screen_position.line, offset }));
+            }
         }
     }
 
     fn current_char_style(&self, token_color: RGBColor) -> (Style, Colors) {
-        let (style, colors) = match self.highlights {
-            Some(highlight_ranges) => {
-                for range in highlight_ranges {
-                    if range.includes(&self.buffer_position) {
-                        // We're inside of one of the highlighted areas.
-                        // Return early with highlight colors.
-                        if range.includes(&self.buffer.cursor) {
-                            return (Style::Bold, Colors::SelectMode)
-                        } else {
-                            return (Style::Inverted, Colors::Default)
-                        }
+        if let Some(highlight_ranges) = self.highlights {
+            for range in highlight_ranges {
+                if range.includes(&self.buffer_position) {
+                    // We're inside of one of the highlighted areas.
+                    // Return early with highlight colors.
+                    if range.includes(&self.buffer.cursor) {
+                        return (Style::Bold, Colors::SelectMode)
+                    } else {
+                        return (Style::Inverted, Colors::Default)
                     }
                 }
-
-                // We aren't inside one of the highlighted areas.
-                // Fall back to other styling considerations.
-                if self.on_cursor_line() {
-                    (Style::Default, Colors::CustomFocusedForeground(token_color))
-                } else {
-                    (Style::Default, Colors::CustomForeground(token_color))
-                }
             }
-            None => {
-                if self.on_cursor_line() {
-                    (Style::Default, Colors::CustomFocusedForeground(token_color))
-                } else {
-                    (Style::Default, Colors::CustomForeground(token_color))
-                }
-            },
+        }
+
+        // We aren't inside one of the highlighted areas. Annotations
+        // restyling the content take precedence over syntax highlighting.
+        let offset = self.buffer_position.offset;
+        let annotation = self.content_annotations.iter().find(|a| match a.position {
+            AnnotationPosition::Range { start, end } => start <= offset && offset < end,
+            AnnotationPosition::Line => true,
+            _ => false,
+        });
+        let colors = match annotation.map(|a| a.colors) {
+            Some(Colors::Default) | None => Colors::CustomForeground(token_color),
+            Some(colors) => colors,
         };
+        let style = annotation.map(|a| a.style).unwrap_or(Style::Default);
 
-        (style, colors)
+        // Keep the cursor line's background for foreground-only colors.
+        match colors {
+            Colors::CustomForeground(color) if self.on_cursor_line() => {
+                (style, Colors::CustomFocusedForeground(color))
+            }
+            colors => (style, colors),
+        }
     }
 
     pub fn print_lexeme<L: Into<Cow<'p, str>>>(&mut self, lexeme: L) {
@@ -164,38 +362,59 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             // Ignore newline characters.
             if character == "\n" { continue; }
 
+            // Placeholders span several columns, and are moved to
+            // the next screen line when they'd cross the wrap column.
+            let placeholder = placeholder::placeholder(character);
+            let width = placeholder.as_ref().map_or(1, |placeholder| placeholder.chars().count());
+            let wrap = self.wrap_offset().is_some_and(|offset| {
+                self.screen_position.offset > self.gutter_width && self.screen_position.offset + width > offset
+            });
+            if wrap {
+                self.wrap_line();
+            }
             self.set_cursor();
 
             // Determine the style we'll use to print.
-            let token_color = to_rgb_color(self.current_style.foreground);
+            let token_style = if placeholder.is_some() {
+                self.placeholder_style
+            } else {
+                self.current_style
+            };
+            let token_color = to_rgb_color(token_style.foreground);
             let (style, color) = self.current_char_style(token_color);
 
-            if self.preferences.line_wrapping() && self.screen_position.offset == self.terminal.width() {
-                self.screen_position.line += 1;
-                self.screen_position.offset = self.gutter_width;
-                self.print(self.screen_position, style, color, character.to_string());
-                self.screen_position.offset += 1;
-                self.buffer_position.offset += 1;
-            } else if character == "\t" {
+            if character == "\t" {
                 // Calculate the next tab stop using the tab-aware offset,
                 // *without considering the line number gutter*, and then
                 // re-add the gutter width to get the actual/screen offset.
                 let buffer_tab_stop = self.next_tab_stop(self.screen_position.offset - self.gutter_width);
                 let mut screen_tab_stop = buffer_tab_stop + self.gutter_width;
 
-                // Now that we know where we'd like to go, prevent it from being off-screen.
-                if screen_tab_stop > self.terminal.width() {
-                    screen_tab_stop = self.terminal.width();
+                // Now that we know where we'd like to go, prevent it from being
+                // off-screen (or crossing the column lines are wrapped at).
+                let limit = self.wrap_offset().unwrap_or(self.area.width + self.column_offset);
+                if screen_tab_stop > limit {
+                    screen_tab_stop = limit;
                 }
 
                 // Print the sequence of spaces and move the offset accordingly.
                 for _ in self.screen_position.offset..screen_tab_stop {
-                    self.print(self.screen_position, style, color, " ");
+                    self.print_content(self.screen_position, style, color, " ");
+                    self.screen_position.offset += 1;
+                }
+                self.buffer_position.offset += 1;
+            } else if let Some(placeholder) = placeholder {
+                // Lines narrower than the placeholder are cut off at the wrap column.
+                let limit = self.wrap_offset();
+                for character in placeholder.chars() {
+                    if limit.map_or(true, |limit| self.screen_position.offset < limit) {
+                        self.print_content(self.screen_position, style, color, character.to_string());
+                    }
                     self.screen_position.offset += 1;
                 }
                 self.buffer_position.offset += 1;
             } else {
-                self.print(self.screen_position, style, color, character.to_string());
+                self.print_content(self.screen_position, style, color, character.to_string());
                 self.screen_position.offset += 1;
                 self.buffer_position.offset += 1;
             }
@@ -209,13 +428,19 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     }
 
     fn after_visible_content(&self) -> bool {
-        self.screen_position.line >= self.terminal.height().checked_sub(1).unwrap_or(0)
+        self.screen_position.line >= self.area.height
+            || self.last_line.is_some_and(|line| self.buffer_position.line > line)
     }
 
     fn inside_visible_content(&mut self) -> bool {
         !self.before_visible_content() && !self.after_visible_content()
     }
 
+    /// Time spent parsing lines for syntax highlighting while rendering.
+    pub fn highlight_time(&self) -> Duration {
+        self.highlight_time
+    }
+
     pub fn render(&mut self, lines: LineIterator<'p>, mut lexeme_mapper: Option<&mut LexemeMapper>) -> Result<Option<Position>> {
         self.terminal.set_cursor(None);
         // Print the first line number. Others will
@@ -238,7 +463,9 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
                     self.render_cache.borrow_mut().insert(line_no, state.clone());
                 }
 
+                let parse_started_at = Instant::now();
                 let events = state.parse.parse_line(line);
+                self.highlight_time += parse_started_at.elapsed();
                 let styled_lexemes = HighlightIterator::new(
                     &mut state.highlight,
                     &events,
@@ -287,8 +514,15 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
 
         self.set_cursor();
 
-        // One last call to this for the last line.
+        // One last call to these for the last line.
+        let line_end = self.screen_position.offset;
+        if self.inside_visible_content() {
+            self.print_annotations();
+        }
         self.print_rest_of_line();
+        if self.inside_visible_content() {
+            self.print_overflow_indicators(line_end);
+        }
 
         // Return the cursor location. If it occurred somewhere in the buffer, it
         // will be shown at the right location. If not, it will be None and will
@@ -297,9 +531,18 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
     }
 
     fn print_line_number(&mut self) {
+        let line = self.buffer_position.line;
+        self.content_annotations = self.annotations
+            .into_iter()
+            .flatten()
+            .filter(|a| a.line == line)
+            .filter(|a| matches!(a.position, AnnotationPosition::Line | AnnotationPosition::Range { .. }))
+            .collect();
+
         if !self.inside_visible_content() { return };
 
         let line_number = self.line_numbers.next().unwrap();
+        self.print_column_annotation();
 
         // Cursor line number is emboldened.
         let weight = if self.on_cursor_line() {
@@ -309,7 +552,7 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
         };
 
         self.print(
-            Position{ line: self.screen_position.line, offset: 0 },
+            Position{ line: self.screen_position.line, offset: self.column_width },
             weight,
             Colors::Focused,
             line_number
@@ -322,17 +565,70 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             Colors::Default
         };
         self.print(
-            Position{ line: self.screen_position.line, offset: self.line_numbers.width() },
+            Position{ line: self.screen_position.line, offset: self.gutter_width - 1 },
             weight,
             gap_color,
             " "
         );
+        self.print_gutter_annotation();
+
+        self.screen_position.offset = self.gutter_width;
+    }
+
+    // Draws the current line's column annotation (if any) to the left of its
+    // line number, padding it (and lines without one) to the column's width.
+    fn print_column_annotation(&mut self) {
+        if self.column_width == 0 { return };
+
+        let line = self.buffer_position.line;
+        let annotation = self.annotations.and_then(|annotations| {
+            annotations.iter().find(|a| {
+                a.line == line && a.position == AnnotationPosition::Column
+            })
+        });
+        let (content, style, colors) = match annotation {
+            Some(annotation) => (annotation.content.as_str(), annotation.style, annotation.colors),
+            None => ("", Style::Default, Colors::Default),
+        };
 
-        self.screen_position.offset = self.line_numbers.width() + 1;
+        // The last column is left blank, separating the annotation from the line number.
+        let padded: String = content
+            .graphemes(true)
+            .chain(std::iter::repeat(" "))
+            .take(self.column_width)
+            .collect();
+        self.print(
+            Position{ line: self.screen_position.line, offset: 0 },
+            style,
+            colors,
+            padded
+        );
+    }
+
+    // Draws the first gutter annotation for the current line (if any)
+    // in the gap between the line number and buffer content.
+    fn print_gutter_annotation(&mut self) {
+        let line = self.buffer_position.line;
+        let annotation = self.annotations.and_then(|annotations| {
+            annotations.iter().find(|a| {
+                a.line == line && a.position == AnnotationPosition::Gutter
+            })
+        });
+
+        if let Some(annotation) = annotation {
+            if let Some(sign) = annotation.content.graphemes(true).next() {
+                self.print(
+                    Position{ line: self.screen_position.line, offset: self.gutter_width - 1 },
+                    annotation.style,
+                    annotation.colors,
+                    sign.to_string()
+                );
+            }
+        }
     }
 
     fn next_tab_stop(&self, offset: usize) -> usize {
-        (offset / self.preferences.tab_width(self.buffer.path.as_ref()) + 1) * self.preferences.tab_width(self.buffer.path.as_ref())
+        (offset / self.tab_width + 1) * self.tab_width
     }
 
     fn mapper_styles(&self) -> (ThemeStyle, ThemeStyle) {
@@ -366,11 +662,26 @@ impl<'a, 'p> BufferRenderer<'a, 'p> {
             .map(|(k, v)| (*k, v.clone()))
     }
 
+    // Prints line content, scrolled by the column offset.
+    fn print_content<C>(&mut self, position: Position, style: Style, colors: Colors, content: C)
+        where C: Into<Cow<'p, str>>
+    {
+        if let Some(offset) = self.scrolled_offset(position.offset) {
+            self.print(Position{ line: position.line, offset }, style, colors, content);
+        }
+    }
+
+    // Prints content at a position relative to the
+    // render area, discarding anything outside of it.
     fn print<C>(&mut self, position: Position, style: Style, colors: Colors, content: C)
         where C: Into<Cow<'p, str>>
     {
+        if position.line >= self.area.height || position.offset >= self.area.width {
+            return;
+        }
+
         self.terminal_buffer.set_cell(
-            position,
+            self.area.translate(position),
             Cell{ content: content.into(), style, colors }
         );
     }
@@ -383,6 +694,18 @@ fn has_trailing_newline(line: &str) -> bool {
         .unwrap_or(false)
 }
 
+// The width of the column annotations' column, including a one-column
+// gap after the widest of them, or zero if the buffer has none.
+fn column_width(annotations: Option<&[Annotation]>) -> usize {
+    annotations
+        .into_iter()
+        .flatten()
+        .filter(|a| a.position == AnnotationPosition::Column)
+        .map(|a| a.content.graphemes(true).count() + 1)
+        .max()
+        .unwrap_or(0)
+}
+
 #[cfg(test)]
 mod tests {
     use crate::models::application::Preferences;
@@ -394,6 +717,8 @@ mod tests {
     use std::path::{Path, PathBuf};
     use std::rc::Rc;
     use super::{BufferRenderer, LexemeMapper, MappedLexeme};
+    use crate::view::buffer::{Annotation, AnnotationPosition};
+    use crate::view::{Area, Colors, Style};
     use syntect::highlighting::ThemeSet;
     use crate::view::terminal::*;
     use crate::yaml::yaml::YamlLoader;
@@ -418,6 +743,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             0,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -449,6 +775,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             0,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -487,6 +814,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             0,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -522,6 +850,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             0,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -537,6 +866,188 @@ mod tests {
         );
     }
 
+    #[test]
+    fn render_replaces_control_characters_with_placeholders() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("\u{1b}]0\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(LineIterator::new(&data), None).unwrap();
+
+        let expected_content = " 1  <0x1b>\n    ]0    \n 2        ";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
+    #[test]
+    fn render_wraps_lines_at_the_configured_column_or_truncates_them() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nsecond line\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let terminal = build_terminal().unwrap();
+        let theme_set = ThemeSet::load_defaults();
+        for &(setting, expected_content) in &[
+            ("line_wrapping: 3", " 1  amp   \n     ed   \n    ito   \n    r     \n 2  sec   \n    ond   "),
+            ("line_wrapping: false", " 1  amp e»\n 2  secon»\n 3        "),
+        ] {
+            let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+            let setting = YamlLoader::load_from_str(setting).unwrap().into_iter().next();
+            let preferences = Preferences::new(setting);
+
+            BufferRenderer::new(
+                workspace.current_buffer().unwrap(),
+                None,
+                None,
+                0,
+                &**terminal,
+                &theme_set.themes["base16-ocean.dark"],
+                &preferences,
+                &Rc::new(RefCell::new(HashMap::new())),
+                &mut terminal_buffer
+            ).render(LineIterator::new(&data), None).unwrap();
+
+            assert_eq!(
+                &terminal_buffer.content()[0..expected_content.len()],
+                expected_content
+            );
+        }
+    }
+
+    #[test]
+    fn render_numbers_lines_relative_to_the_cursor_or_hides_them() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\neditor\nlines\n");
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let terminal = build_terminal().unwrap();
+        let theme_set = ThemeSet::load_defaults();
+        for &(setting, expected_content) in &[
+            ("line_numbers: relative", " 1  amp   \n 0  editor\n 1  lines "),
+            ("line_numbers: hybrid", " 1  amp   \n 2  editor\n 1  lines "),
+            ("line_numbers: none", " amp      \n editor   \n lines    "),
+        ] {
+            let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+            let setting = YamlLoader::load_from_str(setting).unwrap().into_iter().next();
+            let preferences = Preferences::new(setting);
+
+            BufferRenderer::new(
+                workspace.current_buffer().unwrap(),
+                None,
+                None,
+                0,
+                &**terminal,
+                &theme_set.themes["base16-ocean.dark"],
+                &preferences,
+                &Rc::new(RefCell::new(HashMap::new())),
+                &mut terminal_buffer
+            ).render(LineIterator::new(&data), None).unwrap();
+
+            assert_eq!(
+                &terminal_buffer.content()[0..expected_content.len()],
+                expected_content
+            );
+        }
+    }
+
+    #[test]
+    fn render_scrolls_unwrapped_lines_horizontally_and_marks_hidden_content() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp editor\nsecond line\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let terminal = build_terminal().unwrap();
+        let theme_set = ThemeSet::load_defaults();
+        let setting = YamlLoader::load_from_str("line_wrapping: false").unwrap().into_iter().next();
+        let preferences = Preferences::new(setting);
+        for &(cursor_offset, expected_cursor) in &[(5, Some(Position{ line: 0, offset: 6 })), (0, None)] {
+            workspace.current_buffer().unwrap().cursor.move_to(Position{ line: 0, offset: cursor_offset });
+            let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+            let render_cache = Rc::new(RefCell::new(HashMap::new()));
+            let mut renderer = BufferRenderer::new(
+                workspace.current_buffer().unwrap(),
+                None,
+                None,
+                0,
+                &**terminal,
+                &theme_set.themes["base16-ocean.dark"],
+                &preferences,
+                &render_cache,
+                &mut terminal_buffer
+            );
+            renderer.set_column_offset(3);
+            let cursor = renderer.render(LineIterator::new(&data), None).unwrap();
+
+            // The cursor is hidden once it's been scrolled out of view.
+            assert_eq!(cursor, expected_cursor);
+            let expected_content = " 1  «edit»\n 2  «nd l»\n 3        ";
+            assert_eq!(&terminal_buffer.content()[0..expected_content.len()], expected_content);
+        }
+    }
+
+    #[test]
+    fn render_is_confined_to_its_area() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\nline\nmore\n");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+
+        let render_cache = Rc::new(RefCell::new(HashMap::new()));
+        let mut renderer = BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            None,
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &render_cache,
+            &mut terminal_buffer
+        );
+        renderer.set_area(Area{ top: 1, left: 2, width: 8, height: 2 });
+        let cursor_position = renderer.render(lines, None).unwrap();
+
+        let expected_content = "          \n   1  amp \n   2  line\n          ";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+        assert_eq!(cursor_position, Some(Position{ line: 1, offset: 6 }));
+    }
+
     // Used to test lexeme mapper usage.
     struct TestMapper {}
     impl LexemeMapper for TestMapper {
@@ -564,6 +1075,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             0,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -598,6 +1110,7 @@ mod tests {
         let cursor_position = BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             0,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -632,6 +1145,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             495,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -667,6 +1181,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             95,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -689,6 +1204,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             495,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -727,6 +1243,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             95,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -748,6 +1265,7 @@ mod tests {
         BufferRenderer::new(
             workspace.current_buffer().unwrap(),
             None,
+            None,
             200,
             &**terminal,
             &theme_set.themes["base16-ocean.dark"],
@@ -761,4 +1279,166 @@ mod tests {
             &terminal_buffer.content()[0..expected_content.len()],
             expected_content);
     }
+
+    #[test]
+    fn render_prints_annotations_after_line_content_and_in_gutter() {
+        // Set up a workspace and buffer; the workspace will
+        // handle setting up the buffer's syntax definition.
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\nab");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let annotations = vec![
+            Annotation {
+                line: 0,
+                content: String::from("ok"),
+                style: Style::Default,
+                colors: Colors::Insert,
+                position: AnnotationPosition::EndOfLine,
+            },
+            Annotation {
+                line: 1,
+                content: String::from("ok"),
+                style: Style::Default,
+                colors: Colors::Insert,
+                position: AnnotationPosition::EndOfLine,
+            },
+            Annotation {
+                line: 1,
+                content: String::from("*"),
+                style: Style::Default,
+                colors: Colors::Insert,
+                position: AnnotationPosition::Gutter,
+            }
+        ];
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            Some(&annotations),
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        let expected_content = " 1  amp ok\n 2 *ab ok ";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
+
+    #[test]
+    fn render_restyles_content_covered_by_range_and_line_annotations() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\nab");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let annotations = vec![
+            Annotation {
+                line: 0,
+                content: String::new(),
+                style: Style::Underlined,
+                colors: Colors::Warning,
+                position: AnnotationPosition::Range { start: 1, end: 3 },
+            },
+            Annotation {
+                line: 1,
+                content: String::new(),
+                style: Style::Default,
+                colors: Colors::Insert,
+                position: AnnotationPosition::Line,
+            },
+        ];
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            Some(&annotations),
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        let styles: Vec<(Style, Colors)> = terminal_buffer
+            .iter()
+            .filter(|(position, _)| position.offset >= 4 && position.offset < 8)
+            .take(8)
+            .map(|(_, cell)| (cell.style, cell.colors))
+            .collect();
+
+        // The first character keeps its syntax highlighting, on the focused cursor line.
+        match styles[0] {
+            (Style::Default, Colors::CustomFocusedForeground(_)) => (),
+            style => panic!("Unexpected style: {:?}", style),
+        }
+        assert_eq!(&styles[1..4], &[
+            (Style::Underlined, Colors::Warning),
+            (Style::Underlined, Colors::Warning),
+            (Style::Default, Colors::Focused),
+        ]);
+        assert_eq!(&styles[4..], &[(Style::Default, Colors::Insert); 4]);
+    }
+
+    #[test]
+    fn render_prints_column_annotations_before_line_numbers() {
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.insert("amp\nab");
+        workspace.add_buffer(buffer);
+
+        let data = workspace.current_buffer().unwrap().data();
+        let lines = LineIterator::new(&data);
+        let terminal = build_terminal().unwrap();
+        let mut terminal_buffer = TerminalBuffer::new(terminal.width(), terminal.height());
+        let theme_set = ThemeSet::load_defaults();
+        let preferences = Preferences::new(None);
+        let annotations = vec![
+            Annotation {
+                line: 0,
+                content: String::from("a"),
+                style: Style::Default,
+                colors: Colors::Insert,
+                position: AnnotationPosition::Column,
+            }
+        ];
+
+        BufferRenderer::new(
+            workspace.current_buffer().unwrap(),
+            None,
+            Some(&annotations),
+            0,
+            &**terminal,
+            &theme_set.themes["base16-ocean.dark"],
+            &preferences,
+            &Rc::new(RefCell::new(HashMap::new())),
+            &mut terminal_buffer
+        ).render(lines, None).unwrap();
+
+        let expected_content = "a  1  amp \n   2  ab";
+        assert_eq!(
+            &terminal_buffer.content()[0..expected_content.len()],
+            expected_content
+        );
+    }
 }
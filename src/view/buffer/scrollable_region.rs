@@ -1,7 +1,7 @@
 use std::sync::Arc;
-use scribe::buffer::Buffer;
-use unicode_segmentation::UnicodeSegmentation;
-use crate::view::buffer::LineNumbers;
+use scribe::buffer::{Buffer, Position};
+use crate::models::application::LineNumberMode;
+use crate::view::buffer::{LineNumbers, LineWrapping};
 use crate::view::terminal::Terminal;
 
 /// Abstract representation of a fixed-height section of the screen.
@@ -10,6 +10,10 @@ use crate::view::terminal::Terminal;
 pub struct ScrollableRegion {
     terminal: Arc<Box<Terminal + Sync + Send + 'static>>,
     line_offset: usize,
+    column_offset: usize,
+    size: Option<(usize, usize)>,
+    line_numbers: LineNumberMode,
+    line_wrapping: LineWrapping,
 }
 
 impl ScrollableRegion {
@@ -17,6 +21,62 @@ impl ScrollableRegion {
         ScrollableRegion {
             terminal,
             line_offset: 0,
+            column_offset: 0,
+            size: None,
+            line_numbers: LineNumberMode::Absolute,
+            line_wrapping: LineWrapping::default(),
+        }
+    }
+
+    /// Sizes the region to fit a section of the screen (e.g. a pane),
+    /// rather than the terminal's full width and height.
+    pub fn resize(&mut self, width: usize, height: usize) {
+        self.size = Some((width, height));
+    }
+
+    pub fn set_line_numbers(&mut self, line_numbers: LineNumberMode) {
+        self.line_numbers = line_numbers;
+    }
+
+    pub fn set_line_wrapping(&mut self, line_wrapping: LineWrapping) {
+        self.line_wrapping = line_wrapping;
+    }
+
+    /// How the buffer's lines are wrapped, along with the width available
+    /// to their content (i.e. the region's, less the line number gutter).
+    pub fn line_wrapping(&self, buffer: &Buffer) -> (LineWrapping, usize) {
+        (self.line_wrapping, self.width().saturating_sub(self.gutter_width(buffer)).max(1))
+    }
+
+    /// The buffer position drawn at (or closest to) a position relative to
+    /// the region, taking scrolling and line wrapping into account. Positions
+    /// in the line number gutter resolve to the start of the line's visible
+    /// content, and those beyond the last line to the end of it.
+    pub fn buffer_position(&self, buffer: &Buffer, position: Position) -> Position {
+        let (line_wrapping, content_width) = self.line_wrapping(buffer);
+        let column = position.offset.saturating_sub(self.gutter_width(buffer)) + self.column_offset;
+        let data = buffer.data();
+        let mut remaining_rows = position.line;
+        let mut last_line = (self.line_offset, "");
+
+        // Unlike lines(), this yields the empty line
+        // following a trailing newline, as Amp does.
+        for (line_number, line) in data.split('\n').enumerate().skip(self.line_offset) {
+            let rows = line_wrapping.row_count(line, content_width);
+            if remaining_rows < rows {
+                return Position {
+                    line: line_number,
+                    offset: line_wrapping.offset(line, remaining_rows, column, content_width),
+                };
+            }
+            remaining_rows -= rows;
+            last_line = (line_number, line);
+        }
+
+        let (line_number, line) = last_line;
+        Position {
+            line: line_number,
+            offset: line_wrapping.offset(line, usize::MAX, column, content_width),
         }
     }
 
@@ -38,6 +98,8 @@ impl ScrollableRegion {
                 self.line_offset = starting_line;
             }
         }
+
+        self.scroll_column_into_view(buffer);
     }
 
     /// Moves the line offset such that the specified line is centered vertically.
@@ -47,6 +109,7 @@ impl ScrollableRegion {
         self.line_offset = buffer.cursor.line.checked_sub(
             self.preceding_line_count(&buffer, limit)
         ).unwrap_or(0);
+        self.scroll_column_into_view(buffer);
     }
 
     /// The number of lines the region has scrolled over.
@@ -55,6 +118,27 @@ impl ScrollableRegion {
         self.line_offset
     }
 
+    /// The number of (content) columns the region has scrolled over, when
+    /// long lines aren't wrapped. A value of zero represents an unscrolled region.
+    pub fn column_offset(&self) -> usize {
+        self.column_offset
+    }
+
+    /// Scrolls to the specified line and column offsets (e.g. to return to a
+    /// previously recorded scroll position).
+    pub fn scroll_to(&mut self, line_offset: usize, column_offset: usize) {
+        self.line_offset = line_offset;
+        self.column_offset = column_offset;
+    }
+
+    pub fn scroll_left(&mut self, amount: usize) {
+        self.column_offset = self.column_offset.saturating_sub(amount);
+    }
+
+    pub fn scroll_right(&mut self, amount: usize) {
+        self.column_offset += amount;
+    }
+
     pub fn scroll_up(&mut self, amount: usize) {
         self.line_offset = match self.line_offset.checked_sub(amount) {
             Some(amount) => amount,
@@ -66,10 +150,42 @@ impl ScrollableRegion {
         self.line_offset += amount;
     }
 
-    /// Scrollable regions occupy one line short of the full
-    /// terminal height, which is reserved for the status line.
+    /// Unless resized, scrollable regions occupy one line short of
+    /// the full terminal height, which is reserved for the status line.
     fn height(&self) -> usize {
-        self.terminal.height() - 1
+        self.size.map(|(_, height)| height).unwrap_or_else(|| self.terminal.height() - 1)
+    }
+
+    fn width(&self) -> usize {
+        self.size.map(|(width, _)| width).unwrap_or_else(|| self.terminal.width())
+    }
+
+    // The buffer renderer adds a single-column margin
+    // to the right-hand side of the line number columns.
+    fn gutter_width(&self, buffer: &Buffer) -> usize {
+        LineNumbers::new(&buffer, None).with_mode(self.line_numbers).width() + 1
+    }
+
+    /// If necessary, moves the column offset such that the cursor is visible.
+    /// The region's last column is kept clear of it, as is its first once
+    /// scrolled, so that it isn't hidden by off-screen content indicators.
+    fn scroll_column_into_view(&mut self, buffer: &Buffer) {
+        let (line_wrapping, content_width) = self.line_wrapping(buffer);
+        if line_wrapping.width(content_width).is_some() {
+            self.column_offset = 0;
+            return;
+        }
+
+        let data = buffer.data();
+        let line = data.lines().nth(buffer.cursor.line).unwrap_or("");
+        let (_, column) = line_wrapping.position(line, buffer.cursor.offset, content_width);
+        let visible_width = content_width.saturating_sub(1).max(1);
+
+        if self.column_offset > 0 && column <= self.column_offset {
+            self.column_offset = column.saturating_sub(1);
+        } else if column >= self.column_offset + visible_width {
+            self.column_offset = column + 1 - visible_width;
+        }
     }
 
     /// Assuming that the buffer cursor is at the bottom of the screen,
@@ -77,10 +193,7 @@ impl ScrollableRegion {
     /// on-screen, taking line wrapping into consideration.
     fn preceding_line_count(&self, buffer: &Buffer, limit: usize) -> usize {
         let mut preceding_line_count = 0;
-
-        // The buffer renderer adds a single-column margin
-        // to the right-hand side of the line number columns.
-        let gutter_width = LineNumbers::new(&buffer, None).width() + 1;
+        let (line_wrapping, content_width) = self.line_wrapping(buffer);
 
         let end = buffer.cursor.line + 1;
         let start = end.checked_sub(limit).unwrap_or(0);
@@ -91,13 +204,7 @@ impl ScrollableRegion {
             .lines()
             .skip(start)
             .take(line_count)
-            .map(|line| {
-                let grapheme_count = line.graphemes(true).count().max(1) as f32;
-                let buffer_content_width = (self.terminal.width() - gutter_width) as f32;
-                let wrapped_line_count = grapheme_count / buffer_content_width;
-
-                wrapped_line_count.ceil() as usize
-            })
+            .map(|line| line_wrapping.row_count(line, content_width))
             .collect();
 
         // Figure out how many lines we can fit
@@ -127,8 +234,11 @@ impl ScrollableRegion {
 #[cfg(test)]
 mod tests {
     use super::ScrollableRegion;
+    use crate::models::application::{LocalSettings, Preferences};
+    use crate::view::buffer::LineWrapping;
     use crate::view::terminal::*;
     use scribe::buffer::{Buffer, Position};
+    use crate::yaml::yaml::YamlLoader;
 
     #[test]
     fn scroll_into_view_correctly_handles_additonal_rendered_trailing_newline() {
@@ -167,6 +277,35 @@ mod tests {
         assert_eq!(region.line_offset(), 1);
     }
 
+    #[test]
+    fn scroll_into_view_uses_resized_height() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        region.resize(20, 4);
+        for _ in 0..10 {
+            buffer.insert("word \n");
+        }
+        buffer.cursor.move_to(Position{ line: 9, offset: 0 });
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.line_offset(), 6);
+    }
+
+    #[test]
+    fn buffer_position_accounts_for_the_gutter_scrolling_and_wrapping() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        region.resize(10, 5);
+        buffer.insert("zero\none two three\nfour\n");
+        region.scroll_down(1);
+
+        assert_eq!(region.buffer_position(&buffer, Position{ line: 1, offset: 5 }), Position{ line: 1, offset: 7 });
+        assert_eq!(region.buffer_position(&buffer, Position{ line: 3, offset: 0 }), Position{ line: 2, offset: 0 });
+        assert_eq!(region.buffer_position(&buffer, Position{ line: 3, offset: 20 }), Position{ line: 2, offset: 4 });
+        assert_eq!(region.buffer_position(&buffer, Position{ line: 9, offset: 6 }), Position{ line: 3, offset: 0 });
+    }
+
     #[test]
     fn scroll_into_view_recedes_region_if_line_before_current_range() {
         let terminal = build_terminal().unwrap();
@@ -231,6 +370,83 @@ mod tests {
         assert_eq!(region.line_offset(), 2);
     }
 
+    #[test]
+    fn scroll_into_view_ignores_long_lines_when_wrapping_is_disabled() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        let data = YamlLoader::load_from_str("line_wrapping: false").unwrap();
+        region.set_line_wrapping(LineWrapping::new(&Preferences::new(data.into_iter().next()), &LocalSettings::default(), None));
+        buffer.insert("cursor line\n");
+        for _ in 0..5 {
+            buffer.insert("       \n");
+        }
+        buffer.cursor.move_to(Position{ line: 5, offset: 0 });
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.line_offset(), 0);
+    }
+
+    #[test]
+    fn scroll_into_view_wraps_lines_at_the_configured_column() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        let data = YamlLoader::load_from_str("line_wrapping: 4").unwrap();
+        region.set_line_wrapping(LineWrapping::new(&Preferences::new(data.into_iter().next()), &LocalSettings::default(), None));
+        // Lines that only wrap at the configured column.
+        buffer.insert("cursor");
+        for _ in 0..5 {
+            buffer.insert("12345\n");
+        }
+        buffer.cursor.move_to(Position{ line: 5, offset: 0 });
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.line_offset(), 2);
+    }
+
+    #[test]
+    fn scroll_into_view_scrolls_horizontally_when_wrapping_is_disabled() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        let data = YamlLoader::load_from_str("line_wrapping: false").unwrap();
+        region.set_line_wrapping(LineWrapping::new(&Preferences::new(data.into_iter().next()), &LocalSettings::default(), None));
+        buffer.insert("abcdefghijklmnopqrstuvwxyz\n");
+        let (_, content_width) = region.line_wrapping(&buffer);
+
+        // The cursor is kept off of the region's last column.
+        buffer.cursor.move_to(Position{ line: 0, offset: content_width - 2 });
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.column_offset(), 0);
+        buffer.cursor.move_to(Position{ line: 0, offset: 20 });
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.column_offset(), 22 - content_width);
+
+        // Moving back leaves a column for the off-screen content indicator.
+        buffer.cursor.move_to(Position{ line: 0, offset: 16 });
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.column_offset(), 15);
+        buffer.cursor.move_to(Position{ line: 0, offset: 0 });
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.column_offset(), 0);
+    }
+
+    #[test]
+    fn scroll_into_view_resets_column_offset_when_wrapping_lines() {
+        let terminal = build_terminal().unwrap();
+        let mut buffer = Buffer::new();
+        let mut region = ScrollableRegion::new(terminal);
+        buffer.insert("abcdefghijklmnopqrstuvwxyz\n");
+        region.scroll_right(5);
+        region.scroll_into_view(&buffer);
+        assert_eq!(region.column_offset(), 0);
+
+        region.scroll_right(5);
+        region.scroll_left(2);
+        assert_eq!(region.column_offset(), 3);
+        region.scroll_left(5);
+        assert_eq!(region.column_offset(), 0);
+    }
+
     #[test]
     fn scroll_to_center_sets_correct_line_offset() {
         let terminal = build_terminal().unwrap();
@@ -0,0 +1,75 @@
+use crate::view::buffer::RenderCache;
+use unicode_segmentation::UnicodeSegmentation;
+
+// An average adult's silent reading speed, in words per minute.
+const READING_SPEED: usize = 200;
+
+/// A running count of a buffer's words, kept per line so that edits only
+/// require the lines on and after the first changed one to be recounted.
+#[derive(Default)]
+pub struct WordCount {
+    lines: Vec<usize>,
+    total: usize,
+}
+
+impl WordCount {
+    /// Counts the buffer's words, recounting only lines changed since the
+    /// last count; the data must be the content of the buffer being tracked.
+    pub fn count(&mut self, data: &str) -> usize {
+        for line in data.lines().skip(self.lines.len()) {
+            let words = line.unicode_words().count();
+            self.lines.push(words);
+            self.total += words;
+        }
+
+        self.total
+    }
+}
+
+impl RenderCache for WordCount {
+    /// Forgets the counts of lines on and after the specified one.
+    fn invalidate_from(&mut self, line: usize) {
+        if line < self.lines.len() {
+            self.total -= self.lines.drain(line..).sum::<usize>();
+        }
+    }
+}
+
+/// Describes a word count, and how long that many words take to read.
+pub fn word_count_summary(words: usize) -> String {
+    let minutes = words.div_ceil(READING_SPEED);
+
+    format!(
+        "{} word{}, {} min read",
+        words,
+        if words == 1 { "" } else { "s" },
+        minutes.max(1)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{word_count_summary, WordCount};
+    use crate::view::buffer::RenderCache;
+
+    #[test]
+    fn count_only_recounts_invalidated_lines() {
+        let mut word_count = WordCount::default();
+        assert_eq!(word_count.count("amp is a\ntext editor\n"), 5);
+
+        // Lines before the invalidated one aren't recounted,
+        // so changes to them (without invalidation) go unnoticed.
+        word_count.invalidate_from(1);
+        assert_eq!(word_count.count("amp\ntext editor for the terminal\n"), 8);
+
+        word_count.invalidate_from(0);
+        assert_eq!(word_count.count("amp\ntext editor for the terminal\n"), 6);
+    }
+
+    #[test]
+    fn word_count_summary_rounds_reading_time_up() {
+        assert_eq!(word_count_summary(0), "0 words, 1 min read");
+        assert_eq!(word_count_summary(1), "1 word, 1 min read");
+        assert_eq!(word_count_summary(201), "201 words, 2 min read");
+    }
+}
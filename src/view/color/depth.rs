@@ -0,0 +1,126 @@
+use crate::view::color::RGBColor;
+use std::env;
+
+// The colors of the 16-color palette, as xterm displays them by default.
+const ANSI_COLORS: [(u8, u8, u8); 16] = [
+    (0, 0, 0), (205, 0, 0), (0, 205, 0), (205, 205, 0),
+    (0, 0, 238), (205, 0, 205), (0, 205, 205), (229, 229, 229),
+    (127, 127, 127), (255, 0, 0), (0, 255, 0), (255, 255, 0),
+    (92, 92, 255), (255, 0, 255), (0, 255, 255), (255, 255, 255),
+];
+
+// The intensities of each component in the 256-color palette's 6x6x6 cube.
+const CUBE_LEVELS: [u8; 6] = [0, 95, 135, 175, 215, 255];
+
+/// The range of colors a terminal can display, which
+/// theme colors are mapped to the nearest of, if needed.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ColorDepth {
+    /// 24-bit color, displaying theme colors as-is.
+    TrueColor,
+    Ansi256,
+    Ansi16,
+}
+
+impl ColorDepth {
+    /// Detects the terminal's color depth from its environment: `COLORTERM`
+    /// advertises 24-bit color support, and `TERM` names 256-color terminals.
+    pub fn detect() -> ColorDepth {
+        ColorDepth::from_env(env::var("COLORTERM").ok().as_deref(), env::var("TERM").ok().as_deref())
+    }
+
+    fn from_env(colorterm: Option<&str>, term: Option<&str>) -> ColorDepth {
+        match (colorterm, term) {
+            (Some("truecolor"), _) | (Some("24bit"), _) => ColorDepth::TrueColor,
+            (_, Some(term)) if term.contains("256color") => ColorDepth::Ansi256,
+            _ => ColorDepth::Ansi16,
+        }
+    }
+
+    /// The escape sequence that sets the foreground color.
+    pub fn foreground(self, color: RGBColor) -> String {
+        match self {
+            ColorDepth::TrueColor => format!("\x1b[38;2;{};{};{}m", color.0, color.1, color.2),
+            ColorDepth::Ansi256 => format!("\x1b[38;5;{}m", ansi_256(color)),
+            ColorDepth::Ansi16 => match ansi_16(color) {
+                index if index < 8 => format!("\x1b[{}m", 30 + index),
+                index => format!("\x1b[{}m", 90 + index - 8),
+            },
+        }
+    }
+
+    /// The escape sequence that sets the background color.
+    pub fn background(self, color: RGBColor) -> String {
+        match self {
+            ColorDepth::TrueColor => format!("\x1b[48;2;{};{};{}m", color.0, color.1, color.2),
+            ColorDepth::Ansi256 => format!("\x1b[48;5;{}m", ansi_256(color)),
+            ColorDepth::Ansi16 => match ansi_16(color) {
+                index if index < 8 => format!("\x1b[{}m", 40 + index),
+                index => format!("\x1b[{}m", 100 + index - 8),
+            },
+        }
+    }
+}
+
+// Finds the nearest of the 256-color palette's cube and grayscale ramp colors.
+// Its first 16 colors are skipped, as terminals often change them.
+fn ansi_256(color: RGBColor) -> u8 {
+    let level = |component: u8| {
+        (0..CUBE_LEVELS.len())
+            .min_by_key(|&index| (i32::from(CUBE_LEVELS[index]) - i32::from(component)).abs())
+            .unwrap_or(0)
+    };
+    let (r, g, b) = (level(color.0), level(color.1), level(color.2));
+    let cube = (CUBE_LEVELS[r], CUBE_LEVELS[g], CUBE_LEVELS[b]);
+
+    // The ramp runs from 8 to 238, in steps of 10.
+    let average = (u32::from(color.0) + u32::from(color.1) + u32::from(color.2)) / 3;
+    let gray_index = ((average.max(8) - 8 + 5) / 10).min(23) as u8;
+    let gray_level = 8 + gray_index * 10;
+
+    if distance(color, (gray_level, gray_level, gray_level)) < distance(color, cube) {
+        232 + gray_index
+    } else {
+        16 + (36 * r + 6 * g + b) as u8
+    }
+}
+
+fn ansi_16(color: RGBColor) -> u8 {
+    (0..ANSI_COLORS.len())
+        .min_by_key(|&index| distance(color, ANSI_COLORS[index]))
+        .unwrap_or(0) as u8
+}
+
+fn distance(color: RGBColor, other: (u8, u8, u8)) -> u32 {
+    let component = |a: u8, b: u8| (i32::from(a) - i32::from(b)).pow(2) as u32;
+
+    component(color.0, other.0) + component(color.1, other.1) + component(color.2, other.2)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ColorDepth;
+    use crate::view::color::RGBColor;
+
+    #[test]
+    fn from_env_prefers_advertised_true_color_support() {
+        assert_eq!(ColorDepth::from_env(Some("truecolor"), Some("xterm")), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(Some("24bit"), None), ColorDepth::TrueColor);
+        assert_eq!(ColorDepth::from_env(None, Some("screen-256color")), ColorDepth::Ansi256);
+        assert_eq!(ColorDepth::from_env(None, Some("xterm")), ColorDepth::Ansi16);
+        assert_eq!(ColorDepth::from_env(None, None), ColorDepth::Ansi16);
+    }
+
+    #[test]
+    fn colors_are_mapped_to_the_nearest_available_color() {
+        let color = RGBColor(0, 43, 54);
+        assert_eq!(ColorDepth::TrueColor.foreground(color), "\x1b[38;2;0;43;54m");
+        assert_eq!(ColorDepth::Ansi256.background(color), "\x1b[48;5;234m");
+        assert_eq!(ColorDepth::Ansi16.background(color), "\x1b[40m");
+
+        // Grays use the grayscale ramp, rather than the cube.
+        assert_eq!(ColorDepth::Ansi256.foreground(RGBColor(48, 48, 48)), "\x1b[38;5;236m");
+        assert_eq!(ColorDepth::Ansi256.foreground(RGBColor(255, 0, 0)), "\x1b[38;5;196m");
+        assert_eq!(ColorDepth::Ansi16.foreground(RGBColor(250, 10, 10)), "\x1b[91m");
+    }
+}
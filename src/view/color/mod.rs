@@ -9,6 +9,10 @@ pub use self::colors::Colors;
 mod map;
 pub use self::map::ColorMap;
 
+// Define and export the color ranges theme colors are displayed with.
+mod depth;
+pub use self::depth::ColorDepth;
+
 // Re-export external RGB/RGBA types.
 pub use self::termion::color::Rgb as RGBColor;
 use syntect::highlighting::Color as RGBAColor;
@@ -0,0 +1,104 @@
+use crate::view::Area;
+
+// Narrower screens show previews beneath the list, rather than beside it.
+const SPLIT_MIN_WIDTH: usize = 100;
+
+/// A filterable list of entries, alongside a preview of the selected one and
+/// the actions that can be taken on it. Modes that list things (e.g. search
+/// results or diagnostics) describe them with this, and leave their layout
+/// to the presenter.
+pub struct ListPreview<'a> {
+    pub entries: Vec<String>,
+    pub selected_index: usize,
+
+    /// The character indices of each entry that matched the filter's query.
+    pub matches: Vec<Vec<usize>>,
+
+    /// Shown in place of the entries (e.g. when there aren't any yet).
+    pub message: Option<String>,
+
+    /// The filter's input, and the cursor's column within it.
+    pub query: (String, usize),
+    pub insert: bool,
+    pub preview: Option<&'a str>,
+
+    /// The keys that act on the selection, and what they do (e.g. "enter" and "open").
+    pub actions: Vec<(String, &'static str)>,
+
+    /// The number of lines reserved for entries.
+    pub list_height: usize,
+}
+
+/// Where each part of a list preview is drawn.
+#[derive(Debug, PartialEq)]
+pub struct ListPreviewLayout {
+    pub list: Area,
+    pub query: Area,
+    pub preview: Option<Area>,
+
+    /// The column separating the list from a preview beside it.
+    pub divider: Option<usize>,
+}
+
+impl ListPreviewLayout {
+    /// Lays out a list of the specified height within the available space
+    /// (which excludes the status line). On wide screens, previews are shown
+    /// beside the list and query, using the full height; otherwise, they're
+    /// shown beneath the query, using whatever space is left.
+    pub fn new(width: usize, height: usize, list_height: usize, preview: bool) -> ListPreviewLayout {
+        if preview && width >= SPLIT_MIN_WIDTH {
+            let list_width = width / 2;
+
+            return ListPreviewLayout {
+                list: Area { top: 0, left: 0, width: list_width, height: list_height },
+                query: Area { top: list_height, left: 0, width: list_width, height: 1 },
+                preview: Some(Area {
+                    top: 0,
+                    left: list_width + 1,
+                    width: width - list_width - 1,
+                    height,
+                }),
+                divider: Some(list_width),
+            };
+        }
+
+        let preview_height = height.saturating_sub(list_height + 1);
+        ListPreviewLayout {
+            list: Area { top: 0, left: 0, width, height: list_height },
+            query: Area { top: list_height, left: 0, width, height: 1 },
+            preview: if preview && preview_height > 0 {
+                Some(Area { top: list_height + 1, left: 0, width, height: preview_height })
+            } else {
+                None
+            },
+            divider: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ListPreviewLayout;
+    use crate::view::Area;
+
+    #[test]
+    fn previews_are_beside_the_list_on_wide_screens() {
+        assert_eq!(ListPreviewLayout::new(120, 40, 5, true), ListPreviewLayout {
+            list: Area { top: 0, left: 0, width: 60, height: 5 },
+            query: Area { top: 5, left: 0, width: 60, height: 1 },
+            preview: Some(Area { top: 0, left: 61, width: 59, height: 40 }),
+            divider: Some(60),
+        });
+    }
+
+    #[test]
+    fn previews_are_beneath_the_query_on_narrow_screens() {
+        assert_eq!(ListPreviewLayout::new(80, 40, 5, true), ListPreviewLayout {
+            list: Area { top: 0, left: 0, width: 80, height: 5 },
+            query: Area { top: 5, left: 0, width: 80, height: 1 },
+            preview: Some(Area { top: 6, left: 0, width: 80, height: 34 }),
+            divider: None,
+        });
+        assert_eq!(ListPreviewLayout::new(120, 40, 5, false).preview, None);
+    }
+}
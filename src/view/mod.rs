@@ -3,44 +3,108 @@ pub mod terminal;
 mod buffer;
 mod data;
 mod event_listener;
+mod list_preview;
+mod overlay;
+mod pane;
 mod presenter;
+mod sidebar;
 mod style;
 mod theme_loader;
 
 // Published API
 pub use self::data::StatusLineData;
-pub use self::buffer::{LexemeMapper, MappedLexeme};
+pub use self::list_preview::{ListPreview, ListPreviewLayout};
+pub use self::overlay::Overlay;
+pub use self::pane::{Area, SplitDirection};
+pub use self::buffer::{word_count_summary, Annotation, AnnotationPosition, AnnotationProvider, LexemeMapper, MappedLexeme};
 pub use self::style::Style;
-pub use self::color::{Colors, RGBColor};
+pub use self::color::{ColorDepth, Colors, RGBColor};
 pub use self::presenter::Presenter;
 pub use self::terminal::*;
 
+use crate::commands::Command;
 use crate::errors::*;
 use crate::input::Key;
-use crate::models::application::{Event, Preferences};
-use self::buffer::{RenderCache, RenderState};
-use self::buffer::ScrollableRegion;
+use crate::models::application::{Bell, Event, FileTree, LocalSettings, Narrowing, Preferences, Profiler};
+use crate::util;
+use crate::util::indentation::Indentation;
+use self::buffer::{Annotations, BufferRenderer, RenderCache, RenderState};
+use self::buffer::{LineWrapping, ScrollableRegion, WordCount};
 use self::event_listener::EventListener;
-use scribe::buffer::Buffer;
-use std::cmp;
+use self::pane::{Panes, ScrollLock, Viewport};
+use self::sidebar::Sidebar;
+use scribe::buffer::{Buffer, LineRange, Position, Range};
+use scribe::util::LineIterator;
+use std::borrow::Cow;
+use std::{cmp, mem};
 use std::collections::HashMap;
 use std::rc::Rc;
 use std::cell::RefCell;
 use std::ops::Drop;
+use std::path::PathBuf;
 use std::sync::mpsc::{self, Sender, SyncSender};
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use self::theme_loader::ThemeLoader;
-use syntect::highlighting::ThemeSet;
+use syntect::highlighting::{Theme, ThemeSet};
 
 const RENDER_CACHE_FREQUENCY: usize = 100;
 
+type ChangeListeners = Rc<RefCell<Vec<Box<dyn Fn(Position)>>>>;
+
+// How long the status line stays inverted when flashed.
+const FLASH_DURATION: Duration = Duration::from_millis(100);
+
+// How long a line stays highlighted after jumping to it.
+const LINE_HIGHLIGHT_DURATION: Duration = Duration::from_millis(500);
+
 pub struct View {
     terminal: Arc<Box<Terminal + Sync + Send + 'static>>,
     scrollable_regions: HashMap<usize, ScrollableRegion>,
     render_caches: HashMap<usize, Rc<RefCell<HashMap<usize, RenderState>>>>,
+    annotations: HashMap<usize, Rc<RefCell<Annotations>>>,
+    word_counts: HashMap<usize, Rc<RefCell<WordCount>>>,
+
+    /// Hashes of buffers' content as of when they were last opened or
+    /// saved, along with the path they were opened from or saved to.
+    saved_content: HashMap<usize, (Option<PathBuf>, u64)>,
+
+    /// Whether buffers' content differed from what was saved when it was
+    /// last compared, cleared whenever they're edited (see `modified`).
+    content_comparisons: HashMap<usize, Rc<RefCell<Option<bool>>>>,
+
+    /// The revision of buffers' content (see `revision`), and the
+    /// latest revision given to any buffer.
+    revisions: HashMap<usize, Rc<RefCell<usize>>>,
+    latest_revision: Rc<RefCell<usize>>,
+
+    /// Listeners notified of changes to buffers' content (see `on_change`).
+    change_listeners: HashMap<usize, ChangeListeners>,
+
+    /// Indentation detected in buffers' content when they were opened.
+    indentation: HashMap<usize, Indentation>,
+
+    /// Buffers narrowed to some of their lines, hiding the rest.
+    narrowings: HashMap<usize, Narrowing>,
+
+    /// Preferences changed for individual buffers.
+    local_settings: HashMap<usize, LocalSettings>,
+    panes: Panes,
+    pane_cells: Vec<(Position, Cell<'static>)>,
+
+    /// The file tree drawn beside the panes, if it's shown.
+    sidebar: Option<Sidebar>,
     pub theme_set: ThemeSet,
     preferences: Rc<RefCell<Preferences>>,
     pub last_key: Option<Key>,
+    pub profiler: Profiler,
+    flash_until: Option<Instant>,
+
+    /// A buffer (ID) and line highlighted until the specified time.
+    line_highlight: Option<(usize, usize, Instant)>,
+
+    /// The keys that continue a partially typed sequence, drawn over the buffer.
+    key_hints: Vec<String>,
     event_channel: Sender<Event>,
     event_listener_killswitch: SyncSender<()>
 }
@@ -48,6 +112,7 @@ pub struct View {
 impl View {
     pub fn new(preferences: Rc<RefCell<Preferences>>, event_channel: Sender<Event>) -> Result<View> {
         let terminal = build_terminal().chain_err(|| "Failed to initialize terminal")?;
+        terminal.set_mouse_reporting(preferences.borrow().mouse());
         let theme_path = preferences.borrow().theme_path()?;
         let theme_set = ThemeLoader::new(theme_path).load()?;
 
@@ -57,9 +122,26 @@ impl View {
         Ok(View {
             terminal,
             last_key: None,
+            profiler: Profiler::default(),
+            flash_until: None,
+            line_highlight: None,
+            key_hints: Vec::new(),
             preferences,
             scrollable_regions: HashMap::new(),
             render_caches: HashMap::new(),
+            annotations: HashMap::new(),
+            word_counts: HashMap::new(),
+            saved_content: HashMap::new(),
+            content_comparisons: HashMap::new(),
+            revisions: HashMap::new(),
+            latest_revision: Rc::new(RefCell::new(0)),
+            change_listeners: HashMap::new(),
+            indentation: HashMap::new(),
+            narrowings: HashMap::new(),
+            local_settings: HashMap::new(),
+            panes: Panes::default(),
+            pane_cells: Vec::new(),
+            sidebar: None,
             theme_set,
             event_channel,
             event_listener_killswitch: killswitch_tx
@@ -70,6 +152,249 @@ impl View {
         Presenter::new(self)
     }
 
+    /// The theme selected in the user's preferences.
+    pub fn theme(&self) -> Result<Theme> {
+        let preferences = self.preferences.borrow();
+        let theme_name = preferences.theme();
+        let theme = self.theme_set.themes
+            .get(theme_name)
+            .ok_or_else(|| format!("Couldn't find \"{}\" theme", theme_name))?;
+
+        Ok(theme.clone())
+    }
+
+    pub fn pane_count(&self) -> usize {
+        self.panes.count()
+    }
+
+    pub fn split_pane(&mut self, direction: SplitDirection) {
+        self.panes.split(direction);
+    }
+
+    /// Focuses the next pane, returning the ID of the buffer it displays.
+    pub fn focus_next_pane(&mut self) -> Option<usize> {
+        self.panes.focus_next()
+    }
+
+    /// Focuses the pane drawn at the screen position, if it isn't already
+    /// focused, returning the ID of the buffer it displays.
+    pub fn focus_pane_at(&mut self, position: &Position) -> Option<usize> {
+        let pane = self.panes
+            .areas(self.screen_area())
+            .iter()
+            .position(|area| area.contains(position))
+            .filter(|&pane| pane != self.panes.focused())?;
+
+        self.panes.focus(pane)
+    }
+
+    /// Closes the focused pane, returning false if it's the only one.
+    pub fn close_pane(&mut self) -> bool {
+        self.panes.close()
+    }
+
+    /// The ID of the buffer displayed in the focused pane.
+    pub fn focused_pane_buffer(&self) -> Option<usize> {
+        self.panes.buffer(self.panes.focused())
+    }
+
+    pub fn set_focused_pane_buffer(&mut self, buffer_id: Option<usize>) {
+        self.panes.set_buffer(buffer_id);
+    }
+
+    /// Records the buffer's cursor and scroll position as where the focused
+    /// pane was left, to be restored once it's focused again. This lets
+    /// panes display different parts of the same buffer.
+    pub fn store_viewport(&mut self, buffer: &Buffer) -> Result<()> {
+        let cursor = *buffer.cursor;
+        let region = self.get_region(buffer)?;
+        let viewport = Viewport {
+            cursor,
+            line_offset: region.line_offset(),
+            column_offset: region.column_offset(),
+        };
+        self.panes.set_viewport(viewport);
+
+        Ok(())
+    }
+
+    /// Locks the focused pane's scrolling to the next pane's, keeping the
+    /// distance between them (e.g. to compare two similar sections),
+    /// or unlocks them if they're locked. Returns whether they're locked.
+    pub fn toggle_scroll_lock(&mut self, buffer: &Buffer) -> Result<bool> {
+        if self.panes.scroll_lock().is_some() {
+            self.panes.set_scroll_lock(None);
+            return Ok(false);
+        }
+        if self.panes.count() == 1 {
+            bail!("There's only one pane");
+        }
+
+        let focused = self.panes.focused();
+        let other = (focused + 1) % self.panes.count();
+        let line_offset = self.get_region(buffer)?.line_offset();
+        let other_line_offset = self.panes
+            .viewport(other)
+            .map(|viewport| viewport.line_offset)
+            .ok_or("The next pane's scroll position isn't known yet")?;
+        self.panes.set_scroll_lock(Some(ScrollLock {
+            panes: (focused, other),
+            offset: other_line_offset as isize - line_offset as isize,
+        }));
+
+        Ok(true)
+    }
+
+    /// Returns the buffer's cursor and scroll position to where
+    /// the focused pane was left, if it's been recorded.
+    pub fn restore_viewport(&mut self, buffer: &mut Buffer) -> Result<()> {
+        let viewport = match self.panes.viewport(self.panes.focused()) {
+            Some(viewport) => viewport,
+            None => return Ok(()),
+        };
+        buffer.cursor.move_to(viewport.cursor);
+        self.get_region(buffer)?.scroll_to(viewport.line_offset, viewport.column_offset);
+
+        Ok(())
+    }
+
+    /// The section of the screen used to display the current buffer.
+    pub fn focused_pane_area(&self) -> Area {
+        self.panes.areas(self.screen_area())[self.panes.focused()]
+    }
+
+    /// The unfocused panes, alongside the IDs of the buffers they display.
+    pub fn inactive_panes(&self) -> Vec<(usize, usize)> {
+        let focused = self.panes.focused();
+
+        (0..self.panes.count())
+            .filter(|&pane| pane != focused)
+            .filter_map(|pane| self.panes.buffer(pane).map(|id| (pane, id)))
+            .collect()
+    }
+
+    /// Lines dividing panes, which presenters draw alongside their contents.
+    pub fn pane_separators(&self) -> Vec<(SplitDirection, Area)> {
+        self.panes.separators(self.screen_area())
+    }
+
+    /// Discards previously rendered unfocused pane contents.
+    pub fn clear_panes(&mut self) {
+        self.pane_cells.clear();
+    }
+
+    /// Renders a buffer into an unfocused pane, where the pane was last left
+    /// in it (see `store_viewport`), scrolled along with the focused pane if
+    /// they're locked. Its contents are retained and drawn by presenters,
+    /// which only draw the current buffer themselves.
+    pub fn render_pane(&mut self, buffer: &mut Buffer, pane: usize) -> Result<()> {
+        let focused_line_offset = self.panes
+            .buffer(self.panes.focused())
+            .and_then(|id| self.scrollable_regions.get(&id))
+            .map(|region| region.line_offset());
+        if let Some(line_offset) = focused_line_offset {
+            self.panes.follow_scroll_lock(pane, line_offset);
+        }
+        let viewport = self.panes.viewport(pane);
+        let cursor = *buffer.cursor;
+        if let Some(viewport) = viewport {
+            buffer.cursor.move_to(viewport.cursor);
+        }
+        let result = self.render_pane_content(buffer, pane, viewport);
+        buffer.cursor.move_to(cursor);
+
+        result
+    }
+
+    fn render_pane_content(&mut self, buffer: &Buffer, pane: usize, viewport: Option<Viewport>) -> Result<()> {
+        let area = self.panes.areas(self.screen_area())[pane];
+        let theme = self.theme()?;
+        let region = self.get_region_for_area(buffer, area)?;
+        let (scroll_offset, column_offset) = match viewport {
+            Some(viewport) => (viewport.line_offset, viewport.column_offset),
+            None => (region.line_offset(), region.column_offset()),
+        };
+        let annotations = self.get_annotations(buffer);
+        let data = buffer.data();
+        let mut terminal_buffer = TerminalBuffer::new(self.terminal.width(), self.terminal.height());
+        {
+            let preferences = self.preferences.borrow();
+            let mut renderer = BufferRenderer::new(
+                buffer,
+                None,
+                Some(&annotations),
+                scroll_offset,
+                &**self.terminal,
+                &theme,
+                &preferences,
+                self.get_render_cache(buffer)?,
+                &mut terminal_buffer
+            );
+            renderer.set_area(area);
+            if let Some(settings) = self.local_settings(buffer) {
+                renderer.set_local_settings(settings);
+            }
+            renderer.set_column_offset(column_offset);
+            renderer.render(LineIterator::new(&data), None)?;
+        }
+
+        // The rendered content borrows from the buffer data, so we
+        // take ownership of it to keep it around beyond this frame.
+        self.pane_cells.extend(
+            terminal_buffer
+                .iter()
+                .filter(|(position, _)| area.contains(position))
+                .map(|(position, cell)| {
+                    (position, Cell{
+                        content: Cow::Owned(cell.content.to_string()),
+                        colors: cell.colors,
+                        style: cell.style,
+                    })
+                })
+        );
+
+        Ok(())
+    }
+
+    /// Shows the file tree sidebar, up to the specified width,
+    /// narrowing the panes to make room for it.
+    pub fn show_sidebar(&mut self, width: usize) {
+        self.sidebar = Some(Sidebar::new(width));
+    }
+
+    pub fn hide_sidebar(&mut self) {
+        self.sidebar = None;
+    }
+
+    /// Renders the file tree into the sidebar, if it's shown. Its contents
+    /// are retained and drawn by presenters, alongside those of the panes.
+    pub fn render_sidebar(&mut self, tree: &FileTree, focused: bool) {
+        let area = self.sidebar_area();
+        if let (Some(sidebar), Some(area)) = (self.sidebar.as_mut(), area) {
+            sidebar.render(tree, area, focused);
+        }
+    }
+
+    // The section of the screen used by the sidebar, if it's shown.
+    fn sidebar_area(&self) -> Option<Area> {
+        self.sidebar.as_ref().map(|sidebar| {
+            sidebar.area(self.terminal.width(), self.terminal.height().saturating_sub(1))
+        })
+    }
+
+    // The screen, less the status line and the sidebar
+    // (along with the line dividing it from the panes).
+    fn screen_area(&self) -> Area {
+        let left = self.sidebar_area().map(|area| area.width + 1).unwrap_or(0);
+
+        Area{
+            top: 0,
+            left,
+            width: self.terminal.width().saturating_sub(left),
+            height: self.terminal.height().saturating_sub(1),
+        }
+    }
+
     ///
     /// Scrollable region delegation methods.
     ///
@@ -86,12 +411,61 @@ impl View {
         Ok(())
     }
 
+    /// How the buffer's lines are wrapped in the pane displaying it,
+    /// along with the width available to their content.
+    pub fn line_wrapping(&mut self, buffer: &Buffer) -> Result<(LineWrapping, usize)> {
+        Ok(self.get_region(buffer)?.line_wrapping(buffer))
+    }
+
+    /// The position in the buffer drawn at (or closest to) the screen
+    /// position, if it's within the focused pane, which displays the buffer.
+    pub fn buffer_position_at(&mut self, buffer: &Buffer, position: &Position) -> Result<Option<Position>> {
+        let area = self.focused_pane_area();
+        if !area.contains(position) {
+            return Ok(None);
+        }
+        let position = Position {
+            line: position.line - area.top,
+            offset: position.offset - area.left,
+        };
+
+        Ok(Some(self.get_region(buffer)?.buffer_position(buffer, position)))
+    }
+
     pub fn scroll_up(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
         self.get_region(buffer)?.scroll_up(amount);
 
         Ok(())
     }
 
+    pub fn scroll_left(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
+        self.get_region(buffer)?.scroll_left(amount);
+
+        Ok(())
+    }
+
+    /// Scrolls long lines to the right, when they aren't wrapped.
+    pub fn scroll_right(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
+        let region = self.get_region(buffer)?;
+        let (line_wrapping, content_width) = region.line_wrapping(buffer);
+        if line_wrapping.width(content_width).is_some() {
+            bail!("Lines are wrapped; disable line wrapping to scroll horizontally");
+        }
+
+        // Limit scrolling to the end of the buffer's widest line.
+        let max = buffer
+            .data()
+            .lines()
+            .map(|line| line_wrapping.position(line, usize::MAX, content_width).1)
+            .max()
+            .unwrap_or(0)
+            .saturating_sub(region.column_offset());
+
+        region.scroll_right(cmp::min(amount, max));
+
+        Ok(())
+    }
+
     pub fn scroll_down(&mut self, buffer: &Buffer, amount: usize) -> Result<()> {
         let current_offset = self.get_region(buffer)?.line_offset();
         let line_count = buffer.line_count();
@@ -121,19 +495,80 @@ impl View {
     pub fn forget_buffer(&mut self, buffer: &Buffer) -> Result<()> {
         self.scrollable_regions.remove(&buffer_key(buffer)?);
         self.render_caches.remove(&buffer_key(buffer)?);
+        self.annotations.remove(&buffer_key(buffer)?);
+        self.word_counts.remove(&buffer_key(buffer)?);
+        self.saved_content.remove(&buffer_key(buffer)?);
+        self.content_comparisons.remove(&buffer_key(buffer)?);
+        self.revisions.remove(&buffer_key(buffer)?);
+        self.change_listeners.remove(&buffer_key(buffer)?);
+        self.indentation.remove(&buffer_key(buffer)?);
+        self.narrowings.remove(&buffer_key(buffer)?);
+        self.local_settings.remove(&buffer_key(buffer)?);
+        self.panes.forget_buffer(buffer_key(buffer)?);
 
         Ok(())
     }
 
+    /// Approximates the memory used by the buffer's highlighting cache, in bytes.
+    pub fn render_cache_size(&self, buffer_id: usize) -> usize {
+        self.render_caches
+            .get(&buffer_id)
+            .map(|cache| cache.borrow().len() * mem::size_of::<(usize, RenderState)>())
+            .unwrap_or(0)
+    }
+
+    /// Empties the buffer's highlighting cache; it's rebuilt when rendered.
+    pub fn clear_render_cache(&mut self, buffer_id: usize) {
+        if let Some(cache) = self.render_caches.get(&buffer_id) {
+            cache.borrow_mut().clear();
+        }
+    }
+
     // Tries to fetch a scrollable region for the specified buffer,
-    // inserting (and returning a reference to) a new one if not.
+    // inserting (and returning a reference to) a new one if not. The
+    // region is sized to fit the pane displaying the buffer; if none
+    // do, it's assumed it's about to be displayed in the focused pane.
     fn get_region(&mut self, buffer: &Buffer) -> Result<&mut ScrollableRegion> {
-        Ok(self.scrollable_regions
+        let id = buffer_key(buffer)?;
+        let areas = self.panes.areas(self.screen_area());
+        let focused = self.panes.focused();
+        let pane = if self.panes.buffer(focused) == Some(id) {
+            focused
+        } else {
+            (0..areas.len()).find(|&pane| self.panes.buffer(pane) == Some(id)).unwrap_or(focused)
+        };
+
+        self.get_region_for_area(buffer, areas[pane])
+    }
+
+    fn get_region_for_area(&mut self, buffer: &Buffer, area: Area) -> Result<&mut ScrollableRegion> {
+        let region = self.scrollable_regions
             .entry(buffer_key(buffer)?)
             .or_insert(
                 ScrollableRegion::new(self.terminal.clone())
-            )
-        )
+            );
+        region.resize(area.width, area.height);
+        let default_settings = LocalSettings::default();
+        let settings = buffer.id.and_then(|id| self.local_settings.get(&id)).unwrap_or(&default_settings);
+        let preferences = self.preferences.borrow();
+        region.set_line_numbers(settings.line_numbers(&preferences));
+        region.set_line_wrapping(LineWrapping::new(&preferences, settings, buffer.path.as_ref()));
+
+        Ok(region)
+    }
+
+    /// The buffer's word count, if it's shown for the buffer's type (see
+    /// `Preferences::word_count`). Only lines changed since the last count
+    /// are recounted; the data must be the buffer's current content.
+    pub fn word_count(&self, buffer: &Buffer, data: &str) -> Option<usize> {
+        if !self.preferences.borrow().word_count(buffer.path.as_ref()) {
+            return None;
+        }
+
+        let word_count = self.word_counts.get(&buffer.id?)?;
+        let count = word_count.borrow_mut().count(data);
+
+        Some(count)
     }
 
     fn get_render_cache(&self, buffer: &Buffer) -> Result<&Rc<RefCell<HashMap<usize, RenderState>>>> {
@@ -144,6 +579,153 @@ impl View {
         Ok(cache)
     }
 
+    ///
+    /// Annotation (virtual text) methods.
+    ///
+
+    /// Replaces the annotations from the specified provider (e.g. the test
+    /// runner) for the buffer with the specified ID. These are keyed by ID,
+    /// rather than buffer, since they're often produced asynchronously, by
+    /// which time the buffer may no longer be current.
+    pub fn set_annotations(&mut self, buffer_id: usize, provider: AnnotationProvider, annotations: Vec<Annotation>) {
+        self.annotations
+            .entry(buffer_id)
+            .or_default()
+            .borrow_mut()
+            .set(provider, annotations);
+    }
+
+    pub fn clear_annotations(&mut self, buffer_id: usize, provider: AnnotationProvider) {
+        if let Some(annotations) = self.annotations.get(&buffer_id) {
+            annotations.borrow_mut().clear(provider);
+        }
+    }
+
+    fn get_annotations(&self, buffer: &Buffer) -> Vec<Annotation> {
+        buffer.id
+            .and_then(|id| self.annotations.get(&id))
+            .map(|annotations| annotations.borrow().all())
+            .unwrap_or_default()
+    }
+
+    /// Rings the terminal's bell.
+    pub fn bell(&self) {
+        self.terminal.bell();
+    }
+
+    /// Signals an error or boundary condition using the configured bells.
+    pub fn signal(&mut self) {
+        let bells = self.preferences.borrow().bell();
+        for bell in bells {
+            match bell {
+                Bell::Audible => self.bell(),
+                Bell::Flash => self.flash(),
+                // Errors are already shown in the status line.
+                Bell::Visual => (),
+            }
+        }
+    }
+
+    /// Inverts the status line until the flash duration has passed.
+    pub fn flash(&mut self) {
+        self.flash_until = Some(Instant::now() + FLASH_DURATION);
+    }
+
+    /// How long the current flash has left, if one is in progress. The
+    /// view needs to be rendered again once it's over, to restore it.
+    pub fn flash_remaining(&self) -> Option<Duration> {
+        self.flash_until.and_then(|until| until.checked_duration_since(Instant::now()))
+    }
+
+    /// Hides the buffer's content outside of the lines (inclusively),
+    /// protecting it from editing until the buffer is widened.
+    pub fn narrow(&mut self, buffer: &Buffer, lines: &LineRange) -> Result<()> {
+        self.narrowings.insert(buffer_key(buffer)?, Narrowing::new(&buffer.data(), lines));
+
+        Ok(())
+    }
+
+    /// Shows a narrowed buffer's hidden content again,
+    /// returning false if it wasn't narrowed.
+    pub fn widen(&mut self, buffer: &Buffer) -> bool {
+        buffer.id.and_then(|id| self.narrowings.remove(&id)).is_some()
+    }
+
+    pub fn narrowing(&self, buffer: &Buffer) -> Option<&Narrowing> {
+        buffer.id.and_then(|id| self.narrowings.get(&id))
+    }
+
+    /// Changes a preference for the buffer alone (see `LocalSettings::set`).
+    pub fn set_local_setting(&mut self, buffer: &Buffer, setting: &str, value: &str) -> Result<()> {
+        self.local_settings
+            .entry(buffer_key(buffer)?)
+            .or_insert_with(LocalSettings::default)
+            .set(setting, value)
+    }
+
+    /// Reverts the buffer's changed preferences, returning
+    /// them, or None if none of them were changed.
+    pub fn reset_local_settings(&mut self, buffer: &Buffer) -> Option<LocalSettings> {
+        buffer.id
+            .and_then(|id| self.local_settings.remove(&id))
+            .filter(|settings| !settings.is_empty())
+    }
+
+    /// The preferences changed for the buffer alone, if any.
+    pub fn local_settings(&self, buffer: &Buffer) -> Option<&LocalSettings> {
+        buffer.id
+            .and_then(|id| self.local_settings.get(&id))
+            .filter(|settings| !settings.is_empty())
+    }
+
+    /// Briefly highlights one of the buffer's lines (e.g. after jumping to it).
+    pub fn highlight_line(&mut self, buffer: &Buffer, line: usize) {
+        if let Some(id) = buffer.id {
+            self.line_highlight = Some((id, line, Instant::now() + LINE_HIGHLIGHT_DURATION));
+        }
+    }
+
+    /// The buffer's temporarily highlighted line, if it hasn't faded yet.
+    pub fn line_highlight(&self, buffer: &Buffer) -> Option<Range> {
+        match self.line_highlight {
+            Some((id, line, until)) if buffer.id == Some(id) && until > Instant::now() => {
+                Some(Range::new(
+                    Position { line, offset: 0 },
+                    Position { line: line + 1, offset: 0 }
+                ))
+            }
+            _ => None,
+        }
+    }
+
+    /// How long until a flash or line highlight ends, if either is in
+    /// progress. The view needs to be rendered again then, to remove it.
+    pub fn redraw_remaining(&self) -> Option<Duration> {
+        let now = Instant::now();
+        let line_highlight = self.line_highlight.and_then(|(_, _, until)| until.checked_duration_since(now));
+
+        match (self.flash_remaining(), line_highlight) {
+            (Some(flash), Some(highlight)) => Some(flash.min(highlight)),
+            (flash, highlight) => flash.or(highlight),
+        }
+    }
+
+    pub fn set_key_hints(&mut self, hints: Vec<String>) {
+        self.key_hints = hints;
+    }
+
+    pub fn key_hints(&self) -> &[String] {
+        &self.key_hints
+    }
+
+    /// The keys bound to the command (on its own) in the mode, as they'd
+    /// be written in a keymap (e.g. "ctrl-d").
+    pub fn key_for(&self, mode: &str, command: Command) -> Option<String> {
+        self.preferences.borrow().keymap().sequence_for(mode, command).map(|sequence| {
+            sequence.iter().map(|key| key.to_string()).collect::<Vec<_>>().join(" ")
+        })
+    }
+
     pub fn suspend(&mut self) {
         let _ = self.event_listener_killswitch.send(());
         self.terminal.suspend();
@@ -152,12 +734,119 @@ impl View {
         self.event_listener_killswitch = killswitch_tx;
     }
 
+    pub fn terminal(&self) -> Arc<Box<Terminal + Sync + Send + 'static>> {
+        self.terminal.clone()
+    }
+
     pub fn last_key(&self) -> &Option<Key> {
         &self.last_key
     }
 
-    /// Sets up new buffers with render caches and cache invalidation callbacks.
+    /// Whether the buffer has unsaved changes. Its history alone reports
+    /// changes that have since been reverted by hand (e.g. an insertion
+    /// that was then deleted, or anything done after saving part-way through
+    /// an insert mode session), so its content is compared to what was saved,
+    /// too. The comparison's kept until the buffer's edited again.
+    pub fn modified(&self, buffer: &Buffer) -> bool {
+        if !buffer.modified() {
+            return false;
+        }
+
+        let comparison = buffer.id.and_then(|id| self.content_comparisons.get(&id));
+        if let Some(modified) = comparison.and_then(|comparison| *comparison.borrow()) {
+            return modified;
+        }
+
+        let saved_hash = buffer.id.and_then(|id| self.saved_content.get(&id)).map(|(_, hash)| *hash);
+        let modified = saved_hash != Some(util::content_hash(&buffer.data()));
+        if let Some(comparison) = comparison {
+            *comparison.borrow_mut() = Some(modified);
+        }
+
+        modified
+    }
+
+    /// Identifies the buffer's content, changing whenever the buffer's edited
+    /// (or initialized), so that anything derived from its content can tell
+    /// when it's out of date without comparing it. Revisions aren't reused,
+    /// even across buffers. Buffers that haven't been initialized have none.
+    pub fn revision(&self, buffer: &Buffer) -> Option<usize> {
+        buffer.id.and_then(|id| self.revisions.get(&id)).map(|revision| *revision.borrow())
+    }
+
+    /// Calls the listener with the position of each change made to the
+    /// buffer's content, until it's closed. Unlike the buffer's change
+    /// callback, listeners are kept when the buffer is initialized again.
+    pub fn on_change<F>(&mut self, buffer: &Buffer, listener: F)
+        where F: Fn(Position) + 'static
+    {
+        if let Some(id) = buffer.id {
+            self.change_listeners.entry(id).or_default().borrow_mut().push(Box::new(listener));
+        }
+    }
+
+    /// Whether the buffer's content was last saved to (or opened from) its
+    /// current path, and hasn't been changed since.
+    pub fn saved(&self, buffer: &Buffer) -> bool {
+        let saved_path = buffer.id.and_then(|id| self.saved_content.get(&id)).map(|(path, _)| path);
+
+        saved_path == Some(&buffer.path) && !self.modified(buffer)
+    }
+
+    /// Remembers the buffer's content as matching its file.
+    pub fn record_saved_content(&mut self, buffer: &Buffer) {
+        if let Some(id) = buffer.id {
+            self.saved_content.insert(id, (buffer.path.clone(), util::content_hash(&buffer.data())));
+            if let Some(comparison) = self.content_comparisons.get(&id) {
+                *comparison.borrow_mut() = Some(false);
+            }
+        }
+    }
+
+    /// The indentation detected in the buffer's content when it was opened,
+    /// if any (see `Preferences::detect_indentation`).
+    pub fn indentation(&self, buffer: &Buffer) -> Option<Indentation> {
+        buffer.id.and_then(|id| self.indentation.get(&id)).cloned()
+    }
+
+    /// The content inserted to indent the buffer's lines by one level,
+    /// matching its existing lines, or otherwise the user's preferences.
+    pub fn tab_content(&self, buffer: &Buffer) -> String {
+        let local_width = self.local_settings(buffer).and_then(LocalSettings::local_tab_width);
+        match (self.indentation(buffer), local_width) {
+            (Some(Indentation::Spaces(_)), Some(width)) => " ".repeat(width),
+            (Some(indentation), _) => indentation.content(),
+            (None, Some(width)) if self.preferences.borrow().soft_tabs(buffer.path.as_ref()) => " ".repeat(width),
+            (None, _) => self.preferences.borrow().tab_content(buffer.path.as_ref()),
+        }
+    }
+
+    /// The width of the buffer's indentation, as set for the
+    /// buffer alone, detected, or configured, in that order.
+    pub fn tab_width(&self, buffer: &Buffer) -> usize {
+        if let Some(width) = self.local_settings(buffer).and_then(LocalSettings::local_tab_width) {
+            return width;
+        }
+
+        match self.indentation(buffer) {
+            Some(Indentation::Spaces(width)) => width,
+            _ => self.preferences.borrow().tab_width(buffer.path.as_ref()),
+        }
+    }
+
+    /// Sets up new buffers with render caches and cache invalidation
+    /// callbacks, and detects the indentation of their content.
     pub fn initialize_buffer(&mut self, buffer: &mut Buffer) -> Result<()> {
+        // Buffers built up from scratch (rather than opened) don't match a file.
+        if !buffer.modified() {
+            self.record_saved_content(buffer);
+        }
+        if self.preferences.borrow().detect_indentation() {
+            if let Some(indentation) = Indentation::detect(&buffer.data()) {
+                self.indentation.insert(buffer_key(buffer)?, indentation);
+            }
+        }
+
         // Build and store a new render cache for the buffer.
         let render_cache = Rc::new(RefCell::new(HashMap::new()));
         self.render_caches.insert(
@@ -165,10 +854,46 @@ impl View {
             render_cache.clone()
         );
 
-        // Wire up the buffer's change callback to invalidate the render cache.
+        // Annotations may have been set before the buffer was initialized.
+        let annotations = self.annotations
+            .entry(buffer_key(buffer)?)
+            .or_default()
+            .clone();
+
+        let word_count = Rc::new(RefCell::new(WordCount::default()));
+        self.word_counts.insert(buffer_key(buffer)?, word_count.clone());
+
+        let content_comparison = Rc::new(RefCell::new(None));
+        self.content_comparisons.insert(buffer_key(buffer)?, content_comparison.clone());
+
+        *self.latest_revision.borrow_mut() += 1;
+        let revision = Rc::new(RefCell::new(*self.latest_revision.borrow()));
+        self.revisions.insert(buffer_key(buffer)?, revision.clone());
+        let latest_revision = self.latest_revision.clone();
+
+        // Listeners may have been added before the buffer was initialized.
+        let change_listeners = self.change_listeners
+            .entry(buffer_key(buffer)?)
+            .or_default()
+            .clone();
+
+        // Wire up the buffer's change callback to invalidate the render cache,
+        // word count, saved content comparison, and any annotations describing
+        // its content, to move it on to a new revision, and to notify listeners.
         buffer.change_callback = Some(
             Box::new(move |change_position| {
                 render_cache.borrow_mut().invalidate_from(change_position.line);
+                annotations.borrow_mut().invalidate_from(change_position.line);
+                word_count.borrow_mut().invalidate_from(change_position.line);
+                *content_comparison.borrow_mut() = None;
+
+                let mut latest_revision = latest_revision.borrow_mut();
+                *latest_revision += 1;
+                *revision.borrow_mut() = *latest_revision;
+
+                for listener in change_listeners.borrow().iter() {
+                    listener(change_position);
+                }
             })
         );
 
@@ -198,6 +923,45 @@ mod tests {
     use std::sync::mpsc;
     use syntect::highlighting::{Highlighter, ThemeSet};
     use crate::view::buffer::RenderState;
+    use crate::view::{Annotation, AnnotationPosition, AnnotationProvider, Colors, Style};
+    use crate::yaml::yaml::YamlLoader;
+
+    #[test]
+    fn signal_flashes_the_status_line_unless_disabled() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences.clone(), tx).unwrap();
+        view.signal();
+        assert!(view.flash_remaining().is_some());
+
+        let data = YamlLoader::load_from_str("bell: false").unwrap().into_iter().next();
+        *preferences.borrow_mut() = Preferences::new(data);
+        view.flash_until = None;
+        view.signal();
+        assert!(view.flash_remaining().is_none());
+    }
+
+    #[test]
+    fn scroll_right_stops_at_the_end_of_the_widest_line() {
+        let data = YamlLoader::load_from_str("line_wrapping: false").unwrap().into_iter().next();
+        let preferences = Rc::new(RefCell::new(Preferences::new(data)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences.clone(), tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(0);
+        buffer.insert("amp\nwidest line\n");
+
+        view.scroll_right(&buffer, 4).unwrap();
+        assert_eq!(view.get_region(&buffer).unwrap().column_offset(), 4);
+        view.scroll_right(&buffer, 20).unwrap();
+        assert_eq!(view.get_region(&buffer).unwrap().column_offset(), 11);
+        view.scroll_left(&buffer, 20).unwrap();
+        assert_eq!(view.get_region(&buffer).unwrap().column_offset(), 0);
+
+        // Wrapped lines can't be scrolled.
+        *preferences.borrow_mut() = Preferences::new(None);
+        assert!(view.scroll_right(&buffer, 4).is_err());
+    }
 
     #[test]
     fn scroll_down_prevents_scrolling_completely_beyond_buffer() {
@@ -252,6 +1016,82 @@ mod tests {
         assert!(view.render_caches.get(&buffer.id.unwrap()).is_some());
     }
 
+    #[test]
+    fn modified_compares_edited_content_to_what_was_saved() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        let buffer = workspace.current_buffer().unwrap();
+        view.initialize_buffer(buffer).unwrap();
+
+        buffer.insert("amp");
+        assert!(view.modified(buffer));
+        buffer.delete();
+        buffer.delete();
+        buffer.delete();
+        assert!(buffer.modified());
+        assert!(!view.modified(buffer));
+
+        view.forget_buffer(buffer).unwrap();
+        assert!(view.saved_content.is_empty());
+        assert!(view.content_comparisons.is_empty());
+    }
+
+    #[test]
+    fn revision_changes_when_buffers_are_edited_or_initialized() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        view.initialize_buffer(workspace.current_buffer().unwrap()).unwrap();
+        let other_revision = view.revision(workspace.current_buffer().unwrap());
+        workspace.add_buffer(Buffer::new());
+        let buffer = workspace.current_buffer().unwrap();
+        assert_eq!(view.revision(buffer), None);
+
+        view.initialize_buffer(buffer).unwrap();
+        let revision = view.revision(buffer);
+        assert!(revision.is_some());
+        assert_ne!(revision, other_revision);
+        buffer.cursor.move_down();
+        assert_eq!(view.revision(buffer), revision);
+
+        buffer.insert("amp");
+        let edited_revision = view.revision(buffer);
+        assert_ne!(edited_revision, revision);
+        view.initialize_buffer(buffer).unwrap();
+        assert_ne!(view.revision(buffer), edited_revision);
+    }
+
+    #[test]
+    fn on_change_notifies_listeners_until_buffers_are_forgotten() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut workspace = Workspace::new(Path::new(".")).unwrap();
+        workspace.add_buffer(Buffer::new());
+        let buffer = workspace.current_buffer().unwrap();
+        let changes = Rc::new(RefCell::new(Vec::new()));
+        let listener_changes = changes.clone();
+        view.on_change(buffer, move |position| listener_changes.borrow_mut().push(position));
+
+        // Listeners added beforehand are kept when the buffer's initialized, or reinitialized.
+        view.initialize_buffer(buffer).unwrap();
+        buffer.insert("amp\n");
+        view.initialize_buffer(buffer).unwrap();
+        buffer.cursor.move_down();
+        buffer.insert("editor");
+        assert_eq!(*changes.borrow(), vec![Position { line: 0, offset: 0 }, Position { line: 1, offset: 0 }]);
+
+        view.forget_buffer(buffer).unwrap();
+        view.initialize_buffer(buffer).unwrap();
+        buffer.insert("text");
+        assert_eq!(changes.borrow().len(), 2);
+    }
+
     #[test]
     fn initialize_buffer_sets_change_callback_to_clear_render_cache() {
         let preferences = Rc::new(RefCell::new(Preferences::new(None)));
@@ -307,5 +1147,56 @@ mod tests {
             vec![&0]
         );
     }
-}
 
+    #[test]
+    fn initialize_buffer_sets_change_callback_to_invalidate_annotations() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp\neditor\n");
+
+        let sign = |line| Annotation {
+            line,
+            content: String::from("●"),
+            style: Style::Default,
+            colors: Colors::Default,
+            position: AnnotationPosition::Gutter,
+        };
+        let tracked = AnnotationProvider { name: "tracked", priority: 0, invalidated_by_edits: false };
+        let stale = AnnotationProvider { name: "stale", priority: 1, invalidated_by_edits: true };
+        view.set_annotations(1, tracked, vec![sign(0), sign(1)]);
+        view.initialize_buffer(&mut buffer).unwrap();
+        view.set_annotations(1, stale, vec![sign(0), sign(1)]);
+
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        buffer.insert("amp ");
+
+        let lines: Vec<usize> = view.get_annotations(&buffer).iter().map(|a| a.line).collect();
+        assert_eq!(lines, vec![0, 0, 1]);
+    }
+
+    #[test]
+    fn word_count_is_kept_up_to_date_for_prose_buffers() {
+        let data = YamlLoader::load_from_str("word_count: true").unwrap();
+        let preferences = Rc::new(RefCell::new(Preferences::new(data.into_iter().next())));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut buffer = Buffer::new();
+        buffer.id = Some(1);
+        buffer.insert("amp is a\ntext editor\n");
+        view.initialize_buffer(&mut buffer).unwrap();
+        assert_eq!(view.word_count(&buffer, &buffer.data()), None);
+
+        buffer.path = Some(PathBuf::from("notes.md"));
+        assert_eq!(view.word_count(&buffer, &buffer.data()), Some(5));
+
+        buffer.cursor.move_to(Position{ line: 1, offset: 0 });
+        buffer.insert("modal ");
+        assert_eq!(view.word_count(&buffer, &buffer.data()), Some(6));
+
+        buffer.undo();
+        assert_eq!(view.word_count(&buffer, &buffer.data()), Some(5));
+    }
+}
@@ -0,0 +1,91 @@
+use crate::view::Area;
+use scribe::buffer::Position;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Longer lists are scrolled to keep the selected entry visible.
+const MAX_HEIGHT: usize = 10;
+
+/// A list of entries drawn over the buffer (e.g. completions), beneath
+/// the line it's anchored to or, if there isn't room there, above it.
+pub struct Overlay {
+    pub entries: Vec<String>,
+    pub selected_index: usize,
+}
+
+impl Overlay {
+    /// The area the overlay covers when anchored to a screen position, given
+    /// the space available (which excludes the status line), if it fits at all.
+    pub fn area(&self, anchor: Position, width: usize, height: usize) -> Option<Area> {
+        let entry_width = self.entries
+            .iter()
+            .map(|entry| entry.graphemes(true).count() + 2)
+            .max()?
+            .min(width);
+        let length = self.entries.len().min(MAX_HEIGHT);
+        let space_below = height.saturating_sub(anchor.line + 1);
+
+        let (top, visible) = if length <= space_below || space_below >= anchor.line {
+            (anchor.line + 1, length.min(space_below))
+        } else {
+            let visible = length.min(anchor.line);
+            (anchor.line - visible, visible)
+        };
+        if visible == 0 {
+            return None;
+        }
+
+        Some(Area {
+            top,
+            left: anchor.offset.min(width - entry_width),
+            width: entry_width,
+            height: visible,
+        })
+    }
+
+    /// The entries shown in an area of the specified height, scrolled just
+    /// enough to include the selected one, along with their indices.
+    pub fn visible_entries(&self, height: usize) -> impl Iterator<Item=(usize, &String)> {
+        let first = (self.selected_index + 1).saturating_sub(height);
+
+        self.entries.iter().enumerate().skip(first).take(height)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Overlay;
+    use crate::view::Area;
+    use scribe::buffer::Position;
+
+    fn overlay(length: usize, selected_index: usize) -> Overlay {
+        Overlay {
+            entries: (0..length).map(|index| format!("entry{}", index)).collect(),
+            selected_index,
+        }
+    }
+
+    #[test]
+    fn area_is_beneath_the_anchor_when_there_is_room() {
+        assert_eq!(
+            overlay(3, 0).area(Position { line: 2, offset: 4 }, 80, 20),
+            Some(Area { top: 3, left: 4, width: 8, height: 3 })
+        );
+    }
+
+    #[test]
+    fn area_moves_above_the_anchor_and_within_the_width_when_needed() {
+        assert_eq!(
+            overlay(3, 0).area(Position { line: 18, offset: 76 }, 80, 20),
+            Some(Area { top: 15, left: 72, width: 8, height: 3 })
+        );
+        assert_eq!(overlay(0, 0).area(Position { line: 0, offset: 0 }, 80, 20), None);
+    }
+
+    #[test]
+    fn visible_entries_include_the_selection() {
+        let overlay = overlay(20, 12);
+        let indices: Vec<usize> = overlay.visible_entries(10).map(|(index, _)| index).collect();
+
+        assert_eq!(indices, (3..13).collect::<Vec<_>>());
+    }
+}
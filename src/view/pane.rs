@@ -0,0 +1,432 @@
+use scribe::buffer::Position;
+use std::mem;
+
+/// A rectangular section of the screen.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Area {
+    pub top: usize,
+    pub left: usize,
+    pub width: usize,
+    pub height: usize,
+}
+
+impl Area {
+    pub fn contains(&self, position: &Position) -> bool {
+        position.line >= self.top && position.line < self.top + self.height &&
+            position.offset >= self.left && position.offset < self.left + self.width
+    }
+
+    /// Converts a position relative to the area into a screen position.
+    pub fn translate(&self, position: Position) -> Position {
+        Position{
+            line: self.top + position.line,
+            offset: self.left + position.offset,
+        }
+    }
+}
+
+/// The direction of the line dividing two panes. Horizontal splits
+/// stack panes on top of one another; vertical splits place them
+/// side-by-side.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SplitDirection {
+    Horizontal,
+    Vertical,
+}
+
+/// Where a pane was left in the buffer it displays, so that panes displaying
+/// the same buffer can each have their own cursor and scroll position.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Viewport {
+    pub cursor: Position,
+    pub line_offset: usize,
+    pub column_offset: usize,
+}
+
+/// Two panes whose scrolling is locked together, such that the second's
+/// line offset is kept at the specified distance from the first's.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScrollLock {
+    pub panes: (usize, usize),
+    pub offset: isize,
+}
+
+enum Layout {
+    Pane(usize),
+    Split(SplitDirection, Box<Layout>, Box<Layout>),
+}
+
+/// A tree of panes dividing the screen, each of which displays a buffer.
+/// Panes are identified by their index, in the order they appear on-screen.
+pub struct Panes {
+    layout: Layout,
+    buffers: Vec<Option<usize>>,
+    viewports: Vec<Option<Viewport>>,
+    scroll_lock: Option<ScrollLock>,
+    focused: usize,
+}
+
+impl Default for Panes {
+    fn default() -> Panes {
+        Panes {
+            layout: Layout::Pane(0),
+            buffers: vec![None],
+            viewports: vec![None],
+            scroll_lock: None,
+            focused: 0,
+        }
+    }
+}
+
+impl Panes {
+    pub fn count(&self) -> usize {
+        self.buffers.len()
+    }
+
+    pub fn focused(&self) -> usize {
+        self.focused
+    }
+
+    /// The ID of the buffer displayed in the specified pane.
+    pub fn buffer(&self, pane: usize) -> Option<usize> {
+        self.buffers.get(pane).cloned().unwrap_or(None)
+    }
+
+    pub fn set_buffer(&mut self, buffer_id: Option<usize>) {
+        if self.buffers[self.focused] != buffer_id {
+            self.viewports[self.focused] = None;
+        }
+        self.buffers[self.focused] = buffer_id;
+    }
+
+    /// Where the specified pane was last left in its buffer, if it's been recorded.
+    pub fn viewport(&self, pane: usize) -> Option<Viewport> {
+        self.viewports.get(pane).cloned().unwrap_or(None)
+    }
+
+    /// Records where the focused pane was left in its buffer.
+    pub fn set_viewport(&mut self, viewport: Viewport) {
+        self.viewports[self.focused] = Some(viewport);
+    }
+
+    pub fn scroll_lock(&self) -> Option<ScrollLock> {
+        self.scroll_lock
+    }
+
+    pub fn set_scroll_lock(&mut self, scroll_lock: Option<ScrollLock>) {
+        self.scroll_lock = scroll_lock;
+    }
+
+    /// Moves the pane's recorded scroll position to keep it locked to the
+    /// focused pane's (scrolled to the specified line offset), if it is.
+    pub fn follow_scroll_lock(&mut self, pane: usize, line_offset: usize) {
+        let lock = match self.scroll_lock {
+            Some(lock) => lock,
+            None => return,
+        };
+        let offset = if lock.panes == (self.focused, pane) {
+            lock.offset
+        } else if lock.panes == (pane, self.focused) {
+            -lock.offset
+        } else {
+            return;
+        };
+
+        if let Some(Some(ref mut viewport)) = self.viewports.get_mut(pane) {
+            viewport.line_offset = (line_offset as isize + offset).max(0) as usize;
+        }
+    }
+
+    /// Removes the buffer from any panes displaying it.
+    pub fn forget_buffer(&mut self, buffer_id: usize) {
+        for (buffer, viewport) in self.buffers.iter_mut().zip(self.viewports.iter_mut()) {
+            if *buffer == Some(buffer_id) {
+                *buffer = None;
+                *viewport = None;
+            }
+        }
+    }
+
+    /// Divides the focused pane in two, focusing the new pane,
+    /// which initially displays the same buffer, in the same place.
+    pub fn split(&mut self, direction: SplitDirection) {
+        let new_pane = self.focused + 1;
+
+        // Panes following the new one shift over to make room for it,
+        // so any locked panes are unlocked, rather than tracked.
+        self.layout.renumber(new_pane, 1);
+        self.scroll_lock = None;
+        self.layout.split(self.focused, direction, new_pane);
+        let buffer = self.buffers[self.focused];
+        let viewport = self.viewports[self.focused];
+        self.buffers.insert(new_pane, buffer);
+        self.viewports.insert(new_pane, viewport);
+        self.focused = new_pane;
+    }
+
+    /// Focuses the specified pane, returning its buffer ID.
+    pub fn focus(&mut self, pane: usize) -> Option<usize> {
+        self.focused = pane.min(self.count() - 1);
+
+        self.buffers[self.focused]
+    }
+
+    /// Focuses the next pane (wrapping around), returning its buffer ID.
+    pub fn focus_next(&mut self) -> Option<usize> {
+        self.focused = (self.focused + 1) % self.count();
+
+        self.buffers[self.focused]
+    }
+
+    /// Closes the focused pane, giving its space to its sibling.
+    /// Returns false if it's the only pane.
+    pub fn close(&mut self) -> bool {
+        if self.count() == 1 {
+            return false;
+        }
+
+        let closed_pane = self.focused;
+        self.layout.remove(closed_pane);
+        self.layout.renumber(closed_pane + 1, -1);
+        self.buffers.remove(closed_pane);
+        self.viewports.remove(closed_pane);
+        self.scroll_lock = None;
+        self.focused = closed_pane.min(self.count() - 1);
+
+        true
+    }
+
+    /// Divides the area amongst the panes, returning their areas in order.
+    pub fn areas(&self, area: Area) -> Vec<Area> {
+        let mut areas = vec![area; self.count()];
+        self.layout.areas(area, &mut areas, &mut Vec::new());
+
+        areas
+    }
+
+    /// The one-cell-wide areas separating adjacent panes.
+    pub fn separators(&self, area: Area) -> Vec<(SplitDirection, Area)> {
+        let mut separators = Vec::new();
+        self.layout.areas(area, &mut vec![area; self.count()], &mut separators);
+
+        separators
+    }
+}
+
+impl Layout {
+    // Replaces the specified pane with a split between it and a new pane.
+    fn split(&mut self, pane: usize, direction: SplitDirection, new_pane: usize) {
+        match *self {
+            Layout::Pane(index) if index == pane => {
+                *self = Layout::Split(
+                    direction,
+                    Box::new(Layout::Pane(pane)),
+                    Box::new(Layout::Pane(new_pane))
+                );
+            },
+            Layout::Pane(_) => (),
+            Layout::Split(_, ref mut first, ref mut second) => {
+                first.split(pane, direction, new_pane);
+                second.split(pane, direction, new_pane);
+            },
+        }
+    }
+
+    // Removes the specified pane, replacing its parent split with its sibling.
+    fn remove(&mut self, pane: usize) {
+        let sibling = match *self {
+            Layout::Pane(_) => None,
+            Layout::Split(_, ref mut first, ref mut second) => {
+                if first.is_pane(pane) {
+                    Some(mem::replace(&mut **second, Layout::Pane(0)))
+                } else if second.is_pane(pane) {
+                    Some(mem::replace(&mut **first, Layout::Pane(0)))
+                } else {
+                    first.remove(pane);
+                    second.remove(pane);
+                    None
+                }
+            }
+        };
+
+        if let Some(layout) = sibling {
+            *self = layout;
+        }
+    }
+
+    fn is_pane(&self, pane: usize) -> bool {
+        match *self {
+            Layout::Pane(index) => index == pane,
+            Layout::Split(..) => false,
+        }
+    }
+
+    // Shifts pane indices at or beyond the threshold by the specified amount.
+    fn renumber(&mut self, threshold: usize, amount: isize) {
+        match *self {
+            Layout::Pane(ref mut index) => {
+                if *index >= threshold {
+                    *index = (*index as isize + amount) as usize;
+                }
+            },
+            Layout::Split(_, ref mut first, ref mut second) => {
+                first.renumber(threshold, amount);
+                second.renumber(threshold, amount);
+            },
+        }
+    }
+
+    // Halves the area for each split, reserving a line or
+    // column between the two halves for a separator.
+    fn areas(&self, area: Area, areas: &mut [Area], separators: &mut Vec<(SplitDirection, Area)>) {
+        match *self {
+            Layout::Pane(index) => areas[index] = area,
+            Layout::Split(direction, ref first, ref second) => {
+                let (first_area, separator, second_area) = match direction {
+                    SplitDirection::Horizontal => {
+                        let height = area.height.saturating_sub(1) / 2;
+                        (
+                            Area{ height, ..area },
+                            Area{ top: area.top + height, height: 1, ..area },
+                            Area{
+                                top: area.top + height + 1,
+                                height: area.height.saturating_sub(height + 1),
+                                ..area
+                            },
+                        )
+                    },
+                    SplitDirection::Vertical => {
+                        let width = area.width.saturating_sub(1) / 2;
+                        (
+                            Area{ width, ..area },
+                            Area{ left: area.left + width, width: 1, ..area },
+                            Area{
+                                left: area.left + width + 1,
+                                width: area.width.saturating_sub(width + 1),
+                                ..area
+                            },
+                        )
+                    },
+                };
+
+                separators.push((direction, separator));
+                first.areas(first_area, areas, separators);
+                second.areas(second_area, areas, separators);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Area, Panes, ScrollLock, SplitDirection, Viewport};
+    use scribe::buffer::Position;
+
+    #[test]
+    fn split_divides_the_focused_pane_and_focuses_the_new_one() {
+        let mut panes = Panes::default();
+        panes.set_buffer(Some(1));
+        panes.split(SplitDirection::Vertical);
+
+        assert_eq!(panes.count(), 2);
+        assert_eq!(panes.focused(), 1);
+        assert_eq!(panes.buffer(1), Some(1));
+
+        let area = Area{ top: 0, left: 0, width: 21, height: 9 };
+        assert_eq!(
+            panes.areas(area),
+            vec![
+                Area{ top: 0, left: 0, width: 10, height: 9 },
+                Area{ top: 0, left: 11, width: 10, height: 9 },
+            ]
+        );
+        assert_eq!(
+            panes.separators(area),
+            vec![(SplitDirection::Vertical, Area{ top: 0, left: 10, width: 1, height: 9 })]
+        );
+    }
+
+    #[test]
+    fn nested_splits_number_panes_in_screen_order() {
+        let mut panes = Panes::default();
+        panes.set_buffer(Some(1));
+        panes.split(SplitDirection::Vertical);
+        panes.set_buffer(Some(2));
+        panes.focus_next();
+        panes.split(SplitDirection::Horizontal);
+        panes.set_buffer(Some(3));
+
+        assert_eq!(panes.focused(), 1);
+        assert_eq!(
+            (0..3).map(|pane| panes.buffer(pane)).collect::<Vec<_>>(),
+            vec![Some(1), Some(3), Some(2)]
+        );
+
+        let areas = panes.areas(Area{ top: 0, left: 0, width: 21, height: 9 });
+        assert_eq!(areas[0], Area{ top: 0, left: 0, width: 10, height: 4 });
+        assert_eq!(areas[1], Area{ top: 5, left: 0, width: 10, height: 4 });
+        assert_eq!(areas[2], Area{ top: 0, left: 11, width: 10, height: 9 });
+    }
+
+    #[test]
+    fn viewports_are_kept_per_pane_until_another_buffer_is_displayed() {
+        let viewport = Viewport{ cursor: Position{ line: 40, offset: 2 }, line_offset: 30, column_offset: 0 };
+        let mut panes = Panes::default();
+        panes.set_buffer(Some(1));
+        panes.set_viewport(viewport);
+        panes.split(SplitDirection::Horizontal);
+        assert_eq!(panes.viewport(1), Some(viewport));
+
+        panes.set_viewport(Viewport{ line_offset: 0, ..viewport });
+        assert_eq!(panes.viewport(0), Some(viewport));
+        panes.set_buffer(Some(2));
+        assert_eq!(panes.viewport(1), None);
+
+        panes.focus(0);
+        panes.forget_buffer(1);
+        assert_eq!(panes.viewport(0), None);
+    }
+
+    #[test]
+    fn locked_panes_follow_the_focused_pane_at_a_distance() {
+        let viewport = Viewport{ cursor: Position{ line: 0, offset: 0 }, line_offset: 0, column_offset: 0 };
+        let mut panes = Panes::default();
+        panes.set_viewport(viewport);
+        panes.split(SplitDirection::Vertical);
+        panes.split(SplitDirection::Vertical);
+        panes.set_scroll_lock(Some(ScrollLock{ panes: (1, 2), offset: 10 }));
+
+        // The second pane is focused, ten lines below the first.
+        panes.follow_scroll_lock(1, 25);
+        assert_eq!(panes.viewport(1).unwrap().line_offset, 15);
+        panes.follow_scroll_lock(0, 25);
+        assert_eq!(panes.viewport(0).unwrap().line_offset, 0);
+
+        panes.focus(1);
+        panes.follow_scroll_lock(2, 5);
+        assert_eq!(panes.viewport(2).unwrap().line_offset, 15);
+        panes.focus(2);
+        panes.follow_scroll_lock(1, 5);
+        assert_eq!(panes.viewport(1).unwrap().line_offset, 0);
+
+        panes.split(SplitDirection::Horizontal);
+        assert_eq!(panes.scroll_lock(), None);
+    }
+
+    #[test]
+    fn close_gives_the_focused_pane_space_to_its_sibling() {
+        let mut panes = Panes::default();
+        panes.set_buffer(Some(1));
+        panes.split(SplitDirection::Horizontal);
+        panes.set_buffer(Some(2));
+
+        assert!(panes.close());
+        assert_eq!(panes.count(), 1);
+        assert_eq!(panes.focused(), 0);
+        assert_eq!(panes.buffer(0), Some(1));
+
+        let area = Area{ top: 0, left: 0, width: 20, height: 9 };
+        assert_eq!(panes.areas(area), vec![area]);
+        assert!(!panes.close());
+    }
+}
@@ -1,15 +1,20 @@
 use crate::errors::*;
-use crate::view::buffer::{BufferRenderer, LexemeMapper};
-use crate::view::color::{ColorMap, Colors};
+use crate::models::application::Activity;
+use crate::view::buffer::{sanitize, BufferRenderer, LexemeMapper};
+use crate::view::color::{to_rgb_color, ColorMap, Colors, RGBColor};
 use crate::view::StatusLineData;
 use crate::view::style::Style;
 use crate::view::terminal::{Cell, TerminalBuffer};
-use crate::view::View;
-use pad::PadStr;
+use crate::view::{ListPreview, ListPreviewLayout, Overlay, SplitDirection, View};
+use pad::{Alignment, PadStr};
 use scribe::buffer::{Buffer, Position, Range};
 use scribe::util::LineIterator;
 use std::borrow::Cow;
 use syntect::highlighting::Theme;
+use unicode_segmentation::UnicodeSegmentation;
+
+// Used for list entries' matched characters when the theme doesn't have an accent.
+const MATCH_COLOR: RGBColor = RGBColor(240, 140, 20);
 
 pub struct Presenter<'p> {
     cursor_position: Option<Position>,
@@ -20,21 +25,45 @@ pub struct Presenter<'p> {
 
 impl<'p> Presenter<'p> {
     pub fn new(view: &mut View) -> Result<Presenter> {
-        let theme = {
-            let preferences = view.preferences.borrow();
-            let theme_name = preferences.theme();
-            let theme = view.theme_set.themes
-                .get(theme_name)
-                .ok_or_else(|| format!("Couldn't find \"{}\" theme", theme_name))?;
-            theme.clone()
-        };
+        let theme = view.theme()?;
+        let mut terminal_buffer = TerminalBuffer::new(
+            view.terminal.width(),
+            view.terminal.height(),
+        );
+
+        // Start with the contents of unfocused panes, and the lines between them.
+        for (position, cell) in &view.pane_cells {
+            terminal_buffer.set_cell(*position, cell.clone());
+        }
+        if let (Some(sidebar), Some(area)) = (view.sidebar.as_ref(), view.sidebar_area()) {
+            for (position, cell) in sidebar.cells() {
+                terminal_buffer.set_cell(*position, cell.clone());
+            }
+            for line in area.top..area.top + area.height {
+                terminal_buffer.set_cell(
+                    Position{ line, offset: area.left + area.width },
+                    Cell{ content: "│".into(), style: Style::Default, colors: Colors::Focused }
+                );
+            }
+        }
+        for (direction, area) in view.pane_separators() {
+            let content = match direction {
+                SplitDirection::Horizontal => "─",
+                SplitDirection::Vertical => "│",
+            };
+            for line in area.top..area.top + area.height {
+                for offset in area.left..area.left + area.width {
+                    terminal_buffer.set_cell(
+                        Position{ line, offset },
+                        Cell{ content: content.into(), style: Style::Default, colors: Colors::Focused }
+                    );
+                }
+            }
+        }
 
         Ok(Presenter{
             cursor_position: None,
-            terminal_buffer: TerminalBuffer::new(
-                view.terminal.width(),
-                view.terminal.height(),
-            ),
+            terminal_buffer,
             theme,
             view
         })
@@ -52,11 +81,23 @@ impl<'p> Presenter<'p> {
         self.terminal_buffer.clear()
     }
 
+    /// The cursor's screen position (set when printing a buffer).
+    pub fn cursor(&self) -> Option<Position> {
+        self.cursor_position
+    }
+
     pub fn set_cursor(&mut self, position: Option<Position>) {
         self.cursor_position = position;
     }
 
     pub fn present(&mut self) {
+        // Key sequence hints are drawn over whatever the mode presented.
+        if !self.view.key_hints().is_empty() {
+            let overlay = Overlay { entries: self.view.key_hints().to_vec(), selected_index: 0 };
+            let status_line = self.height() - 1;
+            self.print_overlay(&overlay, Position { line: status_line, offset: 0 });
+        }
+
         for (position, cell) in self.terminal_buffer.iter() {
             self.view.terminal.print(
                 &position,
@@ -70,25 +111,60 @@ impl<'p> Presenter<'p> {
     }
 
     pub fn print_buffer(&mut self, buffer: &Buffer, buffer_data: &'p str, highlights: Option<&[Range]>, lexeme_mapper: Option<&'p mut LexemeMapper>) -> Result<()> {
-        let scroll_offset = self.view.get_region(buffer)?.line_offset();
+        // A line highlighted after jumping to it is drawn alongside any others.
+        let mut combined_highlights;
+        let highlights = match self.view.line_highlight(buffer) {
+            Some(line) => {
+                combined_highlights = highlights.map(<[Range]>::to_vec).unwrap_or_default();
+                combined_highlights.push(line);
+                Some(&combined_highlights[..])
+            }
+            None => highlights,
+        };
+
+        // Lines outside of a narrowed buffer's region are hidden.
+        let narrowed_lines = self.view.narrowing(buffer).map(|narrowing| narrowing.lines(buffer_data));
+        let region = self.view.get_region(buffer)?;
+        let (mut scroll_offset, column_offset) = (region.line_offset(), region.column_offset());
+        if let Some(ref lines) = narrowed_lines {
+            scroll_offset = scroll_offset.max(lines.start());
+        }
         let lines = LineIterator::new(buffer_data);
+        let annotations = self.view.get_annotations(buffer);
+
+        let highlight_time = {
+            let preferences = self.view.preferences.borrow();
+            let mut renderer = BufferRenderer::new(
+                buffer,
+                highlights,
+                Some(&annotations),
+                scroll_offset,
+                &**self.view.terminal,
+                &self.theme,
+                &preferences,
+                self.view.get_render_cache(buffer)?,
+                &mut self.terminal_buffer
+            );
+            renderer.set_area(self.view.focused_pane_area());
+            if let Some(settings) = self.view.local_settings(buffer) {
+                renderer.set_local_settings(settings);
+            }
+            renderer.set_column_offset(column_offset);
+            if let Some(ref lines) = narrowed_lines {
+                renderer.set_last_line(lines.end());
+            }
+            self.cursor_position = renderer.render(lines, lexeme_mapper)?;
 
-        self.cursor_position = BufferRenderer::new(
-            buffer,
-            highlights,
-            scroll_offset,
-            &**self.view.terminal,
-            &self.theme,
-            &self.view.preferences.borrow(),
-            self.view.get_render_cache(buffer)?,
-            &mut self.terminal_buffer
-        ).render(lines, lexeme_mapper)?;
+            renderer.highlight_time()
+        };
+        self.view.profiler.record(Activity::Highlight, highlight_time);
 
         Ok(())
     }
 
     pub fn print_status_line(&mut self, entries: &[StatusLineData]) {
         let line = self.view.terminal.height() - 1;
+        let flashing = self.view.flash_remaining().is_some();
 
         entries.iter().enumerate().fold(0, |offset, (index, element)| {
             let content = match entries.len() {
@@ -117,9 +193,15 @@ impl<'p> Presenter<'p> {
             // Update the tracked offset.
             let updated_offset = offset + content.len();
 
+            // Flashing inverts every element, including those that already are.
+            let style = match (flashing, element.style) {
+                (true, Style::Inverted) => Style::Default,
+                (true, _) => Style::Inverted,
+                (false, style) => style,
+            };
             self.print(
                 &Position{ line, offset },
-                element.style,
+                style,
                 element.colors,
                 content
             );
@@ -128,12 +210,167 @@ impl<'p> Presenter<'p> {
         });
     }
 
+    /// Draws the overlay next to the cursor's line, starting at the
+    /// anchor's column (e.g. the start of the word being completed).
+    pub fn print_overlay(&mut self, overlay: &Overlay, anchor: Position) {
+        let width = self.width();
+        let height = self.height() - 1;
+        let area = match overlay.area(anchor, width, height) {
+            Some(area) => area,
+            None => return,
+        };
+
+        for (line, (index, entry)) in overlay.visible_entries(area.height).enumerate() {
+            let colors = if index == overlay.selected_index {
+                Colors::Inverted
+            } else {
+                Colors::Focused
+            };
+            self.print(
+                &Position{ line: area.top + line, offset: area.left },
+                Style::Default,
+                colors,
+                format!(" {}", entry).with_exact_width(area.width)
+            );
+        }
+    }
+
+    /// Draws a list of entries over the buffer, along with its filter
+    /// query, the actions available for the selected entry, and a preview
+    /// of the latter, placing the cursor on the query.
+    pub fn print_list_preview(&mut self, list: &ListPreview) {
+        let width = self.width();
+        let height = self.height() - 1;
+        let layout = ListPreviewLayout::new(width, height, list.list_height, list.preview.is_some());
+
+        for line in 0..layout.list.height {
+            let (content, style, colors) = match list.message {
+                Some(ref message) if line == 0 => (message.clone(), Style::Default, Colors::Default),
+                Some(_) => (String::new(), Style::Default, Colors::Default),
+                None => match list.entries.get(line) {
+                    Some(entry) if line == list.selected_index => {
+                        (format!("> {}", entry), Style::Bold, Colors::Focused)
+                    }
+                    Some(entry) => (format!("  {}", entry), Style::Default, Colors::Default),
+                    None => (String::new(), Style::Default, Colors::Default),
+                },
+            };
+            let matches = match list.message {
+                Some(_) => &[][..],
+                None => list.matches.get(line).map(Vec::as_slice).unwrap_or(&[]),
+            };
+            self.print_matches(
+                layout.list.translate(Position{ line, offset: 0 }),
+                style,
+                colors,
+                content.with_exact_width(layout.list.width),
+                // Entries are listed after a selection marker.
+                matches.iter().map(|index| index + 2)
+            );
+        }
+
+        // Actions are listed at the end of the query line, if there's room.
+        let (ref query, query_cursor) = list.query;
+        let actions = list.actions
+            .iter()
+            .map(|(key, action)| format!("{} {}", key, action))
+            .collect::<Vec<_>>()
+            .join("  ");
+        let query_width = query.graphemes(true).count();
+        let actions_width = actions.graphemes(true).count();
+        let content = if !actions.is_empty() && query_width + actions_width + 2 <= layout.query.width {
+            format!("{}{} ", query, actions.pad_to_width_with_alignment(
+                layout.query.width - query_width - 1,
+                Alignment::Right
+            ))
+        } else {
+            query.clone()
+        };
+        let colors = if list.insert { Colors::Insert } else { Colors::Inverted };
+        self.print(
+            &layout.query.translate(Position{ line: 0, offset: 0 }),
+            Style::Bold,
+            colors,
+            content.with_exact_width(layout.query.width)
+        );
+
+        if let (Some(preview), Some(area)) = (list.preview, layout.preview) {
+            // A preview beside the list replaces the buffer entirely.
+            let lines = preview.lines().map(Some).chain(std::iter::repeat(None));
+            for (line, content) in lines.take(area.height).enumerate() {
+                if content.is_none() && layout.divider.is_none() {
+                    break;
+                }
+                self.print(
+                    &area.translate(Position{ line, offset: 0 }),
+                    Style::Default,
+                    Colors::Default,
+                    content.unwrap_or("").replace('\t', "    ").with_exact_width(area.width)
+                );
+            }
+        }
+        if let Some(divider) = layout.divider {
+            for line in 0..height {
+                self.print(&Position{ line, offset: divider }, Style::Default, Colors::Focused, "│");
+            }
+        }
+
+        self.set_cursor(Some(layout.query.translate(Position{ line: 0, offset: query_cursor })));
+    }
+
+    // Prints the content, drawing the characters at the (ascending) indices
+    // in the theme's accent color, splitting it into runs of cells as needed.
+    fn print_matches<I>(&mut self, position: Position, style: Style, colors: Colors, content: String, indices: I)
+        where I: Iterator<Item=usize>
+    {
+        let mut indices = indices.peekable();
+        if indices.peek().is_none() {
+            self.print(&position, style, colors, content);
+
+            return;
+        }
+
+        let accent = self.theme.settings.accent.map(to_rgb_color).unwrap_or(MATCH_COLOR);
+        let match_colors = match colors {
+            Colors::Focused => Colors::CustomFocusedForeground(accent),
+            _ => Colors::CustomForeground(accent),
+        };
+        let mut offset = position.offset;
+        let mut run = String::new();
+        let mut run_matched = false;
+        for (index, character) in content.chars().enumerate() {
+            let matched = indices.peek() == Some(&index);
+            if matched {
+                indices.next();
+            }
+            if matched != run_matched && !run.is_empty() {
+                let width = sanitize(&run).graphemes(true).count();
+                let run_colors = if run_matched { match_colors } else { colors };
+                self.print(&Position{ line: position.line, offset }, style, run_colors, run.split_off(0));
+                offset += width;
+            }
+            run_matched = matched;
+            run.push(character);
+        }
+        let run_colors = if run_matched { match_colors } else { colors };
+        self.print(&Position{ line: position.line, offset }, style, run_colors, run);
+    }
+
+    /// Prints the content at the position, with anything that can't be printed
+    /// as-is (e.g. escape sequences in file names or command output) replaced.
     pub fn print<C>(&mut self, position: &Position, style: Style, colors: Colors, content: C)
         where C: Into<Cow<'p, str>>
     {
+        let content = content.into();
+        let sanitized = match sanitize(&content) {
+            Cow::Owned(sanitized) => Some(sanitized),
+            Cow::Borrowed(_) => None,
+        };
+        let content = sanitized.map(Cow::Owned).unwrap_or(content);
+
         self.terminal_buffer.set_cell(
             *position,
-            Cell{ content: content.into(), style, colors }
+            Cell{ content, style, colors }
         );
     }
 }
@@ -141,8 +378,9 @@ impl<'p> Presenter<'p> {
 #[cfg(test)]
 mod tests {
     use crate::models::application::Preferences;
-    use crate::view::View;
+    use crate::view::{Colors, Style, View};
     use scribe::{Buffer, Workspace};
+    use scribe::buffer::Position;
     use std::cell::RefCell;
     use std::path::{Path, PathBuf};
     use std::rc::Rc;
@@ -185,4 +423,57 @@ mod tests {
         cache = view.get_render_cache(workspace.current_buffer().unwrap()).unwrap();
         assert_ne!(cache.borrow().iter().count(), 0);
     }
+
+    #[test]
+    fn print_matches_draws_matched_characters_in_separate_cells() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut presenter = view.build_presenter().unwrap();
+
+        presenter.print_matches(
+            Position{ line: 0, offset: 0 },
+            Style::Default,
+            Colors::Default,
+            String::from("> main.rs"),
+            vec![2, 3, 4, 5].into_iter()
+        );
+
+        let runs: Vec<(usize, String, bool)> = presenter.terminal_buffer
+            .iter()
+            .take(3)
+            .map(|(position, cell)| (position.offset, cell.content.to_string(), cell.colors == Colors::Default))
+            .collect();
+        assert_eq!(runs, vec![
+            (0, String::from("> "), true),
+            (2, String::from("main"), false),
+            (6, String::from(".rs"), true),
+        ]);
+    }
+
+    #[test]
+    fn print_replaces_escape_sequences_with_placeholders() {
+        let preferences = Rc::new(RefCell::new(Preferences::new(None)));
+        let (tx, _) = mpsc::channel();
+        let mut view = View::new(preferences, tx).unwrap();
+        let mut presenter = view.build_presenter().unwrap();
+
+        presenter.print_matches(
+            Position{ line: 0, offset: 0 },
+            Style::Default,
+            Colors::Default,
+            String::from("\u{1b}]0;title\u{7}main.rs"),
+            vec![10, 11, 12, 13].into_iter()
+        );
+
+        let runs: Vec<(usize, String)> = presenter.terminal_buffer
+            .iter()
+            .take(2)
+            .map(|(position, cell)| (position.offset, cell.content.to_string()))
+            .collect();
+        assert_eq!(runs, vec![
+            (0, String::from("<0x1b>]0;title<0x07>")),
+            (20, String::from("main")),
+        ]);
+    }
 }
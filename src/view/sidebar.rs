@@ -0,0 +1,97 @@
+use crate::models::application::FileTree;
+use crate::view::{Area, Colors, Style};
+use crate::view::terminal::Cell;
+use pad::PadStr;
+use scribe::buffer::Position;
+use std::borrow::Cow;
+
+// The sidebar never takes up more than this fraction of the screen's width.
+const MAX_WIDTH_RATIO: usize = 2;
+
+/// The file tree, drawn down the left side of the screen beside the panes.
+/// Its contents are rendered ahead of time and drawn by presenters, much
+/// like unfocused panes.
+pub struct Sidebar {
+    width: usize,
+    cells: Vec<(Position, Cell<'static>)>,
+}
+
+impl Sidebar {
+    pub fn new(width: usize) -> Sidebar {
+        Sidebar { width, cells: Vec::new() }
+    }
+
+    /// The sidebar's area on a screen of the specified size,
+    /// excluding the status line and the line dividing it from the panes.
+    pub fn area(&self, screen_width: usize, screen_height: usize) -> Area {
+        Area {
+            top: 0,
+            left: 0,
+            width: self.width.min(screen_width / MAX_WIDTH_RATIO),
+            height: screen_height,
+        }
+    }
+
+    pub fn cells(&self) -> &[(Position, Cell<'static>)] {
+        &self.cells
+    }
+
+    /// Lists the tree's entries, scrolled just enough to include the
+    /// selected one, which is highlighted more prominently when focused.
+    pub fn render(&mut self, tree: &FileTree, area: Area, focused: bool) {
+        let first = (tree.selected_index() + 1).saturating_sub(area.height);
+        let entries = tree.entries().iter().map(Some).chain(std::iter::repeat(None));
+
+        self.cells = entries
+            .enumerate()
+            .skip(first)
+            .take(area.height)
+            .enumerate()
+            .map(|(line, (index, entry))| {
+                let (style, colors) = match entry {
+                    Some(_) if index == tree.selected_index() && focused => (Style::Bold, Colors::Focused),
+                    Some(_) if index == tree.selected_index() => (Style::Bold, Colors::Default),
+                    _ => (Style::Default, Colors::Default),
+                };
+                let content = entry.map(|entry| format!(" {}", entry)).unwrap_or_default();
+
+                (area.translate(Position { line, offset: 0 }), Cell {
+                    content: Cow::Owned(content.with_exact_width(area.width)),
+                    colors,
+                    style,
+                })
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Sidebar;
+    use crate::models::application::FileTree;
+    use crate::view::Area;
+    use std::env;
+    use std::fs;
+
+    #[test]
+    fn render_scrolls_to_the_selected_entry() {
+        let root = env::temp_dir().join("amp_sidebar_render");
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(&root).unwrap();
+        for name in &["a", "b", "c", "d"] {
+            fs::write(root.join(name), "").unwrap();
+        }
+        let mut tree = FileTree::new(root.clone(), None).unwrap();
+        tree.select_next();
+        tree.select_next();
+
+        let mut sidebar = Sidebar::new(6);
+        let area = sidebar.area(80, 2);
+        assert_eq!(area, Area { top: 0, left: 0, width: 6, height: 2 });
+        sidebar.render(&tree, area, true);
+        let lines: Vec<&str> = sidebar.cells().iter().map(|(_, cell)| &*cell.content).collect();
+        assert_eq!(lines, vec!["   b  ", "   c  "]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -29,6 +29,18 @@ pub trait Terminal {
     fn set_cursor(&self, _: Option<Position>);
     fn print<'a>(&self, _: &Position, _: Style, _: Colors, _: &str);
     fn suspend(&self);
+    fn bell(&self);
+
+    /// Has the terminal report mouse input, rather than handling it itself.
+    fn set_mouse_reporting(&self, enabled: bool);
+
+    /// Copies the content to the terminal's own clipboard (using OSC 52).
+    fn set_clipboard(&self, content: &str);
+
+    /// Asks the terminal for its clipboard's content, now and whenever it
+    /// regains focus, which is sent back as an `Event::Clipboard` (if the
+    /// terminal supports reading it).
+    fn request_clipboard(&self);
 }
 
 #[cfg(not(any(test, feature = "bench")))]
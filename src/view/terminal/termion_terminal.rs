@@ -10,32 +10,69 @@ use std::os::unix::io::AsRawFd;
 use scribe::buffer::{Distance, Position};
 use self::termion::color::{Bg, Fg};
 use self::termion::{color, cursor};
-use self::termion::input::{Keys, TermRead};
+use self::termion::input::{Events, TermRead};
 use self::termion::raw::{IntoRawMode, RawTerminal};
 use self::termion::style;
 use std::io::{BufWriter, Stdin, stdin, stdout, Write};
 use std::fmt::Display;
 use std::ops::Drop;
 use std::sync::Mutex;
-use std::time::Duration;
-use crate::view::{Colors, Style};
+use std::time::{Duration, Instant};
+use crate::util::transform;
+use crate::view::{ColorDepth, Colors, Style};
 use unicode_segmentation::UnicodeSegmentation;
 use signal_hook::iterator::Signals;
 
+use self::termion::event::Event as TermionEvent;
 use self::termion::event::Key as TermionKey;
-use crate::input::Key;
+use self::termion::event::{MouseButton, MouseEvent};
+use crate::input::{Key, Mouse};
 use crate::models::application::Event;
 
 const STDIN_INPUT: Token = Token(0);
 const RESIZE: Token = Token(1);
 
+// Mouse reporting, including movement while a button is held,
+// using the extended (SGR) coordinates that support wide screens.
+const ENABLE_MOUSE_REPORTING: &str = "\x1b[?1000h\x1b[?1002h\x1b[?1015h\x1b[?1006h";
+const DISABLE_MOUSE_REPORTING: &str = "\x1b[?1006l\x1b[?1015l\x1b[?1002l\x1b[?1000l";
+
+// Bracketed paste, which surrounds pasted text with markers, so that
+// it can be told apart from typing (termion doesn't recognize them).
+const ENABLE_BRACKETED_PASTE: &str = "\x1b[?2004h";
+const DISABLE_BRACKETED_PASTE: &str = "\x1b[?2004l";
+const PASTE_START: &[u8] = b"\x1b[200~";
+const PASTE_END: &[u8] = b"\x1b[201~";
+
+// The terminal's clipboard is read whenever it regains focus (as reported
+// once focus reporting is enabled), to pick up content copied elsewhere.
+const CLIPBOARD_REQUEST: &str = "\x1b]52;c;?\x07";
+const CLIPBOARD_RESPONSE_PREFIX: &str = "52;";
+
+// How long a response to a clipboard request is waited for. Terminals
+// that don't support reading the clipboard never respond, and alt-]
+// typed after that shouldn't be mistaken for the start of a response.
+const CLIPBOARD_RESPONSE_TIMEOUT: Duration = Duration::from_millis(500);
+const ENABLE_FOCUS_REPORTING: &str = "\x1b[?1004h";
+const DISABLE_FOCUS_REPORTING: &str = "\x1b[?1004l";
+const FOCUS_IN: &[u8] = b"\x1b[I";
+
 pub struct TermionTerminal {
     event_listener: Poll,
     signals: Signals,
-    input: Mutex<Option<Keys<Stdin>>>,
+    input: Mutex<Option<Events<Stdin>>>,
     output: Mutex<Option<BufWriter<RawTerminal<Stdout>>>>,
+    mouse_reporting: Mutex<bool>,
+
+    /// Whether the clipboard is read when the terminal regains focus.
+    clipboard_requests: Mutex<bool>,
+
+    /// When the clipboard was last requested, if the terminal's response is expected.
+    awaiting_clipboard: Mutex<Option<Instant>>,
+
     current_style: Mutex<Option<Style>>,
     current_colors: Mutex<Option<Colors>>,
+    color_depth: ColorDepth,
     current_position: Mutex<Option<Position>>,
 }
 
@@ -47,10 +84,14 @@ impl TermionTerminal {
         Ok(TermionTerminal {
             event_listener,
             signals,
-            input: Mutex::new(Some(stdin().keys())),
+            input: Mutex::new(Some(stdin().events())),
             output: Mutex::new(Some(create_output_instance())),
+            mouse_reporting: Mutex::new(false),
+            clipboard_requests: Mutex::new(false),
+            awaiting_clipboard: Mutex::new(None),
             current_style: Mutex::new(None),
             current_colors: Mutex::new(None),
+            color_depth: ColorDepth::detect(),
             current_position: Mutex::new(None),
         })
     }
@@ -73,13 +114,8 @@ impl TermionTerminal {
 
                             // Resetting styles unfortunately clears active colors, too.
                             if let Ok(color_guard) = self.current_colors.lock() {
-                                if let Some(ref current_colors) = *color_guard {
-                                    match *current_colors {
-                                        Colors::Default => { let _ = write!(output, "{}{}", Fg(color::Reset), Bg(color::Reset)); }
-                                        Colors::Custom(fg, bg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(bg)); }
-                                        Colors::CustomForeground(fg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(color::Reset)); }
-                                        _ => (),
-                                    };
+                                if let Some(current_colors) = *color_guard {
+                                    write_colors(output, current_colors, self.color_depth);
                                 }
                             }
                         }
@@ -98,12 +134,7 @@ impl TermionTerminal {
                 // Check if colors have changed.
                 if let Ok(mut color_guard) = self.current_colors.lock() {
                     if Some(&colors) != color_guard.as_ref() {
-                        match colors {
-                            Colors::Default => { let _ = write!(output, "{}{}", Fg(color::Reset), Bg(color::Reset)); }
-                            Colors::Custom(fg, bg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(bg)); }
-                            Colors::CustomForeground(fg) => { let _ = write!(output, "{}{}", Fg(fg), Bg(color::Reset)); }
-                            _ => (),
-                        };
+                        write_colors(output, colors, self.color_depth);
                     }
 
                     color_guard.replace(colors);
@@ -112,12 +143,39 @@ impl TermionTerminal {
         }
     }
 
+    // Writes a request for the clipboard's content,
+    // noting that the terminal's response is expected.
+    fn send_clipboard_request(&self) {
+        if let Ok(mut guard) = self.output.lock() {
+            if let Some(ref mut output) = *guard {
+                let _ = write!(output, "{}", CLIPBOARD_REQUEST);
+                let _ = output.flush();
+            }
+        }
+        if let Ok(mut awaiting) = self.awaiting_clipboard.lock() {
+            *awaiting = Some(Instant::now());
+        }
+    }
+
+    // Whether a response to a clipboard request is expected, which
+    // it no longer is once it's been taken, or has timed out.
+    fn take_awaiting_clipboard(&self) -> bool {
+        self.awaiting_clipboard
+            .lock()
+            .ok()
+            .and_then(|mut awaiting| awaiting.take())
+            .map_or(false, |requested_at| requested_at.elapsed() < CLIPBOARD_RESPONSE_TIMEOUT)
+    }
+
     fn restore_cursor(&self) {
         if let Ok(mut guard) = self.output.lock() {
             if let Some(ref mut output) = *guard {
                 let _ = write!(
                     output,
-                    "{}{}{}",
+                    "{}{}{}{}{}{}",
+                    DISABLE_MOUSE_REPORTING,
+                    DISABLE_BRACKETED_PASTE,
+                    DISABLE_FOCUS_REPORTING,
                     termion::cursor::Show,
                     style::Reset,
                     termion::clear::All,
@@ -139,7 +197,26 @@ impl Terminal for TermionTerminal {
                     let mut guard = self.input.lock().ok()?;
                     let input_handle = guard.as_mut()?;
                     let input_data = input_handle.next()?;
-                    let key = input_data.ok()?;
+                    let key = match input_data.ok()? {
+                        // Termion reads the start of the (OSC) response to a
+                        // clipboard request as alt-], followed by its content.
+                        TermionEvent::Key(TermionKey::Alt(']')) if self.take_awaiting_clipboard() => {
+                            return read_clipboard_response(input_handle).map(Event::Clipboard);
+                        }
+                        TermionEvent::Key(key) => key,
+                        TermionEvent::Mouse(event) => return map_mouse_event(event).map(Event::Mouse),
+                        TermionEvent::Unsupported(ref sequence) if sequence.as_slice() == PASTE_START => {
+                            return Some(Event::Paste(read_pasted_text(input_handle)));
+                        }
+                        TermionEvent::Unsupported(ref sequence) if sequence.as_slice() == FOCUS_IN => {
+                            drop(guard);
+                            if self.clipboard_requests.lock().map(|enabled| *enabled).unwrap_or(false) {
+                                self.send_clipboard_request();
+                            }
+                            return None;
+                        }
+                        TermionEvent::Unsupported(_) => return None,
+                    };
 
                     match key {
                         TermionKey::Backspace => Some(Event::Key(Key::Backspace)),
@@ -252,8 +329,15 @@ impl Terminal for TermionTerminal {
                 }
 
                 // Now that style, color, and position have been
-                // addressed, print the content.
-                let _ = write!(output, "{}", content);
+                // addressed, print the content. It's been sanitized by
+                // then, but any control characters left are dropped, as
+                // a last line of defence against escape sequences.
+                if content.chars().any(char::is_control) {
+                    let content: String = content.chars().filter(|c| !c.is_control()).collect();
+                    let _ = write!(output, "{}", content);
+                } else {
+                    let _ = write!(output, "{}", content);
+                }
             }
         }
     }
@@ -284,9 +368,57 @@ impl Terminal for TermionTerminal {
             guard.replace(create_output_instance());
         }
         if let Ok(mut guard) = self.input.lock() {
-            guard.replace(stdin().keys());
+            guard.replace(stdin().events());
+        }
+
+        // Reporting was disabled when the terminal was restored.
+        let enabled = self.mouse_reporting.lock().map(|enabled| *enabled).unwrap_or(false);
+        self.set_mouse_reporting(enabled);
+        if self.clipboard_requests.lock().map(|enabled| *enabled).unwrap_or(false) {
+            self.request_clipboard();
+        }
+    }
+
+    fn bell(&self) {
+        // Written along with the next frame, when the output is flushed.
+        if let Ok(mut output) = self.output.lock() {
+            if let Some(ref mut output) = *output {
+                let _ = write!(output, "\x07");
+            }
+        }
+    }
+
+    fn set_mouse_reporting(&self, enabled: bool) {
+        if let Ok(mut guard) = self.output.lock() {
+            if let Some(ref mut output) = *guard {
+                let sequence = if enabled { ENABLE_MOUSE_REPORTING } else { DISABLE_MOUSE_REPORTING };
+                let _ = write!(output, "{}", sequence);
+            }
+        }
+        if let Ok(mut guard) = self.mouse_reporting.lock() {
+            *guard = enabled;
         }
     }
+
+    fn set_clipboard(&self, content: &str) {
+        if let Ok(mut guard) = self.output.lock() {
+            if let Some(ref mut output) = *guard {
+                let _ = write!(output, "\x1b]52;c;{}\x07", transform::base64_encode(content.as_bytes()));
+            }
+        }
+    }
+
+    fn request_clipboard(&self) {
+        if let Ok(mut guard) = self.output.lock() {
+            if let Some(ref mut output) = *guard {
+                let _ = write!(output, "{}", ENABLE_FOCUS_REPORTING);
+            }
+        }
+        if let Ok(mut guard) = self.clipboard_requests.lock() {
+            *guard = true;
+        }
+        self.send_clipboard_request();
+    }
 }
 
 impl Drop for TermionTerminal {
@@ -303,6 +435,80 @@ fn cursor_position(position: &Position) -> cursor::Goto {
     )
 }
 
+// Converts termion's one-based coordinates into screen positions.
+// Presses of buttons other than the left one (and the wheel) are ignored.
+fn map_mouse_event(event: MouseEvent) -> Option<Mouse> {
+    let position = |x: u16, y: u16| Position {
+        line: y.saturating_sub(1) as usize,
+        offset: x.saturating_sub(1) as usize,
+    };
+
+    match event {
+        MouseEvent::Press(MouseButton::Left, x, y) => Some(Mouse::Press(position(x, y))),
+        MouseEvent::Press(MouseButton::WheelUp, x, y) => Some(Mouse::ScrollUp(position(x, y))),
+        MouseEvent::Press(MouseButton::WheelDown, x, y) => Some(Mouse::ScrollDown(position(x, y))),
+        MouseEvent::Press(..) => None,
+        MouseEvent::Hold(x, y) => Some(Mouse::Drag(position(x, y))),
+        MouseEvent::Release(x, y) => Some(Mouse::Release(position(x, y))),
+    }
+}
+
+// Reads the text pasted after a bracketed paste's start marker, up
+// to its end marker. The terminal sends it as though it were typed,
+// so it's mapped back from the keys termion parses it into.
+fn read_pasted_text(input: &mut Events<Stdin>) -> String {
+    let mut text = String::new();
+    while let Some(Ok(event)) = input.next() {
+        match event {
+            TermionEvent::Unsupported(ref sequence) if sequence.as_slice() == PASTE_END => break,
+            TermionEvent::Key(TermionKey::Char(c)) => text.push(c),
+            TermionEvent::Key(TermionKey::Ctrl(c @ 'a'..='z')) => text.push((c as u8 - b'a' + 1) as char),
+            TermionEvent::Key(TermionKey::Backspace) => text.push('\x7f'),
+            _ => (),
+        }
+    }
+
+    text
+}
+
+// Reads the rest of the terminal's response to a clipboard request
+// (e.g. "52;c;YW1w", terminated by a bell or ESC \\), decoding its content.
+// Input that doesn't start like a response isn't read any further, so that
+// it isn't consumed while waiting for a terminator that never arrives.
+fn read_clipboard_response(input: &mut Events<Stdin>) -> Option<String> {
+    for expected in CLIPBOARD_RESPONSE_PREFIX.chars() {
+        match input.next() {
+            Some(Ok(TermionEvent::Key(TermionKey::Char(c)))) if c == expected => (),
+            _ => return None,
+        }
+    }
+
+    let mut response = String::new();
+    while let Some(Ok(event)) = input.next() {
+        match event {
+            TermionEvent::Key(TermionKey::Ctrl('g')) | TermionEvent::Key(TermionKey::Alt('\\')) => break,
+            TermionEvent::Key(TermionKey::Char(c)) => response.push(c),
+            _ => return None,
+        }
+    }
+
+    // Skip the selection (e.g. "c") the content was read from.
+    let content = response.splitn(2, ';').nth(1)?;
+
+    String::from_utf8(transform::base64_decode(content).ok()?).ok()
+}
+
+// Writes the colors' escape sequences, mapping
+// them to the nearest the terminal can display.
+fn write_colors<W: Write>(output: &mut W, colors: Colors, depth: ColorDepth) {
+    let _ = match colors {
+        Colors::Default => write!(output, "{}{}", Fg(color::Reset), Bg(color::Reset)),
+        Colors::Custom(fg, bg) => write!(output, "{}{}", depth.foreground(fg), depth.background(bg)),
+        Colors::CustomForeground(fg) => write!(output, "{}{}", depth.foreground(fg), Bg(color::Reset)),
+        _ => Ok(()),
+    };
+}
+
 fn terminal_size() -> (usize, usize) {
     termion::terminal_size()
         .map(|(x,y)| (x as usize, y as usize))
@@ -331,7 +537,10 @@ fn create_event_listener() -> Result<(Poll, Signals)> {
 
 fn create_output_instance() -> BufWriter<RawTerminal<Stdout>> {
     // Use a 1MB buffered writer for stdout.
-    BufWriter::with_capacity(1_048_576, stdout().into_raw_mode().unwrap())
+    let mut output = BufWriter::with_capacity(1_048_576, stdout().into_raw_mode().unwrap());
+    let _ = write!(output, "{}", ENABLE_BRACKETED_PASTE);
+
+    output
 }
 
 fn map_style(style: Style) -> Option<Box<Display>> {
@@ -340,5 +549,6 @@ fn map_style(style: Style) -> Option<Box<Display>> {
         Style::Bold => Some(Box::new(style::Bold)),
         Style::Inverted => Some(Box::new(style::Invert)),
         Style::Italic => Some(Box::new(style::Italic)),
+        Style::Underlined => Some(Box::new(style::Underline)),
     }
 }
@@ -87,6 +87,10 @@ impl Terminal for TestTerminal {
         *cursor = position;
     }
     fn suspend(&self) { }
+    fn bell(&self) { }
+    fn set_mouse_reporting(&self, _: bool) { }
+    fn set_clipboard(&self, _: &str) { }
+    fn request_clipboard(&self) { }
     fn print(&self, position: &Position, _: Style, colors: Colors, content: &str) {
         // Ignore lines beyond visible height.
         if position.line >= self.height() { return; }